@@ -0,0 +1,705 @@
+//! Administrative endpoints
+//!
+//! Operators list, add and lift IP bans, inspect rate limiter statistics and
+//! replace the live [`SecurityPolicy`] under `/api/v1/admin/security` without
+//! restarting the server, and switch the store to read-only mode for
+//! migrations and backups under `/api/v1/admin/read-only`. Temporary
+//! (break-glass) role elevations are granted and listed under
+//! `/api/v1/admin/elevations`. The read cache is inspected, flushed and
+//! warmed under `/api/v1/admin/cache`, and the retention settings of the
+//! store are read, replaced and applied under `/api/v1/admin/retention`. Every endpoint requires a permission on
+//! [`Resource::System`], and every change is recorded in the audit log.
+
+use crate::middleware::SecurityState;
+use crate::routes::{authorize, caller_event, ApiError, ApiState};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use llm_config_audit::{AuditEventType, AuditSeverity};
+use llm_config_cache::{CacheError, CacheManager, CacheTier, WarmupReport};
+use llm_config_core::{Environment, NamespaceRetention, ReadOnlyMode, RetentionConfig, RetentionReport};
+use llm_config_rbac::{Action, Elevation, Resource, Role};
+use llm_config_security::{
+    rate_limit::BanInfo, RateLimitStats, SecurityContext, SecurityPolicy, TrustedProxies,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A banned client IP
+#[derive(Debug, Serialize)]
+pub struct BanResponse {
+    pub ip: IpAddr,
+    pub reason: String,
+    /// Rate limit violations that led to the ban (0 for manual bans)
+    pub violations: usize,
+    pub banned_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl BanResponse {
+    fn new(ip: IpAddr, info: &BanInfo, ban_duration_seconds: u64) -> Self {
+        let elapsed = chrono::Duration::from_std(info.banned_at.elapsed()).unwrap_or_default();
+        let banned_at = Utc::now() - elapsed;
+        Self {
+            ip,
+            reason: info.reason.clone(),
+            violations: info.violations,
+            banned_at,
+            expires_at: banned_at + chrono::Duration::seconds(ban_duration_seconds as i64),
+        }
+    }
+}
+
+/// Request body for banning a client IP
+#[derive(Debug, Deserialize)]
+pub struct BanRequest {
+    pub ip: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+fn security(state: &ApiState) -> Result<&SecurityState, ApiError> {
+    state
+        .security
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Security administration is not enabled".to_string()))
+}
+
+fn parse_ip(ip: &str) -> Result<IpAddr, ApiError> {
+    ip.parse()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid IP address: {}", ip)))
+}
+
+/// Current, unexpired bans, ordered by IP
+fn current_bans(security: &SecurityState) -> Vec<BanResponse> {
+    let duration = security.rate_limiter.config().ban_duration_seconds;
+    let now = Utc::now();
+    let mut bans: Vec<BanResponse> = security
+        .rate_limiter
+        .get_banned_ips()
+        .iter()
+        .map(|(ip, info)| BanResponse::new(*ip, info, duration))
+        .filter(|ban| ban.expires_at > now)
+        .collect();
+    bans.sort_by_key(|ban| ban.ip);
+    bans
+}
+
+/// Record a security administration change, attributed to the caller
+fn audit_security_change(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    operation: &str,
+    message: String,
+) {
+    audit_admin_change(state, context, "security", operation, message);
+}
+
+/// Record an administrative change of a component, attributed to the caller
+fn audit_admin_change(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    component: &str,
+    operation: &str,
+    message: String,
+) {
+    let Some(logger) = &state.audit_logger else {
+        return;
+    };
+
+    let event_type = AuditEventType::SystemEvent {
+        component: component.to_string(),
+        message,
+    };
+    let event = caller_event(event_type, context)
+        .with_severity(AuditSeverity::Warning)
+        .with_metadata("operation", operation);
+    if let Err(e) = logger.log(event) {
+        tracing::error!("Failed to record {} change: {}", component, e);
+    }
+}
+
+/// GET /api/v1/admin/security/bans - List banned client IPs
+pub async fn list_bans(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Vec<BanResponse>>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    Ok(Json(current_bans(security(&state)?)))
+}
+
+/// POST /api/v1/admin/security/bans - Ban a client IP
+pub async fn ban_ip(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<BanRequest>,
+) -> Result<(StatusCode, Json<BanResponse>), ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let security = security(&state)?;
+    let ip = parse_ip(&req.ip)?;
+    let reason = req.reason.unwrap_or_else(|| "Banned by administrator".to_string());
+
+    security.rate_limiter.ban(ip, reason.clone());
+    audit_security_change(
+        &state,
+        &context,
+        "ban_ip",
+        format!("Banned IP {}: {}", ip, reason),
+    );
+
+    let ban = current_bans(security)
+        .into_iter()
+        .find(|ban| ban.ip == ip)
+        .ok_or_else(|| ApiError::InternalError(format!("Failed to ban IP {}", ip)))?;
+    Ok((StatusCode::CREATED, Json(ban)))
+}
+
+/// DELETE /api/v1/admin/security/bans/:ip - Lift the ban of a client IP
+pub async fn unban_ip(
+    State(state): State<ApiState>,
+    Path(ip): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<StatusCode, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let security = security(&state)?;
+    let ip = parse_ip(&ip)?;
+
+    if !current_bans(security).iter().any(|ban| ban.ip == ip) {
+        return Err(ApiError::NotFound(format!("IP is not banned: {}", ip)));
+    }
+
+    security.rate_limiter.unban(ip);
+    audit_security_change(&state, &context, "unban_ip", format!("Lifted ban of IP {}", ip));
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/admin/security/rate-limits - Rate limiter statistics
+pub async fn get_rate_limit_stats(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<RateLimitStats>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    Ok(Json(security(&state)?.rate_limiter.get_stats()))
+}
+
+/// GET /api/v1/admin/security/policy - The live security policy
+pub async fn get_security_policy(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<SecurityPolicy>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    let policy = security(&state)?.policy_enforcer.read().unwrap().get_policy().clone();
+    Ok(Json(policy))
+}
+
+/// PUT /api/v1/admin/security/policy - Replace the live security policy
+///
+/// Applies to the next request; blocked IPs, endpoints and origins need no
+/// restart.
+pub async fn put_security_policy(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+    Json(policy): Json<SecurityPolicy>,
+) -> Result<Json<SecurityPolicy>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let security = security(&state)?;
+    TrustedProxies::parse(&policy.trusted_proxies)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let message = format!(
+        "Updated security policy: {} blocked IPs, {} blocked endpoints, {} allowed origins",
+        policy.blocked_ips.len(),
+        policy.blocked_endpoints.len(),
+        policy.allowed_origins.len()
+    );
+    security.policy_enforcer.write().unwrap().update_policy(policy.clone());
+    audit_security_change(&state, &context, "update_policy", message);
+
+    Ok(Json(policy))
+}
+
+/// Read-only mode of the store
+#[derive(Debug, Serialize)]
+pub struct ReadOnlyResponse {
+    pub read_only: bool,
+    /// Who enabled read-only mode, when and why
+    #[serde(flatten)]
+    pub mode: Option<ReadOnlyMode>,
+}
+
+impl From<Option<ReadOnlyMode>> for ReadOnlyResponse {
+    fn from(mode: Option<ReadOnlyMode>) -> Self {
+        Self {
+            read_only: mode.is_some(),
+            mode,
+        }
+    }
+}
+
+/// Request body for enabling read-only mode
+#[derive(Debug, Default, Deserialize)]
+pub struct ReadOnlyRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// GET /api/v1/admin/read-only - Whether the store is read-only
+pub async fn get_read_only(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<ReadOnlyResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    Ok(Json(state.configs.read_only_mode().await?.into()))
+}
+
+/// PUT /api/v1/admin/read-only - Reject writes until read-only mode is disabled
+///
+/// Writes are answered with `503 Service Unavailable` (`READ_ONLY`).
+pub async fn enable_read_only(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<ReadOnlyRequest>,
+) -> Result<Json<ReadOnlyResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let user = match &context {
+        Some(Extension(context)) => context.user_id.clone(),
+        None => "api-user".to_string(),
+    };
+
+    let mode = state.configs.enable_read_only(&user, req.reason).await?;
+    let message = match &mode.reason {
+        Some(reason) => format!("Enabled read-only mode: {}", reason),
+        None => "Enabled read-only mode".to_string(),
+    };
+    audit_admin_change(&state, &context, "storage", "enable_read_only", message);
+
+    Ok(Json(Some(mode).into()))
+}
+
+/// DELETE /api/v1/admin/read-only - Accept writes again
+pub async fn disable_read_only(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<ReadOnlyResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    if state.configs.disable_read_only().await? {
+        audit_admin_change(
+            &state,
+            &context,
+            "storage",
+            "disable_read_only",
+            "Disabled read-only mode".to_string(),
+        );
+    }
+    Ok(Json(None.into()))
+}
+
+/// Retention settings of the store
+#[derive(Debug, Serialize)]
+pub struct RetentionResponse {
+    pub config: RetentionConfig,
+    /// Effective retention of the namespace asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective: Option<EffectiveRetention>,
+}
+
+/// Retention of one namespace, after rules and defaults are combined
+#[derive(Debug, Serialize)]
+pub struct EffectiveRetention {
+    pub namespace: String,
+    #[serde(flatten)]
+    pub retention: NamespaceRetention,
+}
+
+/// Query parameters for reading the retention settings
+#[derive(Debug, Default, Deserialize)]
+pub struct RetentionQuery {
+    /// Also return the effective retention of this namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// GET /api/v1/admin/retention - Retention settings, and the effective
+/// retention of `namespace`
+pub async fn get_retention(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+    Query(params): Query<RetentionQuery>,
+) -> Result<Json<RetentionResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    let config = state.configs.retention().await?;
+    let effective = params.namespace.map(|namespace| EffectiveRetention {
+        retention: config.for_namespace(&namespace),
+        namespace,
+    });
+    Ok(Json(RetentionResponse { config, effective }))
+}
+
+/// PUT /api/v1/admin/retention - Replace the retention settings
+///
+/// They are enforced by the next retention run (or `POST
+/// /api/v1/admin/retention/apply`).
+pub async fn put_retention(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+    Json(config): Json<RetentionConfig>,
+) -> Result<Json<RetentionResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    if let Some(rule) = config.namespaces.iter().find(|rule| rule.pattern.trim().is_empty()) {
+        return Err(ApiError::BadRequest(format!("Empty namespace pattern in rule {:?}", rule)));
+    }
+
+    state.configs.set_retention(config.clone()).await?;
+    let message = format!(
+        "Replaced retention settings ({} namespace rules)",
+        config.namespaces.len()
+    );
+    audit_admin_change(&state, &context, "storage", "set_retention", message);
+
+    Ok(Json(RetentionResponse {
+        config,
+        effective: None,
+    }))
+}
+
+/// POST /api/v1/admin/retention/apply - Remove the history the retention
+/// settings do not keep now
+pub async fn apply_retention(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<RetentionReport>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let report = state.configs.apply_retention().await?;
+    let message = format!(
+        "Applied retention: pruned {} versions and {} deleted config histories",
+        report.pruned_versions, report.purged_histories
+    );
+    audit_admin_change(&state, &context, "storage", "apply_retention", message);
+    Ok(Json(report))
+}
+
+/// Request body for a temporary role elevation
+#[derive(Debug, Deserialize)]
+pub struct ElevationRequest {
+    /// User to elevate (defaults to the caller)
+    #[serde(default)]
+    pub user: Option<String>,
+    pub role: String,
+    /// How long the role is granted, in seconds
+    pub ttl_seconds: u64,
+    /// Why the role is needed (e.g. an incident reference)
+    pub justification: String,
+}
+
+/// GET /api/v1/admin/elevations - List active role elevations
+pub async fn list_elevations(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Vec<Elevation>>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    let rbac = state.rbac.read().unwrap();
+    Ok(Json(rbac.active_elevations().into_iter().cloned().collect()))
+}
+
+/// POST /api/v1/admin/elevations - Grant a role for a limited time
+///
+/// The grant, and every request the elevation is needed for, is recorded
+/// as a critical audit event.
+pub async fn elevate_role(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<ElevationRequest>,
+) -> Result<(StatusCode, Json<Elevation>), ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let user = match (req.user, &context) {
+        (Some(user), _) => user,
+        (None, Some(Extension(context))) => context.user_id.clone(),
+        (None, None) => return Err(ApiError::BadRequest("No user to elevate".to_string())),
+    };
+    let role: Role = req.role.parse().unwrap_or_else(|e| match e {});
+
+    let elevation = state.rbac.write().unwrap().assign_role_temporary(
+        user,
+        role,
+        Duration::from_secs(req.ttl_seconds),
+        req.justification,
+    )?;
+
+    if let Some(logger) = &state.audit_logger {
+        let assignment = &elevation.assignment;
+        let event_type = AuditEventType::SystemEvent {
+            component: "rbac".to_string(),
+            message: format!(
+                "Elevated {} to {} until {}: {}",
+                assignment.user,
+                assignment.role,
+                elevation.expires_at.to_rfc3339(),
+                elevation.justification
+            ),
+        };
+        let event = caller_event(event_type, &context)
+            .with_severity(AuditSeverity::Critical)
+            .with_metadata("operation", "elevate_role")
+            .with_metadata("elevated_user", &assignment.user)
+            .with_metadata("role", assignment.role.to_string())
+            .with_metadata("justification", &elevation.justification)
+            .with_metadata("expires_at", elevation.expires_at.to_rfc3339());
+        if let Err(e) = logger.log(event) {
+            tracing::error!("Failed to record role elevation: {}", e);
+        }
+    }
+
+    Ok((StatusCode::CREATED, Json(elevation)))
+}
+
+/// Request body for warming the read cache
+#[derive(Debug, Default, Deserialize)]
+pub struct CacheWarmRequest {
+    /// Namespaces to load (defaults to the namespaces preloaded at startup)
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// Environment to load (defaults to every environment)
+    #[serde(default)]
+    pub env: Option<String>,
+}
+
+fn cache(state: &ApiState) -> Result<&Arc<CacheManager>, ApiError> {
+    state
+        .cache
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("The server has no read cache".to_string()))
+}
+
+/// POST /api/v1/admin/cache/warm - Load namespaces into the read cache
+pub async fn warm_cache(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<CacheWarmRequest>,
+) -> Result<Json<WarmupReport>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let cache = Arc::clone(cache(&state)?);
+    let env: Option<Environment> = req.env.as_deref().map(str::parse).transpose().map_err(ApiError::BadRequest)?;
+    let namespaces = if req.namespaces.is_empty() {
+        cache.preload_namespaces().to_vec()
+    } else {
+        req.namespaces
+    };
+    if namespaces.is_empty() {
+        return Err(ApiError::BadRequest("No namespaces to warm".to_string()));
+    }
+
+    let manager = Arc::clone(&state.manager);
+    let message = format!("Warmed the read cache with {}", namespaces.join(", "));
+    let report = tokio::task::spawn_blocking(move || cache.warm(&manager, &namespaces, env))
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?
+        .map_err(|e| match e {
+            CacheError::Config(e) => e.into(),
+            e => ApiError::InternalError(e.to_string()),
+        })?;
+    audit_admin_change(&state, &context, "cache", "warm_cache", message);
+
+    Ok(Json(report))
+}
+
+/// Statistics of one cache tier
+#[derive(Debug, Serialize)]
+pub struct CacheTierStats {
+    pub entries: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<usize>,
+    /// Approximate size of the cached entries in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<usize>,
+    /// Hits and misses since the tier was last cleared
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub evictions: u64,
+}
+
+/// L1 statistics of one namespace
+#[derive(Debug, Serialize)]
+pub struct CacheNamespaceStats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub evictions: u64,
+    pub invalidations: u64,
+}
+
+/// Statistics of the read cache
+#[derive(Debug, Serialize)]
+pub struct CacheStatsResponse {
+    pub l1: CacheTierStats,
+    pub l2: CacheTierStats,
+    /// L1 statistics by namespace
+    pub namespaces: BTreeMap<String, CacheNamespaceStats>,
+    /// Namespaces loaded at startup and after the cache was cleared
+    pub preloaded: Vec<String>,
+}
+
+impl From<&CacheManager> for CacheStatsResponse {
+    fn from(cache: &CacheManager) -> Self {
+        let l1 = cache.l1_stats();
+        let l2 = cache.l2_stats();
+        Self {
+            l1: CacheTierStats {
+                entries: l1.size,
+                max_entries: Some(l1.max_size),
+                bytes: Some(l1.bytes),
+                max_bytes: l1.max_bytes,
+                hits: l1.hit_count,
+                misses: l1.miss_count,
+                hit_rate: l1.hit_rate,
+                evictions: l1.evictions,
+            },
+            l2: CacheTierStats {
+                entries: l2.size,
+                max_entries: None,
+                bytes: l2.bytes,
+                max_bytes: None,
+                hits: l2.hit_count,
+                misses: l2.miss_count,
+                hit_rate: l2.hit_rate,
+                evictions: 0,
+            },
+            namespaces: cache
+                .namespace_stats()
+                .into_iter()
+                .map(|(namespace, stats)| {
+                    let stats = CacheNamespaceStats {
+                        entries: stats.size,
+                        bytes: stats.bytes,
+                        evictions: stats.evictions,
+                        invalidations: stats.invalidations,
+                    };
+                    (namespace, stats)
+                })
+                .collect(),
+            preloaded: cache.preload_namespaces().to_vec(),
+        }
+    }
+}
+
+/// Query parameters for flushing the read cache
+#[derive(Debug, Default, Deserialize)]
+pub struct CacheFlushQuery {
+    /// Tier to flush (both tiers by default)
+    #[serde(default)]
+    pub tier: Option<CacheTier>,
+    /// Only flush this namespace and its child namespaces
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// Query parameters for inspecting a cached entry
+#[derive(Debug, Default, Deserialize)]
+pub struct CacheEntryQuery {
+    /// Environment (default `development`)
+    #[serde(default)]
+    pub env: Option<String>,
+}
+
+/// Where a config is cached, and whether the cached version is current
+#[derive(Debug, Serialize)]
+pub struct CacheEntryResponse {
+    pub namespace: String,
+    pub key: String,
+    pub environment: String,
+    /// Tier reads of the config are answered from (`null` if not cached)
+    pub tier: Option<CacheTier>,
+    pub cached_version: Option<u64>,
+    /// Version in the store (`null` if the config does not exist)
+    pub stored_version: Option<u64>,
+    /// Whether the cached entry differs from the stored one
+    pub stale: bool,
+}
+
+/// GET /api/v1/admin/cache - Read cache statistics per tier and namespace
+pub async fn get_cache_stats(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<CacheStatsResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    Ok(Json(cache(&state)?.as_ref().into()))
+}
+
+/// DELETE /api/v1/admin/cache - Flush a tier and/or a namespace from the read cache
+///
+/// Answers with the statistics after the flush.
+pub async fn flush_cache(
+    State(state): State<ApiState>,
+    Query(params): Query<CacheFlushQuery>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<CacheStatsResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+    let cache = Arc::clone(cache(&state)?);
+    let tier = params.tier;
+    let namespace = params.namespace.clone();
+    let flushed = Arc::clone(&cache);
+    tokio::task::spawn_blocking(move || flushed.flush(tier, namespace.as_deref()))
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+    let tiers = tier.map_or_else(|| "both tiers".to_string(), |tier| tier.to_string());
+    let message = match &params.namespace {
+        Some(namespace) => format!("Flushed {} from {} of the read cache", namespace, tiers),
+        None => format!("Flushed {} of the read cache", tiers),
+    };
+    audit_admin_change(&state, &context, "cache", "flush_cache", message);
+
+    Ok(Json(cache.as_ref().into()))
+}
+
+/// GET /api/v1/admin/cache/entries/:namespace/:key - Where a config is cached
+///
+/// The lookup does not count as a cache hit or change what is cached.
+pub async fn inspect_cache_entry(
+    State(state): State<ApiState>,
+    Path((namespace, key)): Path<(String, String)>,
+    Query(params): Query<CacheEntryQuery>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<CacheEntryResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    let env: Environment = params
+        .env
+        .as_deref()
+        .unwrap_or("development")
+        .parse()
+        .map_err(ApiError::BadRequest)?;
+    let cache = Arc::clone(cache(&state)?);
+
+    let (ns, k) = (namespace.clone(), key.clone());
+    let cached = tokio::task::spawn_blocking(move || cache.peek(&ns, &k, &env.to_string()))
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    let stored = state.configs.get_untracked(&namespace, &key, env).await?;
+
+    let stale = match (&cached, &stored) {
+        (Some((_, cached)), Some(stored)) => {
+            cached.version != stored.version || cached.metadata.updated_at != stored.metadata.updated_at
+        }
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    Ok(Json(CacheEntryResponse {
+        namespace,
+        key,
+        environment: env.to_string(),
+        tier: cached.as_ref().map(|(tier, _)| *tier),
+        cached_version: cached.map(|(_, entry)| entry.version),
+        stored_version: stored.map(|entry| entry.version),
+        stale,
+    }))
+}
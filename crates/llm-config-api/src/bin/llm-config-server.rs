@@ -1,11 +1,21 @@
 //! LLM Config Manager API Server
 
+use chrono::{DateTime, Utc};
 use clap::Parser;
-use llm_config_api::{serve, ServerConfig};
-use llm_config_core::ConfigManager;
+use llm_config_api::{
+    serve, serve_tenants, PushConfig, ServerConfig, TlsConfig, WebhookEndpoint, DEFAULT_CACHE_SIZE,
+};
+use llm_config_audit::{OverflowPolicy, DEFAULT_QUEUE_CAPACITY};
+use llm_config_cache::CacheTtl;
+use llm_config_core::tenant::TENANT_KEYS_DIR;
+use llm_config_core::{ConfigManager, Environment, TenantKeyStore, TenantRegistry};
 use llm_config_crypto::{Algorithm, SecretKey};
+use llm_config_security::{PrincipalQuotas, SecretAccessLimit, WorkloadIdentityMapper};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 #[derive(Parser)]
 #[command(name = "llm-config-server")]
@@ -20,6 +30,20 @@ struct Cli {
     #[arg(short = 'k', long, env = "LLM_CONFIG_KEY")]
     encryption_key: Option<String>,
 
+    /// Cipher for newly encrypted secrets (aes-256-gcm or the nonce-misuse
+    /// resistant aes-256-gcm-siv); existing secrets keep theirs
+    #[arg(long, env = "LLM_CONFIG_CIPHER", default_value = "aes-256-gcm")]
+    cipher: Algorithm,
+
+    /// Reject secrets that are not bound to their entry (`llm-config bind-secrets`)
+    #[arg(long, env = "LLM_CONFIG_REQUIRE_BOUND_SECRETS")]
+    require_bound_secrets: bool,
+
+    /// Resolve overrides through base -> development -> staging -> production
+    /// until an environment graph is saved (compatibility with older stores)
+    #[arg(long, env = "LLM_CONFIG_LEGACY_OVERRIDES")]
+    legacy_overrides: bool,
+
     /// Server host
     #[arg(long, default_value = "127.0.0.1")]
     host: String,
@@ -35,40 +59,307 @@ struct Cli {
     /// Disable security middleware
     #[arg(long)]
     no_security: bool,
+
+    /// Audit log directory
+    #[arg(long)]
+    audit_dir: Option<PathBuf>,
+
+    /// Audit events queued for storage before --audit-overflow applies
+    #[arg(long, default_value_t = DEFAULT_QUEUE_CAPACITY)]
+    audit_queue_capacity: usize,
+
+    /// What to do with audit events when the queue is full: block, drop
+    /// (counted in metrics) or spill (to a journal recovered on restart)
+    #[arg(long, default_value = "block")]
+    audit_overflow: OverflowPolicy,
+
+    /// SPIFFE workload identity mappings (JSON file)
+    #[arg(long)]
+    spiffe_mappings: Option<PathBuf>,
+
+    /// Enforce RBAC role assignments (loaded from storage at startup)
+    #[arg(long)]
+    enforce_rbac: bool,
+
+    /// Serve Prometheus metrics and health checks on a separate port
+    /// (metrics are served on the API port by default)
+    #[arg(long)]
+    metrics_port: Option<u16>,
+
+    /// Also push metrics to this Prometheus Pushgateway (e.g.
+    /// http://pushgateway:9091), for deployments that cannot be scraped
+    #[arg(long)]
+    metrics_push_url: Option<String>,
+
+    /// Job label of pushed metrics
+    #[arg(long, default_value = "llm-config-server")]
+    metrics_push_job: String,
+
+    /// Grouping label of pushed metrics, as NAME=VALUE (repeatable), e.g.
+    /// instance=api-1 so replicas do not replace each other's metrics
+    #[arg(long = "metrics-push-label")]
+    metrics_push_labels: Vec<String>,
+
+    /// Seconds between metric pushes
+    #[arg(long, default_value_t = 15, value_parser = clap::value_parser!(u64).range(1..))]
+    metrics_push_interval: u64,
+
+    /// Check storage consistency at startup
+    #[arg(long)]
+    check_consistency: bool,
+
+    /// Webhook endpoints notified about config changes (JSON file)
+    #[arg(long)]
+    webhooks: Option<PathBuf>,
+
+    /// Serve Swagger UI for the OpenAPI document at /api/v1/docs
+    #[arg(long)]
+    swagger_ui: bool,
+
+    /// Reverse proxy address or CIDR range trusted to report the client IP
+    /// in Forwarded / X-Forwarded-For, the client certificate in
+    /// x-forwarded-client-cert and the user in x-user-id / x-user-groups
+    /// (repeatable)
+    #[arg(long = "trusted-proxy", value_delimiter = ',')]
+    trusted_proxies: Vec<String>,
+
+    /// Rate limit quotas of certificate principals, by principal and role (JSON file)
+    #[arg(long)]
+    rate_limits: Option<PathBuf>,
+
+    /// Serve HTTPS with this PEM certificate chain (reloaded when it changes)
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key of the TLS certificate
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Require client certificates issued by this PEM CA bundle (mTLS);
+    /// their SPIFFE ID or common name becomes the caller's principal
+    #[arg(long, requires = "tls_cert")]
+    tls_client_ca: Option<PathBuf>,
+
+    /// Seconds in-flight requests get to finish after SIGTERM before the
+    /// server exits
+    #[arg(long, default_value_t = 30)]
+    shutdown_timeout: u64,
+
+    /// When the encryption key was created (RFC 3339), reported as its age
+    /// by the crypto health check
+    #[arg(long, env = "LLM_CONFIG_KEY_CREATED_AT")]
+    key_created_at: Option<DateTime<Utc>>,
+
+    /// Key age in days after which the crypto health check reports that the
+    /// key is due for rotation
+    #[arg(long, default_value_t = 90)]
+    key_max_age_days: i64,
+
+    /// Scan stored values for plaintext credentials every this many seconds,
+    /// recording findings as security events in the audit log
+    #[arg(long)]
+    scan_values_interval: Option<u64>,
+
+    /// Apply the store's retention settings (retention.toml) every this
+    /// many seconds, pruning version history and the audit log
+    #[arg(long)]
+    retention_interval: Option<u64>,
+
+    /// Reveal at most this many secrets per minute to each caller (verified
+    /// principal, or client IP); reveals over the limit are refused and
+    /// logged as Critical security events
+    #[arg(long)]
+    secret_reads_per_minute: Option<u32>,
+
+    /// Secrets a caller may reveal in a burst before the per-minute limit
+    /// applies (defaults to the per-minute limit)
+    #[arg(long, requires = "secret_reads_per_minute")]
+    secret_read_burst: Option<u32>,
+
+    /// Allow callers with the reveal permission to decrypt secrets with
+    /// `reveal` and in rendered templates (refused with 403 otherwise)
+    #[arg(long)]
+    allow_secret_reveal: bool,
+
+    /// Require a reason, recorded in the audit log, for every request that
+    /// reveals secrets
+    #[arg(long)]
+    require_secret_reason: bool,
+
+    /// Serve config reads from a cache whose L2 tier is kept in this
+    /// directory (emptied at startup)
+    #[arg(long, conflicts_with = "multi_tenant")]
+    cache_dir: Option<PathBuf>,
+
+    /// Entries held in memory by the cache
+    #[arg(long, default_value_t = DEFAULT_CACHE_SIZE)]
+    cache_size: usize,
+
+    /// Bytes the configs held in memory by the cache may take up, measured
+    /// by their serialized size
+    #[arg(long, requires = "cache_dir")]
+    cache_max_bytes: Option<usize>,
+
+    /// Load the configs of this namespace into the cache before serving, and
+    /// again whenever the cache is cleared (repeatable or comma-separated)
+    #[arg(long = "preload", value_delimiter = ',', requires = "cache_dir")]
+    preload: Vec<String>,
+
+    /// Only preload configs of this environment (every environment by default)
+    #[arg(long, requires = "cache_dir")]
+    preload_env: Option<Environment>,
+
+    /// Refresh cached configs from storage after this many seconds, for
+    /// writes the server does not see (e.g. made with the CLI)
+    #[arg(long, requires = "cache_dir")]
+    cache_ttl: Option<u64>,
+
+    /// Keep serving a cached config for up to this many seconds past its TTL
+    /// while it is refreshed in the background
+    #[arg(long, requires = "cache_ttl")]
+    cache_stale_while_revalidate: Option<u64>,
+
+    /// Serve one isolated store per tenant, chosen by the caller's workload
+    /// identity or the `X-Tenant-Id` header of a `--trusted-proxy` (the
+    /// encryption key becomes the master key wrapping per-tenant keys)
+    #[arg(long)]
+    multi_tenant: bool,
+
+    /// Share rate limiter buckets and bans between replicas through this
+    /// Redis URL (redis://[[user]:password@]host[:port][/db])
+    #[cfg(feature = "redis")]
+    #[arg(long, env = "LLM_CONFIG_RATE_LIMIT_REDIS")]
+    rate_limit_redis: Option<String>,
+
+    /// Share writes with other replicas through the KV store of this Consul
+    /// agent (http://host[:port])
+    #[cfg(feature = "consul")]
+    #[arg(long, env = "CONSUL_HTTP_ADDR", conflicts_with = "multi_tenant")]
+    consul: Option<String>,
+
+    /// ACL token for Consul
+    #[cfg(feature = "consul")]
+    #[arg(long, env = "CONSUL_HTTP_TOKEN", requires = "consul")]
+    consul_token: Option<String>,
+
+    /// Prefix of the Consul keys configs are stored under
+    #[cfg(feature = "consul")]
+    #[arg(long, default_value = llm_config_core::DEFAULT_KV_PREFIX)]
+    consul_prefix: String,
+
+    /// Export request and config operation spans to this OTLP gRPC endpoint
+    #[cfg(feature = "otel")]
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
 }
 
 #[tokio::main]
 async fn main() {
+    let cli = Cli::parse();
+
+    #[cfg(feature = "otel")]
+    let tracer_provider = match cli.otlp_endpoint.as_deref() {
+        Some(endpoint) => {
+            match llm_config_api::otel::init_tracer_provider(endpoint, "llm-config-server") {
+                Ok(provider) => Some(provider),
+                Err(e) => {
+                    eprintln!("Error: failed to set up OTLP export: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+
     // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    let subscriber = tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::from_default_env()
                 .add_directive(tracing::Level::INFO.into()),
         )
-        .init();
+        .with(tracing_subscriber::fmt::layer());
+    #[cfg(feature = "otel")]
+    let subscriber = subscriber.with(tracer_provider.as_ref().map(llm_config_api::otel::layer));
+    subscriber.init();
 
-    let cli = Cli::parse();
+    let result = run(cli).await;
 
-    if let Err(e) = run(cli).await {
+    // Flush pending spans
+    #[cfg(feature = "otel")]
+    if let Some(provider) = tracer_provider {
+        let _ = provider.shutdown();
+    }
+
+    if let Err(e) = result {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
 }
 
 async fn run(cli: Cli) -> anyhow::Result<()> {
-    // Create manager
-    let mut manager = ConfigManager::new(&cli.storage)?;
+    let key = match cli.encryption_key {
+        Some(ref key_str) => Some(SecretKey::from_base64(cli.cipher, key_str)?),
+        None => {
+            tracing::warn!("No encryption key provided - secret operations will fail");
+            None
+        }
+    };
 
-    // Set encryption key if provided
-    if let Some(key_str) = cli.encryption_key {
-        let key = SecretKey::from_base64(Algorithm::Aes256Gcm, &key_str)?;
-        manager = manager.with_encryption_key(key);
-        tracing::info!("Encryption key configured");
-    } else {
-        tracing::warn!("No encryption key provided - secret operations will fail");
-    }
+    // Load workload identity mappings if provided
+    let workload_identity = match cli.spiffe_mappings {
+        Some(ref path) => Some(WorkloadIdentityMapper::from_json(&std::fs::read_to_string(
+            path,
+        )?)?),
+        None => None,
+    };
+
+    // Load webhook endpoints if provided
+    let webhooks: Vec<WebhookEndpoint> = match cli.webhooks {
+        Some(ref path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => Vec::new(),
+    };
+
+    // Load per-principal rate limits if provided
+    let principal_quotas = match cli.rate_limits {
+        Some(ref path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => PrincipalQuotas::default(),
+    };
+
+    // Connect the shared rate limiter store if configured
+    #[cfg(feature = "redis")]
+    let rate_limit_store = match cli.rate_limit_redis {
+        Some(ref url) => {
+            let config = llm_config_security::RedisRateLimitConfig::new(url);
+            let store: Arc<dyn llm_config_security::RateLimitStore> =
+                Arc::new(llm_config_security::RedisRateLimitStore::new(config)?);
+            Some(store)
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "redis"))]
+    let rate_limit_store = None;
 
-    let manager = Arc::new(manager);
+    // Terminate TLS natively if a certificate is given
+    let tls = cli.tls_cert.zip(cli.tls_key).map(|(cert, key)| {
+        let tls = TlsConfig::new(cert, key);
+        match cli.tls_client_ca {
+            Some(ca) => tls.with_client_ca(ca),
+            None => tls,
+        }
+    });
+
+    // Push metrics to a Pushgateway if configured
+    let metrics_push = match cli.metrics_push_url {
+        Some(ref url) => {
+            let mut push = PushConfig::new(url, &cli.metrics_push_job)
+                .with_interval(Duration::from_secs(cli.metrics_push_interval));
+            for label in &cli.metrics_push_labels {
+                push = push.with_label_spec(label)?;
+            }
+            Some(push)
+        }
+        None => None,
+    };
 
     // Create server configuration
     let config = ServerConfig {
@@ -76,6 +367,43 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
         port: cli.port,
         enable_cors: !cli.no_cors,
         enable_security: !cli.no_security,
+        audit_log_dir: cli.audit_dir,
+        audit_queue_capacity: cli.audit_queue_capacity,
+        audit_overflow: cli.audit_overflow,
+        workload_identity,
+        enforce_rbac: cli.enforce_rbac,
+        metrics_port: cli.metrics_port,
+        metrics_push,
+        check_consistency: cli.check_consistency,
+        webhooks,
+        swagger_ui: cli.swagger_ui,
+        trusted_proxies: cli.trusted_proxies,
+        principal_quotas,
+        rate_limit_store,
+        tls,
+        shutdown_timeout: Duration::from_secs(cli.shutdown_timeout),
+        key_created_at: cli.key_created_at,
+        key_max_age_days: cli.key_max_age_days,
+        value_scan_interval: cli.scan_values_interval.map(Duration::from_secs),
+        retention_interval: cli.retention_interval.map(Duration::from_secs),
+        secret_access_limit: cli.secret_reads_per_minute.map(|reads_per_minute| SecretAccessLimit {
+            reads_per_minute,
+            burst: cli.secret_read_burst.unwrap_or(reads_per_minute),
+        }),
+        allow_secret_reveal: cli.allow_secret_reveal,
+        require_secret_reason: cli.require_secret_reason,
+        cache_dir: cli.cache_dir,
+        cache_size: cli.cache_size,
+        cache_max_bytes: cli.cache_max_bytes,
+        preload_namespaces: cli.preload,
+        preload_env: cli.preload_env,
+        cache_ttl: cli.cache_ttl.map(|ttl| {
+            let ttl = CacheTtl::new(Duration::from_secs(ttl));
+            match cli.cache_stale_while_revalidate {
+                Some(window) => ttl.with_stale_while_revalidate(Duration::from_secs(window)),
+                None => ttl,
+            }
+        }),
     };
 
     tracing::info!(
@@ -86,9 +414,43 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
     tracing::info!("Storage directory: {}", cli.storage.display());
     tracing::info!("CORS enabled: {}", config.enable_cors);
     tracing::info!("Security middleware enabled: {}", config.enable_security);
+    tracing::info!("RBAC enforcement enabled: {}", config.enforce_rbac);
+
+    if cli.multi_tenant {
+        let mut tenants = TenantRegistry::new(&cli.storage)
+            .with_legacy_overrides(cli.legacy_overrides)
+            .with_required_secret_binding(cli.require_bound_secrets);
+        if let Some(master) = key {
+            tenants = tenants
+                .with_key_store(TenantKeyStore::new(cli.storage.join(TENANT_KEYS_DIR), master)?);
+            tracing::info!("Per-tenant encryption keys configured");
+        }
+        tracing::info!("Multi-tenant mode enabled");
+        serve_tenants(tenants, config).await?;
+        return Ok(());
+    }
+
+    // Create manager
+    let mut manager = ConfigManager::new(&cli.storage)?
+        .with_legacy_overrides(cli.legacy_overrides)
+        .with_required_secret_binding(cli.require_bound_secrets);
+    if let Some(key) = key {
+        manager = manager.with_encryption_key(key);
+        tracing::info!("Encryption key configured");
+    }
+    #[cfg(feature = "consul")]
+    if let Some(url) = cli.consul {
+        let mut consul = llm_config_core::ConsulConfig::new(&url);
+        if let Some(token) = cli.consul_token {
+            consul = consul.with_token(token);
+        }
+        let store = Arc::new(llm_config_core::ConsulKv::new(consul)?);
+        manager = manager.with_kv_store(store, cli.consul_prefix);
+        tracing::info!("Sharing writes through Consul at {}", url);
+    }
 
     // Start the server
-    serve(manager, config).await?;
+    serve(Arc::new(manager), config).await?;
 
     Ok(())
 }
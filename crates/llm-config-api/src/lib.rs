@@ -6,8 +6,18 @@
 //! - RESTful API for configuration management
 //! - JSON request/response format
 //! - CORS support
-//! - Graceful shutdown
+//! - Graceful shutdown draining in-flight requests and the audit log queue
+//! - Native TLS with rustls, mTLS client identities and hot certificate reload
 //! - Health check endpoint
+//! - Prometheus metrics, on the API port or a separate listener
+//! - Health checks, including encryption key probes and key age monitoring
+//! - OpenTelemetry tracing with W3C `traceparent` propagation (`otel` feature)
+//! - SPIFFE workload identity for service-to-service callers
+//! - Multi-tenant serving with isolated per-tenant stores and keys
+//! - Signed webhook notifications for config changes
+//! - OpenAPI 3.0 document and optional Swagger UI
+//! - Admin API for IP bans, rate limit statistics and the live security policy
+//! - Scheduled scans of stored values for plaintext credentials, audited as security events
 //! - Comprehensive error handling
 //!
 //! ## Example
@@ -24,10 +34,49 @@
 //! }
 //! ```
 
+pub mod admin;
+pub mod metrics;
 pub mod middleware;
+pub mod notifications;
+pub mod openapi;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod retention;
 pub mod routes;
 pub mod server;
+pub mod shutdown;
+pub mod tenant;
+pub mod tls;
+pub mod value_scan;
 
+pub use admin::{
+    BanRequest, BanResponse, CacheEntryResponse, CacheNamespaceStats, CacheStatsResponse, CacheTierStats,
+    CacheWarmRequest, EffectiveRetention, ReadOnlyRequest, ReadOnlyResponse, RetentionQuery, RetentionResponse,
+};
+pub use llm_config_metrics::PushConfig;
+pub use metrics::{
+    metrics_route, metrics_router, track_http_metrics, CryptoHealthCheck, MetricsState,
+};
 pub use middleware::{SecurityResponse, SecurityState};
-pub use routes::{ApiError, ApiState, ConfigResponse, ErrorResponse};
-pub use server::{create_router, serve, ServerConfig};
+pub use notifications::{
+    ChangeNotification, PayloadFormat, WebhookEndpoint, WebhookNotifier, EVENT_HEADER,
+    SIGNATURE_HEADER,
+};
+pub use openapi::{openapi_route, swagger_ui_route, ApiDoc, OPENAPI_PATH, SWAGGER_UI_PATH};
+pub use retention::{spawn_retention, AuditTarget};
+pub use routes::{
+    AccessStatsResponse, ActivityEntryResponse, ActivitySource, ApiError, ApiState, BatchGetRequest, BatchGetResponse, BatchGetResult, BatchGetStatus,
+    ConfigResponse, DryRunResponse, EnvironmentGraphResponse, ErrorResponse, HealthResponse, HistoryEntryResponse,
+    NamespaceCopyRequest, NamespaceCopyResponse, PromoteRequest, ResolvedConfigResponse, RoleDefinitionRequest,
+    RollbackRequest, RollbackResponse, TypeWarningResponse, ALREADY_EXISTS_ERROR_CODE, MAX_BATCH_KEYS, NEXT_PAGE_TOKEN_HEADER, PRECONDITION_FAILED_ERROR_CODE, PROTECTED_ERROR_CODE, READ_ONLY_ERROR_CODE,
+    SECRET_ACCESS_LIMITED_ERROR_CODE, TOTAL_COUNT_HEADER, TYPE_CHANGE_ERROR_CODE, VERSION_CONFLICT_ERROR_CODE,
+};
+pub use server::{
+    create_router, create_router_with_state, create_tenant_router, serve, serve_tenants, ServerConfig, DEFAULT_CACHE_SIZE,
+};
+pub use shutdown::{
+    track_in_flight, FlushOnShutdown, ShutdownCoordinator, DEFAULT_SHUTDOWN_TIMEOUT,
+};
+pub use tenant::{resolve_tenant, TenantRouters, TENANT_HEADER};
+pub use tls::{serve_tls, ClientCertificate, ReloadingTlsConfig, TlsConfig, TlsSession};
+pub use value_scan::{spawn_value_scan, ScanTargets};
@@ -0,0 +1,305 @@
+//! Prometheus metrics and health endpoints
+//!
+//! Served on a separate listener so scrapers and probes never go through the
+//! API security middleware, or with [`metrics_route`] on the API listener
+//! next to `/health`. Scrapers that accept OpenMetrics get that format, with
+//! exemplars. [`track_http_metrics`] counts and times API requests by route,
+//! keeping the request (and, with the `otel` feature, trace) ID of each as
+//! an exemplar. [`CryptoHealthCheck`] reports whether the encryption key works
+//! and is due for rotation.
+
+use axum::{
+    extract::{FromRef, MatchedPath, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use crate::middleware::REQUEST_ID_HEADER;
+use chrono::{DateTime, Utc};
+use llm_config_core::ConfigManager;
+use llm_config_metrics::{
+    HealthCheck, HealthCheckResult, HealthChecker, MetricsRegistry, OPENMETRICS_CONTENT_TYPE,
+};
+use llm_config_security::KeyValidator;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Shared state of the metrics listener
+#[derive(Clone)]
+pub struct MetricsState {
+    pub registry: Arc<MetricsRegistry>,
+    pub health: Arc<HealthChecker>,
+}
+
+impl MetricsState {
+    /// Create the metrics state
+    pub fn new(registry: Arc<MetricsRegistry>, health: Arc<HealthChecker>) -> Self {
+        Self { registry, health }
+    }
+}
+
+impl FromRef<MetricsState> for Arc<MetricsRegistry> {
+    fn from_ref(state: &MetricsState) -> Self {
+        Arc::clone(&state.registry)
+    }
+}
+
+/// Create the router serving `/metrics` and `/health`
+pub fn metrics_router(state: MetricsState) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .route("/health", get(health))
+        .with_state(state)
+}
+
+/// Create a router serving only `/metrics`, for merging into the API router
+pub fn metrics_route(registry: Arc<MetricsRegistry>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(registry)
+}
+
+/// Count and time requests by method, route and status
+///
+/// Requests are labelled with the matched route (e.g.
+/// `/api/v1/configs/:namespace/:key`) rather than the raw path, so label
+/// cardinality stays bounded. Add it with `route_layer` so the route is known.
+pub async fn track_http_metrics(
+    State(registry): State<Arc<MetricsRegistry>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let system = registry.system();
+    system.record_http_request(&method, &path, response.status().as_u16());
+    let request_id = response
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    #[cfg(feature = "otel")]
+    let trace_id = crate::otel::current_trace_id();
+    #[cfg(not(feature = "otel"))]
+    let trace_id: Option<String> = None;
+
+    let mut exemplar = Vec::new();
+    if let Some(trace_id) = &trace_id {
+        exemplar.push(("trace_id", trace_id.as_str()));
+    }
+    if let Some(request_id) = &request_id {
+        exemplar.push(("request_id", request_id.as_str()));
+    }
+    system.observe_http_duration_with_exemplar(&method, &path, started.elapsed().as_secs_f64(), &exemplar);
+    response
+}
+
+/// All registered metrics, in the OpenMetrics format if the scraper accepts
+/// it and the Prometheus text format otherwise
+async fn metrics(State(registry): State<Arc<MetricsRegistry>>, headers: HeaderMap) -> Response {
+    let openmetrics = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/openmetrics-text"));
+    let encoded = if openmetrics {
+        registry.encode_openmetrics().map(|body| (OPENMETRICS_CONTENT_TYPE, body))
+    } else {
+        registry.encode_text().map(|body| ("text/plain; version=0.0.4", body))
+    };
+
+    match encoded {
+        Ok((content_type, body)) => ([(header::CONTENT_TYPE, content_type)], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Health report of all registered checks (503 when unhealthy)
+async fn health(State(state): State<MetricsState>) -> Response {
+    let report = state.health.check_health().await;
+    let status = if report.status.is_unhealthy() {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    (status, Json(report)).into_response()
+}
+
+/// Health check of the encryption key
+///
+/// Unhealthy when the key cannot encrypt and decrypt a probe value, and
+/// degraded when the key is older than the rotation policy allows. The key
+/// age is exported as the `crypto_key_age_days` gauge.
+pub struct CryptoHealthCheck {
+    manager: Arc<ConfigManager>,
+    created_at: Option<DateTime<Utc>>,
+    validator: KeyValidator,
+    metrics: Option<Arc<MetricsRegistry>>,
+}
+
+impl CryptoHealthCheck {
+    /// Check the encryption key of `manager` against the default rotation policy
+    pub fn new(manager: Arc<ConfigManager>) -> Self {
+        Self {
+            manager,
+            created_at: None,
+            validator: KeyValidator::default(),
+            metrics: None,
+        }
+    }
+
+    /// Report the age of a key created at `created_at` (unknown when unset)
+    pub fn with_created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Check the key age against a rotation policy
+    pub fn with_validator(mut self, validator: KeyValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Export the key age in a metrics registry
+    pub fn with_metrics(mut self, registry: Arc<MetricsRegistry>) -> Self {
+        self.metrics = Some(registry);
+        self
+    }
+
+    fn result(&self) -> HealthCheckResult {
+        if let Err(e) = self.manager.probe_encryption_key() {
+            return HealthCheckResult::unhealthy("crypto", format!("Encryption key probe failed: {}", e));
+        }
+        let Some(created_at) = self.created_at else {
+            return HealthCheckResult::healthy("crypto");
+        };
+
+        let age_days = Utc::now().signed_duration_since(created_at).num_days();
+        if let Some(metrics) = &self.metrics {
+            metrics.crypto().set_key_age_days(age_days);
+        }
+        if self.validator.should_rotate(created_at) {
+            HealthCheckResult::degraded(
+                "crypto",
+                format!(
+                    "Encryption key is {} days old (rotate every {} days)",
+                    age_days,
+                    self.validator.max_age_days()
+                ),
+            )
+        } else {
+            HealthCheckResult::healthy("crypto")
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for CryptoHealthCheck {
+    async fn check(&self) -> HealthCheckResult {
+        let start = Instant::now();
+        self.result().with_duration(start.elapsed())
+    }
+
+    fn name(&self) -> &str {
+        "crypto"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use llm_config_metrics::{HealthStatus, StorageHealthCheck};
+    use tower::ServiceExt;
+
+    struct FailingCheck;
+
+    #[async_trait::async_trait]
+    impl HealthCheck for FailingCheck {
+        async fn check(&self) -> HealthCheckResult {
+            HealthCheckResult::unhealthy("failing", "down")
+        }
+
+        fn name(&self) -> &str {
+            "failing"
+        }
+    }
+
+    fn state() -> MetricsState {
+        let health = HealthChecker::new();
+        health.register(Box::new(StorageHealthCheck::new("storage")));
+        MetricsState::new(Arc::new(MetricsRegistry::new().unwrap()), Arc::new(health))
+    }
+
+    async fn get_path(router: Router, path: &str) -> (StatusCode, String) {
+        let response = router
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let state = state();
+        state.registry.config().record_operation("get", "production");
+
+        let (status, body) = get_path(metrics_router(state), "/metrics").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("# TYPE"));
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint() {
+        let state = state();
+        let (status, body) = get_path(metrics_router(state.clone()), "/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("\"storage\""));
+
+        state.health.register(Box::new(FailingCheck));
+        let (status, _) = get_path(metrics_router(state), "/health").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_crypto_health_check() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+        let result = CryptoHealthCheck::new(Arc::clone(&manager)).check().await;
+        assert_eq!(result.status, HealthStatus::Unhealthy);
+
+        let manager = Arc::new(
+            ConfigManager::new(temp_dir.path())
+                .unwrap()
+                .with_encryption_key(SecretKey::generate(Algorithm::Aes256Gcm).unwrap()),
+        );
+        let registry = Arc::new(MetricsRegistry::new().unwrap());
+        let check = CryptoHealthCheck::new(Arc::clone(&manager))
+            .with_created_at(Utc::now() - chrono::Duration::days(10))
+            .with_metrics(Arc::clone(&registry));
+        assert_eq!(check.check().await.status, HealthStatus::Healthy);
+        assert_eq!(registry.crypto().key_age_days(), 10.0);
+
+        // Past the rotation policy the key still works, but is due for rotation
+        let check = check.with_validator(KeyValidator::new(7));
+        let result = check.check().await;
+        assert_eq!(result.status, HealthStatus::Degraded);
+        assert!(result.message.unwrap().contains("10 days old"));
+    }
+}
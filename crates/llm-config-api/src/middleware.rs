@@ -1,31 +1,67 @@
 //! Security middleware for API requests
 //!
 //! This module provides security middleware layers including:
-//! - Input validation
+//! - Input validation, including JSON request bodies
 //! - Rate limiting
 //! - Policy enforcement
+//! - Workload identity (SPIFFE) resolution
+//! - Client certificate identities of native mTLS connections
+//! - Client IP resolution behind trusted proxies (`Forwarded`, `X-Forwarded-For`)
+//! - Request IDs (`x-request-id`) for correlating responses with audit entries
 //! - Request/response sanitization
 
+use crate::tls::TlsSession;
 use axum::{
+    body::{self, Body},
     extract::{ConnectInfo, Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
 use llm_config_security::{
-    InputValidator, PolicyEnforcer, RateLimiter, SecurityContext, SecurityError,
+    InputValidator, PolicyEnforcer, PrincipalQuotas, RateLimitStore, RateLimiter,
+    SecurityContext, SecurityError, SpiffeId, WorkloadIdentityMapper,
 };
+use llm_config_metrics::MetricsRegistry;
 use serde_json::json;
-use std::net::SocketAddr;
-use std::sync::Arc;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// Header carrying the verified client certificate from the mTLS proxy
+pub const CLIENT_CERT_HEADER: &str = "x-forwarded-client-cert";
+
+/// Header carrying the request ID, generated when the caller sends none
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Header carrying the caller's user ID, set by an authenticating proxy. Only
+/// honored from trusted proxies.
+pub const USER_ID_HEADER: &str = "x-user-id";
+
+/// Header carrying the caller's session ID
+pub const SESSION_ID_HEADER: &str = "x-session-id";
+
+/// Header carrying the caller's external identity groups (comma-separated),
+/// e.g. the OIDC `groups` claim forwarded by an SSO proxy. Only honored from
+/// trusted proxies.
+pub const USER_GROUPS_HEADER: &str = "x-user-groups";
+
+/// Header carrying the client IP chain from reverse proxies
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
 
 /// Security middleware state
 #[derive(Clone)]
 pub struct SecurityState {
     pub rate_limiter: Arc<RateLimiter>,
     pub input_validator: Arc<InputValidator>,
-    pub policy_enforcer: Arc<PolicyEnforcer>,
+    /// Live security policy, updated through the admin API
+    pub policy_enforcer: Arc<RwLock<PolicyEnforcer>>,
+    pub workload_identities: Option<Arc<WorkloadIdentityMapper>>,
+    /// Registry counting rejected requests
+    pub metrics: Option<Arc<MetricsRegistry>>,
+    /// RBAC role assignments selecting per-role rate limit quotas
+    pub principal_roles: Option<Arc<RwLock<llm_config_rbac::PolicyEnforcer>>>,
 }
 
 impl SecurityState {
@@ -34,7 +70,10 @@ impl SecurityState {
         Self {
             rate_limiter: Arc::new(RateLimiter::new(Default::default())),
             input_validator: Arc::new(InputValidator::default()),
-            policy_enforcer: Arc::new(PolicyEnforcer::default()),
+            policy_enforcer: Arc::new(RwLock::new(PolicyEnforcer::default())),
+            workload_identities: None,
+            metrics: None,
+            principal_roles: None,
         }
     }
 
@@ -47,8 +86,92 @@ impl SecurityState {
         Self {
             rate_limiter: Arc::new(rate_limiter),
             input_validator: Arc::new(input_validator),
-            policy_enforcer: Arc::new(policy_enforcer),
+            policy_enforcer: Arc::new(RwLock::new(policy_enforcer)),
+            workload_identities: None,
+            metrics: None,
+            principal_roles: None,
+        }
+    }
+
+    /// Accept SPIFFE workload identities resolved through the given mapper
+    pub fn with_workload_identities(mut self, mapper: WorkloadIdentityMapper) -> Self {
+        self.workload_identities = Some(Arc::new(mapper));
+        self
+    }
+
+    /// Take the client IP from forwarding headers of the given proxies
+    /// (addresses or CIDR ranges)
+    pub fn with_trusted_proxies(mut self, proxies: Vec<String>) -> Self {
+        let mut policy = self.policy_enforcer.read().unwrap().get_policy().clone();
+        policy.trusted_proxies = proxies;
+        self.policy_enforcer = Arc::new(RwLock::new(PolicyEnforcer::new(policy)));
+        self
+    }
+
+    /// The client IP of a request from `peer`
+    ///
+    /// Forwarding headers are only honored when `peer` is a trusted proxy.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        self.policy_enforcer
+            .read()
+            .unwrap()
+            .client_ip(peer, header("forwarded"), header(X_FORWARDED_FOR))
+    }
+
+    /// Whether `peer` is a trusted proxy
+    pub fn is_trusted_proxy(&self, peer: IpAddr) -> bool {
+        self.policy_enforcer.read().unwrap().is_trusted_proxy(peer)
+    }
+
+    /// Limit authenticated principals individually with the given quotas
+    pub fn with_principal_quotas(mut self, quotas: PrincipalQuotas) -> Self {
+        let config = self.rate_limiter.config().clone();
+        self.rate_limiter = Arc::new(RateLimiter::new(config).with_principal_quotas(quotas));
+        self
+    }
+
+    /// Keep rate limiter buckets and bans in a store shared between replicas
+    pub fn with_rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        let limiter = RateLimiter::new(self.rate_limiter.config().clone())
+            .with_principal_quotas(self.rate_limiter.principal_quotas().clone())
+            .with_store(store);
+        self.rate_limiter = Arc::new(limiter);
+        self
+    }
+
+    /// Select role quotas by the role assignments of the given RBAC enforcer
+    pub fn with_principal_roles(mut self, rbac: Arc<RwLock<llm_config_rbac::PolicyEnforcer>>) -> Self {
+        self.principal_roles = Some(rbac);
+        self
+    }
+
+    /// Names of the roles assigned to a principal
+    fn principal_roles(&self, principal: &str) -> Vec<String> {
+        match &self.principal_roles {
+            Some(rbac) => rbac
+                .read()
+                .unwrap()
+                .get_user_roles(principal)
+                .iter()
+                .map(|assignment| assignment.role.to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Count rejected requests by check in the given registry
+    pub fn with_metrics(mut self, metrics: Arc<MetricsRegistry>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Record a request rejected by `check` and build its error response
+    fn reject(&self, check: &str, error: SecurityError, status: StatusCode) -> SecurityResponse {
+        if let Some(metrics) = &self.metrics {
+            metrics.security().record_rejection(check);
         }
+        SecurityResponse::from_security_error(error, status)
     }
 }
 
@@ -68,7 +191,7 @@ pub async fn rate_limit_middleware(
     request: Request,
     next: Next,
 ) -> Result<Response, SecurityResponse> {
-    let ip = addr.ip();
+    let ip = security.client_ip(addr.ip(), &headers);
 
     // Check if request has authentication (simplified - in production use proper auth)
     let is_authenticated = headers.get("authorization").is_some();
@@ -77,7 +200,7 @@ pub async fn rate_limit_middleware(
     security
         .rate_limiter
         .check_request(ip, is_authenticated)
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::TOO_MANY_REQUESTS))?;
+        .map_err(|e| security.reject("rate_limit", e, StatusCode::TOO_MANY_REQUESTS))?;
 
     Ok(next.run(request).await)
 }
@@ -98,17 +221,25 @@ pub async fn input_validation_middleware(
     security
         .input_validator
         .validate(path)
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::BAD_REQUEST))?;
+        .map_err(|e| security.reject("input_validation", e, StatusCode::BAD_REQUEST))?;
 
     // Validate query parameters
-    if !query.is_empty() {
+    validate_query(&security, query)?;
+
+    Ok(next.run(request).await)
+}
+
+/// Validate each query parameter on its own
+///
+/// The `&` separating parameters would otherwise trip command injection detection.
+fn validate_query(security: &SecurityState, query: &str) -> Result<(), SecurityResponse> {
+    for param in query.split('&').filter(|param| !param.is_empty()) {
         security
             .input_validator
-            .validate(query)
-            .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::BAD_REQUEST))?;
+            .validate(param)
+            .map_err(|e| security.reject("input_validation", e, StatusCode::BAD_REQUEST))?;
     }
-
-    Ok(next.run(request).await)
+    Ok(())
 }
 
 /// Policy enforcement middleware
@@ -121,13 +252,15 @@ pub async fn policy_enforcement_middleware(
     request: Request,
     next: Next,
 ) -> Result<Response, SecurityResponse> {
-    let ip = addr.ip();
+    let ip = security.client_ip(addr.ip(), &headers);
 
     // Check if IP is blocked
     security
         .policy_enforcer
+        .read()
+        .unwrap()
         .check_ip(&ip.to_string())
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::FORBIDDEN))?;
+        .map_err(|e| security.reject("ip", e, StatusCode::FORBIDDEN))?;
 
     // Check TLS (in production, check X-Forwarded-Proto or similar)
     let is_tls = headers
@@ -138,15 +271,19 @@ pub async fn policy_enforcement_middleware(
 
     security
         .policy_enforcer
+        .read()
+        .unwrap()
         .check_tls(is_tls, "1.2")
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::UPGRADE_REQUIRED))?;
+        .map_err(|e| security.reject("tls", e, StatusCode::UPGRADE_REQUIRED))?;
 
     // Check CORS origin
     if let Some(origin) = headers.get("origin").and_then(|v| v.to_str().ok()) {
         security
             .policy_enforcer
+            .read()
+            .unwrap()
             .check_origin(origin)
-            .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::FORBIDDEN))?;
+            .map_err(|e| security.reject("origin", e, StatusCode::FORBIDDEN))?;
     }
 
     // Check request size
@@ -157,16 +294,20 @@ pub async fn policy_enforcement_middleware(
     {
         security
             .policy_enforcer
+            .read()
+            .unwrap()
             .check_request_size(content_length)
-            .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::PAYLOAD_TOO_LARGE))?;
+            .map_err(|e| security.reject("request_size", e, StatusCode::PAYLOAD_TOO_LARGE))?;
     }
 
     // Check endpoint access
     let endpoint = request.uri().path();
     security
         .policy_enforcer
+        .read()
+        .unwrap()
         .check_endpoint(endpoint)
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::FORBIDDEN))?;
+        .map_err(|e| security.reject("endpoint", e, StatusCode::FORBIDDEN))?;
 
     Ok(next.run(request).await)
 }
@@ -184,18 +325,19 @@ pub async fn security_context_middleware(
 
     // Extract user ID from headers (simplified - in production use proper auth)
     let user_id = headers
-        .get("x-user-id")
+        .get(USER_ID_HEADER)
         .and_then(|v| v.to_str().ok())
         .unwrap_or("anonymous")
         .to_string();
 
     // Create security context
-    let context = SecurityContext::new(user_id, ip.to_string());
+    let request_id = request_id(&headers);
+    let context = caller_context(user_id, ip.to_string(), &request_id, &headers);
 
     // Store context in request extensions for use in handlers
     request.extensions_mut().insert(context);
 
-    next.run(request).await
+    with_request_id(next.run(request).await, &request_id)
 }
 
 /// Comprehensive security middleware
@@ -208,65 +350,250 @@ pub async fn comprehensive_security_middleware(
     mut request: Request,
     next: Next,
 ) -> Result<Response, SecurityResponse> {
-    let ip = addr.ip();
+    let ip = security.client_ip(addr.ip(), &headers);
     let is_authenticated = headers.get("authorization").is_some();
+    let tls = request.extensions().get::<TlsSession>().cloned();
+    let client_certificate = tls.as_ref().and_then(|tls| tls.client_certificate.as_ref());
+
+    // 1. Workload identity, from the client certificate of a native mTLS
+    // connection or the SVID forwarded by a trusted mTLS-terminating proxy.
+    // Anyone else cannot claim an identity through the header, so it is
+    // dropped before the handlers see it.
+    let workload = match (&security.workload_identities, client_certificate) {
+        (Some(mapper), Some(certificate)) => match certificate.spiffe_id() {
+            Some(spiffe_id) => Some(
+                mapper
+                    .resolve(&spiffe_id)
+                    .map_err(|e| security.reject("workload_identity", e, StatusCode::UNAUTHORIZED))?,
+            ),
+            None => None,
+        },
+        _ => None,
+    };
+    let forwarded_certificate = match tls {
+        None if security.is_trusted_proxy(addr.ip()) => headers.get(CLIENT_CERT_HEADER),
+        _ => {
+            request.headers_mut().remove(CLIENT_CERT_HEADER);
+            None
+        }
+    };
+    let workload = match (&security.workload_identities, forwarded_certificate) {
+        (Some(mapper), Some(header)) => {
+            let spiffe_id = header
+                .to_str()
+                .ok()
+                .and_then(SpiffeId::from_xfcc)
+                .ok_or_else(|| {
+                    if let Some(metrics) = &security.metrics {
+                        metrics.security().record_rejection("workload_identity");
+                    }
+                    SecurityResponse::new(
+                        StatusCode::UNAUTHORIZED,
+                        "Client certificate does not carry a SPIFFE ID".to_string(),
+                    )
+                })?;
+
+            let identity = mapper
+                .resolve(&spiffe_id)
+                .map_err(|e| security.reject("workload_identity", e, StatusCode::UNAUTHORIZED))?;
+            Some(identity)
+        }
+        _ => workload,
+    };
+
+    // Principal of a verified client certificate without a mapped SPIFFE ID
+    let certificate_principal = match &workload {
+        Some(identity) => Some(identity.principal.clone()),
+        None => client_certificate.and_then(|certificate| certificate.common_name.clone()),
+    };
+
+    // 2. Rate limiting, per principal first so a throttled principal does not
+    // consume the shared buckets. Only principals of verified certificates
+    // are limited individually: `x-user-id` and bearer tokens are not
+    // checked, so keying on them would let callers pick any bucket.
+    if let Some(principal) = &certificate_principal {
+        security
+            .rate_limiter
+            .check_principal(principal, &security.principal_roles(principal))
+            .map_err(|e| security.reject("rate_limit", e, StatusCode::TOO_MANY_REQUESTS))?;
+    }
 
-    // 1. Rate limiting
     security
         .rate_limiter
         .check_request(ip, is_authenticated)
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::TOO_MANY_REQUESTS))?;
+        .map_err(|e| security.reject("rate_limit", e, StatusCode::TOO_MANY_REQUESTS))?;
 
-    // 2. Policy enforcement - IP check
+    // 3. Policy enforcement - IP check
     security
         .policy_enforcer
+        .read()
+        .unwrap()
         .check_ip(&ip.to_string())
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::FORBIDDEN))?;
+        .map_err(|e| security.reject("ip", e, StatusCode::FORBIDDEN))?;
 
-    // 3. Policy enforcement - TLS check
-    let is_tls = headers
+    // 4. Policy enforcement - TLS check (native, or terminated by a proxy)
+    let forwarded_tls = headers
         .get("x-forwarded-proto")
         .and_then(|v| v.to_str().ok())
         .map(|v| v == "https")
         .unwrap_or(false);
+    let (is_tls, tls_version) = match &tls {
+        Some(tls) => (true, tls.version),
+        None => (forwarded_tls, "1.2"),
+    };
 
     security
         .policy_enforcer
-        .check_tls(is_tls, "1.2")
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::UPGRADE_REQUIRED))?;
+        .read()
+        .unwrap()
+        .check_tls(is_tls, tls_version)
+        .map_err(|e| security.reject("tls", e, StatusCode::UPGRADE_REQUIRED))?;
 
-    // 4. Policy enforcement - endpoint check
+    // 5. Policy enforcement - endpoint check
     let endpoint = request.uri().path();
     security
         .policy_enforcer
+        .read()
+        .unwrap()
         .check_endpoint(endpoint)
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::FORBIDDEN))?;
+        .map_err(|e| security.reject("endpoint", e, StatusCode::FORBIDDEN))?;
 
-    // 5. Input validation
+    // 6. Input validation
     let uri = request.uri();
     security
         .input_validator
         .validate(uri.path())
-        .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::BAD_REQUEST))?;
+        .map_err(|e| security.reject("input_validation", e, StatusCode::BAD_REQUEST))?;
 
     if let Some(query) = uri.query() {
+        validate_query(&security, query)?;
+    }
+
+    let mut request = validate_json_body(&security, request).await?;
+
+    // 7. Create security context. The caller is the principal of a verified
+    // certificate, or the user and groups a trusted proxy asserts in its
+    // headers; anyone else cannot claim them, so they are dropped.
+    let trusted_proxy = security.is_trusted_proxy(addr.ip());
+    if !trusted_proxy {
+        request.headers_mut().remove(USER_ID_HEADER);
+        request.headers_mut().remove(USER_GROUPS_HEADER);
+    }
+    let forwarded_user = headers
+        .get(USER_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|_| trusted_proxy);
+    let user_id = match (&certificate_principal, forwarded_user) {
+        (Some(principal), _) => principal.clone(),
+        (None, Some(user)) => user.to_string(),
+        (None, None) => "anonymous".to_string(),
+    };
+
+    let request_id = request_id(&headers);
+    let mut context = caller_context(user_id, ip.to_string(), &request_id, &headers);
+    if let Some(principal) = certificate_principal {
+        context = context.with_metadata("verified_principal", principal);
+    }
+    if let Some(groups) = headers.get(USER_GROUPS_HEADER).and_then(|v| v.to_str().ok()) {
+        if trusted_proxy {
+            context = context.with_metadata("groups", groups);
+        }
+    }
+    if let Some(identity) = workload {
+        context = context.with_metadata("spiffe_id", identity.spiffe_id.to_string());
+        request.extensions_mut().insert(identity);
+    }
+    request.extensions_mut().insert(context);
+
+    Ok(with_request_id(next.run(request).await, &request_id))
+}
+
+/// Validate a JSON request body before it reaches the handlers
+///
+/// The body is buffered up to the policy's maximum request size, checked as a
+/// whole with [`InputValidator::validate_json`] and then field by field, since
+/// escaped markup (`\u003cscript`) only shows once the payload is parsed. The
+/// request is rebuilt around the buffered body.
+async fn validate_json_body(
+    security: &SecurityState,
+    request: Request,
+) -> Result<Request, SecurityResponse> {
+    if !is_json(request.headers()) {
+        return Ok(request);
+    }
+
+    let limit = security.policy_enforcer.read().unwrap().get_policy().max_request_size;
+    let (parts, body) = request.into_parts();
+    let bytes = body::to_bytes(body, limit).await.map_err(|_| {
+        security.reject(
+            "request_size",
+            SecurityError::ValidationError(format!(
+                "Request body exceeds maximum size of {} bytes",
+                limit
+            )),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        )
+    })?;
+
+    if !bytes.is_empty() {
+        let text = std::str::from_utf8(&bytes).map_err(|_| {
+            security.reject(
+                "input_validation",
+                SecurityError::ValidationError("Request body is not valid UTF-8".to_string()),
+                StatusCode::BAD_REQUEST,
+            )
+        })?;
+        let payload = security
+            .input_validator
+            .validate_json(text)
+            .map_err(|e| security.reject("input_validation", e, StatusCode::BAD_REQUEST))?;
         security
             .input_validator
-            .validate(query)
-            .map_err(|e| SecurityResponse::from_security_error(e, StatusCode::BAD_REQUEST))?;
+            .validate_json_fields(&payload)
+            .map_err(|e| security.reject("input_validation", e, StatusCode::BAD_REQUEST))?;
     }
 
-    // 6. Create security context
-    let user_id = headers
-        .get("x-user-id")
+    Ok(Request::from_parts(parts, Body::from(bytes)))
+}
+
+/// Whether the request declares a JSON body (`application/json` or `+json`)
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
         .and_then(|v| v.to_str().ok())
-        .unwrap_or("anonymous")
-        .to_string();
+        .and_then(|v| v.split(';').next())
+        .map(|mime| {
+            let mime = mime.trim().to_ascii_lowercase();
+            mime == "application/json" || mime.ends_with("+json")
+        })
+        .unwrap_or(false)
+}
 
-    let context = SecurityContext::new(user_id, ip.to_string());
-    request.extensions_mut().insert(context);
+/// Security context for a caller, with its request ID and `x-session-id` session
+fn caller_context(user_id: String, ip: String, request_id: &str, headers: &HeaderMap) -> SecurityContext {
+    let mut context = SecurityContext::new(user_id, ip).with_metadata("request_id", request_id);
+    if let Some(session_id) = headers.get(SESSION_ID_HEADER).and_then(|v| v.to_str().ok()) {
+        context = context.with_session(session_id);
+    }
+    context
+}
 
-    Ok(next.run(request).await)
+/// The caller's `x-request-id`, or a new ID if it is missing or malformed
+fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.chars().all(|c| c.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Echo the request ID so callers can correlate responses with audit entries
+fn with_request_id(mut response: Response, request_id: &str) -> Response {
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
 }
 
 /// Security error response
@@ -327,6 +654,7 @@ mod tests {
         assert!(Arc::strong_count(&state.rate_limiter) == 1);
         assert!(Arc::strong_count(&state.input_validator) == 1);
         assert!(Arc::strong_count(&state.policy_enforcer) == 1);
+        assert!(state.workload_identities.is_none());
     }
 
     #[test]
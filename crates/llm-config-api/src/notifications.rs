@@ -0,0 +1,496 @@
+//! Webhook notifications for config changes
+//!
+//! A [`WebhookNotifier`] subscribed to a manager's change bus POSTs a
+//! [`ChangeNotification`] to every [`WebhookEndpoint`] whose namespace
+//! patterns match the changed config. Payloads are signed with HMAC-SHA256
+//! when the endpoint has a secret, failed deliveries are retried with
+//! exponential backoff, and outcomes are counted in the
+//! [`MetricsRegistry`]. Notifications never contain config values, so
+//! secrets do not leave the server.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use llm_config_core::error_utils::{retry_with_backoff, RetryPolicy};
+use llm_config_core::{ChangeEvent, ChangeKind, Environment, TenantId};
+use llm_config_metrics::MetricsRegistry;
+use llm_config_rbac::namespace_matches;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+
+/// Header carrying `sha256=<hex HMAC of the body>` for endpoints with a secret
+pub const SIGNATURE_HEADER: &str = "x-llm-config-signature";
+
+/// Header carrying the notification's event name
+pub const EVENT_HEADER: &str = "x-llm-config-event";
+
+/// Body format of the notifications sent to an endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    /// The [`ChangeNotification`] as JSON
+    #[default]
+    Json,
+    /// A Slack incoming-webhook message
+    Slack,
+}
+
+/// A webhook receiving change notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// Namespace patterns to notify about (see [`namespace_matches`]);
+    /// all namespaces when empty
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// Shared secret the payloads are signed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub format: PayloadFormat,
+}
+
+impl WebhookEndpoint {
+    /// Notify a URL about changes in all namespaces
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            namespaces: Vec::new(),
+            secret: None,
+            format: PayloadFormat::Json,
+        }
+    }
+
+    /// Only notify about namespaces matching `pattern`
+    pub fn with_namespace(mut self, pattern: impl Into<String>) -> Self {
+        self.namespaces.push(pattern.into());
+        self
+    }
+
+    /// Sign payloads with `secret`
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Set the payload format
+    pub fn with_format(mut self, format: PayloadFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Check whether the endpoint is notified about changes in `namespace`
+    pub fn matches(&self, namespace: &str) -> bool {
+        self.namespaces.is_empty()
+            || self
+                .namespaces
+                .iter()
+                .any(|pattern| namespace_matches(pattern, namespace))
+    }
+}
+
+/// Notification about a created, updated, deleted or rolled back config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeNotification {
+    /// `config.created`, `config.updated`, `config.deleted` or `config.rolled_back`
+    pub event: String,
+    pub namespace: String,
+    pub key: String,
+    pub environment: Environment,
+    /// Version after the change (the deleted version for deletes)
+    pub version: u64,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<TenantId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ChangeNotification {
+    /// Notification for a change event (`None` for metadata-only changes)
+    pub fn from_change(change: &ChangeEvent) -> Option<Self> {
+        let event = match change.kind {
+            ChangeKind::Set if change.version <= 1 => "config.created",
+            ChangeKind::Set => "config.updated",
+            ChangeKind::Delete => "config.deleted",
+            ChangeKind::Rollback => "config.rolled_back",
+            ChangeKind::Metadata => return None,
+        };
+
+        Some(Self {
+            event: event.to_string(),
+            namespace: change.namespace.clone(),
+            key: change.key.clone(),
+            environment: change.environment,
+            version: change.version,
+            user: change.user.clone(),
+            tenant_id: change.tenant_id.clone(),
+            request_id: change
+                .context
+                .as_ref()
+                .and_then(|context| context.request_id.clone()),
+            timestamp: Utc::now(),
+        })
+    }
+
+    /// The notification as a Slack incoming-webhook message
+    pub fn slack_payload(&self) -> serde_json::Value {
+        let action = match self.event.as_str() {
+            "config.created" => "created",
+            "config.deleted" => "deleted",
+            "config.rolled_back" => "rolled back",
+            _ => "updated",
+        };
+        let tenant = match &self.tenant_id {
+            Some(tenant) => format!(" for tenant `{}`", tenant),
+            None => String::new(),
+        };
+
+        serde_json::json!({
+            "text": format!(
+                "*{}* {} `{}/{}` in {}{} (version {})",
+                self.user, action, self.namespace, self.key, self.environment, tenant, self.version
+            )
+        })
+    }
+
+    /// Request body for an endpoint
+    fn body(&self, format: PayloadFormat) -> Vec<u8> {
+        let body = match format {
+            PayloadFormat::Json => serde_json::to_vec(self),
+            PayloadFormat::Slack => serde_json::to_vec(&self.slack_payload()),
+        };
+        body.expect("notifications serialize to JSON")
+    }
+}
+
+/// Signature header value of a payload: `sha256=<hex HMAC-SHA256>`
+///
+/// Receivers recompute it over the raw request body with the shared secret.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Error from a single delivery attempt
+#[derive(Debug)]
+enum DeliveryError {
+    Transport(reqwest::Error),
+    Status(reqwest::StatusCode),
+}
+
+impl DeliveryError {
+    /// Connection problems, throttling, and server errors are worth retrying
+    fn is_retriable(&self) -> bool {
+        match self {
+            DeliveryError::Transport(_) => true,
+            DeliveryError::Status(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryError::Transport(e) => write!(f, "request failed: {}", e),
+            DeliveryError::Status(status) => write!(f, "unexpected status {}", status),
+        }
+    }
+}
+
+/// Delivers change notifications to webhook endpoints
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    endpoints: Arc<Vec<WebhookEndpoint>>,
+    retry_policy: RetryPolicy,
+    metrics: Option<Arc<MetricsRegistry>>,
+}
+
+impl WebhookNotifier {
+    /// Create a notifier for the given endpoints
+    pub fn new(endpoints: Vec<WebhookEndpoint>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoints: Arc::new(endpoints),
+            retry_policy: RetryPolicy::default(),
+            metrics: None,
+        }
+    }
+
+    /// Set the retry policy for failed deliveries
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Count deliveries and their outcomes in `registry`
+    pub fn with_metrics(mut self, registry: Arc<MetricsRegistry>) -> Self {
+        self.metrics = Some(registry);
+        self
+    }
+
+    /// The configured endpoints
+    pub fn endpoints(&self) -> &[WebhookEndpoint] {
+        &self.endpoints
+    }
+
+    /// Notify the matching endpoints about a change
+    ///
+    /// Endpoints are notified concurrently; a failure at one does not stop
+    /// the others, and all failures are reported together.
+    pub async fn notify(&self, change: &ChangeEvent) -> anyhow::Result<()> {
+        let Some(notification) = ChangeNotification::from_change(change) else {
+            return Ok(());
+        };
+
+        let endpoints: Vec<&WebhookEndpoint> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.matches(&notification.namespace))
+            .collect();
+        let results = futures::future::join_all(
+            endpoints
+                .iter()
+                .map(|endpoint| self.deliver(endpoint, &notification)),
+        )
+        .await;
+
+        let failures: Vec<String> = endpoints
+            .iter()
+            .zip(results)
+            .filter_map(|(endpoint, result)| {
+                result.err().map(|e| format!("webhook {}: {}", endpoint.url, e))
+            })
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!(failures.join("; "))
+        }
+    }
+
+    /// Notify about every change published on a bus from now on
+    ///
+    /// Failed deliveries and missed changes are logged. The task ends when
+    /// the bus is dropped.
+    pub fn notify_changes(&self, mut changes: broadcast::Receiver<ChangeEvent>) -> JoinHandle<()> {
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(change) => {
+                        if let Err(e) = notifier.notify(&change).await {
+                            tracing::warn!("Change notification failed: {}", e);
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!("{} config changes were not notified", missed);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    async fn deliver(
+        &self,
+        endpoint: &WebhookEndpoint,
+        notification: &ChangeNotification,
+    ) -> Result<(), DeliveryError> {
+        let body = notification.body(endpoint.format);
+        let start = Instant::now();
+
+        let result = retry_with_backoff(
+            || self.post(endpoint, &notification.event, &body),
+            self.retry_policy.clone(),
+            DeliveryError::is_retriable,
+        )
+        .await;
+
+        if let Some(registry) = &self.metrics {
+            let outcome = if result.is_ok() { "delivered" } else { "failed" };
+            let metrics = registry.notifications();
+            metrics.record_delivery(&notification.event, outcome);
+            metrics.observe_duration(start.elapsed().as_secs_f64());
+        }
+        result
+    }
+
+    async fn post(
+        &self,
+        endpoint: &WebhookEndpoint,
+        event: &str,
+        body: &[u8],
+    ) -> Result<(), DeliveryError> {
+        if let Some(registry) = &self.metrics {
+            registry.notifications().record_attempt();
+        }
+
+        let mut request = self
+            .client
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event)
+            .body(body.to_vec());
+        if let Some(secret) = &endpoint.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, body));
+        }
+
+        let response = request.send().await.map_err(DeliveryError::Transport)?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(DeliveryError::Status(response.status()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+    use llm_config_core::{ConfigEntry, ConfigValue, OperationContext};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+    fn change(kind: ChangeKind, namespace: &str, version: u64) -> ChangeEvent {
+        let mut entry = ConfigEntry::new(
+            namespace,
+            "model",
+            ConfigValue::String("gpt-4".to_string()),
+            Environment::Production,
+        );
+        entry.version = version;
+        ChangeEvent::stored(kind, &entry)
+            .with_user("alice")
+            .with_context(Some(OperationContext::default().with_request_id("req-1")))
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy::new(3, 1, 10, 2.0)
+    }
+
+    /// Webhook receiver answering the first `failures` requests with 503
+    async fn receiver(failures: usize) -> (String, Received) {
+        let received: Received = Arc::default();
+        let failed = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(received): State<Received>, headers: HeaderMap, body: Bytes| {
+                        let failed = Arc::clone(&failed);
+                        async move {
+                            if failed.fetch_add(1, Ordering::SeqCst) < failures {
+                                return StatusCode::SERVICE_UNAVAILABLE;
+                            }
+                            received.lock().unwrap().push((headers, body));
+                            StatusCode::OK
+                        }
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&received));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    #[test]
+    fn test_notification_events() {
+        let event = |kind, version| {
+            ChangeNotification::from_change(&change(kind, "app", version)).map(|n| n.event)
+        };
+        assert_eq!(event(ChangeKind::Set, 1).as_deref(), Some("config.created"));
+        assert_eq!(event(ChangeKind::Set, 2).as_deref(), Some("config.updated"));
+        assert_eq!(event(ChangeKind::Delete, 2).as_deref(), Some("config.deleted"));
+        assert_eq!(event(ChangeKind::Rollback, 3).as_deref(), Some("config.rolled_back"));
+        assert_eq!(event(ChangeKind::Metadata, 3), None);
+
+        let notification = ChangeNotification::from_change(&change(ChangeKind::Set, "app", 2)).unwrap();
+        assert_eq!(notification.request_id.as_deref(), Some("req-1"));
+        assert_eq!(
+            notification.slack_payload()["text"],
+            "*alice* updated `app/model` in production (version 2)"
+        );
+    }
+
+    #[test]
+    fn test_endpoint_namespace_patterns() {
+        let endpoint = WebhookEndpoint::new("http://localhost/hook").with_namespace("team-a/*");
+        assert!(endpoint.matches("team-a/prompts"));
+        assert!(!endpoint.matches("team-b/prompts"));
+        assert!(WebhookEndpoint::new("http://localhost/hook").matches("anything"));
+    }
+
+    #[tokio::test]
+    async fn test_signed_delivery_with_retries() {
+        let (url, received) = receiver(2).await;
+        let registry = Arc::new(MetricsRegistry::new().unwrap());
+        let notifier = WebhookNotifier::new(vec![
+            WebhookEndpoint::new(&url).with_secret("s3cret"),
+            WebhookEndpoint::new(&url).with_namespace("other/*"),
+        ])
+        .with_retry_policy(fast_retries())
+        .with_metrics(Arc::clone(&registry));
+
+        notifier.notify(&change(ChangeKind::Set, "app", 2)).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let (headers, body) = &received[0];
+        assert_eq!(headers[EVENT_HEADER], "config.updated");
+        assert_eq!(headers[SIGNATURE_HEADER], signature("s3cret", body).as_str());
+        let payload: ChangeNotification = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload.key, "model");
+        assert_eq!(payload.version, 2);
+        assert!(!String::from_utf8_lossy(body).contains("gpt-4"));
+
+        let metrics = registry.notifications();
+        assert_eq!(metrics.deliveries("config.updated", "delivered"), 1.0);
+        assert_eq!(metrics.attempts(), 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_reported() {
+        let (url, received) = receiver(usize::MAX).await;
+        let registry = Arc::new(MetricsRegistry::new().unwrap());
+        let notifier = WebhookNotifier::new(vec![
+            WebhookEndpoint::new(&url).with_format(PayloadFormat::Slack)
+        ])
+        .with_retry_policy(fast_retries())
+        .with_metrics(Arc::clone(&registry));
+
+        let err = notifier
+            .notify(&change(ChangeKind::Delete, "app", 1))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("503"));
+        assert!(received.lock().unwrap().is_empty());
+        assert_eq!(
+            registry.notifications().deliveries("config.deleted", "failed"),
+            1.0
+        );
+    }
+}
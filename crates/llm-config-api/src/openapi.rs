@@ -0,0 +1,172 @@
+//! OpenAPI document of the REST API
+//!
+//! [`ApiDoc`] is generated from the route handlers and describes the config,
+//! history, rollback and health endpoints with their request, response and
+//! error schemas. [`openapi_route`] serves it as OpenAPI 3.0 JSON at
+//! [`OPENAPI_PATH`]; [`swagger_ui_route`] adds an optional Swagger UI page.
+
+use crate::routes::{
+    self, AccessStatsResponse, ActivityEntryResponse, ActivitySource, BatchGetRequest, BatchGetResponse, BatchGetResult, BatchGetStatus, ConfigMetadataResponse, DryRunResponse,
+    ConfigResponse, ErrorResponse, ExplainResponse, HealthResponse, HistoryEntryResponse, NamespaceCopyRequest, NamespaceCopyResponse, PromoteRequest, ResolvedConfigResponse,
+    RollbackRequest, RollbackResponse, SetConfigRequest, TypeWarningResponse, UpdateMetadataRequest,
+};
+use axum::{response::Html, routing::get, Json, Router};
+use llm_config_core::{ValueChange, ValueDiff};
+use utoipa::OpenApi;
+
+/// Path the OpenAPI document is served at
+pub const OPENAPI_PATH: &str = "/api/v1/openapi.json";
+
+/// Path the Swagger UI is served at
+pub const SWAGGER_UI_PATH: &str = "/api/v1/docs";
+
+/// OpenAPI document of the REST API
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "LLM Config Manager API",
+        description = "Configuration and secret management for LLM applications. \
+            Errors are returned as `ErrorResponse`; errors clients are expected to \
+            handle carry a machine-readable `code`."
+    ),
+    paths(
+        routes::store_health_check,
+        routes::get_config,
+        routes::set_config,
+        routes::delete_config,
+        routes::list_configs,
+        routes::batch_get_configs,
+        routes::resolve_configs,
+        routes::explain_config,
+        routes::promote_configs,
+        routes::clone_namespace,
+        routes::rename_namespace,
+        routes::update_metadata,
+        routes::get_history,
+        routes::get_config_stats,
+        routes::get_changelog,
+        routes::get_activity,
+        routes::rollback_config,
+    ),
+    components(schemas(
+        ErrorResponse,
+        HealthResponse,
+        ConfigResponse,
+        ConfigMetadataResponse,
+        TypeWarningResponse,
+        DryRunResponse,
+        SetConfigRequest,
+        UpdateMetadataRequest,
+        BatchGetRequest,
+        BatchGetResponse,
+        BatchGetResult,
+        BatchGetStatus,
+        ResolvedConfigResponse,
+        ExplainResponse,
+        PromoteRequest,
+        NamespaceCopyRequest,
+        NamespaceCopyResponse,
+        HistoryEntryResponse,
+        AccessStatsResponse,
+        ActivityEntryResponse,
+        ActivitySource,
+        RollbackRequest,
+        RollbackResponse,
+        ValueDiff,
+        ValueChange,
+    )),
+    tags(
+        (name = "configs", description = "Read and write configuration values"),
+        (name = "history", description = "Version history, changelogs and rollback"),
+        (name = "health", description = "Liveness"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Create a router serving the OpenAPI document at [`OPENAPI_PATH`]
+///
+/// The document is public, like `/health`, so merge it outside the security
+/// middleware.
+pub fn openapi_route() -> Router {
+    Router::new().route(OPENAPI_PATH, get(|| async { Json(ApiDoc::openapi()) }))
+}
+
+/// Create a router serving Swagger UI for the OpenAPI document at [`SWAGGER_UI_PATH`]
+///
+/// The page loads the Swagger UI assets from the unpkg CDN.
+pub fn swagger_ui_route() -> Router {
+    Router::new().route(SWAGGER_UI_PATH, get(|| async { Html(swagger_ui_page()) }))
+}
+
+fn swagger_ui_page() -> String {
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>LLM Config Manager API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: "{}", dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>
+"##,
+        OPENAPI_PATH
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refs<'a>(value: &'a serde_json::Value, out: &mut Vec<&'a str>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    match value.as_str() {
+                        Some(target) if key == "$ref" => out.push(target),
+                        _ => refs(value, out),
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => values.iter().for_each(|value| refs(value, out)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_document_is_complete() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(doc["openapi"].as_str().unwrap().starts_with("3.0"));
+
+        let paths = doc["paths"].as_object().unwrap();
+        for path in [
+            "/health",
+            "/api/v1/configs/{namespace}",
+            "/api/v1/configs/{namespace}/{key}",
+            "/api/v1/configs/{namespace}/{key}/history",
+            "/api/v1/configs/{namespace}/{key}/rollback/{version}",
+        ] {
+            assert!(paths.contains_key(path), "{} is not documented", path);
+        }
+        let get = &paths["/api/v1/configs/{namespace}/{key}"]["get"];
+        assert_eq!(
+            get["responses"]["404"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ErrorResponse"
+        );
+
+        // Every referenced schema is defined
+        let mut targets = Vec::new();
+        refs(&doc, &mut targets);
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+        for target in targets {
+            let name = target.trim_start_matches("#/components/schemas/");
+            assert!(schemas.contains_key(name), "{} is not defined", target);
+        }
+    }
+}
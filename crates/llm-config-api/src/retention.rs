@@ -0,0 +1,96 @@
+//! Scheduled enforcement of retention settings
+//!
+//! Applies the `retention.toml` of every store the server serves at a fixed
+//! interval (see [`llm_config_core::RetentionConfig`]), and prunes the audit
+//! log by the `[audit]` settings of the store it belongs to. Whatever was
+//! removed is recorded as a `system_event` in the audit log.
+
+use crate::value_scan::ScanTargets;
+use llm_config_audit::{AuditEvent, AuditEventType, AuditLogger};
+use llm_config_core::{ConfigManager, RetentionReport};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Audit log pruned by the retention settings of a store
+pub type AuditTarget = (Arc<AuditLogger>, Arc<ConfigManager>);
+
+/// Apply the retention settings of the stores returned by `targets` every
+/// `interval`, starting now
+///
+/// In multi-tenant mode the audit log is shared by all tenants, so no store
+/// decides how long it is kept and `audit` is `None`.
+pub fn spawn_retention<F>(interval: Duration, audit: Option<AuditTarget>, targets: F)
+where
+    F: Fn() -> anyhow::Result<ScanTargets> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            let targets = match targets() {
+                Ok(targets) => targets,
+                Err(e) => {
+                    tracing::warn!("Retention failed: {}", e);
+                    continue;
+                }
+            };
+
+            for (tenant, manager) in targets {
+                let applied = tokio::task::spawn_blocking(move || manager.apply_retention())
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result.map_err(anyhow::Error::from));
+                match applied {
+                    Ok(report) if report == RetentionReport::default() => {}
+                    Ok(report) => {
+                        let message = format!(
+                            "Pruned {} versions and {} deleted config histories",
+                            report.pruned_versions, report.purged_histories
+                        );
+                        tracing::info!("{}", message);
+                        if let Some((logger, _)) = &audit {
+                            record(logger, message, tenant.as_deref());
+                        }
+                    }
+                    Err(e) => tracing::warn!("Retention failed: {}", e),
+                }
+            }
+
+            if let Some((logger, manager)) = &audit {
+                let (logger, manager) = (Arc::clone(logger), Arc::clone(manager));
+                let pruned = tokio::task::spawn_blocking(move || -> anyhow::Result<usize> {
+                    let pruned = logger.apply_retention(&manager.retention()?.audit)?;
+                    if pruned > 0 {
+                        let message = format!("Pruned {} audit events", pruned);
+                        tracing::info!("{}", message);
+                        record(&logger, message, None);
+                    }
+                    Ok(pruned)
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+                if let Err(e) = pruned {
+                    tracing::warn!("Audit log retention failed: {}", e);
+                }
+            }
+        }
+    });
+}
+
+fn record(logger: &AuditLogger, message: String, tenant: Option<&str>) {
+    let event = AuditEvent::new(
+        AuditEventType::SystemEvent {
+            component: "retention".to_string(),
+            message,
+        },
+        "system",
+    );
+    let event = match tenant {
+        Some(tenant) => event.with_tenant(tenant),
+        None => event,
+    };
+    if let Err(e) = logger.log(event) {
+        tracing::warn!("Failed to audit retention: {}", e);
+    }
+}
@@ -1,41 +1,210 @@
 //! REST API routes
 
+use crate::middleware::SecurityState;
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
-    Json,
+    Extension, Json,
 };
-use llm_config_core::{ConfigEntry, ConfigManager, ConfigValue, Environment};
+use llm_config_audit::{AuditEvent, AuditEventType, AuditFilter, AuditLogger, AuditSeverity};
+use llm_config_cache::CacheManager;
+use llm_config_core::{
+    parse_since, AppliedBlueprint, AsyncConfigManager, Blueprint, BlueprintEntry, CheckedEntry, ConfigEntry, ConfigManager, ConfigValue, Environment,
+    EnvironmentGraph, HistoryOptions, ListOptions, ListSort, OperationContext, Plan, PlannedChange, PromoteKeys, RenderedTemplate, SetOptions,
+    StoredBlueprint, StoredTemplate,
+    TenantId, TraceStep, TypeWarning, ValueDiff, ValueSource, ValueType, VersionEntry, ENVIRONMENTS, READ_ONLY_MODE, TEMPLATE_NAMESPACE, BLUEPRINT_NAMESPACE,
+};
+use llm_config_templates::{ConfigSource, Reference, ReferenceKind, Template, TemplateError};
+use llm_config_metrics::MetricsRegistry;
+use llm_config_rbac::{
+    Action, Elevation, Group, GroupRole, Permission, RbacError, Resource, RoleDefinition, RoleStore,
+};
+use llm_config_security::{SecretAccessLimiter, SecurityContext, WorkloadIdentity};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use utoipa::{IntoParams, ToSchema};
 
 /// API state shared across handlers
 #[derive(Clone)]
 pub struct ApiState {
     pub manager: Arc<ConfigManager>,
+    /// Async view of `manager` used by handlers so storage IO does not block the runtime
+    pub configs: AsyncConfigManager,
+    pub audit_logger: Option<Arc<AuditLogger>>,
+    pub rbac: Arc<RwLock<llm_config_rbac::PolicyEnforcer>>,
+    pub role_store: Option<RoleStore>,
+    /// Whether handlers check the caller's RBAC permissions
+    pub enforce_rbac: bool,
+    /// Registry counting config operations and permission checks
+    pub metrics: Option<Arc<MetricsRegistry>>,
+    /// Security middleware state managed through the admin API
+    pub security: Option<SecurityState>,
+    /// Per-user limit of revealed secrets
+    pub secret_access: Option<Arc<SecretAccessLimiter>>,
+    /// Whether secrets may be revealed at all
+    pub allow_secret_reveal: bool,
+    /// Whether revealing secrets needs a `reason`
+    pub require_secret_reason: bool,
+    /// Cache single config reads are served from
+    pub cache: Option<Arc<CacheManager>>,
+}
+
+impl ApiState {
+    /// Create API state without audit logging or persisted roles
+    pub fn new(manager: Arc<ConfigManager>) -> Self {
+        Self {
+            configs: AsyncConfigManager::new(Arc::clone(&manager)),
+            manager,
+            audit_logger: None,
+            rbac: Arc::new(RwLock::new(llm_config_rbac::PolicyEnforcer::new())),
+            role_store: None,
+            enforce_rbac: false,
+            metrics: None,
+            security: None,
+            secret_access: None,
+            allow_secret_reveal: false,
+            require_secret_reason: false,
+            cache: None,
+        }
+    }
+
+    /// Load custom roles and role assignments from the given store and persist role changes to it
+    pub fn with_role_store(mut self, store: RoleStore) -> llm_config_rbac::Result<Self> {
+        {
+            let mut rbac = self.rbac.write().unwrap();
+            rbac.load_role_definitions(&store)?;
+            rbac.load_assignments(&store)?;
+            rbac.load_groups(&store)?;
+        }
+        self.role_store = Some(store);
+        Ok(self)
+    }
+
+    /// Require callers to hold an RBAC permission for every request
+    pub fn with_rbac_enforcement(mut self, enforce: bool) -> Self {
+        self.enforce_rbac = enforce;
+        self
+    }
+
+    /// Record secret reads and other auditable events with the given logger
+    pub fn with_audit_logger(mut self, logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(logger);
+        self
+    }
+
+    /// Record config operations, RBAC checks and decision cache hits in the given registry
+    ///
+    /// The router built from this state also serves the registry at `/metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<MetricsRegistry>) -> Self {
+        self.configs = self.configs.with_metrics(metrics.clone());
+        self.rbac.write().unwrap().set_metrics(Arc::clone(&metrics));
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Manage bans, rate limits and the security policy of the given
+    /// middleware state through `/api/v1/admin/security`
+    pub fn with_security_state(mut self, security: SecurityState) -> Self {
+        self.security = Some(security);
+        self
+    }
+
+    /// Limit how many secrets each user may reveal
+    ///
+    /// Reveals over the limit are refused with 429 and recorded as Critical
+    /// security events.
+    pub fn with_secret_access_limiter(mut self, limiter: Arc<SecretAccessLimiter>) -> Self {
+        self.secret_access = Some(limiter);
+        self
+    }
+
+    /// Allow callers with the reveal permission to decrypt secrets
+    ///
+    /// Without it, requests with `reveal` and templates referencing secrets
+    /// are refused with 403, whether or not RBAC is enforced.
+    pub fn with_secret_reveal(mut self, allow: bool) -> Self {
+        self.allow_secret_reveal = allow;
+        self
+    }
+
+    /// Require a `reason` query parameter (recorded in the audit log) for
+    /// every request that reveals secrets
+    pub fn with_required_secret_reason(mut self, required: bool) -> Self {
+        self.require_secret_reason = required;
+        self
+    }
+
+    /// Serve single config reads from `cache`, managed through
+    /// `/api/v1/admin/cache`
+    ///
+    /// The cache must be kept consistent with writes, e.g. with
+    /// [`CacheManager::subscribe_invalidations`]. Reads served from it are
+    /// counted as accesses but carry no type warning.
+    pub fn with_cache(mut self, cache: Arc<CacheManager>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 }
 
 /// Standard API error response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
     pub message: String,
+    /// Machine-readable error code for errors clients are expected to handle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
 }
 
+/// Error code returned when a write would implicitly change a value's type
+pub const TYPE_CHANGE_ERROR_CODE: &str = "TYPE_CHANGE_NOT_ALLOWED";
+
+/// Error code returned when an environment's protection level rejects a write
+pub const PROTECTED_ERROR_CODE: &str = "ENVIRONMENT_PROTECTED";
+
+/// Error code returned when a write's expected version is no longer current
+pub const VERSION_CONFLICT_ERROR_CODE: &str = "VERSION_CONFLICT";
+
+/// Error code returned when an `If-Match` header does not match the current entry
+pub const PRECONDITION_FAILED_ERROR_CODE: &str = "PRECONDITION_FAILED";
+
+/// Error code returned when the target of a create (e.g. a namespace clone) is taken
+pub const ALREADY_EXISTS_ERROR_CODE: &str = "ALREADY_EXISTS";
+
+/// Error code returned for writes while the store is in read-only mode
+pub const READ_ONLY_ERROR_CODE: &str = "READ_ONLY";
+
+/// Error code returned when a user reveals more secrets than their limit allows
+pub const SECRET_ACCESS_LIMITED_ERROR_CODE: &str = "SECRET_ACCESS_LIMITED";
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
-            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+        let (status, error_message, code) = match self {
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, None),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg, None),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg, None),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg, None),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, None),
+            ApiError::TypeChange(msg) => (StatusCode::CONFLICT, msg, Some(TYPE_CHANGE_ERROR_CODE)),
+            ApiError::Protected(msg) => (StatusCode::FORBIDDEN, msg, Some(PROTECTED_ERROR_CODE)),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg, Some(VERSION_CONFLICT_ERROR_CODE)),
+            ApiError::PreconditionFailed(msg) => {
+                (StatusCode::PRECONDITION_FAILED, msg, Some(PRECONDITION_FAILED_ERROR_CODE))
+            }
+            ApiError::AlreadyExists(msg) => (StatusCode::CONFLICT, msg, Some(ALREADY_EXISTS_ERROR_CODE)),
+            ApiError::ReadOnly(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg, Some(READ_ONLY_ERROR_CODE)),
+            ApiError::SecretAccessLimited(msg) => {
+                (StatusCode::TOO_MANY_REQUESTS, msg, Some(SECRET_ACCESS_LIMITED_ERROR_CODE))
+            }
         };
 
         let body = Json(ErrorResponse {
             error: status.canonical_reason().unwrap_or("Unknown").to_string(),
             message: error_message,
+            code: code.map(str::to_string),
         });
 
         (status, body).into_response()
@@ -49,33 +218,303 @@ pub enum ApiError {
     BadRequest(String),
     InternalError(String),
     Unauthorized(String),
+    Forbidden(String),
+    /// Implicit change of a value's type (409, see [`TYPE_CHANGE_ERROR_CODE`])
+    TypeChange(String),
+    /// Write rejected by an environment's protection level (403, see [`PROTECTED_ERROR_CODE`])
+    Protected(String),
+    /// Write based on a stale version (409, see [`VERSION_CONFLICT_ERROR_CODE`])
+    Conflict(String),
+    /// Stale `If-Match` header (412, see [`PRECONDITION_FAILED_ERROR_CODE`])
+    PreconditionFailed(String),
+    /// Target of a create is taken (409, see [`ALREADY_EXISTS_ERROR_CODE`])
+    AlreadyExists(String),
+    /// Write while the store is read-only (503, see [`READ_ONLY_ERROR_CODE`])
+    ReadOnly(String),
+    /// Secret reveal over the user's limit (429, see [`SECRET_ACCESS_LIMITED_ERROR_CODE`])
+    SecretAccessLimited(String),
 }
 
 impl From<llm_config_core::ConfigError> for ApiError {
     fn from(err: llm_config_core::ConfigError) -> Self {
-        ApiError::InternalError(err.to_string())
+        match err {
+            llm_config_core::ConfigError::TypeChange(_) => ApiError::TypeChange(err.to_string()),
+            llm_config_core::ConfigError::Protected(_) => ApiError::Protected(err.to_string()),
+            llm_config_core::ConfigError::Conflict(_) => ApiError::Conflict(err.to_string()),
+            llm_config_core::ConfigError::NotFound(_) => ApiError::NotFound(err.to_string()),
+            llm_config_core::ConfigError::AlreadyExists(_) => ApiError::AlreadyExists(err.to_string()),
+            llm_config_core::ConfigError::InvalidOperation(ref msg) if msg == READ_ONLY_MODE => {
+                ApiError::ReadOnly(err.to_string())
+            }
+            llm_config_core::ConfigError::TemplateError(TemplateError::AccessDenied(_)) => {
+                ApiError::Forbidden(err.to_string())
+            }
+            llm_config_core::ConfigError::ValidationError(_)
+            | llm_config_core::ConfigError::TemplateError(_) => ApiError::BadRequest(err.to_string()),
+            _ => ApiError::InternalError(err.to_string()),
+        }
+    }
+}
+
+impl From<RbacError> for ApiError {
+    fn from(err: RbacError) -> Self {
+        match err {
+            RbacError::AccessDenied(msg) => ApiError::Forbidden(msg),
+            RbacError::InvalidRole(_)
+            | RbacError::InvalidPermission(_)
+            | RbacError::InvalidElevation(_) => ApiError::BadRequest(err.to_string()),
+            RbacError::UserNotFound(_) => ApiError::NotFound(err.to_string()),
+            RbacError::StorageError(_) => ApiError::InternalError(err.to_string()),
+        }
     }
 }
 
 /// Query parameters for get config
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GetConfigQuery {
+    /// Environment (default `development`)
     #[serde(default)]
     env: Option<String>,
+    /// Reserved for future use
     #[serde(default)]
     #[allow(dead_code)] // Reserved for future use
     with_overrides: bool,
+    /// Decrypt secrets (requires the secret reveal permission)
+    #[serde(default)]
+    reveal: bool,
+    /// Why secrets are revealed, recorded in the audit log (required with
+    /// `reveal` when the server requires reasons)
+    reason: Option<String>,
+}
+
+/// Query parameters for list configs
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListConfigsQuery {
+    /// Environment (default `development`)
+    #[serde(default)]
+    env: Option<String>,
+    /// Decrypt secrets (requires the secret reveal permission)
+    #[serde(default)]
+    reveal: bool,
+    /// Why secrets are revealed, recorded in the audit log (required with
+    /// `reveal` when the server requires reasons)
+    reason: Option<String>,
+    /// Return at most this many configurations
+    limit: Option<usize>,
+    /// Skip this many configurations (after `page_token`)
+    #[serde(default)]
+    offset: usize,
+    /// Continue after the page whose `X-Next-Page-Token` this is
+    page_token: Option<String>,
+    /// `key` (default), `updated_at` or `version`; ties are ordered by key
+    sort_by: Option<String>,
+    /// Reverse the order
+    #[serde(default)]
+    descending: bool,
+    /// Only list keys starting with this prefix
+    key_prefix: Option<String>,
+    /// Only list configurations with this tag
+    tag: Option<String>,
 }
 
+/// Response header with the number of configurations matching a list's filters
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Response header with the `page_token` of the next page of a list
+pub const NEXT_PAGE_TOKEN_HEADER: &str = "x-next-page-token";
+
 /// Request body for set config
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SetConfigRequest {
+    /// New value (a string when `secret` is set)
     pub value: serde_json::Value,
+    /// Environment
+    #[schema(example = "production")]
     pub env: String,
     #[serde(default = "default_user")]
     pub user: String,
     #[serde(default)]
     pub secret: bool,
+    /// Parse the string `value` as a `duration` (e.g. `5m`), byte `size`
+    /// (e.g. `512MiB`) or `url`, rejecting invalid values
+    #[serde(default, rename = "type")]
+    #[schema(example = "duration")]
+    pub value_type: Option<String>,
+    /// How the value combines with inherited values: `replace` or
+    /// `deep-merge` (object fields merged recursively)
+    #[serde(default)]
+    #[schema(example = "deep-merge")]
+    pub merge: Option<String>,
+    /// Allow replacing the value with one of a different type
+    #[serde(default)]
+    pub allow_type_change: bool,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+    /// Description recorded in the version history
+    #[serde(default)]
+    pub change_description: Option<String>,
+    /// Only write if the stored version is still this one (0: the key must not exist yet)
+    #[serde(default)]
+    pub expected_version: Option<u64>,
+}
+
+/// Maximum number of keys in a single batch get
+pub const MAX_BATCH_KEYS: usize = 100;
+
+/// Request body for batch get
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchGetRequest {
+    /// Keys to get, at most [`MAX_BATCH_KEYS`]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub env: Option<String>,
+    /// Decrypt secrets (requires the secret reveal permission)
+    #[serde(default)]
+    pub reveal: bool,
+    /// Why secrets are revealed, recorded in the audit log
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Outcome of a single key in a batch get
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchGetStatus {
+    Found,
+    NotFound,
+    /// The key holds a secret the caller may not read
+    Forbidden,
+}
+
+/// Result for a single key in a batch get
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchGetResult {
+    pub key: String,
+    pub status: BatchGetStatus,
+    pub config: Option<ConfigResponse>,
+}
+
+/// Response for batch get, one result per requested key in request order
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchGetResponse {
+    pub namespace: String,
+    pub environment: String,
+    pub results: Vec<BatchGetResult>,
+}
+
+/// Resolved configuration of a namespace with environment overrides applied
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ResolvedConfigResponse {
+    pub namespace: String,
+    pub environment: String,
+    /// Resolved value per key, sorted by key
+    #[schema(value_type = Object)]
+    pub values: serde_json::Map<String, serde_json::Value>,
+    /// Namespaces keys are looked up in, from the requested one to its most
+    /// distant parent; the first that defines a key supplies its value
+    pub lineage: Vec<String>,
+    /// Namespace, environment and version each value came from, in the
+    /// order they were applied (several when overrides were deep-merged)
+    #[schema(value_type = Object)]
+    pub sources: BTreeMap<String, Vec<ValueSource>>,
+}
+
+/// How a configuration value was resolved
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExplainResponse {
+    pub namespace: String,
+    pub key: String,
+    pub environment: String,
+    /// Namespaces the key may come from, from the requested one to its most
+    /// distant parent
+    pub namespaces: Vec<String>,
+    /// Environments consulted in each namespace, from most general to most specific
+    pub environments: Vec<String>,
+    /// Every namespace and environment consulted, in order, with the version
+    /// found there and how it was merged (`set`, `replace` or `deep-merge`)
+    #[schema(value_type = Vec<Object>)]
+    pub steps: Vec<TraceStep>,
+    /// Entries the value was built from, in the order they were applied
+    #[schema(value_type = Vec<Object>)]
+    pub sources: Vec<ValueSource>,
+    /// Most specific entry the value came from (`null` if the key is not defined)
+    #[schema(value_type = Option<Object>)]
+    pub source: Option<ValueSource>,
+    /// Resolved value (`null` if the key is not defined)
+    #[schema(value_type = Option<Object>)]
+    pub value: Option<serde_json::Value>,
+    /// Whether the value is a secret
+    pub secret: bool,
+    /// Whether the secret was decrypted (with `reveal`)
+    pub decrypted: bool,
+}
+
+/// Request body for updating tags and description
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateMetadataRequest {
+    #[serde(default)]
+    pub env: Option<String>,
+    #[serde(default = "default_user")]
+    pub user: String,
+    /// Replacement tags (left unchanged when omitted)
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Replacement description (left unchanged when omitted, cleared when empty)
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+}
+
+/// Query parameters for delete config
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteConfigQuery {
+    /// Environment (default `development`)
+    #[serde(default)]
+    env: Option<String>,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    reviewed_by: Option<String>,
+    /// Check the delete and return the change without applying it
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Query parameters for setting a configuration
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SetConfigQuery {
+    /// Check the write and return the change without applying it
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Result of a write made with `dry_run=true`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DryRunResponse {
+    /// Version the config would be at (`null` for deletes)
+    pub version: Option<u64>,
+    /// The change the write would make (secrets masked)
+    #[schema(value_type = Object)]
+    pub plan: Plan,
+}
+
+impl DryRunResponse {
+    fn new(operation: &str, current: Option<&ConfigEntry>, result: Option<&ConfigEntry>) -> Self {
+        let plan = Plan::new(operation);
+        Self {
+            version: result.map(|entry| entry.version),
+            plan: match PlannedChange::between(current, result) {
+                Some(change) => plan.with_change(change),
+                None => plan,
+            },
+        }
+    }
 }
 
 fn default_user() -> String {
@@ -83,18 +522,49 @@ fn default_user() -> String {
 }
 
 /// Response for config operations
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ConfigResponse {
     pub id: String,
     pub namespace: String,
     pub key: String,
+    /// The value; secrets are masked unless revealed
     pub value: serde_json::Value,
+    /// Type of a typed value (`duration`, `size` or `url`), whose `value` is
+    /// its text
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<String>,
     pub environment: String,
     pub version: u64,
     pub metadata: ConfigMetadataResponse,
+    /// Set by `GET` if the value's type differs from its declared type or
+    /// the type of its previous version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_warning: Option<TypeWarningResponse>,
 }
 
-#[derive(Debug, Serialize)]
+/// A value read with a different type than expected
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TypeWarningResponse {
+    pub expected: String,
+    pub actual: String,
+    pub message: String,
+    /// The value converted to the expected type, if it converts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coerced: Option<serde_json::Value>,
+}
+
+impl From<TypeWarning> for TypeWarningResponse {
+    fn from(warning: TypeWarning) -> Self {
+        Self {
+            message: warning.to_string(),
+            coerced: warning.coerced.as_ref().map(config_value_to_json),
+            expected: warning.expected,
+            actual: warning.actual,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ConfigMetadataResponse {
     pub created_at: String,
     pub created_by: String,
@@ -111,6 +581,10 @@ impl From<ConfigEntry> for ConfigResponse {
             namespace: entry.namespace,
             key: entry.key,
             value: config_value_to_json(&entry.value),
+            value_type: entry
+                .value
+                .typed_text()
+                .map(|_| entry.value.type_name().to_string()),
             environment: entry.environment.to_string(),
             version: entry.version,
             metadata: ConfigMetadataResponse {
@@ -121,6 +595,7 @@ impl From<ConfigEntry> for ConfigResponse {
                 tags: entry.metadata.tags,
                 description: entry.metadata.description,
             },
+            type_warning: None,
         }
     }
 }
@@ -143,6 +618,9 @@ fn config_value_to_json(value: &ConfigValue) -> serde_json::Value {
                 .collect();
             serde_json::Value::Object(obj.into_iter().collect())
         }
+        ConfigValue::Duration(_) | ConfigValue::Size(_) | ConfigValue::Url(_) => {
+            serde_json::Value::String(value.typed_text().unwrap_or_default())
+        }
         ConfigValue::Secret(_) => serde_json::Value::String("<encrypted>".to_string()),
     }
 }
@@ -175,21 +653,438 @@ fn json_to_config_value(value: &serde_json::Value) -> Result<ConfigValue, ApiErr
     })
 }
 
-/// GET /health - Health check endpoint
-pub async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "service": "llm-config-manager",
-        "version": env!("CARGO_PKG_VERSION")
+/// Reject namespaces outside the calling workload's scope
+fn check_workload_scope(
+    workload: &Option<Extension<WorkloadIdentity>>,
+    namespace: &str,
+) -> Result<(), ApiError> {
+    if let Some(Extension(identity)) = workload {
+        identity
+            .check_namespace(namespace)
+            .map_err(|e| ApiError::Forbidden(e.public_message()))?;
+    }
+    Ok(())
+}
+
+/// Principal a write is attributed to
+///
+/// Workloads always act as their mapped principal, RBAC-checked callers as
+/// themselves; otherwise the user named in the request is trusted.
+fn acting_user(
+    state: &ApiState,
+    workload: &Option<Extension<WorkloadIdentity>>,
+    context: &Option<Extension<SecurityContext>>,
+    requested: String,
+) -> String {
+    match (workload, context) {
+        (Some(Extension(identity)), _) => identity.principal.clone(),
+        (None, Some(Extension(context))) if state.enforce_rbac => context.user_id.clone(),
+        _ => requested,
+    }
+}
+
+/// Write options carrying an optional reviewer and the caller's request context
+fn write_options(reviewed_by: Option<String>, context: &Option<Extension<SecurityContext>>) -> SetOptions {
+    let mut options = SetOptions::default();
+    if let Some(reviewer) = reviewed_by {
+        options = options.with_reviewed_by(reviewer);
+    }
+    if let Some(Extension(context)) = context {
+        options = options.with_context(operation_context(context));
+    }
+    options
+}
+
+/// Request context of the caller, recorded with writes and audit events
+fn operation_context(context: &SecurityContext) -> OperationContext {
+    let mut operation = OperationContext::default().with_ip_address(context.ip_address.clone());
+    operation.session_id = context.session_id.clone();
+    operation.request_id = context.metadata.get("request_id").cloned();
+    operation.spiffe_id = context.metadata.get("spiffe_id").cloned();
+    operation
+}
+
+/// Check the caller's RBAC permission in a namespace when enforcement is enabled
+pub(crate) fn authorize(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    resource: Resource,
+    action: Action,
+    namespace: Option<&str>,
+) -> Result<(), ApiError> {
+    if !state.enforce_rbac {
+        return Ok(());
+    }
+    check_permission(state, context, resource, action, namespace)
+}
+
+/// Check the caller's permission to manage roles and groups
+///
+/// Unlike [`authorize`], this is checked even when RBAC is not enforced:
+/// whoever can define roles can grant themselves anything.
+fn authorize_role_management(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    action: Action,
+) -> Result<(), ApiError> {
+    check_permission(state, context, Resource::Roles, action, None)
+}
+
+/// Check the caller's RBAC permission in a namespace
+fn check_permission(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    resource: Resource,
+    action: Action,
+    namespace: Option<&str>,
+) -> Result<(), ApiError> {
+    let Some(Extension(context)) = context else {
+        return Err(ApiError::Unauthorized("Missing security context".to_string()));
+    };
+
+    let groups = external_groups(context);
+    let (result, elevation) = {
+        let rbac = state.rbac.read().unwrap();
+        let result =
+            rbac.check_permission_as(&context.user_id, &groups, &resource, &action, namespace);
+        let elevation = result
+            .is_ok()
+            .then(|| {
+                rbac.granting_elevation(&context.user_id, &groups, &resource, &action, namespace)
+                    .cloned()
+            })
+            .flatten();
+        (result, elevation)
+    };
+    if let Some(elevation) = elevation {
+        audit_elevated_access(state, context, &resource, &action, namespace, &elevation);
+    }
+    if let Some(metrics) = &state.metrics {
+        metrics.rbac().record_permission_check(
+            &resource.to_string(),
+            &action.to_string(),
+            result.is_ok(),
+        );
+    }
+    result?;
+    Ok(())
+}
+
+/// External identity groups of the caller, as forwarded in `x-user-groups`
+/// by a trusted proxy
+fn external_groups(context: &SecurityContext) -> Vec<String> {
+    context
+        .metadata
+        .get("groups")
+        .map(|groups| {
+            groups
+                .split(',')
+                .map(str::trim)
+                .filter(|group| !group.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Record a request allowed only through a temporary role elevation
+fn audit_elevated_access(
+    state: &ApiState,
+    context: &SecurityContext,
+    resource: &Resource,
+    action: &Action,
+    namespace: Option<&str>,
+    elevation: &Elevation,
+) {
+    let Some(logger) = &state.audit_logger else {
+        return;
+    };
+
+    let permission = format!("{}:{}", resource, action);
+    let event_type = AuditEventType::SecurityEvent {
+        threat_type: "elevated_access".to_string(),
+        details: format!(
+            "{} used {} through a temporary {} role: {}",
+            context.user_id,
+            permission,
+            elevation.assignment.role,
+            elevation.justification
+        ),
+    };
+    let event = caller_event(event_type, &Some(Extension(context.clone())))
+        .with_severity(AuditSeverity::Critical)
+        .with_metadata("permission", permission)
+        .with_metadata("namespace", namespace.unwrap_or_default())
+        .with_metadata("elevation_role", elevation.assignment.role.to_string())
+        .with_metadata("justification", &elevation.justification)
+        .with_metadata("expires_at", elevation.expires_at.to_rfc3339());
+    if let Err(e) = logger.log(event) {
+        tracing::error!("Failed to record elevated access: {}", e);
+    }
+}
+
+/// Record a secret read, attributed to the caller's principal and workload identity
+///
+/// Reads that return the decrypted plaintext are recorded as `SecretRevealed`.
+fn audit_secret_read(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    namespace: &str,
+    key: &str,
+    env: Environment,
+    revealed: bool,
+    reason: Option<&str>,
+) {
+    let Some(logger) = &state.audit_logger else {
+        return;
+    };
+
+    let (namespace, key, environment) = (namespace.to_string(), key.to_string(), env.to_string());
+    let event_type = if revealed {
+        AuditEventType::SecretRevealed {
+            namespace,
+            key,
+            environment,
+        }
+    } else {
+        AuditEventType::SecretAccessed {
+            namespace,
+            key,
+            environment,
+        }
+    };
+
+    let mut event = caller_event(event_type, context);
+    if let Some(reason) = reason {
+        event = event.with_metadata("reason", reason);
+    }
+    if let Err(e) = logger.log(event) {
+        tracing::error!("Failed to record secret access: {}", e);
+    }
+}
+
+/// The reason given for revealing secrets
+///
+/// Fails if the server requires a reason and none (or a blank one) is given.
+fn reveal_reason<'a>(state: &ApiState, reveal: bool, reason: Option<&'a str>) -> Result<Option<&'a str>, ApiError> {
+    let reason = reason.map(str::trim).filter(|reason| !reason.is_empty());
+    if reveal && reason.is_none() && state.require_secret_reason {
+        return Err(ApiError::BadRequest("A reason is required to reveal secrets".to_string()));
+    }
+    Ok(reason)
+}
+
+/// Check that secrets may be revealed and the caller may reveal them in a namespace
+fn authorize_reveal(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    namespace: &str,
+) -> Result<(), ApiError> {
+    if !state.allow_secret_reveal {
+        return Err(ApiError::Forbidden("Revealing secrets is not enabled".to_string()));
+    }
+    authorize(state, context, Resource::Secret, Action::Reveal, Some(namespace))
+}
+
+/// Count a secret reveal against the caller's secret access limit
+///
+/// Callers are counted by their verified principal (client certificate or
+/// workload identity), otherwise by client IP, since `x-user-id` can name
+/// anyone. Reveals over the limit are refused and recorded as a Critical
+/// `secret_access_rate_exceeded` security event.
+fn limit_secret_reveal(
+    state: &ApiState,
+    context: &Option<Extension<SecurityContext>>,
+    namespace: &str,
+    key: &str,
+) -> Result<(), ApiError> {
+    let Some(limiter) = &state.secret_access else {
+        return Ok(());
+    };
+    let caller = match context {
+        Some(Extension(context)) => match context.metadata.get("verified_principal") {
+            Some(principal) => principal.clone(),
+            None => format!("ip:{}", context.ip_address),
+        },
+        None => "anonymous".to_string(),
+    };
+    let Err(e) = limiter.check(&caller) else {
+        return Ok(());
+    };
+
+    let denied = limiter.denied(&caller);
+    tracing::error!(
+        "Refused to reveal secret {}:{} to {}: over the secret access limit ({} reveals refused)",
+        namespace,
+        key,
+        caller,
+        denied
+    );
+    if let Some(logger) = &state.audit_logger {
+        let event_type = AuditEventType::SecurityEvent {
+            threat_type: "secret_access_rate_exceeded".to_string(),
+            details: format!("{} revealed secrets faster than {} per minute", caller, limiter.limit().reads_per_minute),
+        };
+        let event = caller_event(event_type, context)
+            .with_severity(AuditSeverity::Critical)
+            .with_metadata("namespace", namespace)
+            .with_metadata("key", key)
+            .with_metadata("denied", denied.to_string());
+        if let Err(e) = logger.log(event) {
+            tracing::error!("Failed to record secret access limit: {}", e);
+        }
+    }
+    Err(ApiError::SecretAccessLimited(e.to_string()))
+}
+
+/// Audit event attributed to the caller's principal, request context and workload identity
+pub(crate) fn caller_event(event_type: AuditEventType, context: &Option<Extension<SecurityContext>>) -> AuditEvent {
+    match context {
+        Some(Extension(context)) => {
+            let event = AuditEvent::new(event_type, context.user_id.clone())
+                .with_context(&operation_context(context));
+            match context.metadata.get("tenant_id") {
+                Some(tenant) => event.with_tenant(tenant.clone()),
+                None => event,
+            }
+        }
+        None => AuditEvent::new(event_type, "anonymous"),
+    }
+}
+
+/// Strong ETag of an entry, derived from its id and version
+fn entry_etag(entry: &ConfigEntry) -> String {
+    format!("\"{}-v{}\"", entry.id, entry.version)
+}
+
+/// ETag of a list of entries, changing whenever any entry is added, removed or updated
+fn list_etag(entries: &[ConfigEntry], total: usize) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for entry in entries {
+        (entry.id, entry.version).hash(&mut hasher);
+    }
+    total.hash(&mut hasher);
+    format!("\"{:016x}-{}\"", hasher.finish(), entries.len())
+}
+
+/// Whether a conditional header (`If-Match`/`If-None-Match`) lists `etag` or `*`
+///
+/// Weak validators (`W/"..."`) compare by their opaque tag.
+fn etag_matches(headers: &HeaderMap, name: header::HeaderName, etag: &str) -> Option<bool> {
+    let value = headers.get(name)?.to_str().unwrap_or_default();
+    Some(value.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
     }))
 }
 
+/// Reject a write whose `If-Match` header does not match the current entry
+fn check_if_match(headers: &HeaderMap, current: Option<&ConfigEntry>) -> Result<(), ApiError> {
+    let matched = match current {
+        Some(entry) => etag_matches(headers, header::IF_MATCH, &entry_etag(entry)),
+        // Nothing matches a missing entry, not even "*"
+        None => headers.contains_key(header::IF_MATCH).then_some(false),
+    };
+
+    match matched {
+        Some(false) => Err(ApiError::PreconditionFailed(
+            "Configuration was modified since it was read (If-Match does not match)".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Respond with `body` and its ETag, or `304 Not Modified` if `If-None-Match` lists it
+fn conditional_response(headers: &HeaderMap, etag: String, body: impl IntoResponse) -> Response {
+    let etag_header = [(header::ETAG, HeaderValue::from_str(&etag).expect("ETags are valid header values"))];
+    if etag_matches(headers, header::IF_NONE_MATCH, &etag) == Some(true) {
+        (StatusCode::NOT_MODIFIED, etag_header).into_response()
+    } else {
+        (etag_header, body).into_response()
+    }
+}
+
+/// Response of the health check
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthResponse {
+    #[schema(example = "healthy")]
+    pub status: String,
+    pub service: String,
+    pub version: String,
+    /// Whether the store accepts writes (`read-write` or `read-only`; not
+    /// reported by multi-tenant servers)
+    #[schema(example = "read-write")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Why the store is read-only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode_reason: Option<String>,
+}
+
+impl HealthResponse {
+    fn healthy() -> Self {
+        Self {
+            status: "healthy".to_string(),
+            service: "llm-config-manager".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            mode: None,
+            mode_reason: None,
+        }
+    }
+}
+
+/// GET /health - Health check endpoint of a multi-tenant server
+pub async fn health_check() -> Json<HealthResponse> {
+    Json(HealthResponse::healthy())
+}
+
+/// GET /health - Health check endpoint, reporting whether the store is read-only
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "The server is up", body = HealthResponse))
+)]
+pub async fn store_health_check(State(state): State<ApiState>) -> Json<HealthResponse> {
+    let mut health = HealthResponse::healthy();
+    match state.configs.read_only_mode().await {
+        Ok(Some(mode)) => {
+            health.mode = Some("read-only".to_string());
+            health.mode_reason = mode.reason;
+        }
+        Ok(None) => health.mode = Some("read-write".to_string()),
+        Err(e) => tracing::warn!("Failed to read the read-only mode: {}", e),
+    }
+    Json(health)
+}
+
 /// GET /api/v1/configs/:namespace/:key - Get a configuration value
+///
+/// Responses carry an ETag; `If-None-Match` returns `304 Not Modified`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/configs/{namespace}/{key}",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), ("key" = String, Path, description = "Config key"), GetConfigQuery),
+    responses(
+        (status = 200, description = "The configuration", body = ConfigResponse),
+        (status = 304, description = "Not modified since the ETag in `If-None-Match`"),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+        (status = 404, description = "Configuration not found", body = ErrorResponse),
+    )
+)]
 pub async fn get_config(
     State(state): State<ApiState>,
     Path((namespace, key)): Path<(String, String)>,
     Query(params): Query<GetConfigQuery>,
-) -> Result<Json<ConfigResponse>, ApiError> {
+    headers: HeaderMap,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Response, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
     let env: Environment = params
         .env
         .as_deref()
@@ -197,48 +1092,185 @@ pub async fn get_config(
         .parse()
         .map_err(|e| ApiError::BadRequest(e))?;
 
-    let entry = state
-        .manager
-        .get(&namespace, &key, env)?
+    let CheckedEntry { mut entry, warning } = get_cached(&state, &namespace, &key, env)
+        .await?
         .ok_or_else(|| ApiError::NotFound(format!("Configuration not found: {}:{}", namespace, key)))?;
 
-    Ok(Json(entry.into()))
+    if entry.value.is_secret() {
+        authorize(&state, &context, Resource::Secret, Action::Read, Some(&namespace))?;
+        let reason = reveal_reason(&state, params.reveal, params.reason.as_deref())?;
+        if params.reveal {
+            authorize_reveal(&state, &context, &namespace)?;
+            limit_secret_reveal(&state, &context, &namespace, &key)?;
+            entry.value = state.manager.reveal(entry.value)?;
+        }
+        audit_secret_read(&state, &context, &namespace, &key, env, params.reveal, reason);
+    } else {
+        authorize(&state, &context, Resource::Config, Action::Read, Some(&namespace))?;
+    }
+
+    let etag = entry_etag(&entry);
+    let mut response = ConfigResponse::from(entry);
+    response.type_warning = warning.map(TypeWarningResponse::from);
+    Ok(conditional_response(&headers, etag, Json(response)))
+}
+
+/// Read an entry through the server's cache, if it has one
+async fn get_cached(
+    state: &ApiState,
+    namespace: &str,
+    key: &str,
+    env: Environment,
+) -> Result<Option<CheckedEntry>, ApiError> {
+    let Some(cache) = &state.cache else {
+        return Ok(state.configs.get_checked(namespace, key, env).await?);
+    };
+    if let Ok(entry) = cache.get(namespace, key, &env.to_string()) {
+        state.manager.record_cached_read(&entry);
+        return Ok(Some(CheckedEntry { entry, warning: None }));
+    }
+
+    let checked = state.configs.get_checked(namespace, key, env).await?;
+    if let Some(checked) = &checked {
+        if let Err(e) = cache.put(checked.entry.clone()) {
+            tracing::warn!("Failed to cache {}:{}: {}", namespace, key, e);
+        }
+    }
+    Ok(checked)
 }
 
 /// POST /api/v1/configs/:namespace/:key - Set a configuration value
+///
+/// With `If-Match`, the write only succeeds if the entry still has that ETag.
+#[utoipa::path(
+    post,
+    path = "/api/v1/configs/{namespace}/{key}",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), ("key" = String, Path, description = "Config key"), SetConfigQuery),
+    request_body = SetConfigRequest,
+    responses(
+        (status = 200, description = "The stored configuration, or with `dry_run` the change it would make (`DryRunResponse`)", body = ConfigResponse),
+        (status = 400, description = "Invalid value or environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted, or rejected by the environment's protection (`ENVIRONMENT_PROTECTED`)", body = ErrorResponse),
+        (status = 409, description = "Type change (`TYPE_CHANGE_NOT_ALLOWED`) or stale `expected_version` (`VERSION_CONFLICT`)", body = ErrorResponse),
+        (status = 412, description = "Stale `If-Match` (`PRECONDITION_FAILED`)", body = ErrorResponse),
+    )
+)]
 pub async fn set_config(
     State(state): State<ApiState>,
     Path((namespace, key)): Path<(String, String)>,
+    Query(params): Query<SetConfigQuery>,
+    headers: HeaderMap,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
     Json(req): Json<SetConfigRequest>,
-) -> Result<Json<ConfigResponse>, ApiError> {
+) -> Result<Response, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let user = acting_user(&state, &workload, &context, req.user);
+
     let env: Environment = req
         .env
         .parse()
         .map_err(|e| ApiError::BadRequest(e))?;
 
+    let resource = if req.secret { Resource::Secret } else { Resource::Config };
+    let current = state.configs.get_untracked(&namespace, &key, env).await?;
+    let action = if current.is_some() {
+        Action::Update
+    } else {
+        Action::Create
+    };
+    authorize(&state, &context, resource, action, Some(&namespace))?;
+    check_if_match(&headers, current.as_ref())?;
+
+    let mut options = write_options(req.reviewed_by, &context)
+        .with_allow_type_change(req.allow_type_change)
+        .with_dry_run(params.dry_run);
+    options.change_description = req.change_description;
+    // The manager re-checks the version atomically with the write
+    options.expected_version = match (&current, headers.contains_key(header::IF_MATCH)) {
+        (Some(entry), true) => Some(entry.version),
+        _ => req.expected_version,
+    };
+    options.merge = req
+        .merge
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+    let value_type: Option<ValueType> = req
+        .value_type
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
     let entry = if req.secret {
+        if value_type.is_some() {
+            return Err(ApiError::BadRequest("Secrets cannot have a value type".to_string()));
+        }
         // Store as encrypted secret
         let value_str = req.value.as_str()
             .ok_or_else(|| ApiError::BadRequest("Secret value must be a string".to_string()))?;
         state
-            .manager
-            .set_secret(&namespace, &key, value_str.as_bytes(), env, &req.user)?
+            .configs
+            .set_secret_with_options(&namespace, &key, value_str.as_bytes().to_vec(), env, &user, &options)
+            .await?
     } else {
-        let config_value = json_to_config_value(&req.value)?;
+        let config_value = match value_type {
+            Some(value_type) => {
+                let text = req.value.as_str().ok_or_else(|| {
+                    ApiError::BadRequest(format!("A {} value must be a string", value_type))
+                })?;
+                value_type.parse(text).map_err(ApiError::BadRequest)?
+            }
+            None => json_to_config_value(&req.value)?,
+        };
         state
-            .manager
-            .set(&namespace, &key, config_value, env, &req.user)?
+            .configs
+            .set_with_options(&namespace, &key, config_value, env, &user, &options)
+            .await?
     };
 
-    Ok(Json(entry.into()))
+    if params.dry_run {
+        return Ok(Json(DryRunResponse::new("set", current.as_ref(), Some(&entry))).into_response());
+    }
+    let etag = HeaderValue::from_str(&entry_etag(&entry)).expect("ETags are valid header values");
+    Ok(([(header::ETAG, etag)], Json(ConfigResponse::from(entry))).into_response())
 }
 
 /// GET /api/v1/configs/:namespace - List configurations in a namespace
+///
+/// Configurations are sorted by key unless `sort_by` says otherwise. With
+/// `limit`, one page is returned; `X-Total-Count` holds the number of
+/// matching configurations and `X-Next-Page-Token` the token for the next
+/// page. Responses carry an ETag; `If-None-Match` returns `304 Not Modified`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/configs/{namespace}",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), ListConfigsQuery),
+    responses(
+        (status = 200, description = "Configurations in the namespace (secrets only for callers that may read them)", body = [ConfigResponse],
+            headers(
+                ("x-total-count" = usize, description = "Number of configurations matching the filters, across all pages"),
+                ("x-next-page-token" = String, description = "`page_token` of the next page, if there is one"),
+            )),
+        (status = 304, description = "Not modified since the ETag in `If-None-Match`"),
+        (status = 400, description = "Invalid environment, sort order or page token", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+    )
+)]
 pub async fn list_configs(
     State(state): State<ApiState>,
     Path(namespace): Path<String>,
-    Query(params): Query<GetConfigQuery>,
-) -> Result<Json<Vec<ConfigResponse>>, ApiError> {
+    Query(params): Query<ListConfigsQuery>,
+    headers: HeaderMap,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Response, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
     let env: Environment = params
         .env
         .as_deref()
@@ -246,40 +1278,446 @@ pub async fn list_configs(
         .parse()
         .map_err(|e| ApiError::BadRequest(e))?;
 
-    let entries = state.manager.list(&namespace, env)?;
-    let responses: Vec<ConfigResponse> = entries.into_iter().map(|e| e.into()).collect();
+    authorize(&state, &context, Resource::Config, Action::List, Some(&namespace))?;
+    let can_read_secrets =
+        authorize(&state, &context, Resource::Secret, Action::Read, Some(&namespace)).is_ok();
+    if params.reveal {
+        authorize_reveal(&state, &context, &namespace)?;
+    }
+    let reason = reveal_reason(&state, params.reveal, params.reason.as_deref())?;
 
-    Ok(Json(responses))
-}
+    let sort_by: ListSort = match params.sort_by.as_deref() {
+        Some(sort_by) => sort_by.parse().map_err(ApiError::BadRequest)?,
+        None => ListSort::default(),
+    };
+    let options = ListOptions {
+        limit: params.limit,
+        offset: params.offset,
+        page_token: params.page_token,
+        sort_by,
+        descending: params.descending,
+        key_prefix: params.key_prefix,
+        tag: params.tag,
+        // Secrets are omitted for callers that may not read them
+        exclude_secrets: !can_read_secrets,
+    };
+    let page = state.configs.list_with_options(&namespace, env, options).await?;
+    let etag = list_etag(&page.entries, page.total);
+
+    let mut page_headers = HeaderMap::new();
+    page_headers.insert(TOTAL_COUNT_HEADER, HeaderValue::from(page.total));
+    if let Some(token) = page.next_page_token {
+        let token = HeaderValue::from_str(&token)
+            .map_err(|_| ApiError::InternalError("Page token is not a valid header value".to_string()))?;
+        page_headers.insert(NEXT_PAGE_TOKEN_HEADER, token);
+    }
+
+    let mut responses = Vec::new();
+    for mut entry in page.entries {
+        if entry.value.is_secret() {
+            if params.reveal {
+                limit_secret_reveal(&state, &context, &namespace, &entry.key)?;
+                entry.value = state.manager.reveal(entry.value)?;
+            }
+            audit_secret_read(&state, &context, &namespace, &entry.key, env, params.reveal, reason);
+        }
+        responses.push(ConfigResponse::from(entry));
+    }
+
+    Ok(conditional_response(&headers, etag, (page_headers, Json(responses))))
+}
+
+/// POST /api/v1/configs/:namespace/batch-get - Get several configurations at once
+#[utoipa::path(
+    post,
+    path = "/api/v1/configs/{namespace}/batch-get",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace")),
+    request_body = BatchGetRequest,
+    responses(
+        (status = 200, description = "One result per requested key", body = BatchGetResponse),
+        (status = 400, description = "No keys, too many keys or invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+    )
+)]
+pub async fn batch_get_configs(
+    State(state): State<ApiState>,
+    Path(namespace): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<BatchGetRequest>,
+) -> Result<Json<BatchGetResponse>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let env: Environment = req
+        .env
+        .as_deref()
+        .unwrap_or("development")
+        .parse()
+        .map_err(ApiError::BadRequest)?;
+
+    if req.keys.is_empty() {
+        return Err(ApiError::BadRequest("At least one key is required".to_string()));
+    }
+    if req.keys.len() > MAX_BATCH_KEYS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} keys may be requested at once",
+            MAX_BATCH_KEYS
+        )));
+    }
+
+    authorize(&state, &context, Resource::Config, Action::Read, Some(&namespace))?;
+    let can_read_secrets =
+        authorize(&state, &context, Resource::Secret, Action::Read, Some(&namespace)).is_ok();
+    if req.reveal {
+        authorize_reveal(&state, &context, &namespace)?;
+    }
+    let reason = reveal_reason(&state, req.reveal, req.reason.as_deref())?;
+
+    let mut results = Vec::with_capacity(req.keys.len());
+    for (key, entry) in state.configs.get_many(&namespace, req.keys, env).await? {
+        let Some(mut entry) = entry else {
+            results.push(BatchGetResult {
+                key,
+                status: BatchGetStatus::NotFound,
+                config: None,
+            });
+            continue;
+        };
+
+        if entry.value.is_secret() {
+            if !can_read_secrets {
+                results.push(BatchGetResult {
+                    key,
+                    status: BatchGetStatus::Forbidden,
+                    config: None,
+                });
+                continue;
+            }
+            if req.reveal {
+                limit_secret_reveal(&state, &context, &namespace, &key)?;
+                entry.value = state.manager.reveal(entry.value)?;
+            }
+            audit_secret_read(&state, &context, &namespace, &key, env, req.reveal, reason);
+        }
+
+        results.push(BatchGetResult {
+            key,
+            status: BatchGetStatus::Found,
+            config: Some(entry.into()),
+        });
+    }
+
+    Ok(Json(BatchGetResponse {
+        namespace,
+        environment: env.to_string(),
+        results,
+    }))
+}
+
+/// GET /api/v1/configs/:namespace/resolved - Resolve a namespace with environment overrides
+#[utoipa::path(
+    get,
+    path = "/api/v1/configs/{namespace}/resolved",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), GetConfigQuery),
+    responses(
+        (status = 200, description = "Resolved values", body = ResolvedConfigResponse),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+    )
+)]
+pub async fn resolve_configs(
+    State(state): State<ApiState>,
+    Path(namespace): Path<String>,
+    Query(params): Query<GetConfigQuery>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<ResolvedConfigResponse>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
 
-/// DELETE /api/v1/configs/:namespace/:key - Delete a configuration
-pub async fn delete_config(
-    State(state): State<ApiState>,
-    Path((namespace, key)): Path<(String, String)>,
-    Query(params): Query<GetConfigQuery>,
-) -> Result<StatusCode, ApiError> {
     let env: Environment = params
         .env
         .as_deref()
         .unwrap_or("development")
         .parse()
-        .map_err(|e| ApiError::BadRequest(e))?;
+        .map_err(ApiError::BadRequest)?;
 
-    let deleted = state.manager.delete(&namespace, &key, env)?;
+    // Values may come from parent namespaces, which the caller must be
+    // allowed to read as well
+    let lineage = state.manager.namespace_settings().lineage(&namespace);
+    let mut secret_readable = BTreeSet::new();
+    for ancestor in &lineage {
+        check_workload_scope(&workload, ancestor)?;
+        authorize(&state, &context, Resource::Config, Action::List, Some(ancestor))?;
+        if authorize(&state, &context, Resource::Secret, Action::Read, Some(ancestor)).is_ok() {
+            secret_readable.insert(ancestor.clone());
+        }
+        if params.reveal {
+            authorize_reveal(&state, &context, ancestor)?;
+        }
+    }
+    let reason = reveal_reason(&state, params.reveal, params.reason.as_deref())?;
 
-    if deleted {
-        Ok(StatusCode::NO_CONTENT)
+    let mut values = serde_json::Map::new();
+    let mut sources = BTreeMap::new();
+    for (key, resolved) in state.configs.resolve_namespace_with_sources(&namespace, env).await? {
+        let mut value = resolved.value;
+        let source = resolved.sources.last().map_or(namespace.as_str(), |s| s.namespace.as_str());
+        if value.is_secret() {
+            // Secrets are omitted for callers that may not read them
+            if !secret_readable.contains(source) {
+                continue;
+            }
+            if params.reveal {
+                limit_secret_reveal(&state, &context, source, &key)?;
+                value = state.manager.reveal(value)?;
+            }
+            audit_secret_read(&state, &context, source, &key, env, params.reveal, reason);
+        }
+        values.insert(key.clone(), config_value_to_json(&value));
+        sources.insert(key, resolved.sources);
+    }
+
+    Ok(Json(ResolvedConfigResponse {
+        namespace,
+        environment: env.to_string(),
+        values,
+        lineage,
+        sources,
+    }))
+}
+
+/// GET /api/v1/configs/:namespace/:key/explain - Trace how a value is resolved
+///
+/// Undefined keys are not an error: the trace shows every place that was
+/// consulted.
+#[utoipa::path(
+    get,
+    path = "/api/v1/configs/{namespace}/{key}/explain",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), ("key" = String, Path, description = "Config key"), GetConfigQuery),
+    responses(
+        (status = 200, description = "The resolution trace", body = ExplainResponse),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+    )
+)]
+pub async fn explain_config(
+    State(state): State<ApiState>,
+    Path((namespace, key)): Path<(String, String)>,
+    Query(params): Query<GetConfigQuery>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<ExplainResponse>, ApiError> {
+    let env: Environment = params
+        .env
+        .as_deref()
+        .unwrap_or("development")
+        .parse()
+        .map_err(ApiError::BadRequest)?;
+
+    // The trace names entries of parent namespaces too
+    for ancestor in state.manager.namespace_settings().lineage(&namespace) {
+        check_workload_scope(&workload, &ancestor)?;
+        authorize(&state, &context, Resource::Config, Action::Read, Some(&ancestor))?;
+    }
+
+    let mut trace = state.configs.explain(&namespace, &key, env, false).await?;
+    if let (Some(mut value), Some(source)) = (trace.value.take(), trace.source.as_ref()) {
+        if trace.secret {
+            authorize(&state, &context, Resource::Secret, Action::Read, Some(&source.namespace))?;
+            let reason = reveal_reason(&state, params.reveal, params.reason.as_deref())?;
+            if params.reveal {
+                authorize_reveal(&state, &context, &source.namespace)?;
+                limit_secret_reveal(&state, &context, &source.namespace, &key)?;
+                value = state.manager.reveal(value)?;
+                trace.decrypted = true;
+            }
+            audit_secret_read(&state, &context, &source.namespace, &key, env, params.reveal, reason);
+        }
+        trace.value = Some(value);
+    }
+
+    Ok(Json(ExplainResponse {
+        namespace: trace.namespace,
+        key: trace.key,
+        environment: trace.environment.to_string(),
+        namespaces: trace.namespaces,
+        environments: trace.environments.iter().map(ToString::to_string).collect(),
+        steps: trace.steps,
+        sources: trace.sources,
+        source: trace.source,
+        value: trace.value.as_ref().map(config_value_to_json),
+        secret: trace.secret,
+        decrypted: trace.decrypted,
+    }))
+}
+
+/// PATCH /api/v1/configs/:namespace/:key/metadata - Update tags and description
+#[utoipa::path(
+    patch,
+    path = "/api/v1/configs/{namespace}/{key}/metadata",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), ("key" = String, Path, description = "Config key")),
+    request_body = UpdateMetadataRequest,
+    responses(
+        (status = 200, description = "The updated configuration", body = ConfigResponse),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted, or rejected by the environment's protection (`ENVIRONMENT_PROTECTED`)", body = ErrorResponse),
+        (status = 404, description = "Configuration not found", body = ErrorResponse),
+    )
+)]
+pub async fn update_metadata(
+    State(state): State<ApiState>,
+    Path((namespace, key)): Path<(String, String)>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<UpdateMetadataRequest>,
+) -> Result<Json<ConfigResponse>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let env: Environment = req
+        .env
+        .as_deref()
+        .unwrap_or("development")
+        .parse()
+        .map_err(ApiError::BadRequest)?;
+
+    authorize(&state, &context, Resource::Config, Action::Update, Some(&namespace))?;
+
+    let existing = state
+        .configs
+        .get_untracked(&namespace, &key, env)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Configuration not found: {}:{}", namespace, key)))?;
+
+    let tags = req.tags.unwrap_or(existing.metadata.tags);
+    let description = req.description.or(existing.metadata.description);
+
+    let user = acting_user(&state, &workload, &context, req.user);
+    let entry = state
+        .configs
+        .set_metadata_with_options(
+            &namespace,
+            &key,
+            env,
+            tags,
+            description,
+            &user,
+            &write_options(req.reviewed_by, &context),
+        )
+        .await?;
+
+    Ok(Json(entry.into()))
+}
+
+/// DELETE /api/v1/configs/:namespace/:key - Delete a configuration
+///
+/// With `If-Match`, the delete only succeeds if the entry still has that ETag.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/configs/{namespace}/{key}",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), ("key" = String, Path, description = "Config key"), DeleteConfigQuery),
+    responses(
+        (status = 200, description = "With `dry_run`, the change the delete would make", body = DryRunResponse),
+        (status = 204, description = "Deleted"),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted, or rejected by the environment's protection (`ENVIRONMENT_PROTECTED`)", body = ErrorResponse),
+        (status = 404, description = "Configuration not found", body = ErrorResponse),
+        (status = 409, description = "Modified concurrently (`VERSION_CONFLICT`)", body = ErrorResponse),
+        (status = 412, description = "Stale `If-Match` (`PRECONDITION_FAILED`)", body = ErrorResponse),
+    )
+)]
+pub async fn delete_config(
+    State(state): State<ApiState>,
+    Path((namespace, key)): Path<(String, String)>,
+    Query(params): Query<DeleteConfigQuery>,
+    headers: HeaderMap,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Response, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let env: Environment = params
+        .env
+        .as_deref()
+        .unwrap_or("development")
+        .parse()
+        .map_err(|e| ApiError::BadRequest(e))?;
+
+    authorize(&state, &context, Resource::Config, Action::Delete, Some(&namespace))?;
+    let mut options = write_options(params.reviewed_by, &context).with_dry_run(params.dry_run);
+    let current = if headers.contains_key(header::IF_MATCH) || params.dry_run {
+        state.configs.get_untracked(&namespace, &key, env).await?
     } else {
+        None
+    };
+    if headers.contains_key(header::IF_MATCH) {
+        check_if_match(&headers, current.as_ref())?;
+        options.expected_version = current.as_ref().map(|entry| entry.version);
+    }
+
+    let user = acting_user(&state, &workload, &context, default_user());
+    let deleted = state
+        .configs
+        .delete_with_options(&namespace, &key, env, &user, &options)
+        .await?;
+
+    if !deleted {
         Err(ApiError::NotFound(format!("Configuration not found: {}:{}", namespace, key)))
+    } else if params.dry_run {
+        Ok(Json(DryRunResponse::new("delete", current.as_ref(), None)).into_response())
+    } else {
+        Ok(StatusCode::NO_CONTENT.into_response())
     }
 }
 
-/// GET /api/v1/configs/:namespace/:key/history - Get version history
+/// Query parameters for version history
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryQuery {
+    /// Environment (default `development`)
+    #[serde(default)]
+    env: Option<String>,
+    /// Return at most this many versions
+    limit: Option<usize>,
+    /// Only return versions older than this one (the last version of the previous page)
+    before_version: Option<u64>,
+}
+
+/// A version in the history of a configuration
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HistoryEntryResponse {
+    pub version: u64,
+    pub value: serde_json::Value,
+    pub created_at: String,
+    pub created_by: String,
+    pub change_description: Option<String>,
+}
+
+/// GET /api/v1/configs/:namespace/:key/history - Get version history, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/configs/{namespace}/{key}/history",
+    tag = "history",
+    params(("namespace" = String, Path, description = "Config namespace"), ("key" = String, Path, description = "Config key"), HistoryQuery),
+    responses(
+        (status = 200, description = "Versions, newest first", body = [HistoryEntryResponse]),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+    )
+)]
 pub async fn get_history(
     State(state): State<ApiState>,
     Path((namespace, key)): Path<(String, String)>,
-    Query(params): Query<GetConfigQuery>,
-) -> Result<Json<Vec<serde_json::Value>>, ApiError> {
+    Query(params): Query<HistoryQuery>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Vec<HistoryEntryResponse>>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
     let env: Environment = params
         .env
         .as_deref()
@@ -287,35 +1725,621 @@ pub async fn get_history(
         .parse()
         .map_err(|e| ApiError::BadRequest(e))?;
 
-    let history = state.manager.get_history(&namespace, &key, env)?;
+    authorize(&state, &context, Resource::History, Action::Read, Some(&namespace))?;
+
+    let options = HistoryOptions {
+        limit: params.limit,
+        before_version: params.before_version,
+    };
+    let history = state
+        .configs
+        .get_history_with_options(&namespace, &key, env, options)
+        .await?;
 
-    let response: Vec<serde_json::Value> = history
+    let response = history
         .into_iter()
-        .map(|v| {
-            serde_json::json!({
-                "version": v.version,
-                "value": config_value_to_json(&v.value),
-                "created_at": v.created_at.to_rfc3339(),
-                "created_by": v.created_by,
-                "change_description": v.change_description,
-            })
+        .map(|v| HistoryEntryResponse {
+            version: v.version,
+            value: config_value_to_json(&v.value),
+            created_at: v.created_at.to_rfc3339(),
+            created_by: v.created_by,
+            change_description: v.change_description,
         })
         .collect();
 
     Ok(Json(response))
 }
 
-/// POST /api/v1/configs/:namespace/:key/rollback/:version - Rollback to a specific version
-#[derive(Debug, Deserialize)]
+/// Query parameters for access statistics
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQuery {
+    /// Environment (default `development`)
+    #[serde(default)]
+    env: Option<String>,
+}
+
+/// How often and when a configuration was read
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccessStatsResponse {
+    pub namespace: String,
+    pub key: String,
+    pub environment: String,
+    pub reads: u64,
+    pub first_accessed: Option<String>,
+    pub last_accessed: Option<String>,
+}
+
+/// GET /api/v1/configs/:namespace/:key/stats - Get access statistics
+///
+/// Counts are persisted periodically, so reads of other servers sharing the
+/// store may show up with a delay of up to a minute.
+#[utoipa::path(
+    get,
+    path = "/api/v1/configs/{namespace}/{key}/stats",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace"), ("key" = String, Path, description = "Config key"), StatsQuery),
+    responses(
+        (status = 200, description = "Access statistics", body = AccessStatsResponse),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+        (status = 404, description = "Configuration not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_config_stats(
+    State(state): State<ApiState>,
+    Path((namespace, key)): Path<(String, String)>,
+    Query(params): Query<StatsQuery>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<AccessStatsResponse>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let env: Environment = params
+        .env
+        .as_deref()
+        .unwrap_or("development")
+        .parse()
+        .map_err(ApiError::BadRequest)?;
+
+    authorize(&state, &context, Resource::Config, Action::Read, Some(&namespace))?;
+
+    let stats = state
+        .configs
+        .access_stats(&namespace, &key, env)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Configuration not found: {}:{}", namespace, key)))?;
+
+    Ok(Json(AccessStatsResponse {
+        namespace,
+        key,
+        environment: env.to_string(),
+        reads: stats.reads,
+        first_accessed: stats.first_accessed.map(|t| t.to_rfc3339()),
+        last_accessed: stats.last_accessed.map(|t| t.to_rfc3339()),
+    }))
+}
+
+/// Query parameters for the changelog
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChangelogQuery {
+    /// Start of the window: an age such as `7d`, a date or an RFC 3339 timestamp (default `7d`)
+    since: Option<String>,
+    /// Only include changes in this environment
+    env: Option<String>,
+    /// `json` (default) or `markdown`
+    format: Option<String>,
+}
+
+/// GET /api/v1/changelog/:namespace - Release notes for a namespace
+#[utoipa::path(
+    get,
+    path = "/api/v1/changelog/{namespace}",
+    tag = "history",
+    params(("namespace" = String, Path, description = "Config namespace"), ChangelogQuery),
+    responses(
+        (status = 200, description = "Changes grouped by environment and key, as JSON or Markdown", body = Object, content_type = ["application/json", "text/markdown"]),
+        (status = 400, description = "Invalid window, environment or format", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+    )
+)]
+pub async fn get_changelog(
+    State(state): State<ApiState>,
+    Path(namespace): Path<String>,
+    Query(params): Query<ChangelogQuery>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Response, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let env: Option<Environment> = params
+        .env
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+    let since = parse_since(params.since.as_deref().unwrap_or("7d"), chrono::Utc::now())
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    authorize(&state, &context, Resource::History, Action::Read, Some(&namespace))?;
+
+    let changelog = state.configs.changelog(&namespace, env, since).await?;
+
+    match params.format.as_deref().unwrap_or("json") {
+        "json" => Ok(Json(changelog).into_response()),
+        "markdown" | "md" => Ok((
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            changelog.to_markdown(),
+        )
+            .into_response()),
+        other => Err(ApiError::BadRequest(format!("Unknown changelog format: {}", other))),
+    }
+}
+
+/// Default number of entries in an activity feed
+pub const DEFAULT_ACTIVITY_LIMIT: usize = 100;
+
+/// Largest number of entries in an activity feed
+pub const MAX_ACTIVITY_LIMIT: usize = 1000;
+
+/// Query parameters for the activity feed
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityQuery {
+    /// Only include activity in this environment
+    env: Option<String>,
+    /// Return at most this many entries (default 100, at most 1000)
+    limit: Option<usize>,
+}
+
+/// Where an activity entry comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivitySource {
+    /// A version snapshot in the config history
+    History,
+    /// An event in the audit log
+    Audit,
+}
+
+/// An entry in the activity feed of a namespace
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivityEntryResponse {
+    pub timestamp: String,
+    pub source: ActivitySource,
+    /// `version` for history snapshots, the event type for audit events
+    /// (e.g. `config_updated`, `secret_revealed`)
+    pub action: String,
+    pub key: String,
+    pub environment: String,
+    pub user: String,
+    /// Version created (history snapshots only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// Value of the version (history snapshots only; secrets are not revealed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    /// Change description of the version, or summary of the audit event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Request that caused the audit event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ActivityEntryResponse {
+    fn from_version(version: VersionEntry) -> (chrono::DateTime<chrono::Utc>, Self) {
+        let entry = Self {
+            timestamp: version.created_at.to_rfc3339(),
+            source: ActivitySource::History,
+            action: "version".to_string(),
+            key: version.key,
+            environment: version.environment.to_string(),
+            user: version.created_by,
+            version: Some(version.version),
+            value: Some(config_value_to_json(&version.value)),
+            description: version.change_description,
+            request_id: None,
+        };
+        (version.created_at, entry)
+    }
+
+    fn from_event(event: AuditEvent) -> Option<(chrono::DateTime<chrono::Utc>, Self)> {
+        let (_, key, environment) = event.event_type.target()?;
+        let entry = Self {
+            timestamp: event.timestamp.to_rfc3339(),
+            source: ActivitySource::Audit,
+            action: event.event_type.name().to_string(),
+            key: key.to_string(),
+            environment: environment.to_string(),
+            description: Some(event.summary()),
+            user: event.user,
+            version: None,
+            value: None,
+            request_id: event.request_id,
+        };
+        Some((event.timestamp, entry))
+    }
+}
+
+/// GET /api/v1/configs/:namespace/activity - Timeline of changes and audit events
+///
+/// Merges the version history of every config in the namespace with the
+/// audit events that refer to it, newest first. Audit events are only
+/// included for callers allowed to read the audit log.
+#[utoipa::path(
+    get,
+    path = "/api/v1/configs/{namespace}/activity",
+    tag = "history",
+    params(("namespace" = String, Path, description = "Config namespace"), ActivityQuery),
+    responses(
+        (status = 200, description = "Versions and audit events, newest first", body = [ActivityEntryResponse]),
+        (status = 400, description = "Invalid environment or limit", body = ErrorResponse),
+        (status = 403, description = "Not permitted", body = ErrorResponse),
+    )
+)]
+pub async fn get_activity(
+    State(state): State<ApiState>,
+    Path(namespace): Path<String>,
+    Query(params): Query<ActivityQuery>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    tenant: Option<Extension<TenantId>>,
+) -> Result<Json<Vec<ActivityEntryResponse>>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let env: Option<Environment> = params
+        .env
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+    let limit = params.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT);
+    if limit == 0 || limit > MAX_ACTIVITY_LIMIT {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_ACTIVITY_LIMIT
+        )));
+    }
+
+    authorize(&state, &context, Resource::History, Action::Read, Some(&namespace))?;
+
+    let mut activity: Vec<_> = state
+        .configs
+        .namespace_history(&namespace, env)
+        .await?
+        .into_iter()
+        .take(limit)
+        .map(ActivityEntryResponse::from_version)
+        .collect();
+
+    let audit_permitted =
+        authorize(&state, &context, Resource::AuditLog, Action::Read, Some(&namespace)).is_ok();
+    if let (Some(logger), true) = (&state.audit_logger, audit_permitted) {
+        let mut filter = AuditFilter::new().with_namespace(namespace.as_str());
+        if let Some(env) = env {
+            filter = filter.with_environment(env.to_string());
+        }
+        if let Some(Extension(tenant)) = &tenant {
+            filter = filter.with_tenant(tenant.to_string());
+        }
+        let logger = Arc::clone(logger);
+        let events = tokio::task::spawn_blocking(move || logger.recent(&filter, Some(limit)))
+            .await
+            .map_err(|e| ApiError::InternalError(e.to_string()))?
+            .map_err(|e| ApiError::InternalError(e.to_string()))?;
+        activity.extend(events.into_iter().filter_map(ActivityEntryResponse::from_event));
+    }
+
+    // Newest first; on equal timestamps the snapshot precedes its audit event
+    activity.sort_by(|(a, a_entry), (b, b_entry)| {
+        let is_audit = |entry: &ActivityEntryResponse| entry.source == ActivitySource::Audit;
+        b.cmp(a).then(is_audit(a_entry).cmp(&is_audit(b_entry)))
+    });
+    activity.truncate(limit);
+
+    Ok(Json(activity.into_iter().map(|(_, entry)| entry).collect()))
+}
+
+/// Request body for promoting configs between environments
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PromoteRequest {
+    /// Environment to copy from
+    #[schema(example = "staging")]
+    pub from: String,
+    /// Environment to copy to
+    #[schema(example = "production")]
+    pub to: String,
+    /// Keys to promote (every key of the namespace if omitted)
+    #[serde(default)]
+    pub keys: Option<Vec<String>>,
+    /// Return the changes without applying them
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+    /// Description recorded in the version history (default "Promoted from <from>")
+    #[serde(default)]
+    pub change_description: Option<String>,
+}
+
+/// POST /api/v1/configs/:namespace/promote - Copy configs to another environment
+///
+/// Writes the keys whose value in `to` differs from the one in `from`,
+/// re-encrypting secrets, and returns the applied changes as a plan (secrets
+/// masked). Nothing is written if any key fails its checks. With `dry_run`,
+/// the plan is returned without applying it.
+#[utoipa::path(
+    post,
+    path = "/api/v1/configs/{namespace}/promote",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Config namespace")),
+    request_body = PromoteRequest,
+    responses(
+        (status = 200, description = "Plan of the applied (or, with `dry_run`, pending) changes", body = Object),
+        (status = 400, description = "Invalid environment, or promoting an environment to itself", body = ErrorResponse),
+        (status = 403, description = "Not permitted, or rejected by the environment's protection (`ENVIRONMENT_PROTECTED`)", body = ErrorResponse),
+        (status = 404, description = "A requested key does not exist in `from`", body = ErrorResponse),
+        (status = 409, description = "A value would change type (`TYPE_CHANGE_NOT_ALLOWED`)", body = ErrorResponse),
+    )
+)]
+pub async fn promote_configs(
+    State(state): State<ApiState>,
+    Path(namespace): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<PromoteRequest>,
+) -> Result<Json<Plan>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let from: Environment = req.from.parse().map_err(ApiError::BadRequest)?;
+    let to: Environment = req.to.parse().map_err(ApiError::BadRequest)?;
+    let keys = match req.keys {
+        Some(keys) => PromoteKeys::Only(keys),
+        None => PromoteKeys::All,
+    };
+
+    authorize(&state, &context, Resource::Config, Action::Read, Some(&namespace))?;
+    let plan = state.configs.plan_promotion(&namespace, &keys, from, to).await?;
+    if req.dry_run {
+        return Ok(Json(plan));
+    }
+
+    authorize(&state, &context, Resource::Config, Action::Create, Some(&namespace))?;
+    authorize(&state, &context, Resource::Config, Action::Update, Some(&namespace))?;
+    let secret_keys: HashSet<String> = state
+        .configs
+        .list(&namespace, from)
+        .await?
+        .into_iter()
+        .filter(|entry| matches!(entry.value, ConfigValue::Secret(_)))
+        .map(|entry| entry.key)
+        .collect();
+    if plan.changes.iter().any(|change| secret_keys.contains(&change.key)) {
+        authorize(&state, &context, Resource::Secret, Action::Update, Some(&namespace))?;
+    }
+
+    let user = acting_user(&state, &workload, &context, default_user());
+    let mut options = write_options(req.reviewed_by, &context);
+    options.change_description = req.change_description;
+    let applied = state
+        .configs
+        .promote_with_options(&namespace, &keys, from, to, &user, &options)
+        .await?;
+    Ok(Json(applied))
+}
+
+/// Request body for cloning or renaming a namespace
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NamespaceCopyRequest {
+    /// Target namespace (must not have configs yet)
+    #[schema(example = "team-b/llm")]
+    pub to: String,
+    /// Environments to clone (every environment if omitted; renames move all)
+    #[serde(default)]
+    pub envs: Option<Vec<String>>,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+}
+
+/// Result of cloning or renaming a namespace
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NamespaceCopyResponse {
+    /// Target namespace
+    pub namespace: String,
+    /// Number of configs copied or moved
+    pub configs: usize,
+}
+
+/// Whether `namespace` has secrets in any of `envs`
+async fn has_secrets(state: &ApiState, namespace: &str, envs: &[Environment]) -> Result<bool, ApiError> {
+    for env in envs {
+        let entries = state.configs.list(namespace, *env).await?;
+        if entries.iter().any(|entry| matches!(entry.value, ConfigValue::Secret(_))) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// POST /api/v1/namespaces/:namespace/clone - Copy a namespace with its history
+///
+/// Secrets are copied in their encrypted form; cloning them needs permission
+/// to read them in the source and create them in the target.
+#[utoipa::path(
+    post,
+    path = "/api/v1/namespaces/{namespace}/clone",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Namespace to copy")),
+    request_body = NamespaceCopyRequest,
+    responses(
+        (status = 200, description = "Number of copied configs", body = NamespaceCopyResponse),
+        (status = 400, description = "Invalid environment", body = ErrorResponse),
+        (status = 403, description = "Not permitted, or rejected by the environment's protection (`ENVIRONMENT_PROTECTED`)", body = ErrorResponse),
+        (status = 404, description = "The namespace has no configs", body = ErrorResponse),
+        (status = 409, description = "The target namespace is not empty (`ALREADY_EXISTS`)", body = ErrorResponse),
+    )
+)]
+pub async fn clone_namespace(
+    State(state): State<ApiState>,
+    Path(namespace): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<NamespaceCopyRequest>,
+) -> Result<Json<NamespaceCopyResponse>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+    check_workload_scope(&workload, &req.to)?;
+
+    let envs: Vec<Environment> = req
+        .envs
+        .unwrap_or_default()
+        .iter()
+        .map(|env| env.parse())
+        .collect::<Result<_, String>>()
+        .map_err(ApiError::BadRequest)?;
+
+    authorize(&state, &context, Resource::Config, Action::Read, Some(&namespace))?;
+    authorize(&state, &context, Resource::Config, Action::Create, Some(&req.to))?;
+    let scope = if envs.is_empty() { &ENVIRONMENTS[..] } else { &envs[..] };
+    if has_secrets(&state, &namespace, scope).await? {
+        authorize(&state, &context, Resource::Secret, Action::Read, Some(&namespace))?;
+        authorize(&state, &context, Resource::Secret, Action::Create, Some(&req.to))?;
+    }
+
+    let user = acting_user(&state, &workload, &context, default_user());
+    let options = write_options(req.reviewed_by, &context);
+    let copies = state
+        .configs
+        .clone_namespace_with_options(&namespace, &req.to, &envs, &user, &options)
+        .await?;
+
+    Ok(Json(NamespaceCopyResponse {
+        namespace: req.to,
+        configs: copies.len(),
+    }))
+}
+
+/// POST /api/v1/namespaces/:namespace/rename - Move a namespace with its history
+#[utoipa::path(
+    post,
+    path = "/api/v1/namespaces/{namespace}/rename",
+    tag = "configs",
+    params(("namespace" = String, Path, description = "Namespace to move")),
+    request_body = NamespaceCopyRequest,
+    responses(
+        (status = 200, description = "Number of moved configs", body = NamespaceCopyResponse),
+        (status = 400, description = "Environments given (renames move every environment)", body = ErrorResponse),
+        (status = 403, description = "Not permitted, or rejected by the environment's protection (`ENVIRONMENT_PROTECTED`)", body = ErrorResponse),
+        (status = 404, description = "The namespace has no configs", body = ErrorResponse),
+        (status = 409, description = "The target namespace is not empty (`ALREADY_EXISTS`)", body = ErrorResponse),
+    )
+)]
+pub async fn rename_namespace(
+    State(state): State<ApiState>,
+    Path(namespace): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<NamespaceCopyRequest>,
+) -> Result<Json<NamespaceCopyResponse>, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+    check_workload_scope(&workload, &req.to)?;
+    if req.envs.is_some() {
+        return Err(ApiError::BadRequest(
+            "Renames move every environment; clone and delete to move some".to_string(),
+        ));
+    }
+
+    authorize(&state, &context, Resource::Config, Action::Delete, Some(&namespace))?;
+    authorize(&state, &context, Resource::Config, Action::Create, Some(&req.to))?;
+    if has_secrets(&state, &namespace, &ENVIRONMENTS).await? {
+        authorize(&state, &context, Resource::Secret, Action::Delete, Some(&namespace))?;
+        authorize(&state, &context, Resource::Secret, Action::Create, Some(&req.to))?;
+    }
+
+    let user = acting_user(&state, &workload, &context, default_user());
+    let options = write_options(req.reviewed_by, &context);
+    let moved = state
+        .configs
+        .rename_namespace_with_options(&namespace, &req.to, &user, &options)
+        .await?;
+
+    Ok(Json(NamespaceCopyResponse {
+        namespace: req.to,
+        configs: moved.len(),
+    }))
+}
+
+/// Query parameters for a rollback
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct RollbackQuery {
+    /// Environment (default `development`)
     env: Option<String>,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    reviewed_by: Option<String>,
+    /// Check the rollback and return the change without applying it
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Optional request body for a rollback
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RollbackRequest {
+    /// Why the rollback was made, recorded in the version history
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// The config after a rollback, with the change the rollback applied
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RollbackResponse {
+    #[serde(flatten)]
+    pub config: ConfigResponse,
+    /// Difference between the previous and the new version (`None` if the
+    /// previous version was pruned from the history)
+    pub diff: Option<ValueDiff>,
 }
 
+/// POST /api/v1/configs/:namespace/:key/rollback/:version - Rollback to a specific version
+#[utoipa::path(
+    post,
+    path = "/api/v1/configs/{namespace}/{key}/rollback/{version}",
+    tag = "history",
+    params(
+        ("namespace" = String, Path, description = "Config namespace"),
+        ("key" = String, Path, description = "Config key"),
+        ("version" = u64, Path, description = "Version to restore"),
+        RollbackQuery,
+    ),
+    request_body(content = Option<RollbackRequest>, description = "Optional reason for the rollback"),
+    responses(
+        (status = 200, description = "The restored configuration and the change it applied, or with `dry_run` the change it would make (`DryRunResponse`)", body = RollbackResponse),
+        (status = 400, description = "Invalid environment or request body", body = ErrorResponse),
+        (status = 403, description = "Not permitted, or rejected by the environment's protection (`ENVIRONMENT_PROTECTED`)", body = ErrorResponse),
+        (status = 404, description = "Version not found", body = ErrorResponse),
+    )
+)]
 pub async fn rollback_config(
     State(state): State<ApiState>,
     Path((namespace, key, version)): Path<(String, String, u64)>,
     Query(params): Query<RollbackQuery>,
-) -> Result<Json<ConfigResponse>, ApiError> {
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    check_workload_scope(&workload, &namespace)?;
+
+    let request: RollbackRequest = if body.is_empty() {
+        RollbackRequest::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid rollback request: {}", e)))?
+    };
+
     let env: Environment = params
         .env
         .as_deref()
@@ -323,10 +2347,593 @@ pub async fn rollback_config(
         .parse()
         .map_err(|e| ApiError::BadRequest(e))?;
 
+    authorize(&state, &context, Resource::Config, Action::Rollback, Some(&namespace))?;
+
+    let user = acting_user(&state, &workload, &context, default_user());
+    let mut options = write_options(params.reviewed_by, &context).with_dry_run(params.dry_run);
+    options.change_description = request.reason;
     let entry = state
-        .manager
-        .rollback(&namespace, &key, env, version)?
+        .configs
+        .rollback_with_options(&namespace, &key, env, version, &user, &options)
+        .await?
         .ok_or_else(|| ApiError::NotFound(format!("Version {} not found", version)))?;
 
-    Ok(Json(entry.into()))
+    if params.dry_run {
+        let current = state.configs.get_untracked(&namespace, &key, env).await?;
+        return Ok(Json(DryRunResponse::new("rollback", current.as_ref(), Some(&entry))).into_response());
+    }
+
+    let diff = match state
+        .configs
+        .diff_versions(&namespace, &key, env, entry.version - 1, entry.version)
+        .await
+    {
+        Ok(diff) => Some(diff),
+        Err(llm_config_core::ConfigError::NotFound(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(Json(RollbackResponse {
+        config: entry.into(),
+        diff,
+    })
+    .into_response())
+}
+
+/// Request body for saving a prompt template
+#[derive(Debug, Deserialize)]
+pub struct PutTemplateRequest {
+    pub content: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Default values for optional variables
+    #[serde(default)]
+    pub defaults: HashMap<String, String>,
+    #[serde(default)]
+    pub env: Option<String>,
+    #[serde(default = "default_user")]
+    pub user: String,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+    /// Description recorded in the version history
+    #[serde(default)]
+    pub change_description: Option<String>,
+}
+
+/// Query parameters for template reads
+#[derive(Debug, Deserialize)]
+pub struct TemplateQuery {
+    #[serde(default)]
+    env: Option<String>,
+}
+
+/// Request body for rendering a prompt template
+#[derive(Debug, Deserialize)]
+pub struct RenderTemplateRequest {
+    #[serde(default)]
+    pub env: Option<String>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// Why secrets referenced by the template are revealed, recorded in the
+    /// audit log
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+fn parse_env(env: Option<&str>) -> Result<Environment, ApiError> {
+    env.unwrap_or("development").parse().map_err(ApiError::BadRequest)
+}
+
+/// GET /api/v1/templates - List the prompt templates that apply to an environment
+pub async fn list_templates(
+    State(state): State<ApiState>,
+    Query(params): Query<TemplateQuery>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Vec<StoredTemplate>>, ApiError> {
+    let env = parse_env(params.env.as_deref())?;
+    authorize(&state, &context, Resource::Config, Action::List, Some(TEMPLATE_NAMESPACE))?;
+
+    Ok(Json(state.configs.list_templates(env).await?))
+}
+
+/// GET /api/v1/templates/:name - Get the prompt template that applies to an environment
+pub async fn get_template(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    Query(params): Query<TemplateQuery>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<StoredTemplate>, ApiError> {
+    let env = parse_env(params.env.as_deref())?;
+    authorize(&state, &context, Resource::Config, Action::Read, Some(TEMPLATE_NAMESPACE))?;
+
+    let template = state
+        .configs
+        .get_template(&name, env)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Template not found: {}", name)))?;
+    Ok(Json(template))
+}
+
+/// PUT /api/v1/templates/:name - Save a new version of a prompt template
+pub async fn put_template(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<PutTemplateRequest>,
+) -> Result<Json<StoredTemplate>, ApiError> {
+    check_workload_scope(&workload, TEMPLATE_NAMESPACE)?;
+    let env = parse_env(req.env.as_deref())?;
+
+    let action = if state.configs.get(TEMPLATE_NAMESPACE, &name, env).await?.is_some() {
+        Action::Update
+    } else {
+        Action::Create
+    };
+    authorize(&state, &context, Resource::Config, action, Some(TEMPLATE_NAMESPACE))?;
+
+    let mut template =
+        Template::new(name, req.content).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    if let Some(description) = req.description {
+        template = template.with_description(description);
+    }
+    for (var, value) in req.defaults {
+        template = template.with_default(var, value);
+    }
+
+    let user = acting_user(&state, &workload, &context, req.user);
+    let mut options = write_options(req.reviewed_by, &context);
+    options.change_description = req.change_description;
+    Ok(Json(state.configs.put_template(template, env, &user, &options).await?))
+}
+
+/// Resolves template references with the caller's workload scope and RBAC permissions
+///
+/// Config references need config read access to the referenced namespace,
+/// secret references need secret read and reveal access, and a reason when
+/// the server requires one, and are audited as `SecretRevealed`.
+struct CallerSource {
+    state: ApiState,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    reason: Option<String>,
+}
+
+impl ConfigSource for CallerSource {
+    type Environment = Environment;
+
+    fn resolve(
+        &self,
+        reference: &Reference,
+        env: Environment,
+    ) -> llm_config_templates::Result<Option<String>> {
+        let namespace = reference.namespace.as_str();
+        let allowed = check_workload_scope(&self.workload, namespace).is_ok()
+            && match reference.kind {
+                ReferenceKind::Config => {
+                    authorize(&self.state, &self.context, Resource::Config, Action::Read, Some(namespace))
+                        .is_ok()
+                }
+                ReferenceKind::Secret => {
+                    authorize(&self.state, &self.context, Resource::Secret, Action::Read, Some(namespace))
+                        .is_ok()
+                        && authorize_reveal(&self.state, &self.context, namespace).is_ok()
+                }
+            };
+        if !allowed {
+            return Err(TemplateError::AccessDenied(reference.to_string()));
+        }
+
+        let is_secret = reference.kind == ReferenceKind::Secret;
+        let reason = reveal_reason(&self.state, is_secret, self.reason.as_deref()).map_err(|_| {
+            TemplateError::UnresolvedReference(format!("{}: a reason is required to reveal secrets", reference))
+        })?;
+        if is_secret && limit_secret_reveal(&self.state, &self.context, namespace, &reference.key).is_err() {
+            return Err(TemplateError::AccessDenied(reference.to_string()));
+        }
+        let value = self.state.manager.resolve(reference, env)?;
+        if is_secret && value.is_some() {
+            audit_secret_read(&self.state, &self.context, namespace, &reference.key, env, true, reason);
+        }
+        Ok(value)
+    }
+}
+
+/// POST /api/v1/templates/:name/render - Render a prompt template with variables
+///
+/// References to stored values are resolved with the caller's permissions.
+pub async fn render_template(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<RenderTemplateRequest>,
+) -> Result<Json<RenderedTemplate>, ApiError> {
+    check_workload_scope(&workload, TEMPLATE_NAMESPACE)?;
+    let env = parse_env(req.env.as_deref())?;
+    authorize(&state, &context, Resource::Config, Action::Read, Some(TEMPLATE_NAMESPACE))?;
+
+    let source = CallerSource {
+        state: state.clone(),
+        workload,
+        context: context.clone(),
+        reason: req.reason,
+    };
+    let rendered = state
+        .configs
+        .render_template_with_source(&name, env, req.variables, source)
+        .await?;
+
+    if let Some(logger) = &state.audit_logger {
+        let event_type = AuditEventType::TemplateRendered {
+            name: rendered.name.clone(),
+            environment: rendered.environment.to_string(),
+            version: rendered.version,
+        };
+        if let Err(e) = logger.log(caller_event(event_type, &context)) {
+            tracing::error!("Failed to record template render: {}", e);
+        }
+    }
+
+    Ok(Json(rendered))
+}
+
+/// Request body for saving a blueprint
+#[derive(Debug, Deserialize)]
+pub struct PutBlueprintRequest {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Default values for variables
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+    /// Entries to create, by key
+    pub entries: BTreeMap<String, BlueprintEntry>,
+    #[serde(default = "default_user")]
+    pub user: String,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+    /// Description recorded in the version history
+    #[serde(default)]
+    pub change_description: Option<String>,
+}
+
+/// Request body for applying a blueprint
+#[derive(Debug, Deserialize)]
+pub struct ApplyBlueprintRequest {
+    /// Namespace to create the entries in
+    pub namespace: String,
+    #[serde(default)]
+    pub env: Option<String>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// Why secrets referenced by the blueprint are revealed, recorded in the
+    /// audit log
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default = "default_user")]
+    pub user: String,
+    /// Reviewer of the change, for review-required environments
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+    /// Description recorded in the version history
+    #[serde(default)]
+    pub change_description: Option<String>,
+}
+
+/// GET /api/v1/blueprints - List blueprints
+pub async fn list_blueprints(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Vec<StoredBlueprint>>, ApiError> {
+    authorize(&state, &context, Resource::Config, Action::List, Some(BLUEPRINT_NAMESPACE))?;
+
+    Ok(Json(state.configs.list_blueprints().await?))
+}
+
+/// GET /api/v1/blueprints/:name - Get a blueprint
+pub async fn get_blueprint(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<StoredBlueprint>, ApiError> {
+    authorize(&state, &context, Resource::Config, Action::Read, Some(BLUEPRINT_NAMESPACE))?;
+
+    let blueprint = state
+        .configs
+        .get_blueprint(&name)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Blueprint not found: {}", name)))?;
+    Ok(Json(blueprint))
+}
+
+/// PUT /api/v1/blueprints/:name - Save a new version of a blueprint
+pub async fn put_blueprint(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<PutBlueprintRequest>,
+) -> Result<Json<StoredBlueprint>, ApiError> {
+    check_workload_scope(&workload, BLUEPRINT_NAMESPACE)?;
+
+    let action = if state.configs.get_blueprint(&name).await?.is_some() {
+        Action::Update
+    } else {
+        Action::Create
+    };
+    authorize(&state, &context, Resource::Config, action, Some(BLUEPRINT_NAMESPACE))?;
+
+    let blueprint = Blueprint {
+        name,
+        description: req.description,
+        defaults: req.defaults,
+        entries: req.entries,
+    };
+    let user = acting_user(&state, &workload, &context, req.user);
+    let mut options = write_options(req.reviewed_by, &context);
+    options.change_description = req.change_description;
+    Ok(Json(state.configs.put_blueprint(blueprint, &user, &options).await?))
+}
+
+/// POST /api/v1/blueprints/:name/apply - Create a namespace's entries from a blueprint
+///
+/// Needs create access to the namespace (for secrets too if the blueprint
+/// has secret entries); references to stored values are resolved with the
+/// caller's permissions.
+pub async fn apply_blueprint(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    workload: Option<Extension<WorkloadIdentity>>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<ApplyBlueprintRequest>,
+) -> Result<Json<AppliedBlueprint>, ApiError> {
+    check_workload_scope(&workload, &req.namespace)?;
+    let env = parse_env(req.env.as_deref())?;
+    authorize(&state, &context, Resource::Config, Action::Read, Some(BLUEPRINT_NAMESPACE))?;
+
+    let stored = state
+        .configs
+        .get_blueprint(&name)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Blueprint not found: {}", name)))?;
+    authorize(&state, &context, Resource::Config, Action::Create, Some(&req.namespace))?;
+    if stored.blueprint.entries.values().any(|entry| entry.secret) {
+        authorize(&state, &context, Resource::Secret, Action::Create, Some(&req.namespace))?;
+    }
+
+    let user = acting_user(&state, &workload, &context, req.user);
+    let mut options = write_options(req.reviewed_by, &context);
+    options.change_description = req.change_description;
+    let source = CallerSource {
+        state: state.clone(),
+        workload,
+        context,
+        reason: req.reason,
+    };
+    let applied = state
+        .configs
+        .apply_blueprint_with_source(&name, &req.namespace, env, req.variables, &user, &options, source)
+        .await?;
+    Ok(Json(applied))
+}
+
+/// Environment inheritance graph
+#[derive(Debug, Serialize)]
+pub struct EnvironmentGraphResponse {
+    /// Parent of every environment except base
+    pub parents: BTreeMap<Environment, Environment>,
+    /// Environments whose values apply to each environment, from base to the environment itself
+    pub chains: BTreeMap<Environment, Vec<Environment>>,
+}
+
+impl From<EnvironmentGraph> for EnvironmentGraphResponse {
+    fn from(graph: EnvironmentGraph) -> Self {
+        Self {
+            parents: ENVIRONMENTS
+                .into_iter()
+                .filter_map(|env| graph.parent(env).map(|parent| (env, parent)))
+                .collect(),
+            chains: ENVIRONMENTS.into_iter().map(|env| (env, graph.chain(env))).collect(),
+        }
+    }
+}
+
+/// Request body for changing the parent of an environment
+#[derive(Debug, Deserialize)]
+pub struct SetEnvironmentParentRequest {
+    pub parent: String,
+}
+
+/// GET /api/v1/environments - Show the environment inheritance graph
+pub async fn get_environments(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<EnvironmentGraphResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Read, None)?;
+    Ok(Json(state.manager.environment_graph().into()))
+}
+
+/// PUT /api/v1/environments/:env - Change the parent an environment inherits from
+pub async fn set_environment_parent(
+    State(state): State<ApiState>,
+    Path(env): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<SetEnvironmentParentRequest>,
+) -> Result<Json<EnvironmentGraphResponse>, ApiError> {
+    authorize(&state, &context, Resource::System, Action::Update, None)?;
+
+    let env: Environment = env.parse().map_err(ApiError::BadRequest)?;
+    let parent: Environment = req.parent.parse().map_err(ApiError::BadRequest)?;
+
+    let graph = state.manager.environment_graph().with_parent(env, parent);
+    state.configs.set_environment_graph(graph.clone()).await?;
+
+    Ok(Json(graph.into()))
+}
+
+/// Request body for defining a custom role
+#[derive(Debug, Deserialize)]
+pub struct RoleDefinitionRequest {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub permissions: HashSet<Permission>,
+    #[serde(default)]
+    pub namespace_patterns: Vec<String>,
+}
+
+/// Request body for defining a group
+#[derive(Debug, Deserialize)]
+pub struct GroupRequest {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub members: BTreeSet<String>,
+    #[serde(default)]
+    pub external_groups: BTreeSet<String>,
+    #[serde(default)]
+    pub roles: Vec<GroupRole>,
+}
+
+fn role_store(state: &ApiState) -> Result<&RoleStore, ApiError> {
+    state
+        .role_store
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Role management is not enabled".to_string()))
+}
+
+/// GET /api/v1/roles - List custom role definitions
+pub async fn list_roles(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Vec<RoleDefinition>>, ApiError> {
+    authorize_role_management(&state, &context, Action::List)?;
+
+    let rbac = state.rbac.read().unwrap();
+    Ok(Json(rbac.role_definitions().into_iter().cloned().collect()))
+}
+
+/// GET /api/v1/roles/:name - Get a custom role definition
+pub async fn get_role(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<RoleDefinition>, ApiError> {
+    authorize_role_management(&state, &context, Action::Read)?;
+
+    let rbac = state.rbac.read().unwrap();
+    rbac.get_role_definition(&name)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Role not found: {}", name)))
+}
+
+/// PUT /api/v1/roles/:name - Define or replace a custom role
+pub async fn put_role(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<RoleDefinitionRequest>,
+) -> Result<Json<RoleDefinition>, ApiError> {
+    authorize_role_management(&state, &context, Action::Update)?;
+    let store = role_store(&state)?;
+
+    let mut definition = RoleDefinition::new(name);
+    definition.description = req.description;
+    definition.permissions = req.permissions;
+    definition.namespace_patterns = req.namespace_patterns;
+
+    store.save(&definition)?;
+    state.rbac.write().unwrap().define_role(definition.clone())?;
+
+    Ok(Json(definition))
+}
+
+/// DELETE /api/v1/roles/:name - Delete a custom role
+pub async fn delete_role(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<StatusCode, ApiError> {
+    authorize_role_management(&state, &context, Action::Delete)?;
+    let store = role_store(&state)?;
+
+    let deleted = store.delete(&name)?;
+    state.rbac.write().unwrap().remove_role_definition(&name);
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("Role not found: {}", name)))
+    }
+}
+
+/// GET /api/v1/groups - List groups
+pub async fn list_groups(
+    State(state): State<ApiState>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Vec<Group>>, ApiError> {
+    authorize_role_management(&state, &context, Action::List)?;
+
+    let rbac = state.rbac.read().unwrap();
+    Ok(Json(rbac.groups().into_iter().cloned().collect()))
+}
+
+/// GET /api/v1/groups/:name - Get a group
+pub async fn get_group(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<Json<Group>, ApiError> {
+    authorize_role_management(&state, &context, Action::Read)?;
+
+    let rbac = state.rbac.read().unwrap();
+    rbac.get_group(&name)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Group not found: {}", name)))
+}
+
+/// PUT /api/v1/groups/:name - Define or replace a group
+pub async fn put_group(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+    Json(req): Json<GroupRequest>,
+) -> Result<Json<Group>, ApiError> {
+    authorize_role_management(&state, &context, Action::Update)?;
+    let store = role_store(&state)?;
+
+    let mut group = Group::new(name);
+    group.description = req.description;
+    group.members = req.members;
+    group.external_groups = req.external_groups;
+    for role in req.roles {
+        group.assign(role);
+    }
+
+    store.save_group(&group)?;
+    state.rbac.write().unwrap().define_group(group.clone())?;
+
+    Ok(Json(group))
+}
+
+/// DELETE /api/v1/groups/:name - Delete a group
+pub async fn delete_group(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    context: Option<Extension<SecurityContext>>,
+) -> Result<StatusCode, ApiError> {
+    authorize_role_management(&state, &context, Action::Delete)?;
+    let store = role_store(&state)?;
+
+    let deleted = store.delete_group(&name)?;
+    state.rbac.write().unwrap().remove_group(&name);
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("Group not found: {}", name)))
+    }
 }
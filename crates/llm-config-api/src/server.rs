@@ -1,18 +1,51 @@
 //! HTTP server implementation
 
+use crate::admin::{
+    apply_retention, ban_ip, disable_read_only, elevate_role, enable_read_only, flush_cache,
+    get_cache_stats, get_rate_limit_stats, get_read_only, get_retention, get_security_policy,
+    inspect_cache_entry, list_bans, list_elevations, put_retention, put_security_policy, unban_ip,
+    warm_cache,
+};
+use crate::metrics::{
+    metrics_route, metrics_router, track_http_metrics, CryptoHealthCheck, MetricsState,
+};
 use crate::middleware::{comprehensive_security_middleware, SecurityState};
+use crate::notifications::{WebhookEndpoint, WebhookNotifier};
+use crate::openapi::{openapi_route, swagger_ui_route};
+use crate::shutdown::{track_in_flight, ShutdownCoordinator, DEFAULT_SHUTDOWN_TIMEOUT};
+use crate::tls::{serve_tls, ReloadingTlsConfig, TlsConfig};
+use crate::retention::spawn_retention;
+use crate::value_scan::spawn_value_scan;
+use crate::tenant::{dispatch, resolve_tenant, TenantRouters};
 use crate::routes::{
-    delete_config, get_config, get_history, health_check, list_configs, rollback_config,
-    set_config, ApiState,
+    apply_blueprint, batch_get_configs, clone_namespace, delete_config, delete_group, delete_role, explain_config, get_activity, get_changelog, get_config, get_config_stats, get_environments,
+    get_blueprint, get_group, get_history, get_role, get_template, health_check, list_blueprints, list_configs, list_groups, list_roles, list_templates, promote_configs,
+    put_blueprint, put_group, put_role, put_template, render_template, rename_namespace, resolve_configs, rollback_config, set_config,
+    set_environment_parent, store_health_check, update_metadata, ApiState,
 };
 use axum::{
     middleware,
-    routing::{delete, get, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
-use llm_config_core::ConfigManager;
+use llm_config_audit::{
+    AuditLogger, FileAuditStorage, OverflowPolicy, QueueConfig, QueueStats, DEFAULT_QUEUE_CAPACITY,
+};
+use llm_config_cache::{CacheManager, CacheTtl, L1Config};
+use llm_config_core::{AsyncConfigManager, ConfigManager, Environment, TenantRegistry};
+use chrono::{DateTime, Utc};
+use llm_config_metrics::{
+    spawn_push, HealthCheck, HealthChecker, MetricsRegistry, PushConfig, StorageHealthCheck,
+};
+use llm_config_rbac::RoleStore;
+use llm_config_security::{
+    KeyValidator, PrincipalQuotas, RateLimitStore, SecretAccessLimit, SecretAccessLimiter,
+    TrustedProxies, WorkloadIdentityMapper, MAX_KEY_AGE_DAYS,
+};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 
@@ -23,8 +56,79 @@ pub struct ServerConfig {
     pub port: u16,
     pub enable_cors: bool,
     pub enable_security: bool,
+    /// Directory for the audit log (disabled when unset)
+    pub audit_log_dir: Option<PathBuf>,
+    /// Audit events queued for storage before `audit_overflow` applies
+    pub audit_queue_capacity: usize,
+    /// What to do with audit events that do not fit the queue (spilled
+    /// events go to `spill/` under the audit log directory)
+    pub audit_overflow: OverflowPolicy,
+    /// SPIFFE workload identity mappings for mTLS callers
+    pub workload_identity: Option<WorkloadIdentityMapper>,
+    /// Check RBAC permissions of the calling principal on every request
+    pub enforce_rbac: bool,
+    /// Port of a separate Prometheus metrics and health listener
+    /// (`/metrics` is served on the API port when unset)
+    pub metrics_port: Option<u16>,
+    /// Also push metrics to a Prometheus Pushgateway, for deployments that
+    /// cannot be scraped
+    pub metrics_push: Option<PushConfig>,
+    /// Check storage consistency before serving
+    pub check_consistency: bool,
+    /// Webhooks notified about config changes
+    pub webhooks: Vec<WebhookEndpoint>,
+    /// Serve Swagger UI for the OpenAPI document at `/api/v1/docs`
+    pub swagger_ui: bool,
+    /// Reverse proxies (addresses or CIDR ranges) whose `Forwarded` /
+    /// `X-Forwarded-For` headers carry the client IP
+    pub trusted_proxies: Vec<String>,
+    /// Rate limit quotas of individual principals (user IDs, workload
+    /// principals, API keys) and roles
+    pub principal_quotas: PrincipalQuotas,
+    /// Store sharing rate limiter buckets and bans between replicas
+    /// (in-memory per instance when unset)
+    pub rate_limit_store: Option<Arc<dyn RateLimitStore>>,
+    /// Terminate TLS (and optionally verify client certificates) natively
+    pub tls: Option<TlsConfig>,
+    /// How long in-flight requests get to finish after a shutdown signal
+    pub shutdown_timeout: Duration,
+    /// When the encryption key was created, for key age monitoring
+    pub key_created_at: Option<DateTime<Utc>>,
+    /// Key age in days after which the crypto health check reports degraded
+    pub key_max_age_days: i64,
+    /// Scan stored values for plaintext credentials this often, recording
+    /// findings in the audit log (disabled when unset)
+    pub value_scan_interval: Option<Duration>,
+    /// Apply the retention settings of the store (`retention.toml`) this
+    /// often, pruning history and the audit log (disabled when unset)
+    pub retention_interval: Option<Duration>,
+    /// Per-user limit of revealed secrets (unlimited when unset)
+    pub secret_access_limit: Option<SecretAccessLimit>,
+    /// Allow callers with the reveal permission to decrypt secrets
+    pub allow_secret_reveal: bool,
+    /// Require a `reason` for every request that reveals secrets
+    pub require_secret_reason: bool,
+    /// Directory of the L2 tier of a cache config reads are served from
+    /// (no cache when unset); emptied at startup
+    pub cache_dir: Option<PathBuf>,
+    /// Entries held in the L1 tier of the cache
+    pub cache_size: usize,
+    /// Approximate bytes the entries in the L1 tier may take up (bounded
+    /// only by `cache_size` when unset)
+    pub cache_max_bytes: Option<usize>,
+    /// Namespaces loaded into the cache before serving, and again after the
+    /// cache was cleared
+    pub preload_namespaces: Vec<String>,
+    /// Environment of the preloaded namespaces (every environment when unset)
+    pub preload_env: Option<Environment>,
+    /// How long cached entries are served before they are refreshed from
+    /// storage (until changed when unset)
+    pub cache_ttl: Option<CacheTtl>,
 }
 
+/// Default number of entries in the L1 tier of the server's cache
+pub const DEFAULT_CACHE_SIZE: usize = 10_000;
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -32,37 +136,176 @@ impl Default for ServerConfig {
             port: 8080,
             enable_cors: true,
             enable_security: true,
+            audit_log_dir: None,
+            audit_queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            audit_overflow: OverflowPolicy::default(),
+            workload_identity: None,
+            enforce_rbac: false,
+            metrics_port: None,
+            metrics_push: None,
+            check_consistency: false,
+            webhooks: Vec::new(),
+            swagger_ui: false,
+            trusted_proxies: Vec::new(),
+            principal_quotas: PrincipalQuotas::default(),
+            rate_limit_store: None,
+            tls: None,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            key_created_at: None,
+            key_max_age_days: MAX_KEY_AGE_DAYS,
+            value_scan_interval: None,
+            retention_interval: None,
+            secret_access_limit: None,
+            allow_secret_reveal: false,
+            require_secret_reason: false,
+            cache_dir: None,
+            cache_size: DEFAULT_CACHE_SIZE,
+            cache_max_bytes: None,
+            preload_namespaces: Vec::new(),
+            preload_env: None,
+            cache_ttl: None,
         }
     }
 }
 
+/// How long a watch for writes of other replicas waits before starting over
+const KV_WATCH_WAIT: Duration = Duration::from_secs(300);
+
 /// Create and configure the Axum router
 pub fn create_router(manager: Arc<ConfigManager>, security_state: SecurityState) -> Router {
-    let api_state = ApiState { manager };
+    create_router_with_state(ApiState::new(manager), security_state)
+}
+
+/// Create the Axum router from a fully configured API state
+///
+/// When the state has a metrics registry, API requests are counted in it.
+/// The registry itself is not served; see [`metrics_route`].
+pub fn create_router_with_state(api_state: ApiState, security_state: SecurityState) -> Router {
+    // Main router with health check and API docs (no security on either)
+    Router::new()
+        .route("/health", get(store_health_check).with_state(api_state.clone()))
+        .nest("/api/v1", api_routes(api_state, Some(security_state)))
+        .merge(openapi_route())
+}
+
+/// Create the router of a multi-tenant server
+///
+/// Requests are authenticated once, assigned a tenant by [`resolve_tenant`]
+/// and served by that tenant's router from `routers`.
+pub fn create_tenant_router(routers: TenantRouters, security_state: SecurityState) -> Router {
+    let api_routes = Router::new()
+        .fallback(dispatch)
+        .layer(middleware::from_fn_with_state(security_state.clone(), resolve_tenant))
+        .layer(middleware::from_fn_with_state(
+            security_state,
+            comprehensive_security_middleware,
+        ))
+        .with_state(routers);
+
+    Router::new()
+        .route("/health", get(health_check))
+        .nest("/api/v1", api_routes)
+        .merge(openapi_route())
+}
+
+/// The API v1 routes over the given state, behind the security middleware
+/// unless the caller already applies it
+///
+/// Security administration is only served behind the middleware it manages.
+pub(crate) fn api_routes(api_state: ApiState, security_state: Option<SecurityState>) -> Router {
+    let metrics = api_state.metrics.clone();
 
-    // API v1 routes with security middleware
     let api_routes = Router::new()
         // Config operations
         .route("/configs/:namespace/:key", get(get_config))
         .route("/configs/:namespace/:key", post(set_config))
         .route("/configs/:namespace/:key", delete(delete_config))
         .route("/configs/:namespace", get(list_configs))
+        .route("/configs/:namespace/batch-get", post(batch_get_configs))
+        .route("/configs/:namespace/resolved", get(resolve_configs))
+        .route("/configs/:namespace/:key/explain", get(explain_config))
+        .route("/configs/:namespace/:key/metadata", patch(update_metadata))
+        .route("/configs/:namespace/:key/stats", get(get_config_stats))
         // Version history and rollback
         .route("/configs/:namespace/:key/history", get(get_history))
         .route(
             "/configs/:namespace/:key/rollback/:version",
             post(rollback_config),
         )
-        .layer(middleware::from_fn_with_state(
-            security_state.clone(),
-            comprehensive_security_middleware,
-        ))
-        .with_state(api_state);
+        .route("/changelog/:namespace", get(get_changelog))
+        .route("/configs/:namespace/activity", get(get_activity))
+        .route("/configs/:namespace/promote", post(promote_configs))
+        .route("/namespaces/:namespace/clone", post(clone_namespace))
+        .route("/namespaces/:namespace/rename", post(rename_namespace))
+        // Prompt templates
+        .route("/templates", get(list_templates))
+        .route("/templates/:name", get(get_template))
+        .route("/templates/:name", put(put_template))
+        .route("/templates/:name/render", post(render_template))
+        // Blueprints
+        .route("/blueprints", get(list_blueprints))
+        .route("/blueprints/:name", get(get_blueprint))
+        .route("/blueprints/:name", put(put_blueprint))
+        .route("/blueprints/:name/apply", post(apply_blueprint))
+        // Environment inheritance
+        .route("/environments", get(get_environments))
+        .route("/environments/:env", put(set_environment_parent))
+        // Maintenance
+        .route("/admin/read-only", get(get_read_only))
+        .route("/admin/read-only", put(enable_read_only))
+        .route("/admin/read-only", delete(disable_read_only))
+        .route("/admin/cache", get(get_cache_stats))
+        .route("/admin/cache", delete(flush_cache))
+        .route("/admin/cache/entries/:namespace/:key", get(inspect_cache_entry))
+        .route("/admin/cache/warm", post(warm_cache))
+        .route("/admin/retention", get(get_retention))
+        .route("/admin/retention", put(put_retention))
+        .route("/admin/retention/apply", post(apply_retention))
+        // Temporary role elevation
+        .route("/admin/elevations", get(list_elevations))
+        .route("/admin/elevations", post(elevate_role))
+        // Custom role management
+        .route("/roles", get(list_roles))
+        .route("/roles/:name", get(get_role))
+        .route("/roles/:name", put(put_role))
+        .route("/roles/:name", delete(delete_role))
+        // Groups and external identity group mapping
+        .route("/groups", get(list_groups))
+        .route("/groups/:name", get(get_group))
+        .route("/groups/:name", put(put_group))
+        .route("/groups/:name", delete(delete_group));
+    let (api_routes, api_state) = match security_state {
+        Some(security_state) => {
+            let api_routes = api_routes
+                // Security administration
+                .route("/admin/security/bans", get(list_bans))
+                .route("/admin/security/bans", post(ban_ip))
+                .route("/admin/security/bans/:ip", delete(unban_ip))
+                .route("/admin/security/rate-limits", get(get_rate_limit_stats))
+                .route("/admin/security/policy", get(get_security_policy))
+                .route("/admin/security/policy", put(put_security_policy))
+                .layer(middleware::from_fn_with_state(
+                    security_state.clone(),
+                    comprehensive_security_middleware,
+                ));
+            (api_routes, api_state.with_security_state(security_state))
+        }
+        None => (api_routes, api_state),
+    };
 
-    // Main router with health check (no security on health endpoint)
-    Router::new()
-        .route("/health", get(health_check))
-        .nest("/api/v1", api_routes)
+    // Outside the security middleware, so rejected requests are counted too
+    let api_routes = match metrics {
+        Some(registry) => {
+            api_routes.route_layer(middleware::from_fn_with_state(registry, track_http_metrics))
+        }
+        None => api_routes,
+    };
+
+    // Outermost, so the request span covers the security checks
+    #[cfg(feature = "otel")]
+    let api_routes = api_routes.route_layer(middleware::from_fn(crate::otel::trace_request));
+
+    api_routes.with_state(api_state)
 }
 
 /// Start the HTTP server
@@ -70,17 +313,372 @@ pub async fn serve(
     manager: Arc<ConfigManager>,
     config: ServerConfig,
 ) -> anyhow::Result<()> {
-    // Create security state
-    let security_state = if config.enable_security {
+    if config.check_consistency {
+        check_consistency(Arc::clone(&manager)).await?;
+    }
+    if manager.has_kv_store() {
+        watch_replicas(Arc::clone(&manager)).await?;
+    }
+
+    // One registry for the API, security middleware and metrics endpoint
+    let registry = Arc::new(MetricsRegistry::new()?);
+    let security_state = security_state(&config, &registry)?;
+    let shutdown = shutdown_coordinator(&config, &registry);
+    manager.set_metrics(registry.clone());
+    let health = health_checker();
+    if manager.has_encryption_key() {
+        health.register(Box::new(crypto_health_check(&manager, &config, &registry).await));
+    }
+
+    // Create API state with custom roles persisted alongside the configs
+    let role_store = RoleStore::new(manager.storage().clone());
+    let changes = manager.subscribe_changes();
+    if let Some(notifier) = notifier(&config, &registry) {
+        notifier.notify_changes(manager.subscribe_changes());
+    }
+    let cache = read_cache(&manager, &config, &registry).await?;
+    let mut api_state = ApiState::new(manager)
+        .with_role_store(role_store)?
+        .with_rbac_enforcement(config.enforce_rbac)
+        .with_secret_reveal(config.allow_secret_reveal)
+        .with_required_secret_reason(config.require_secret_reason)
+        .with_metrics(Arc::clone(&registry));
+    if let Some(limit) = config.secret_access_limit {
+        tracing::info!("Limiting secret reveals to {} per caller per minute", limit.reads_per_minute);
+        api_state = api_state.with_secret_access_limiter(Arc::new(SecretAccessLimiter::new(limit)));
+    }
+    if let Some(cache) = cache {
+        api_state = api_state.with_cache(cache);
+    }
+    let logger = audit_logger(&config, &registry)?;
+    if let Some(interval) = config.value_scan_interval {
+        let manager = Arc::clone(&api_state.manager);
+        spawn_value_scan(interval, logger.clone(), move || Ok(vec![(None, Arc::clone(&manager))]));
+    }
+    if let Some(interval) = config.retention_interval {
+        let manager = Arc::clone(&api_state.manager);
+        let audit = logger.clone().map(|logger| (logger, Arc::clone(&manager)));
+        spawn_retention(interval, audit, move || Ok(vec![(None, Arc::clone(&manager))]));
+    }
+    if let Some(logger) = logger {
+        logger.record_changes(changes);
+        shutdown.register(logger.clone());
+        api_state = api_state.with_audit_logger(logger);
+    }
+
+    // Role quotas follow the live role assignments
+    let security_state = security_state.with_principal_roles(Arc::clone(&api_state.rbac));
+    let app = create_router_with_state(api_state, security_state);
+    run(app, registry, health, shutdown, &config).await
+}
+
+/// Start a multi-tenant HTTP server
+///
+/// Each request is served from the store of the tenant resolved for it; see
+/// [`resolve_tenant`]. Audit events of all tenants go to one log, tagged with
+/// their tenant.
+pub async fn serve_tenants(tenants: TenantRegistry, config: ServerConfig) -> anyhow::Result<()> {
+    let registry = Arc::new(MetricsRegistry::new()?);
+    let security_state = security_state(&config, &registry)?;
+    let shutdown = shutdown_coordinator(&config, &registry);
+
+    let logger = audit_logger(&config, &registry)?;
+    let tenants = match &logger {
+        Some(logger) => tenants.with_audit_logger(logger.clone()),
+        None => tenants,
+    };
+    if let Some(notifier) = notifier(&config, &registry) {
+        notifier.notify_changes(tenants.change_bus().subscribe());
+    }
+    let tenants = Arc::new(tenants);
+
+    if config.check_consistency {
+        for tenant in tenants.tenants()? {
+            tracing::info!("Checking storage of tenant {}", tenant);
+            check_consistency(tenants.manager(&tenant)?).await?;
+        }
+    }
+
+    if let Some(interval) = config.value_scan_interval {
+        let tenants = Arc::clone(&tenants);
+        spawn_value_scan(interval, logger.clone(), move || {
+            tenants
+                .tenants()?
+                .into_iter()
+                .map(|tenant| Ok((Some(tenant.to_string()), tenants.manager(&tenant)?)))
+                .collect()
+        });
+    }
+
+    if let Some(interval) = config.retention_interval {
+        let tenants = Arc::clone(&tenants);
+        spawn_retention(interval, None, move || {
+            tenants
+                .tenants()?
+                .into_iter()
+                .map(|tenant| Ok((Some(tenant.to_string()), tenants.manager(&tenant)?)))
+                .collect()
+        });
+    }
+
+    let mut routers = TenantRouters::new(tenants)
+        .with_rbac_enforcement(config.enforce_rbac)
+        .with_secret_access(config.secret_access_limit, config.require_secret_reason)
+        .with_secret_reveal(config.allow_secret_reveal)
+        .with_metrics(Arc::clone(&registry));
+    if let Some(logger) = logger {
+        shutdown.register(logger.clone());
+        routers = routers.with_audit_logger(logger);
+    }
+
+    let app = create_tenant_router(routers, security_state);
+    run(app, registry, health_checker(), shutdown, &config).await
+}
+
+/// Log storage inconsistencies of a manager
+async fn check_consistency(manager: Arc<ConfigManager>) -> anyhow::Result<()> {
+    let report = AsyncConfigManager::new(manager).fsck(false).await?;
+    for issue in &report.issues {
+        tracing::warn!("Storage inconsistency in {}: {}", issue.subject, issue.detail);
+    }
+    if !report.is_clean() {
+        tracing::warn!(
+            "Found {} storage inconsistencies; run `llm-config fsck --repair` to fix them",
+            report.issues.len()
+        );
+    }
+    Ok(())
+}
+
+/// Cache config reads are served from, with the preloaded namespaces loaded
+///
+/// The cache follows the manager's changes and refreshes stale entries from
+/// it; its L2 tier is emptied first, as the store may have changed while the
+/// server was down.
+async fn read_cache(
+    manager: &Arc<ConfigManager>,
+    config: &ServerConfig,
+    registry: &Arc<MetricsRegistry>,
+) -> anyhow::Result<Option<Arc<CacheManager>>> {
+    let Some(ref dir) = config.cache_dir else {
+        return Ok(None);
+    };
+    let mut l1 = L1Config::new(config.cache_size);
+    if let Some(max_bytes) = config.cache_max_bytes {
+        l1 = l1.with_max_bytes(max_bytes);
+    }
+    let mut cache = CacheManager::with_l1_config(l1, dir.clone())?
+        .with_metrics(registry.clone())
+        .with_preload(Arc::clone(manager), config.preload_namespaces.clone(), config.preload_env);
+    if let Some(ttl) = config.cache_ttl {
+        cache = cache.with_ttl(ttl);
+    }
+    cache.clear()?;
+    let cache = Arc::new(cache);
+    cache.subscribe_invalidations(manager.subscribe_changes());
+    cache.spawn_revalidation(Arc::clone(manager));
+    tracing::info!("Caching config reads (L2 tier in {})", dir.display());
+
+    let preload = Arc::clone(&cache);
+    if let Some(report) = tokio::task::spawn_blocking(move || preload.preload()).await?? {
+        tracing::info!(
+            "Preloaded {} entries of {} namespaces in {} ms",
+            report.entries,
+            report.namespaces,
+            report.duration_ms
+        );
+    }
+    Ok(Some(cache))
+}
+
+/// Apply writes made through other replicas sharing the manager's
+/// key-value store: those made so far before serving, later ones as they
+/// happen
+async fn watch_replicas(manager: Arc<ConfigManager>) -> anyhow::Result<()> {
+    let configs = AsyncConfigManager::new(manager);
+    let applied = configs.watch(Duration::ZERO).await?;
+    tracing::info!("Sharing writes with other replicas ({} changes applied)", applied.len());
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = configs.watch(KV_WATCH_WAIT).await {
+                tracing::warn!("Failed to watch for writes of other replicas: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Security middleware state for the configured workload identities and proxies
+fn security_state(
+    config: &ServerConfig,
+    registry: &Arc<MetricsRegistry>,
+) -> anyhow::Result<SecurityState> {
+    let mut security_state = if config.enable_security {
         SecurityState::new()
     } else {
         SecurityState::new() // Always create but can be configured differently
     };
 
-    let app = create_router(manager, security_state);
+    if let Some(mapper) = config.workload_identity.clone() {
+        tracing::info!(
+            "Accepting SPIFFE workload identities from trust domain {}",
+            mapper.trust_domain
+        );
+        security_state = security_state.with_workload_identities(mapper);
+    }
+
+    if !config.trusted_proxies.is_empty() {
+        TrustedProxies::parse(&config.trusted_proxies)?;
+        tracing::info!(
+            "Trusting client IPs forwarded by {}",
+            config.trusted_proxies.join(", ")
+        );
+        security_state = security_state.with_trusted_proxies(config.trusted_proxies.clone());
+    }
+
+    if !config.principal_quotas.is_empty() {
+        tracing::info!("Rate limiting principals individually");
+        security_state = security_state.with_principal_quotas(config.principal_quotas.clone());
+    }
+
+    if let Some(ref store) = config.rate_limit_store {
+        tracing::info!("Sharing rate limiter state through {:?}", store);
+        security_state = security_state.with_rate_limit_store(Arc::clone(store));
+    }
+
+    Ok(security_state.with_metrics(Arc::clone(registry)))
+}
+
+/// Audit logger writing to the configured directory, if any
+fn audit_logger(
+    config: &ServerConfig,
+    registry: &Arc<MetricsRegistry>,
+) -> anyhow::Result<Option<Arc<AuditLogger>>> {
+    let Some(ref dir) = config.audit_log_dir else {
+        return Ok(None);
+    };
+    let storage = FileAuditStorage::new(dir)?;
+    tracing::info!("Audit log directory: {}", dir.display());
+
+    let mut queue = QueueConfig::new(config.audit_queue_capacity)
+        .with_overflow(config.audit_overflow);
+    if config.audit_overflow == OverflowPolicy::Spill {
+        queue = queue.with_spill_dir(dir.join("spill"));
+    }
+    let logger = Arc::new(AuditLogger::with_queue(Arc::new(storage), None, queue)?);
+    report_audit_queue(Arc::clone(&logger), Arc::clone(registry));
+    Ok(Some(logger))
+}
+
+/// Export the audit queue size and overflow counts as metrics
+fn report_audit_queue(logger: Arc<AuditLogger>, registry: Arc<MetricsRegistry>) {
+    tokio::spawn(async move {
+        let mut reported = QueueStats::default();
+        let mut interval = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            interval.tick().await;
+            let stats = logger.stats();
+            let audit = registry.audit();
+            audit.set_queue_size(stats.pending);
+            audit.record_overflow("dropped", stats.dropped - reported.dropped);
+            audit.record_overflow("spilled", stats.spilled - reported.spilled);
+            reported = stats;
+        }
+    });
+}
+
+/// Shutdown coordinator draining requests for the configured timeout
+/// Health checks served on the metrics listener
+fn health_checker() -> Arc<HealthChecker> {
+    let health = Arc::new(HealthChecker::new());
+    health.register(Box::new(StorageHealthCheck::new("storage")));
+    health
+}
+
+/// Check the encryption key of a manager, warning at startup if it is broken
+/// or due for rotation
+async fn crypto_health_check(
+    manager: &Arc<ConfigManager>,
+    config: &ServerConfig,
+    registry: &Arc<MetricsRegistry>,
+) -> CryptoHealthCheck {
+    let mut check = CryptoHealthCheck::new(Arc::clone(manager))
+        .with_validator(KeyValidator::new(config.key_max_age_days))
+        .with_metrics(Arc::clone(registry));
+    if let Some(created_at) = config.key_created_at {
+        check = check.with_created_at(created_at);
+    }
+
+    let result = check.check().await;
+    if !result.status.is_healthy() {
+        tracing::warn!("{}", result.message.unwrap_or_default());
+    }
+    check
+}
+
+fn shutdown_coordinator(config: &ServerConfig, registry: &Arc<MetricsRegistry>) -> ShutdownCoordinator {
+    ShutdownCoordinator::new(config.shutdown_timeout).with_metrics(Arc::clone(registry))
+}
+
+/// Notifier for the configured webhooks, if any
+fn notifier(config: &ServerConfig, registry: &Arc<MetricsRegistry>) -> Option<WebhookNotifier> {
+    if config.webhooks.is_empty() {
+        return None;
+    }
+    tracing::info!("Notifying {} webhooks of config changes", config.webhooks.len());
+    Some(WebhookNotifier::new(config.webhooks.clone()).with_metrics(Arc::clone(registry)))
+}
+
+/// Serve the API and metrics until shutdown
+async fn run(
+    mut app: Router,
+    registry: Arc<MetricsRegistry>,
+    health: Arc<HealthChecker>,
+    shutdown: ShutdownCoordinator,
+    config: &ServerConfig,
+) -> anyhow::Result<()> {
+    {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            shutdown.trigger();
+        });
+    }
+
+    if let Some(push) = &config.metrics_push {
+        tracing::info!("Pushing metrics to {} every {:?}", push.group_url(), push.interval);
+        spawn_push(Arc::clone(&registry), push.clone());
+    }
+
+    // Serve metrics on their own listener, or next to the API
+    if let Some(port) = config.metrics_port {
+        let addr: SocketAddr = format!("{}:{}", config.host, port).parse()?;
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!("Serving metrics and health checks on {}", addr);
+
+        let app = metrics_router(MetricsState::new(registry, health));
+        let stopped = shutdown.triggered();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app)
+                .with_graceful_shutdown(stopped)
+                .await
+            {
+                tracing::error!("Metrics server failed: {}", e);
+            }
+        });
+    } else {
+        app = app.merge(metrics_route(registry));
+    }
+
+    if config.swagger_ui {
+        app = app.merge(swagger_ui_route());
+    }
 
     // Add middleware layers
     let app = app
+        .layer(middleware::from_fn_with_state(shutdown.clone(), track_in_flight))
         .layer(TraceLayer::new_for_http())
         .layer(if config.enable_cors {
             CorsLayer::new()
@@ -91,26 +689,40 @@ pub async fn serve(
             CorsLayer::permissive()
         });
 
+    // Load the certificates before binding, so a bad one fails the start
+    let tls = match &config.tls {
+        Some(tls) => Some(Arc::new(ReloadingTlsConfig::new(tls.clone())?)),
+        None => None,
+    };
+
     // Bind to address
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
     tracing::info!(
-        "Starting LLM Config API server on {} (security: {})",
+        "Starting LLM Config API server on {} (security: {}, TLS: {})",
         addr,
-        config.enable_security
+        config.enable_security,
+        tls.is_some()
     );
 
     // Create listener
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
+    if let Some(tls) = tls {
+        let watcher = tls.watch();
+        let result = shutdown
+            .run(serve_tls(listener, app, tls, shutdown.triggered()))
+            .await;
+        watcher.abort();
+        return result;
+    }
+
     // Serve with graceful shutdown
-    axum::serve(
+    let server = axum::serve(
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
     )
-    .with_graceful_shutdown(shutdown_signal())
-    .await?;
-
-    Ok(())
+    .with_graceful_shutdown(shutdown.triggered());
+    shutdown.run(async { Ok(server.await?) }).await
 }
 
 /// Graceful shutdown handler
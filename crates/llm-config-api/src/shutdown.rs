@@ -0,0 +1,215 @@
+//! Graceful shutdown with in-flight request draining
+//!
+//! A [`ShutdownCoordinator`] counts the requests being served (see
+//! [`track_in_flight`]). Once triggered, the server stops accepting
+//! connections and waits for in-flight requests up to the shutdown timeout;
+//! then buffered state is flushed through the registered [`FlushOnShutdown`]
+//! hooks, such as the audit logger queue or an L2 cache. Requests and audit
+//! events that did not make it are reported in the logs and metrics.
+
+use axum::{extract::Request, extract::State, middleware::Next, response::Response};
+use llm_config_audit::AuditLogger;
+use llm_config_metrics::MetricsRegistry;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Default time in-flight requests get to finish after a shutdown signal
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Buffered state written out before the server exits
+#[async_trait::async_trait]
+pub trait FlushOnShutdown: Send + Sync {
+    /// Name used in shutdown logs
+    fn name(&self) -> &str;
+
+    /// Write out buffered state
+    async fn flush(&self) -> anyhow::Result<()>;
+
+    /// Items still buffered, reported when flushing does not finish in time
+    fn pending(&self) -> usize {
+        0
+    }
+}
+
+#[async_trait::async_trait]
+impl FlushOnShutdown for AuditLogger {
+    fn name(&self) -> &str {
+        "audit log"
+    }
+
+    async fn flush(&self) -> anyhow::Result<()> {
+        Ok(AuditLogger::flush(self).await?)
+    }
+
+    fn pending(&self) -> usize {
+        AuditLogger::pending(self)
+    }
+}
+
+/// Coordinates stopping the server without losing tail requests
+#[derive(Clone)]
+pub struct ShutdownCoordinator {
+    inner: Arc<Inner>,
+    metrics: Option<Arc<MetricsRegistry>>,
+}
+
+struct Inner {
+    timeout: Duration,
+    in_flight: AtomicUsize,
+    triggered: watch::Sender<bool>,
+    hooks: Mutex<Vec<Arc<dyn FlushOnShutdown>>>,
+}
+
+impl ShutdownCoordinator {
+    /// Give in-flight requests up to `timeout` to finish once triggered
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                timeout,
+                in_flight: AtomicUsize::new(0),
+                triggered: watch::Sender::new(false),
+                hooks: Mutex::new(Vec::new()),
+            }),
+            metrics: None,
+        }
+    }
+
+    /// Report in-flight and abandoned requests in a metrics registry
+    pub fn with_metrics(mut self, registry: Arc<MetricsRegistry>) -> Self {
+        self.metrics = Some(registry);
+        self
+    }
+
+    /// Flush `hook` after the requests drained
+    pub fn register(&self, hook: Arc<dyn FlushOnShutdown>) {
+        self.inner.hooks.lock().unwrap().push(hook);
+    }
+
+    /// How long in-flight requests get to finish
+    pub fn timeout(&self) -> Duration {
+        self.inner.timeout
+    }
+
+    /// Requests currently being served
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Start shutting down
+    pub fn trigger(&self) {
+        self.inner.triggered.send_replace(true);
+    }
+
+    /// Whether shutdown was triggered
+    pub fn is_triggered(&self) -> bool {
+        *self.inner.triggered.borrow()
+    }
+
+    /// Completes once shutdown is triggered
+    pub fn triggered(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut triggered = self.inner.triggered.subscribe();
+        async move {
+            // The sender lives as long as the coordinator
+            let _ = triggered.wait_for(|triggered| *triggered).await;
+        }
+    }
+
+    /// Drive `server` until it stops after the shutdown trigger, or until its
+    /// connections outlast the timeout; then flush the hooks
+    ///
+    /// `server` must stop accepting connections once [`triggered`](Self::triggered)
+    /// completes.
+    pub async fn run<F>(&self, server: F) -> anyhow::Result<()>
+    where
+        F: Future<Output = anyhow::Result<()>>,
+    {
+        tokio::pin!(server);
+        let deadline = async {
+            self.triggered().await;
+            tracing::info!(
+                "Draining {} in-flight requests (timeout {:?})",
+                self.in_flight(),
+                self.inner.timeout
+            );
+            tokio::time::sleep(self.inner.timeout).await;
+        };
+
+        let result = tokio::select! {
+            result = &mut server => result,
+            _ = deadline => Ok(()),
+        };
+        self.finish().await;
+        result
+    }
+
+    /// Report abandoned requests and flush the hooks
+    async fn finish(&self) {
+        let abandoned = self.in_flight();
+        if abandoned > 0 {
+            tracing::warn!(
+                "Shutdown deadline passed with {} requests in flight",
+                abandoned
+            );
+        } else {
+            tracing::info!("All in-flight requests finished");
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.system().record_abandoned_requests(abandoned);
+        }
+
+        let hooks = self.inner.hooks.lock().unwrap().clone();
+        for hook in hooks {
+            match tokio::time::timeout(self.inner.timeout, hook.flush()).await {
+                Ok(Ok(())) => tracing::info!("Flushed {}", hook.name()),
+                Ok(Err(e)) => tracing::error!(
+                    "Failed to flush {} ({} items pending): {}",
+                    hook.name(),
+                    hook.pending(),
+                    e
+                ),
+                Err(_) => tracing::warn!(
+                    "Timed out flushing {} with {} items pending",
+                    hook.name(),
+                    hook.pending()
+                ),
+            }
+        }
+    }
+
+    fn update_in_flight(&self, count: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.system().set_http_requests_in_flight(count);
+        }
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHUTDOWN_TIMEOUT)
+    }
+}
+
+/// Decrements the in-flight count when the request ends, even if cancelled
+struct InFlightGuard(ShutdownCoordinator);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let count = self.0.inner.in_flight.fetch_sub(1, Ordering::SeqCst) - 1;
+        self.0.update_in_flight(count);
+    }
+}
+
+/// Middleware counting the requests in flight
+pub async fn track_in_flight(
+    State(coordinator): State<ShutdownCoordinator>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let count = coordinator.inner.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+    coordinator.update_in_flight(count);
+    let _guard = InFlightGuard(coordinator);
+    next.run(request).await
+}
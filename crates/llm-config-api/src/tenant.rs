@@ -0,0 +1,207 @@
+//! Tenant resolution and per-tenant routing
+//!
+//! [`resolve_tenant`] determines the tenant of each request from the
+//! caller's workload identity or, behind a trusted proxy, the `x-tenant-id`
+//! header, and [`dispatch`]
+//! hands the request to a router built around that tenant's own config
+//! manager, role store and keys. Handlers only ever see one tenant's state,
+//! so a request cannot reach another tenant's data.
+
+use crate::middleware::SecurityState;
+use crate::routes::{ApiError, ApiState};
+use crate::server::api_routes;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router,
+};
+use llm_config_audit::AuditLogger;
+use llm_config_core::{TenantId, TenantRegistry};
+use llm_config_metrics::MetricsRegistry;
+use llm_config_rbac::RoleStore;
+use llm_config_security::{SecretAccessLimit, SecretAccessLimiter, SecurityContext, WorkloadIdentity};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use tower::ServiceExt;
+
+/// Header naming the tenant of callers without a tenant-bound workload
+/// identity, set by a trusted proxy
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Per-tenant API routers, built on first use
+#[derive(Clone)]
+pub struct TenantRouters {
+    registry: Arc<TenantRegistry>,
+    enforce_rbac: bool,
+    secret_access_limit: Option<SecretAccessLimit>,
+    require_secret_reason: bool,
+    allow_secret_reveal: bool,
+    audit_logger: Option<Arc<AuditLogger>>,
+    metrics: Option<Arc<MetricsRegistry>>,
+    routers: Arc<RwLock<HashMap<TenantId, Router>>>,
+}
+
+impl TenantRouters {
+    /// Route requests to the tenants of the given registry
+    pub fn new(registry: Arc<TenantRegistry>) -> Self {
+        Self {
+            registry,
+            enforce_rbac: false,
+            secret_access_limit: None,
+            require_secret_reason: false,
+            allow_secret_reveal: false,
+            audit_logger: None,
+            metrics: None,
+            routers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Check RBAC permissions against each tenant's own roles
+    pub fn with_rbac_enforcement(mut self, enforce: bool) -> Self {
+        self.enforce_rbac = enforce;
+        self
+    }
+
+    /// Limit secret reveals of each tenant's users and require reasons for them
+    ///
+    /// Every tenant counts its own users' reveals.
+    pub fn with_secret_access(mut self, limit: Option<SecretAccessLimit>, require_reason: bool) -> Self {
+        self.secret_access_limit = limit;
+        self.require_secret_reason = require_reason;
+        self
+    }
+
+    /// Allow callers with the reveal permission to decrypt secrets of their tenant
+    pub fn with_secret_reveal(mut self, allow: bool) -> Self {
+        self.allow_secret_reveal = allow;
+        self
+    }
+
+    /// Record API events of all tenants, tagged with their tenant
+    pub fn with_audit_logger(mut self, logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(logger);
+        self
+    }
+
+    /// Count API requests and config operations of all tenants
+    pub fn with_metrics(mut self, registry: Arc<MetricsRegistry>) -> Self {
+        self.metrics = Some(registry);
+        self
+    }
+
+    /// The tenant registry requests are routed to
+    pub fn registry(&self) -> &TenantRegistry {
+        &self.registry
+    }
+
+    /// The API router of a tenant, serving its own configs and roles
+    pub fn router(&self, tenant: &TenantId) -> Result<Router, ApiError> {
+        if let Some(router) = self.routers.read().unwrap().get(tenant) {
+            return Ok(router.clone());
+        }
+
+        let manager = self.registry.manager(tenant)?;
+        if let Some(registry) = &self.metrics {
+            manager.set_metrics(registry.clone());
+        }
+        let role_store = RoleStore::new(manager.storage().clone());
+        let mut state = ApiState::new(manager)
+            .with_role_store(role_store)?
+            .with_rbac_enforcement(self.enforce_rbac)
+            .with_secret_reveal(self.allow_secret_reveal)
+            .with_required_secret_reason(self.require_secret_reason);
+        if let Some(limit) = self.secret_access_limit {
+            state = state.with_secret_access_limiter(Arc::new(SecretAccessLimiter::new(limit)));
+        }
+        if let Some(logger) = &self.audit_logger {
+            state = state.with_audit_logger(Arc::clone(logger));
+        }
+        if let Some(registry) = &self.metrics {
+            state = state.with_metrics(Arc::clone(registry));
+        }
+        let router = api_routes(state, None);
+
+        let mut routers = self.routers.write().unwrap();
+        Ok(routers.entry(tenant.clone()).or_insert(router).clone())
+    }
+}
+
+/// Resolve the tenant of a request
+///
+/// A workload identity mapped to a tenant determines it. Otherwise the tenant
+/// is taken from the `x-tenant-id` header, which only a trusted proxy (that
+/// authenticated the caller) may set; the header from any other peer is
+/// rejected, as is a header naming a different tenant than the caller's
+/// identity.
+pub async fn resolve_tenant(
+    State(security): State<SecurityState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let claimed = request
+        .extensions()
+        .get::<WorkloadIdentity>()
+        .and_then(|identity| identity.tenant.clone());
+    let from_trusted_proxy = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .is_some_and(|ConnectInfo(peer)| security.is_trusted_proxy(peer.ip()));
+    let requested = request
+        .headers()
+        .get(TENANT_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .map(str::to_string)
+                .map_err(|_| ApiError::BadRequest(format!("Invalid {} header", TENANT_HEADER)))
+        })
+        .transpose()?;
+
+    let tenant = match (claimed, requested) {
+        (Some(claimed), Some(requested)) if claimed != requested => {
+            return Err(ApiError::Forbidden(format!(
+                "Workload identity is not authorized for tenant {}",
+                requested
+            )));
+        }
+        (Some(tenant), _) => tenant,
+        (None, Some(_)) if !from_trusted_proxy => {
+            return Err(ApiError::Forbidden(format!(
+                "The {} header is only accepted from trusted proxies",
+                TENANT_HEADER
+            )));
+        }
+        (None, Some(tenant)) => tenant,
+        (None, None) => {
+            return Err(ApiError::BadRequest(format!(
+                "Missing {} header",
+                TENANT_HEADER
+            )));
+        }
+    };
+    let tenant = TenantId::new(tenant).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    if let Some(context) = request.extensions_mut().get_mut::<SecurityContext>() {
+        context
+            .metadata
+            .insert("tenant_id".to_string(), tenant.to_string());
+    }
+    request.extensions_mut().insert(tenant);
+    Ok(next.run(request).await)
+}
+
+/// Serve a request with the router of its resolved tenant
+pub async fn dispatch(State(routers): State<TenantRouters>, request: Request) -> Response {
+    let Some(tenant) = request.extensions().get::<TenantId>().cloned() else {
+        return ApiError::InternalError("Tenant was not resolved".to_string()).into_response();
+    };
+    match routers.router(&tenant) {
+        Ok(router) => match router.oneshot(request).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        },
+        Err(e) => e.into_response(),
+    }
+}
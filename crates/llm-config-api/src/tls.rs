@@ -0,0 +1,336 @@
+//! Native TLS termination with rustls
+//!
+//! [`serve_tls`] accepts TLS connections itself instead of relying on a
+//! terminating proxy. With a client CA bundle configured, clients must
+//! present a certificate issued by it (mTLS); the verified certificate is
+//! attached to each request as part of its [`TlsSession`], and the security
+//! middleware takes the caller's principal from it. The certificate, key and
+//! CA bundle are watched and reloaded without dropping connections.
+
+use axum::{extract::ConnectInfo, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::{conn::auto, graceful::GracefulShutdown};
+use llm_config_security::SpiffeId;
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{ProtocolVersion, RootCertStore, ServerConnection};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+use x509_parser::extensions::GeneralName;
+
+/// Default interval between checks for changed certificate files
+pub const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Certificate, key and client CA files of the TLS listener
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first
+    pub cert_path: PathBuf,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: PathBuf,
+    /// PEM CA bundle verifying client certificates; when set, clients without
+    /// a valid certificate are rejected during the handshake
+    pub client_ca_path: Option<PathBuf>,
+    /// How often the files are checked for changes
+    pub reload_interval: Duration,
+}
+
+impl TlsConfig {
+    /// Serve TLS with the given certificate chain and private key
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            client_ca_path: None,
+            reload_interval: DEFAULT_RELOAD_INTERVAL,
+        }
+    }
+
+    /// Require client certificates issued by the CAs in the given bundle
+    pub fn with_client_ca(mut self, path: impl Into<PathBuf>) -> Self {
+        self.client_ca_path = Some(path.into());
+        self
+    }
+
+    /// Check the files for changes at the given interval
+    pub fn with_reload_interval(mut self, interval: Duration) -> Self {
+        self.reload_interval = interval;
+        self
+    }
+
+    fn paths(&self) -> Vec<&Path> {
+        let mut paths = vec![self.cert_path.as_path(), self.key_path.as_path()];
+        paths.extend(self.client_ca_path.as_deref());
+        paths
+    }
+
+    /// Build the rustls server configuration from the files
+    pub fn load(&self) -> anyhow::Result<rustls::ServerConfig> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let certs = CertificateDer::pem_file_iter(&self.cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| anyhow::anyhow!("Invalid certificate {}: {}", self.cert_path.display(), e))?;
+        if certs.is_empty() {
+            anyhow::bail!("No certificate found in {}", self.cert_path.display());
+        }
+        let key = PrivateKeyDer::from_pem_file(&self.key_path)
+            .map_err(|e| anyhow::anyhow!("Invalid private key {}: {}", self.key_path.display(), e))?;
+
+        let builder = rustls::ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()?;
+        let builder = match &self.client_ca_path {
+            Some(path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in CertificateDer::pem_file_iter(path)
+                    .map_err(|e| anyhow::anyhow!("Invalid client CA {}: {}", path.display(), e))?
+                {
+                    roots.add(cert.map_err(|e| {
+                        anyhow::anyhow!("Invalid client CA {}: {}", path.display(), e)
+                    })?)?;
+                }
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut config = builder.with_single_cert(certs, key)?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(config)
+    }
+}
+
+/// Identity carried by a verified client certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// Subject common name, used as the RBAC principal
+    pub common_name: Option<String>,
+    /// URI subject alternative names, such as SPIFFE IDs
+    pub uris: Vec<String>,
+}
+
+impl ClientCertificate {
+    /// Extract the identity of a DER-encoded certificate
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+        let common_name = cert
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_string);
+        let uris = match cert.subject_alternative_name() {
+            Ok(Some(san)) => san
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::URI(uri) => Some(uri.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Some(Self { common_name, uris })
+    }
+
+    /// The SPIFFE ID among the URI SANs, if any
+    pub fn spiffe_id(&self) -> Option<SpiffeId> {
+        self.uris.iter().find_map(|uri| SpiffeId::parse(uri).ok())
+    }
+}
+
+/// TLS session of a request received by [`serve_tls`]
+#[derive(Debug, Clone)]
+pub struct TlsSession {
+    /// Negotiated protocol version ("1.2" or "1.3")
+    pub version: &'static str,
+    /// Verified client certificate (mTLS)
+    pub client_certificate: Option<ClientCertificate>,
+}
+
+impl TlsSession {
+    fn new(connection: &ServerConnection) -> Self {
+        let version = match connection.protocol_version() {
+            Some(ProtocolVersion::TLSv1_3) => "1.3",
+            _ => "1.2",
+        };
+        let client_certificate = connection
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| ClientCertificate::from_der(cert));
+        Self {
+            version,
+            client_certificate,
+        }
+    }
+}
+
+/// Server TLS configuration, reloaded when its files change
+///
+/// A reload that fails keeps the previous configuration, so a half-written
+/// certificate never takes the listener down. Established connections keep
+/// the configuration they were accepted with.
+pub struct ReloadingTlsConfig {
+    config: TlsConfig,
+    current: RwLock<Arc<rustls::ServerConfig>>,
+    modified: Mutex<Vec<Option<SystemTime>>>,
+}
+
+impl ReloadingTlsConfig {
+    /// Load the initial configuration
+    pub fn new(config: TlsConfig) -> anyhow::Result<Self> {
+        let current = RwLock::new(Arc::new(config.load()?));
+        let modified = Mutex::new(modified_times(&config));
+        Ok(Self {
+            config,
+            current,
+            modified,
+        })
+    }
+
+    /// The configuration new connections are accepted with
+    pub fn current(&self) -> Arc<rustls::ServerConfig> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    /// Reload the configuration if any of its files changed
+    ///
+    /// Returns whether a new configuration was loaded.
+    pub fn reload_if_changed(&self) -> bool {
+        let times = modified_times(&self.config);
+        let mut modified = self.modified.lock().unwrap();
+        if *modified == times {
+            return false;
+        }
+        *modified = times;
+
+        match self.config.load() {
+            Ok(config) => {
+                *self.current.write().unwrap() = Arc::new(config);
+                tracing::info!(
+                    "Reloaded TLS certificate {}",
+                    self.config.cert_path.display()
+                );
+                true
+            }
+            Err(e) => {
+                tracing::error!("Keeping the current TLS certificate: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Check for changed files in the background
+    pub fn watch(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let tls = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tls.config.reload_interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                tls.reload_if_changed();
+            }
+        })
+    }
+}
+
+fn modified_times(config: &TlsConfig) -> Vec<Option<SystemTime>> {
+    config
+        .paths()
+        .into_iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Serve `app` over TLS until `shutdown` completes
+///
+/// Requests carry the peer address as [`ConnectInfo`] and their
+/// [`TlsSession`] as extensions. On shutdown the listener stops accepting and
+/// open connections finish their in-flight requests.
+pub async fn serve_tls(
+    listener: TcpListener,
+    app: Router,
+    tls: Arc<ReloadingTlsConfig>,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let acceptor = TlsAcceptor::from(tls.current());
+        let app = app.clone();
+        let watcher = graceful.watcher();
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::debug!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            };
+            let session = TlsSession::new(stream.get_ref().1);
+
+            let service = hyper::service::service_fn(move |mut request: hyper::Request<Incoming>| {
+                request.extensions_mut().insert(ConnectInfo(peer));
+                request.extensions_mut().insert(session.clone());
+                app.clone().oneshot(request)
+            });
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            if let Err(e) = watcher.watch(connection).await {
+                tracing::debug!("Connection from {} closed: {}", peer, e);
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_certificate_identity() {
+        let mut params = rcgen::CertificateParams::new(vec!["client.internal".to_string()]).unwrap();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "deploy-bot");
+        params.subject_alt_names.push(rcgen::SanType::URI(
+            "spiffe://example.org/ns/prod/sa/deployer".try_into().unwrap(),
+        ));
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+
+        let identity = ClientCertificate::from_der(cert.der()).unwrap();
+        assert_eq!(identity.common_name.as_deref(), Some("deploy-bot"));
+        assert_eq!(
+            identity.spiffe_id().unwrap().to_string(),
+            "spiffe://example.org/ns/prod/sa/deployer"
+        );
+
+        assert!(ClientCertificate::from_der(b"not a certificate").is_none());
+    }
+}
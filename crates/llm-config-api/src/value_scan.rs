@@ -0,0 +1,153 @@
+//! Scheduled scan of stored values for plaintext credentials
+//!
+//! Runs the devtools [`ConfigContentScanner`] over every store the server
+//! serves at a fixed interval and records each finding as a
+//! `security_event` (threat type `plaintext_secret`) in the audit log.
+//! A finding is recorded once, when a scan first sees it; it is recorded
+//! again only if it disappears and comes back.
+
+use llm_config_audit::{AuditEvent, AuditEventType, AuditLogger, AuditSeverity};
+use llm_config_core::ConfigManager;
+use llm_config_devtools::security::baseline::fingerprint;
+use llm_config_devtools::security::config_values::{
+    ConfigContentScanner, ValueFinding, PLAINTEXT_SECRET_CATEGORY,
+};
+use llm_config_devtools::security::Severity;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stores to scan, with the tenant they belong to (`None` in single-tenant mode)
+pub type ScanTargets = Vec<(Option<String>, Arc<ConfigManager>)>;
+
+/// Scan the stores returned by `targets` every `interval`, starting now
+pub fn spawn_value_scan<F>(interval: Duration, logger: Option<Arc<AuditLogger>>, targets: F)
+where
+    F: Fn() -> anyhow::Result<ScanTargets> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reported: HashMap<Option<String>, HashSet<String>> = HashMap::new();
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            let targets = match targets() {
+                Ok(targets) => targets,
+                Err(e) => {
+                    tracing::warn!("Value scan failed: {}", e);
+                    continue;
+                }
+            };
+
+            for (tenant, manager) in targets {
+                let scan = tokio::task::spawn_blocking(move || ConfigContentScanner::new(manager).scan())
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result.map_err(anyhow::Error::from));
+                let findings = match scan {
+                    Ok(findings) => findings,
+                    Err(e) => {
+                        tracing::warn!("Value scan failed: {}", e);
+                        continue;
+                    }
+                };
+
+                let seen = reported.entry(tenant.clone()).or_default();
+                for event in new_finding_events(&findings, seen, tenant.as_deref()) {
+                    tracing::warn!("Plaintext secret in stored config: {}", event.metadata["config"]);
+                    if let Some(logger) = &logger {
+                        if let Err(e) = logger.log(event) {
+                            tracing::warn!("Failed to audit value scan finding: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Audit events for the findings of a scan that the previous scan did not
+/// have; `reported` is replaced by the findings of this scan
+pub fn new_finding_events(
+    findings: &[ValueFinding],
+    reported: &mut HashSet<String>,
+    tenant: Option<&str>,
+) -> Vec<AuditEvent> {
+    let previous = std::mem::take(reported);
+    let mut events = Vec::new();
+    for found in findings {
+        let id = fingerprint(&found.finding);
+        if !previous.contains(&id) && !reported.contains(&id) {
+            events.push(audit_event(found, tenant));
+        }
+        reported.insert(id);
+    }
+    events
+}
+
+fn audit_event(found: &ValueFinding, tenant: Option<&str>) -> AuditEvent {
+    let severity = match found.finding.severity {
+        Severity::Critical => AuditSeverity::Critical,
+        Severity::High => AuditSeverity::Error,
+        Severity::Medium => AuditSeverity::Warning,
+        Severity::Low => AuditSeverity::Info,
+    };
+    let event = AuditEvent::new(
+        AuditEventType::SecurityEvent {
+            threat_type: PLAINTEXT_SECRET_CATEGORY.to_string(),
+            details: found.finding.message.clone(),
+        },
+        "system",
+    )
+    .with_severity(severity)
+    .with_metadata("config", format!("{}/{} ({})", found.namespace, found.key, found.environment))
+    .with_metadata("namespace", &found.namespace)
+    .with_metadata("key", &found.key)
+    .with_metadata("environment", found.environment.to_string())
+    .with_metadata("field", &found.field);
+    match tenant {
+        Some(tenant) => event.with_tenant(tenant),
+        None => event,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_core::{ConfigValue, Environment};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_new_finding_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+        let set_password = |key: &str, password: &str| {
+            manager
+                .set("app", key, ConfigValue::String(password.to_string()), Environment::Production, "user")
+                .unwrap();
+        };
+        let scan = || ConfigContentScanner::new(Arc::clone(&manager)).scan().unwrap();
+        let mut reported = HashSet::new();
+
+        set_password("db.password", "hunter22x");
+        let events = new_finding_events(&scan(), &mut reported, Some("acme"));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].severity, AuditSeverity::Critical);
+        assert_eq!(events[0].event_type.name(), "security_event");
+        assert_eq!(events[0].metadata["key"], "db.password");
+        assert_eq!(events[0].tenant_id.as_deref(), Some("acme"));
+
+        // Already reported
+        assert!(new_finding_events(&scan(), &mut reported, Some("acme")).is_empty());
+
+        set_password("cache.password", "s3cr3t-pw");
+        let events = new_finding_events(&scan(), &mut reported, Some("acme"));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].metadata["key"], "cache.password");
+
+        // Reported again after it was fixed and came back
+        manager.delete("app", "db.password", Environment::Production).unwrap();
+        assert!(new_finding_events(&scan(), &mut reported, None).is_empty());
+        set_password("db.password", "hunter22x");
+        assert_eq!(new_finding_events(&scan(), &mut reported, None).len(), 1);
+    }
+}
@@ -0,0 +1,1736 @@
+//! Configuration API behavior tests
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    response::Response,
+    Router,
+};
+use llm_config_api::{
+    create_router_with_state, ALREADY_EXISTS_ERROR_CODE, metrics_route, swagger_ui_route, ApiState, SecurityState,
+    NEXT_PAGE_TOKEN_HEADER, OPENAPI_PATH, PRECONDITION_FAILED_ERROR_CODE, PROTECTED_ERROR_CODE,
+    READ_ONLY_ERROR_CODE, SWAGGER_UI_PATH, TOTAL_COUNT_HEADER, TYPE_CHANGE_ERROR_CODE, VERSION_CONFLICT_ERROR_CODE,
+};
+use llm_config_audit::{AuditLogger, AuditStorage, FileAuditStorage};
+use llm_config_cache::CacheManager;
+use llm_config_core::{ConfigManager, ConfigValue, Environment, EnvironmentProtection, ProtectionLevel, SetOptions};
+use llm_config_metrics::MetricsRegistry;
+use llm_config_security::{
+    InputValidator, PolicyEnforcer, RateLimitConfig, RateLimiter, SecurityPolicy,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+fn create_app() -> (Router, TempDir) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+
+    (create_app_with_manager(manager), temp_dir)
+}
+
+fn create_app_with_manager(manager: Arc<ConfigManager>) -> Router {
+    create_app_with_state(ApiState::new(manager))
+}
+
+fn create_app_with_state(state: ApiState) -> Router {
+    let policy = SecurityPolicy {
+        require_tls: false,
+        ..SecurityPolicy::default()
+    };
+
+    let security_state = SecurityState::with_components(
+        RateLimiter::new(RateLimitConfig::default()),
+        InputValidator::default(),
+        PolicyEnforcer::new(policy),
+    );
+
+    create_router_with_state(state, security_state)
+}
+
+fn request(method: &str, uri: &str, body: Option<serde_json::Value>) -> Request<Body> {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .extension(ConnectInfo(addr));
+
+    match body {
+        Some(body) => builder.body(Body::from(body.to_string())).unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+async fn json_body(response: Response) -> serde_json::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_implicit_type_change_rejected() {
+    let (app, _temp_dir) = create_app();
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/timeout",
+            Some(serde_json::json!({ "value": 30, "env": "development" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/timeout",
+            Some(serde_json::json!({ "value": "30s", "env": "development" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let json = json_body(response).await;
+    assert_eq!(json["code"], TYPE_CHANGE_ERROR_CODE);
+
+    let response = app
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/timeout",
+            Some(serde_json::json!({
+                "value": "30s",
+                "env": "development",
+                "allow_type_change": true
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["value"], "30s");
+    assert_eq!(json["version"], 2);
+}
+
+#[tokio::test]
+async fn test_review_required_environment() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    manager
+        .set_protection(
+            EnvironmentProtection::new()
+                .with_level(Environment::Staging, ProtectionLevel::ReviewRequired),
+        )
+        .unwrap();
+    let app = create_app_with_manager(manager);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/timeout",
+            Some(serde_json::json!({ "value": 30, "env": "staging", "user": "alice" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let json = json_body(response).await;
+    assert_eq!(json["code"], PROTECTED_ERROR_CODE);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/timeout",
+            Some(serde_json::json!({
+                "value": 30,
+                "env": "staging",
+                "user": "alice",
+                "reviewed_by": "bob"
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/configs/app/timeout?env=staging", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .oneshot(request(
+            "DELETE",
+            "/api/v1/configs/app/timeout?env=staging&reviewed_by=bob",
+            None,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_changelog() {
+    let (app, _temp_dir) = create_app();
+
+    for (model, description) in [("gpt-4", "Initial model"), ("gpt-4o", "Upgrade model")] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/configs/serving/model",
+                Some(serde_json::json!({
+                    "value": model,
+                    "env": "production",
+                    "user": "alice",
+                    "change_description": description
+                })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/changelog/serving?since=7d&env=production", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    let changes = json["groups"][0]["changes"].as_array().unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0]["description"], "Upgrade model");
+    assert_eq!(changes[0]["old_value"], "gpt-4");
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/changelog/serving?format=markdown", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let markdown = String::from_utf8(body.to_vec()).unwrap();
+    assert!(markdown.contains("## model (production)"));
+    assert!(markdown.contains("Upgrade model"));
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/changelog/serving?since=yesterday", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_namespace_activity() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path().join("data")).unwrap());
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let logger = Arc::new(AuditLogger::new(audit_storage.clone()));
+    logger.record_changes(manager.subscribe_changes());
+    let app = create_app_with_state(ApiState::new(Arc::clone(&manager)).with_audit_logger(logger));
+
+    for (namespace, model) in [("serving", "gpt-4"), ("serving", "gpt-4o"), ("other", "claude")] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                &format!("/api/v1/configs/{}/model", namespace),
+                Some(serde_json::json!({ "value": model, "env": "production", "user": "alice" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // Change events reach the audit log asynchronously
+    let mut activity = Vec::new();
+    for _ in 0..50 {
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/api/v1/configs/serving/activity?env=production", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        activity = json_body(response).await.as_array().unwrap().clone();
+        if activity.len() == 4 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(activity.len(), 4);
+    let versions: Vec<_> = activity.iter().filter(|entry| entry["source"] == "history").collect();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0]["value"], "gpt-4o");
+    assert_eq!(versions[0]["version"], 2);
+    assert_eq!(versions[1]["value"], "gpt-4");
+    assert!(activity.iter().all(|entry| entry["user"] == "alice"));
+    let timestamps: Vec<_> = activity.iter().map(|entry| entry["timestamp"].as_str().unwrap()).collect();
+    assert!(timestamps.windows(2).all(|pair| pair[0] >= pair[1]));
+    let actions: Vec<_> = activity.iter().map(|entry| entry["action"].as_str().unwrap()).collect();
+    assert!(actions.contains(&"config_created"));
+    assert!(actions.contains(&"config_updated"));
+    assert!(activity.iter().all(|entry| entry["key"] == "model"));
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/serving/activity?limit=1", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await.as_array().unwrap().len(), 1);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/serving/activity?env=staging", None))
+        .await
+        .unwrap();
+    assert!(json_body(response).await.as_array().unwrap().is_empty());
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/serving/activity?limit=0", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_typed_values() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let app = create_app_with_manager(Arc::clone(&manager));
+
+    for (key, value_type, value, stored) in [
+        ("timeout", "duration", "90s", "1m30s"),
+        ("max_upload", "size", "512MiB", "512MiB"),
+        ("endpoint", "url", "https://api.openai.com/v1", "https://api.openai.com/v1"),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                &format!("/api/v1/configs/serving/{}", key),
+                Some(serde_json::json!({ "value": value, "type": value_type, "env": "production" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", &format!("/api/v1/configs/serving/{}?env=production", key), None))
+            .await
+            .unwrap();
+        let json = json_body(response).await;
+        assert_eq!(json["value"], stored);
+        assert_eq!(json["type"], value_type);
+    }
+
+    let entry = manager.get("serving", "timeout", Environment::Production).unwrap().unwrap();
+    assert_eq!(entry.value.as_duration(), Some(std::time::Duration::from_secs(90)));
+    let entry = manager.get("serving", "max_upload", Environment::Production).unwrap().unwrap();
+    assert_eq!(entry.value.as_bytes(), Some(512 << 20));
+
+    let response = app
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/serving/timeout",
+            Some(serde_json::json!({ "value": "soon", "type": "duration", "env": "production" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_promote() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let app = create_app_with_manager(Arc::clone(&manager));
+    for (key, value) in [("model", "gpt-4"), ("region", "eu")] {
+        manager
+            .set("app", key, ConfigValue::String(value.into()), Environment::Staging, "alice")
+            .unwrap();
+    }
+
+    let promote = |body: serde_json::Value| request("POST", "/api/v1/configs/app/promote", Some(body));
+    let response = app
+        .clone()
+        .oneshot(promote(serde_json::json!({ "from": "staging", "to": "production", "dry_run": true })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let plan = json_body(response).await;
+    assert_eq!(plan["operation"], "promote");
+    assert_eq!(plan["summary"]["create"], 2);
+    assert!(manager.get("app", "model", Environment::Production).unwrap().is_none());
+
+    let response = app
+        .clone()
+        .oneshot(promote(serde_json::json!({ "from": "staging", "to": "production", "keys": ["model"] })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let plan = json_body(response).await;
+    assert_eq!(plan["changes"][0]["key"], "model");
+    assert_eq!(plan["changes"][0]["new_value"], "gpt-4");
+    assert!(manager.get("app", "model", Environment::Production).unwrap().is_some());
+    assert!(manager.get("app", "region", Environment::Production).unwrap().is_none());
+
+    let response = app
+        .oneshot(promote(serde_json::json!({ "from": "staging", "to": "staging" })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_clone_and_rename_namespace() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let app = create_app_with_manager(Arc::clone(&manager));
+    for env in [Environment::Staging, Environment::Production] {
+        manager
+            .set("team-a", "model", ConfigValue::String("gpt-4".into()), env, "alice")
+            .unwrap();
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/namespaces/team-a/clone",
+            Some(serde_json::json!({ "to": "sandbox", "envs": ["staging"] })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["configs"], 1);
+    assert!(manager.get("sandbox", "model", Environment::Staging).unwrap().is_some());
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/namespaces/team-a/rename",
+            Some(serde_json::json!({ "to": "sandbox" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(json_body(response).await["code"], ALREADY_EXISTS_ERROR_CODE);
+
+    let response = app
+        .oneshot(request(
+            "POST",
+            "/api/v1/namespaces/team-a/rename",
+            Some(serde_json::json!({ "to": "team-b" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["configs"], 2);
+    assert!(manager.list("team-a", Environment::Production).unwrap().is_empty());
+    assert_eq!(manager.get_history("team-b", "model", Environment::Production).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_cached_reads_and_warmup() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path().join("storage")).unwrap());
+    let env = Environment::Development;
+    manager.set("llm", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+
+    let cache = CacheManager::new(100, temp_dir.path().join("cache"))
+        .unwrap()
+        .with_preload(Arc::clone(&manager), vec!["llm".to_string()], None);
+    let cache = Arc::new(cache);
+    cache.subscribe_invalidations(manager.subscribe_changes());
+    let app = create_app_with_state(ApiState::new(Arc::clone(&manager)).with_cache(Arc::clone(&cache)));
+
+    // Without namespaces, the preloaded ones are warmed
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/admin/cache/warm", Some(serde_json::json!({}))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["namespaces"], 1);
+    assert_eq!(body["entries"], 1);
+    assert_eq!(cache.l1_stats().size, 1);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/llm/model?env=development", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await["value"], "gpt-4");
+    assert_eq!(cache.l1_stats().hit_count, 1);
+
+    // Writes invalidate the cached entry
+    manager.set("llm", "model", ConfigValue::String("gpt-4o".into()), env, "alice").unwrap();
+    for _ in 0..100 {
+        if cache.l1_stats().size == 0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/llm/model?env=development", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await["value"], "gpt-4o");
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/admin/cache/warm",
+            Some(serde_json::json!({ "namespaces": ["llm"], "env": "nowhere" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_warmup_without_cache() {
+    let (app, _temp_dir) = create_app();
+    let response = app
+        .oneshot(request("POST", "/api/v1/admin/cache/warm", Some(serde_json::json!({ "namespaces": ["app"] }))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_cache_admin() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path().join("storage")).unwrap());
+    let env = Environment::Development;
+    for namespace in ["llm", "rag"] {
+        manager.set(namespace, "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+    }
+    let cache = Arc::new(CacheManager::new(100, temp_dir.path().join("cache")).unwrap());
+    cache.warm(&manager, &["llm", "rag"], Some(env)).unwrap();
+    let app = create_app_with_state(ApiState::new(Arc::clone(&manager)).with_cache(Arc::clone(&cache)));
+
+    let response = app.clone().oneshot(request("GET", "/api/v1/admin/cache", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["l1"]["entries"], 2);
+    assert_eq!(body["l2"]["entries"], 2);
+    assert!(body["l2"]["bytes"].as_u64().unwrap() > 0);
+    assert_eq!(body["namespaces"]["rag"]["entries"], 1);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/admin/cache/entries/llm/model?env=development", None))
+        .await
+        .unwrap();
+    let body = json_body(response).await;
+    assert_eq!(body["tier"], "l1");
+    assert_eq!(body["cached_version"], 1);
+    assert_eq!(body["stale"], false);
+
+    // A write the cache did not see leaves a stale entry
+    manager.set("llm", "model", ConfigValue::String("gpt-4o".into()), env, "alice").unwrap();
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/admin/cache/entries/llm/model?env=development", None))
+        .await
+        .unwrap();
+    let body = json_body(response).await;
+    assert_eq!(body["stored_version"], 2);
+    assert_eq!(body["stale"], true);
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/admin/cache?tier=l1&namespace=llm", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["l1"]["entries"], 1);
+    assert_eq!(body["l2"]["entries"], 2);
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/admin/cache/entries/llm/model?env=development", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await["tier"], "l2");
+
+    let response = app.clone().oneshot(request("DELETE", "/api/v1/admin/cache", None)).await.unwrap();
+    let body = json_body(response).await;
+    assert_eq!(body["l1"]["entries"], 0);
+    assert_eq!(body["l2"]["entries"], 0);
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/admin/cache/entries/llm/model?env=development", None))
+        .await
+        .unwrap();
+    let body = json_body(response).await;
+    assert!(body["tier"].is_null());
+    assert_eq!(body["stale"], false);
+}
+
+#[tokio::test]
+async fn test_read_only_mode() {
+    let (app, _temp_dir) = create_app();
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PUT",
+            "/api/v1/admin/read-only",
+            Some(serde_json::json!({ "reason": "nightly backup" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["read_only"], true);
+    assert_eq!(body["reason"], "nightly backup");
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/model",
+            Some(serde_json::json!({ "value": "gpt-4", "env": "development" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(json_body(response).await["code"], READ_ONLY_ERROR_CODE);
+
+    let response = app.clone().oneshot(request("GET", "/health", None)).await.unwrap();
+    let body = json_body(response).await;
+    assert_eq!(body["mode"], "read-only");
+    assert_eq!(body["mode_reason"], "nightly backup");
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/admin/read-only", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await["read_only"], false);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/model",
+            Some(serde_json::json!({ "value": "gpt-4", "env": "development" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.oneshot(request("GET", "/health", None)).await.unwrap();
+    assert_eq!(json_body(response).await["mode"], "read-write");
+}
+
+#[tokio::test]
+async fn test_retention_settings() {
+    let (app, _temp_dir) = create_app();
+    for value in ["gpt-3.5", "gpt-4", "gpt-4o"] {
+        app.clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/configs/app/model",
+                Some(serde_json::json!({ "value": value, "env": "development" })),
+            ))
+            .await
+            .unwrap();
+    }
+
+    let response = app.clone().oneshot(request("GET", "/api/v1/admin/retention", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["config"]["namespace"], serde_json::Value::Null);
+
+    let settings = serde_json::json!({
+        "default": { "trash_days": 90 },
+        "namespace": [{ "pattern": "app/**", "history": { "keep_last": 1 } }],
+        "audit": { "keep_days": 365 },
+    });
+    let response = app
+        .clone()
+        .oneshot(request("PUT", "/api/v1/admin/retention", Some(settings)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/admin/retention?namespace=app", None))
+        .await
+        .unwrap();
+    let body = json_body(response).await;
+    assert_eq!(body["config"]["audit"]["keep_days"], 365);
+    assert_eq!(body["effective"]["history"]["keep_last"], 1);
+    assert_eq!(body["effective"]["trash_days"], 90);
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/admin/retention/apply", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await["pruned_versions"], 2);
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/model/history?env=development", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await.as_array().unwrap().len(), 1);
+
+    let invalid = serde_json::json!({ "namespace": [{ "pattern": " " }] });
+    let response = app
+        .oneshot(request("PUT", "/api/v1/admin/retention", Some(invalid)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_config_stats() {
+    let (app, _temp_dir) = create_app();
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/model",
+            Some(serde_json::json!({ "value": "gpt-4", "env": "staging" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/api/v1/configs/app/model?env=staging", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/model/stats?env=staging", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["reads"], 2);
+    assert!(body["last_accessed"].is_string());
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/app/model/stats?env=production", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_dry_run() {
+    let (app, _temp_dir) = create_app();
+
+    for value in ["gpt-4", "gpt-4o"] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/configs/app/model",
+                Some(serde_json::json!({ "value": value, "env": "staging" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/model?dry_run=true",
+            Some(serde_json::json!({ "value": "gpt-5", "env": "staging" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["version"], 3);
+    assert_eq!(body["plan"]["changes"][0]["action"], "update");
+    assert_eq!(body["plan"]["changes"][0]["old_value"], "gpt-4o");
+    assert_eq!(body["plan"]["changes"][0]["new_value"], "gpt-5");
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/configs/app/model/rollback/1?env=staging&dry_run=true", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["version"], 3);
+    assert_eq!(body["plan"]["changes"][0]["new_value"], "gpt-4");
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/configs/app/model?env=staging&dry_run=true", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert!(body["version"].is_null());
+    assert_eq!(body["plan"]["changes"][0]["action"], "delete");
+
+    // Nothing was written
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/app/model?env=staging", None))
+        .await
+        .unwrap();
+    let body = json_body(response).await;
+    assert_eq!(body["value"], "gpt-4o");
+    assert_eq!(body["version"], 2);
+}
+
+#[tokio::test]
+async fn test_get_config_type_warning() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let env = Environment::Staging;
+    manager.set("app", "max_tokens", ConfigValue::Integer(4096), env, "alice").unwrap();
+    manager
+        .set_with_options(
+            "app",
+            "max_tokens",
+            ConfigValue::String("8192".into()),
+            env,
+            "alice",
+            &SetOptions::default().with_allow_type_change(true),
+        )
+        .unwrap();
+    let app = create_app_with_manager(manager);
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/app/max_tokens?env=staging", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["value"], "8192");
+    assert_eq!(body["type_warning"]["expected"], "integer");
+    assert_eq!(body["type_warning"]["actual"], "string");
+    assert_eq!(body["type_warning"]["coerced"], 8192);
+}
+
+#[tokio::test]
+async fn test_update_metadata() {
+    let (app, _temp_dir) = create_app();
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PATCH",
+            "/api/v1/configs/app/model/metadata",
+            Some(serde_json::json!({ "tags": ["llm"] })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/model",
+            Some(serde_json::json!({ "value": "gpt-4", "env": "development" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PATCH",
+            "/api/v1/configs/app/model/metadata",
+            Some(serde_json::json!({ "tags": ["llm", "chat"], "description": "Default model" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["metadata"]["tags"], serde_json::json!(["llm", "chat"]));
+    assert_eq!(json["version"], 1);
+
+    // Omitted fields are left unchanged
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PATCH",
+            "/api/v1/configs/app/model/metadata",
+            Some(serde_json::json!({ "description": "Chat model" })),
+        ))
+        .await
+        .unwrap();
+    let json = json_body(response).await;
+    assert_eq!(json["metadata"]["tags"], serde_json::json!(["llm", "chat"]));
+    assert_eq!(json["metadata"]["description"], "Chat model");
+
+    let response = app
+        .oneshot(request(
+            "PATCH",
+            "/api/v1/configs/app/model/metadata",
+            Some(serde_json::json!({ "tags": [""] })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_batch_get() {
+    let (app, _temp_dir) = create_app();
+
+    for (key, value) in [("model", serde_json::json!("gpt-4")), ("max_tokens", serde_json::json!(512))] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                &format!("/api/v1/configs/app/{}", key),
+                Some(serde_json::json!({ "value": value, "env": "development" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/batch-get",
+            Some(serde_json::json!({ "keys": ["max_tokens", "missing", "model"] })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["key"], "max_tokens");
+    assert_eq!(results[0]["status"], "found");
+    assert_eq!(results[0]["config"]["value"], 512);
+    assert_eq!(results[1]["status"], "not_found");
+    assert!(results[1]["config"].is_null());
+    assert_eq!(results[2]["config"]["value"], "gpt-4");
+
+    let response = app
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/batch-get",
+            Some(serde_json::json!({ "keys": [] })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_resolved_snapshot() {
+    let (app, _temp_dir) = create_app();
+
+    for (key, value, env) in [
+        ("model", serde_json::json!("gpt-4"), "base"),
+        ("timeout", serde_json::json!(30), "base"),
+        ("model", serde_json::json!("gpt-4o"), "production"),
+        ("debug", serde_json::json!(true), "development"),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                &format!("/api/v1/configs/app/{}", key),
+                Some(serde_json::json!({ "value": value, "env": env })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/resolved?env=production", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["environment"], "production");
+    // Development values do not apply to production
+    assert_eq!(json["values"], serde_json::json!({ "model": "gpt-4o", "timeout": 30 }));
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/app/resolved?env=edge", None))
+        .await
+        .unwrap();
+    let json = json_body(response).await;
+    assert_eq!(json["values"], serde_json::json!({ "model": "gpt-4", "timeout": 30 }));
+}
+
+#[tokio::test]
+async fn test_resolved_parent_namespace() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let env = Environment::Production;
+    manager.set("org/app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+    manager.set("org/app/svc", "timeout", ConfigValue::Integer(5), env, "alice").unwrap();
+    manager.set_namespace_parent("org/app/svc", Some("org/app")).unwrap();
+    let app = create_app_with_manager(manager);
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/org%2Fapp%2Fsvc/resolved?env=production", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["values"], serde_json::json!({ "model": "gpt-4", "timeout": 5 }));
+    assert_eq!(json["lineage"], serde_json::json!(["org/app/svc", "org/app"]));
+    assert_eq!(
+        json["sources"]["model"],
+        serde_json::json!([{ "namespace": "org/app", "environment": "production", "version": 1 }])
+    );
+    assert_eq!(json["sources"]["timeout"][0]["namespace"], "org/app/svc");
+}
+
+#[tokio::test]
+async fn test_explain() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    manager.set("org/app", "model", ConfigValue::String("gpt-4".into()), Environment::Base, "alice").unwrap();
+    manager.set_namespace_parent("org/app/svc", Some("org/app")).unwrap();
+    let app = create_app_with_manager(manager);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/org%2Fapp%2Fsvc/model/explain?env=production", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["value"], "gpt-4");
+    assert_eq!(json["namespaces"], serde_json::json!(["org/app/svc", "org/app"]));
+    assert_eq!(json["environments"], serde_json::json!(["base", "production"]));
+    assert_eq!(json["steps"].as_array().unwrap().len(), 4);
+    assert_eq!(
+        json["steps"][2],
+        serde_json::json!({ "namespace": "org/app", "environment": "base", "version": 1, "merge": "set" })
+    );
+    assert_eq!(json["source"]["namespace"], "org/app");
+    assert_eq!(json["decrypted"], false);
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/org%2Fapp%2Fsvc/missing/explain", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert!(json["value"].is_null() && json["source"].is_null());
+}
+
+#[tokio::test]
+async fn test_environment_inheritance() {
+    let (app, _temp_dir) = create_app();
+
+    for (value, env) in [("base", "base"), ("staging", "staging")] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/configs/app/model",
+                Some(serde_json::json!({ "value": value, "env": env })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/environments", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["parents"]["production"], "base");
+    assert_eq!(json["chains"]["production"], serde_json::json!(["base", "production"]));
+
+    let resolved = || request("GET", "/api/v1/configs/app/resolved?env=production", None);
+    let json = json_body(app.clone().oneshot(resolved()).await.unwrap()).await;
+    assert_eq!(json["values"]["model"], "base");
+
+    // Production inherits from staging once staging is its parent
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PUT",
+            "/api/v1/environments/production",
+            Some(serde_json::json!({ "parent": "staging" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["chains"]["production"], serde_json::json!(["base", "staging", "production"]));
+
+    let json = json_body(app.clone().oneshot(resolved()).await.unwrap()).await;
+    assert_eq!(json["values"]["model"], "staging");
+
+    // Cycles are rejected
+    let response = app
+        .oneshot(request(
+            "PUT",
+            "/api/v1/environments/staging",
+            Some(serde_json::json!({ "parent": "production" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_prompt_templates() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path().join("data")).unwrap());
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let state = ApiState::new(manager).with_audit_logger(Arc::new(AuditLogger::new(audit_storage.clone())));
+    let app = create_app_with_state(state);
+
+    for content in ["Answer as {{persona}}: {{question}}", "You are {{persona}}. {{question}}"] {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                "/api/v1/templates/qa",
+                Some(serde_json::json!({
+                    "content": content,
+                    "env": "base",
+                    "defaults": { "persona": "a helpful assistant" }
+                })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/templates/qa/render",
+            Some(serde_json::json!({ "env": "production", "variables": { "question": "Why?" } })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["output"], "You are a helpful assistant. Why?");
+    assert_eq!(json["version"], 2);
+    assert_eq!(json["environment"], "base");
+
+    // Missing variables are a client error
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/templates/qa/render", Some(serde_json::json!({}))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Templates share the config history and rollback workflow
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/configs/_templates/qa/rollback/1?env=base", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .oneshot(request("GET", "/api/v1/templates/qa?env=staging", None))
+        .await
+        .unwrap();
+    let json = json_body(response).await;
+    assert_eq!(json["template"]["content"], "Answer as {{persona}}: {{question}}");
+
+    let mut rendered = Vec::new();
+    for _ in 0..50 {
+        rendered = audit_storage
+            .read_all()
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.event_type.name() == "template_rendered")
+            .collect();
+        if !rendered.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(rendered.len(), 1);
+}
+
+#[tokio::test]
+async fn test_blueprints() {
+    let (app, _temp_dir) = create_app();
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PUT",
+            "/api/v1/blueprints/llm-app",
+            Some(serde_json::json!({
+                "defaults": { "model": "gpt-4o-mini" },
+                "entries": {
+                    "model": { "template": "{{model}}" },
+                    "max_tokens": { "template": "{{max_tokens}}", "type": "integer" }
+                }
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let apply = |variables: serde_json::Value| {
+        Some(serde_json::json!({ "namespace": "bot", "env": "staging", "variables": variables }))
+    };
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/blueprints/llm-app/apply", apply(serde_json::json!({}))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/blueprints/llm-app/apply",
+            apply(serde_json::json!({ "max_tokens": "2048" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["created"], serde_json::json!(["max_tokens", "model"]));
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/bot/max_tokens?env=staging", None))
+        .await
+        .unwrap();
+    assert_eq!(json_body(response).await["value"], 2048);
+
+    // Applying again would overwrite the entries
+    let response = app
+        .oneshot(request(
+            "POST",
+            "/api/v1/blueprints/llm-app/apply",
+            apply(serde_json::json!({ "max_tokens": "2048" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+fn with_header(mut request: Request<Body>, name: &'static str, value: &str) -> Request<Body> {
+    request.headers_mut().insert(name, value.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn test_conditional_get() {
+    let (app, _temp_dir) = create_app();
+    let set = |value: i64| Some(serde_json::json!({ "value": value, "env": "development" }));
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/configs/app/timeout", set(30)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+
+    // Unchanged entries and lists answer If-None-Match with 304
+    let get = || request("GET", "/api/v1/configs/app/timeout?env=development", None);
+    let response = app.clone().oneshot(get()).await.unwrap();
+    assert_eq!(response.headers()["etag"], etag.as_str());
+    let response = app
+        .clone()
+        .oneshot(with_header(get(), "if-none-match", &etag))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let list = || request("GET", "/api/v1/configs/app?env=development", None);
+    let response = app.clone().oneshot(list()).await.unwrap();
+    let list_etag = response.headers()["etag"].to_str().unwrap().to_string();
+    let response = app
+        .clone()
+        .oneshot(with_header(list(), "if-none-match", &list_etag))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // Any change to the namespace changes the list ETag
+    app.clone()
+        .oneshot(request("POST", "/api/v1/configs/app/retries", set(3)))
+        .await
+        .unwrap();
+    let response = app
+        .oneshot(with_header(list(), "if-none-match", &list_etag))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(response.headers()["etag"], list_etag.as_str());
+}
+
+#[tokio::test]
+async fn test_conditional_write() {
+    let (app, _temp_dir) = create_app();
+    let set = |value: i64| Some(serde_json::json!({ "value": value, "env": "development" }));
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/configs/app/timeout", set(30)))
+        .await
+        .unwrap();
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+
+    // A write with the current ETag succeeds and yields a new one
+    let response = app
+        .clone()
+        .oneshot(with_header(
+            request("POST", "/api/v1/configs/app/timeout", set(60)),
+            "if-match",
+            &etag,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let new_etag = response.headers()["etag"].to_str().unwrap().to_string();
+    assert_ne!(new_etag, etag);
+    // A second writer holding the old ETag is rejected instead of overwriting
+    let response = app
+        .clone()
+        .oneshot(with_header(
+            request("POST", "/api/v1/configs/app/timeout", set(90)),
+            "if-match",
+            &etag,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(json_body(response).await["code"], PRECONDITION_FAILED_ERROR_CODE);
+
+    let delete = || request("DELETE", "/api/v1/configs/app/timeout?env=development", None);
+    let response = app
+        .clone()
+        .oneshot(with_header(delete(), "if-match", &etag))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    let response = app
+        .clone()
+        .oneshot(with_header(delete(), "if-match", &new_etag))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // If-Match: * requires the entry to exist
+    let response = app
+        .oneshot(with_header(
+            request("POST", "/api/v1/configs/app/timeout", set(30)),
+            "if-match",
+            "*",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+#[tokio::test]
+async fn test_expected_version_conflict() {
+    let (app, _temp_dir) = create_app();
+    let set = |value: i64, expected: u64| {
+        Some(serde_json::json!({ "value": value, "env": "development", "expected_version": expected }))
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/configs/app/timeout", set(30, 0)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/configs/app/timeout", set(60, 1)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["version"], 2);
+
+    // A second operator still working from version 1
+    let response = app
+        .oneshot(request("POST", "/api/v1/configs/app/timeout", set(90, 1)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = json_body(response).await;
+    assert_eq!(body["code"], VERSION_CONFLICT_ERROR_CODE);
+    assert!(body["message"].as_str().unwrap().contains("is at version 2, expected 1"));
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let registry = Arc::new(MetricsRegistry::new().unwrap());
+
+    let policy = SecurityPolicy {
+        require_tls: false,
+        ..SecurityPolicy::default()
+    };
+    let security_state = SecurityState::with_components(
+        RateLimiter::new(RateLimitConfig::default()),
+        InputValidator::default(),
+        PolicyEnforcer::new(policy),
+    )
+    .with_metrics(Arc::clone(&registry));
+    let state = ApiState::new(manager).with_metrics(Arc::clone(&registry));
+    let app = create_router_with_state(state, security_state)
+        .merge(metrics_route(Arc::clone(&registry)));
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/model",
+            Some(serde_json::json!({ "value": "gpt-4", "env": "production" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/missing?env=production", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/model?env=production;ls", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app.clone().oneshot(request("GET", "/metrics", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+
+    // Requests are labelled by route, not by raw path
+    assert!(text.contains(
+        "http_requests_total{method=\"POST\",path=\"/api/v1/configs/:namespace/:key\",status=\"200\"} 1"
+    ));
+    assert!(text.contains(
+        "http_requests_total{method=\"GET\",path=\"/api/v1/configs/:namespace/:key\",status=\"404\"} 1"
+    ));
+    assert!(text.contains(
+        "config_operations_total{environment=\"production\",operation=\"set\"} 1"
+    ));
+    assert!(text.contains("security_rejections_total{check=\"input_validation\"} 1"));
+
+    // Scrapers asking for OpenMetrics get request IDs as exemplars
+    let mut openmetrics = request("GET", "/metrics", None);
+    openmetrics
+        .headers_mut()
+        .insert("accept", "application/openmetrics-text; version=1.0.0".parse().unwrap());
+    let response = app.oneshot(openmetrics).await.unwrap();
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("application/openmetrics-text"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.ends_with("# EOF\n"));
+    assert!(text.contains("# TYPE http_requests counter"));
+    assert!(text
+        .lines()
+        .any(|line| line.starts_with("http_request_duration_seconds_bucket{method=\"POST\"")
+            && line.contains(" # {request_id=\"")));
+}
+
+#[tokio::test]
+async fn test_operation_context_audited() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path().join("data")).unwrap());
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let logger = Arc::new(AuditLogger::new(audit_storage.clone()));
+    logger.record_changes(manager.subscribe_changes());
+    let app = create_app_with_state(ApiState::new(Arc::clone(&manager)).with_audit_logger(logger));
+
+    let set = request(
+        "POST",
+        "/api/v1/configs/app/model",
+        Some(serde_json::json!({ "value": "gpt-4", "env": "production", "user": "alice" })),
+    );
+    let set = with_header(with_header(set, "x-request-id", "req-42"), "x-session-id", "session-1");
+    let response = app.clone().oneshot(set).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-request-id"], "req-42");
+
+    let entry = manager.get("app", "model", Environment::Production).unwrap().unwrap();
+    let context = entry.metadata.context.unwrap();
+    assert_eq!(context.ip_address.as_deref(), Some("127.0.0.1"));
+    assert_eq!(context.session_id.as_deref(), Some("session-1"));
+    assert_eq!(context.request_id.as_deref(), Some("req-42"));
+
+    // Requests without an ID get a generated one
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/app/model?env=production", None))
+        .await
+        .unwrap();
+    assert!(!response.headers()["x-request-id"].is_empty());
+
+    let mut created = Vec::new();
+    for _ in 0..50 {
+        created = audit_storage
+            .read_all()
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.event_type.name() == "config_created")
+            .collect();
+        if !created.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].user, "alice");
+    assert_eq!(created[0].source_ip.as_deref(), Some("127.0.0.1"));
+    assert_eq!(created[0].request_id.as_deref(), Some("req-42"));
+    assert_eq!(created[0].metadata["session_id"], "session-1");
+}
+
+#[tokio::test]
+async fn test_history_pagination() {
+    let (app, _temp_dir) = create_app();
+
+    for value in 1..=3 {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/configs/app/timeout",
+                Some(serde_json::json!({ "value": value, "env": "development" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let versions = |body: serde_json::Value| -> Vec<u64> {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v["version"].as_u64().unwrap())
+            .collect()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/timeout/history?limit=2", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(versions(json_body(response).await), vec![3, 2]);
+
+    let response = app
+        .oneshot(request(
+            "GET",
+            "/api/v1/configs/app/timeout/history?limit=2&before_version=2",
+            None,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(versions(json_body(response).await), vec![1]);
+}
+
+#[tokio::test]
+async fn test_rollback_reason_and_diff() {
+    let (app, _temp_dir) = create_app();
+
+    for value in 1..=2 {
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/configs/app/timeout",
+                Some(serde_json::json!({ "value": value, "env": "development" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/timeout/rollback/1",
+            Some(serde_json::json!({ "reason": "timeouts in production" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["value"], 1);
+    assert_eq!(json["version"], 3);
+    assert_eq!(json["diff"]["from_version"], 2);
+    assert_eq!(json["diff"]["to_version"], 3);
+    assert_eq!(
+        json["diff"]["changes"],
+        serde_json::json!([{ "op": "changed", "path": "", "from": 2, "to": 1 }])
+    );
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/timeout/history?limit=1", None))
+        .await
+        .unwrap();
+    let json = json_body(response).await;
+    assert_eq!(
+        json[0]["change_description"],
+        "Rollback to version 1: timeouts in production"
+    );
+
+    let response = app
+        .oneshot(request(
+            "POST",
+            "/api/v1/configs/app/timeout/rollback/1",
+            Some(serde_json::json!({ "reason": 42 })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_openapi_document() {
+    let (app, _temp_dir) = create_app();
+
+    // Served without going through the security middleware
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(OPENAPI_PATH)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let doc = json_body(response).await;
+    assert_eq!(doc["info"]["title"], "LLM Config Manager API");
+    assert!(doc["paths"]["/api/v1/configs/{namespace}/{key}"]["post"].is_object());
+    assert!(doc["components"]["schemas"]["ErrorResponse"].is_object());
+
+    // Swagger UI is only served when merged in
+    let response = app
+        .clone()
+        .oneshot(request("GET", SWAGGER_UI_PATH, None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = app
+        .merge(swagger_ui_route())
+        .oneshot(request("GET", SWAGGER_UI_PATH, None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_list_pagination() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    for key in ["timeout", "model", "temperature", "max_tokens"] {
+        manager
+            .set("app", key, ConfigValue::Integer(1), Environment::Development, "alice")
+            .unwrap();
+    }
+    manager
+        .set_metadata("app", "model", Environment::Development, vec!["llm".into()], None)
+        .unwrap();
+    let app = create_app_with_manager(manager);
+
+    let list = |uri: &str| {
+        let app = app.clone();
+        let uri = uri.to_string();
+        async move {
+            let response = app.oneshot(request("GET", &uri, None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let total = response.headers()[TOTAL_COUNT_HEADER].to_str().unwrap().to_string();
+            let token = response
+                .headers()
+                .get(NEXT_PAGE_TOKEN_HEADER)
+                .map(|token| token.to_str().unwrap().to_string());
+            let body = json_body(response).await;
+            let keys: Vec<String> = body
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|config| config["key"].as_str().unwrap().to_string())
+                .collect();
+            (keys, total, token)
+        }
+    };
+
+    // Sorted by key, one page at a time
+    let (keys, total, token) = list("/api/v1/configs/app?limit=3").await;
+    assert_eq!(keys, vec!["max_tokens", "model", "temperature"]);
+    assert_eq!(total, "4");
+    let (keys, _, next) = list(&format!("/api/v1/configs/app?limit=3&page_token={}", token.unwrap())).await;
+    assert_eq!(keys, vec!["timeout"]);
+    assert!(next.is_none());
+    let (keys, _, _) = list("/api/v1/configs/app?limit=2&offset=1&sort_by=key&descending=true").await;
+    assert_eq!(keys, vec!["temperature", "model"]);
+
+    // Filters
+    let (keys, total, _) = list("/api/v1/configs/app?key_prefix=t").await;
+    assert_eq!((keys, total.as_str()), (vec!["temperature".to_string(), "timeout".to_string()], "2"));
+    let (keys, _, _) = list("/api/v1/configs/app?tag=llm").await;
+    assert_eq!(keys, vec!["model"]);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app?sort_by=size", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
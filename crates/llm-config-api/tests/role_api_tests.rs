@@ -0,0 +1,770 @@
+//! Custom role management API tests
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    Router,
+};
+use llm_config_api::{
+    create_router_with_state, ApiState, SecurityState, SECRET_ACCESS_LIMITED_ERROR_CODE,
+};
+use llm_config_audit::{AuditEvent, AuditLogger, AuditSeverity, AuditStorage, FileAuditStorage};
+use llm_config_core::{ConfigManager, ConfigValue, Environment};
+use llm_config_crypto::{Algorithm, SecretKey};
+use llm_config_rbac::{Action, Resource, Role, RoleAssignment, RoleStore};
+use llm_config_security::{
+    InputValidator, PolicyEnforcer, RateLimitConfig, RateLimiter, SecretAccessLimit,
+    SecretAccessLimiter, SecurityPolicy,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+fn create_test_state() -> (ApiState, TempDir) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let store = RoleStore::new(manager.storage().clone());
+    let state = ApiState::new(manager).with_role_store(store).unwrap();
+    state
+        .rbac
+        .write()
+        .unwrap()
+        .assign_role(RoleAssignment::new("admin", Role::Admin));
+    (state, temp_dir)
+}
+
+fn create_app(state: ApiState) -> Router {
+    let policy = SecurityPolicy {
+        require_tls: false,
+        ..SecurityPolicy::default()
+    };
+
+    let security_state = SecurityState::with_components(
+        RateLimiter::new(RateLimitConfig::default()),
+        InputValidator::default(),
+        PolicyEnforcer::new(policy),
+    )
+    .with_trusted_proxies(vec!["127.0.0.1".to_string()]);
+
+    create_router_with_state(state, security_state)
+}
+
+fn request(method: &str, uri: &str, body: Option<serde_json::Value>) -> Request<Body> {
+    request_as("admin", method, uri, body)
+}
+
+fn request_as(
+    user: &str,
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> Request<Body> {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("x-user-id", user)
+        .extension(ConnectInfo(addr));
+
+    match body {
+        Some(body) => builder.body(Body::from(body.to_string())).unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn test_define_and_delete_custom_role() {
+    let (state, temp_dir) = create_test_state();
+    let rbac = Arc::clone(&state.rbac);
+    let app = create_app(state);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PUT",
+            "/api/v1/roles/prompt-engineer",
+            Some(serde_json::json!({
+                "permissions": [
+                    {"resource": "config", "action": "read"},
+                    {"resource": "config", "action": "update"}
+                ],
+                "namespace_patterns": ["*/prompts/*"]
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Applied to the running enforcer
+    {
+        let mut rbac = rbac.write().unwrap();
+        rbac.assign_role(RoleAssignment::new(
+            "carol",
+            Role::Custom("prompt-engineer".to_string()),
+        ));
+        assert!(rbac.has_permission(
+            "carol",
+            &Resource::Config,
+            &Action::Update,
+            Some("team-a/prompts/summarize")
+        ));
+    }
+
+    // And persisted to storage
+    let store = RoleStore::open(temp_dir.path()).unwrap();
+    assert!(store.get("prompt-engineer").unwrap().is_some());
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/roles/prompt-engineer", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/roles/prompt-engineer", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(store.load_all().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_builtin_role_name_rejected() {
+    let (state, _temp_dir) = create_test_state();
+    let app = create_app(state);
+
+    let response = app
+        .oneshot(request(
+            "PUT",
+            "/api/v1/roles/admin",
+            Some(serde_json::json!({ "permissions": [] })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_role_management_requires_permission_without_enforcement() {
+    let (state, _temp_dir) = create_test_state();
+    let app = create_app(state);
+    let body = serde_json::json!({
+        "permissions": [{"resource": "config", "action": "update"}]
+    });
+
+    // Other users cannot define or delete roles even though RBAC is not enforced
+    let response = app
+        .clone()
+        .oneshot(request_as("mallory", "PUT", "/api/v1/roles/owner", Some(body)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .clone()
+        .oneshot(request_as("mallory", "DELETE", "/api/v1/roles/owner", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .oneshot(request_as("mallory", "PUT", "/api/v1/groups/owners", Some(serde_json::json!({}))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_rbac_enforced_with_namespace_scope() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let store = RoleStore::new(manager.storage().clone());
+    store
+        .assign(&RoleAssignment::with_scope("bob", Role::Editor, "team-*"))
+        .unwrap();
+
+    let state = ApiState::new(manager)
+        .with_role_store(store)
+        .unwrap()
+        .with_rbac_enforcement(true);
+    let app = create_app(state);
+
+    let body = serde_json::json!({ "value": "gpt-4", "env": "development" });
+
+    let response = app
+        .clone()
+        .oneshot(request_as("bob", "POST", "/api/v1/configs/team-a/model", Some(body.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(request_as("bob", "POST", "/api/v1/configs/ops/model", Some(body.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Users without assignments are denied
+    let response = app
+        .clone()
+        .oneshot(request_as("mallory", "GET", "/api/v1/configs/team-a/model", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Editors cannot manage roles
+    let response = app
+        .oneshot(request_as("bob", "GET", "/api/v1/roles", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_group_roles_from_external_groups() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let store = RoleStore::new(manager.storage().clone());
+    store.assign(&RoleAssignment::new("admin", Role::Admin)).unwrap();
+
+    let state = ApiState::new(manager.clone())
+        .with_role_store(store)
+        .unwrap()
+        .with_rbac_enforcement(true);
+    let app = create_app(state);
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PUT",
+            "/api/v1/groups/ml-platform",
+            Some(serde_json::json!({
+                "members": ["carol"],
+                "external_groups": ["sso-ml-eng"],
+                "roles": [{ "role": "editor", "scope": "ml" }]
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = serde_json::json!({ "value": "gpt-4", "env": "development" });
+    let sso_request = |user: &str, groups: &str| {
+        let mut request = request_as(user, "POST", "/api/v1/configs/ml/model", Some(body.clone()));
+        request
+            .headers_mut()
+            .insert("x-user-groups", groups.parse().unwrap());
+        request
+    };
+
+    // SSO users inherit the group's roles through their external groups
+    let response = app
+        .clone()
+        .oneshot(sso_request("bob", "sso-finance, sso-ml-eng"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(sso_request("bob", "sso-finance"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Groups sent by a client that is not a trusted proxy are ignored
+    let mut untrusted = sso_request("bob", "sso-ml-eng");
+    let client: SocketAddr = "10.0.0.2:8080".parse().unwrap();
+    untrusted.extensions_mut().insert(ConnectInfo(client));
+    let response = app.clone().oneshot(untrusted).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Listed members without a groups header
+    let response = app
+        .clone()
+        .oneshot(request_as("carol", "POST", "/api/v1/configs/ml/model", Some(body.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Persisted to the role store
+    let store = RoleStore::new(manager.storage().clone());
+    assert!(store.get_group("ml-platform").unwrap().is_some());
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/groups/ml-platform", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .oneshot(sso_request("bob", "sso-ml-eng"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_secret_reveal_requires_permission() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+    let manager = Arc::new(
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key),
+    );
+    manager
+        .set_secret("team-a", "api-key", b"sk-123", Environment::Development, "admin")
+        .unwrap();
+
+    let store = RoleStore::new(manager.storage().clone());
+    store.assign(&RoleAssignment::new("bob", Role::Editor)).unwrap();
+    store.assign(&RoleAssignment::new("root", Role::Admin)).unwrap();
+
+    let state = ApiState::new(manager)
+        .with_role_store(store)
+        .unwrap()
+        .with_rbac_enforcement(true)
+        .with_secret_reveal(true);
+    let app = create_app(state);
+
+    // Plain reads return the masked form
+    let response = app
+        .clone()
+        .oneshot(request_as("bob", "GET", "/api/v1/configs/team-a/api-key", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["value"], "<encrypted>");
+
+    // Editors may read secrets but not reveal them
+    let response = app
+        .clone()
+        .oneshot(request_as("bob", "GET", "/api/v1/configs/team-a/api-key?reveal=true", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .oneshot(request_as("root", "GET", "/api/v1/configs/team-a/api-key?reveal=true", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["value"], "sk-123");
+}
+
+#[tokio::test]
+async fn test_secret_reveal_disabled_by_default() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+    let manager = Arc::new(
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key),
+    );
+    manager
+        .set_secret("team-a", "api-key", b"sk-123", Environment::Development, "admin")
+        .unwrap();
+    let app = create_app(ApiState::new(manager));
+
+    // Refused even though RBAC is not enforced
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/team-a/api-key?reveal=true", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/team-a?reveal=true", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let reveal = serde_json::json!({ "keys": ["api-key"], "reveal": true });
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/configs/team-a/batch-get", Some(reveal)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Masked reads are unaffected
+    let response = app
+        .oneshot(request("GET", "/api/v1/configs/team-a/api-key", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_batch_get_secret_redaction() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+    let manager = Arc::new(
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key),
+    );
+    manager
+        .set_secret("team-a", "api-key", b"sk-123", Environment::Development, "admin")
+        .unwrap();
+    manager
+        .set("team-a", "model", ConfigValue::String("gpt-4".to_string()), Environment::Development, "admin")
+        .unwrap();
+
+    let store = RoleStore::new(manager.storage().clone());
+    store.assign(&RoleAssignment::new("vera", Role::Viewer)).unwrap();
+    store.assign(&RoleAssignment::new("root", Role::Admin)).unwrap();
+
+    let state = ApiState::new(manager)
+        .with_role_store(store)
+        .unwrap()
+        .with_rbac_enforcement(true)
+        .with_secret_reveal(true);
+    let app = create_app(state);
+    let body = serde_json::json!({ "keys": ["api-key", "model"] });
+
+    // Viewers get non-secret values but not secrets
+    let response = app
+        .clone()
+        .oneshot(request_as("vera", "POST", "/api/v1/configs/team-a/batch-get", Some(body.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(json["results"][0]["status"], "forbidden");
+    assert!(json["results"][0]["config"].is_null());
+    assert_eq!(json["results"][1]["config"]["value"], "gpt-4");
+
+    // Revealing requires the reveal permission
+    let reveal = serde_json::json!({ "keys": ["api-key"], "reveal": true });
+    let response = app
+        .clone()
+        .oneshot(request_as("vera", "POST", "/api/v1/configs/team-a/batch-get", Some(reveal.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .oneshot(request_as("root", "POST", "/api/v1/configs/team-a/batch-get", Some(reveal)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(json["results"][0]["status"], "found");
+    assert_eq!(json["results"][0]["config"]["value"], "sk-123");
+}
+
+#[tokio::test]
+async fn test_render_template_references() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+    let manager = Arc::new(
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key),
+    );
+    manager
+        .set_secret("team-a", "api-key", b"sk-123", Environment::Development, "admin")
+        .unwrap();
+    manager
+        .set("team-a", "model", ConfigValue::String("gpt-4".to_string()), Environment::Development, "admin")
+        .unwrap();
+
+    let store = RoleStore::new(manager.storage().clone());
+    store.assign(&RoleAssignment::new("vera", Role::Viewer)).unwrap();
+    store.assign(&RoleAssignment::new("root", Role::Admin)).unwrap();
+
+    let state = ApiState::new(manager)
+        .with_role_store(store)
+        .unwrap()
+        .with_rbac_enforcement(true)
+        .with_secret_reveal(true);
+    let app = create_app(state);
+
+    let template = serde_json::json!({
+        "content": "model={{config:team-a.model}} key={{secret:team-a.api-key}}"
+    });
+    let response = app
+        .clone()
+        .oneshot(request_as("root", "PUT", "/api/v1/templates/client", Some(template)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Secret references need the reveal permission in the referenced namespace
+    let render = serde_json::json!({});
+    let response = app
+        .clone()
+        .oneshot(request_as("vera", "POST", "/api/v1/templates/client/render", Some(render.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .oneshot(request_as("root", "POST", "/api/v1/templates/client/render", Some(render)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(json["output"], "model=gpt-4 key=sk-123");
+}
+
+#[tokio::test]
+async fn test_render_template_requires_secret_reason() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+    let manager = Arc::new(
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key),
+    );
+    manager
+        .set_secret("team-a", "api-key", b"sk-123", Environment::Development, "admin")
+        .unwrap();
+
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let state = ApiState::new(manager)
+        .with_audit_logger(Arc::new(AuditLogger::new(audit_storage.clone())))
+        .with_required_secret_reason(true)
+        .with_secret_reveal(true);
+    let app = create_app(state);
+
+    for (name, content) in [("client", "key={{secret:team-a.api-key}}"), ("plain", "hello {{name}}")] {
+        let template = serde_json::json!({ "content": content });
+        let uri = format!("/api/v1/templates/{}", name);
+        let response = app.clone().oneshot(request("PUT", &uri, Some(template))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // Rendering secrets needs a reason, like revealing them directly
+    let uri = "/api/v1/templates/client/render";
+    for render in [serde_json::json!({}), serde_json::json!({ "reason": " " })] {
+        let response = app.clone().oneshot(request("POST", uri, Some(render))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // Templates without secret references do not
+    let render = serde_json::json!({ "variables": { "name": "world" } });
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/templates/plain/render", Some(render)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let render = serde_json::json!({ "reason": "INC-7" });
+    let response = app.oneshot(request("POST", uri, Some(render))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut revealed = Vec::new();
+    for _ in 0..50 {
+        revealed = audit_storage
+            .read_all()
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.event_type.name() == "secret_revealed")
+            .collect::<Vec<_>>();
+        if !revealed.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(revealed.len(), 1);
+    assert_eq!(revealed[0].metadata["reason"], "INC-7");
+}
+
+#[tokio::test]
+async fn test_temporary_role_elevation() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+    let manager = Arc::new(
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key),
+    );
+    manager
+        .set_secret("payments", "api-key", b"sk-123", Environment::Production, "admin")
+        .unwrap();
+
+    let store = RoleStore::new(manager.storage().clone());
+    store.assign(&RoleAssignment::new("bob", Role::Viewer)).unwrap();
+    store.assign(&RoleAssignment::new("root", Role::Admin)).unwrap();
+
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let state = ApiState::new(manager)
+        .with_role_store(store)
+        .unwrap()
+        .with_rbac_enforcement(true)
+        .with_secret_reveal(true)
+        .with_audit_logger(Arc::new(AuditLogger::new(audit_storage.clone())));
+    let app = create_app(state);
+    let uri = "/api/v1/configs/payments/api-key?env=production&reveal=true";
+
+    let response = app.clone().oneshot(request_as("bob", "GET", uri, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Only admins may elevate, and a justification is required
+    let elevation = serde_json::json!({
+        "user": "bob",
+        "role": "admin",
+        "ttl_seconds": 900,
+        "justification": "INC-42: rotate leaked payment key"
+    });
+    let response = app
+        .clone()
+        .oneshot(request_as("bob", "POST", "/api/v1/admin/elevations", Some(elevation.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let mut unjustified = elevation.clone();
+    unjustified["justification"] = serde_json::json!("");
+    let response = app
+        .clone()
+        .oneshot(request_as("root", "POST", "/api/v1/admin/elevations", Some(unjustified)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .clone()
+        .oneshot(request_as("root", "POST", "/api/v1/admin/elevations", Some(elevation)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app.clone().oneshot(request_as("bob", "GET", uri, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(request_as("root", "GET", "/api/v1/admin/elevations", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["user"], "bob");
+    assert_eq!(json[0]["role"], "admin");
+    assert_eq!(json[0]["justification"], "INC-42: rotate leaked payment key");
+
+    // The grant and each permission the elevation was needed for (reading and
+    // revealing the secret) are critical audit events
+    let mut critical: Vec<AuditEvent> = Vec::new();
+    for _ in 0..50 {
+        critical = audit_storage
+            .read_all()
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.severity == AuditSeverity::Critical)
+            .collect();
+        if critical.len() == 3 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(critical.len(), 3);
+    assert_eq!(critical[0].user, "root");
+    assert_eq!(critical[0].metadata["elevated_user"], "bob");
+    assert_eq!(critical[1].user, "bob");
+    assert_eq!(critical[1].event_type.name(), "security_event");
+    assert_eq!(critical[1].metadata["permission"], "secret:read");
+    assert_eq!(critical[1].metadata["elevation_role"], "admin");
+    assert_eq!(critical[2].metadata["permission"], "secret:reveal");
+}
+
+#[tokio::test]
+async fn test_secret_reveal_reason_and_limit() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+    let manager = Arc::new(
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key),
+    );
+    manager
+        .set_secret("payments", "api-key", b"sk-123", Environment::Production, "admin")
+        .unwrap();
+
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let limit = SecretAccessLimit {
+        reads_per_minute: 1,
+        burst: 2,
+    };
+    let state = ApiState::new(manager)
+        .with_audit_logger(Arc::new(AuditLogger::new(audit_storage.clone())))
+        .with_secret_access_limiter(Arc::new(SecretAccessLimiter::new(limit)))
+        .with_required_secret_reason(true)
+        .with_secret_reveal(true);
+    let app = create_app(state);
+    let uri = "/api/v1/configs/payments/api-key?env=production&reveal=true";
+
+    for uri in [uri.to_string(), format!("{}&reason=%20", uri)] {
+        let response = app.clone().oneshot(request_as("bob", "GET", &uri, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let uri = format!("{}&reason=INC-42", uri);
+    for _ in 0..2 {
+        let response = app.clone().oneshot(request_as("bob", "GET", &uri, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = app.clone().oneshot(request_as("bob", "GET", &uri, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["code"], SECRET_ACCESS_LIMITED_ERROR_CODE);
+
+    // Claiming another user from the same client does not reset the limit
+    let response = app.clone().oneshot(request_as("carol", "GET", &uri, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Other clients and masked reads are not limited
+    let mut request = request_as("carol", "GET", &uri, None);
+    let other_client: SocketAddr = "10.0.0.2:8080".parse().unwrap();
+    request.extensions_mut().insert(ConnectInfo(other_client));
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .oneshot(request_as("bob", "GET", "/api/v1/configs/payments/api-key?env=production", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut events: Vec<AuditEvent> = Vec::new();
+    for _ in 0..50 {
+        events = audit_storage.read_all().unwrap();
+        if events.len() == 6 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(events.len(), 6);
+    assert_eq!(events[0].event_type.name(), "secret_revealed");
+    assert_eq!(events[0].metadata["reason"], "INC-42");
+    let exceeded: Vec<_> = events
+        .iter()
+        .filter(|event| event.severity == AuditSeverity::Critical)
+        .collect();
+    assert_eq!(exceeded.len(), 2);
+    assert_eq!(exceeded[0].user, "bob");
+    assert_eq!(exceeded[1].user, "carol");
+    assert_eq!(exceeded[0].event_type.name(), "security_event");
+    assert_eq!(exceeded[0].metadata["key"], "api-key");
+}
@@ -0,0 +1,270 @@
+//! Security administration API tests
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    Router,
+};
+use llm_config_api::{create_router_with_state, ApiState, SecurityState};
+use llm_config_audit::{AuditLogger, AuditStorage, FileAuditStorage};
+use llm_config_core::ConfigManager;
+use llm_config_rbac::{Role, RoleAssignment};
+use llm_config_security::{
+    InputValidator, PolicyEnforcer, RateLimitConfig, RateLimiter, SecurityPolicy,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+fn create_app(state: ApiState) -> Router {
+    let policy = SecurityPolicy {
+        require_tls: false,
+        ..SecurityPolicy::default()
+    };
+
+    let security_state = SecurityState::with_components(
+        RateLimiter::new(RateLimitConfig::default()),
+        InputValidator::default(),
+        PolicyEnforcer::new(policy),
+    )
+    .with_trusted_proxies(vec!["127.0.0.1".to_string()]);
+
+    create_router_with_state(state, security_state)
+}
+
+fn create_test_app() -> (Router, Arc<FileAuditStorage>, TempDir) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path().join("data")).unwrap());
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let state = ApiState::new(manager)
+        .with_audit_logger(Arc::new(AuditLogger::new(audit_storage.clone())))
+        .with_rbac_enforcement(true);
+    state
+        .rbac
+        .write()
+        .unwrap()
+        .assign_role(RoleAssignment::new("admin", Role::Admin));
+    (create_app(state), audit_storage, temp_dir)
+}
+
+/// A request of `user` at `client`, forwarded by the trusted proxy
+fn request_from(
+    client: &str,
+    user: &str,
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> Request<Body> {
+    let proxy: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", client)
+        .header("x-user-id", user)
+        .extension(ConnectInfo(proxy));
+
+    match body {
+        Some(body) => builder.body(Body::from(body.to_string())).unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+fn request(method: &str, uri: &str, body: Option<serde_json::Value>) -> Request<Body> {
+    request_from("127.0.0.1", "admin", method, uri, body)
+}
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+/// Operations of the audited security changes, once `count` were written
+async fn audited_operations(storage: &FileAuditStorage, count: usize) -> Vec<String> {
+    let mut operations = Vec::new();
+    for _ in 0..50 {
+        operations = storage
+            .read_all()
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.event_type.name() == "system_event")
+            .map(|event| {
+                assert_eq!(event.user, "admin");
+                event.metadata["operation"].clone()
+            })
+            .collect();
+        if operations.len() >= count {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    operations
+}
+
+#[tokio::test]
+async fn test_ban_and_unban_ip() {
+    let (app, audit_storage, _temp_dir) = create_test_app();
+
+    let response = app
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/api/v1/admin/security/bans",
+            Some(serde_json::json!({ "ip": "203.0.113.7", "reason": "credential stuffing" })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let ban = json_body(response).await;
+    assert_eq!(ban["ip"], "203.0.113.7");
+    assert_eq!(ban["reason"], "credential stuffing");
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/admin/security/bans", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bans = json_body(response).await;
+    assert_eq!(bans.as_array().unwrap().len(), 1);
+
+    // The banned client is rejected by the middleware
+    let response = app
+        .clone()
+        .oneshot(request_from("203.0.113.7", "admin", "GET", "/api/v1/roles", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/admin/security/bans/203.0.113.7", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/admin/security/bans/203.0.113.7", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .oneshot(request_from("203.0.113.7", "admin", "GET", "/api/v1/roles", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(
+        audited_operations(&audit_storage, 2).await,
+        vec!["ban_ip", "unban_ip"]
+    );
+}
+
+#[tokio::test]
+async fn test_update_security_policy() {
+    let (app, audit_storage, _temp_dir) = create_test_app();
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/admin/security/policy", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut policy = json_body(response).await;
+    assert_eq!(policy["require_tls"], false);
+
+    policy["blocked_ips"] = serde_json::json!(["198.51.100.9"]);
+    policy["blocked_endpoints"] = serde_json::json!(["*/locked"]);
+    let response = app
+        .clone()
+        .oneshot(request("PUT", "/api/v1/admin/security/policy", Some(policy.clone())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Applied to the next request without a restart
+    let response = app
+        .clone()
+        .oneshot(request_from("198.51.100.9", "admin", "GET", "/api/v1/roles", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/configs/app/locked", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Invalid trusted proxies are rejected
+    policy["trusted_proxies"] = serde_json::json!(["not-an-ip"]);
+    let response = app
+        .clone()
+        .oneshot(request("PUT", "/api/v1/admin/security/policy", Some(policy)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(request("GET", "/api/v1/admin/security/rate-limits", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let stats = json_body(response).await;
+    assert_eq!(stats["banned_ips"], 0);
+
+    assert_eq!(audited_operations(&audit_storage, 1).await, vec!["update_policy"]);
+}
+
+#[tokio::test]
+async fn test_security_admin_requires_system_permission() {
+    let (app, _audit_storage, _temp_dir) = create_test_app();
+
+    for (method, uri, body) in [
+        ("GET", "/api/v1/admin/security/bans", None),
+        (
+            "POST",
+            "/api/v1/admin/security/bans",
+            Some(serde_json::json!({ "ip": "203.0.113.7" })),
+        ),
+        ("GET", "/api/v1/admin/security/rate-limits", None),
+        ("GET", "/api/v1/admin/security/policy", None),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(request_from("127.0.0.1", "mallory", method, uri, body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{} {}", method, uri);
+    }
+}
+
+#[tokio::test]
+async fn test_user_id_requires_trusted_proxy() {
+    let (app, _audit_storage, _temp_dir) = create_test_app();
+    let uri = "/api/v1/admin/security/policy";
+
+    // A client connecting directly cannot claim to be the administrator
+    let client: SocketAddr = "203.0.113.7:4000".parse().unwrap();
+    let request = Request::builder()
+        .uri(uri)
+        .header("x-user-id", "admin")
+        .extension(ConnectInfo(client))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .oneshot(request_from("203.0.113.7", "admin", "GET", uri, None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
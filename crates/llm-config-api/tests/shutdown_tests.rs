@@ -0,0 +1,108 @@
+//! Graceful shutdown tests
+
+use axum::{middleware, routing::get, Router};
+use llm_config_api::{track_in_flight, ShutdownCoordinator};
+use llm_config_audit::{AuditEventType, AuditLogger, FileAuditStorage};
+use llm_config_metrics::MetricsRegistry;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// Serve a route taking `delay` until `shutdown` is triggered
+async fn start_server(
+    shutdown: &ShutdownCoordinator,
+    delay: Duration,
+) -> (SocketAddr, tokio::task::JoinHandle<anyhow::Result<()>>) {
+    let app = Router::new()
+        .route(
+            "/slow",
+            get(move || async move {
+                tokio::time::sleep(delay).await;
+                "done"
+            }),
+        )
+        .layer(middleware::from_fn_with_state(shutdown.clone(), track_in_flight));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = axum::serve(listener, app).with_graceful_shutdown(shutdown.triggered());
+    let shutdown = shutdown.clone();
+    let handle = tokio::spawn(async move { shutdown.run(async { Ok(server.await?) }).await });
+    (addr, handle)
+}
+
+/// Wait until the server is serving `count` requests
+async fn wait_for_in_flight(shutdown: &ShutdownCoordinator, count: usize) {
+    for _ in 0..100 {
+        if shutdown.in_flight() == count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("expected {} requests in flight, found {}", count, shutdown.in_flight());
+}
+
+#[tokio::test]
+async fn test_in_flight_requests_finish_and_audit_log_is_flushed() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let logger = Arc::new(AuditLogger::new(Arc::new(
+        FileAuditStorage::new(temp_dir.path()).unwrap(),
+    )));
+    let shutdown = ShutdownCoordinator::new(Duration::from_secs(5));
+    shutdown.register(logger.clone());
+
+    let (addr, server) = start_server(&shutdown, Duration::from_millis(300)).await;
+    let request = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+    wait_for_in_flight(&shutdown, 1).await;
+
+    for i in 0..50 {
+        logger
+            .log_event(
+                AuditEventType::ConfigAccessed {
+                    namespace: "app".to_string(),
+                    key: format!("key{}", i),
+                    environment: "production".to_string(),
+                },
+                "alice",
+            )
+            .unwrap();
+    }
+    shutdown.trigger();
+
+    // The request started before the signal completes
+    let response = request.await.unwrap().unwrap();
+    assert_eq!(response.text().await.unwrap(), "done");
+
+    server.await.unwrap().unwrap();
+    assert_eq!(shutdown.in_flight(), 0);
+    assert_eq!(logger.pending(), 0);
+    assert_eq!(logger.count().unwrap(), 50);
+
+    // New connections are refused
+    assert!(reqwest::get(format!("http://{}/slow", addr)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_requests_outlasting_the_deadline_are_reported() {
+    let registry = Arc::new(MetricsRegistry::new().unwrap());
+    let shutdown =
+        ShutdownCoordinator::new(Duration::from_millis(100)).with_metrics(Arc::clone(&registry));
+
+    let (addr, server) = start_server(&shutdown, Duration::from_secs(30)).await;
+    let _request = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+    wait_for_in_flight(&shutdown, 1).await;
+
+    shutdown.trigger();
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("shutdown should not wait past the deadline")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(registry.system().abandoned_requests(), 1.0);
+    assert!(registry
+        .encode_text()
+        .unwrap()
+        .contains("http_requests_in_flight 1"));
+}
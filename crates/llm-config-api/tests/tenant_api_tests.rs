@@ -0,0 +1,156 @@
+//! Multi-tenant API tests
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    Router,
+};
+use llm_config_api::{create_tenant_router, SecurityState, TenantRouters};
+use llm_config_audit::{AuditLogger, AuditStorage, FileAuditStorage};
+use llm_config_core::{Environment, TenantId, TenantRegistry};
+use llm_config_security::{
+    InputValidator, PolicyEnforcer, RateLimitConfig, RateLimiter, SecurityPolicy,
+    WorkloadIdentityMapper, WorkloadMapping,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+const SERVING: &str = "spiffe://prod.example.org/ns/ml/sa/serving";
+
+/// Security state trusting requests from 127.0.0.1, the proxy of these tests
+fn security_state() -> SecurityState {
+    let policy = SecurityPolicy {
+        require_tls: false,
+        ..SecurityPolicy::default()
+    };
+
+    SecurityState::with_components(
+        RateLimiter::new(RateLimitConfig::default()),
+        InputValidator::default(),
+        PolicyEnforcer::new(policy),
+    )
+    .with_trusted_proxies(vec!["127.0.0.1".to_string()])
+}
+
+fn create_app(registry: TenantRegistry, security_state: SecurityState) -> Router {
+    create_tenant_router(TenantRouters::new(Arc::new(registry)), security_state)
+}
+
+fn request(method: &str, uri: &str, tenant: Option<&str>, body: Option<serde_json::Value>) -> Request<Body> {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .extension(ConnectInfo(addr));
+    if let Some(tenant) = tenant {
+        builder = builder.header("x-tenant-id", tenant);
+    }
+
+    match body {
+        Some(body) => builder.body(Body::from(body.to_string())).unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+fn set_model(tenant: &str, value: &str) -> Request<Body> {
+    request(
+        "POST",
+        "/api/v1/configs/app/model",
+        Some(tenant),
+        Some(serde_json::json!({ "value": value, "env": "production", "user": "alice" })),
+    )
+}
+
+#[tokio::test]
+async fn test_tenants_isolated() {
+    let temp_dir = TempDir::new().unwrap();
+    let audit_storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+    let logger = Arc::new(AuditLogger::new(audit_storage.clone()));
+    let registry = TenantRegistry::new(temp_dir.path()).with_audit_logger(logger.clone());
+    let app = create_app(registry, security_state());
+
+    let response = app.clone().oneshot(set_model("acme", "gpt-4")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let get = |tenant| request("GET", "/api/v1/configs/app/model?env=production", tenant, None);
+    let response = app.clone().oneshot(get(Some("acme"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.clone().oneshot(get(Some("globex"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Every request must name a valid tenant
+    let response = app.clone().oneshot(get(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = app.oneshot(get(Some("../acme"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Stored under the tenant's own directory and audited with its tenant
+    let acme = TenantId::new("acme").unwrap();
+    let stored = TenantRegistry::new(temp_dir.path())
+        .manager(&acme)
+        .unwrap()
+        .get("app", "model", Environment::Production)
+        .unwrap();
+    assert!(stored.is_some());
+    assert!(acme.storage_path(temp_dir.path()).exists());
+
+    logger.flush().await.unwrap();
+    let created: Vec<_> = audit_storage
+        .read_all()
+        .unwrap()
+        .into_iter()
+        .filter(|event| event.event_type.name() == "config_created")
+        .collect();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].tenant_id.as_deref(), Some("acme"));
+}
+
+#[tokio::test]
+async fn test_workload_identity_binds_tenant() {
+    let temp_dir = TempDir::new().unwrap();
+    let security_state = security_state().with_workload_identities(
+            WorkloadIdentityMapper::new("prod.example.org")
+                .with_mapping(WorkloadMapping::new(SERVING, "ml-serving").with_tenant("acme")),
+        );
+    let app = create_app(TenantRegistry::new(temp_dir.path()), security_state);
+
+    let workload = |tenant| {
+        let mut request = set_model(tenant, "gpt-4");
+        request.headers_mut().insert(
+            "x-forwarded-client-cert",
+            format!("Hash=abc123;URI={}", SERVING).parse().unwrap(),
+        );
+        request
+    };
+
+    // The workload cannot claim another tenant
+    let response = app.clone().oneshot(workload("globex")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let mut request = workload("acme");
+    request.headers_mut().remove("x-tenant-id");
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let globex = TenantId::new("globex").unwrap();
+    assert!(!globex.storage_path(temp_dir.path()).exists());
+}
+
+#[tokio::test]
+async fn test_tenant_header_requires_trusted_proxy() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = create_app(TenantRegistry::new(temp_dir.path()), security_state());
+
+    let mut request = set_model("acme", "gpt-4");
+    let client: SocketAddr = "203.0.113.7:4000".parse().unwrap();
+    request.extensions_mut().insert(ConnectInfo(client));
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let acme = TenantId::new("acme").unwrap();
+    assert!(!acme.storage_path(temp_dir.path()).exists());
+}
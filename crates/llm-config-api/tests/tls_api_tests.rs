@@ -0,0 +1,187 @@
+//! Native TLS and mTLS tests
+
+use llm_config_api::{
+    create_router_with_state, serve_tls, ApiState, ReloadingTlsConfig, SecurityState, TlsConfig,
+};
+use llm_config_core::ConfigManager;
+use llm_config_rbac::{Role, RoleAssignment};
+use rcgen::{
+    BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsConnector;
+
+struct Ca {
+    cert: rcgen::Certificate,
+    key: KeyPair,
+}
+
+impl Ca {
+    fn new() -> Self {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.distinguished_name.push(DnType::CommonName, "test ca");
+        let key = KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+        Self { cert, key }
+    }
+
+    fn issue(
+        &self,
+        common_name: &str,
+        purpose: ExtendedKeyUsagePurpose,
+    ) -> (rcgen::Certificate, KeyPair) {
+        let mut params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, common_name);
+        params.extended_key_usages = vec![purpose];
+        let key = KeyPair::generate().unwrap();
+        let cert = params.signed_by(&key, &self.cert, &self.key).unwrap();
+        (cert, key)
+    }
+}
+
+fn write_server_cert(ca: &Ca, dir: &Path, common_name: &str) -> CertificateDer<'static> {
+    let (cert, key) = ca.issue(common_name, ExtendedKeyUsagePurpose::ServerAuth);
+    std::fs::write(dir.join("server.pem"), cert.pem()).unwrap();
+    std::fs::write(dir.join("server.key"), key.serialize_pem()).unwrap();
+    cert.der().clone()
+}
+
+async fn start_server(dir: &Path, ca: &Ca) -> (SocketAddr, Arc<ReloadingTlsConfig>, TempDir) {
+    std::fs::write(dir.join("ca.pem"), ca.cert.pem()).unwrap();
+    write_server_cert(ca, dir, "server-1");
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(ConfigManager::new(temp_dir.path()).unwrap());
+    let state = ApiState::new(manager).with_rbac_enforcement(true);
+    state
+        .rbac
+        .write()
+        .unwrap()
+        .assign_role(RoleAssignment::new("deploy-bot", Role::Admin));
+    // The default policy requires TLS
+    let app = create_router_with_state(state, SecurityState::new());
+
+    let tls = TlsConfig::new(dir.join("server.pem"), dir.join("server.key"))
+        .with_client_ca(dir.join("ca.pem"));
+    let tls = Arc::new(ReloadingTlsConfig::new(tls).unwrap());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_tls(listener, app, Arc::clone(&tls), std::future::pending()));
+
+    (addr, tls, temp_dir)
+}
+
+fn connector(ca: &Ca, client: Option<(rcgen::Certificate, KeyPair)>) -> TlsConnector {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(ca.cert.der().clone()).unwrap();
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+    let config = match client {
+        Some((cert, key)) => builder
+            .with_client_auth_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::try_from(key.serialize_der()).unwrap(),
+            )
+            .unwrap(),
+        None => builder.with_no_client_auth(),
+    };
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Send a GET request, returning the status code and the server certificate
+async fn get(
+    connector: &TlsConnector,
+    addr: SocketAddr,
+    path: &str,
+) -> std::io::Result<(u16, CertificateDer<'static>)> {
+    let stream = TcpStream::connect(addr).await?;
+    let server_name = ServerName::try_from("localhost").unwrap();
+    let mut stream = connector.connect(server_name, stream).await?;
+    let server_cert = stream.get_ref().1.peer_certificates().unwrap()[0].clone();
+
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nx-user-id: mallory\r\nConnection: close\r\n\r\n",
+        path
+    );
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0);
+    Ok((status, server_cert))
+}
+
+#[tokio::test]
+async fn test_mtls_principal_from_client_certificate() {
+    let dir = tempfile::tempdir().unwrap();
+    let ca = Ca::new();
+    let (addr, _tls, _data) = start_server(dir.path(), &ca).await;
+
+    // The certificate's common name is the principal, not x-user-id
+    let client = ca.issue("deploy-bot", ExtendedKeyUsagePurpose::ClientAuth);
+    let (status, _) = get(&connector(&ca, Some(client)), addr, "/api/v1/roles").await.unwrap();
+    assert_eq!(status, 200);
+
+    let client = ca.issue("intern", ExtendedKeyUsagePurpose::ClientAuth);
+    let (status, _) = get(&connector(&ca, Some(client)), addr, "/api/v1/roles").await.unwrap();
+    assert_eq!(status, 403);
+
+    // Clients without a certificate fail the handshake
+    assert!(get(&connector(&ca, None), addr, "/api/v1/roles").await.is_err());
+}
+
+#[tokio::test]
+async fn test_certificate_hot_reload() {
+    let dir = tempfile::tempdir().unwrap();
+    let ca = Ca::new();
+    let (addr, tls, _data) = start_server(dir.path(), &ca).await;
+    let client = || Some(ca.issue("deploy-bot", ExtendedKeyUsagePurpose::ClientAuth));
+
+    let (_, before) = get(&connector(&ca, client()), addr, "/health").await.unwrap();
+    assert!(!tls.reload_if_changed());
+
+    let renewed = write_server_cert(&ca, dir.path(), "server-2");
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+    for file in ["server.pem", "server.key"] {
+        std::fs::File::options()
+            .write(true)
+            .open(dir.path().join(file))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    }
+    assert!(tls.reload_if_changed());
+
+    let (status, after) = get(&connector(&ca, client()), addr, "/health").await.unwrap();
+    assert_eq!(status, 200);
+    assert_ne!(before, after);
+    assert_eq!(after, renewed);
+
+    // A broken certificate keeps the current one
+    std::fs::write(dir.path().join("server.pem"), "garbage").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(dir.path().join("server.pem"))
+        .unwrap()
+        .set_modified(later + std::time::Duration::from_secs(5))
+        .unwrap();
+    assert!(!tls.reload_if_changed());
+    let (status, current) = get(&connector(&ca, client()), addr, "/health").await.unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(current, renewed);
+}
@@ -1,7 +1,9 @@
 //! Audit event definitions
 
 use chrono::{DateTime, Utc};
+use llm_config_core::{ChangeEvent, ChangeKind, OperationContext};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -73,6 +75,20 @@ pub enum AuditEventType {
         environment: String,
     },
 
+    /// Secret plaintext was revealed (decrypted) to a caller
+    SecretRevealed {
+        namespace: String,
+        key: String,
+        environment: String,
+    },
+
+    /// Prompt template was rendered
+    TemplateRendered {
+        name: String,
+        environment: String,
+        version: u64,
+    },
+
     /// Authentication attempt
     AuthAttempt {
         user: String,
@@ -101,6 +117,104 @@ pub enum AuditEventType {
     },
 }
 
+impl AuditEventType {
+    /// Event type name, as serialized in the `type` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditEventType::ConfigCreated { .. } => "config_created",
+            AuditEventType::ConfigUpdated { .. } => "config_updated",
+            AuditEventType::ConfigDeleted { .. } => "config_deleted",
+            AuditEventType::ConfigAccessed { .. } => "config_accessed",
+            AuditEventType::ConfigRolledBack { .. } => "config_rolled_back",
+            AuditEventType::SecretModified { .. } => "secret_modified",
+            AuditEventType::SecretAccessed { .. } => "secret_accessed",
+            AuditEventType::SecretRevealed { .. } => "secret_revealed",
+            AuditEventType::TemplateRendered { .. } => "template_rendered",
+            AuditEventType::AuthAttempt { .. } => "auth_attempt",
+            AuditEventType::AuthzCheck { .. } => "authz_check",
+            AuditEventType::SystemEvent { .. } => "system_event",
+            AuditEventType::SecurityEvent { .. } => "security_event",
+        }
+    }
+
+    /// The (namespace, key, environment) a config or secret event refers to
+    pub fn target(&self) -> Option<(&str, &str, &str)> {
+        match self {
+            AuditEventType::ConfigCreated {
+                namespace,
+                key,
+                environment,
+            }
+            | AuditEventType::ConfigUpdated {
+                namespace,
+                key,
+                environment,
+                ..
+            }
+            | AuditEventType::ConfigDeleted {
+                namespace,
+                key,
+                environment,
+            }
+            | AuditEventType::ConfigAccessed {
+                namespace,
+                key,
+                environment,
+            }
+            | AuditEventType::ConfigRolledBack {
+                namespace,
+                key,
+                environment,
+                ..
+            }
+            | AuditEventType::SecretModified {
+                namespace,
+                key,
+                environment,
+            }
+            | AuditEventType::SecretAccessed {
+                namespace,
+                key,
+                environment,
+            }
+            | AuditEventType::SecretRevealed {
+                namespace,
+                key,
+                environment,
+            } => Some((namespace, key, environment)),
+            _ => None,
+        }
+    }
+
+    /// The RBAC permission (`resource:action`) the event shows was used
+    ///
+    /// Rollbacks count as config updates, as the API authorizes them that way;
+    /// denied authorization checks and events without a caller action have none.
+    pub fn permission(&self) -> Option<String> {
+        let permission = match self {
+            AuditEventType::ConfigCreated { .. } => "config:create",
+            AuditEventType::ConfigUpdated { .. } | AuditEventType::ConfigRolledBack { .. } => {
+                "config:update"
+            }
+            AuditEventType::ConfigDeleted { .. } => "config:delete",
+            AuditEventType::ConfigAccessed { .. } | AuditEventType::TemplateRendered { .. } => {
+                "config:read"
+            }
+            AuditEventType::SecretModified { .. } => "secret:update",
+            AuditEventType::SecretAccessed { .. } => "secret:read",
+            AuditEventType::SecretRevealed { .. } => "secret:reveal",
+            AuditEventType::AuthzCheck {
+                resource,
+                action,
+                allowed: true,
+                ..
+            } => return Some(format!("{}:{}", resource, action)),
+            _ => return None,
+        };
+        Some(permission.to_string())
+    }
+}
+
 /// Complete audit event with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEvent {
@@ -128,9 +242,21 @@ pub struct AuditEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub request_id: Option<String>,
 
+    /// Tenant the event belongs to (multi-tenant deployments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+
     /// Additional metadata
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub metadata: HashMap<String, String>,
+
+    /// Hash of the previous event in the log (hash chain link)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+
+    /// SHA-256 hash of this event, including `prev_hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
 }
 
 impl AuditEvent {
@@ -144,7 +270,10 @@ impl AuditEvent {
             user: user.into(),
             source_ip: None,
             request_id: None,
+            tenant_id: None,
             metadata: HashMap::new(),
+            prev_hash: None,
+            hash: None,
         }
     }
 
@@ -166,12 +295,120 @@ impl AuditEvent {
         self
     }
 
+    /// Set the tenant
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant.into());
+        self
+    }
+
     /// Add metadata
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
         self
     }
 
+    /// Record the request context: source IP, request ID, session and workload identity
+    pub fn with_context(mut self, context: &OperationContext) -> Self {
+        if let Some(ip) = &context.ip_address {
+            self = self.with_source_ip(ip.clone());
+        }
+        if let Some(request_id) = &context.request_id {
+            self = self.with_request_id(request_id.clone());
+        }
+        if let Some(session_id) = &context.session_id {
+            self = self.with_metadata("session_id", session_id.clone());
+        }
+        if let Some(spiffe_id) = &context.spiffe_id {
+            self = self.with_metadata("spiffe_id", spiffe_id.clone());
+        }
+        if let Some(commit) = &context.git_commit {
+            self = self.with_metadata("git_commit", commit.clone());
+        }
+        self
+    }
+
+    /// Audit event for a config change published by the config manager
+    ///
+    /// Rollbacks record the versions before and after the rollback; metadata
+    /// changes are recorded as updates that keep the version.
+    pub fn from_change(change: &ChangeEvent) -> Self {
+        let (namespace, key, environment) = (
+            change.namespace.clone(),
+            change.key.clone(),
+            change.environment.to_string(),
+        );
+        let secret = change.entry.as_ref().is_some_and(|entry| entry.value.is_secret());
+
+        let event_type = match change.kind {
+            ChangeKind::Set if secret => AuditEventType::SecretModified {
+                namespace,
+                key,
+                environment,
+            },
+            ChangeKind::Set if change.version <= 1 => AuditEventType::ConfigCreated {
+                namespace,
+                key,
+                environment,
+            },
+            ChangeKind::Set => AuditEventType::ConfigUpdated {
+                namespace,
+                key,
+                environment,
+                old_version: change.version - 1,
+                new_version: change.version,
+            },
+            ChangeKind::Metadata => AuditEventType::ConfigUpdated {
+                namespace,
+                key,
+                environment,
+                old_version: change.version,
+                new_version: change.version,
+            },
+            ChangeKind::Delete => AuditEventType::ConfigDeleted {
+                namespace,
+                key,
+                environment,
+            },
+            ChangeKind::Rollback => AuditEventType::ConfigRolledBack {
+                namespace,
+                key,
+                environment,
+                from_version: change.version.saturating_sub(1),
+                to_version: change.version,
+            },
+        };
+
+        let mut event = Self::new(event_type, change.user.clone());
+        event.tenant_id = change.tenant_id.as_ref().map(ToString::to_string);
+        match &change.context {
+            Some(context) => event.with_context(context),
+            None => event,
+        }
+    }
+
+    /// Compute the SHA-256 hash of the event (hex encoded)
+    ///
+    /// The hash covers every field except `hash` itself, serialized with
+    /// sorted keys so it is stable across reads and writes.
+    pub fn compute_hash(&self) -> String {
+        let mut unhashed = self.clone();
+        unhashed.hash = None;
+
+        // serde_json::Value objects keep their keys sorted
+        let canonical = serde_json::to_value(&unhashed)
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+
+        hex::encode(Sha256::digest(canonical.as_bytes()))
+    }
+
+    /// Link the event to its predecessor and seal it with its own hash
+    pub fn seal(mut self, prev_hash: Option<String>) -> Self {
+        self.prev_hash = prev_hash;
+        self.hash = Some(self.compute_hash());
+        self
+    }
+
     /// Get a human-readable summary of the event
     pub fn summary(&self) -> String {
         match &self.event_type {
@@ -205,6 +442,12 @@ impl AuditEvent {
             AuditEventType::SecretAccessed { namespace, key, .. } => {
                 format!("Accessed secret {}/{} by {}", namespace, key, self.user)
             }
+            AuditEventType::SecretRevealed { namespace, key, .. } => {
+                format!("Revealed secret {}/{} to {}", namespace, key, self.user)
+            }
+            AuditEventType::TemplateRendered { name, version, .. } => {
+                format!("Rendered template {} v{} for {}", name, version, self.user)
+            }
             AuditEventType::AuthAttempt {
                 user,
                 method,
@@ -315,5 +558,79 @@ mod tests {
 
         assert_eq!(event.id, deserialized.id);
         assert_eq!(event.user, deserialized.user);
+
+        // name() matches the serialized type tag
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], event.event_type.name());
+    }
+
+    #[test]
+    fn test_event_hash() {
+        let event = AuditEvent::new(
+            AuditEventType::ConfigCreated {
+                namespace: "test".to_string(),
+                key: "key".to_string(),
+                environment: "dev".to_string(),
+            },
+            "user",
+        )
+        .with_metadata("b", "2")
+        .with_metadata("a", "1")
+        .seal(Some("abc".to_string()));
+
+        // Stable across a serialization round trip
+        let json = serde_json::to_string(&event).unwrap();
+        let deserialized: AuditEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.compute_hash(), event.hash.clone().unwrap());
+
+        // Any change to the content or the link changes the hash
+        let mut tampered = deserialized.clone();
+        tampered.user = "attacker".to_string();
+        assert_ne!(tampered.compute_hash(), event.hash.clone().unwrap());
+
+        let mut relinked = deserialized;
+        relinked.prev_hash = Some("def".to_string());
+        assert_ne!(relinked.compute_hash(), event.hash.unwrap());
+    }
+
+    #[test]
+    fn test_from_change() {
+        use llm_config_core::{ConfigEntry, ConfigValue, Environment};
+
+        let mut entry = ConfigEntry::new("app", "model", ConfigValue::String("gpt-4".into()), Environment::Production);
+        entry.version = 3;
+        entry.metadata.updated_by = "alice".to_string();
+        entry.metadata.context = Some(
+            OperationContext::default()
+                .with_ip_address("10.0.0.7")
+                .with_session_id("session-1")
+                .with_spiffe_id("spiffe://example.org/ns/ml/sa/trainer"),
+        );
+
+        let event = AuditEvent::from_change(&ChangeEvent::stored(ChangeKind::Set, &entry));
+        assert_eq!(event.user, "alice");
+        assert_eq!(event.source_ip.as_deref(), Some("10.0.0.7"));
+        assert_eq!(event.metadata["session_id"], "session-1");
+        assert_eq!(event.metadata["spiffe_id"], "spiffe://example.org/ns/ml/sa/trainer");
+        assert!(matches!(
+            event.event_type,
+            AuditEventType::ConfigUpdated { old_version: 2, new_version: 3, .. }
+        ));
+        assert_eq!(event.event_type.permission().as_deref(), Some("config:update"));
+
+        let deleted = ChangeEvent::deleted(&entry).with_user("bob").with_context(None);
+        let event = AuditEvent::from_change(&deleted);
+        assert_eq!(event.user, "bob");
+        assert!(event.source_ip.is_none());
+        assert_eq!(event.event_type.name(), "config_deleted");
+        assert_eq!(event.event_type.permission().as_deref(), Some("config:delete"));
+
+        let denied = AuditEventType::AuthzCheck {
+            user: "bob".to_string(),
+            resource: "secret".to_string(),
+            action: "reveal".to_string(),
+            allowed: false,
+        };
+        assert!(denied.permission().is_none());
     }
 }
@@ -0,0 +1,304 @@
+//! Audit log export (JSONL and CSV)
+
+use crate::{events::AuditEvent, storage::AuditStorage, AuditError, Result};
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::ops::RangeBounds;
+
+/// Export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON event per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = AuditError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "csv" => Ok(Self::Csv),
+            _ => Err(AuditError::InvalidEvent(format!("Unknown export format: {}", s))),
+        }
+    }
+}
+
+/// Event filter for exports
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only events triggered by this user
+    pub user: Option<String>,
+    /// Only events of this type (e.g. "config_updated")
+    pub event_type: Option<String>,
+    /// Only events of this tenant
+    pub tenant: Option<String>,
+    /// Only config and secret events in this namespace
+    pub namespace: Option<String>,
+    /// Only config and secret events in this environment
+    pub environment: Option<String>,
+}
+
+impl AuditFilter {
+    /// Create a filter matching every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match events triggered by a user
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Only match events of a type
+    pub fn with_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_type = Some(event_type.into());
+        self
+    }
+
+    /// Only match events of a tenant
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Only match config and secret events in a namespace
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Only match config and secret events in an environment
+    pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Check whether an event matches the filter
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        let target = event.event_type.target();
+        self.user.as_deref().map_or(true, |user| event.user == user)
+            && self
+                .event_type
+                .as_deref()
+                .map_or(true, |event_type| event.event_type.name() == event_type)
+            && self
+                .tenant
+                .as_deref()
+                .map_or(true, |tenant| event.tenant_id.as_deref() == Some(tenant))
+            && self.namespace.as_deref().map_or(true, |namespace| {
+                target.is_some_and(|(event_namespace, _, _)| event_namespace == namespace)
+            })
+            && self.environment.as_deref().map_or(true, |environment| {
+                target.is_some_and(|(_, _, event_environment)| event_environment == environment)
+            })
+    }
+}
+
+/// CSV column names, in order
+const CSV_HEADER: [&str; 12] = [
+    "id",
+    "timestamp",
+    "severity",
+    "event_type",
+    "user",
+    "namespace",
+    "key",
+    "environment",
+    "source_ip",
+    "request_id",
+    "tenant_id",
+    "summary",
+];
+
+/// Export the events of a storage backend within a time range
+///
+/// Returns the number of exported events.
+pub fn export(
+    storage: &dyn AuditStorage,
+    range: impl RangeBounds<DateTime<Utc>>,
+    filter: &AuditFilter,
+    format: ExportFormat,
+    mut writer: impl Write,
+) -> Result<usize> {
+    let events = storage
+        .read_all()?
+        .into_iter()
+        .filter(|event| range.contains(&event.timestamp) && filter.matches(event));
+
+    if format == ExportFormat::Csv {
+        writeln!(writer, "{}", CSV_HEADER.join(","))?;
+    }
+
+    let mut count = 0;
+    for event in events {
+        match format {
+            ExportFormat::Jsonl => {
+                let json = serde_json::to_string(&event)
+                    .map_err(|e| AuditError::Serialization(e.to_string()))?;
+                writeln!(writer, "{}", json)?;
+            }
+            ExportFormat::Csv => writeln!(writer, "{}", csv_row(&event))?,
+        }
+        count += 1;
+    }
+
+    writer.flush()?;
+    Ok(count)
+}
+
+fn csv_row(event: &AuditEvent) -> String {
+    let (namespace, key, environment) = event.event_type.target().unwrap_or_default();
+    let severity = serde_json::to_value(event.severity)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    [
+        event.id.to_string(),
+        event.timestamp.to_rfc3339(),
+        severity,
+        event.event_type.name().to_string(),
+        event.user.clone(),
+        namespace.to_string(),
+        key.to_string(),
+        environment.to_string(),
+        event.source_ip.clone().unwrap_or_default(),
+        event.request_id.clone().unwrap_or_default(),
+        event.tenant_id.clone().unwrap_or_default(),
+        event.summary(),
+    ]
+    .iter()
+    .map(|field| csv_escape(field))
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+/// Quote a CSV field if it contains separators, quotes, or newlines
+///
+/// Fields a spreadsheet would evaluate as a formula (starting with `=`, `+`,
+/// `-`, `@`, a tab or a carriage return) are prefixed with `'` and quoted.
+fn csv_escape(field: &str) -> String {
+    if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("\"'{}\"", field.replace('"', "\"\""))
+    } else if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{events::AuditEventType, storage::FileAuditStorage};
+    use tempfile::TempDir;
+
+    fn populated_storage(temp_dir: &TempDir) -> FileAuditStorage {
+        let storage = FileAuditStorage::new(temp_dir.path()).unwrap();
+        for i in 0..4 {
+            let user = if i % 2 == 0 { "alice" } else { "bob" };
+            let event_type = if i < 2 {
+                AuditEventType::ConfigCreated {
+                    namespace: "app".to_string(),
+                    key: format!("key{}", i),
+                    environment: "dev".to_string(),
+                }
+            } else {
+                AuditEventType::SystemEvent {
+                    component: "api".to_string(),
+                    message: "started, \"ok\"".to_string(),
+                }
+            };
+            storage.store(&AuditEvent::new(event_type, user)).unwrap();
+        }
+        storage
+    }
+
+    #[test]
+    fn test_export_jsonl_with_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = populated_storage(&temp_dir);
+
+        let mut out = Vec::new();
+        let filter = AuditFilter::new().with_user("alice");
+        let count = export(&storage, .., &filter, ExportFormat::Jsonl, &mut out).unwrap();
+        assert_eq!(count, 2);
+
+        let lines: Vec<AuditEvent> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|e| e.user == "alice"));
+    }
+
+    #[test]
+    fn test_filter_by_namespace_and_environment() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = populated_storage(&temp_dir);
+
+        let mut out = Vec::new();
+        let filter = AuditFilter::new().with_namespace("app").with_environment("dev");
+        let count = export(&storage, .., &filter, ExportFormat::Jsonl, &mut out).unwrap();
+        assert_eq!(count, 2);
+
+        let filter = AuditFilter::new().with_namespace("app").with_environment("production");
+        let count = export(&storage, .., &filter, ExportFormat::Jsonl, &mut Vec::new()).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = populated_storage(&temp_dir);
+
+        let mut out = Vec::new();
+        let filter = AuditFilter::new().with_event_type("system_event");
+        let count = export(&storage, .., &filter, ExportFormat::Csv, &mut out).unwrap();
+        assert_eq!(count, 2);
+
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains(",system_event,alice,,,,"));
+        assert!(lines[1].ends_with("\"System: api - started, \"\"ok\"\"\""));
+    }
+
+    #[test]
+    fn test_csv_escape_formulas() {
+        assert_eq!(csv_escape("alice"), "alice");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_escape("+1"), "\"'+1\"");
+        assert_eq!(csv_escape("-1"), "\"'-1\"");
+        assert_eq!(csv_escape("@SUM(A1)"), "\"'@SUM(A1)\"");
+        assert_eq!(csv_escape("\tcmd"), "\"'\tcmd\"");
+        assert_eq!(csv_escape("\rcmd"), "\"'\rcmd\"");
+    }
+
+    #[test]
+    fn test_export_time_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = populated_storage(&temp_dir);
+
+        let future = Utc::now() + chrono::Duration::hours(1);
+        let mut out = Vec::new();
+        let count = export(&storage, future.., &AuditFilter::new(), ExportFormat::Jsonl, &mut out)
+            .unwrap();
+        assert_eq!(count, 0);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!("CSV".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+        assert_eq!("jsonl".parse::<ExportFormat>().unwrap(), ExportFormat::Jsonl);
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+}
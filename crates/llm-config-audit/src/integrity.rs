@@ -0,0 +1,133 @@
+//! Hash chain verification for tamper-evident audit logs
+
+use crate::events::AuditEvent;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// First broken link found while walking the hash chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrokenLink {
+    /// Position of the offending event in the log
+    pub index: usize,
+    /// ID of the offending event
+    pub event_id: Uuid,
+    /// Why the link is broken
+    pub reason: String,
+}
+
+/// Result of an integrity verification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Number of events checked
+    pub checked: usize,
+    /// First broken link, if any
+    pub first_broken: Option<BrokenLink>,
+}
+
+impl IntegrityReport {
+    /// Whether every checked link is intact
+    pub fn is_intact(&self) -> bool {
+        self.first_broken.is_none()
+    }
+}
+
+/// Verify the hash chain of consecutive events
+///
+/// `offset` is the log position of `events[0]` and `prev_hash` the hash of
+/// the event preceding it (`None` when the chain starts at the beginning of
+/// the log). Verification stops at the first broken link.
+pub fn verify_chain(events: &[AuditEvent], offset: usize, prev_hash: Option<&str>) -> IntegrityReport {
+    let mut expected_prev = prev_hash.map(str::to_string);
+
+    for (i, event) in events.iter().enumerate() {
+        let broken = |reason: String| IntegrityReport {
+            checked: i + 1,
+            first_broken: Some(BrokenLink {
+                index: offset + i,
+                event_id: event.id,
+                reason,
+            }),
+        };
+
+        let Some(hash) = &event.hash else {
+            return broken("event is not hashed".to_string());
+        };
+
+        if event.prev_hash != expected_prev {
+            return broken(format!(
+                "previous hash mismatch: expected {}, found {}",
+                expected_prev.as_deref().unwrap_or("none"),
+                event.prev_hash.as_deref().unwrap_or("none")
+            ));
+        }
+
+        if event.compute_hash() != *hash {
+            return broken("event content does not match its hash".to_string());
+        }
+
+        expected_prev = Some(hash.clone());
+    }
+
+    IntegrityReport {
+        checked: events.len(),
+        first_broken: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::AuditEventType;
+
+    fn chain(len: usize) -> Vec<AuditEvent> {
+        let mut prev_hash = None;
+        (0..len)
+            .map(|i| {
+                let event = AuditEvent::new(
+                    AuditEventType::ConfigAccessed {
+                        namespace: "test".to_string(),
+                        key: format!("key{}", i),
+                        environment: "dev".to_string(),
+                    },
+                    "user",
+                )
+                .seal(prev_hash.take());
+                prev_hash = event.hash.clone();
+                event
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_intact_chain() {
+        let events = chain(5);
+        let report = verify_chain(&events, 0, None);
+        assert!(report.is_intact());
+        assert_eq!(report.checked, 5);
+
+        // A sub-range verifies against its predecessor's hash
+        let report = verify_chain(&events[2..], 2, events[1].hash.as_deref());
+        assert!(report.is_intact());
+        assert_eq!(report.checked, 3);
+    }
+
+    #[test]
+    fn test_tampered_event() {
+        let mut events = chain(5);
+        events[3].user = "attacker".to_string();
+
+        let broken = verify_chain(&events, 0, None).first_broken.unwrap();
+        assert_eq!(broken.index, 3);
+        assert_eq!(broken.event_id, events[3].id);
+    }
+
+    #[test]
+    fn test_removed_event() {
+        let mut events = chain(5);
+        events.remove(2);
+
+        let report = verify_chain(&events, 0, None);
+        assert_eq!(report.first_broken.unwrap().index, 2);
+        assert_eq!(report.checked, 3);
+    }
+}
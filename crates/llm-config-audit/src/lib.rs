@@ -4,11 +4,19 @@
 //! all configuration changes, access attempts, and system events.
 
 pub mod events;
+pub mod export;
+pub mod integrity;
 pub mod logger;
+pub mod queue;
+pub mod sinks;
 pub mod storage;
 
 pub use events::{AuditEvent, AuditEventType, AuditSeverity};
-pub use logger::AuditLogger;
+pub use export::{AuditFilter, ExportFormat};
+pub use integrity::{BrokenLink, IntegrityReport};
+pub use logger::{AuditLogger, BlockingAuditRecorder};
+pub use queue::{OverflowPolicy, QueueConfig, QueueStats, DEFAULT_QUEUE_CAPACITY};
+pub use sinks::{AuditSink, FanOutSink, SyslogSink, WebhookSink};
 pub use storage::{AuditStorage, FileAuditStorage};
 
 use thiserror::Error;
@@ -26,6 +34,12 @@ pub enum AuditError {
 
     #[error("Invalid event: {0}")]
     InvalidEvent(String),
+
+    #[error("Sink error: {0}")]
+    Sink(String),
+
+    #[error("Audit queue is full")]
+    QueueFull,
 }
 
 pub type Result<T> = std::result::Result<T, AuditError>;
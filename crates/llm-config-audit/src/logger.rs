@@ -1,60 +1,323 @@
 //! Audit logger implementation
 
 use crate::{
-    events::{AuditEvent, AuditEventType},
+    events::{AuditEvent, AuditEventType, AuditSeverity},
+    export::{self, AuditFilter, ExportFormat},
+    integrity::{self, IntegrityReport},
+    queue::{Journal, OverflowPolicy, QueueConfig, QueueStats},
+    sinks::AuditSink,
     storage::AuditStorage,
-    Result,
+    AuditError, Result,
 };
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::{error, info};
+use chrono::{DateTime, Utc};
+use llm_config_core::{AuditRecorder, AuditRetention, AuditedOperation, ChangeEvent};
+use std::collections::HashSet;
+use std::io::Write;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::{oneshot, Notify};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Message to the background task of an [`AuditLogger`]
+enum Message {
+    Event(Box<AuditEvent>),
+    /// Answered once every event queued before it was processed
+    Flush(oneshot::Sender<()>),
+}
+
+/// State shared by the loggers and their background task
+struct Shared {
+    overflow: OverflowPolicy,
+    /// Spill journal ([`OverflowPolicy::Spill`] only)
+    journal: Option<Mutex<Journal>>,
+    /// Wakes the task when events were spilled
+    spilled_events: Notify,
+    pending: AtomicUsize,
+    dropped: AtomicUsize,
+    spilled: AtomicUsize,
+}
 
 /// Audit logger with async event processing
+///
+/// Events are stored by a background task, fed through a bounded queue (see
+/// [`QueueConfig`]).
 pub struct AuditLogger {
     storage: Arc<dyn AuditStorage>,
-    event_tx: mpsc::UnboundedSender<AuditEvent>,
+    event_tx: mpsc::Sender<Message>,
+    shared: Arc<Shared>,
+}
+
+fn stopped() -> AuditError {
+    AuditError::Storage("Failed to send event: audit logger stopped".to_string())
 }
 
 impl AuditLogger {
     /// Create a new audit logger
     pub fn new(storage: Arc<dyn AuditStorage>) -> Self {
-        let (event_tx, mut event_rx) = mpsc::unbounded_channel::<AuditEvent>();
+        Self::spawn(storage, None, QueueConfig::default(), None)
+    }
+
+    /// Create an audit logger that also forwards stored events to a sink
+    ///
+    /// Use a [`FanOutSink`](crate::sinks::FanOutSink) to forward to several destinations.
+    pub fn with_sink(storage: Arc<dyn AuditStorage>, sink: Arc<dyn AuditSink>) -> Self {
+        Self::spawn(storage, Some(sink), QueueConfig::default(), None)
+    }
+
+    /// Create an audit logger with the given queue size and overflow policy
+    ///
+    /// With [`OverflowPolicy::Spill`], events left in the spill journal by a
+    /// previous process are stored first (skipping those already stored).
+    pub fn with_queue(
+        storage: Arc<dyn AuditStorage>,
+        sink: Option<Arc<dyn AuditSink>>,
+        config: QueueConfig,
+    ) -> Result<Self> {
+        let journal = match config.overflow {
+            OverflowPolicy::Spill => {
+                let dir = config.spill_dir.as_deref().ok_or_else(|| {
+                    AuditError::Storage("Spilling audit events requires a spill directory".to_string())
+                })?;
+                let mut journal = Journal::open(dir)?;
+                recover(storage.as_ref(), &mut journal)?;
+                Some(journal)
+            }
+            _ => None,
+        };
+        Ok(Self::spawn(storage, sink, config, journal))
+    }
+
+    fn spawn(
+        storage: Arc<dyn AuditStorage>,
+        sink: Option<Arc<dyn AuditSink>>,
+        config: QueueConfig,
+        journal: Option<Journal>,
+    ) -> Self {
+        let (event_tx, mut event_rx) = mpsc::channel::<Message>(config.capacity.max(1));
+        let shared = Arc::new(Shared {
+            overflow: config.overflow,
+            journal: journal.map(Mutex::new),
+            spilled_events: Notify::new(),
+            pending: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+            spilled: AtomicUsize::new(0),
+        });
 
         let storage_clone = Arc::clone(&storage);
+        let shared_clone = Arc::clone(&shared);
 
         // Spawn background task to process events
         tokio::spawn(async move {
-            while let Some(event) = event_rx.recv().await {
-                if let Err(e) = storage_clone.store(&event) {
-                    error!("Failed to store audit event: {}", e);
-                } else {
-                    info!(
-                        event_id = %event.id,
-                        event_type = ?event.event_type,
-                        user = %event.user,
-                        "Audit event logged"
-                    );
+            let storage = storage_clone;
+            let shared = shared_clone;
+            // Events stored since the journal was last truncated
+            let mut stored = HashSet::new();
+            let mut flushes = Vec::new();
+
+            loop {
+                let message = tokio::select! {
+                    message = event_rx.recv() => match message {
+                        Some(message) => Some(message),
+                        None => break,
+                    },
+                    _ = shared.spilled_events.notified() => None,
+                };
+
+                match message {
+                    Some(Message::Event(event)) => {
+                        if let Some(journal) = &shared.journal {
+                            journal.lock().unwrap().queued -= 1;
+                            stored.insert(event.id);
+                        }
+                        let is_stored = store(storage.as_ref(), &event);
+                        shared.pending.fetch_sub(1, Ordering::SeqCst);
+                        if is_stored {
+                            forward(&sink, &event).await;
+                        }
+                    }
+                    Some(Message::Flush(done)) => flushes.push(done),
+                    None => {}
+                }
+
+                // Store spilled events once everything queued before them is
+                let spilled = match &shared.journal {
+                    Some(journal) => {
+                        let mut journal = journal.lock().unwrap();
+                        if journal.queued > 0 {
+                            continue;
+                        }
+                        let spilled = drain_journal(storage.as_ref(), &mut journal, &stored);
+                        shared.pending.fetch_sub(spilled.len(), Ordering::SeqCst);
+                        stored.clear();
+                        spilled
+                    }
+                    None => Vec::new(),
+                };
+                for event in &spilled {
+                    forward(&sink, event).await;
+                }
+                for done in flushes.drain(..) {
+                    let _ = done.send(());
                 }
             }
         });
 
-        Self { storage, event_tx }
+        Self {
+            storage,
+            event_tx,
+            shared,
+        }
     }
 
     /// Log an audit event
+    ///
+    /// When the queue is full, the [`OverflowPolicy`] decides whether this
+    /// waits, drops the event or spills it to disk.
     pub fn log(&self, event: AuditEvent) -> Result<()> {
-        self.event_tx
-            .send(event)
-            .map_err(|e| crate::AuditError::Storage(format!("Failed to send event: {}", e)))?;
+        if let Some(journal) = &self.shared.journal {
+            return self.log_journaled(journal, event);
+        }
+
+        self.shared.pending.fetch_add(1, Ordering::SeqCst);
+        let result = match self.event_tx.try_send(Message::Event(Box::new(event))) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Closed(_)) => Err(stopped()),
+            Err(TrySendError::Full(message)) => match self.shared.overflow {
+                // Counted rather than failing the caller's operation
+                OverflowPolicy::Drop => {
+                    self.shared.pending.fetch_sub(1, Ordering::SeqCst);
+                    let dropped = self.shared.dropped.fetch_add(1, Ordering::SeqCst) + 1;
+                    warn!(dropped, "Audit queue full, dropping event");
+                    return Ok(());
+                }
+                _ => self.send_blocking(message),
+            },
+        };
+        if result.is_err() {
+            self.shared.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+        result
+    }
+
+    /// Log an audit event, waiting asynchronously for room in the queue
+    /// under [`OverflowPolicy::Block`]
+    async fn log_async(&self, event: AuditEvent) -> Result<()> {
+        if self.shared.journal.is_some() || self.shared.overflow == OverflowPolicy::Drop {
+            return self.log(event);
+        }
+        self.shared.pending.fetch_add(1, Ordering::SeqCst);
+        if self.event_tx.send(Message::Event(Box::new(event))).await.is_err() {
+            self.shared.pending.fetch_sub(1, Ordering::SeqCst);
+            return Err(stopped());
+        }
+        Ok(())
+    }
+
+    /// Journal an event, queueing it unless earlier events were spilled
+    fn log_journaled(&self, journal: &Mutex<Journal>, event: AuditEvent) -> Result<()> {
+        let mut journal = journal.lock().unwrap();
+        journal.append(&event)?;
+        self.shared.pending.fetch_add(1, Ordering::SeqCst);
+
+        // Once one event is spilled, later ones follow it to keep the order
+        if !journal.backlog {
+            match self.event_tx.try_send(Message::Event(Box::new(event))) {
+                Ok(()) => {
+                    journal.queued += 1;
+                    return Ok(());
+                }
+                Err(TrySendError::Full(_)) => journal.backlog = true,
+                Err(TrySendError::Closed(_)) => {
+                    // Still journaled, so stored on the next start
+                    self.shared.pending.fetch_sub(1, Ordering::SeqCst);
+                    return Err(stopped());
+                }
+            }
+        }
+        self.shared.spilled.fetch_add(1, Ordering::SeqCst);
+        self.shared.spilled_events.notify_one();
         Ok(())
     }
 
+    /// Wait for room in the queue
+    fn send_blocking(&self, message: Message) -> Result<()> {
+        let sent = match Handle::try_current() {
+            Err(_) => self.event_tx.blocking_send(message),
+            // The logger task cannot run while this thread waits
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::CurrentThread => {
+                return Err(AuditError::QueueFull);
+            }
+            Ok(_) => tokio::task::block_in_place(|| self.event_tx.blocking_send(message)),
+        };
+        sent.map_err(|_| stopped())
+    }
+
+    /// Number of logged events not yet stored
+    pub fn pending(&self) -> usize {
+        self.shared.pending.load(Ordering::SeqCst)
+    }
+
+    /// Pending, dropped and spilled event counts
+    pub fn stats(&self) -> QueueStats {
+        QueueStats {
+            pending: self.pending(),
+            dropped: self.shared.dropped.load(Ordering::SeqCst),
+            spilled: self.shared.spilled.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Wait until every event logged so far is stored (and forwarded to the
+    /// sink, if any)
+    pub async fn flush(&self) -> Result<()> {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.event_tx
+            .send(Message::Flush(done_tx))
+            .await
+            .map_err(|_| stopped())?;
+        done_rx.await.map_err(|_| stopped())
+    }
+
     /// Log an audit event (convenience method that creates the event)
     pub fn log_event(&self, event_type: AuditEventType, user: impl Into<String>) -> Result<()> {
         let event = AuditEvent::new(event_type, user);
         self.log(event)
     }
 
+    /// Spawn a task logging every config change received from a change bus
+    ///
+    /// Events carry the user and request context of the write (see
+    /// [`AuditEvent::from_change`]). If the task falls behind and misses
+    /// changes, a warning event records how many. The task ends when the bus
+    /// is dropped.
+    pub fn record_changes(&self, mut changes: broadcast::Receiver<ChangeEvent>) -> JoinHandle<()> {
+        let logger = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match changes.recv().await {
+                    Ok(change) => AuditEvent::from_change(&change),
+                    Err(RecvError::Lagged(missed)) => AuditEvent::new(
+                        AuditEventType::SystemEvent {
+                            component: "audit".to_string(),
+                            message: format!("{} config change events were not recorded", missed),
+                        },
+                        "system",
+                    )
+                    .with_severity(AuditSeverity::Warning),
+                    Err(RecvError::Closed) => break,
+                };
+                if logger.log_async(event).await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
     /// Query events from storage
     pub fn query(
         &self,
@@ -70,10 +333,213 @@ impl AuditLogger {
         self.storage.query_by_user(user, limit)
     }
 
+    /// The most recent events matching a filter, newest first
+    pub fn recent(&self, filter: &AuditFilter, limit: Option<usize>) -> Result<Vec<AuditEvent>> {
+        let events = self.storage.read_all()?;
+        Ok(events
+            .into_iter()
+            .rev()
+            .filter(|event| filter.matches(event))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
     /// Get total event count
     pub fn count(&self) -> Result<usize> {
         self.storage.count()
     }
+
+    /// Export events within a time range to a writer, returning the number exported
+    pub fn export(
+        &self,
+        range: impl RangeBounds<DateTime<Utc>>,
+        filter: &AuditFilter,
+        format: ExportFormat,
+        writer: impl Write,
+    ) -> Result<usize> {
+        export::export(self.storage.as_ref(), range, filter, format, writer)
+    }
+
+    /// Verify the hash chain over a range of event positions (`..` for the whole log)
+    ///
+    /// Reports the first broken link: a modified, inserted, or removed event.
+    pub fn verify_integrity(&self, range: impl RangeBounds<usize>) -> Result<IntegrityReport> {
+        let events = self.storage.read_all()?;
+
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0,
+        }
+        .min(events.len());
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n + 1,
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => events.len(),
+        }
+        .clamp(start, events.len());
+
+        // The log starts after the pruned events, if any were pruned
+        let prev_hash = match start.checked_sub(1) {
+            Some(i) => events[i].hash.clone(),
+            None => self.storage.pruned_hash()?,
+        };
+
+        Ok(integrity::verify_chain(&events[start..end], start, prev_hash.as_deref()))
+    }
+
+    /// Delete the events older than `retention` allows from the start of the
+    /// log, returning how many were deleted
+    pub fn apply_retention(&self, retention: &AuditRetention) -> Result<usize> {
+        match retention.keep_days {
+            Some(days) => self
+                .storage
+                .prune_before(Utc::now() - chrono::Duration::days(days.into())),
+            None => Ok(0),
+        }
+    }
+}
+
+/// Store an event, logging failures
+fn store(storage: &dyn AuditStorage, event: &AuditEvent) -> bool {
+    if let Err(e) = storage.store(event) {
+        error!("Failed to store audit event: {}", e);
+        return false;
+    }
+    info!(
+        event_id = %event.id,
+        event_type = ?event.event_type,
+        user = %event.user,
+        "Audit event logged"
+    );
+    true
+}
+
+/// Forward a stored event to the sink, if any
+async fn forward(sink: &Option<Arc<dyn AuditSink>>, event: &AuditEvent) {
+    if let Some(sink) = sink {
+        if let Err(e) = sink.send(event).await {
+            error!(sink = sink.name(), "Failed to forward audit event: {}", e);
+        }
+    }
+}
+
+/// Store the journaled events not in `stored` and empty the journal,
+/// returning the events stored
+///
+/// Events that fail to store stay in the journal for the next start.
+fn drain_journal(
+    storage: &dyn AuditStorage,
+    journal: &mut Journal,
+    stored: &HashSet<Uuid>,
+) -> Vec<AuditEvent> {
+    let spilled = if journal.backlog {
+        match journal.read() {
+            Ok(events) => events,
+            Err(e) => {
+                error!("Failed to read audit journal: {}", e);
+                return Vec::new();
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let mut drained = Vec::new();
+    for event in spilled.into_iter().filter(|event| !stored.contains(&event.id)) {
+        if !store(storage, &event) {
+            return drained;
+        }
+        drained.push(event);
+    }
+    if let Err(e) = journal.truncate() {
+        error!("Failed to truncate audit journal: {}", e);
+    }
+    drained
+}
+
+/// Store the events a previous process left in the journal
+fn recover(storage: &dyn AuditStorage, journal: &mut Journal) -> Result<()> {
+    let events = journal.read()?;
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let stored: HashSet<Uuid> = storage.read_all()?.into_iter().map(|event| event.id).collect();
+    let mut recovered = 0;
+    for event in events.iter().filter(|event| !stored.contains(&event.id)) {
+        storage.store(event)?;
+        recovered += 1;
+    }
+    if recovered > 0 {
+        warn!(recovered, "Recovered audit events from the spill journal");
+    }
+    journal.truncate()
+}
+
+/// Lets a [`ConfigManager`](llm_config_core::ConfigManager) log its own operations
+impl AuditRecorder for AuditLogger {
+    fn record(&self, operation: AuditedOperation<'_>) {
+        if let Err(e) = self.log(operation_event(operation)) {
+            error!("Failed to record config operation: {}", e);
+        }
+    }
+}
+
+/// Recorder storing each operation before the write returns
+///
+/// For short-lived processes such as the CLI, which have no runtime for an
+/// [`AuditLogger`] and may exit right after a write.
+pub struct BlockingAuditRecorder {
+    storage: Arc<dyn AuditStorage>,
+}
+
+impl BlockingAuditRecorder {
+    /// Create a recorder storing to `storage`
+    pub fn new(storage: Arc<dyn AuditStorage>) -> Self {
+        Self { storage }
+    }
+}
+
+impl AuditRecorder for BlockingAuditRecorder {
+    fn record(&self, operation: AuditedOperation<'_>) {
+        if let Err(e) = self.storage.store(&operation_event(operation)) {
+            error!("Failed to record config operation: {}", e);
+        }
+    }
+}
+
+/// Audit event for an operation reported by a config manager
+fn operation_event(operation: AuditedOperation<'_>) -> AuditEvent {
+    match operation {
+        AuditedOperation::Change(change) => AuditEvent::from_change(change),
+        AuditedOperation::SecretRead {
+            namespace,
+            key,
+            environment,
+            user,
+            context,
+            tenant,
+            reason,
+        } => {
+            let mut event = AuditEvent::new(
+                AuditEventType::SecretRevealed {
+                    namespace: namespace.to_string(),
+                    key: key.to_string(),
+                    environment: environment.to_string(),
+                },
+                user,
+            );
+            event.tenant_id = tenant.map(ToString::to_string);
+            if let Some(reason) = reason {
+                event = event.with_metadata("reason", reason);
+            }
+            match context {
+                Some(context) => event.with_context(context),
+                None => event,
+            }
+        }
+    }
 }
 
 impl Clone for AuditLogger {
@@ -81,6 +547,7 @@ impl Clone for AuditLogger {
         Self {
             storage: Arc::clone(&self.storage),
             event_tx: self.event_tx.clone(),
+            shared: Arc::clone(&self.shared),
         }
     }
 }
@@ -150,6 +617,192 @@ mod tests {
         assert_eq!(count, 10);
     }
 
+    #[tokio::test]
+    async fn test_flush() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FileAuditStorage::new(temp_dir.path()).unwrap());
+        let logger = AuditLogger::new(storage);
+
+        for i in 0..20 {
+            logger
+                .log_event(
+                    AuditEventType::ConfigAccessed {
+                        namespace: "test".to_string(),
+                        key: format!("key{}", i),
+                        environment: "dev".to_string(),
+                    },
+                    "user",
+                )
+                .unwrap();
+        }
+
+        logger.flush().await.unwrap();
+        assert_eq!(logger.pending(), 0);
+        assert_eq!(logger.count().unwrap(), 20);
+    }
+
+    /// File storage whose writes wait while the gate is closed
+    struct GatedStorage {
+        inner: FileAuditStorage,
+        closed: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl GatedStorage {
+        fn new(dir: impl AsRef<std::path::Path>) -> Arc<Self> {
+            Arc::new(Self {
+                inner: FileAuditStorage::new(dir).unwrap(),
+                closed: Arc::new(std::sync::atomic::AtomicBool::new(true)),
+            })
+        }
+
+        fn open(&self) {
+            self.closed.store(false, Ordering::SeqCst);
+        }
+    }
+
+    impl AuditStorage for GatedStorage {
+        fn store(&self, event: &AuditEvent) -> Result<()> {
+            while self.closed.load(Ordering::SeqCst) {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            self.inner.store(event)
+        }
+
+        fn read_all(&self) -> Result<Vec<AuditEvent>> {
+            self.inner.read_all()
+        }
+
+        fn query(
+            &self,
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+            limit: Option<usize>,
+        ) -> Result<Vec<AuditEvent>> {
+            self.inner.query(start, end, limit)
+        }
+
+        fn query_by_user(&self, user: &str, limit: Option<usize>) -> Result<Vec<AuditEvent>> {
+            self.inner.query_by_user(user, limit)
+        }
+
+        fn count(&self) -> Result<usize> {
+            self.inner.count()
+        }
+    }
+
+    fn accessed(i: usize) -> AuditEvent {
+        AuditEvent::new(
+            AuditEventType::ConfigAccessed {
+                namespace: "test".to_string(),
+                key: format!("key{}", i),
+                environment: "dev".to_string(),
+            },
+            "user",
+        )
+    }
+
+    fn keys(events: &[AuditEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match &event.event_type {
+                AuditEventType::ConfigAccessed { key, .. } => key.clone(),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_block_policy_waits_for_room() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = GatedStorage::new(temp_dir.path());
+        let logger = AuditLogger::with_queue(storage.clone(), None, QueueConfig::new(1)).unwrap();
+
+        let gate = storage.clone();
+        let opener = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            gate.open();
+        });
+        for i in 0..5 {
+            logger.log(accessed(i)).unwrap();
+        }
+        opener.join().unwrap();
+
+        logger.flush().await.unwrap();
+        assert_eq!(logger.stats(), QueueStats::default());
+        assert_eq!(keys(&storage.read_all().unwrap()), ["key0", "key1", "key2", "key3", "key4"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_drop_policy_counts_dropped_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = GatedStorage::new(temp_dir.path());
+        let config = QueueConfig::new(1).with_overflow(OverflowPolicy::Drop);
+        let logger = AuditLogger::with_queue(storage.clone(), None, config).unwrap();
+
+        for i in 0..5 {
+            logger.log(accessed(i)).unwrap();
+        }
+        let dropped = logger.stats().dropped;
+        assert!(dropped >= 3, "dropped {}", dropped);
+        storage.open();
+
+        logger.flush().await.unwrap();
+        assert_eq!(logger.pending(), 0);
+        assert_eq!(logger.count().unwrap() + dropped, 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_spill_policy_keeps_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = GatedStorage::new(temp_dir.path().join("audit"));
+        let config = QueueConfig::new(2).with_spill_dir(temp_dir.path().join("spill"));
+        let logger = AuditLogger::with_queue(storage.clone(), None, config).unwrap();
+
+        for i in 0..10 {
+            logger.log(accessed(i)).unwrap();
+        }
+        let stats = logger.stats();
+        assert_eq!(stats.pending, 10);
+        assert!(stats.spilled >= 7, "spilled {}", stats.spilled);
+        storage.open();
+
+        logger.flush().await.unwrap();
+        assert_eq!(logger.pending(), 0);
+        let expected: Vec<String> = (0..10).map(|i| format!("key{}", i)).collect();
+        assert_eq!(keys(&storage.read_all().unwrap()), expected);
+        assert!(logger.verify_integrity(..).unwrap().is_intact());
+
+        let journal = temp_dir.path().join("spill").join(crate::queue::JOURNAL_FILE);
+        assert_eq!(std::fs::metadata(journal).unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_spilled_events_recovered_on_restart() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+        let spill_dir = temp_dir.path().join("spill");
+
+        // A crash left three events in the journal, one of them already stored
+        let mut journal = Journal::open(&spill_dir).unwrap();
+        for i in 0..3 {
+            let event = accessed(i);
+            journal.append(&event).unwrap();
+            if i == 0 {
+                storage.store(&event).unwrap();
+            }
+        }
+        drop(journal);
+
+        let config = QueueConfig::default().with_spill_dir(&spill_dir);
+        let logger = AuditLogger::with_queue(storage.clone(), None, config).unwrap();
+        assert_eq!(keys(&storage.read_all().unwrap()), ["key0", "key1", "key2"]);
+
+        logger.log(accessed(3)).unwrap();
+        logger.flush().await.unwrap();
+        assert_eq!(logger.count().unwrap(), 4);
+        assert!(Journal::open(&spill_dir).unwrap().read().unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_query_events() {
         let temp_dir = TempDir::new().unwrap();
@@ -176,4 +829,181 @@ mod tests {
         let events = logger.query(start, end, None).unwrap();
         assert_eq!(events.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_events_forwarded_to_sink() {
+        struct CountingSink(std::sync::atomic::AtomicUsize);
+
+        #[async_trait::async_trait]
+        impl AuditSink for CountingSink {
+            async fn send(&self, _event: &AuditEvent) -> Result<()> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(())
+            }
+
+            fn name(&self) -> &str {
+                "counting"
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FileAuditStorage::new(temp_dir.path()).unwrap());
+        let sink = Arc::new(CountingSink(std::sync::atomic::AtomicUsize::new(0)));
+        let logger = AuditLogger::with_sink(storage, sink.clone());
+
+        for i in 0..3 {
+            logger
+                .log_event(
+                    AuditEventType::ConfigAccessed {
+                        namespace: "test".to_string(),
+                        key: format!("key{}", i),
+                        environment: "dev".to_string(),
+                    },
+                    "user",
+                )
+                .unwrap();
+        }
+
+        // Wait for async processing
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        assert_eq!(logger.count().unwrap(), 3);
+        assert_eq!(sink.0.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_verify_integrity() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FileAuditStorage::new(temp_dir.path()).unwrap());
+        let logger = AuditLogger::new(storage);
+
+        for i in 0..5 {
+            logger
+                .log_event(
+                    AuditEventType::ConfigAccessed {
+                        namespace: "test".to_string(),
+                        key: format!("key{}", i),
+                        environment: "dev".to_string(),
+                    },
+                    "user",
+                )
+                .unwrap();
+        }
+
+        // Wait for async processing
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+        let report = logger.verify_integrity(..).unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.checked, 5);
+
+        // Tamper with the fourth event on disk
+        let log_path = temp_dir.path().join("audit.log");
+        let content = std::fs::read_to_string(&log_path).unwrap();
+        let tampered: Vec<String> = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 3 {
+                    line.replace("\"user\":\"user\"", "\"user\":\"attacker\"")
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        std::fs::write(&log_path, tampered.join("\n") + "\n").unwrap();
+
+        let broken = logger.verify_integrity(..).unwrap().first_broken.unwrap();
+        assert_eq!(broken.index, 3);
+
+        // Ranges before the tampered event still verify
+        assert!(logger.verify_integrity(0..3).unwrap().is_intact());
+        assert!(!logger.verify_integrity(2..=3).unwrap().is_intact());
+    }
+
+    #[tokio::test]
+    async fn test_record_changes() {
+        use llm_config_core::{ConfigManager, ConfigValue, Environment, OperationContext, SetOptions};
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+        let logger = AuditLogger::new(storage);
+        let manager = ConfigManager::new(temp_dir.path().join("configs")).unwrap();
+        logger.record_changes(manager.subscribe_changes());
+
+        let context = OperationContext::default()
+            .with_ip_address("10.0.0.7")
+            .with_request_id("req-1");
+        let options = SetOptions::default().with_context(context);
+        let env = Environment::Production;
+        manager
+            .set_with_options("app", "model", ConfigValue::String("gpt-4".into()), env, "alice", &options)
+            .unwrap();
+        manager.delete_with_options("app", "model", env, "bob", &SetOptions::default()).unwrap();
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let created = logger.query_by_user("alice", None).unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].event_type.name(), "config_created");
+        assert_eq!(created[0].source_ip.as_deref(), Some("10.0.0.7"));
+        assert_eq!(created[0].request_id.as_deref(), Some("req-1"));
+
+        let deleted = logger.query_by_user("bob", None).unwrap();
+        assert_eq!(deleted[0].event_type.name(), "config_deleted");
+        assert!(deleted[0].request_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_config_manager_audit() {
+        use llm_config_core::{ConfigManager, ConfigValue, Environment, OperationContext};
+        use llm_config_crypto::{Algorithm, SecretKey};
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+        let logger = Arc::new(AuditLogger::new(storage));
+        let manager = ConfigManager::new(temp_dir.path().join("configs"))
+            .unwrap()
+            .with_encryption_key(SecretKey::generate(Algorithm::Aes256Gcm).unwrap())
+            .with_audit_logger(logger.clone());
+
+        let env = Environment::Production;
+        manager.set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        manager.set("app", "model", ConfigValue::String("gpt-4o".into()), env, "alice").unwrap();
+        manager.rollback("app", "model", env, 1).unwrap();
+        manager.set_secret("app", "api_key", b"sk-123", env, "alice").unwrap();
+        let context = OperationContext::default().with_request_id("req-1");
+        manager.get_secret_as("app", "api_key", env, "bob", Some(context)).unwrap();
+        manager.delete("app", "model", env).unwrap();
+
+        // Once reasons are required, reads without one are refused
+        let manager = manager.with_required_access_reason(true);
+        assert!(manager.get_secret_as("app", "api_key", env, "carol", None).is_err());
+        assert!(manager.get_secret_with_reason("app", "api_key", env, "carol", None, Some(" ")).is_err());
+        manager
+            .get_secret_with_reason("app", "api_key", env, "carol", None, Some("INC-42 rotation"))
+            .unwrap();
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let events = logger.storage.read_all().unwrap();
+        let names: Vec<&str> = events.iter().map(|event| event.event_type.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "config_created",
+                "config_updated",
+                "config_rolled_back",
+                "secret_modified",
+                "secret_revealed",
+                "config_deleted",
+                "secret_revealed",
+            ]
+        );
+        assert_eq!(events[4].user, "bob");
+        assert_eq!(events[4].request_id.as_deref(), Some("req-1"));
+        assert!(!events[4].metadata.contains_key("reason"));
+        assert_eq!(events[6].user, "carol");
+        assert_eq!(events[6].metadata["reason"], "INC-42 rotation");
+    }
 }
@@ -0,0 +1,222 @@
+//! Bounded event queue of the audit logger
+//!
+//! [`AuditLogger`](crate::AuditLogger) hands events to a background task
+//! through a queue of at most [`QueueConfig::capacity`] events. What happens
+//! when it is full is the [`OverflowPolicy`]: callers wait, the event is
+//! dropped and counted, or it is spilled to a write-ahead journal on disk.
+//! With spilling, every event is journaled before it is queued, and events
+//! left in the journal by a crash are stored when the logger restarts.
+
+use crate::{events::AuditEvent, AuditError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Default number of events queued for storage
+pub const DEFAULT_QUEUE_CAPACITY: usize = 10_000;
+
+/// File of the spill journal inside the spill directory
+pub const JOURNAL_FILE: &str = "audit-journal.jsonl";
+
+/// What to do with an event when the queue is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverflowPolicy {
+    /// Wait until the queue has room
+    ///
+    /// Blocks the calling thread. On a single-threaded runtime, where waiting
+    /// would stall the logger itself, logging fails with
+    /// [`AuditError::QueueFull`] instead.
+    #[default]
+    Block,
+    /// Drop the event, counting it in [`QueueStats::dropped`]
+    Drop,
+    /// Append the event to the journal in [`QueueConfig::spill_dir`] and
+    /// store it once the queue drains
+    Spill,
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Block => "block",
+            Self::Drop => "drop",
+            Self::Spill => "spill",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = AuditError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "block" => Ok(Self::Block),
+            "drop" => Ok(Self::Drop),
+            "spill" => Ok(Self::Spill),
+            _ => Err(AuditError::InvalidEvent(format!(
+                "Unknown overflow policy '{}' (expected block, drop or spill)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Size and overflow behavior of the audit logger queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueConfig {
+    /// Events queued before the overflow policy applies
+    pub capacity: usize,
+    /// What to do with events that do not fit
+    pub overflow: OverflowPolicy,
+    /// Directory of the spill journal (required for [`OverflowPolicy::Spill`])
+    pub spill_dir: Option<PathBuf>,
+}
+
+impl QueueConfig {
+    /// Queue up to `capacity` events, blocking callers when full
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            overflow: OverflowPolicy::Block,
+            spill_dir: None,
+        }
+    }
+
+    /// Handle events that do not fit with the given policy
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Spill events that do not fit to a journal in `dir`
+    pub fn with_spill_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = Some(dir.into());
+        self.overflow = OverflowPolicy::Spill;
+        self
+    }
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUEUE_CAPACITY)
+    }
+}
+
+/// Counters of the audit logger queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueueStats {
+    /// Events logged but not stored yet
+    pub pending: usize,
+    /// Events dropped because the queue was full
+    pub dropped: usize,
+    /// Events spilled to the journal because the queue was full
+    pub spilled: usize,
+}
+
+/// Write-ahead journal of events not known to be stored
+pub(crate) struct Journal {
+    path: PathBuf,
+    file: File,
+    /// Events after the first one that did not fit are only in the journal
+    pub(crate) backlog: bool,
+    /// Events sent through the queue and not received by the logger yet
+    pub(crate) queued: usize,
+}
+
+impl Journal {
+    /// Open (or create) the journal in `dir`
+    pub(crate) fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(JOURNAL_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            backlog: false,
+            queued: 0,
+        })
+    }
+
+    /// Durably append an event
+    pub(crate) fn append(&mut self, event: &AuditEvent) -> Result<()> {
+        let json =
+            serde_json::to_string(event).map_err(|e| AuditError::Serialization(e.to_string()))?;
+        writeln!(self.file, "{}", json)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Events in the journal, skipping a line torn by a crash
+    pub(crate) fn read(&mut self) -> Result<Vec<AuditEvent>> {
+        self.file.rewind()?;
+        let mut events = Vec::new();
+        for line in BufReader::new(&self.file).lines() {
+            let line = line?;
+            match serde_json::from_str(&line) {
+                Ok(event) => events.push(event),
+                Err(e) => tracing::warn!(
+                    "Skipping unreadable entry in {}: {}",
+                    self.path.display(),
+                    e
+                ),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Forget every journaled event
+    pub(crate) fn truncate(&mut self) -> Result<()> {
+        self.file.set_len(0)?;
+        self.file.sync_data()?;
+        self.backlog = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::AuditEventType;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_overflow_policy_names() {
+        for policy in [OverflowPolicy::Block, OverflowPolicy::Drop, OverflowPolicy::Spill] {
+            assert_eq!(policy.to_string().parse::<OverflowPolicy>().unwrap(), policy);
+        }
+        assert!("discard".parse::<OverflowPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_journal_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut journal = Journal::open(temp_dir.path()).unwrap();
+        let event = AuditEvent::new(
+            AuditEventType::ConfigAccessed {
+                namespace: "app".to_string(),
+                key: "model".to_string(),
+                environment: "production".to_string(),
+            },
+            "alice",
+        );
+        journal.append(&event).unwrap();
+
+        // A torn write at the end is skipped
+        write!(journal.file, "{{\"id\":").unwrap();
+        let events = Journal::open(temp_dir.path()).unwrap().read().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, event.id);
+
+        journal.truncate().unwrap();
+        assert!(journal.read().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,462 @@
+//! Audit event sinks
+//!
+//! Sinks forward audit events to external destinations in addition to the
+//! queryable [`AuditStorage`](crate::storage::AuditStorage):
+//! - [`SyslogSink`]: RFC 5424 messages over UDP
+//! - [`WebhookSink`]: JSON POSTs with retry and exponential backoff
+//! - [`FanOutSink`]: delivers each event to several sinks concurrently
+
+use crate::{
+    events::{AuditEvent, AuditSeverity},
+    AuditError, Result,
+};
+use llm_config_core::error_utils::{retry_with_backoff, RetryPolicy};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+
+/// Destination for audit events
+#[async_trait::async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Deliver an audit event
+    async fn send(&self, event: &AuditEvent) -> Result<()>;
+
+    /// Get sink name
+    fn name(&self) -> &str;
+}
+
+/// Syslog facility `local0`, used unless configured otherwise
+pub const DEFAULT_SYSLOG_FACILITY: u8 = 16;
+
+/// Private enterprise number used for the structured data ID
+const SD_ID: &str = "audit@32473";
+
+/// Syslog sink sending RFC 5424 messages over UDP
+pub struct SyslogSink {
+    socket: UdpSocket,
+    target: SocketAddr,
+    facility: u8,
+    hostname: String,
+    app_name: String,
+}
+
+impl SyslogSink {
+    /// Create a sink sending to a syslog server over UDP
+    pub async fn udp(target: SocketAddr) -> Result<Self> {
+        let bind_addr: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(bind_addr).await?;
+
+        Ok(Self {
+            socket,
+            target,
+            facility: DEFAULT_SYSLOG_FACILITY,
+            hostname: "-".to_string(),
+            app_name: "llm-config".to_string(),
+        })
+    }
+
+    /// Set the syslog facility (0-23)
+    pub fn with_facility(mut self, facility: u8) -> Self {
+        self.facility = facility.min(23);
+        self
+    }
+
+    /// Set the HOSTNAME field
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    /// Set the APP-NAME field
+    pub fn with_app_name(mut self, app_name: impl Into<String>) -> Self {
+        self.app_name = app_name.into();
+        self
+    }
+
+    /// Format an event as an RFC 5424 message
+    pub fn format_message(&self, event: &AuditEvent) -> String {
+        let priority = self.facility as u32 * 8 + syslog_severity(event.severity) as u32;
+
+        let mut params = vec![
+            ("id", event.id.to_string()),
+            ("user", event.user.clone()),
+        ];
+        if let Some((namespace, key, environment)) = event.event_type.target() {
+            params.push(("namespace", namespace.to_string()));
+            params.push(("key", key.to_string()));
+            params.push(("environment", environment.to_string()));
+        }
+        if let Some(ip) = &event.source_ip {
+            params.push(("source_ip", ip.clone()));
+        }
+        if let Some(request_id) = &event.request_id {
+            params.push(("request_id", request_id.clone()));
+        }
+
+        let structured_data: String = params
+            .iter()
+            .map(|(name, value)| format!(" {}=\"{}\"", name, escape_param_value(value)))
+            .collect();
+
+        format!(
+            "<{}>1 {} {} {} {} {} [{}{}] {}",
+            priority,
+            event
+                .timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            header_field(&self.hostname, 255),
+            header_field(&self.app_name, 48),
+            std::process::id(),
+            event.event_type.name(),
+            SD_ID,
+            structured_data,
+            event.summary()
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for SyslogSink {
+    async fn send(&self, event: &AuditEvent) -> Result<()> {
+        let message = self.format_message(event);
+        self.socket.send_to(message.as_bytes(), self.target).await?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "syslog"
+    }
+}
+
+/// Map an audit severity to a syslog severity
+fn syslog_severity(severity: AuditSeverity) -> u8 {
+    match severity {
+        AuditSeverity::Debug => 7,
+        AuditSeverity::Info => 6,
+        AuditSeverity::Warning => 4,
+        AuditSeverity::Error => 3,
+        AuditSeverity::Critical => 2,
+    }
+}
+
+/// Header fields are printable ASCII without spaces; empty values become NILVALUE
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// Escape `"`, `\` and `]` in structured data parameter values
+fn escape_param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Error from a single webhook delivery attempt
+#[derive(Debug)]
+enum WebhookError {
+    Transport(reqwest::Error),
+    Status(reqwest::StatusCode),
+}
+
+impl WebhookError {
+    /// Connection problems, throttling, and server errors are worth retrying
+    fn is_retriable(&self) -> bool {
+        match self {
+            WebhookError::Transport(_) => true,
+            WebhookError::Status(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::Transport(e) => write!(f, "request failed: {}", e),
+            WebhookError::Status(status) => write!(f, "unexpected status {}", status),
+        }
+    }
+}
+
+/// Webhook sink POSTing each event as JSON
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+    headers: Vec<(String, String)>,
+    retry_policy: RetryPolicy,
+}
+
+impl WebhookSink {
+    /// Create a webhook sink for a URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            headers: Vec::new(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Add a header to every request (e.g. an authorization token)
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the retry policy for failed deliveries
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    async fn post(&self, event: &AuditEvent) -> std::result::Result<(), WebhookError> {
+        let mut request = self.client.post(&self.url).json(event);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await.map_err(WebhookError::Transport)?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(WebhookError::Status(response.status()))
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for WebhookSink {
+    async fn send(&self, event: &AuditEvent) -> Result<()> {
+        retry_with_backoff(
+            || self.post(event),
+            self.retry_policy.clone(),
+            WebhookError::is_retriable,
+        )
+        .await
+        .map_err(|e| AuditError::Sink(format!("webhook {}: {}", self.url, e)))
+    }
+
+    fn name(&self) -> &str {
+        "webhook"
+    }
+}
+
+/// Sink delivering every event to multiple sinks concurrently
+#[derive(Default)]
+pub struct FanOutSink {
+    sinks: Vec<Arc<dyn AuditSink>>,
+}
+
+impl FanOutSink {
+    /// Create an empty fan-out sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a destination sink
+    pub fn with_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Number of destination sinks
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Check if there are no destination sinks
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for FanOutSink {
+    /// Delivers to every sink; a failure in one does not stop the others
+    async fn send(&self, event: &AuditEvent) -> Result<()> {
+        let results =
+            futures::future::join_all(self.sinks.iter().map(|sink| sink.send(event))).await;
+
+        let failures: Vec<String> = self
+            .sinks
+            .iter()
+            .zip(results)
+            .filter_map(|(sink, result)| result.err().map(|e| format!("{}: {}", sink.name(), e)))
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AuditError::Sink(failures.join("; ")))
+        }
+    }
+
+    fn name(&self) -> &str {
+        "fan-out"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::AuditEventType;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn test_event() -> AuditEvent {
+        AuditEvent::new(
+            AuditEventType::ConfigUpdated {
+                namespace: "app".to_string(),
+                key: "model".to_string(),
+                environment: "production".to_string(),
+                old_version: 1,
+                new_version: 2,
+            },
+            "ali\"ce]",
+        )
+        .with_severity(AuditSeverity::Warning)
+    }
+
+    #[derive(Default)]
+    struct CollectingSink {
+        events: Mutex<Vec<AuditEvent>>,
+    }
+
+    #[async_trait::async_trait]
+    impl AuditSink for CollectingSink {
+        async fn send(&self, event: &AuditEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "collecting"
+        }
+    }
+
+    struct FailingSink;
+
+    #[async_trait::async_trait]
+    impl AuditSink for FailingSink {
+        async fn send(&self, _event: &AuditEvent) -> Result<()> {
+            Err(AuditError::Sink("unavailable".to_string()))
+        }
+
+        fn name(&self) -> &str {
+            "failing"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_syslog_message_format() {
+        let sink = SyslogSink::udp("127.0.0.1:514".parse().unwrap())
+            .await
+            .unwrap()
+            .with_hostname("host-1");
+        let event = test_event();
+
+        let message = sink.format_message(&event);
+
+        // local0 (16) * 8 + warning (4)
+        assert!(message.starts_with("<132>1 "));
+        assert!(message.contains(" host-1 llm-config "));
+        assert!(message.contains(" config_updated [audit@32473 id="));
+        assert!(message.contains(r#"user="ali\"ce\]""#));
+        assert!(message.contains(r#"namespace="app" key="model" environment="production""#));
+        assert!(message.ends_with(&event.summary()));
+    }
+
+    #[tokio::test]
+    async fn test_syslog_udp_delivery() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sink = SyslogSink::udp(server.local_addr().unwrap()).await.unwrap();
+
+        sink.send(&test_event()).await.unwrap();
+
+        let mut buf = vec![0u8; 2048];
+        let len = server.recv(&mut buf).await.unwrap();
+        let message = String::from_utf8_lossy(&buf[..len]);
+        assert!(message.starts_with("<132>1 "));
+    }
+
+    /// Serve `statuses` in order, one per connection, returning the request count
+    async fn spawn_webhook_server(statuses: Vec<u16>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/audit", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = Arc::clone(&requests);
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let _ = stream.read(&mut buf).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn test_webhook_retries_server_errors() {
+        let (url, requests) = spawn_webhook_server(vec![503, 500, 200]).await;
+        let sink = WebhookSink::new(url).with_retry_policy(RetryPolicy::new(3, 1, 10, 2.0));
+
+        sink.send(&test_event()).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_does_not_retry_client_errors() {
+        let (url, requests) = spawn_webhook_server(vec![400, 200]).await;
+        let sink = WebhookSink::new(url).with_retry_policy(RetryPolicy::new(3, 1, 10, 2.0));
+
+        assert!(sink.send(&test_event()).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_delivers_to_all_sinks() {
+        let first = Arc::new(CollectingSink::default());
+        let second = Arc::new(CollectingSink::default());
+        let sink = FanOutSink::new()
+            .with_sink(first.clone())
+            .with_sink(Arc::new(FailingSink))
+            .with_sink(second.clone());
+
+        let err = sink.send(&test_event()).await.unwrap_err();
+        assert!(err.to_string().contains("failing"));
+
+        // The failing sink does not prevent delivery elsewhere
+        assert_eq!(first.events.lock().unwrap().len(), 1);
+        assert_eq!(second.events.lock().unwrap().len(), 1);
+    }
+}
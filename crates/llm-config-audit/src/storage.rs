@@ -2,15 +2,20 @@
 
 use crate::{events::AuditEvent, AuditError, Result};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 /// Trait for audit log storage backends
 pub trait AuditStorage: Send + Sync {
     /// Store an audit event
     fn store(&self, event: &AuditEvent) -> Result<()>;
 
+    /// Read all stored events in log order
+    fn read_all(&self) -> Result<Vec<AuditEvent>>;
+
     /// Query audit events within a time range
     fn query(
         &self,
@@ -24,11 +29,47 @@ pub trait AuditStorage: Send + Sync {
 
     /// Get total event count
     fn count(&self) -> Result<usize>;
+
+    /// Delete the events logged before `cutoff` from the start of the log,
+    /// returning how many were deleted
+    ///
+    /// Only a prefix of the log is deleted, so the hash chain of the rest
+    /// stays intact. Storage that cannot delete events keeps them.
+    fn prune_before(&self, _cutoff: DateTime<Utc>) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Hash of the last deleted event, which the first remaining event links to
+    fn pruned_hash(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
 
 /// File-based audit log storage
+///
+/// Events are hash chained: each stored event records the hash of the
+/// event written before it. When old events are pruned, the log is
+/// rewritten to start with an anchor line holding the hash of the last
+/// pruned event, so the chain can still be verified from the first
+/// remaining event. The anchor is part of the rewritten log, which replaces
+/// the old one in a single rename.
 pub struct FileAuditStorage {
     log_path: PathBuf,
+    last_hash: Mutex<Option<String>>,
+}
+
+/// First line of a pruned log
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PruneAnchor {
+    /// Hash of the last pruned event
+    pruned_hash: Option<String>,
+}
+
+/// A line of the log
+enum LogLine {
+    Anchor(Option<String>),
+    Event(String),
 }
 
 impl FileAuditStorage {
@@ -37,9 +78,68 @@ impl FileAuditStorage {
         let log_dir = log_dir.as_ref();
         std::fs::create_dir_all(log_dir)?;
 
-        let log_path = log_dir.join("audit.log");
+        let mut storage = Self {
+            log_path: log_dir.join("audit.log"),
+            last_hash: Mutex::new(None),
+        };
+        *storage.last_hash.get_mut().unwrap() = storage.read_last_hash()?;
+        Ok(storage)
+    }
+
+    /// Read the hash of the last event in an existing log, or of the last
+    /// pruned one if every event was pruned
+    fn read_last_hash(&self) -> Result<Option<String>> {
+        let mut anchor = None;
+        let mut last_line = None;
+        for line in self.lines()? {
+            match line? {
+                LogLine::Anchor(hash) => anchor = hash,
+                LogLine::Event(line) => last_line = Some(line),
+            }
+        }
 
-        Ok(Self { log_path })
+        match last_line {
+            Some(line) => {
+                let event: AuditEvent = serde_json::from_str(&line)
+                    .map_err(|e| AuditError::Serialization(e.to_string()))?;
+                Ok(event.hash)
+            }
+            None => Ok(anchor),
+        }
+    }
+
+    /// Non-empty lines of the log, the anchor of a pruned log first
+    fn lines(&self) -> Result<Box<dyn Iterator<Item = Result<LogLine>>>> {
+        if !self.log_path.exists() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+
+        let reader = BufReader::new(File::open(&self.log_path)?);
+        let mut first = true;
+        Ok(Box::new(reader.lines().filter_map(move |line| {
+            let line = match line {
+                Ok(line) if line.trim().is_empty() => return None,
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if std::mem::take(&mut first) {
+                if let Ok(anchor) = serde_json::from_str::<PruneAnchor>(&line) {
+                    return Some(Ok(LogLine::Anchor(anchor.pruned_hash)));
+                }
+            }
+            Some(Ok(LogLine::Event(line)))
+        })))
+    }
+
+    /// Events of the log in order
+    fn events(&self) -> Result<impl Iterator<Item = Result<AuditEvent>>> {
+        Ok(self.lines()?.filter_map(|line| match line {
+            Ok(LogLine::Anchor(_)) => None,
+            Ok(LogLine::Event(line)) => Some(
+                serde_json::from_str(&line).map_err(|e| AuditError::Serialization(e.to_string())),
+            ),
+            Err(e) => Some(Err(e)),
+        }))
     }
 
     /// Get the current log file path
@@ -61,44 +161,44 @@ impl FileAuditStorage {
 
 impl AuditStorage for FileAuditStorage {
     fn store(&self, event: &AuditEvent) -> Result<()> {
+        // Hold the lock until the write completes to keep the chain linear
+        let mut last_hash = self
+            .last_hash
+            .lock()
+            .map_err(|e| AuditError::Storage(format!("Lock poisoned: {}", e)))?;
+
+        let event = event.clone().seal(last_hash.clone());
+
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&self.log_path)?;
 
-        let json = serde_json::to_string(event)
+        let json = serde_json::to_string(&event)
             .map_err(|e| AuditError::Serialization(e.to_string()))?;
 
         writeln!(file, "{}", json)?;
         file.sync_all()?;
 
+        *last_hash = event.hash;
+
         Ok(())
     }
 
+    fn read_all(&self) -> Result<Vec<AuditEvent>> {
+        self.events()?.collect()
+    }
+
     fn query(
         &self,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
         limit: Option<usize>,
     ) -> Result<Vec<AuditEvent>> {
-        if !self.log_path.exists() {
-            return Ok(Vec::new());
-        }
-
-        let file = File::open(&self.log_path)?;
-        let reader = BufReader::new(file);
-
         let mut events = Vec::new();
 
-        for line in reader.lines() {
-            let line = line?;
-            if line.trim().is_empty() {
-                continue;
-            }
-
-            let event: AuditEvent = serde_json::from_str(&line)
-                .map_err(|e| AuditError::Serialization(e.to_string()))?;
-
+        for event in self.events()? {
+            let event = event?;
             if event.timestamp >= start && event.timestamp <= end {
                 events.push(event);
 
@@ -114,24 +214,10 @@ impl AuditStorage for FileAuditStorage {
     }
 
     fn query_by_user(&self, user: &str, limit: Option<usize>) -> Result<Vec<AuditEvent>> {
-        if !self.log_path.exists() {
-            return Ok(Vec::new());
-        }
-
-        let file = File::open(&self.log_path)?;
-        let reader = BufReader::new(file);
-
         let mut events = Vec::new();
 
-        for line in reader.lines() {
-            let line = line?;
-            if line.trim().is_empty() {
-                continue;
-            }
-
-            let event: AuditEvent = serde_json::from_str(&line)
-                .map_err(|e| AuditError::Serialization(e.to_string()))?;
-
+        for event in self.events()? {
+            let event = event?;
             if event.user == user {
                 events.push(event);
 
@@ -147,16 +233,66 @@ impl AuditStorage for FileAuditStorage {
     }
 
     fn count(&self) -> Result<usize> {
-        if !self.log_path.exists() {
-            return Ok(0);
+        let mut count = 0;
+        for line in self.lines()? {
+            if let LogLine::Event(_) = line? {
+                count += 1;
+            }
         }
+        Ok(count)
+    }
 
-        let file = File::open(&self.log_path)?;
-        let reader = BufReader::new(file);
+    fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        // No events are appended while the log is rewritten
+        let _last_hash = self
+            .last_hash
+            .lock()
+            .map_err(|e| AuditError::Storage(format!("Lock poisoned: {}", e)))?;
+
+        // Kept lines are copied as is, so their hashes stay valid
+        let mut lines = Vec::new();
+        for line in self.lines()? {
+            if let LogLine::Event(line) = line? {
+                lines.push(line);
+            }
+        }
+        let mut pruned = 0;
+        let mut anchor = None;
+        for line in &lines {
+            let event: AuditEvent = serde_json::from_str(line)
+                .map_err(|e| AuditError::Serialization(e.to_string()))?;
+            if event.timestamp >= cutoff {
+                break;
+            }
+            pruned += 1;
+            anchor = event.hash;
+        }
+        if pruned == 0 {
+            return Ok(0);
+        }
 
-        let count = reader.lines().filter(|l| l.is_ok()).count();
+        // The anchor goes into the new log itself, so a crash leaves either
+        // the old log or the new one with its anchor
+        let anchor = serde_json::to_string(&PruneAnchor { pruned_hash: anchor })
+            .map_err(|e| AuditError::Serialization(e.to_string()))?;
+        let temp_path = self.log_path.with_extension("log.tmp");
+        {
+            let mut file = File::create(&temp_path)?;
+            writeln!(file, "{}", anchor)?;
+            for line in &lines[pruned..] {
+                writeln!(file, "{}", line)?;
+            }
+            file.sync_all()?;
+        }
+        std::fs::rename(&temp_path, &self.log_path)?;
+        Ok(pruned)
+    }
 
-        Ok(count)
+    fn pruned_hash(&self) -> Result<Option<String>> {
+        match self.lines()?.next().transpose()? {
+            Some(LogLine::Anchor(hash)) => Ok(hash),
+            _ => Ok(None),
+        }
     }
 }
 
@@ -271,4 +407,98 @@ mod tests {
         let events = storage.query(start, end, Some(10)).unwrap();
         assert_eq!(events.len(), 10);
     }
+
+    #[test]
+    fn test_events_are_hash_chained() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let store_event = |storage: &FileAuditStorage, i: usize| {
+            let event = AuditEvent::new(
+                AuditEventType::ConfigAccessed {
+                    namespace: "test".to_string(),
+                    key: format!("key{}", i),
+                    environment: "dev".to_string(),
+                },
+                "user",
+            );
+            storage.store(&event).unwrap();
+        };
+
+        let storage = FileAuditStorage::new(temp_dir.path()).unwrap();
+        store_event(&storage, 0);
+        store_event(&storage, 1);
+
+        // The chain continues across reopening the log
+        let storage = FileAuditStorage::new(temp_dir.path()).unwrap();
+        store_event(&storage, 2);
+
+        let events = storage.read_all().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].prev_hash, None);
+        assert_eq!(events[1].prev_hash, events[0].hash);
+        assert_eq!(events[2].prev_hash, events[1].hash);
+        assert!(crate::integrity::verify_chain(&events, 0, None).is_intact());
+    }
+
+    fn system_event() -> AuditEventType {
+        AuditEventType::SystemEvent {
+            component: "test".to_string(),
+            message: "tick".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_prune_before() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileAuditStorage::new(temp_dir.path()).unwrap();
+        let now = Utc::now();
+        for days_ago in [30, 20, 10, 0] {
+            let mut event = AuditEvent::new(system_event(), "system");
+            event.timestamp = now - chrono::Duration::days(days_ago);
+            storage.store(&event).unwrap();
+        }
+        let events = storage.read_all().unwrap();
+        assert_eq!(storage.pruned_hash().unwrap(), None);
+
+        assert_eq!(storage.prune_before(now - chrono::Duration::days(15)).unwrap(), 2);
+        assert_eq!(storage.prune_before(now - chrono::Duration::days(15)).unwrap(), 0);
+        let remaining = storage.read_all().unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(storage.pruned_hash().unwrap(), events[1].hash);
+
+        // The rest of the chain verifies from the pruned hash, and goes on
+        let anchor = storage.pruned_hash().unwrap();
+        assert!(crate::integrity::verify_chain(&remaining, 0, anchor.as_deref()).is_intact());
+        assert!(!crate::integrity::verify_chain(&remaining, 0, None).is_intact());
+        storage.store(&AuditEvent::new(system_event(), "system")).unwrap();
+        let remaining = storage.read_all().unwrap();
+        assert!(crate::integrity::verify_chain(&remaining, 0, anchor.as_deref()).is_intact());
+
+        // The anchor is kept in the log itself
+        assert!(!temp_dir.path().join("audit.log.anchor").exists());
+        let reopened = FileAuditStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.pruned_hash().unwrap(), anchor);
+        assert_eq!(reopened.count().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_prune_all_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileAuditStorage::new(temp_dir.path()).unwrap();
+        for _ in 0..2 {
+            storage.store(&AuditEvent::new(system_event(), "system")).unwrap();
+        }
+        let events = storage.read_all().unwrap();
+
+        assert_eq!(storage.prune_before(Utc::now() + chrono::Duration::days(1)).unwrap(), 2);
+        assert!(storage.read_all().unwrap().is_empty());
+        assert_eq!(storage.count().unwrap(), 0);
+
+        // Events stored after a restart still link to the pruned ones
+        let reopened = FileAuditStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.pruned_hash().unwrap(), events[1].hash);
+        reopened.store(&AuditEvent::new(system_event(), "system")).unwrap();
+        let remaining = reopened.read_all().unwrap();
+        assert_eq!(remaining[0].prev_hash, events[1].hash);
+    }
 }
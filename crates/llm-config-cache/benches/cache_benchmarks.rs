@@ -21,6 +21,9 @@ fn create_test_entry(namespace: &str, key: &str, env: Environment) -> ConfigEntr
             updated_by: "bench".to_string(),
             tags: vec![],
             description: None,
+            context: None,
+            merge: None,
+            signature: None,
         },
     }
 }
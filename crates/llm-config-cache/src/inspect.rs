@@ -0,0 +1,148 @@
+//! Inspecting and flushing the tiers
+//!
+//! Support for debugging stale reads on a running cache: per-tier
+//! statistics, finding out which tier answers a key without disturbing it,
+//! and flushing one tier or one namespace.
+
+use crate::{CacheError, CacheManager, Result};
+use llm_config_core::ConfigEntry;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::Ordering;
+
+/// A tier of the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheTier {
+    L1,
+    L2,
+}
+
+impl fmt::Display for CacheTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheTier::L1 => write!(f, "l1"),
+            CacheTier::L2 => write!(f, "l2"),
+        }
+    }
+}
+
+/// L2 cache statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct L2Stats {
+    pub size: usize,
+    /// Approximate size of the cached entries in bytes, if the backend
+    /// tracks it
+    pub bytes: Option<usize>,
+    /// Lookups answered by L2 after missing L1, since L2 was last cleared
+    pub hit_count: u64,
+    pub miss_count: u64,
+    pub hit_rate: f64,
+}
+
+impl CacheManager {
+    /// Get L2 cache statistics
+    pub fn l2_stats(&self) -> L2Stats {
+        let hit_count = self.l2_hits.load(Ordering::Relaxed);
+        let miss_count = self.l2_misses.load(Ordering::Relaxed);
+        L2Stats {
+            size: self.l2.size(),
+            bytes: self.l2.bytes(),
+            hit_count,
+            miss_count,
+            hit_rate: if hit_count + miss_count > 0 {
+                hit_count as f64 / (hit_count + miss_count) as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// The first tier holding an entry, and the entry it holds
+    ///
+    /// Unlike [`get`](Self::get), nothing is counted, promoted or marked as
+    /// used.
+    pub fn peek(&self, namespace: &str, key: &str, env: &str) -> Result<Option<(CacheTier, ConfigEntry)>> {
+        if let Some(entry) = self.l1.peek(namespace, key, env) {
+            return Ok(Some((CacheTier::L1, entry)));
+        }
+        match self.l2.get(namespace, key, env) {
+            Ok(entry) => Ok(Some((CacheTier::L2, entry))),
+            Err(CacheError::CacheMiss(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove entries from `tier` (both tiers if `None`), only those under
+    /// the `/`-separated `prefix` if one is given
+    ///
+    /// Flushing only L2 keeps L1's copies, which are still served.
+    pub fn flush(&self, tier: Option<CacheTier>, prefix: Option<&str>) -> Result<()> {
+        match (tier, prefix) {
+            (None, None) => self.clear(),
+            (None, Some(prefix)) => self.invalidate_prefix(prefix).map(drop),
+            (Some(CacheTier::L1), None) => {
+                self.clear_l1();
+                Ok(())
+            }
+            (Some(CacheTier::L1), Some(prefix)) => {
+                self.l1.invalidate_prefix(prefix);
+                Ok(())
+            }
+            (Some(CacheTier::L2), None) => self.clear_l2(),
+            (Some(CacheTier::L2), Some(prefix)) => {
+                self.l2.invalidate_prefix(prefix)?;
+                self.record_l2_size();
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_core::{ConfigManager, ConfigValue, Environment};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_peek_and_flush() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("storage")).unwrap();
+        let env = Environment::Production;
+        for namespace in ["team-a", "team-a/prompts", "team-b"] {
+            manager.set(namespace, "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        }
+        let cache = CacheManager::new(100, temp_dir.path().join("cache")).unwrap();
+        cache.warm(&manager, &["team-a", "team-a/prompts", "team-b"], Some(env)).unwrap();
+
+        let (tier, entry) = cache.peek("team-a", "model", "production").unwrap().unwrap();
+        assert_eq!((tier, entry.version), (CacheTier::L1, 1));
+        assert_eq!(cache.l1_stats().hit_count, 0);
+        assert!(cache.peek("team-a", "missing", "production").unwrap().is_none());
+
+        cache.flush(Some(CacheTier::L1), Some("team-a")).unwrap();
+        assert_eq!(cache.peek("team-a/prompts", "model", "production").unwrap().unwrap().0, CacheTier::L2);
+        assert_eq!(cache.peek("team-b", "model", "production").unwrap().unwrap().0, CacheTier::L1);
+
+        // Served from L2 and promoted
+        cache.get("team-a", "model", "production").unwrap();
+        assert_eq!(cache.l2_stats().hit_count, 1);
+        assert_eq!(cache.peek("team-a", "model", "production").unwrap().unwrap().0, CacheTier::L1);
+
+        cache.flush(None, Some("team-a")).unwrap();
+        assert!(cache.peek("team-a", "model", "production").unwrap().is_none());
+        assert_eq!(cache.l2_stats().size, 1);
+
+        cache.flush(Some(CacheTier::L2), None).unwrap();
+        let empty = L2Stats {
+            size: 0,
+            bytes: Some(0),
+            hit_count: 0,
+            miss_count: 0,
+            hit_rate: 0.0,
+        };
+        assert_eq!(cache.l2_stats(), empty);
+        assert_eq!(cache.l1_stats().size, 1);
+    }
+}
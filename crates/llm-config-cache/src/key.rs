@@ -0,0 +1,86 @@
+//! Structured cache keys
+
+use llm_config_core::ConfigEntry;
+use std::fmt;
+
+/// Hierarchical cache key (namespace / key / environment)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub namespace: String,
+    pub key: String,
+    pub env: String,
+}
+
+impl CacheKey {
+    /// Create a new cache key
+    pub fn new(namespace: impl Into<String>, key: impl Into<String>, env: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            key: key.into(),
+            env: env.into(),
+        }
+    }
+
+    /// Create the cache key for a config entry
+    pub fn for_entry(entry: &ConfigEntry) -> Self {
+        Self::new(&entry.namespace, &entry.key, entry.environment.to_string())
+    }
+
+    /// Path segments of the key, from most to least general
+    fn segments(&self) -> impl Iterator<Item = &str> {
+        self.namespace
+            .split('/')
+            .chain([self.key.as_str(), self.env.as_str()])
+    }
+
+    /// Check whether the key falls under a `/`-separated prefix
+    ///
+    /// Prefixes match whole segments: `team-a` matches `team-a/prompts`
+    /// but not `team-ab`, and `team-a/prompts/summary` matches the
+    /// `summary` key of the `team-a/prompts` namespace in every environment.
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        let prefix = prefix.trim_matches('/');
+        if prefix.is_empty() {
+            return true;
+        }
+
+        let mut segments = self.segments();
+        prefix
+            .split('/')
+            .all(|expected| segments.next() == Some(expected))
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.namespace, self.key, self.env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let key = CacheKey::new("team-a/prompts", "summary", "production");
+        assert_eq!(key.to_string(), "team-a/prompts:summary:production");
+    }
+
+    #[test]
+    fn test_has_prefix() {
+        let key = CacheKey::new("team-a/prompts", "summary", "production");
+
+        assert!(key.has_prefix(""));
+        assert!(key.has_prefix("team-a"));
+        assert!(key.has_prefix("team-a/"));
+        assert!(key.has_prefix("team-a/prompts"));
+        assert!(key.has_prefix("team-a/prompts/summary"));
+        assert!(key.has_prefix("team-a/prompts/summary/production"));
+
+        assert!(!key.has_prefix("team"));
+        assert!(!key.has_prefix("team-a/prompts/sum"));
+        assert!(!key.has_prefix("team-a/prompts/summary/staging"));
+        assert!(!key.has_prefix("team-a/prompts/summary/production/extra"));
+    }
+}
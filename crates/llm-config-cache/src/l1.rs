@@ -1,8 +1,17 @@
 //! L1 in-memory cache with LRU eviction
-
-use crate::{CacheError, Result};
+//!
+//! Sizing and admission are configured with [`L1Config`]; see
+//! [`crate::policy`] for how victims are chosen.
+
+use crate::{
+    key::CacheKey,
+    policy::{AdmissionPolicy, EvictionCause, L1Config},
+    sketch::FrequencySketch,
+    CacheError, Result,
+};
 use chrono::{DateTime, Utc};
 use llm_config_core::ConfigEntry;
+use llm_config_sink::MetricsSink;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -10,111 +19,323 @@ use std::sync::{Arc, RwLock};
 #[derive(Debug, Clone)]
 struct CachedEntry {
     entry: ConfigEntry,
+    /// When the entry was loaded from storage, for TTLs
+    cached_at: DateTime<Utc>,
     accessed_at: DateTime<Utc>,
     access_count: u64,
+    /// Approximate size in bytes
+    size: usize,
+}
+
+/// Cached entries and their total size
+#[derive(Debug, Default)]
+struct Entries {
+    map: HashMap<CacheKey, CachedEntry>,
+    bytes: usize,
+}
+
+impl Entries {
+    fn remove(&mut self, cache_key: &CacheKey) -> Option<CachedEntry> {
+        let removed = self.map.remove(cache_key)?;
+        self.bytes -= removed.size;
+        Some(removed)
+    }
 }
 
 /// L1 in-memory cache with LRU eviction policy
 pub struct L1Cache {
-    cache: Arc<RwLock<HashMap<String, CachedEntry>>>,
-    max_size: usize,
+    cache: Arc<RwLock<Entries>>,
+    config: L1Config,
+    sketch: Option<Arc<RwLock<FrequencySketch>>>,
     hit_count: Arc<RwLock<u64>>,
     miss_count: Arc<RwLock<u64>>,
+    namespace_stats: Arc<RwLock<HashMap<String, NamespaceStats>>>,
+    metrics: Option<Arc<dyn MetricsSink>>,
 }
 
 impl L1Cache {
     /// Create a new L1 cache with specified max size
     pub fn new(max_size: usize) -> Self {
+        Self::with_config(L1Config::new(max_size))
+    }
+
+    /// Create a new L1 cache with byte limits, namespace weights or admission policy
+    pub fn with_config(config: L1Config) -> Self {
+        let sketch = (config.admission == AdmissionPolicy::TinyLfu)
+            .then(|| Arc::new(RwLock::new(FrequencySketch::new(config.max_entries))));
+
         Self {
-            cache: Arc::new(RwLock::new(HashMap::new())),
-            max_size,
+            cache: Arc::new(RwLock::new(Entries::default())),
+            config,
+            sketch,
             hit_count: Arc::new(RwLock::new(0)),
             miss_count: Arc::new(RwLock::new(0)),
+            namespace_stats: Arc::new(RwLock::new(HashMap::new())),
+            metrics: None,
         }
     }
 
-    /// Generate cache key from namespace, key, and environment
-    fn cache_key(namespace: &str, key: &str, env: &str) -> String {
-        format!("{}:{}:{}", namespace, key, env)
+    /// Record hits, misses, evictions and size in the given sink as the `l1` tier
+    pub fn with_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 
     /// Get an entry from the cache
     pub fn get(&self, namespace: &str, key: &str, env: &str) -> Result<ConfigEntry> {
-        let cache_key = Self::cache_key(namespace, key, env);
+        self.get_with_cached_at(namespace, key, env).map(|(entry, _)| entry)
+    }
+
+    /// Get an entry from the cache and when it was loaded from storage
+    pub fn get_with_cached_at(&self, namespace: &str, key: &str, env: &str) -> Result<(ConfigEntry, DateTime<Utc>)> {
+        let cache_key = CacheKey::new(namespace, key, env);
+        self.record_access(&cache_key);
 
         let mut cache = self.cache.write().unwrap();
 
-        if let Some(cached) = cache.get_mut(&cache_key) {
+        if let Some(cached) = cache.map.get_mut(&cache_key) {
             // Update access metadata
             cached.accessed_at = Utc::now();
             cached.access_count += 1;
 
             // Increment hit counter
             *self.hit_count.write().unwrap() += 1;
+            if let Some(metrics) = &self.metrics {
+                metrics.record_counter("cache_hits_total", &[("tier", "l1")], 1.0);
+            }
 
-            Ok(cached.entry.clone())
+            Ok((cached.entry.clone(), cached.cached_at))
         } else {
             // Increment miss counter
             *self.miss_count.write().unwrap() += 1;
+            if let Some(metrics) = &self.metrics {
+                metrics.record_counter("cache_misses_total", &[("tier", "l1")], 1.0);
+            }
 
-            Err(CacheError::CacheMiss(cache_key))
+            Err(CacheError::CacheMiss(cache_key.to_string()))
         }
     }
 
+    /// Get an entry without counting a hit or miss or marking it as used
+    pub fn peek(&self, namespace: &str, key: &str, env: &str) -> Option<ConfigEntry> {
+        let cache_key = CacheKey::new(namespace, key, env);
+        let cache = self.cache.read().unwrap();
+        cache.map.get(&cache_key).map(|cached| cached.entry.clone())
+    }
+
     /// Put an entry into the cache
+    ///
+    /// When the cache is full the entry may be rejected by the admission
+    /// policy, in which case it is simply not cached.
     pub fn put(&self, entry: ConfigEntry) -> Result<()> {
-        let cache_key = Self::cache_key(&entry.namespace, &entry.key, &entry.environment.to_string());
+        self.put_cached_at(entry, Utc::now())
+    }
+
+    /// Put an entry loaded from storage at `cached_at` into the cache
+    pub fn put_cached_at(&self, entry: ConfigEntry, cached_at: DateTime<Utc>) -> Result<()> {
+        let cache_key = CacheKey::for_entry(&entry);
+        let size = self.config.weigher.weigh(&entry);
+        self.record_access(&cache_key);
 
         let mut cache = self.cache.write().unwrap();
 
-        // Check if we need to evict
-        if cache.len() >= self.max_size && !cache.contains_key(&cache_key) {
-            self.evict_lru(&mut cache)?;
+        // Updates replace the cached entry without an admission check
+        let is_update = cache.remove(&cache_key).is_some();
+
+        if self.config.max_bytes.is_some_and(|max_bytes| size > max_bytes) {
+            self.record_rejection(&cache_key.namespace);
+            return Ok(());
+        }
+
+        // Evict until the entry fits
+        let mut admitted = is_update;
+        while let Some(cause) = self.pressure(&cache, size) {
+            let victim = self.select_victim(&cache)?;
+
+            if !admitted {
+                if !self.admit(&cache_key, &victim) {
+                    self.record_rejection(&cache_key.namespace);
+                    return Ok(());
+                }
+                admitted = true;
+            }
+
+            cache.remove(&victim);
+            self.record_eviction(&victim.namespace, cause);
         }
 
         // Insert or update entry
-        cache.insert(
+        cache.bytes += size;
+        cache.map.insert(
             cache_key,
             CachedEntry {
                 entry,
+                cached_at,
                 accessed_at: Utc::now(),
                 access_count: 1,
+                size,
             },
         );
+        self.record_size(&cache);
 
         Ok(())
     }
 
-    /// Evict the least recently used entry
-    fn evict_lru(&self, cache: &mut HashMap<String, CachedEntry>) -> Result<()> {
-        if cache.is_empty() {
-            return Ok(());
+    /// Limit that inserting an entry of `size` bytes would exceed, if any
+    fn pressure(&self, cache: &Entries, size: usize) -> Option<EvictionCause> {
+        if cache.map.is_empty() {
+            None
+        } else if cache.map.len() >= self.config.max_entries {
+            Some(EvictionCause::EntryLimit)
+        } else if self
+            .config
+            .max_bytes
+            .is_some_and(|max_bytes| cache.bytes + size > max_bytes)
+        {
+            Some(EvictionCause::ByteLimit)
+        } else {
+            None
         }
+    }
 
-        // Find LRU entry
-        let lru_key = cache
-            .iter()
-            .min_by_key(|(_, entry)| entry.accessed_at)
-            .map(|(k, _)| k.clone())
-            .ok_or_else(|| CacheError::Eviction("Failed to find LRU entry".to_string()))?;
+    /// Pick the entry to evict
+    ///
+    /// Without namespace weights this is the least recently used entry.
+    /// Otherwise it is the least recently used entry of the namespace whose
+    /// usage is largest relative to its weight.
+    fn select_victim(&self, cache: &Entries) -> Result<CacheKey> {
+        let candidates: Vec<(&CacheKey, &CachedEntry)> = if self.config.namespace_weights.is_empty() {
+            cache.map.iter().collect()
+        } else {
+            let mut usage: HashMap<&str, usize> = HashMap::new();
+            for (cache_key, cached) in &cache.map {
+                let used = if self.config.max_bytes.is_some() { cached.size } else { 1 };
+                *usage.entry(cache_key.namespace.as_str()).or_default() += used;
+            }
+
+            let namespace = usage
+                .into_iter()
+                .map(|(namespace, used)| {
+                    let weight = self.config.weight(namespace).max(f64::MIN_POSITIVE);
+                    (namespace, used as f64 / weight)
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(namespace, _)| namespace);
+
+            cache
+                .map
+                .iter()
+                .filter(|(cache_key, _)| Some(cache_key.namespace.as_str()) == namespace)
+                .collect()
+        };
+
+        candidates
+            .into_iter()
+            .min_by_key(|(_, cached)| cached.accessed_at)
+            .map(|(cache_key, _)| cache_key.clone())
+            .ok_or_else(|| CacheError::Eviction("Failed to find LRU entry".to_string()))
+    }
 
-        cache.remove(&lru_key);
-        Ok(())
+    /// Whether a new entry should replace the eviction victim
+    fn admit(&self, candidate: &CacheKey, victim: &CacheKey) -> bool {
+        match &self.sketch {
+            Some(sketch) => {
+                let sketch = sketch.read().unwrap();
+                sketch.frequency(candidate) > sketch.frequency(victim)
+            }
+            None => true,
+        }
+    }
+
+    fn record_access(&self, cache_key: &CacheKey) {
+        if let Some(sketch) = &self.sketch {
+            sketch.write().unwrap().increment(cache_key);
+        }
+    }
+
+    fn record_eviction(&self, namespace: &str, cause: EvictionCause) {
+        let mut namespace_stats = self.namespace_stats.write().unwrap();
+        let stats = namespace_stats.entry(namespace.to_string()).or_default();
+        stats.evictions += 1;
+        *stats.eviction_causes.entry(cause).or_default() += 1;
+
+        if let Some(metrics) = &self.metrics {
+            let cause = cause.to_string();
+            metrics.record_counter("cache_evictions_total", &[("tier", "l1"), ("cause", &cause)], 1.0);
+        }
+    }
+
+    fn record_size(&self, cache: &Entries) {
+        if let Some(metrics) = &self.metrics {
+            metrics.gauge("cache_size_entries", &[("tier", "l1")], cache.map.len() as f64);
+            metrics.gauge("cache_size_bytes", &[("tier", "l1")], cache.bytes as f64);
+        }
+    }
+
+    fn record_rejection(&self, namespace: &str) {
+        self.namespace_stats
+            .write()
+            .unwrap()
+            .entry(namespace.to_string())
+            .or_default()
+            .rejections += 1;
     }
 
     /// Invalidate a specific entry
     pub fn invalidate(&self, namespace: &str, key: &str, env: &str) {
-        let cache_key = Self::cache_key(namespace, key, env);
+        let cache_key = CacheKey::new(namespace, key, env);
+        let mut cache = self.cache.write().unwrap();
+        if cache.remove(&cache_key).is_some() {
+            self.record_invalidations(namespace, 1);
+            self.record_size(&cache);
+        }
+    }
+
+    /// Invalidate all entries under a `/`-separated prefix (see [`CacheKey::has_prefix`])
+    ///
+    /// Returns the number of entries removed.
+    pub fn invalidate_prefix(&self, prefix: &str) -> usize {
         let mut cache = self.cache.write().unwrap();
-        cache.remove(&cache_key);
+
+        let mut removed: HashMap<String, u64> = HashMap::new();
+        let mut removed_bytes = 0;
+        cache.map.retain(|cache_key, cached| {
+            if cache_key.has_prefix(prefix) {
+                *removed.entry(cache_key.namespace.clone()).or_default() += 1;
+                removed_bytes += cached.size;
+                false
+            } else {
+                true
+            }
+        });
+        cache.bytes -= removed_bytes;
+        self.record_size(&cache);
+
+        let mut total = 0;
+        for (namespace, count) in removed {
+            self.record_invalidations(&namespace, count);
+            total += count as usize;
+        }
+        total
+    }
+
+    fn record_invalidations(&self, namespace: &str, count: u64) {
+        self.namespace_stats
+            .write()
+            .unwrap()
+            .entry(namespace.to_string())
+            .or_default()
+            .invalidations += count;
     }
 
     /// Clear the entire cache
     pub fn clear(&self) {
         let mut cache = self.cache.write().unwrap();
-        cache.clear();
+        *cache = Entries::default();
+        self.record_size(&cache);
         *self.hit_count.write().unwrap() = 0;
         *self.miss_count.write().unwrap() = 0;
+        self.namespace_stats.write().unwrap().clear();
     }
 
     /// Get cache statistics
@@ -122,10 +343,13 @@ impl L1Cache {
         let cache = self.cache.read().unwrap();
         let hit_count = *self.hit_count.read().unwrap();
         let miss_count = *self.miss_count.read().unwrap();
+        let namespace_stats = self.namespace_stats.read().unwrap();
 
         CacheStats {
-            size: cache.len(),
-            max_size: self.max_size,
+            size: cache.map.len(),
+            max_size: self.config.max_entries,
+            bytes: cache.bytes,
+            max_bytes: self.config.max_bytes,
             hit_count,
             miss_count,
             hit_rate: if hit_count + miss_count > 0 {
@@ -133,8 +357,41 @@ impl L1Cache {
             } else {
                 0.0
             },
+            evictions: namespace_stats.values().map(|stats| stats.evictions).sum(),
+            rejections: namespace_stats.values().map(|stats| stats.rejections).sum(),
         }
     }
+
+    /// Get statistics broken down by namespace
+    pub fn namespace_stats(&self) -> HashMap<String, NamespaceStats> {
+        let cache = self.cache.read().unwrap();
+        let mut stats = self.namespace_stats.read().unwrap().clone();
+
+        for (cache_key, cached) in &cache.map {
+            let namespace = stats.entry(cache_key.namespace.clone()).or_default();
+            namespace.size += 1;
+            namespace.bytes += cached.size;
+        }
+
+        stats
+    }
+}
+
+/// Per-namespace cache statistics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceStats {
+    /// Entries currently cached
+    pub size: usize,
+    /// Approximate size of the cached entries in bytes
+    pub bytes: usize,
+    /// Entries evicted to make room
+    pub evictions: u64,
+    /// Evictions broken down by the limit that caused them
+    pub eviction_causes: HashMap<EvictionCause, u64>,
+    /// New entries not cached because of the admission policy or byte limit
+    pub rejections: u64,
+    /// Entries removed by invalidation
+    pub invalidations: u64,
 }
 
 /// Cache statistics
@@ -142,15 +399,22 @@ impl L1Cache {
 pub struct CacheStats {
     pub size: usize,
     pub max_size: usize,
+    /// Approximate size of the cached entries in bytes
+    pub bytes: usize,
+    pub max_bytes: Option<usize>,
     pub hit_count: u64,
     pub miss_count: u64,
     pub hit_rate: f64,
+    pub evictions: u64,
+    pub rejections: u64,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::Weigher;
     use llm_config_core::{ConfigMetadata, ConfigValue, Environment};
+    use llm_config_metrics::MetricsRegistry;
     use uuid::Uuid;
 
     fn create_test_entry(namespace: &str, key: &str, env: Environment) -> ConfigEntry {
@@ -168,6 +432,9 @@ mod tests {
                 updated_by: "test".to_string(),
                 tags: vec![],
                 description: None,
+                context: None,
+                merge: None,
+                signature: None,
             },
         }
     }
@@ -269,4 +536,147 @@ mod tests {
         assert_eq!(stats.miss_count, 1);
         assert!((stats.hit_rate - 0.666).abs() < 0.01);
     }
+
+    #[test]
+    fn test_cache_metrics() {
+        let metrics = Arc::new(MetricsRegistry::new().unwrap());
+        let cache = L1Cache::new(2).with_metrics(metrics.clone());
+
+        for key in ["a", "b", "c"] {
+            cache.put(create_test_entry("ns", key, Environment::Development)).unwrap();
+        }
+        cache.get("ns", "c", "development").unwrap();
+        let _ = cache.get("ns", "a", "development");
+
+        assert!((metrics.cache().hit_rate("l1") - 0.5).abs() < 0.01);
+        let text = metrics.encode_text().unwrap();
+        assert!(text.contains("cache_evictions_total{cause=\"entry_limit\",tier=\"l1\"} 1"));
+        assert!(text.contains("cache_size_entries{tier=\"l1\"} 2"));
+    }
+
+    #[test]
+    fn test_invalidate_prefix() {
+        let cache = L1Cache::new(100);
+        for namespace in ["team-a/prompts", "team-a/models", "team-ab"] {
+            for key in ["k1", "k2"] {
+                cache
+                    .put(create_test_entry(namespace, key, Environment::Development))
+                    .unwrap();
+            }
+        }
+
+        assert_eq!(cache.invalidate_prefix("team-a/prompts/k1"), 1);
+        assert_eq!(cache.invalidate_prefix("team-a"), 3);
+        assert_eq!(cache.stats().size, 2);
+        assert!(cache.get("team-ab", "k1", "development").is_ok());
+
+        let stats = cache.namespace_stats();
+        assert_eq!(stats["team-a/prompts"].invalidations, 2);
+        assert_eq!(stats["team-a/prompts"].size, 0);
+        assert_eq!(stats["team-ab"].size, 2);
+    }
+
+    #[test]
+    fn test_namespace_eviction_stats() {
+        let cache = L1Cache::new(2);
+        cache.put(create_test_entry("ns-a", "key0", Environment::Development)).unwrap();
+        cache.put(create_test_entry("ns-b", "key1", Environment::Development)).unwrap();
+        cache.put(create_test_entry("ns-b", "key2", Environment::Development)).unwrap();
+
+        let stats = cache.namespace_stats();
+        assert_eq!(stats["ns-a"].evictions, 1);
+        assert_eq!(stats["ns-a"].size, 0);
+        assert_eq!(stats["ns-b"].evictions, 0);
+        assert_eq!(stats["ns-b"].size, 2);
+        assert_eq!(stats["ns-a"].eviction_causes[&EvictionCause::EntryLimit], 1);
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let entry_size = serde_json::to_vec(&create_test_entry("ns", "key0", Environment::Development))
+            .unwrap()
+            .len();
+        let cache = L1Cache::with_config(L1Config::new(100).with_max_bytes(entry_size * 2 + 10));
+
+        for i in 0..3 {
+            cache
+                .put(create_test_entry("ns", &format!("key{}", i), Environment::Development))
+                .unwrap();
+        }
+
+        let stats = cache.stats();
+        assert_eq!(stats.size, 2);
+        assert!(stats.bytes <= entry_size * 2 + 10);
+        assert_eq!(stats.evictions, 1);
+        assert!(cache.get("ns", "key0", "development").is_err());
+        assert_eq!(cache.namespace_stats()["ns"].eviction_causes[&EvictionCause::ByteLimit], 1);
+
+        cache.invalidate_prefix("ns");
+        assert_eq!(cache.stats().bytes, 0);
+    }
+
+    #[test]
+    fn test_weigher() {
+        let weigher = Weigher::new(|entry| if entry.key == "large" { 1000 } else { 10 });
+        let cache = L1Cache::with_config(L1Config::new(100).with_max_bytes(1025).with_weigher(weigher));
+        for i in 0..3 {
+            cache
+                .put(create_test_entry("ns", &format!("key{}", i), Environment::Development))
+                .unwrap();
+        }
+        assert_eq!(cache.stats().bytes, 30);
+
+        // The large entry pushes out the least recently used small ones
+        cache.put(create_test_entry("ns", "large", Environment::Development)).unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.size, stats.bytes, stats.evictions), (3, 1020, 1));
+        assert!(cache.get("ns", "key0", "development").is_err());
+    }
+
+    #[test]
+    fn test_namespace_weights() {
+        let cache = L1Cache::with_config(L1Config::new(4).with_namespace_weight("hot", 2.0));
+        cache.put(create_test_entry("cold", "key0", Environment::Development)).unwrap();
+        for i in 0..6 {
+            cache
+                .put(create_test_entry("hot", &format!("key{}", i), Environment::Development))
+                .unwrap();
+        }
+
+        // The burst in "hot" evicts its own entries once it reaches its share
+        assert!(cache.get("cold", "key0", "development").is_ok());
+        assert_eq!(cache.namespace_stats()["hot"].size, 3);
+
+        // A second cold entry takes room from "hot", which is over its share
+        cache.put(create_test_entry("cold", "key1", Environment::Development)).unwrap();
+        let stats = cache.namespace_stats();
+        assert_eq!(stats["cold"].size, 2);
+        assert_eq!(stats["hot"].size, 2);
+    }
+
+    #[test]
+    fn test_tinylfu_admission() {
+        let cache = L1Cache::with_config(L1Config::new(2).with_admission(AdmissionPolicy::TinyLfu));
+        for i in 0..2 {
+            cache
+                .put(create_test_entry("ns", &format!("key{}", i), Environment::Development))
+                .unwrap();
+            for _ in 0..3 {
+                cache.get("ns", &format!("key{}", i), "development").unwrap();
+            }
+        }
+
+        // A one-off entry does not displace frequently read ones
+        cache.put(create_test_entry("ns", "scan", Environment::Development)).unwrap();
+        assert!(cache.get("ns", "scan", "development").is_err());
+        assert_eq!(cache.stats().rejections, 1);
+
+        // Repeated requests make it worth admitting
+        for _ in 0..6 {
+            let _ = cache.get("ns", "popular", "development");
+        }
+        cache.put(create_test_entry("ns", "popular", Environment::Development)).unwrap();
+        assert!(cache.get("ns", "popular", "development").is_ok());
+        assert_eq!(cache.stats().evictions, 1);
+    }
 }
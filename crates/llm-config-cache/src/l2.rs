@@ -1,17 +1,112 @@
 //! L2 persistent cache for warm restarts
-
-use crate::{CacheError, Result};
-use llm_config_core::ConfigEntry;
+//!
+//! The L2 tier is pluggable through [`L2Backend`]: [`L2Cache`] keeps entries
+//! on local disk, and with the `redis` feature `RedisCache` shares them
+//! between replicas.
+
+use crate::{key::CacheKey, CacheError, Result};
+use chrono::{DateTime, Utc};
+use llm_config_core::{ConfigEntry, TenantId};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
+/// Storage for the L2 cache tier
+pub trait L2Backend: Send + Sync {
+    /// Get an entry from the cache
+    fn get(&self, namespace: &str, key: &str, env: &str) -> Result<ConfigEntry>;
+
+    /// Put an entry into the cache
+    fn put(&self, entry: &ConfigEntry) -> Result<()>;
+
+    /// Invalidate a specific entry
+    fn invalidate(&self, namespace: &str, key: &str, env: &str) -> Result<()>;
+
+    /// Invalidate all entries under a `/`-separated prefix, returning how many were removed
+    fn invalidate_prefix(&self, prefix: &str) -> Result<usize>;
+
+    /// Clear the entire cache
+    fn clear(&self) -> Result<()>;
+
+    /// Number of cached entries
+    fn size(&self) -> usize;
+
+    /// Approximate size of the cached entries in bytes, if the backend
+    /// tracks it
+    fn bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// When a cached entry was stored, if the backend knows
+    ///
+    /// Entries of unknown age count as stored when they are read.
+    fn cached_at(&self, _namespace: &str, _key: &str, _env: &str) -> Option<DateTime<Utc>> {
+        None
+    }
+}
+
+/// Where the L2 tier keeps its entries
+#[derive(Debug, Clone)]
+pub enum L2Config {
+    /// Files in a local directory
+    Disk(PathBuf),
+    /// A Redis server shared between replicas
+    #[cfg(feature = "redis")]
+    Redis(crate::redis::RedisConfig),
+}
+
+impl L2Config {
+    /// Scope the backend to a tenant
+    ///
+    /// Disk caches move to the tenant's subdirectory and Redis keys get a
+    /// tenant prefix, so tenants sharing a backend never see each other's entries.
+    pub fn for_tenant(self, tenant: &TenantId) -> Self {
+        match self {
+            L2Config::Disk(dir) => L2Config::Disk(tenant.storage_path(dir)),
+            #[cfg(feature = "redis")]
+            L2Config::Redis(config) => {
+                let key_prefix = format!("{}tenants/{}:", config.key_prefix, tenant);
+                L2Config::Redis(config.with_key_prefix(key_prefix))
+            }
+        }
+    }
+
+    /// Open the configured backend
+    pub fn open(self) -> Result<Arc<dyn L2Backend>> {
+        Ok(match self {
+            L2Config::Disk(dir) => Arc::new(L2Cache::new(dir)?),
+            #[cfg(feature = "redis")]
+            L2Config::Redis(config) => Arc::new(crate::redis::RedisCache::new(config)?),
+        })
+    }
+}
+
+impl<P: AsRef<Path>> From<P> for L2Config {
+    fn from(dir: P) -> Self {
+        L2Config::Disk(dir.as_ref().to_path_buf())
+    }
+}
+
+#[cfg(feature = "redis")]
+impl From<crate::redis::RedisConfig> for L2Config {
+    fn from(config: crate::redis::RedisConfig) -> Self {
+        L2Config::Redis(config)
+    }
+}
+
+/// A cache file and its size
+#[derive(Debug, Clone)]
+struct CacheFile {
+    path: PathBuf,
+    size: usize,
+}
+
 /// L2 persistent cache
 pub struct L2Cache {
     cache_dir: PathBuf,
-    index: Arc<RwLock<HashMap<String, PathBuf>>>,
+    index: Arc<RwLock<HashMap<CacheKey, CacheFile>>>,
 }
 
 impl L2Cache {
@@ -31,15 +126,10 @@ impl L2Cache {
         Ok(cache)
     }
 
-    /// Generate cache key
-    fn cache_key(namespace: &str, key: &str, env: &str) -> String {
-        format!("{}:{}:{}", namespace, key, env)
-    }
-
     /// Get cache file path for a key
-    fn cache_file_path(&self, cache_key: &str) -> PathBuf {
+    fn cache_file_path(&self, cache_key: &CacheKey) -> PathBuf {
         // Use hex encoding for safe filesystem names
-        let encoded = hex::encode(cache_key.as_bytes());
+        let encoded = hex::encode(cache_key.to_string().as_bytes());
         self.cache_dir.join(format!("{}.cache", encoded))
     }
 
@@ -59,14 +149,10 @@ impl L2Cache {
             if path.extension().and_then(|s| s.to_str()) == Some("cache") {
                 // Read the file to get the cache key
                 if let Ok(file) = File::open(&path) {
+                    let size = file.metadata().map_or(0, |metadata| metadata.len() as usize);
                     let reader = BufReader::new(file);
                     if let Ok(cached_entry) = serde_json::from_reader::<_, ConfigEntry>(reader) {
-                        let cache_key = Self::cache_key(
-                            &cached_entry.namespace,
-                            &cached_entry.key,
-                            &cached_entry.environment.to_string(),
-                        );
-                        index.insert(cache_key, path);
+                        index.insert(CacheKey::for_entry(&cached_entry), CacheFile { path, size });
                     }
                 }
             }
@@ -77,59 +163,81 @@ impl L2Cache {
 
     /// Get an entry from the cache
     pub fn get(&self, namespace: &str, key: &str, env: &str) -> Result<ConfigEntry> {
-        let cache_key = Self::cache_key(namespace, key, env);
+        let cache_key = CacheKey::new(namespace, key, env);
 
         let index = self.index.read().unwrap();
 
-        if let Some(path) = index.get(&cache_key) {
-            let file = File::open(path)?;
+        if let Some(cached) = index.get(&cache_key) {
+            let file = File::open(&cached.path)?;
             let reader = BufReader::new(file);
             let entry = serde_json::from_reader(reader)
                 .map_err(|e| CacheError::Serialization(e.to_string()))?;
             Ok(entry)
         } else {
-            Err(CacheError::CacheMiss(cache_key))
+            Err(CacheError::CacheMiss(cache_key.to_string()))
         }
     }
 
     /// Put an entry into the cache
     pub fn put(&self, entry: &ConfigEntry) -> Result<()> {
-        let cache_key = Self::cache_key(&entry.namespace, &entry.key, &entry.environment.to_string());
+        let cache_key = CacheKey::for_entry(entry);
         let path = self.cache_file_path(&cache_key);
 
         // Write to temp file first for atomicity
         let temp_path = path.with_extension("tmp");
-        {
+        let size = {
             let file = File::create(&temp_path)?;
             let mut writer = BufWriter::new(file);
             serde_json::to_writer(&mut writer, entry)
                 .map_err(|e| CacheError::Serialization(e.to_string()))?;
             writer.flush()?;
-        }
+            writer.get_ref().metadata()?.len() as usize
+        };
 
         // Atomic rename
         fs::rename(&temp_path, &path)?;
 
         // Update index
         let mut index = self.index.write().unwrap();
-        index.insert(cache_key, path);
+        index.insert(cache_key, CacheFile { path, size });
 
         Ok(())
     }
 
     /// Invalidate a specific entry
     pub fn invalidate(&self, namespace: &str, key: &str, env: &str) -> Result<()> {
-        let cache_key = Self::cache_key(namespace, key, env);
+        let cache_key = CacheKey::new(namespace, key, env);
 
         let mut index = self.index.write().unwrap();
 
-        if let Some(path) = index.remove(&cache_key) {
-            let _ = fs::remove_file(path); // Ignore errors if file doesn't exist
+        if let Some(cached) = index.remove(&cache_key) {
+            let _ = fs::remove_file(cached.path); // Ignore errors if file doesn't exist
         }
 
         Ok(())
     }
 
+    /// Invalidate all entries under a `/`-separated prefix (see [`CacheKey::has_prefix`])
+    ///
+    /// Returns the number of entries removed.
+    pub fn invalidate_prefix(&self, prefix: &str) -> Result<usize> {
+        let mut index = self.index.write().unwrap();
+
+        let matching: Vec<CacheKey> = index
+            .keys()
+            .filter(|cache_key| cache_key.has_prefix(prefix))
+            .cloned()
+            .collect();
+
+        for cache_key in &matching {
+            if let Some(cached) = index.remove(cache_key) {
+                let _ = fs::remove_file(cached.path); // Ignore errors if file doesn't exist
+            }
+        }
+
+        Ok(matching.len())
+    }
+
     /// Clear the entire cache
     pub fn clear(&self) -> Result<()> {
         let mut index = self.index.write().unwrap();
@@ -153,6 +261,53 @@ impl L2Cache {
     pub fn size(&self) -> usize {
         self.index.read().unwrap().len()
     }
+
+    /// Size of the cache files in bytes
+    pub fn bytes(&self) -> usize {
+        self.index.read().unwrap().values().map(|cached| cached.size).sum()
+    }
+
+    /// When an entry was stored, from its file's modification time
+    pub fn cached_at(&self, namespace: &str, key: &str, env: &str) -> Option<DateTime<Utc>> {
+        let cache_key = CacheKey::new(namespace, key, env);
+        let index = self.index.read().unwrap();
+        let modified = fs::metadata(&index.get(&cache_key)?.path).ok()?.modified().ok()?;
+        Some(modified.into())
+    }
+}
+
+impl L2Backend for L2Cache {
+    fn get(&self, namespace: &str, key: &str, env: &str) -> Result<ConfigEntry> {
+        L2Cache::get(self, namespace, key, env)
+    }
+
+    fn put(&self, entry: &ConfigEntry) -> Result<()> {
+        L2Cache::put(self, entry)
+    }
+
+    fn invalidate(&self, namespace: &str, key: &str, env: &str) -> Result<()> {
+        L2Cache::invalidate(self, namespace, key, env)
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) -> Result<usize> {
+        L2Cache::invalidate_prefix(self, prefix)
+    }
+
+    fn clear(&self) -> Result<()> {
+        L2Cache::clear(self)
+    }
+
+    fn size(&self) -> usize {
+        L2Cache::size(self)
+    }
+
+    fn bytes(&self) -> Option<usize> {
+        Some(L2Cache::bytes(self))
+    }
+
+    fn cached_at(&self, namespace: &str, key: &str, env: &str) -> Option<DateTime<Utc>> {
+        L2Cache::cached_at(self, namespace, key, env)
+    }
 }
 
 #[cfg(test)]
@@ -177,6 +332,9 @@ mod tests {
                 updated_by: "test".to_string(),
                 tags: vec![],
                 description: None,
+                context: None,
+                merge: None,
+                signature: None,
             },
         }
     }
@@ -214,10 +372,13 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let entry = create_test_entry("ns", "key1", Environment::Development);
 
+        let entry_size = serde_json::to_vec(&entry).unwrap().len();
+
         // Create cache, add entry, drop it
         {
             let cache = L2Cache::new(temp_dir.path()).unwrap();
             cache.put(&entry).unwrap();
+            assert_eq!(cache.bytes(), entry_size);
         }
 
         // Create new cache instance, entry should still be there
@@ -225,6 +386,7 @@ mod tests {
             let cache = L2Cache::new(temp_dir.path()).unwrap();
             let retrieved = cache.get("ns", "key1", "development").unwrap();
             assert_eq!(retrieved.id, entry.id);
+            assert_eq!(cache.bytes(), entry_size);
         }
     }
 
@@ -241,6 +403,7 @@ mod tests {
         cache.invalidate("ns", "key1", "development").unwrap();
 
         assert!(cache.get("ns", "key1", "development").is_err());
+        assert_eq!(cache.bytes(), 0);
     }
 
     #[test]
@@ -259,4 +422,23 @@ mod tests {
 
         assert_eq!(cache.size(), 0);
     }
+
+    #[test]
+    fn test_invalidate_prefix() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = L2Cache::new(temp_dir.path()).unwrap();
+
+        for namespace in ["team-a/prompts", "team-a/models", "team-b"] {
+            let entry = create_test_entry(namespace, "key1", Environment::Development);
+            cache.put(&entry).unwrap();
+        }
+
+        assert_eq!(cache.invalidate_prefix("team-a").unwrap(), 2);
+        assert_eq!(cache.size(), 1);
+
+        // Removed entries stay gone after a restart
+        let cache = L2Cache::new(temp_dir.path()).unwrap();
+        assert_eq!(cache.size(), 1);
+        assert!(cache.get("team-b", "key1", "development").is_ok());
+    }
 }
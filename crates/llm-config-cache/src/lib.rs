@@ -1,21 +1,49 @@
 //! Multi-tier caching for LLM Config Manager
 //!
 //! This module provides a two-tier caching system:
-//! - L1 Cache: In-memory cache for ultra-fast access (LRU eviction)
-//! - L2 Cache: Persistent cache for warm restarts
+//! - L1 Cache: In-memory cache for ultra-fast access (LRU eviction, optional
+//!   byte limits, per-namespace weights and TinyLFU admission)
+//! - L2 Cache: Persistent cache for warm restarts, on local disk or (with the
+//!   `redis` feature) in Redis shared between replicas
+//!
+//! Entries are addressed by hierarchical [`CacheKey`]s (namespace / key /
+//! environment), so everything under a namespace can be invalidated by prefix.
+//! [`CacheManager::subscribe_invalidations`] keeps both tiers consistent with
+//! writes by applying the change events published by `ConfigManager`.
+//! Entries can expire after a per-namespace TTL, optionally served stale
+//! while they are refreshed (see [`ttl`]).
+//! [`CacheManager::warm`] preloads hot namespaces, e.g. before serving, and
+//! [`CacheManager::peek`] shows which tier answers a key.
+//! In multi-tenant deployments, [`CacheManager::for_tenant`] gives each
+//! tenant its own cache whose L2 entries never share keys with another's.
 //!
 //! ## Performance Characteristics
 //! - L1 Cache: <1μs latency
 //! - L2 Cache: <1ms latency
 //! - Cache miss: 5-10ms (disk read)
 
+pub mod inspect;
+pub mod key;
 pub mod l1;
 pub mod l2;
 pub mod manager;
+pub mod policy;
+#[cfg(feature = "redis")]
+pub mod redis;
+mod sketch;
+pub mod ttl;
+pub mod warmup;
 
-pub use l1::L1Cache;
-pub use l2::L2Cache;
-pub use manager::CacheManager;
+pub use inspect::{CacheTier, L2Stats};
+pub use key::CacheKey;
+pub use l1::{L1Cache, NamespaceStats};
+pub use l2::{L2Backend, L2Cache, L2Config};
+pub use manager::{CacheManager, InvalidationMode};
+pub use policy::{AdmissionPolicy, EvictionCause, L1Config, Weigher};
+#[cfg(feature = "redis")]
+pub use redis::{RedisCache, RedisConfig};
+pub use ttl::{CacheTtl, Freshness};
+pub use warmup::WarmupReport;
 
 use thiserror::Error;
 
@@ -32,6 +60,12 @@ pub enum CacheError {
 
     #[error("Eviction error: {0}")]
     Eviction(String),
+
+    #[error("L2 backend error: {0}")]
+    Backend(String),
+
+    #[error("Config error: {0}")]
+    Config(#[from] llm_config_core::ConfigError),
 }
 
 pub type Result<T> = std::result::Result<T, CacheError>;
@@ -1,23 +1,104 @@
 //! Cache manager coordinating L1 and L2 caches
 
-use crate::{l1::L1Cache, l2::L2Cache, Result};
-use llm_config_core::ConfigEntry;
-use std::path::Path;
+use crate::{
+    l1::{L1Cache, NamespaceStats},
+    l2::{L2Backend, L2Config},
+    policy::L1Config,
+    ttl::{Freshness, Revalidation, TtlPolicy},
+    warmup::Preload,
+    Result,
+};
+use chrono::Utc;
+use llm_config_core::{ChangeEvent, ConfigEntry, TenantId};
+use llm_config_sink::MetricsSink;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+
+/// How the cache reacts to a change event for a cached key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidationMode {
+    /// Drop the entry; the next read goes to storage
+    #[default]
+    Evict,
+    /// Replace the entry with the one carried by the event
+    Refresh,
+}
 
 /// Multi-tier cache manager
 pub struct CacheManager {
-    l1: Arc<L1Cache>,
-    l2: Arc<L2Cache>,
+    pub(crate) l1: L1Cache,
+    pub(crate) l2: Arc<dyn L2Backend>,
+    invalidation_mode: InvalidationMode,
+    pub(crate) metrics: Option<Arc<dyn MetricsSink>>,
+    tenant: Option<TenantId>,
+    pub(crate) preload: Option<Preload>,
+    /// L2 hits and misses since the L2 tier was last cleared
+    pub(crate) l2_hits: AtomicU64,
+    pub(crate) l2_misses: AtomicU64,
+    pub(crate) ttl: TtlPolicy,
+    pub(crate) revalidation: Revalidation,
 }
 
 impl CacheManager {
     /// Create a new cache manager
-    pub fn new(l1_size: usize, l2_dir: impl AsRef<Path>) -> Result<Self> {
-        Ok(Self {
-            l1: Arc::new(L1Cache::new(l1_size)),
-            l2: Arc::new(L2Cache::new(l2_dir)?),
-        })
+    ///
+    /// `l2` is a cache directory or, with the `redis` feature, a
+    /// `RedisConfig` for an L2 tier shared between replicas.
+    pub fn new(l1_size: usize, l2: impl Into<L2Config>) -> Result<Self> {
+        Self::with_l1_config(L1Config::new(l1_size), l2)
+    }
+
+    /// Create a new cache manager with a custom L1 sizing and eviction policy
+    pub fn with_l1_config(l1_config: L1Config, l2: impl Into<L2Config>) -> Result<Self> {
+        Ok(Self::with_l2_backend(l1_config, l2.into().open()?))
+    }
+
+    /// Create a new cache manager with a custom L2 backend
+    pub fn with_l2_backend(l1_config: L1Config, l2: Arc<dyn L2Backend>) -> Self {
+        Self {
+            l1: L1Cache::with_config(l1_config),
+            l2,
+            invalidation_mode: InvalidationMode::default(),
+            metrics: None,
+            tenant: None,
+            preload: None,
+            l2_hits: AtomicU64::new(0),
+            l2_misses: AtomicU64::new(0),
+            ttl: TtlPolicy::default(),
+            revalidation: Revalidation::default(),
+        }
+    }
+
+    /// Create a cache manager for one tenant
+    ///
+    /// The L2 tier is scoped with [`L2Config::for_tenant`], and change events
+    /// of other tenants are ignored.
+    pub fn for_tenant(tenant: TenantId, l1_config: L1Config, l2: impl Into<L2Config>) -> Result<Self> {
+        let mut cache = Self::with_l1_config(l1_config, l2.into().for_tenant(&tenant))?;
+        cache.tenant = Some(tenant);
+        Ok(cache)
+    }
+
+    /// Set how change events update cached entries
+    pub fn with_invalidation_mode(mut self, mode: InvalidationMode) -> Self {
+        self.invalidation_mode = mode;
+        self
+    }
+
+    /// Record hits, misses, lookup durations, L1 evictions and the size of
+    /// both tiers in the given sink
+    ///
+    /// The L2 size is only reported for backends that track their size in
+    /// bytes, such as the disk cache.
+    pub fn with_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.l1 = self.l1.with_metrics(Arc::clone(&metrics));
+        self.metrics = Some(metrics);
+        self.record_l2_size();
+        self
     }
 
     /// Get an entry from the cache
@@ -26,18 +107,43 @@ impl CacheManager {
     /// 1. L1 cache (fastest)
     /// 2. L2 cache (fast)
     /// 3. Return cache miss
+    ///
+    /// Entries past their [TTL](crate::ttl) are misses, or served while a
+    /// refresh is queued if the TTL allows serving them stale.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "cache.get", skip(self)))]
     pub fn get(&self, namespace: &str, key: &str, env: &str) -> Result<ConfigEntry> {
+        let started = Instant::now();
+        let ttl = self.ttl.for_namespace(namespace);
+        let now = Utc::now();
+        let servable = |cached_at| match ttl.map_or(Freshness::Fresh, |ttl| ttl.freshness(cached_at, now)) {
+            Freshness::Fresh => true,
+            Freshness::Stale if self.can_revalidate() => {
+                self.revalidate(namespace, key, env);
+                true
+            }
+            _ => false,
+        };
+
         // Try L1 first
-        if let Ok(entry) = self.l1.get(namespace, key, env) {
-            return Ok(entry);
+        if let Ok((entry, cached_at)) = self.l1.get_with_cached_at(namespace, key, env) {
+            if servable(cached_at) {
+                self.record_lookup("l1", true, started);
+                return Ok(entry);
+            }
         }
 
         // Try L2 if L1 miss
         if let Ok(entry) = self.l2.get(namespace, key, env) {
-            // Promote to L1
-            self.l1.put(entry.clone())?;
-            return Ok(entry);
+            let cached_at = self.l2.cached_at(namespace, key, env).unwrap_or(now);
+            if servable(cached_at) {
+                self.record_lookup("l2", true, started);
+                // Promote to L1
+                self.l1.put_cached_at(entry.clone(), cached_at)?;
+                return Ok(entry);
+            }
+            self.invalidate(namespace, key, env)?;
         }
+        self.record_lookup("l2", false, started);
 
         // Complete cache miss
         Err(crate::CacheError::CacheMiss(format!(
@@ -46,26 +152,129 @@ impl CacheManager {
         )))
     }
 
+    /// Record a lookup answered by (or missing from) `tier` and how long it took
+    fn record_lookup(&self, tier: &str, hit: bool, started: Instant) {
+        if tier == "l2" {
+            let count = if hit { &self.l2_hits } else { &self.l2_misses };
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        // The L1 cache counts its own hits and misses
+        if tier != "l1" {
+            let name = if hit { "cache_hits_total" } else { "cache_misses_total" };
+            metrics.record_counter(name, &[("tier", tier)], 1.0);
+        }
+        metrics.observe(
+            "cache_operation_duration_seconds",
+            &[("operation", "get"), ("tier", tier)],
+            started.elapsed().as_secs_f64(),
+        );
+    }
+
     /// Put an entry into the cache (both L1 and L2)
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "cache.put", skip_all, fields(namespace = %entry.namespace, key = %entry.key)))]
     pub fn put(&self, entry: ConfigEntry) -> Result<()> {
         // Write to both caches
         self.l1.put(entry.clone())?;
         self.l2.put(&entry)?;
+        self.record_l2_size();
         Ok(())
     }
 
+    /// Report the L2 size, for backends that track it cheaply
+    pub(crate) fn record_l2_size(&self) {
+        let (Some(metrics), Some(bytes)) = (&self.metrics, self.l2.bytes()) else {
+            return;
+        };
+        metrics.gauge("cache_size_entries", &[("tier", "l2")], self.l2.size() as f64);
+        metrics.gauge("cache_size_bytes", &[("tier", "l2")], bytes as f64);
+    }
+
     /// Invalidate an entry from both caches
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "cache.invalidate", skip(self)))]
     pub fn invalidate(&self, namespace: &str, key: &str, env: &str) -> Result<()> {
         self.l1.invalidate(namespace, key, env);
         self.l2.invalidate(namespace, key, env)?;
+        self.record_l2_size();
         Ok(())
     }
 
+    /// Invalidate every entry under a `/`-separated prefix from both caches
+    ///
+    /// A namespace prefix such as `team-a` also covers child namespaces like
+    /// `team-a/prompts`. Returns the number of entries removed from L2, which
+    /// holds every cached entry.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "cache.invalidate_prefix", skip(self)))]
+    pub fn invalidate_prefix(&self, prefix: &str) -> Result<usize> {
+        self.l1.invalidate_prefix(prefix);
+        let removed = self.l2.invalidate_prefix(prefix)?;
+        self.record_l2_size();
+        Ok(removed)
+    }
+
+    /// Update the caches for a config change
+    ///
+    /// Events older than the cached entry, and events of another tenant, are
+    /// ignored. Keys that are not cached are left alone, so refreshing never
+    /// adds entries.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "cache.apply_change", skip_all, fields(namespace = %event.namespace, key = %event.key)))]
+    pub fn apply_change(&self, event: &ChangeEvent) -> Result<()> {
+        if event.tenant_id != self.tenant {
+            return Ok(());
+        }
+        let env = event.environment.to_string();
+
+        // L2 holds every cached entry
+        let cached = match self.l2.get(&event.namespace, &event.key, &env) {
+            Ok(cached) => cached,
+            Err(_) => return self.invalidate(&event.namespace, &event.key, &env),
+        };
+        if cached.version > event.version {
+            return Ok(());
+        }
+
+        match (&event.entry, self.invalidation_mode) {
+            (Some(entry), InvalidationMode::Refresh) => self.put(entry.clone()),
+            _ => self.invalidate(&event.namespace, &event.key, &env),
+        }
+    }
+
+    /// Apply change events from a [`ChangeBus`](llm_config_core::ChangeBus) in a background task
+    ///
+    /// If the task falls behind and misses events, both caches are cleared
+    /// and the [preloaded](Self::with_preload) namespaces loaded again. The
+    /// task ends when the bus is dropped.
+    pub fn subscribe_invalidations(
+        self: &Arc<Self>,
+        mut changes: broadcast::Receiver<ChangeEvent>,
+    ) -> JoinHandle<()> {
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(event) => {
+                        if cache.apply_change(&event).is_err() {
+                            let env = event.environment.to_string();
+                            let _ = cache.invalidate(&event.namespace, &event.key, &env);
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => {
+                        if cache.clear().is_ok() {
+                            let _ = cache.preload();
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
     /// Clear both caches
     pub fn clear(&self) -> Result<()> {
         self.l1.clear();
-        self.l2.clear()?;
-        Ok(())
+        self.clear_l2()
     }
 
     /// Get L1 cache statistics
@@ -73,26 +282,36 @@ impl CacheManager {
         self.l1.stats()
     }
 
+    /// Get L1 cache statistics per namespace (size, evictions by cause, invalidations)
+    pub fn namespace_stats(&self) -> HashMap<String, NamespaceStats> {
+        self.l1.namespace_stats()
+    }
+
     /// Get L2 cache size
     pub fn l2_size(&self) -> usize {
         self.l2.size()
     }
 
-    /// Clear only L1 cache (for testing)
+    /// Clear only L1 cache
     pub fn clear_l1(&self) {
         self.l1.clear();
     }
 
-    /// Clear only L2 cache (for testing)
+    /// Clear only L2 cache
     pub fn clear_l2(&self) -> Result<()> {
-        self.l2.clear()
+        self.l2.clear()?;
+        self.record_l2_size();
+        self.l2_hits.store(0, Ordering::Relaxed);
+        self.l2_misses.store(0, Ordering::Relaxed);
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_config_core::{ConfigMetadata, ConfigValue, Environment};
+    use llm_config_core::{ChangeKind, ConfigMetadata, ConfigValue, Environment};
+    use llm_config_metrics::MetricsRegistry;
     use tempfile::TempDir;
     use uuid::Uuid;
 
@@ -111,6 +330,9 @@ mod tests {
                 updated_by: "test".to_string(),
                 tags: vec![],
                 description: None,
+                context: None,
+                merge: None,
+                signature: None,
             },
         }
     }
@@ -209,4 +431,136 @@ mod tests {
         let stats = manager.l1_stats();
         assert_eq!(stats.size, 2); // Still at capacity
     }
+
+    #[test]
+    fn test_invalidate_prefix() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = CacheManager::new(100, temp_dir.path()).unwrap();
+
+        for namespace in ["team-a", "team-a/prompts", "team-b"] {
+            for env in [Environment::Development, Environment::Production] {
+                manager.put(create_test_entry(namespace, "key1", env)).unwrap();
+            }
+        }
+
+        assert_eq!(manager.invalidate_prefix("team-a").unwrap(), 4);
+        assert!(manager.get("team-a/prompts", "key1", "production").is_err());
+        assert!(manager.get("team-b", "key1", "production").is_ok());
+
+        let stats = manager.namespace_stats();
+        assert_eq!(stats["team-a"].invalidations, 2);
+        assert_eq!(stats["team-a/prompts"].invalidations, 2);
+        assert_eq!(stats["team-b"].size, 2);
+    }
+
+    #[test]
+    fn test_apply_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = CacheManager::new(100, temp_dir.path()).unwrap();
+
+        let mut entry = create_test_entry("ns", "key1", Environment::Development);
+        entry.version = 2;
+        manager.put(entry.clone()).unwrap();
+
+        // Events older than the cached entry are ignored
+        let mut old = entry.clone();
+        old.version = 1;
+        manager.apply_change(&ChangeEvent::stored(ChangeKind::Set, &old)).unwrap();
+        assert_eq!(manager.get("ns", "key1", "development").unwrap().version, 2);
+
+        let mut newer = entry.clone();
+        newer.version = 3;
+        manager.apply_change(&ChangeEvent::stored(ChangeKind::Set, &newer)).unwrap();
+        assert!(manager.get("ns", "key1", "development").is_err());
+        assert_eq!(manager.l2_size(), 0);
+    }
+
+    #[test]
+    fn test_apply_change_refresh() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = CacheManager::new(100, temp_dir.path())
+            .unwrap()
+            .with_invalidation_mode(InvalidationMode::Refresh);
+
+        let entry = create_test_entry("ns", "key1", Environment::Development);
+        manager.put(entry.clone()).unwrap();
+
+        let mut updated = entry.clone();
+        updated.version = 2;
+        updated.value = ConfigValue::String("updated".to_string());
+        manager.apply_change(&ChangeEvent::stored(ChangeKind::Set, &updated)).unwrap();
+        let cached = manager.get("ns", "key1", "development").unwrap();
+        assert_eq!(cached.value.as_str(), Some("updated"));
+
+        // Uncached keys are not added
+        let other = create_test_entry("ns", "key2", Environment::Development);
+        manager.apply_change(&ChangeEvent::stored(ChangeKind::Set, &other)).unwrap();
+        assert_eq!(manager.l2_size(), 1);
+
+        // Deletes always evict
+        manager.apply_change(&ChangeEvent::deleted(&updated)).unwrap();
+        assert!(manager.get("ns", "key1", "development").is_err());
+    }
+
+    #[test]
+    fn test_tenant_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let acme_id = TenantId::new("acme").unwrap();
+        let acme = CacheManager::for_tenant(acme_id.clone(), L1Config::new(100), temp_dir.path()).unwrap();
+        let globex = CacheManager::for_tenant(
+            TenantId::new("globex").unwrap(),
+            L1Config::new(100),
+            temp_dir.path(),
+        )
+        .unwrap();
+
+        let entry = create_test_entry("ns", "key1", Environment::Development);
+        acme.put(entry.clone()).unwrap();
+        assert!(globex.get("ns", "key1", "development").is_err());
+        assert!(temp_dir.path().join("tenants").join("acme").is_dir());
+
+        // Only the tenant's own change events evict its entries
+        let mut event = ChangeEvent::deleted(&entry);
+        acme.apply_change(&event).unwrap();
+        assert!(acme.get("ns", "key1", "development").is_ok());
+        event.tenant_id = Some(acme_id);
+        acme.apply_change(&event).unwrap();
+        assert!(acme.get("ns", "key1", "development").is_err());
+    }
+
+    #[test]
+    fn test_l1_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = L1Config::new(1).with_namespace_weight("ns", 2.0);
+        let manager = CacheManager::with_l1_config(config, temp_dir.path()).unwrap();
+
+        manager.put(create_test_entry("ns", "key0", Environment::Development)).unwrap();
+        manager.put(create_test_entry("ns", "key1", Environment::Development)).unwrap();
+
+        assert_eq!(manager.l1_stats().evictions, 1);
+        assert_eq!(manager.l2_size(), 2);
+        assert!(manager.get("ns", "key0", "development").is_ok());
+    }
+
+    #[test]
+    fn test_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let metrics = Arc::new(MetricsRegistry::new().unwrap());
+        let manager = CacheManager::new(100, temp_dir.path())
+            .unwrap()
+            .with_metrics(metrics.clone());
+
+        manager.put(create_test_entry("ns", "key1", Environment::Development)).unwrap();
+        manager.get("ns", "key1", "development").unwrap();
+        manager.clear_l1();
+        manager.get("ns", "key1", "development").unwrap();
+        assert!(manager.get("ns", "missing", "development").is_err());
+
+        let cache = metrics.cache();
+        assert_eq!(cache.hit_rate("l1"), 1.0 / 3.0);
+        assert_eq!(cache.hit_rate("l2"), 0.5);
+        assert_eq!(cache.size_bytes("l1"), manager.l1_stats().bytes as u64);
+        assert_eq!(Some(cache.size_bytes("l2") as usize), manager.l2_stats().bytes);
+        assert!(cache.size_bytes("l2") > 0);
+    }
 }
@@ -0,0 +1,160 @@
+//! L1 sizing and eviction policy
+//!
+//! The L1 cache is bounded by an entry count and, optionally, by the
+//! approximate number of bytes its entries occupy, as measured by its
+//! [`Weigher`]. When it is full:
+//!
+//! - with namespace weights configured, the victim is the least recently used
+//!   entry of the namespace that is furthest over its weighted share, so a
+//!   burst in one namespace cannot flush the others
+//! - with the TinyLFU admission policy, a new entry only replaces the victim
+//!   if it has been requested more often recently
+
+use llm_config_core::ConfigEntry;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// Whether new entries are admitted into a full L1 cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AdmissionPolicy {
+    /// Always admit, evicting to make room
+    #[default]
+    Always,
+    /// Admit only entries requested more often than the eviction victim
+    TinyLfu,
+}
+
+/// Why an entry was evicted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvictionCause {
+    /// The cache held its maximum number of entries
+    EntryLimit,
+    /// The cache exceeded its byte limit
+    ByteLimit,
+}
+
+impl std::fmt::Display for EvictionCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EntryLimit => write!(f, "entry_limit"),
+            Self::ByteLimit => write!(f, "byte_limit"),
+        }
+    }
+}
+
+/// Measures the approximate size of an entry in bytes
+#[derive(Clone)]
+pub struct Weigher(Arc<dyn Fn(&ConfigEntry) -> usize + Send + Sync>);
+
+impl Weigher {
+    /// Weigh entries with a custom function
+    pub fn new(weigh: impl Fn(&ConfigEntry) -> usize + Send + Sync + 'static) -> Self {
+        Self(Arc::new(weigh))
+    }
+
+    /// Weigh entries by the length of their JSON serialization
+    pub fn serialized_size() -> Self {
+        Self::new(|entry| {
+            let mut counter = ByteCounter(0);
+            match serde_json::to_writer(&mut counter, entry) {
+                Ok(()) => counter.0,
+                Err(_) => 0,
+            }
+        })
+    }
+
+    /// Approximate size of `entry` in bytes
+    pub fn weigh(&self, entry: &ConfigEntry) -> usize {
+        (self.0)(entry)
+    }
+}
+
+impl Default for Weigher {
+    fn default() -> Self {
+        Self::serialized_size()
+    }
+}
+
+impl fmt::Debug for Weigher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Weigher")
+    }
+}
+
+/// Counts bytes written without keeping them
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// L1 cache configuration
+#[derive(Debug, Clone)]
+pub struct L1Config {
+    /// Maximum number of entries
+    pub max_entries: usize,
+    /// Maximum approximate size of all entries in bytes
+    pub max_bytes: Option<usize>,
+    /// How entry sizes are measured
+    pub weigher: Weigher,
+    /// Relative share of the cache per namespace (defaults to 1.0)
+    pub namespace_weights: HashMap<String, f64>,
+    pub admission: AdmissionPolicy,
+}
+
+impl L1Config {
+    /// Create a configuration bounded only by entry count
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            max_bytes: None,
+            weigher: Weigher::default(),
+            namespace_weights: HashMap::new(),
+            admission: AdmissionPolicy::default(),
+        }
+    }
+
+    /// Also bound the cache by the approximate size of its entries
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Measure entry sizes with `weigher` instead of their serialized length
+    pub fn with_weigher(mut self, weigher: Weigher) -> Self {
+        self.weigher = weigher;
+        self
+    }
+
+    /// Set the relative share of the cache a namespace may use under pressure
+    pub fn with_namespace_weight(mut self, namespace: impl Into<String>, weight: f64) -> Self {
+        self.namespace_weights.insert(namespace.into(), weight);
+        self
+    }
+
+    /// Set the admission policy
+    pub fn with_admission(mut self, admission: AdmissionPolicy) -> Self {
+        self.admission = admission;
+        self
+    }
+
+    /// Weight of a namespace
+    pub fn weight(&self, namespace: &str) -> f64 {
+        self.namespace_weights.get(namespace).copied().unwrap_or(1.0)
+    }
+}
+
+impl Default for L1Config {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
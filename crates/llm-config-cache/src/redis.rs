@@ -0,0 +1,478 @@
+//! Redis-backed L2 cache shared between replicas
+//!
+//! Entries are stored as JSON under `{key_prefix}{namespace}:{key}:{env}`
+//! with an optional TTL. The client speaks RESP over a single blocking TCP
+//! connection, reconnecting once when a command fails with an IO error.
+
+use crate::{key::CacheKey, l2::L2Backend, CacheError, Result};
+use llm_config_core::ConfigEntry;
+use llm_config_storage::patterns::escape_glob;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Default prefix of every key written by the cache
+pub const DEFAULT_KEY_PREFIX: &str = "llm-config:cache:";
+
+/// Number of keys requested per `SCAN` round trip
+const SCAN_COUNT: &str = "500";
+
+/// Redis connection and key settings
+#[derive(Debug, Clone)]
+pub struct RedisConfig {
+    /// `redis://[[user]:password@]host[:port][/db]`
+    pub url: String,
+    pub key_prefix: String,
+    /// Expiry of cached entries (none by default)
+    pub ttl: Option<Duration>,
+    /// Connect, read and write timeout
+    pub timeout: Duration,
+}
+
+impl RedisConfig {
+    /// Create a configuration for a Redis URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            key_prefix: DEFAULT_KEY_PREFIX.to_string(),
+            ttl: None,
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// Namespace keys, e.g. per deployment
+    pub fn with_key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Expire cached entries after `ttl`
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set the connect, read and write timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Parsed connection URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RedisUrl {
+    host: String,
+    port: u16,
+    username: Option<String>,
+    password: Option<String>,
+    db: u32,
+}
+
+impl RedisUrl {
+    fn parse(url: &str) -> Result<Self> {
+        let invalid = |reason: &str| CacheError::Backend(format!("Invalid Redis URL {}: {}", url, reason));
+
+        let rest = url
+            .strip_prefix("redis://")
+            .ok_or_else(|| invalid("expected redis:// scheme"))?;
+        let (authority, db) = match rest.split_once('/') {
+            Some((authority, "")) => (authority, 0),
+            Some((authority, db)) => (authority, db.parse().map_err(|_| invalid("bad database"))?),
+            None => (rest, 0),
+        };
+        let (credentials, address) = match authority.rsplit_once('@') {
+            Some((credentials, address)) => (Some(credentials), address),
+            None => (None, authority),
+        };
+        let (username, password) = match credentials.map(|c| c.split_once(':')) {
+            Some(Some((user, password))) => {
+                ((!user.is_empty()).then(|| user.to_string()), Some(password.to_string()))
+            }
+            Some(None) => (None, credentials.map(str::to_string)),
+            None => (None, None),
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid("bad port"))?),
+            None => (address, 6379),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            username,
+            password,
+            db,
+        })
+    }
+}
+
+/// A RESP reply
+#[derive(Debug, Clone, PartialEq)]
+enum Reply {
+    Nil,
+    Status(String),
+    Integer(i64),
+    Bulk(Vec<u8>),
+    Array(Vec<Reply>),
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(url: &RedisUrl, timeout: Duration) -> Result<Self> {
+        let address = (url.host.as_str(), url.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| CacheError::Backend(format!("Cannot resolve {}", url.host)))?;
+        let stream = TcpStream::connect_timeout(&address, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let mut connection = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+
+        match (&url.username, &url.password) {
+            (Some(user), Some(password)) => {
+                connection.command(&[b"AUTH", user.as_bytes(), password.as_bytes()])?;
+            }
+            (None, Some(password)) => {
+                connection.command(&[b"AUTH", password.as_bytes()])?;
+            }
+            _ => {}
+        }
+        if url.db != 0 {
+            connection.command(&[b"SELECT", url.db.to_string().as_bytes()])?;
+        }
+
+        Ok(connection)
+    }
+
+    fn command(&mut self, args: &[&[u8]]) -> Result<Reply> {
+        let mut request = format!("*{}\r\n", args.len()).into_bytes();
+        for arg in args {
+            request.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+            request.extend_from_slice(arg);
+            request.extend_from_slice(b"\r\n");
+        }
+        self.writer.write_all(&request)?;
+
+        read_reply(&mut self.reader)
+    }
+}
+
+fn read_reply(reader: &mut impl BufRead) -> Result<Reply> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Connection closed by Redis").into());
+    }
+    let line = line.trim_end_matches("\r\n");
+    let protocol_error = || CacheError::Backend(format!("Unexpected Redis reply: {}", line));
+    let (kind, body) = line.split_at(line.len().min(1));
+
+    match kind {
+        "+" => Ok(Reply::Status(body.to_string())),
+        "-" => Err(CacheError::Backend(body.to_string())),
+        ":" => body.parse().map(Reply::Integer).map_err(|_| protocol_error()),
+        "$" => {
+            let len: i64 = body.parse().map_err(|_| protocol_error())?;
+            if len < 0 {
+                return Ok(Reply::Nil);
+            }
+            let mut data = vec![0; len as usize + 2];
+            reader.read_exact(&mut data)?;
+            data.truncate(len as usize);
+            Ok(Reply::Bulk(data))
+        }
+        "*" => {
+            let len: i64 = body.parse().map_err(|_| protocol_error())?;
+            if len < 0 {
+                return Ok(Reply::Nil);
+            }
+            (0..len).map(|_| read_reply(reader)).collect::<Result<_>>().map(Reply::Array)
+        }
+        _ => Err(protocol_error()),
+    }
+}
+
+/// L2 cache stored in Redis
+pub struct RedisCache {
+    config: RedisConfig,
+    url: RedisUrl,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl RedisCache {
+    /// Connect to Redis
+    pub fn new(config: RedisConfig) -> Result<Self> {
+        let url = RedisUrl::parse(&config.url)?;
+        let connection = Connection::open(&url, config.timeout)?;
+
+        Ok(Self {
+            config,
+            url,
+            connection: Mutex::new(Some(connection)),
+        })
+    }
+
+    fn redis_key(&self, cache_key: &CacheKey) -> String {
+        format!("{}{}", self.config.key_prefix, cache_key)
+    }
+
+    /// Run a command, reconnecting once if the connection was lost
+    fn command(&self, args: &[&[u8]]) -> Result<Reply> {
+        let mut connection = self.connection.lock().unwrap();
+
+        if let Some(conn) = connection.as_mut() {
+            match conn.command(args) {
+                Err(CacheError::Io(_)) => {}
+                result => return result,
+            }
+        }
+
+        *connection = None;
+        let conn = connection.insert(Connection::open(&self.url, self.config.timeout)?);
+        conn.command(args)
+    }
+
+    /// All keys written by this cache
+    fn scan_keys(&self) -> Result<Vec<Vec<u8>>> {
+        let pattern = format!("{}*", escape_glob(&self.config.key_prefix));
+        let mut cursor = b"0".to_vec();
+        let mut keys = Vec::new();
+
+        loop {
+            let reply = self.command(&[b"SCAN", &cursor, b"MATCH", pattern.as_bytes(), b"COUNT", SCAN_COUNT.as_bytes()])?;
+            let Reply::Array(mut parts) = reply else {
+                return Err(CacheError::Backend("Unexpected SCAN reply".to_string()));
+            };
+            if parts.len() != 2 {
+                return Err(CacheError::Backend("Unexpected SCAN reply".to_string()));
+            }
+            if let Reply::Array(batch) = parts.pop().unwrap() {
+                keys.extend(batch.into_iter().filter_map(|key| match key {
+                    Reply::Bulk(key) => Some(key),
+                    _ => None,
+                }));
+            }
+            cursor = match parts.pop().unwrap() {
+                Reply::Bulk(next) => next,
+                _ => return Err(CacheError::Backend("Unexpected SCAN cursor".to_string())),
+            };
+            if cursor == b"0" {
+                return Ok(keys);
+            }
+        }
+    }
+
+    fn delete_keys(&self, keys: &[Vec<u8>]) -> Result<()> {
+        for batch in keys.chunks(500) {
+            let mut args: Vec<&[u8]> = vec![b"DEL"];
+            args.extend(batch.iter().map(Vec::as_slice));
+            self.command(&args)?;
+        }
+        Ok(())
+    }
+}
+
+impl L2Backend for RedisCache {
+    fn get(&self, namespace: &str, key: &str, env: &str) -> Result<ConfigEntry> {
+        let cache_key = CacheKey::new(namespace, key, env);
+        match self.command(&[b"GET", self.redis_key(&cache_key).as_bytes()])? {
+            Reply::Bulk(data) => {
+                serde_json::from_slice(&data).map_err(|e| CacheError::Serialization(e.to_string()))
+            }
+            _ => Err(CacheError::CacheMiss(cache_key.to_string())),
+        }
+    }
+
+    fn put(&self, entry: &ConfigEntry) -> Result<()> {
+        let data = serde_json::to_vec(entry).map_err(|e| CacheError::Serialization(e.to_string()))?;
+        let redis_key = self.redis_key(&CacheKey::for_entry(entry));
+
+        match self.config.ttl {
+            Some(ttl) => {
+                let millis = ttl.as_millis().max(1).to_string();
+                self.command(&[b"SET", redis_key.as_bytes(), &data, b"PX", millis.as_bytes()])?
+            }
+            None => self.command(&[b"SET", redis_key.as_bytes(), &data])?,
+        };
+        Ok(())
+    }
+
+    fn invalidate(&self, namespace: &str, key: &str, env: &str) -> Result<()> {
+        let cache_key = CacheKey::new(namespace, key, env);
+        self.command(&[b"DEL", self.redis_key(&cache_key).as_bytes()])?;
+        Ok(())
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) -> Result<usize> {
+        // Keys are matched on the stored entry, since `:` may appear in names
+        let mut matching = Vec::new();
+        for redis_key in self.scan_keys()? {
+            if let Reply::Bulk(data) = self.command(&[b"GET", &redis_key])? {
+                if let Ok(entry) = serde_json::from_slice::<ConfigEntry>(&data) {
+                    if CacheKey::for_entry(&entry).has_prefix(prefix) {
+                        matching.push(redis_key);
+                    }
+                }
+            }
+        }
+
+        self.delete_keys(&matching)?;
+        Ok(matching.len())
+    }
+
+    fn clear(&self) -> Result<()> {
+        let keys = self.scan_keys()?;
+        self.delete_keys(&keys)
+    }
+
+    /// Number of cached entries (0 if Redis cannot be reached)
+    fn size(&self) -> usize {
+        self.scan_keys().map(|keys| keys.len()).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_core::{ConfigValue, Environment};
+    use std::collections::BTreeMap;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    /// Minimal in-memory Redis speaking the commands the cache uses
+    fn fake_redis() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<u8>>>> = Arc::default();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let data = Arc::clone(&data);
+                std::thread::spawn(move || serve(stream, data));
+            }
+        });
+
+        format!("redis://:secret@{}/2", address)
+    }
+
+    fn serve(stream: TcpStream, data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<u8>>>>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+
+        while let Ok(Reply::Array(args)) = read_reply(&mut reader) {
+            let args: Vec<Vec<u8>> = args
+                .into_iter()
+                .map(|arg| match arg {
+                    Reply::Bulk(arg) => arg,
+                    other => panic!("unexpected argument {:?}", other),
+                })
+                .collect();
+            let mut data = data.lock().unwrap();
+
+            let bulk = |value: &[u8]| {
+                let mut out = format!("${}\r\n", value.len()).into_bytes();
+                out.extend_from_slice(value);
+                out.extend_from_slice(b"\r\n");
+                out
+            };
+            let response = match args[0].as_slice() {
+                b"AUTH" if args[1] == b"secret" => b"+OK\r\n".to_vec(),
+                b"AUTH" => b"-WRONGPASS invalid password\r\n".to_vec(),
+                b"SELECT" => b"+OK\r\n".to_vec(),
+                b"GET" => match data.get(&args[1]) {
+                    Some(value) => bulk(value),
+                    None => b"$-1\r\n".to_vec(),
+                },
+                b"SET" => {
+                    assert!(args.len() == 3 || args[3] == b"PX");
+                    data.insert(args[1].clone(), args[2].clone());
+                    b"+OK\r\n".to_vec()
+                }
+                b"DEL" => {
+                    let removed = args[1..].iter().filter(|key| data.remove(*key).is_some()).count();
+                    format!(":{}\r\n", removed).into_bytes()
+                }
+                b"SCAN" => {
+                    let prefix = args[3].strip_suffix(b"*").unwrap();
+                    let keys: Vec<&Vec<u8>> = data.keys().filter(|key| key.starts_with(prefix)).collect();
+                    let mut out = format!("*2\r\n$1\r\n0\r\n*{}\r\n", keys.len()).into_bytes();
+                    for key in keys {
+                        out.extend(bulk(key));
+                    }
+                    out
+                }
+                other => format!("-ERR unknown command {}\r\n", String::from_utf8_lossy(other)).into_bytes(),
+            };
+            if writer.write_all(&response).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn entry(namespace: &str, key: &str) -> ConfigEntry {
+        ConfigEntry::new(namespace, key, ConfigValue::String("value".into()), Environment::Production)
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            RedisUrl::parse("redis://user:pw@cache.internal:6380/3").unwrap(),
+            RedisUrl {
+                host: "cache.internal".to_string(),
+                port: 6380,
+                username: Some("user".to_string()),
+                password: Some("pw".to_string()),
+                db: 3,
+            }
+        );
+
+        let url = RedisUrl::parse("redis://localhost").unwrap();
+        assert_eq!((url.port, url.db, url.password), (6379, 0, None));
+
+        assert!(RedisUrl::parse("http://localhost").is_err());
+        assert!(RedisUrl::parse("redis://localhost:port").is_err());
+    }
+
+    #[test]
+    fn test_redis_cache() {
+        let url = fake_redis();
+        let cache = RedisCache::new(RedisConfig::new(&url).with_ttl(Duration::from_secs(60))).unwrap();
+
+        cache.put(&entry("team-a/prompts", "summary")).unwrap();
+        cache.put(&entry("team-a", "model")).unwrap();
+        cache.put(&entry("team-ab", "model")).unwrap();
+        assert_eq!(cache.size(), 3);
+
+        let cached = cache.get("team-a", "model", "production").unwrap();
+        assert_eq!(cached.value.as_str(), Some("value"));
+        assert!(matches!(cache.get("team-a", "missing", "production"), Err(CacheError::CacheMiss(_))));
+
+        assert_eq!(cache.invalidate_prefix("team-a").unwrap(), 2);
+        assert!(cache.get("team-ab", "model", "production").is_ok());
+
+        cache.invalidate("team-ab", "model", "production").unwrap();
+        assert_eq!(cache.size(), 0);
+
+        // Another replica with a different prefix does not see these entries
+        let other = RedisCache::new(RedisConfig::new(&url).with_key_prefix("other:")).unwrap();
+        cache.put(&entry("ns", "key")).unwrap();
+        assert_eq!(other.size(), 0);
+        other.clear().unwrap();
+        assert_eq!(cache.size(), 1);
+
+        assert!(RedisCache::new(RedisConfig::new(url.replace("secret", "wrong"))).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Count-min frequency sketch for TinyLFU admission
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEPTH: usize = 4;
+const MAX_COUNT: u8 = 15;
+const SEEDS: [u64; DEPTH] = [
+    0x9E37_79B9_7F4A_7C15,
+    0xC2B2_AE3D_27D4_EB4F,
+    0x1656_67B1_9E37_79F9,
+    0x85EB_CA77_C2B2_AE63,
+];
+
+/// Approximate recent access frequency of keys
+///
+/// Counters saturate at 15 and are halved once `10 × capacity` accesses
+/// have been recorded, so old popularity fades.
+#[derive(Debug)]
+pub(crate) struct FrequencySketch {
+    table: Vec<u8>,
+    width: usize,
+    additions: usize,
+    sample_size: usize,
+}
+
+impl FrequencySketch {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let width = capacity.max(16).next_power_of_two();
+        Self {
+            table: vec![0; width * DEPTH],
+            width,
+            additions: 0,
+            sample_size: capacity.max(16) * 10,
+        }
+    }
+
+    fn indexes<K: Hash>(&self, key: &K) -> [usize; DEPTH] {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut indexes = [0; DEPTH];
+        for (row, seed) in SEEDS.iter().enumerate() {
+            let slot = (hash.wrapping_mul(*seed) >> 32) as usize & (self.width - 1);
+            indexes[row] = row * self.width + slot;
+        }
+        indexes
+    }
+
+    /// Record an access
+    pub(crate) fn increment<K: Hash>(&mut self, key: &K) {
+        for index in self.indexes(key) {
+            if self.table[index] < MAX_COUNT {
+                self.table[index] += 1;
+            }
+        }
+
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            for counter in &mut self.table {
+                *counter /= 2;
+            }
+            self.additions /= 2;
+        }
+    }
+
+    /// Estimated number of recent accesses
+    pub(crate) fn frequency<K: Hash>(&self, key: &K) -> u8 {
+        self.indexes(key)
+            .into_iter()
+            .map(|index| self.table[index])
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequency_and_aging() {
+        let mut sketch = FrequencySketch::new(16);
+        for _ in 0..5 {
+            sketch.increment(&"hot");
+        }
+        sketch.increment(&"cold");
+
+        assert!(sketch.frequency(&"hot") >= 5);
+        assert!(sketch.frequency(&"hot") > sketch.frequency(&"cold"));
+
+        // Filling the sample halves every counter
+        let before = sketch.frequency(&"hot");
+        for _ in 0..160 {
+            sketch.increment(&"filler");
+        }
+        assert!(sketch.frequency(&"hot") < before);
+    }
+}
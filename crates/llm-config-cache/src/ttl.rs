@@ -0,0 +1,245 @@
+//! Entry TTLs and stale-while-revalidate
+//!
+//! A [`CacheTtl`] bounds how long an entry is served after it was loaded
+//! from storage. Past its TTL an entry is expired and reads miss, unless the
+//! TTL allows serving it stale for a while: such a read returns the cached
+//! entry at once and queues a refresh from storage, which
+//! [`CacheManager::spawn_revalidation`] performs in the background.
+//!
+//! TTLs are set for the whole cache with [`CacheManager::with_ttl`] and per
+//! namespace with [`CacheManager::with_namespace_ttl`]; the TTL of the
+//! closest configured namespace applies, so a namespace's TTL also covers
+//! its child namespaces. Entries without a TTL live until evicted or
+//! invalidated.
+
+use crate::key::CacheKey;
+use crate::CacheManager;
+use chrono::{DateTime, Utc};
+use llm_config_core::{ConfigManager, Environment};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// How long cached entries are served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtl {
+    /// Age after which an entry is refreshed from storage
+    pub ttl: Duration,
+    /// How long past `ttl` an entry is still served while it is refreshed
+    pub stale_while_revalidate: Option<Duration>,
+}
+
+impl CacheTtl {
+    /// Expire entries `ttl` after they were loaded
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            stale_while_revalidate: None,
+        }
+    }
+
+    /// Serve expired entries for up to `window` while they are refreshed
+    pub fn with_stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = Some(window);
+        self
+    }
+
+    /// How an entry loaded at `cached_at` may be served at `now`
+    pub fn freshness(&self, cached_at: DateTime<Utc>, now: DateTime<Utc>) -> Freshness {
+        let age = (now - cached_at).to_std().unwrap_or_default();
+        if age < self.ttl {
+            Freshness::Fresh
+        } else if self.stale_while_revalidate.is_some_and(|window| age < self.ttl + window) {
+            Freshness::Stale
+        } else {
+            Freshness::Expired
+        }
+    }
+}
+
+/// Whether a cached entry may be served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Fresh,
+    /// Served, but refreshed from storage
+    Stale,
+    /// Not served
+    Expired,
+}
+
+/// TTLs of a cache
+#[derive(Debug, Default)]
+pub(crate) struct TtlPolicy {
+    default: Option<CacheTtl>,
+    namespaces: HashMap<String, CacheTtl>,
+}
+
+impl TtlPolicy {
+    /// TTL of the closest configured namespace, or the default
+    pub(crate) fn for_namespace(&self, namespace: &str) -> Option<CacheTtl> {
+        let mut namespace = namespace.trim_matches('/');
+        loop {
+            if let Some(ttl) = self.namespaces.get(namespace) {
+                return Some(*ttl);
+            }
+            match namespace.rfind('/') {
+                Some(parent) => namespace = &namespace[..parent],
+                None => return self.default,
+            }
+        }
+    }
+}
+
+/// Queue of entries to refresh from storage
+#[derive(Default)]
+pub(crate) struct Revalidation {
+    sender: Mutex<Option<mpsc::UnboundedSender<CacheKey>>>,
+    /// Keys queued and not refreshed yet
+    pending: Mutex<HashSet<CacheKey>>,
+}
+
+impl CacheManager {
+    /// Expire entries of every namespace without a TTL of its own
+    pub fn with_ttl(mut self, ttl: CacheTtl) -> Self {
+        self.ttl.default = Some(ttl);
+        self
+    }
+
+    /// Expire entries of `namespace` and its child namespaces
+    pub fn with_namespace_ttl(mut self, namespace: impl Into<String>, ttl: CacheTtl) -> Self {
+        let namespace = namespace.into().trim_matches('/').to_string();
+        self.ttl.namespaces.insert(namespace, ttl);
+        self
+    }
+
+    /// TTL that applies to entries of `namespace`
+    pub fn ttl(&self, namespace: &str) -> Option<CacheTtl> {
+        self.ttl.for_namespace(namespace)
+    }
+
+    /// Refresh stale entries from `manager` in a background task
+    ///
+    /// Without it, stale entries are not served. The task ends when the
+    /// cache is dropped.
+    pub fn spawn_revalidation(self: &Arc<Self>, manager: Arc<ConfigManager>) -> JoinHandle<()> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<CacheKey>();
+        *self.revalidation.sender.lock().unwrap() = Some(sender);
+        let cache = Arc::downgrade(self);
+
+        tokio::spawn(async move {
+            while let Some(cache_key) = receiver.recv().await {
+                let Some(cache) = cache.upgrade() else {
+                    break;
+                };
+                let manager = Arc::clone(&manager);
+                let _ = tokio::task::spawn_blocking(move || {
+                    // An entry that cannot be refreshed is not served stale again
+                    if cache.refresh(&manager, &cache_key).is_err() {
+                        let _ = cache.invalidate(&cache_key.namespace, &cache_key.key, &cache_key.env);
+                    }
+                    cache.revalidation.pending.lock().unwrap().remove(&cache_key);
+                })
+                .await;
+            }
+        })
+    }
+
+    /// Whether stale entries can be refreshed
+    pub(crate) fn can_revalidate(&self) -> bool {
+        self.revalidation.sender.lock().unwrap().is_some()
+    }
+
+    /// Queue a refresh of a stale entry, unless one is queued already
+    pub(crate) fn revalidate(&self, namespace: &str, key: &str, env: &str) {
+        let cache_key = CacheKey::new(namespace, key, env);
+        if !self.revalidation.pending.lock().unwrap().insert(cache_key.clone()) {
+            return;
+        }
+        let sender = self.revalidation.sender.lock().unwrap();
+        if !sender.as_ref().is_some_and(|sender| sender.send(cache_key.clone()).is_ok()) {
+            self.revalidation.pending.lock().unwrap().remove(&cache_key);
+        }
+    }
+
+    /// Replace a cached entry with the stored one, or drop it if it is gone
+    fn refresh(&self, manager: &ConfigManager, cache_key: &CacheKey) -> crate::Result<()> {
+        let env: Environment = cache_key
+            .env
+            .parse()
+            .map_err(crate::CacheError::Backend)?;
+        match manager.get_untracked(&cache_key.namespace, &cache_key.key, env)? {
+            Some(entry) => self.put(entry),
+            None => self.invalidate(&cache_key.namespace, &cache_key.key, &cache_key.env),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_core::ConfigValue;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_freshness() {
+        let ttl = CacheTtl::new(Duration::from_secs(60));
+        let now = Utc::now();
+        let ago = |seconds| now - chrono::Duration::seconds(seconds);
+        assert_eq!(ttl.freshness(ago(59), now), Freshness::Fresh);
+        assert_eq!(ttl.freshness(ago(61), now), Freshness::Expired);
+
+        let ttl = ttl.with_stale_while_revalidate(Duration::from_secs(30));
+        assert_eq!(ttl.freshness(ago(61), now), Freshness::Stale);
+        assert_eq!(ttl.freshness(ago(91), now), Freshness::Expired);
+    }
+
+    #[test]
+    fn test_namespace_ttls() {
+        let temp_dir = TempDir::new().unwrap();
+        let (short, long) = (CacheTtl::new(Duration::from_secs(1)), CacheTtl::new(Duration::from_secs(60)));
+        let cache = CacheManager::new(10, temp_dir.path())
+            .unwrap()
+            .with_ttl(long)
+            .with_namespace_ttl("team-a/", short);
+        assert_eq!(cache.ttl("team-a"), Some(short));
+        assert_eq!(cache.ttl("team-a/prompts"), Some(short));
+        assert_eq!(cache.ttl("team-ab"), Some(long));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_stale_while_revalidate() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(ConfigManager::new(temp_dir.path().join("storage")).unwrap());
+        let env = Environment::Production;
+        manager.set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+
+        let ttl = CacheTtl::new(Duration::from_millis(50)).with_stale_while_revalidate(Duration::from_secs(60));
+        let cache = CacheManager::new(10, temp_dir.path().join("cache")).unwrap().with_ttl(ttl);
+        cache.put(manager.get_untracked("app", "model", env).unwrap().unwrap()).unwrap();
+        let cache = Arc::new(cache);
+
+        // Written behind the cache's back
+        manager.set("app", "model", ConfigValue::String("gpt-4o".into()), env, "alice").unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        // Without revalidation, stale entries miss
+        assert!(cache.get("app", "model", "production").is_err());
+        cache.put(manager.get_untracked("app", "model", env).unwrap().unwrap()).unwrap();
+        manager.set("app", "model", ConfigValue::String("claude".into()), env, "alice").unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        cache.spawn_revalidation(Arc::clone(&manager));
+        let stale = cache.get("app", "model", "production").unwrap();
+        assert_eq!(stale.value.as_str(), Some("gpt-4o"));
+        for _ in 0..100 {
+            if cache.l1.peek("app", "model", "production").is_some_and(|entry| entry.version == 3) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let fresh = cache.get("app", "model", "production").unwrap();
+        assert_eq!(fresh.value.as_str(), Some("claude"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Preloading hot namespaces
+//!
+//! [`CacheManager::warm`] loads every entry of a set of namespaces into both
+//! tiers, so the first reads after a deploy are served from L1 instead of
+//! storage. With [`CacheManager::with_preload`], the same namespaces are
+//! loaded again whenever the caches were cleared because the invalidation
+//! task fell behind a burst of change events.
+
+use crate::{CacheManager, Result};
+use llm_config_core::{ConfigManager, Environment, ENVIRONMENTS};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Namespaces loaded again after the caches were cleared
+pub(crate) struct Preload {
+    manager: Arc<ConfigManager>,
+    namespaces: Vec<String>,
+    env: Option<Environment>,
+}
+
+/// Outcome of a warmup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WarmupReport {
+    pub namespaces: usize,
+    /// Entries loaded into the cache
+    pub entries: usize,
+    pub duration_ms: u64,
+}
+
+impl CacheManager {
+    /// Load every entry of `namespaces` in `env` (every environment if
+    /// `None`) from `manager` into both tiers
+    ///
+    /// Entries are listed like `ConfigManager::list`, so secrets stay
+    /// encrypted and the reads are not counted as accesses.
+    pub fn warm<N: AsRef<str>>(
+        &self,
+        manager: &ConfigManager,
+        namespaces: &[N],
+        env: Option<Environment>,
+    ) -> Result<WarmupReport> {
+        let started = Instant::now();
+        let envs = match env {
+            Some(env) => vec![env],
+            None => ENVIRONMENTS.to_vec(),
+        };
+
+        let mut entries = 0;
+        for namespace in namespaces {
+            for &env in &envs {
+                for entry in manager.list(namespace.as_ref(), env)? {
+                    self.put(entry)?;
+                    entries += 1;
+                }
+            }
+        }
+
+        let duration = started.elapsed();
+        if let Some(metrics) = &self.metrics {
+            metrics.observe("cache_warmup_duration_seconds", &[], duration.as_secs_f64());
+            metrics.record_counter("cache_warmed_entries_total", &[], entries as f64);
+        }
+        Ok(WarmupReport {
+            namespaces: namespaces.len(),
+            entries,
+            duration_ms: duration.as_millis() as u64,
+        })
+    }
+
+    /// Preload `namespaces` of `manager` with [`preload`](Self::preload), and
+    /// again whenever the invalidation task cleared the caches
+    pub fn with_preload(
+        mut self,
+        manager: Arc<ConfigManager>,
+        namespaces: Vec<String>,
+        env: Option<Environment>,
+    ) -> Self {
+        self.preload = Some(Preload {
+            manager,
+            namespaces,
+            env,
+        });
+        self
+    }
+
+    /// Namespaces set with [`with_preload`](Self::with_preload)
+    pub fn preload_namespaces(&self) -> &[String] {
+        self.preload.as_ref().map_or(&[], |preload| &preload.namespaces)
+    }
+
+    /// Load the namespaces set with [`with_preload`](Self::with_preload)
+    ///
+    /// Returns `None` if no namespaces are preloaded.
+    pub fn preload(&self) -> Result<Option<WarmupReport>> {
+        match &self.preload {
+            Some(preload) if !preload.namespaces.is_empty() => self
+                .warm(&preload.manager, &preload.namespaces, preload.env)
+                .map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_core::ConfigValue;
+    use llm_config_metrics::MetricsRegistry;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_warm() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(ConfigManager::new(temp_dir.path().join("storage")).unwrap());
+        for (namespace, env) in [
+            ("app/llm", Environment::Production),
+            ("app/llm", Environment::Staging),
+            ("app/other", Environment::Production),
+        ] {
+            manager.set(namespace, "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        }
+
+        let metrics = Arc::new(MetricsRegistry::new().unwrap());
+        let cache = CacheManager::new(100, temp_dir.path().join("cache"))
+            .unwrap()
+            .with_metrics(metrics.clone());
+        let report = cache.warm(&manager, &["app/llm"], Some(Environment::Production)).unwrap();
+        assert_eq!((report.namespaces, report.entries), (1, 1));
+        assert!(cache.get("app/llm", "model", "production").is_ok());
+        assert!(cache.get("app/llm", "model", "staging").is_err());
+
+        let report = cache.warm(&manager, &["app/llm", "app/other"], None).unwrap();
+        assert_eq!(report.entries, 3);
+        assert_eq!(cache.l1_stats().size, 3);
+        assert_eq!(metrics.cache().warmed_entries(), 4);
+    }
+
+    #[test]
+    fn test_preload() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(ConfigManager::new(temp_dir.path().join("storage")).unwrap());
+        let env = Environment::Production;
+        manager.set("app/llm", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+
+        let cache = CacheManager::new(100, temp_dir.path().join("cache")).unwrap();
+        assert_eq!(cache.preload().unwrap(), None);
+        let cache = cache.with_preload(Arc::clone(&manager), vec!["app/llm".to_string()], Some(env));
+        assert_eq!(cache.preload_namespaces(), ["app/llm"]);
+        assert_eq!(cache.preload().unwrap().unwrap().entries, 1);
+        assert!(cache.get("app/llm", "model", "production").is_ok());
+    }
+}
@@ -0,0 +1,518 @@
+//! Interactive terminal browser for namespaces, environments, and values
+
+use llm_config_core::{ConfigEntry, ConfigManager, ConfigValue, Environment, VersionEntry};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::{BTreeSet, HashMap};
+
+const ENVIRONMENTS: [Environment; 5] = [
+    Environment::Base,
+    Environment::Development,
+    Environment::Staging,
+    Environment::Production,
+    Environment::Edge,
+];
+
+const HELP: &str =
+    "q quit | / search | tab switch pane | ←/→ environment | r reveal | h history";
+
+/// Pane receiving navigation keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Namespaces,
+    Keys,
+    History,
+}
+
+/// Browser state
+struct Browser<'a> {
+    manager: &'a ConfigManager,
+    can_reveal: bool,
+    entries: Vec<ConfigEntry>,
+    env: usize,
+    focus: Focus,
+    namespace: usize,
+    key: usize,
+    search: String,
+    searching: bool,
+    /// Revealed secrets by `namespace:key@env#version`
+    revealed: HashMap<String, String>,
+    history: Vec<VersionEntry>,
+    version: usize,
+    status: Option<String>,
+}
+
+/// Run the interactive browser until the user quits
+///
+/// Secrets can only be revealed when `can_reveal` is set (an encryption key
+/// is configured).
+pub fn run(manager: &ConfigManager, can_reveal: bool) -> anyhow::Result<()> {
+    let mut browser = Browser::new(manager, can_reveal)?;
+
+    let mut terminal = ratatui::init();
+    let result = browser.event_loop(&mut terminal);
+    ratatui::restore();
+
+    result
+}
+
+impl<'a> Browser<'a> {
+    fn new(manager: &'a ConfigManager, can_reveal: bool) -> anyhow::Result<Self> {
+        let mut entries = manager.list_all()?;
+        entries.sort_by(|a, b| (&a.namespace, &a.key).cmp(&(&b.namespace, &b.key)));
+
+        Ok(Self {
+            manager,
+            can_reveal,
+            entries,
+            env: 0,
+            focus: Focus::Namespaces,
+            namespace: 0,
+            key: 0,
+            search: String::new(),
+            searching: false,
+            revealed: HashMap::new(),
+            history: Vec::new(),
+            version: 0,
+            status: None,
+        })
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn environment(&self) -> Environment {
+        ENVIRONMENTS[self.env]
+    }
+
+    fn matches(&self, entry: &ConfigEntry) -> bool {
+        self.search.is_empty()
+            || entry
+                .full_path()
+                .to_lowercase()
+                .contains(&self.search.to_lowercase())
+    }
+
+    /// Namespaces with at least one matching value in any environment
+    fn namespaces(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| self.matches(entry))
+            .map(|entry| entry.namespace.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Matching values of the selected namespace in the selected environment
+    fn keys(&self) -> Vec<&ConfigEntry> {
+        let Some(namespace) = self.namespaces().get(self.namespace).copied() else {
+            return Vec::new();
+        };
+
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry.namespace == namespace
+                    && entry.environment == self.environment()
+                    && self.matches(entry)
+            })
+            .collect()
+    }
+
+    fn selected_entry(&self) -> Option<&ConfigEntry> {
+        self.keys().get(self.key).copied()
+    }
+
+    /// Handle a key press, returning whether to quit
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        self.status = None;
+
+        if self.searching {
+            match code {
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.reset_selection();
+                }
+                KeyCode::Char(c) => {
+                    self.search.push(c);
+                    self.reset_selection();
+                }
+                _ => {}
+            }
+            return false;
+        }
+
+        match code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc if self.focus == Focus::History => self.focus = Focus::Keys,
+            KeyCode::Esc if !self.search.is_empty() => {
+                self.search.clear();
+                self.reset_selection();
+            }
+            KeyCode::Esc => return true,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Namespaces => Focus::Keys,
+                    Focus::Keys | Focus::History => Focus::Namespaces,
+                }
+            }
+            KeyCode::Left => self.cycle_environment(ENVIRONMENTS.len() - 1),
+            KeyCode::Right => self.cycle_environment(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(true),
+            KeyCode::Char('r') => self.toggle_reveal(),
+            KeyCode::Char('h') | KeyCode::Enter => self.toggle_history(),
+            _ => {}
+        }
+
+        false
+    }
+
+    fn reset_selection(&mut self) {
+        self.namespace = 0;
+        self.key = 0;
+        if self.focus == Focus::History {
+            self.focus = Focus::Keys;
+        }
+    }
+
+    fn cycle_environment(&mut self, step: usize) {
+        self.env = (self.env + step) % ENVIRONMENTS.len();
+        self.key = 0;
+        if self.focus == Focus::History {
+            self.focus = Focus::Keys;
+        }
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let len = match self.focus {
+            Focus::Namespaces => self.namespaces().len(),
+            Focus::Keys => self.keys().len(),
+            Focus::History => self.history.len(),
+        };
+        let index = match self.focus {
+            Focus::Namespaces => &mut self.namespace,
+            Focus::Keys => &mut self.key,
+            Focus::History => &mut self.version,
+        };
+
+        if down {
+            *index = (*index + 1).min(len.saturating_sub(1));
+        } else {
+            *index = index.saturating_sub(1);
+        }
+
+        if self.focus == Focus::Namespaces {
+            self.key = 0;
+        }
+    }
+
+    /// Value and reveal key of the selected entry or history version
+    fn selected_value(&self) -> Option<(String, ConfigValue)> {
+        if self.focus == Focus::History {
+            let version = self.history.get(self.version)?;
+            let id = reveal_id(&version.namespace, &version.key, version.environment, version.version);
+            Some((id, version.value.clone()))
+        } else {
+            let entry = self.selected_entry()?;
+            let id = reveal_id(&entry.namespace, &entry.key, entry.environment, entry.version);
+            Some((id, entry.value.clone()))
+        }
+    }
+
+    fn toggle_reveal(&mut self) {
+        let Some((id, value)) = self.selected_value() else {
+            return;
+        };
+
+        if self.revealed.remove(&id).is_some() {
+            return;
+        }
+        if !value.is_secret() {
+            self.status = Some("Selected value is not a secret".to_string());
+            return;
+        }
+        if !self.can_reveal {
+            self.status = Some("An encryption key is required to reveal secrets".to_string());
+            return;
+        }
+
+        match self.manager.reveal(value) {
+            Ok(plaintext) => {
+                self.revealed.insert(id, display_value(&plaintext));
+            }
+            Err(e) => self.status = Some(format!("Failed to reveal secret: {}", e)),
+        }
+    }
+
+    fn toggle_history(&mut self) {
+        if self.focus == Focus::History {
+            self.focus = Focus::Keys;
+            return;
+        }
+
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+
+        match self
+            .manager
+            .get_history(&entry.namespace, &entry.key, entry.environment)
+        {
+            Ok(history) => {
+                self.history = history;
+                self.version = 0;
+                self.focus = Focus::History;
+            }
+            Err(e) => self.status = Some(format!("Failed to load history: {}", e)),
+        }
+    }
+
+    fn value_text(&self, id: &str, value: &ConfigValue) -> String {
+        self.revealed
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| display_value(value))
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(body);
+
+        // Environment tabs and search query
+        let mut spans: Vec<Span> = ENVIRONMENTS
+            .iter()
+            .enumerate()
+            .map(|(i, env)| {
+                let style = if i == self.env {
+                    Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Span::styled(format!(" {} ", env), style)
+            })
+            .collect();
+        if self.searching || !self.search.is_empty() {
+            spans.push(Span::raw(format!("  /{}", self.search)));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), header);
+
+        let namespaces: Vec<ListItem> = self
+            .namespaces()
+            .into_iter()
+            .map(ListItem::new)
+            .collect();
+        self.draw_list(frame, left, "Namespaces", namespaces, self.namespace, Focus::Namespaces);
+
+        if self.focus == Focus::History {
+            let title = self
+                .history
+                .first()
+                .map(|v| format!("History of {}:{}", v.namespace, v.key))
+                .unwrap_or_else(|| "History".to_string());
+            let versions: Vec<ListItem> = self
+                .history
+                .iter()
+                .map(|v| {
+                    let id = reveal_id(&v.namespace, &v.key, v.environment, v.version);
+                    ListItem::new(format!(
+                        "v{}  {} by {}  {}",
+                        v.version,
+                        v.created_at.format("%Y-%m-%d %H:%M:%S"),
+                        v.created_by,
+                        self.value_text(&id, &v.value)
+                    ))
+                })
+                .collect();
+            self.draw_list(frame, right, &title, versions, self.version, Focus::History);
+        } else {
+            let keys: Vec<ListItem> = self
+                .keys()
+                .into_iter()
+                .map(|entry| {
+                    let id = reveal_id(&entry.namespace, &entry.key, entry.environment, entry.version);
+                    ListItem::new(format!(
+                        "{} = {}  (v{})",
+                        entry.key,
+                        self.value_text(&id, &entry.value),
+                        entry.version
+                    ))
+                })
+                .collect();
+            self.draw_list(frame, right, "Values", keys, self.key, Focus::Keys);
+        }
+
+        let status = self.status.as_deref().unwrap_or(HELP);
+        frame.render_widget(
+            Paragraph::new(status).style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn draw_list(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        title: &str,
+        items: Vec<ListItem>,
+        selected: usize,
+        focus: Focus,
+    ) {
+        let border = if self.focus == focus {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+        let list = List::new(items)
+            .block(Block::bordered().title(title.to_string()).border_style(border))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = ListState::default().with_selected(Some(selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+fn reveal_id(namespace: &str, key: &str, env: Environment, version: u64) -> String {
+    format!("{}:{}@{}#{}", namespace, key, env, version)
+}
+
+/// Plain (uncolored) rendering of a value
+fn display_value(value: &ConfigValue) -> String {
+    match value {
+        ConfigValue::String(s) => s.clone(),
+        ConfigValue::Integer(i) => i.to_string(),
+        ConfigValue::Float(f) => f.to_string(),
+        ConfigValue::Boolean(b) => b.to_string(),
+        ConfigValue::Array(arr) => format!(
+            "[{}]",
+            arr.iter().map(display_value).collect::<Vec<_>>().join(", ")
+        ),
+        ConfigValue::Object(_) => "<object>".to_string(),
+        ConfigValue::Duration(_) | ConfigValue::Size(_) | ConfigValue::Url(_) => {
+            value.typed_text().unwrap_or_default()
+        }
+        ConfigValue::Secret(_) => "<encrypted>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use tempfile::TempDir;
+
+    fn manager(temp_dir: &TempDir) -> ConfigManager {
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key);
+
+        for (namespace, key, env) in [
+            ("app/llm", "model", Environment::Base),
+            ("app/llm", "timeout", Environment::Base),
+            ("app/llm", "model", Environment::Production),
+            ("billing", "currency", Environment::Base),
+        ] {
+            manager
+                .set(namespace, key, ConfigValue::String("v1".to_string()), env, "test")
+                .unwrap();
+        }
+        manager
+            .set_secret("app/llm", "api_key", "sk-123", Environment::Base, "test")
+            .unwrap();
+        manager
+    }
+
+    fn keys(browser: &Browser) -> Vec<String> {
+        browser.keys().iter().map(|e| e.key.clone()).collect()
+    }
+
+    #[test]
+    fn test_navigation_and_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        let mut browser = Browser::new(&manager, true).unwrap();
+
+        assert_eq!(browser.namespaces(), vec!["app/llm", "billing"]);
+        assert_eq!(keys(&browser), vec!["api_key", "model", "timeout"]);
+
+        // Environments cycle in both directions
+        browser.handle_key(KeyCode::Left);
+        assert_eq!(browser.environment(), Environment::Edge);
+        browser.handle_key(KeyCode::Right);
+        browser.handle_key(KeyCode::Right);
+        assert_eq!(browser.environment(), Environment::Development);
+        assert!(keys(&browser).is_empty());
+        browser.env = 0;
+
+        browser.handle_key(KeyCode::Down);
+        assert_eq!(keys(&browser), vec!["currency"]);
+
+        for code in [KeyCode::Char('/'), KeyCode::Char('m'), KeyCode::Char('o'), KeyCode::Enter] {
+            assert!(!browser.handle_key(code));
+        }
+        assert_eq!(browser.namespaces(), vec!["app/llm"]);
+        assert_eq!(keys(&browser), vec!["model"]);
+
+        browser.handle_key(KeyCode::Esc);
+        assert_eq!(browser.namespaces().len(), 2);
+        assert!(browser.handle_key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_reveal_and_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        manager
+            .set("app/llm", "model", ConfigValue::String("v2".to_string()), Environment::Base, "test")
+            .unwrap();
+
+        let mut browser = Browser::new(&manager, false).unwrap();
+        browser.handle_key(KeyCode::Tab);
+
+        // Secrets stay masked without an encryption key
+        browser.handle_key(KeyCode::Char('r'));
+        assert!(browser.revealed.is_empty());
+        assert!(browser.status.is_some());
+
+        browser.can_reveal = true;
+        browser.handle_key(KeyCode::Char('r'));
+        assert_eq!(browser.revealed.values().next().unwrap(), "sk-123");
+        browser.handle_key(KeyCode::Char('r'));
+        assert!(browser.revealed.is_empty());
+
+        browser.handle_key(KeyCode::Down);
+        browser.handle_key(KeyCode::Char('h'));
+        assert_eq!(browser.focus, Focus::History);
+        let versions: Vec<u64> = browser.history.iter().map(|v| v.version).collect();
+        assert_eq!(versions, vec![2, 1]);
+
+        browser.handle_key(KeyCode::Down);
+        assert_eq!(browser.selected_value().unwrap().1.as_str(), Some("v1"));
+        browser.handle_key(KeyCode::Esc);
+        assert_eq!(browser.focus, Focus::Keys);
+    }
+}
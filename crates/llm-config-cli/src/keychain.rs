@@ -0,0 +1,80 @@
+//! Encryption keys in the OS keychain (`keyring` feature)
+//!
+//! Keys are stored per storage directory under the `llm-config` service of
+//! the macOS Keychain, Windows Credential Manager or Secret Service, so they
+//! never have to appear in environment variables or shell history.
+
+use clap::Subcommand;
+use colored::Colorize;
+use llm_config_crypto::{Algorithm, SecretKey};
+use std::io::BufRead;
+use std::path::Path;
+
+/// Keychain service the keys are stored under
+const SERVICE: &str = "llm-config";
+
+#[derive(Subcommand)]
+pub enum KeyCommands {
+    /// Store the encryption key of the storage directory in the keychain
+    ///
+    /// Stores the key given with --encryption-key or --passphrase, otherwise
+    /// a base64 key read from stdin.
+    Store {
+        /// Store a newly generated key instead
+        #[arg(long)]
+        generate: bool,
+    },
+
+    /// Print the stored key (base64), e.g. to provision a server
+    Load,
+
+    /// Remove the stored key from the keychain
+    Delete,
+}
+
+/// Run a keychain command for a storage directory
+pub fn run(storage: &Path, key: Option<SecretKey>, command: &KeyCommands) -> anyhow::Result<()> {
+    match command {
+        KeyCommands::Store { generate } => {
+            let key = match (key, generate) {
+                (Some(key), _) => key,
+                (None, true) => SecretKey::generate(Algorithm::Aes256Gcm)?,
+                (None, false) => {
+                    let mut line = String::new();
+                    std::io::stdin().lock().read_line(&mut line)?;
+                    SecretKey::from_base64(Algorithm::Aes256Gcm, line.trim())?
+                }
+            };
+            std::fs::create_dir_all(storage)?;
+            entry(storage)?.set_password(&key.to_base64())?;
+            eprintln!("{} Stored encryption key in the OS keychain", "✓".green().bold());
+        }
+        KeyCommands::Load => match load(storage)? {
+            Some(key) => println!("{}", key.to_base64()),
+            None => anyhow::bail!("no encryption key stored for {}", storage.display()),
+        },
+        KeyCommands::Delete => {
+            entry(storage)?.delete_credential()?;
+            eprintln!("{} Removed encryption key from the OS keychain", "✓".green().bold());
+        }
+    }
+    Ok(())
+}
+
+/// The stored key of a storage directory, if there is one
+pub fn load(storage: &Path) -> anyhow::Result<Option<SecretKey>> {
+    match entry(storage)?.get_password() {
+        Ok(encoded) => Ok(Some(SecretKey::from_base64(Algorithm::Aes256Gcm, &encoded)?)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Keychain entry of a storage directory, named by its absolute path
+fn entry(storage: &Path) -> anyhow::Result<keyring::Entry> {
+    let path = match std::fs::canonicalize(storage) {
+        Ok(path) => path,
+        Err(_) => std::env::current_dir()?.join(storage),
+    };
+    Ok(keyring::Entry::new(SERVICE, &path.to_string_lossy())?)
+}
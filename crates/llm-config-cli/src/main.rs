@@ -1,10 +1,48 @@
 //! LLM Config Manager CLI
 
-use clap::{Parser, Subcommand, ValueEnum};
+mod browse;
+#[cfg(feature = "keyring")]
+mod keychain;
+mod output;
+mod prompt;
+mod remote;
+
+use chrono::{DateTime, Utc};
+use anyhow::Context;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use colored::Colorize;
-use llm_config_core::{ConfigManager, ConfigValue, Environment};
-use llm_config_crypto::{Algorithm, SecretKey};
-use std::path::PathBuf;
+use llm_config_api::{serve, PushConfig, ServerConfig, TlsConfig, DEFAULT_CACHE_SIZE};
+use llm_config_audit::{
+    AuditEvent, AuditEventType, AuditFilter, AuditStorage, BlockingAuditRecorder, ExportFormat,
+    FileAuditStorage, OverflowPolicy, DEFAULT_QUEUE_CAPACITY,
+};
+use llm_config_cache::CacheTtl;
+use llm_config_core::{
+    parse_since, Blueprint, CheckedEntry, ConfigEntry, ConfigError, ConfigManager, ConfigValue, DesiredState, DocumentFormat, EntrySigner, Environment, EnvironmentGraph,
+    K8sExportOptions, HistoryOptions, ListOptions, ListSort, MergeStrategy, MigrateOptions, MigrationTarget, Plan, PlanAction, PlannedChange, PromoteKeys, ProtectionLevel, ResolutionTrace, ResolvedValue, RetentionPolicy, SetOptions,
+    SignaturePolicy, SignatureStatus, TenantId, TenantKeyStore, ValueType, ENVIRONMENTS,
+};
+use llm_config_core::tenant::TENANT_KEYS_DIR;
+use llm_config_crypto::key_derivation::{derive_key_from_passphrase, has_passphrase};
+use llm_config_crypto::{Algorithm, SecretKey, SigningKey, VerifyingKey};
+use llm_config_devtools::security::config_values::ConfigContentScanner;
+use llm_config_devtools::security::secret::SecretScanConfig;
+use llm_config_rbac::{
+    AccessReview, AccessUse, Group, GroupRole, Permission, PolicyEnforcer, Role, RoleAssignment,
+    RoleDefinition, RoleStore,
+};
+use llm_config_security::SecretAccessLimit;
+use llm_config_sync::{GitRepository, SyncReport, Syncer};
+use llm_config_templates::Template;
+use output::{exit_code, print_raw, raw_text, use_color, Output, EXIT_FAILURE, EXIT_VALIDATION};
+use remote::RemoteClient;
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use zeroize::Zeroize;
 
 #[derive(Parser)]
 #[command(name = "llm-config")]
@@ -19,6 +57,71 @@ struct Cli {
     #[arg(short = 'k', long, env = "LLM_CONFIG_KEY")]
     encryption_key: Option<String>,
 
+    /// Derive the encryption key from a passphrase with Argon2id (prompted
+    /// for when no value is given; the salt is kept in the storage directory)
+    #[arg(
+        long,
+        env = "LLM_CONFIG_PASSPHRASE",
+        num_args = 0..=1,
+        conflicts_with = "encryption_key"
+    )]
+    passphrase: Option<Option<String>>,
+
+    /// Cipher for newly encrypted secrets (aes-256-gcm or the nonce-misuse
+    /// resistant aes-256-gcm-siv); existing secrets keep theirs
+    #[arg(long, env = "LLM_CONFIG_CIPHER", default_value = "aes-256-gcm")]
+    cipher: Algorithm,
+
+    /// Reject secrets that are not bound to their entry; see `bind-secrets`
+    #[arg(long, env = "LLM_CONFIG_REQUIRE_BOUND_SECRETS")]
+    require_bound_secrets: bool,
+
+    /// Ed25519 signing key (base64) that signs every written entry; see `keygen --signing`
+    #[arg(long, env = "LLM_CONFIG_SIGNING_KEY", hide_env_values = true)]
+    signing_key: Option<String>,
+
+    /// Identity recorded in signatures made with --signing-key
+    #[arg(long, env = "LLM_CONFIG_SIGNER", default_value = "llm-config")]
+    signer: String,
+
+    /// Trust signatures of NAME=PUBLIC_KEY (base64) when reading and
+    /// verifying entries; repeatable
+    #[arg(long = "trusted-signer", env = "LLM_CONFIG_TRUSTED_SIGNERS", value_delimiter = ',')]
+    trusted_signers: Vec<String>,
+
+    /// Reject unsigned entries on read
+    #[arg(long, env = "LLM_CONFIG_REQUIRE_SIGNATURES")]
+    require_signatures: bool,
+
+    /// Resolve overrides through base -> development -> staging -> production
+    /// until an environment graph is saved (compatibility with older stores)
+    #[arg(long, env = "LLM_CONFIG_LEGACY_OVERRIDES")]
+    legacy_overrides: bool,
+
+    /// Log and count every value read with a different type than its previous
+    /// version (`get` always warns about these)
+    #[arg(long, env = "LLM_CONFIG_STRICT_TYPES")]
+    strict_types: bool,
+
+    /// Tenant whose store to use (the encryption key then acts as the master
+    /// key wrapping the tenant's own key)
+    #[arg(long, env = "LLM_CONFIG_TENANT")]
+    tenant: Option<TenantId>,
+
+    /// Print results as json, yaml or plain text for scripts, with errors in
+    /// the same format on stderr (takes precedence over `--format`)
+    #[arg(long, value_enum, env = "LLM_CONFIG_OUTPUT")]
+    output: Option<Output>,
+
+    /// Run config commands against this API server instead of the storage
+    /// directory (e.g., https://config.internal)
+    #[arg(long, env = "LLM_CONFIG_SERVER")]
+    server: Option<String>,
+
+    /// Bearer token (API key) for --server
+    #[arg(long, env = "LLM_CONFIG_TOKEN", requires = "server", hide_env_values = true)]
+    token: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -37,9 +140,26 @@ enum Commands {
         #[arg(short, long, value_enum, default_value = "development")]
         env: Env,
 
-        /// Apply environment overrides
+        /// Apply environment overrides and fall back to parent namespaces
         #[arg(short = 'o', long)]
         with_overrides: bool,
+
+        /// Decrypt secret values (requires the encryption key)
+        #[arg(long)]
+        reveal: bool,
+
+        /// Why the secret is revealed, recorded in the server's audit log
+        /// (with --server)
+        #[arg(long, requires = "reveal")]
+        reason: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+
+        /// Print only the value, as is (secrets byte for byte with --reveal)
+        #[arg(long, conflicts_with = "format")]
+        raw: bool,
     },
 
     /// Set a configuration value
@@ -50,8 +170,18 @@ enum Commands {
         /// Configuration key
         key: String,
 
-        /// Configuration value
-        value: String,
+        /// Configuration value (prompted for without echo if omitted with --secret)
+        #[arg(required_unless_present_any = ["from_file", "stdin", "secret"])]
+        value: Option<String>,
+
+        /// Read the value from a file instead (YAML files, by extension, are
+        /// stored like the equivalent JSON)
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["value", "stdin"])]
+        from_file: Option<PathBuf>,
+
+        /// Read the value from stdin instead
+        #[arg(long, conflicts_with = "value")]
+        stdin: bool,
 
         /// Environment
         #[arg(short, long, value_enum, default_value = "development")]
@@ -64,6 +194,37 @@ enum Commands {
         /// Store as a secret (encrypted)
         #[arg(short, long)]
         secret: bool,
+
+        /// Parse the value as a duration (e.g. 5m), byte size (e.g. 512MiB),
+        /// url, string, int, float, bool or json instead of guessing its type,
+        /// rejecting invalid values
+        #[arg(long = "type", value_name = "TYPE", conflicts_with = "secret")]
+        value_type: Option<ValueType>,
+
+        /// How the value combines with inherited environments: replace or
+        /// deep-merge (object fields merged recursively)
+        #[arg(long, value_name = "STRATEGY")]
+        merge: Option<MergeStrategy>,
+
+        /// Allow replacing the value with one of a different type
+        #[arg(long)]
+        allow_type_change: bool,
+
+        /// Only write if the stored version is still this one (0: the key must not exist yet)
+        #[arg(long)]
+        expected_version: Option<u64>,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+
+        /// Description of the change, shown in history and changelogs
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Check the write and show the change and resulting version without applying it
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// List configurations in a namespace
@@ -78,6 +239,78 @@ enum Commands {
         /// Output format
         #[arg(short, long, value_enum, default_value = "table")]
         format: OutputFormat,
+
+        /// Decrypt secret values (requires the encryption key)
+        #[arg(long)]
+        reveal: bool,
+
+        /// Show at most this many configurations
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Skip this many configurations
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+
+        /// Continue after the page this token was printed with
+        #[arg(long)]
+        page_token: Option<String>,
+
+        /// Order by key, updated_at or version
+        #[arg(long, default_value = "key")]
+        sort_by: ListSort,
+
+        /// Reverse the order
+        #[arg(long)]
+        desc: bool,
+
+        /// Only list keys starting with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Only list configurations with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Show every key in a namespace with environment overrides applied
+    Resolve {
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+
+        /// Decrypt secret values (requires the encryption key)
+        #[arg(long)]
+        reveal: bool,
+    },
+
+    /// Show how a value is resolved: every environment and parent namespace
+    /// consulted, which one supplied it and how overrides were merged
+    Explain {
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Configuration key
+        key: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+
+        /// Decrypt a secret value (requires the encryption key)
+        #[arg(long)]
+        reveal: bool,
     },
 
     /// Delete a configuration
@@ -92,22 +325,104 @@ enum Commands {
         #[arg(short, long, value_enum, default_value = "development")]
         env: Env,
 
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+
         /// Skip confirmation
         #[arg(short = 'y', long)]
         yes: bool,
+
+        /// Check the delete and show the change without applying it
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Show version history
+    /// Show version history, newest first (or prune it with `history prune`)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     History {
+        #[command(subcommand)]
+        command: Option<HistoryCommands>,
+
+        /// Namespace
+        #[arg(required = true)]
+        namespace: Option<String>,
+
+        /// Configuration key
+        #[arg(required = true)]
+        key: Option<String>,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Show at most this many versions
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Only show versions older than this one
+        #[arg(long)]
+        before: Option<u64>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+
+        /// Print only the values, one per line
+        #[arg(long, conflicts_with = "format")]
+        raw: bool,
+    },
+
+    /// Add or remove tags on a configuration
+    Tag {
+        #[command(subcommand)]
+        command: TagCommands,
+    },
+
+    /// Set (or clear, with "") the description of a configuration
+    Describe {
         /// Namespace
         namespace: String,
 
         /// Configuration key
         key: String,
 
+        /// Description
+        description: String,
+
         /// Environment
         #[arg(short, long, value_enum, default_value = "development")]
         env: Env,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+    },
+
+    /// Show release notes for a namespace
+    Changelog {
+        /// Namespace
+        namespace: String,
+
+        /// Start of the window: an age (30m, 12h, 7d, 2w), a date or an RFC 3339 timestamp
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Only include changes in this environment
+        #[arg(short, long, value_enum)]
+        env: Option<Env>,
+
+        /// Output format (table prints Markdown)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
 
     /// Rollback to a specific version
@@ -124,296 +439,3815 @@ enum Commands {
         /// Environment
         #[arg(short, long, value_enum, default_value = "development")]
         env: Env,
-    },
 
-    /// Export all configurations
-    Export {
-        /// Export directory path
-        path: PathBuf,
-    },
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
 
-    /// Generate a new encryption key
-    Keygen,
-}
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-enum Env {
-    Base,
-    Dev,
-    Development,
-    Staging,
-    Stage,
-    Prod,
-    Production,
-    Edge,
-}
+        /// Why the rollback is made, recorded in the version history
+        #[arg(short, long)]
+        reason: Option<String>,
 
-impl From<Env> for Environment {
-    fn from(env: Env) -> Self {
-        match env {
-            Env::Base => Environment::Base,
-            Env::Dev | Env::Development => Environment::Development,
-            Env::Staging | Env::Stage => Environment::Staging,
-            Env::Prod | Env::Production => Environment::Production,
-            Env::Edge => Environment::Edge,
-        }
-    }
-}
+        /// Check the rollback and show the change and resulting version without applying it
+        #[arg(long)]
+        dry_run: bool,
+    },
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-enum OutputFormat {
-    Table,
-    Json,
-    Yaml,
-}
+    /// Copy configurations of a namespace from one environment to another
+    Promote {
+        /// Namespace
+        namespace: String,
 
-fn main() {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .init();
+        /// Environment to copy from
+        #[arg(long, value_enum)]
+        from: Env,
 
-    let cli = Cli::parse();
+        /// Environment to copy to
+        #[arg(long, value_enum)]
+        to: Env,
 
-    if let Err(e) = run(cli) {
-        eprintln!("{} {}", "Error:".red().bold(), e);
-        std::process::exit(1);
-    }
-}
+        /// Key to promote (repeatable; every key of the namespace if omitted)
+        #[arg(short, long = "key", value_name = "KEY")]
+        keys: Vec<String>,
 
-fn run(cli: Cli) -> anyhow::Result<()> {
-    // Create manager
-    let mut manager = ConfigManager::new(&cli.storage)?;
+        /// Show the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
 
-    // Set encryption key if provided
-    let has_key = cli.encryption_key.is_some();
-    if let Some(key_str) = cli.encryption_key {
-        let key = SecretKey::from_base64(Algorithm::Aes256Gcm, &key_str)?;
-        manager = manager.with_encryption_key(key);
-    }
+        /// Output format (json and yaml print the plan)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
 
-    match cli.command {
-        Commands::Get {
-            namespace,
-            key,
-            env,
-            with_overrides,
-        } => {
-            let env: Environment = env.into();
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
 
-            if with_overrides {
-                if let Some(value) = manager.get_with_overrides(&namespace, &key, env)? {
-                    println!("{}", format_value(&value));
-                } else {
-                    println!("{}", "Configuration not found".yellow());
-                }
-            } else {
-                if let Some(entry) = manager.get(&namespace, &key, env)? {
-                    println!("{}", "Configuration:".green().bold());
-                    println!("  Namespace: {}", entry.namespace);
-                    println!("  Key: {}", entry.key);
-                    println!("  Environment: {}", entry.environment);
-                    println!("  Value: {}", format_value(&entry.value));
-                    println!("  Version: {}", entry.version);
-                    println!("  Updated: {}", entry.metadata.updated_at);
-                    println!("  Updated by: {}", entry.metadata.updated_by);
-                } else {
-                    println!("{}", "Configuration not found".yellow());
-                }
-            }
-        }
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
 
-        Commands::Set {
-            namespace,
-            key,
-            value,
-            env,
-            user,
-            secret,
-        } => {
-            let env: Environment = env.into();
+        /// Description recorded in the version history (default "Promoted from <from>")
+        #[arg(short, long)]
+        message: Option<String>,
+    },
 
-            let entry = if secret {
-                if !has_key {
-                    anyhow::bail!("Encryption key required for secrets. Set --encryption-key or LLM_CONFIG_KEY environment variable.");
-                }
-                manager.set_secret(&namespace, &key, value.as_bytes(), env, &user)?
-            } else {
-                let config_value = parse_value(&value)?;
-                manager.set(&namespace, &key, config_value, env, &user)?
-            };
+    /// Restore a namespace to its state at a point in time, as new versions
+    RestoreAt {
+        /// Namespace
+        namespace: String,
 
-            println!("{}", "Configuration saved successfully!".green().bold());
-            println!("  Version: {}", entry.version);
-            println!("  ID: {}", entry.id);
-        }
+        /// Environment
+        #[arg(short, long, value_enum)]
+        env: Env,
 
-        Commands::List {
-            namespace,
-            env,
-            format,
-        } => {
-            let env: Environment = env.into();
-            let entries = manager.list(&namespace, env)?;
+        /// Point in time: an age (30m, 12h, 1d), a date or an RFC 3339 timestamp
+        #[arg(long)]
+        at: String,
 
-            if entries.is_empty() {
-                println!("{}", "No configurations found".yellow());
-                return Ok(());
-            }
+        /// Show the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
 
-            match format {
-                OutputFormat::Table => {
-                    println!("{}", format!("Configurations in {} ({})", namespace, env).green().bold());
-                    println!();
-                    for entry in entries {
-                        println!("  {} {} = {}", "•".blue(), entry.key.bold(), format_value(&entry.value));
-                        println!("    Version: {} | Updated: {} by {}",
-                            entry.version,
-                            entry.metadata.updated_at.format("%Y-%m-%d %H:%M:%S"),
-                            entry.metadata.updated_by
-                        );
-                        println!();
-                    }
-                }
-                OutputFormat::Json => {
-                    println!("{}", serde_json::to_string_pretty(&entries)?);
-                }
-                OutputFormat::Yaml => {
-                    println!("{}", serde_yaml::to_string(&entries)?);
-                }
-            }
-        }
+        /// Output format (json and yaml print the plan)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
 
-        Commands::Delete {
-            namespace,
-            key,
-            env,
-            yes,
-        } => {
-            let env: Environment = env.into();
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
 
-            if !yes {
-                print!("Delete configuration {}:{} in {} environment? [y/N] ", namespace, key, env);
-                use std::io::{self, Write};
-                io::stdout().flush()?;
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
 
-                let mut input = String::new();
-                io::stdin().read_line(&mut input)?;
+        /// Description recorded in the version history (default "Restored to state at <time>")
+        #[arg(short, long)]
+        message: Option<String>,
+    },
 
-                if !input.trim().eq_ignore_ascii_case("y") {
-                    println!("Cancelled.");
-                    return Ok(());
-                }
+    /// Export a namespace as a TOML, YAML, JSON or CSV document
+    ///
+    /// Values are resolved with overrides applied; secrets are left out
+    /// unless --reveal-secrets is given. With --dir, every stored entry is
+    /// written as a JSON file into a directory instead.
+    Export {
+        /// Namespace (e.g., "org/project/service")
+        #[arg(required_unless_present = "dir")]
+        namespace: Option<String>,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Document format
+        #[arg(short, long, value_enum, default_value = "toml")]
+        format: DocFormat,
+
+        /// Include decrypted secrets
+        #[arg(long)]
+        reveal_secrets: bool,
+
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Write all stored entries of every namespace as JSON files into this directory
+        #[arg(long, conflicts_with = "namespace")]
+        dir: Option<PathBuf>,
+    },
+
+    /// Import a TOML, YAML, JSON or CSV document into a namespace
+    ///
+    /// Top-level keys become config keys and nested tables object values;
+    /// keys not in the document are left alone. Credential-like keys are
+    /// stored as secrets, as are CSV rows of type `secret`.
+    Import {
+        /// Document to import
+        file: PathBuf,
+
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Document format (defaults to the file extension)
+        #[arg(short, long, value_enum)]
+        format: Option<DocFormat>,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+
+        /// Description of the change, shown in history and changelogs
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+
+    /// Print a namespace as a .env file, with overrides applied and secrets decrypted
+    ExportEnv {
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+    },
+
+    /// Print a namespace as `export NAME=value` lines for a shell (eval "$(llm-config env ...)")
+    ///
+    /// Overrides are applied and secrets decrypted. Keys become upper-case
+    /// variable names with `.`, `-` and `/` replaced by `_`.
+    Env {
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+    },
+
+    /// Run a command with a namespace's configuration in its environment
+    ///
+    /// Variables are named as by `env`. Decrypted values are wiped from this
+    /// process once the command has started; its exit code is passed on.
+    Exec {
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Command and its arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Import KEY=VALUE pairs from a .env file (credential-like keys are stored as secrets)
+    ImportEnv {
+        /// .env file to import
+        file: PathBuf,
+
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+
+        /// Description of the change, shown in history and changelogs
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+
+    /// Manage secret values
+    Secret {
+        #[command(subcommand)]
+        command: SecretCommands,
+    },
+
+    /// Generate a new encryption key
+    Keygen {
+        /// Generate an Ed25519 signing key instead (see --signing-key)
+        #[arg(long)]
+        signing: bool,
+    },
+
+    /// Keep the encryption key in the OS keychain
+    #[cfg(feature = "keyring")]
+    Key {
+        #[command(subcommand)]
+        command: keychain::KeyCommands,
+    },
+
+    /// Manage per-environment write protection
+    Protection {
+        #[command(subcommand)]
+        command: ProtectionCommands,
+    },
+
+    /// Manage versioned prompt templates
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommands,
+    },
+
+    /// Manage blueprints: parameterized sets of entries for new namespaces
+    Blueprint {
+        #[command(subcommand)]
+        command: BlueprintCommands,
+    },
+
+    /// Manage environment inheritance
+    Environments {
+        #[command(subcommand)]
+        command: EnvironmentCommands,
+    },
+
+    /// Export configurations as Kubernetes manifests
+    K8s {
+        #[command(subcommand)]
+        command: K8sCommands,
+    },
+
+    /// Clone or rename namespaces
+    Ns {
+        #[command(subcommand)]
+        command: NsCommands,
+    },
+
+    /// Compare stored configs with a desired-state file
+    Drift {
+        #[command(subcommand)]
+        command: DriftCommands,
+    },
+
+    /// Apply config manifests from a Git repository (GitOps)
+    Sync {
+        /// Repository URL
+        #[arg(long)]
+        repo: String,
+
+        /// Branch to sync
+        #[arg(long, default_value = llm_config_sync::git::DEFAULT_BRANCH)]
+        branch: String,
+
+        /// Directory of the repository holding the manifests
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Local checkout of the repository (defaults to <storage>/sync)
+        #[arg(long)]
+        checkout_dir: Option<PathBuf>,
+
+        /// Keep syncing, every SECS seconds (syncs once by default)
+        #[arg(long, value_name = "SECS")]
+        interval: Option<u64>,
+
+        /// Delete keys of declared namespaces that no manifest lists
+        #[arg(long)]
+        prune: bool,
+
+        /// User synced changes are attributed to
+        #[arg(short, long, default_value = llm_config_sync::SYNC_USER)]
+        user: String,
+    },
+
+    /// Manage custom roles
+    Role {
+        #[command(subcommand)]
+        command: RoleCommands,
+    },
+
+    /// Manage groups and their mapping to external identity groups
+    Group {
+        #[command(subcommand)]
+        command: GroupCommands,
+    },
+
+    /// Inspect the audit log
+    Audit {
+        /// Audit log directory (defaults to <storage>/audit)
+        #[arg(long)]
+        audit_dir: Option<PathBuf>,
+
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+
+    /// Report who has access to what, and what they used, over a period
+    AccessReview {
+        /// Period to review: an age (30m, 12h, 7d, 2w), a date or an RFC 3339 timestamp
+        #[arg(long, default_value = "90d")]
+        period: String,
+
+        /// Report format
+        #[arg(short, long, value_enum, default_value = "markdown")]
+        format: ReviewFormat,
+
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Audit log directory (defaults to <storage>/audit)
+        #[arg(long)]
+        audit_dir: Option<PathBuf>,
+    },
+
+    /// Run the REST API server
+    Serve(Box<ServeArgs>),
+
+    /// Browse namespaces, environments, and values interactively
+    Browse,
+
+    /// Generate shell completions
+    Completions {
+        /// Target shell
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Check consistency of the index, config files, and version history
+    Fsck {
+        /// Repair inconsistencies where possible
+        #[arg(long)]
+        repair: bool,
+    },
+
+    /// Save a fresh index snapshot and tidy up leftover and fragmented storage files
+    Compact,
+
+    /// Reject all writes during migrations and backups
+    ReadOnly {
+        #[command(subcommand)]
+        command: ReadOnlyCommands,
+    },
+
+    /// Store identical large values once (content-addressed blobs)
+    Dedup {
+        #[command(subcommand)]
+        command: DedupCommands,
+    },
+
+    /// Copy every entry, with version history and settings, to another store
+    /// and verify the copy
+    ///
+    /// Stores are `file:PATH` or, when built with `--features consul`,
+    /// `consul://HOST:PORT/PREFIX` (entries only, authenticated with
+    /// CONSUL_HTTP_TOKEN). The source is read-only while copying. Entries the
+    /// target already holds are skipped, so an interrupted migration is
+    /// resumed by running it again.
+    Migrate {
+        /// Store to copy from (default: the storage directory)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Store to copy to
+        #[arg(long)]
+        to: String,
+
+        /// Replace entries the target holds with a different value
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Leave the source writable while copying
+        #[arg(long, conflicts_with = "keep_read_only")]
+        keep_writable: bool,
+
+        /// Leave the source read-only after a verified migration, for
+        /// switching clients over to the target
+        #[arg(long)]
+        keep_read_only: bool,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+    },
+
+    /// Find credentials stored as plain values instead of secrets (fails if any are found)
+    ScanValues {
+        /// Only scan this namespace
+        #[arg(long)]
+        namespace: Option<String>,
+
+        /// Only scan this environment
+        #[arg(short, long, value_enum)]
+        env: Option<Env>,
+
+        /// Do not report high-entropy strings, only values matching secret patterns
+        #[arg(long)]
+        no_entropy: bool,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Verify the signatures of every entry in a namespace (fails on unsigned
+    /// or invalid entries)
+    Verify {
+        /// Namespace to verify
+        namespace: String,
+
+        /// Only fail on invalid signatures, not on unsigned entries
+        #[arg(long)]
+        allow_unsigned: bool,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Re-encrypt secrets written by older versions bound to their entry, so
+    /// they cannot be copied to another key undetected
+    BindSecrets {
+        /// Only bind the secrets of this namespace
+        #[arg(short, long)]
+        namespace: Option<String>,
+
+        /// Show the secrets that would be bound without changing them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+    },
+}
+
+/// Options of `serve`
+#[derive(Args)]
+struct ServeArgs {
+    /// Address to bind the API server to
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: SocketAddr,
+
+    /// Serve Prometheus metrics and health checks on a separate port
+    /// (metrics are served on the API port by default)
+    #[arg(long)]
+    metrics_port: Option<u16>,
+
+    /// Also push metrics to this Prometheus Pushgateway (e.g.
+    /// http://pushgateway:9091), for deployments that cannot be scraped
+    #[arg(long)]
+    metrics_push_url: Option<String>,
+
+    /// Job label of pushed metrics
+    #[arg(long, default_value = "llm-config-server")]
+    metrics_push_job: String,
+
+    /// Grouping label of pushed metrics, as NAME=VALUE (repeatable), e.g.
+    /// instance=api-1 so replicas do not replace each other's metrics
+    #[arg(long = "metrics-push-label")]
+    metrics_push_labels: Vec<String>,
+
+    /// Seconds between metric pushes
+    #[arg(long, default_value_t = 15, value_parser = clap::value_parser!(u64).range(1..))]
+    metrics_push_interval: u64,
+
+    /// Audit log directory
+    #[arg(long)]
+    audit_dir: Option<PathBuf>,
+
+    /// Audit events queued for storage before --audit-overflow applies
+    #[arg(long, default_value_t = DEFAULT_QUEUE_CAPACITY)]
+    audit_queue_capacity: usize,
+
+    /// What to do with audit events when the queue is full: block, drop
+    /// (counted in metrics) or spill (to a journal recovered on restart)
+    #[arg(long, default_value = "block")]
+    audit_overflow: OverflowPolicy,
+
+    /// Enforce RBAC role assignments
+    #[arg(long)]
+    enforce_rbac: bool,
+
+    /// Disable CORS
+    #[arg(long)]
+    no_cors: bool,
+
+    /// Check storage consistency before serving
+    #[arg(long)]
+    check_consistency: bool,
+
+    /// Webhook endpoints notified about config changes (JSON file)
+    #[arg(long)]
+    webhooks: Option<PathBuf>,
+
+    /// Serve Swagger UI for the OpenAPI document at /api/v1/docs
+    #[arg(long)]
+    swagger_ui: bool,
+
+    /// Reverse proxy address or CIDR range trusted to report the client
+    /// IP in Forwarded / X-Forwarded-For, the client certificate in
+    /// x-forwarded-client-cert and the user in x-user-id / x-user-groups
+    /// (repeatable)
+    #[arg(long = "trusted-proxy", value_delimiter = ',')]
+    trusted_proxies: Vec<String>,
+
+    /// Rate limit quotas of certificate principals, by principal and role (JSON file)
+    #[arg(long)]
+    rate_limits: Option<PathBuf>,
+
+    /// Serve HTTPS with this PEM certificate chain (reloaded when it changes)
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key of the TLS certificate
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Require client certificates issued by this PEM CA bundle (mTLS);
+    /// their SPIFFE ID or common name becomes the caller's principal
+    #[arg(long, requires = "tls_cert")]
+    tls_client_ca: Option<PathBuf>,
+
+    /// Seconds in-flight requests get to finish after SIGTERM before the
+    /// server exits
+    #[arg(long, default_value_t = 30)]
+    shutdown_timeout: u64,
+
+    /// When the encryption key was created (RFC 3339), reported as its
+    /// age by the crypto health check
+    #[arg(long, env = "LLM_CONFIG_KEY_CREATED_AT")]
+    key_created_at: Option<DateTime<Utc>>,
+
+    /// Key age in days after which the crypto health check reports that
+    /// the key is due for rotation
+    #[arg(long, default_value_t = 90)]
+    key_max_age_days: i64,
+
+    /// Scan stored values for plaintext credentials every this many
+    /// seconds, recording findings as security events in the audit log
+    #[arg(long)]
+    scan_values_interval: Option<u64>,
+
+    /// Apply the store's retention settings (retention.toml) every this
+    /// many seconds, pruning version history and the audit log
+    #[arg(long)]
+    retention_interval: Option<u64>,
+
+    /// Reveal at most this many secrets per minute to each caller
+    /// (verified principal, or client IP); reveals over the limit are
+    /// refused and logged as Critical security events
+    #[arg(long)]
+    secret_reads_per_minute: Option<u32>,
+
+    /// Secrets a caller may reveal in a burst before the per-minute limit
+    /// applies (defaults to the per-minute limit)
+    #[arg(long, requires = "secret_reads_per_minute")]
+    secret_read_burst: Option<u32>,
+
+    /// Allow callers with the reveal permission to decrypt secrets
+    /// (refused with 403 otherwise)
+    #[arg(long)]
+    allow_secret_reveal: bool,
+
+    /// Require a reason for every request that reveals secrets
+    #[arg(long)]
+    require_secret_reason: bool,
+
+    /// Serve config reads from a cache whose L2 tier is kept in this
+    /// directory (emptied at startup)
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Entries held in memory by the cache
+    #[arg(long, default_value_t = DEFAULT_CACHE_SIZE)]
+    cache_size: usize,
+
+    /// Bytes the configs held in memory by the cache may take up,
+    /// measured by their serialized size
+    #[arg(long, requires = "cache_dir")]
+    cache_max_bytes: Option<usize>,
+
+    /// Load the configs of this namespace into the cache before serving,
+    /// and again whenever the cache is cleared (repeatable or
+    /// comma-separated)
+    #[arg(long = "preload", value_delimiter = ',', requires = "cache_dir")]
+    preload: Vec<String>,
+
+    /// Only preload configs of this environment (every environment by default)
+    #[arg(long, value_enum, requires = "cache_dir")]
+    preload_env: Option<Env>,
+
+    /// Refresh cached configs from storage after this many seconds, for
+    /// writes the server does not see
+    #[arg(long, requires = "cache_dir")]
+    cache_ttl: Option<u64>,
+
+    /// Keep serving a cached config for up to this many seconds past its
+    /// TTL while it is refreshed in the background
+    #[arg(long, requires = "cache_ttl")]
+    cache_stale_while_revalidate: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// Delete old versions of every config (the latest version is always kept)
+    #[command(group(
+        clap::ArgGroup::new("policy")
+            .required(true)
+            .multiple(true)
+            .args(["keep_last", "keep_days"])
+    ))]
+    Prune {
+        /// Keep this many most recent versions of each config
+        #[arg(long)]
+        keep_last: Option<usize>,
+
+        /// Keep versions created within this many days
+        #[arg(long)]
+        keep_days: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Export audit events
+    Export {
+        /// Only events at or after this time (RFC 3339)
+        #[arg(long)]
+        from: Option<DateTime<Utc>>,
+
+        /// Only events at or before this time (RFC 3339)
+        #[arg(long)]
+        to: Option<DateTime<Utc>>,
+
+        /// Only events triggered by this user
+        #[arg(long)]
+        user: Option<String>,
+
+        /// Only events of this type (e.g., "config_updated")
+        #[arg(long)]
+        event_type: Option<String>,
+
+        /// Export format
+        #[arg(short, long, value_enum, default_value = "jsonl")]
+        format: AuditFormat,
+
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// List configs nobody has read recently
+    Unused {
+        /// Not read within this period (e.g., "90d") or since a date or RFC 3339 timestamp
+        #[arg(long, default_value = "90d")]
+        older_than: String,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommands {
+    /// Add tags to a configuration
+    Add {
+        /// Namespace
+        namespace: String,
+
+        /// Configuration key
+        key: String,
+
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+    },
+
+    /// Remove tags from a configuration
+    Remove {
+        /// Namespace
+        namespace: String,
+
+        /// Configuration key
+        key: String,
+
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProtectionCommands {
+    /// Show the protection level of every environment
+    Show,
+
+    /// Set the protection level of an environment
+    Set {
+        /// Environment
+        #[arg(value_enum)]
+        env: Env,
+
+        /// Protection level
+        #[arg(value_enum)]
+        level: Level,
+    },
+
+    /// Allow a principal to write to admin-only environments
+    AddAdmin {
+        /// Principal (e.g., "deploy-bot")
+        user: String,
+    },
+
+    /// Revoke a principal's admin-only write access
+    RemoveAdmin {
+        /// Principal
+        user: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretCommands {
+    /// Replace a secret with a new value, prompted for without echo; the old
+    /// value stays in the version history
+    Rotate {
+        /// Namespace
+        namespace: String,
+
+        /// Configuration key
+        key: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+
+        /// Description of the change (default: the version it replaces)
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReadOnlyCommands {
+    /// Show whether the store is read-only
+    Status,
+
+    /// Make the store read-only, including for running servers
+    Enable {
+        /// Why writes are stopped (e.g., "nightly backup")
+        #[arg(short, long)]
+        reason: Option<String>,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+    },
+
+    /// Accept writes again
+    Disable,
+}
+
+#[derive(Subcommand)]
+enum DedupCommands {
+    /// Show whether values are deduplicated and how much space it saves
+    Status,
+
+    /// Store values from a size on as blobs (run `compact` to move existing values)
+    Enable {
+        /// Smallest value to deduplicate (e.g., 4096, 4KiB)
+        #[arg(long, default_value = "1KiB", value_parser = parse_min_size)]
+        min_size: usize,
+    },
+
+    /// Store new values inline again
+    Disable,
+}
+
+#[derive(Subcommand)]
+enum TemplateCommands {
+    /// Save a new version of a template
+    Put {
+        /// Template name
+        name: String,
+
+        /// Template content with {{variable}} placeholders (read from --file if omitted)
+        content: Option<String>,
+
+        /// Read the template content from a file
+        #[arg(long, conflicts_with = "content")]
+        file: Option<PathBuf>,
+
+        /// Template description
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// Default value for a variable as name=value; repeatable
+        #[arg(long = "default", value_parser = parse_var)]
+        defaults: Vec<(String, String)>,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+
+        /// Description of the change, shown in history and changelogs
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+
+    /// Show the template that applies to an environment
+    Show {
+        /// Template name
+        name: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+    },
+
+    /// List the templates that apply to an environment
+    List {
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+    },
+
+    /// Render a template with variables (recorded in the audit log)
+    Render {
+        /// Template name
+        name: String,
+
+        /// Variable as name=value; repeatable
+        #[arg(long = "var", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// User the render is attributed to
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BlueprintCommands {
+    /// Save a new version of a blueprint from a YAML, JSON or TOML file
+    Put {
+        /// Blueprint file
+        file: PathBuf,
+
+        /// File format (defaults to the file extension)
+        #[arg(short, long, value_enum)]
+        format: Option<DocFormat>,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Description of the change, shown in history and changelogs
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+
+    /// Show a blueprint
+    Show {
+        /// Blueprint name
+        name: String,
+    },
+
+    /// List blueprints
+    List,
+
+    /// Create a namespace's entries from a blueprint
+    Apply {
+        /// Blueprint name
+        name: String,
+
+        /// Namespace to create the entries in (e.g., "org/project/service")
+        namespace: String,
+
+        /// Variable as name=value; repeatable
+        #[arg(long = "var", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+
+        /// Description of the change, shown in history and changelogs
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum EnvironmentCommands {
+    /// Show the parent and override chain of every environment
+    Show,
+
+    /// Set the environment an environment inherits values from
+    SetParent {
+        /// Environment
+        #[arg(value_enum)]
+        env: Env,
+
+        /// Parent environment
+        #[arg(value_enum)]
+        parent: Env,
+    },
+
+    /// Make every environment inherit from base again
+    Reset {
+        /// Save the legacy chain (base -> development -> staging -> production) instead
+        #[arg(long)]
+        legacy: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum K8sCommands {
+    /// Render a namespace into a ConfigMap and, if it has secrets, a Secret
+    Export {
+        /// Namespace (e.g., "org/project/service")
+        namespace: String,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Manifest name (derived from the namespace by default)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Kubernetes namespace to create the manifests in
+        #[arg(long)]
+        k8s_namespace: Option<String>,
+
+        /// Convert the Secret into a SealedSecret with kubeseal
+        #[arg(long)]
+        sealed: bool,
+
+        /// Sealed-secrets controller certificate passed to kubeseal
+        #[arg(long, requires = "sealed")]
+        cert: Option<PathBuf>,
+
+        /// Write the manifests to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum NsCommands {
+    /// Copy a namespace, with its version history, to a new namespace
+    Clone {
+        /// Namespace to copy
+        src: String,
+
+        /// New namespace (must not have configs yet)
+        dst: String,
+
+        /// Environment to copy (repeatable; every environment if omitted)
+        #[arg(short, long = "env", value_enum, value_name = "ENV")]
+        envs: Vec<Env>,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+    },
+
+    /// Move a namespace, with its version history, to a new name
+    Rename {
+        /// Namespace to move
+        src: String,
+
+        /// New namespace (must not have configs yet)
+        dst: String,
+
+        /// User performing the operation
+        #[arg(short, long, default_value = "cli-user")]
+        user: String,
+
+        /// Reviewer of the change (required in review-required environments)
+        #[arg(long)]
+        reviewed_by: Option<String>,
+    },
+
+    /// Show the parent and lookup order of every namespace that has a parent
+    Parents,
+
+    /// Set the namespace a namespace inherits missing keys from
+    SetParent {
+        /// Namespace (e.g., "org/app/service")
+        namespace: String,
+
+        /// Parent namespace (e.g., "org/app")
+        parent: String,
+    },
+
+    /// Stop a namespace from inheriting keys from its parent
+    ClearParent {
+        /// Namespace
+        namespace: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DriftCommands {
+    /// Report keys missing from, unexpected in, or differing from the
+    /// desired state (exits with an error if there is any drift)
+    Check {
+        /// Desired-state file (YAML or JSON)
+        file: PathBuf,
+
+        /// Environment
+        #[arg(short, long, value_enum, default_value = "development")]
+        env: Env,
+
+        /// Output format (json and yaml print the reconciliation plan)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum RoleCommands {
+    /// Define (or replace) a custom role
+    Define {
+        /// Role name (e.g., "prompt-engineer")
+        name: String,
+
+        /// Granted permission as resource:action (e.g., "config:update"); repeatable
+        #[arg(short, long = "permission", required = true)]
+        permissions: Vec<Permission>,
+
+        /// Namespace pattern the role is limited to (e.g., "*/prompts/*"); repeatable
+        #[arg(short, long = "namespace")]
+        namespaces: Vec<String>,
+
+        /// Role description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+
+    /// List custom roles
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Delete a custom role
+    Delete {
+        /// Role name
+        name: String,
+    },
+
+    /// Assign a role to a user
+    Assign {
+        /// User (principal) receiving the role
+        user: String,
+
+        /// Predefined role (admin, editor, viewer, auditor) or custom role name
+        role: Role,
+
+        /// Namespace pattern the assignment is limited to (e.g., "team-a/*")
+        #[arg(long)]
+        scope: Option<String>,
+    },
+
+    /// Revoke a role assignment from a user
+    Revoke {
+        /// User (principal) holding the role
+        user: String,
+
+        /// Assigned role
+        role: Role,
+
+        /// Namespace pattern of the assignment
+        #[arg(long)]
+        scope: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GroupCommands {
+    /// Define (or replace) a group, keeping the roles assigned to it
+    Define {
+        /// Group name (e.g., "ml-platform")
+        name: String,
+
+        /// User belonging to the group; repeatable
+        #[arg(short, long = "member")]
+        members: Vec<String>,
+
+        /// External group (e.g., from an OIDC groups claim) whose users belong to the group; repeatable
+        #[arg(short, long = "external-group")]
+        external_groups: Vec<String>,
+
+        /// Group description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+
+    /// List groups
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Delete a group
+    Delete {
+        /// Group name
+        name: String,
+    },
+
+    /// Assign a role to a group
+    Assign {
+        /// Group receiving the role
+        group: String,
+
+        /// Predefined role (admin, editor, viewer, auditor) or custom role name
+        role: Role,
+
+        /// Namespace pattern the assignment is limited to (e.g., "team-a/*")
+        #[arg(long)]
+        scope: Option<String>,
+    },
+
+    /// Revoke a role from a group
+    Revoke {
+        /// Group holding the role
+        group: String,
+
+        /// Assigned role
+        role: Role,
+
+        /// Namespace pattern of the assignment
+        #[arg(long)]
+        scope: Option<String>,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum Env {
+    Base,
+    Dev,
+    Development,
+    Staging,
+    Stage,
+    Prod,
+    Production,
+    Edge,
+}
+
+impl From<Env> for Environment {
+    fn from(env: Env) -> Self {
+        match env {
+            Env::Base => Environment::Base,
+            Env::Dev | Env::Development => Environment::Development,
+            Env::Staging | Env::Stage => Environment::Staging,
+            Env::Prod | Env::Production => Environment::Production,
+            Env::Edge => Environment::Edge,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Level {
+    Open,
+    ReviewRequired,
+    Frozen,
+    AdminOnly,
+}
+
+impl From<Level> for ProtectionLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Open => ProtectionLevel::Open,
+            Level::ReviewRequired => ProtectionLevel::ReviewRequired,
+            Level::Frozen => ProtectionLevel::Frozen,
+            Level::AdminOnly => ProtectionLevel::AdminOnly,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+    Yaml,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum AuditFormat {
+    Jsonl,
+    Csv,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ReviewFormat {
+    Markdown,
+    Csv,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum DocFormat {
+    Toml,
+    Yaml,
+    Json,
+    Csv,
+}
+
+impl From<DocFormat> for DocumentFormat {
+    fn from(format: DocFormat) -> Self {
+        match format {
+            DocFormat::Toml => DocumentFormat::Toml,
+            DocFormat::Yaml => DocumentFormat::Yaml,
+            DocFormat::Json => DocumentFormat::Json,
+            DocFormat::Csv => DocumentFormat::Csv,
+        }
+    }
+}
+
+impl From<AuditFormat> for ExportFormat {
+    fn from(format: AuditFormat) -> Self {
+        match format {
+            AuditFormat::Jsonl => ExportFormat::Jsonl,
+            AuditFormat::Csv => ExportFormat::Csv,
+        }
+    }
+}
+
+fn main() {
+    // Initialize tracing
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive(tracing::Level::INFO.into()),
+        )
+        .init();
+
+    // Usage errors are validation errors; clap's own exit code 2 means not found here
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) if e.use_stderr() => {
+            let _ = e.print();
+            std::process::exit(EXIT_VALIDATION);
+        }
+        Err(e) => e.exit(),
+    };
+
+    let output = cli.output;
+    let no_color = std::env::var_os("NO_COLOR");
+    if output.is_some() || !use_color(no_color.as_deref(), std::io::stdout().is_terminal()) {
+        colored::control::set_override(false);
+    }
+
+    if let Err(e) = run(cli) {
+        match output {
+            Some(output) => output.print_error(&e),
+            None => eprintln!("{} {}", "Error:".red().bold(), e),
+        }
+        std::process::exit(exit_code(&e));
+    }
+}
+
+/// `--format` of a subcommand, unless `--output` overrides it
+fn output_format(output: Option<Output>, format: OutputFormat) -> OutputFormat {
+    match output {
+        Some(Output::Json) => OutputFormat::Json,
+        Some(Output::Yaml) => OutputFormat::Yaml,
+        Some(Output::Plain) => OutputFormat::Table,
+        None => format,
+    }
+}
+
+/// `--output` of `get` and `history`, or their `--format` if it is not set
+fn document_output(output: Option<Output>, format: OutputFormat) -> Option<Output> {
+    match output_format(output, format) {
+        OutputFormat::Json => Some(Output::Json),
+        OutputFormat::Yaml => Some(Output::Yaml),
+        OutputFormat::Table => output,
+    }
+}
+
+/// Ask a yes/no question on the terminal (default no)
+fn confirm(question: &str) -> std::io::Result<bool> {
+    use std::io::{self, Write};
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Print one line of a plan: `+ key: value`, `~ key: old -> new` or `- key: value`
+fn print_change(change: &PlannedChange) {
+    let render = |value: &Option<serde_json::Value>| value.as_ref().map(|v| v.to_string()).unwrap_or_default();
+    match change.action {
+        PlanAction::Update => println!(
+            "  {} {}: {} -> {}",
+            "~".yellow(),
+            change.key.bold(),
+            render(&change.old_value),
+            render(&change.new_value)
+        ),
+        PlanAction::Delete => println!("  {} {}: {}", "-".red(), change.key.bold(), render(&change.old_value)),
+        PlanAction::Create => println!("  {} {}: {}", "+".green(), change.key.bold(), render(&change.new_value)),
+    }
+}
+
+/// Print what a write made with `--dry-run` would change
+///
+/// `result` is the entry the write would store (`None` for deletes).
+fn print_dry_run(
+    output: Option<Output>,
+    operation: &str,
+    current: Option<&ConfigEntry>,
+    result: Option<&ConfigEntry>,
+) -> anyhow::Result<()> {
+    let plan = Plan::new(operation);
+    let plan = match PlannedChange::between(current, result) {
+        Some(change) => plan.with_change(change),
+        None => plan,
+    };
+    show_dry_run(output, result.map(|entry| entry.version), &plan)
+}
+
+/// Print the plan and resulting version of a dry run
+fn show_dry_run(output: Option<Output>, version: Option<u64>, plan: &Plan) -> anyhow::Result<()> {
+    match output {
+        Some(output) => {
+            let dry_run = serde_json::json!({ "version": version, "plan": plan });
+            output.print(&dry_run, || version.map(|v| v.to_string()).unwrap_or_default())?;
+        }
+        None => {
+            println!("{}", "Dry run: nothing was written".yellow().bold());
+            plan.changes.iter().for_each(print_change);
+            if let Some(version) = version {
+                println!("  Version: {}", version);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print a resolution trace
+fn show_trace(format: OutputFormat, trace: &ResolutionTrace) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(trace)?),
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(trace)?),
+        OutputFormat::Table => {
+            let envs: Vec<String> = trace.environments.iter().map(ToString::to_string).collect();
+            println!(
+                "{}",
+                format!("Resolution of {}:{} ({})", trace.namespace, trace.key, trace.environment).green().bold()
+            );
+            println!("  Namespaces:   {}", trace.namespaces.join(" -> "));
+            println!("  Environments: {}", envs.join(" -> "));
+            println!();
+            for step in &trace.steps {
+                let found = match (step.version, step.merge) {
+                    (Some(version), Some(merge)) => format!("v{} ({})", version, merge).green(),
+                    _ => "not set".dimmed(),
+                };
+                println!("  {} {} {}: {}", "•".blue(), step.namespace, step.environment, found);
+            }
+            println!();
+            match (&trace.value, &trace.source) {
+                (Some(value), Some(source)) => {
+                    println!("  {} {}", "Value:".bold(), format_value(value));
+                    println!(
+                        "  {} {} {} v{}",
+                        "From:".bold(),
+                        source.namespace,
+                        source.environment,
+                        source.version
+                    );
+                    if trace.secret {
+                        let state = if trace.decrypted { "decrypted" } else { "encrypted" };
+                        println!("  {} {}", "Secret:".bold(), state);
+                    }
+                }
+                _ => println!("{}", "Not defined in any consulted environment".yellow()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Exit code to pass on for a child process (128 + signal number if it was killed)
+fn exit_status_code(status: std::process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+    status.code().unwrap_or(EXIT_FAILURE)
+}
+
+/// Store a `migrate` copies to: `file:PATH` or `consul://HOST:PORT/PREFIX`
+fn migration_target(spec: &str, source: &std::path::Path) -> anyhow::Result<MigrationTarget> {
+    if let Some(path) = spec.strip_prefix("file:") {
+        let same = std::fs::create_dir_all(path).is_ok()
+            && std::fs::canonicalize(path).ok() == std::fs::canonicalize(source).ok();
+        if same {
+            anyhow::bail!("{} is the store being migrated", spec);
+        }
+        return Ok(MigrationTarget::file(path)?);
+    }
+
+    #[cfg(feature = "consul")]
+    if let Some(rest) = spec.strip_prefix("consul://") {
+        let (address, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let prefix = if prefix.is_empty() { llm_config_core::DEFAULT_KV_PREFIX } else { prefix };
+        let mut config = llm_config_core::ConsulConfig::new(format!("http://{}", address));
+        if let Ok(token) = std::env::var("CONSUL_HTTP_TOKEN") {
+            config = config.with_token(token);
+        }
+        let store = llm_config_core::ConsulKv::new(config)?;
+        return Ok(MigrationTarget::kv(Arc::new(store), prefix));
+    }
+
+    let expected = if cfg!(feature = "consul") {
+        "file:PATH or consul://HOST:PORT/PREFIX"
+    } else {
+        "file:PATH"
+    };
+    anyhow::bail!("Unsupported store {:?}: expected {}", spec, expected)
+}
+
+/// Value of `set`: the argument, the contents of `--from-file` or stdin, or
+/// (for secrets given none of them) the answer to a hidden prompt
+fn read_set_value(key: &str, value: Option<String>, from_file: Option<&Path>, stdin: bool) -> anyhow::Result<Vec<u8>> {
+    use std::io::Read;
+    match (value, from_file) {
+        (Some(value), _) => Ok(value.into_bytes()),
+        (None, Some(path)) => std::fs::read(path).with_context(|| format!("Failed to read {}", path.display())),
+        (None, None) if !stdin => Ok(prompt::secret(key)?.into_bytes()),
+        (None, None) => {
+            let mut bytes = Vec::new();
+            std::io::stdin().read_to_end(&mut bytes).context("Failed to read the value from stdin")?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// Text of a non-secret value read by [`read_set_value`]; YAML files are
+/// converted to JSON so they are parsed like JSON values
+fn set_value_text(bytes: Vec<u8>, from_file: Option<&Path>) -> anyhow::Result<String> {
+    let text = String::from_utf8(bytes)
+        .map_err(|_| anyhow::anyhow!("The value is not valid UTF-8 (only secrets can hold binary data)"))?;
+    let Some(path) = from_file.filter(|path| path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml")) else {
+        return Ok(text);
+    };
+    let value: serde_json::Value =
+        serde_yaml::from_str(&text).with_context(|| format!("Invalid YAML in {}", path.display()))?;
+    Ok(value.to_string())
+}
+
+/// Error for a config that does not exist
+fn not_found(namespace: &str, key: &str, env: Environment) -> anyhow::Error {
+    ConfigError::NotFound(format!("{}:{} in {}", namespace, key, env)).into()
+}
+
+fn run(cli: Cli) -> anyhow::Result<()> {
+    // Completions don't touch the storage directory
+    if let Commands::Completions { shell } = cli.command {
+        clap_complete::generate(shell, &mut Cli::command(), "llm-config", &mut std::io::stdout());
+        return Ok(());
+    }
+
+    if let Some(server) = &cli.server {
+        let client = RemoteClient::new(server, cli.token.clone())?;
+        return remote::run(&client, cli.output, cli.command);
+    }
+
+    // Resolve the encryption key if provided
+    let key = match (cli.encryption_key, cli.passphrase) {
+        (Some(key_str), _) => Some(SecretKey::from_base64(Algorithm::Aes256Gcm, &key_str)?),
+        (None, Some(passphrase)) => {
+            let passphrase = match passphrase {
+                Some(passphrase) => passphrase,
+                None => prompt::passphrase(!has_passphrase(&cli.storage))?,
+            };
+            Some(derive_key_from_passphrase(&cli.storage, &passphrase)?)
+        }
+        (None, None) => None,
+    };
+
+    #[cfg(feature = "keyring")]
+    if let Commands::Key { command } = &cli.command {
+        return keychain::run(&cli.storage, key, command);
+    }
+
+    // Fall back to the key stored in the OS keychain
+    #[cfg(feature = "keyring")]
+    let key = key.or_else(|| keychain::load(&cli.storage).ok().flatten());
+
+    // Create manager, over the tenant's own store if one is given
+    let storage = match &cli.tenant {
+        Some(tenant) => tenant.storage_path(&cli.storage),
+        None => cli.storage.clone(),
+    };
+    let mut manager = ConfigManager::new(&storage)?
+        .with_legacy_overrides(cli.legacy_overrides)
+        .with_strict_types(cli.strict_types)
+        .with_required_secret_binding(cli.require_bound_secrets);
+    if let Some(tenant) = cli.tenant.clone() {
+        manager = manager.with_tenant(tenant);
+    }
+
+    // Set encryption key if provided
+    let has_key = key.is_some();
+    if let Some(mut key) = key {
+        if let Some(tenant) = &cli.tenant {
+            key = TenantKeyStore::new(cli.storage.join(TENANT_KEYS_DIR), key)?.key(tenant)?;
+        }
+        manager = manager.with_encryption_key(key.with_algorithm(cli.cipher));
+    }
+
+    // Sign writes and check signatures on read
+    let mut signatures = SignaturePolicy::default().with_required_signatures(cli.require_signatures);
+    for trusted in &cli.trusted_signers {
+        let Some((name, public_key)) = trusted.split_once('=') else {
+            anyhow::bail!("Invalid trusted signer {:?}: expected NAME=PUBLIC_KEY", trusted);
+        };
+        signatures = signatures.with_trusted_key(name.trim(), VerifyingKey::from_base64(public_key.trim())?);
+    }
+    manager = manager.with_signature_policy(signatures);
+    if let Some(signing_key) = &cli.signing_key {
+        manager = manager.with_signer(EntrySigner::new(&cli.signer, SigningKey::from_base64(signing_key)?)?);
+    }
+
+    let output = cli.output;
+    match cli.command {
+        Commands::Get {
+            namespace,
+            key,
+            env,
+            with_overrides,
+            reveal,
+            format,
+            raw,
+            ..
+        } => {
+            let env: Environment = env.into();
+            let output = document_output(output, format);
+
+            if with_overrides {
+                let ResolvedValue { mut value, sources } = manager
+                    .resolve_key(&namespace, &key, env)?
+                    .ok_or_else(|| not_found(&namespace, &key, env))?;
+                if reveal {
+                    value = manager.reveal(value)?;
+                }
+                if raw {
+                    print_raw(&raw_text(&value)?)?;
+                    return Ok(());
+                }
+                match output {
+                    Some(output) => {
+                        let resolved = serde_json::json!({
+                            "namespace": namespace,
+                            "key": key,
+                            "environment": env,
+                            "value": value.redacted(),
+                            "sources": sources,
+                        });
+                        output.print(&resolved, || format_value(&value))?;
+                    }
+                    None => println!("{}", format_value(&value)),
+                }
+            } else {
+                let CheckedEntry { mut entry, warning } = manager
+                    .get_checked(&namespace, &key, env)?
+                    .ok_or_else(|| not_found(&namespace, &key, env))?;
+                if reveal {
+                    entry.value = manager.reveal(entry.value)?;
+                }
+                if let Some(warning) = warning {
+                    eprintln!("{} {}", "Warning:".yellow().bold(), warning);
+                }
+
+                if raw {
+                    print_raw(&raw_text(&entry.value)?)?;
+                } else if let Some(output) = output {
+                    let plain = format_value(&entry.value);
+                    entry.value = entry.value.redacted();
+                    output.print(&entry, || plain)?;
+                } else {
+                    println!("{}", "Configuration:".green().bold());
+                    println!("  Namespace: {}", entry.namespace);
+                    println!("  Key: {}", entry.key);
+                    println!("  Environment: {}", entry.environment);
+                    println!("  Value: {}", format_value(&entry.value));
+                    println!("  Version: {}", entry.version);
+                    println!("  Updated: {}", entry.metadata.updated_at);
+                    println!("  Updated by: {}", entry.metadata.updated_by);
+                }
+            }
+        }
+
+        Commands::Set {
+            namespace,
+            key,
+            value,
+            from_file,
+            stdin,
+            env,
+            user,
+            secret,
+            value_type,
+            merge,
+            allow_type_change,
+            expected_version,
+            reviewed_by,
+            message,
+            dry_run,
+        } => {
+            let env: Environment = env.into();
+            let mut options = write_options(reviewed_by)
+                .with_allow_type_change(allow_type_change)
+                .with_dry_run(dry_run);
+            options.change_description = message;
+            options.expected_version = expected_version;
+            options.merge = merge;
+
+            if secret && !has_key {
+                anyhow::bail!("Encryption key required for secrets. Set --encryption-key (or LLM_CONFIG_KEY) or --passphrase.");
+            }
+            let mut bytes = read_set_value(&key, value, from_file.as_deref(), stdin)?;
+            let result = if secret {
+                let result = manager.set_secret_with_options(&namespace, &key, &bytes, env, &user, &options);
+                bytes.zeroize();
+                result
+            } else {
+                let value = set_value_text(bytes, from_file.as_deref())?;
+                let config_value = match value_type {
+                    Some(value_type) => value_type.parse(&value).map_err(anyhow::Error::msg)?,
+                    None => parse_value(&value),
+                };
+                manager.set_with_options(&namespace, &key, config_value, env, &user, &options)
+            };
+            let mut entry = match result {
+                Err(ConfigError::Conflict(msg)) => {
+                    let hint = format!(
+                        "{} was changed by someone else ({}). Check `llm-config history` and retry with the current version.",
+                        key, msg
+                    );
+                    return Err(anyhow::Error::new(ConfigError::Conflict(msg)).context(hint));
+                }
+                other => other?,
+            };
+
+            if dry_run {
+                let current = manager.get_untracked(&namespace, &key, env)?;
+                print_dry_run(output, "set", current.as_ref(), Some(&entry))?;
+            } else if let Some(output) = output {
+                entry.value = entry.value.redacted();
+                output.print(&entry, || entry.version.to_string())?;
+            } else {
+                println!("{}", "Configuration saved successfully!".green().bold());
+                println!("  Version: {}", entry.version);
+                println!("  ID: {}", entry.id);
+            }
+        }
+
+        Commands::List {
+            namespace,
+            env,
+            format,
+            reveal,
+            limit,
+            offset,
+            page_token,
+            sort_by,
+            desc,
+            prefix,
+            tag,
+        } => {
+            let env: Environment = env.into();
+            let options = ListOptions {
+                limit,
+                offset,
+                page_token,
+                sort_by,
+                descending: desc,
+                key_prefix: prefix,
+                tag,
+                exclude_secrets: false,
+            };
+            let page = manager.list_with_options(&namespace, env, &options)?;
+            let mut entries = page.entries;
+
+            // Structured output must not leak ciphertext either
+            for entry in &mut entries {
+                if reveal {
+                    entry.value = manager.reveal(entry.value.clone())?;
+                }
+                entry.value = entry.value.redacted();
+            }
+
+            if entries.is_empty() && output.is_none() {
+                println!("{}", "No configurations found".yellow());
+                return Ok(());
+            }
+
+            let shown = entries.len();
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    println!("{}", format!("Configurations in {} ({})", namespace, env).green().bold());
+                    println!();
+                    for entry in entries {
+                        println!("  {} {} = {}", "•".blue(), entry.key.bold(), format_value(&entry.value));
+                        println!("    Version: {} | Updated: {} by {}",
+                            entry.version,
+                            entry.metadata.updated_at.format("%Y-%m-%d %H:%M:%S"),
+                            entry.metadata.updated_by
+                        );
+                        println!();
+                    }
+                    if shown < page.total {
+                        println!("Showing {} of {} configurations", shown, page.total);
+                    }
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&entries)?);
+                }
+                OutputFormat::Yaml => {
+                    println!("{}", serde_yaml::to_string(&entries)?);
+                }
+            }
+
+            // On stderr, so structured output stays parseable
+            if let Some(token) = &page.next_page_token {
+                eprintln!("Next page: --page-token '{}'", token);
+            }
+        }
+
+        Commands::Resolve {
+            namespace,
+            env,
+            format,
+            reveal,
+        } => {
+            let env: Environment = env.into();
+            let mut values = manager.resolve_namespace(&namespace, env)?;
+            for value in values.values_mut() {
+                if reveal {
+                    *value = manager.reveal(value.clone())?;
+                }
+                *value = value.redacted();
+            }
+
+            if values.is_empty() && output.is_none() {
+                println!("{}", "No configurations found".yellow());
+                return Ok(());
+            }
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    println!("{}", format!("Resolved configuration of {} ({})", namespace, env).green().bold());
+                    println!();
+                    for (key, value) in &values {
+                        println!("  {} {} = {}", "•".blue(), key.bold(), format_value(value));
+                    }
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&values)?);
+                }
+                OutputFormat::Yaml => {
+                    println!("{}", serde_yaml::to_string(&values)?);
+                }
+            }
+        }
+
+        Commands::Explain {
+            namespace,
+            key,
+            env,
+            format,
+            reveal,
+        } => {
+            let mut trace = manager.explain(&namespace, &key, env.into(), reveal)?;
+            trace.value = trace.value.map(|value| value.redacted());
+            show_trace(output_format(output, format), &trace)?;
+        }
+
+        Commands::Delete {
+            namespace,
+            key,
+            env,
+            user,
+            reviewed_by,
+            yes,
+            dry_run,
+        } => {
+            let env: Environment = env.into();
+
+            if !yes && !dry_run && output.is_some() {
+                return Err(ConfigError::ValidationError("--yes is required with --output".to_string()).into());
+            }
+            if !yes
+                && !dry_run
+                && !confirm(&format!("Delete configuration {}:{} in {} environment?", namespace, key, env))?
+            {
+                println!("Cancelled.");
+                return Ok(());
+            }
+
+            let current = if dry_run {
+                manager.get_untracked(&namespace, &key, env)?
+            } else {
+                None
+            };
+            let options = write_options(reviewed_by).with_dry_run(dry_run);
+            let deleted = manager.delete_with_options(&namespace, &key, env, &user, &options)?;
+
+            if !deleted {
+                return Err(not_found(&namespace, &key, env));
+            }
+            if dry_run {
+                return print_dry_run(output, "delete", current.as_ref(), None);
+            }
+            match output {
+                Some(output) => {
+                    let deleted = serde_json::json!({
+                        "namespace": namespace,
+                        "key": key,
+                        "environment": env,
+                        "deleted": true,
+                    });
+                    output.print(&deleted, || "deleted".to_string())?;
+                }
+                None => println!("{}", "Configuration deleted successfully!".green().bold()),
+            }
+        }
+
+        Commands::History {
+            command: Some(HistoryCommands::Prune { keep_last, keep_days }),
+            ..
+        } => {
+            let policy = RetentionPolicy {
+                keep_last,
+                keep_days,
+            };
+            let pruned = manager.prune_history(&policy)?;
+            println!("{} Pruned {} versions", "✓".green().bold(), pruned);
+        }
+
+        Commands::History {
+            command: None,
+            namespace,
+            key,
+            env,
+            limit,
+            before,
+            format,
+            raw,
+        } => {
+            let (Some(namespace), Some(key)) = (namespace, key) else {
+                unreachable!("namespace and key are required without a subcommand");
+            };
+            let env: Environment = env.into();
+            let options = HistoryOptions {
+                limit,
+                before_version: before,
+            };
+            let mut history = manager.get_history_with_options(&namespace, &key, env, &options)?;
+
+            if raw {
+                for version in &history {
+                    println!("{}", raw_text(&version.value)?);
+                }
+                return Ok(());
+            }
+
+            if let Some(output) = document_output(output, format) {
+                let plain = history
+                    .iter()
+                    .map(|version| {
+                        format!(
+                            "{}\t{}\t{}\t{}",
+                            version.version,
+                            version.created_at.to_rfc3339(),
+                            version.created_by,
+                            format_value(&version.value)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                for version in &mut history {
+                    version.value = version.value.redacted();
+                }
+                output.print(&history, || plain)?;
+                return Ok(());
+            }
+
+            if history.is_empty() {
+                println!("{}", "No version history found".yellow());
+                return Ok(());
+            }
+
+            println!("{}", format!("Version history for {}:{}", namespace, key).green().bold());
+            println!();
+
+            for version in history {
+                println!("  {} Version {}", "→".blue(), version.version.to_string().bold());
+                println!("    Value: {}", format_value(&version.value));
+                println!("    Created: {} by {}", version.created_at.format("%Y-%m-%d %H:%M:%S"), version.created_by);
+                if let Some(desc) = version.change_description {
+                    println!("    Note: {}", desc);
+                }
+                println!();
+            }
+        }
+
+        Commands::Tag { command } => run_tag_command(&manager, command)?,
+
+        Commands::Describe {
+            namespace,
+            key,
+            description,
+            env,
+            user,
+            reviewed_by,
+        } => {
+            let env: Environment = env.into();
+            let entry = manager
+                .get(&namespace, &key, env)?
+                .ok_or_else(|| anyhow::anyhow!("Configuration not found: {}:{}", namespace, key))?;
+
+            manager.set_metadata_with_options(
+                &namespace,
+                &key,
+                env,
+                entry.metadata.tags,
+                Some(description),
+                &user,
+                &write_options(reviewed_by),
+            )?;
+            println!("{}", "Description updated".green().bold());
+        }
+
+        Commands::Changelog {
+            namespace,
+            since,
+            env,
+            format,
+        } => {
+            let since = parse_since(&since, Utc::now())?;
+            let changelog = manager.changelog(&namespace, env.map(Environment::from), since)?;
+
+            match output_format(output, format) {
+                OutputFormat::Table => print!("{}", changelog.to_markdown()),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&changelog)?),
+                OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&changelog)?),
+            }
+        }
+
+        Commands::Rollback {
+            namespace,
+            key,
+            version,
+            env,
+            user,
+            reviewed_by,
+            reason,
+            dry_run,
+        } => {
+            let env: Environment = env.into();
+            let mut options = write_options(reviewed_by).with_dry_run(dry_run);
+            options.change_description = reason;
+
+            let mut entry = manager
+                .rollback_with_options(&namespace, &key, env, version, &user, &options)?
+                .ok_or_else(|| {
+                    ConfigError::NotFound(format!("version {} of {}:{} in {}", version, namespace, key, env))
+                })?;
+
+            if dry_run {
+                let current = manager.get_untracked(&namespace, &key, env)?;
+                print_dry_run(output, "rollback", current.as_ref(), Some(&entry))?;
+            } else if let Some(output) = output {
+                entry.value = entry.value.redacted();
+                output.print(&entry, || entry.version.to_string())?;
+            } else {
+                println!("{}", "Rollback successful!".green().bold());
+                println!("  New version: {}", entry.version);
+                println!("  Value: {}", format_value(&entry.value));
+            }
+        }
+
+        Commands::Promote {
+            namespace,
+            from,
+            to,
+            keys,
+            dry_run,
+            format,
+            user,
+            reviewed_by,
+            message,
+        } => {
+            let (from, to): (Environment, Environment) = (from.into(), to.into());
+            let keys = if keys.is_empty() {
+                PromoteKeys::All
+            } else {
+                PromoteKeys::Only(keys)
+            };
+            let plan = if dry_run {
+                manager.plan_promotion(&namespace, &keys, from, to)?
+            } else {
+                let mut options = write_options(reviewed_by);
+                options.change_description = message;
+                manager.promote_with_options(&namespace, &keys, from, to, &user, &options)?
+            };
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    let verb = if dry_run { "Would promote" } else { "Promoted" };
+                    println!(
+                        "{}",
+                        format!(
+                            "{} {} keys of {} from {} to {} ({} created, {} updated)",
+                            verb,
+                            plan.changes.len(),
+                            namespace,
+                            from,
+                            to,
+                            plan.summary.create,
+                            plan.summary.update
+                        )
+                        .green()
+                        .bold()
+                    );
+                    plan.changes.iter().for_each(print_change);
+                }
+                OutputFormat::Json => print!("{}", plan.to_json()),
+                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&plan)?),
+            }
+        }
+
+        Commands::RestoreAt {
+            namespace,
+            env,
+            at,
+            dry_run,
+            format,
+            user,
+            reviewed_by,
+            message,
+        } => {
+            let env: Environment = env.into();
+            let at = parse_since(&at, Utc::now())?;
+            let plan = if dry_run {
+                manager.plan_namespace_restore(&namespace, env, at)?
+            } else {
+                let mut options = write_options(reviewed_by);
+                options.change_description = message;
+                manager.restore_namespace_at_with_options(&namespace, env, at, &user, &options)?
+            };
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    let verb = if dry_run { "Would restore" } else { "Restored" };
+                    println!(
+                        "{}",
+                        format!(
+                            "{} {} in {} to {} ({} created, {} updated, {} deleted)",
+                            verb,
+                            namespace,
+                            env,
+                            at.to_rfc3339(),
+                            plan.summary.create,
+                            plan.summary.update,
+                            plan.summary.delete
+                        )
+                        .green()
+                        .bold()
+                    );
+                    plan.changes.iter().for_each(print_change);
+                }
+                OutputFormat::Json => print!("{}", plan.to_json()),
+                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&plan)?),
+            }
+        }
+
+        Commands::Export {
+            dir: Some(path), ..
+        } => {
+            let count = manager.export_all(&path)?;
+            println!("{}", format!("Exported {} configurations to {}", count, path.display()).green().bold());
+        }
+
+        Commands::Export {
+            namespace,
+            env,
+            format,
+            reveal_secrets,
+            output: file,
+            dir: None,
+        } => {
+            let namespace = namespace.context("a namespace or --dir is required")?;
+            let document = manager.export_document(&namespace, env.into(), format.into(), reveal_secrets)?;
+            match file {
+                Some(path) => {
+                    std::fs::write(&path, document)?;
+                    println!("{}", format!("Exported {} to {}", namespace, path.display()).green().bold());
+                }
+                None => print!("{}", document),
+            }
+        }
+
+        Commands::Import {
+            file,
+            namespace,
+            env,
+            format,
+            user,
+            reviewed_by,
+            message,
+        } => {
+            let format = match format {
+                Some(format) => format.into(),
+                None => DocumentFormat::from_path(&file).with_context(|| {
+                    format!("cannot tell the format of {}; pass --format", file.display())
+                })?,
+            };
+            let content = std::fs::read_to_string(&file)?;
+            let mut options = write_options(reviewed_by);
+            options.change_description = message;
+            let import = manager.import_document(&namespace, &content, format, env.into(), &user, &options)?;
+
+            println!(
+                "{}",
+                format!(
+                    "Imported {}: {} created, {} updated, {} unchanged",
+                    file.display(),
+                    import.created.len(),
+                    import.updated.len(),
+                    import.unchanged.len()
+                )
+                .green()
+                .bold()
+            );
+            for key in &import.secrets {
+                println!("  {} {} (secret)", "•".blue(), key);
+            }
+        }
+
+        Commands::ExportEnv { namespace, env } => {
+            print!("{}", manager.export_dotenv(&namespace, env.into())?);
+        }
+
+        Commands::Env { namespace, env } => match output {
+            Some(Output::Plain) | None => print!("{}", manager.export_shell(&namespace, env.into())?),
+            Some(output) => {
+                let vars: serde_json::Map<String, serde_json::Value> = manager
+                    .env_vars(&namespace, env.into())?
+                    .into_iter()
+                    .map(|(name, value)| (name, value.into()))
+                    .collect();
+                output.print(&vars, String::new)?;
+            }
+        },
+
+        Commands::Exec {
+            namespace,
+            env,
+            command,
+        } => {
+            let mut vars = manager.env_vars(&namespace, env.into())?;
+            let child = std::process::Command::new(&command[0])
+                .args(&command[1..])
+                .envs(vars.iter().map(|(name, value)| (name, value)))
+                .spawn();
+            for (_, value) in &mut vars {
+                value.zeroize();
+            }
+            let status = child
+                .with_context(|| format!("failed to run {}", command[0]))?
+                .wait()?;
+
+            if !status.success() {
+                manager.flush_access_stats()?;
+                std::process::exit(exit_status_code(status));
+            }
+        }
+
+        Commands::ImportEnv {
+            file,
+            namespace,
+            env,
+            user,
+            reviewed_by,
+            message,
+        } => {
+            let content = std::fs::read_to_string(&file)?;
+            let mut options = write_options(reviewed_by);
+            options.change_description = message;
+            let import = manager.import_dotenv(&namespace, &content, env.into(), &user, &options)?;
+
+            println!(
+                "{}",
+                format!(
+                    "Imported {}: {} created, {} updated, {} unchanged",
+                    file.display(),
+                    import.created.len(),
+                    import.updated.len(),
+                    import.unchanged.len()
+                )
+                .green()
+                .bold()
+            );
+            for key in &import.secrets {
+                println!("  {} {} (secret)", "•".blue(), key);
+            }
+        }
+
+        Commands::Keygen { signing: true } => {
+            let key = SigningKey::generate()?;
+            let public_key = key.verifying_key()?;
+            if let Some(output) = output {
+                let generated = serde_json::json!({
+                    "algorithm": "ed25519",
+                    "key": key.to_base64(),
+                    "public_key": public_key.to_base64(),
+                    "key_id": public_key.key_id(),
+                });
+                output.print(&generated, || key.to_base64())?;
+                return Ok(());
+            }
+            println!("{}", "Generated signing key:".green().bold());
+            println!();
+            println!("{}", key.to_base64());
+            println!();
+            println!("Public key (key ID {}):", public_key.key_id());
+            println!();
+            println!("{}", public_key.to_base64());
+            println!();
+            println!("Sign writes using:");
+            println!("  {} export LLM_CONFIG_SIGNING_KEY=\"{}\"", "•".blue(), key.to_base64());
+            println!("Verify them elsewhere using:");
+            println!(
+                "  {} llm-config --trusted-signer <name>={} verify <namespace>",
+                "•".blue(),
+                public_key.to_base64()
+            );
+        }
+
+        Commands::Keygen { signing: false } => {
+            let key = SecretKey::generate(Algorithm::Aes256Gcm)?;
+            if let Some(output) = output {
+                let generated = serde_json::json!({ "algorithm": "aes-256-gcm", "key": key.to_base64() });
+                output.print(&generated, || key.to_base64())?;
+                return Ok(());
+            }
+            println!("{}", "Generated encryption key:".green().bold());
+            println!();
+            println!("{}", key.to_base64());
+            println!();
+            println!("Set this key using:");
+            println!("  {} export LLM_CONFIG_KEY=\"{}\"", "•".blue(), key.to_base64());
+            println!("  {} llm-config --encryption-key <key> ...", "•".blue());
+            println!();
+            println!("Or derive it from a passphrase instead:");
+            println!("  {} llm-config --passphrase ...", "•".blue());
+            #[cfg(feature = "keyring")]
+            {
+                println!();
+                println!("Or keep it in the OS keychain:");
+                println!("  {} llm-config key store --generate", "•".blue());
+            }
+        }
+
+        Commands::Protection { command } => run_protection_command(&manager, command)?,
+        Commands::Secret { command } => run_secret_command(&manager, has_key, output, command)?,
+        Commands::Environments { command } => run_environment_command(&manager, command)?,
+        Commands::Template { command } => {
+            run_template_command(&manager, &cli.storage.join("audit"), command)?
+        }
+        Commands::Blueprint { command } => run_blueprint_command(&manager, command)?,
+
+        Commands::K8s { command } => run_k8s_command(&manager, command)?,
+        Commands::Ns { command } => run_ns_command(&manager, command)?,
+        Commands::Drift { command } => run_drift_command(&manager, output, command)?,
+        Commands::Sync {
+            repo,
+            branch,
+            path,
+            checkout_dir,
+            interval,
+            prune,
+            user,
+        } => {
+            let audit = FileAuditStorage::new(cli.storage.join("audit"))?;
+            let manager = manager.with_audit_logger(Arc::new(BlockingAuditRecorder::new(Arc::new(audit))));
+            let checkout_dir = checkout_dir.unwrap_or_else(|| cli.storage.join("sync"));
+            let mut syncer = Syncer::new(Arc::new(manager), GitRepository::new(repo, checkout_dir).with_branch(branch))
+                .with_user(user)
+                .with_prune(prune);
+            if let Some(path) = path {
+                syncer = syncer.with_path(path);
+            }
+
+            match interval {
+                Some(secs) => syncer.run(Duration::from_secs(secs), |result| match result {
+                    Ok(report) => print_sync_report(&report),
+                    Err(e) => eprintln!("{} {}", "Sync failed:".red().bold(), e),
+                }),
+                None => {
+                    let report = syncer.sync()?;
+                    print_sync_report(&report);
+                    if !report.is_in_sync() {
+                        anyhow::bail!("{} changes were not applied", report.skipped.len());
+                    }
+                }
+            }
+        }
+
+        Commands::Role { command } => {
+            let store = RoleStore::open(&storage)?;
+            run_role_command(&store, output, command)?;
+        }
+
+        Commands::Group { command } => {
+            let store = RoleStore::open(&storage)?;
+            run_group_command(&store, output, command)?;
+        }
+
+        Commands::Audit { audit_dir, command } => {
+            let audit_dir = audit_dir.unwrap_or_else(|| cli.storage.join("audit"));
+            let storage = FileAuditStorage::new(&audit_dir)?;
+            run_audit_command(&manager, &storage, cli.tenant.as_ref(), output, command)?;
+        }
+
+        Commands::AccessReview {
+            period,
+            format,
+            output,
+            audit_dir,
+        } => {
+            let end = Utc::now();
+            let start = parse_since(&period, end)?;
+            let enforcer = PolicyEnforcer::from_store(&RoleStore::open(&storage)?)?;
+            let audit_dir = audit_dir.unwrap_or_else(|| cli.storage.join("audit"));
+            let filter = AuditFilter {
+                tenant: cli.tenant.as_ref().map(TenantId::to_string),
+                ..AuditFilter::default()
+            };
+            let uses = FileAuditStorage::new(&audit_dir)?
+                .query(start, end, None)?
+                .into_iter()
+                .filter(|event| filter.matches(event))
+                .filter_map(|event| {
+                    Some(AccessUse {
+                        permission: event.event_type.permission()?.parse().ok()?,
+                        namespace: event.event_type.target().map(|(namespace, _, _)| namespace.to_string()),
+                        user: event.user,
+                        at: event.timestamp,
+                    })
+                });
+            let review = AccessReview::new(&enforcer, start, end, uses);
+
+            let report = match format {
+                ReviewFormat::Markdown => review.to_markdown(),
+                ReviewFormat::Csv => review.to_csv(),
+            };
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, report)?;
+                    println!(
+                        "{}",
+                        format!("Wrote access review of {} users to {}", review.users.len(), path.display())
+                            .green()
+                            .bold()
+                    );
+                }
+                None => print!("{}", report),
+            }
+        }
+
+        Commands::Serve(args) => {
+            let ServeArgs {
+                bind,
+                metrics_port,
+                metrics_push_url,
+                metrics_push_job,
+                metrics_push_labels,
+                metrics_push_interval,
+                audit_dir,
+                audit_queue_capacity,
+                audit_overflow,
+                enforce_rbac,
+                no_cors,
+                check_consistency,
+                webhooks,
+                swagger_ui,
+                trusted_proxies,
+                rate_limits,
+                tls_cert,
+                tls_key,
+                tls_client_ca,
+                shutdown_timeout,
+                key_created_at,
+                key_max_age_days,
+                scan_values_interval,
+                retention_interval,
+                secret_reads_per_minute,
+                secret_read_burst,
+                allow_secret_reveal,
+                require_secret_reason,
+                cache_dir,
+                cache_size,
+                cache_max_bytes,
+                preload,
+                preload_env,
+                cache_ttl,
+                cache_stale_while_revalidate,
+            } = *args;
+            if !has_key {
+                tracing::warn!("No encryption key provided - secret operations will fail");
+            }
+
+            let metrics_push = match metrics_push_url {
+                Some(url) => {
+                    let mut push = PushConfig::new(url, metrics_push_job)
+                        .with_interval(Duration::from_secs(metrics_push_interval));
+                    for label in &metrics_push_labels {
+                        push = push.with_label_spec(label)?;
+                    }
+                    Some(push)
+                }
+                None => None,
+            };
+
+            let config = ServerConfig {
+                host: bind.ip().to_string(),
+                port: bind.port(),
+                enable_cors: !no_cors,
+                audit_log_dir: audit_dir,
+                audit_queue_capacity,
+                audit_overflow,
+                enforce_rbac,
+                metrics_port,
+                metrics_push,
+                check_consistency,
+                webhooks: match webhooks {
+                    Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+                    None => Vec::new(),
+                },
+                swagger_ui,
+                trusted_proxies,
+                principal_quotas: match rate_limits {
+                    Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+                    None => Default::default(),
+                },
+                tls: tls_cert.zip(tls_key).map(|(cert, key)| {
+                    let tls = TlsConfig::new(cert, key);
+                    match tls_client_ca {
+                        Some(ca) => tls.with_client_ca(ca),
+                        None => tls,
+                    }
+                }),
+                shutdown_timeout: Duration::from_secs(shutdown_timeout),
+                key_created_at,
+                key_max_age_days,
+                value_scan_interval: scan_values_interval.map(Duration::from_secs),
+                retention_interval: retention_interval.map(Duration::from_secs),
+                secret_access_limit: secret_reads_per_minute.map(|reads_per_minute| SecretAccessLimit {
+                    reads_per_minute,
+                    burst: secret_read_burst.unwrap_or(reads_per_minute),
+                }),
+                allow_secret_reveal,
+                require_secret_reason,
+                cache_dir,
+                cache_size,
+                cache_max_bytes,
+                preload_namespaces: preload,
+                preload_env: preload_env.map(Into::into),
+                cache_ttl: cache_ttl.map(|ttl| {
+                    let ttl = CacheTtl::new(Duration::from_secs(ttl));
+                    match cache_stale_while_revalidate {
+                        Some(window) => ttl.with_stale_while_revalidate(Duration::from_secs(window)),
+                        None => ttl,
+                    }
+                }),
+                ..ServerConfig::default()
+            };
+
+            tokio::runtime::Runtime::new()?.block_on(serve(Arc::new(manager), config))?;
+        }
+
+        Commands::Browse => browse::run(&manager, has_key)?,
+
+        Commands::Completions { .. } => unreachable!("handled before opening storage"),
+        #[cfg(feature = "keyring")]
+        Commands::Key { .. } => unreachable!("handled before opening storage"),
+
+        Commands::Fsck { repair } => {
+            let report = manager.fsck(repair)?;
+
+            for issue in &report.issues {
+                let status = if issue.repaired {
+                    "repaired".green()
+                } else {
+                    "unrepaired".red()
+                };
+                println!(
+                    "{} {} ({:?}): {}",
+                    status,
+                    issue.subject.cyan(),
+                    issue.kind,
+                    issue.detail
+                );
+            }
+
+            println!(
+                "Checked {} configs and {} versions: {} issues, {} unrepaired",
+                report.configs_checked,
+                report.versions_checked,
+                report.issues.len(),
+                report.unrepaired()
+            );
+
+            if report.unrepaired() > 0 {
+                anyhow::bail!("storage is inconsistent");
+            }
+        }
+
+        Commands::Compact => {
+            let report = manager.compact()?;
+
+            for file in &report.unreadable_version_files {
+                println!("{} {}", "unreadable".red(), file);
+            }
+
+            println!(
+                "Indexed {} configs ({} journaled changes): removed {} temporary files and {} empty history directories, rewrote {} version files",
+                report.configs,
+                report.journal_entries,
+                report.temp_files_removed,
+                report.empty_dirs_removed,
+                report.version_files_rewritten
+            );
+            if manager.value_dedup().is_some() || report.blobs_removed > 0 {
+                println!(
+                    "Moved {} values into blobs, removed {} unreferenced blobs, corrected {} reference counts",
+                    report.values_moved_to_blobs, report.blobs_removed, report.blob_counts_repaired
+                );
+            }
+        }
+
+        Commands::ReadOnly { command } => run_read_only_command(&manager, command)?,
+        Commands::Dedup { command } => run_dedup_command(&manager, command)?,
+
+        Commands::Migrate {
+            from,
+            to,
+            overwrite,
+            keep_writable,
+            keep_read_only,
+            user,
+        } => {
+            let (source, source_path) = match &from {
+                Some(spec) => {
+                    let path = spec
+                        .strip_prefix("file:")
+                        .map(PathBuf::from)
+                        .with_context(|| format!("Unsupported source {:?}: migrations read from file:PATH", spec))?;
+                    (ConfigManager::new(&path)?, path)
+                }
+                None => (manager, storage),
+            };
+            let target = migration_target(&to, &source_path)?;
+            let options = MigrateOptions::default()
+                .with_overwrite(overwrite)
+                .with_keep_writable(keep_writable)
+                .with_keep_read_only(keep_read_only);
+            let report = source.migrate_to(&target, &user, &options)?;
+
+            match output {
+                Some(format) => format.print(&report, || report.target_digest.clone())?,
+                None => {
+                    println!(
+                        "Copied {} of {} entries to {} ({} already there), {} versions, {} settings records",
+                        report.copied, report.entries, to, report.skipped, report.versions, report.records
+                    );
+                    if report.is_verified() {
+                        println!("{} {} entries, sha256 {}", "Verified".green().bold(), report.target_entries, report.target_digest);
+                    }
+                }
+            }
+            if !report.is_verified() {
+                anyhow::bail!(
+                    "verification failed: source has {} entries (sha256 {}), target {} (sha256 {})",
+                    report.entries,
+                    report.source_digest,
+                    report.target_entries,
+                    report.target_digest
+                );
+            }
+            if keep_read_only && output.is_none() {
+                println!("The source stays read-only; enable writes again with `llm-config read-only disable`");
+            }
+        }
+
+        Commands::ScanValues {
+            namespace,
+            env,
+            no_entropy,
+            format,
+        } => {
+            let mut scanner = ConfigContentScanner::new(Arc::new(manager)).with_config(SecretScanConfig {
+                entropy: !no_entropy,
+                ..SecretScanConfig::default()
+            });
+            if let Some(namespace) = namespace {
+                scanner = scanner.with_namespace(namespace);
+            }
+            if let Some(env) = env {
+                scanner = scanner.with_environment(env.into());
+            }
+            let findings = scanner.scan()?;
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    for found in &findings {
+                        println!(
+                            "{} {}/{} ({}) {}: {}",
+                            format!("{:<8}", found.finding.severity.to_string()).red().bold(),
+                            found.namespace,
+                            found.key.bold(),
+                            found.environment,
+                            found.field.cyan(),
+                            found.finding.title
+                        );
+                    }
+                    if findings.is_empty() {
+                        println!("{}", "No plaintext secrets found".green().bold());
+                    } else {
+                        println!();
+                        println!("Store these values with `set --secret` and rotate the credentials");
+                    }
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&findings)?),
+                OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&findings)?),
+            }
+
+            if !findings.is_empty() {
+                anyhow::bail!("{} plaintext secrets found", findings.len());
+            }
+        }
+
+        Commands::Verify {
+            namespace,
+            allow_unsigned,
+            format,
+        } => {
+            let verification = manager.verify_namespace(&namespace)?;
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    for check in &verification.entries {
+                        let status = match &check.status {
+                            SignatureStatus::Valid { .. } => "valid".green(),
+                            SignatureStatus::Unsigned => "unsigned".yellow(),
+                            SignatureStatus::UnknownSigner { .. } | SignatureStatus::Invalid { .. } => {
+                                "invalid".red()
+                            }
+                        };
+                        println!(
+                            "{} {} ({}) v{}: {}",
+                            format!("{:<8}", status).bold(),
+                            check.key,
+                            check.environment,
+                            check.version,
+                            check.status
+                        );
+                    }
+                    println!(
+                        "Checked {} entries in {}: {} unsigned, {} invalid",
+                        verification.entries.len(),
+                        namespace,
+                        verification.unsigned().count(),
+                        verification.invalid().count()
+                    );
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&verification)?),
+                OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&verification)?),
+            }
+
+            let invalid = verification.invalid().count();
+            let unsigned = if allow_unsigned { 0 } else { verification.unsigned().count() };
+            if invalid > 0 || unsigned > 0 {
+                anyhow::bail!("{} invalid and {} unsigned entries in {}", invalid, unsigned, namespace);
+            }
+        }
+
+        Commands::BindSecrets {
+            namespace,
+            dry_run,
+            user,
+            reviewed_by,
+        } => {
+            let options = write_options(reviewed_by).with_dry_run(dry_run);
+            let bound = manager.bind_secrets_with_options(namespace.as_deref(), &user, &options)?;
+
+            if let Some(output) = output {
+                let keys: Vec<_> = bound
+                    .iter()
+                    .map(|entry| {
+                        serde_json::json!({
+                            "namespace": entry.namespace,
+                            "key": entry.key,
+                            "environment": entry.environment,
+                            "version": entry.version,
+                        })
+                    })
+                    .collect();
+                let summary = serde_json::json!({ "dry_run": dry_run, "secrets": keys });
+                output.print(&summary, || bound.len().to_string())?;
+                return Ok(());
+            }
+            let verb = if dry_run { "Would bind" } else { "Bound" };
+            println!("{}", format!("{} {} secrets to their entry", verb, bound.len()).green().bold());
+            for entry in &bound {
+                println!("  {} {}:{} ({})", "•".blue(), entry.namespace, entry.key, entry.environment);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run an audit command, seeing only the events of `tenant` if one is given
+fn run_audit_command(
+    manager: &ConfigManager,
+    storage: &FileAuditStorage,
+    tenant: Option<&TenantId>,
+    output: Option<Output>,
+    command: AuditCommands,
+) -> anyhow::Result<()> {
+    match command {
+        AuditCommands::Export {
+            from,
+            to,
+            user,
+            event_type,
+            format,
+            output,
+        } => {
+            let filter = AuditFilter {
+                user,
+                event_type,
+                tenant: tenant.map(TenantId::to_string),
+                ..AuditFilter::default()
+            };
+            let from = from.unwrap_or(DateTime::<Utc>::MIN_UTC);
+            let to = to.unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+            let count = match &output {
+                Some(path) => {
+                    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                    llm_config_audit::export::export(storage, from..=to, &filter, format.into(), file)?
+                }
+                None => llm_config_audit::export::export(
+                    storage,
+                    from..=to,
+                    &filter,
+                    format.into(),
+                    std::io::stdout().lock(),
+                )?,
+            };
+
+            // Keep stdout clean for piping
+            eprintln!("{}", format!("Exported {} audit events", count).green().bold());
+        }
+        AuditCommands::Unused { older_than, format } => {
+            let cutoff = parse_since(&older_than, Utc::now())?;
+            let unused = manager.unused_configs(cutoff)?;
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    for config in &unused {
+                        let entry = &config.entry;
+                        let last_read = match config.stats.last_accessed {
+                            Some(at) => format!("last read {}", at.to_rfc3339()),
+                            None => "never read".to_string(),
+                        };
+                        println!(
+                            "{} {}:{} ({}): {}",
+                            "•".blue(),
+                            entry.namespace.cyan(),
+                            entry.key,
+                            entry.environment,
+                            last_read
+                        );
+                    }
+                    println!("{} configs not read since {}", unused.len(), cutoff.to_rfc3339());
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&unused)?),
+                OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&unused)?),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_protection_command(
+    manager: &ConfigManager,
+    command: ProtectionCommands,
+) -> anyhow::Result<()> {
+    let mut protection = manager.protection();
+
+    match command {
+        ProtectionCommands::Show => {
+            println!("{}", "Environment protection:".green().bold());
+            for env in [
+                Environment::Base,
+                Environment::Development,
+                Environment::Staging,
+                Environment::Production,
+                Environment::Edge,
+            ] {
+                println!("  {} {}: {}", "•".blue(), env, protection.level(env));
+            }
+            if !protection.admins.is_empty() {
+                let admins: Vec<&str> = protection.admins.iter().map(String::as_str).collect();
+                println!("  Admins: {}", admins.join(", "));
+            }
+        }
+        ProtectionCommands::Set { env, level } => {
+            let (env, level): (Environment, ProtectionLevel) = (env.into(), level.into());
+            protection = protection.with_level(env, level);
+            manager.set_protection(protection)?;
+            println!("{}", format!("{} is now {}", env, level).green().bold());
+        }
+        ProtectionCommands::AddAdmin { user } => {
+            manager.set_protection(protection.with_admin(&user))?;
+            println!("{}", format!("Added protection admin {}", user).green().bold());
+        }
+        ProtectionCommands::RemoveAdmin { user } => {
+            if !protection.admins.remove(&user) {
+                anyhow::bail!("{} is not a protection admin", user);
+            }
+            manager.set_protection(protection)?;
+            println!("{}", format!("Removed protection admin {}", user).green().bold());
+        }
+    }
+
+    Ok(())
+}
+
+fn run_secret_command(
+    manager: &ConfigManager,
+    has_key: bool,
+    output: Option<Output>,
+    command: SecretCommands,
+) -> anyhow::Result<()> {
+    match command {
+        SecretCommands::Rotate {
+            namespace,
+            key,
+            env,
+            user,
+            reviewed_by,
+            message,
+        } => {
+            if !has_key {
+                anyhow::bail!("Encryption key required for secrets. Set --encryption-key (or LLM_CONFIG_KEY) or --passphrase.");
+            }
+            let env: Environment = env.into();
+            let current = manager
+                .get_untracked(&namespace, &key, env)?
+                .ok_or_else(|| not_found(&namespace, &key, env))?;
+            if !current.value.is_secret() {
+                return Err(ConfigError::ValidationError(format!("{}:{} is not a secret", namespace, key)).into());
+            }
+
+            let mut value = prompt::secret(&key)?;
+            let mut options = write_options(reviewed_by);
+            options.change_description =
+                Some(message.unwrap_or_else(|| format!("Rotated secret (replaces version {})", current.version)));
+            // A rotation racing another write fails instead of overwriting it
+            options.expected_version = Some(current.version);
+            let result = manager.set_secret_with_options(&namespace, &key, value.as_bytes(), env, &user, &options);
+            value.zeroize();
+            let entry = result?;
+
+            match output {
+                Some(output) => {
+                    let rotated = serde_json::json!({
+                        "namespace": namespace,
+                        "key": key,
+                        "environment": env,
+                        "previous_version": current.version,
+                        "version": entry.version,
+                    });
+                    output.print(&rotated, || entry.version.to_string())?;
+                }
+                None => println!(
+                    "{} Rotated {}:{} from version {} to {}",
+                    "✓".green().bold(),
+                    namespace,
+                    key,
+                    current.version,
+                    entry.version
+                ),
             }
+        }
+    }
 
-            let deleted = manager.delete(&namespace, &key, env)?;
+    Ok(())
+}
 
-            if deleted {
-                println!("{}", "Configuration deleted successfully!".green().bold());
+fn run_read_only_command(manager: &ConfigManager, command: ReadOnlyCommands) -> anyhow::Result<()> {
+    match command {
+        ReadOnlyCommands::Status => match manager.read_only_mode()? {
+            Some(mode) => {
+                println!(
+                    "{}",
+                    format!("Read-only since {} (enabled by {})", mode.enabled_at.to_rfc3339(), mode.enabled_by)
+                        .yellow()
+                        .bold()
+                );
+                if let Some(reason) = mode.reason {
+                    println!("  Reason: {}", reason);
+                }
+            }
+            None => println!("{}", "Read-write".green().bold()),
+        },
+        ReadOnlyCommands::Enable { reason, user } => {
+            manager.enable_read_only(&user, reason)?;
+            println!("{}", "Store is now read-only".yellow().bold());
+        }
+        ReadOnlyCommands::Disable => {
+            if manager.disable_read_only()? {
+                println!("{}", "Store accepts writes again".green().bold());
             } else {
-                println!("{}", "Configuration not found".yellow());
+                println!("Store was not read-only");
             }
         }
+    }
 
-        Commands::History {
-            namespace,
-            key,
+    Ok(())
+}
+
+fn run_dedup_command(manager: &ConfigManager, command: DedupCommands) -> anyhow::Result<()> {
+    match command {
+        DedupCommands::Status => {
+            match manager.value_dedup() {
+                Some(min_size) => println!("{}", format!("Deduplicating values from {} bytes", min_size).green().bold()),
+                None => println!("{}", "Values are stored inline".yellow().bold()),
+            }
+            let stats = manager.blob_stats()?;
+            println!(
+                "  {} blobs ({} bytes) referenced {} times, saving {} bytes",
+                stats.blobs, stats.bytes, stats.references, stats.bytes_saved
+            );
+        }
+        DedupCommands::Enable { min_size } => {
+            manager.set_value_dedup(Some(min_size))?;
+            println!(
+                "{}",
+                format!("Deduplicating values from {} bytes; run `compact` to move existing values", min_size)
+                    .green()
+                    .bold()
+            );
+        }
+        DedupCommands::Disable => {
+            manager.set_value_dedup(None)?;
+            println!("{}", "New values are stored inline; existing blobs stay in use".green().bold());
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the size threshold of `dedup enable`
+fn parse_min_size(s: &str) -> Result<usize, String> {
+    let size = ValueType::Size.parse(s)?.as_bytes().unwrap_or_default();
+    usize::try_from(size).map_err(|e| e.to_string())
+}
+
+fn run_template_command(
+    manager: &ConfigManager,
+    audit_dir: &std::path::Path,
+    command: TemplateCommands,
+) -> anyhow::Result<()> {
+    let templates = manager.templates();
+
+    match command {
+        TemplateCommands::Put {
+            name,
+            content,
+            file,
+            description,
+            defaults,
             env,
+            user,
+            reviewed_by,
+            message,
         } => {
+            let content = match (content, file) {
+                (Some(content), _) => content,
+                (None, Some(file)) => std::fs::read_to_string(file)?,
+                (None, None) => anyhow::bail!("Provide the template content or --file"),
+            };
+
+            let mut template = Template::new(name, content)?;
+            if let Some(description) = description {
+                template = template.with_description(description);
+            }
+            for (var, value) in defaults {
+                template = template.with_default(var, value);
+            }
+
+            let mut options = write_options(reviewed_by);
+            options.change_description = message;
+            let stored = templates.put(&template, env.into(), &user, &options)?;
+            println!(
+                "{}",
+                format!("Saved template {} v{} in {}", stored.template.name, stored.version, stored.environment)
+                    .green()
+                    .bold()
+            );
+        }
+        TemplateCommands::Show { name, env } => {
+            let stored = templates
+                .get(&name, env.into())?
+                .ok_or_else(|| anyhow::anyhow!("Template not found: {}", name))?;
+
+            println!(
+                "{}",
+                format!("{} v{} ({})", stored.template.name, stored.version, stored.environment).green().bold()
+            );
+            if let Some(description) = &stored.template.description {
+                println!("  {}", description);
+            }
+            println!("  Variables: {}", stored.template.required_vars.join(", "));
+            let mut defaults: Vec<_> = stored.template.defaults.iter().collect();
+            defaults.sort();
+            for (var, value) in defaults {
+                println!("    {} = {}", var, value);
+            }
+            println!("  Updated: {} by {}", stored.updated_at.format("%Y-%m-%d %H:%M:%S"), stored.updated_by);
+            println!();
+            println!("{}", stored.template.content);
+        }
+        TemplateCommands::List { env } => {
             let env: Environment = env.into();
-            let history = manager.get_history(&namespace, &key, env)?;
+            let stored = templates.list(env)?;
 
-            if history.is_empty() {
-                println!("{}", "No version history found".yellow());
+            if stored.is_empty() {
+                println!("{}", "No templates found".yellow());
                 return Ok(());
             }
 
-            println!("{}", format!("Version history for {}:{}", namespace, key).green().bold());
+            println!("{}", format!("Templates in {}", env).green().bold());
+            for stored in stored {
+                println!(
+                    "  {} {} v{} ({})",
+                    "•".blue(),
+                    stored.template.name.bold(),
+                    stored.version,
+                    stored.environment
+                );
+            }
+        }
+        TemplateCommands::Render { name, vars, env, user } => {
+            let rendered = templates.render(&name, env.into(), &vars.into_iter().collect())?;
+
+            let event = AuditEvent::new(
+                AuditEventType::TemplateRendered {
+                    name: rendered.name.clone(),
+                    environment: rendered.environment.to_string(),
+                    version: rendered.version,
+                },
+                user,
+            );
+            FileAuditStorage::new(audit_dir)?.store(&event)?;
+
+            println!("{}", rendered.output);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_blueprint_command(manager: &ConfigManager, command: BlueprintCommands) -> anyhow::Result<()> {
+    let blueprints = manager.blueprints();
+
+    match command {
+        BlueprintCommands::Put {
+            file,
+            format,
+            user,
+            message,
+        } => {
+            let format = match format {
+                Some(format) => format.into(),
+                None => DocumentFormat::from_path(&file).with_context(|| {
+                    format!("cannot tell the format of {}; pass --format", file.display())
+                })?,
+            };
+            let blueprint = Blueprint::parse(&std::fs::read_to_string(&file)?, format)?;
+
+            let mut options = write_options(None);
+            options.change_description = message;
+            let stored = blueprints.put(&blueprint, &user, &options)?;
+            println!(
+                "{}",
+                format!("Saved blueprint {} v{}", stored.blueprint.name, stored.version)
+                    .green()
+                    .bold()
+            );
+        }
+        BlueprintCommands::Show { name } => {
+            let stored = blueprints
+                .get(&name)?
+                .ok_or_else(|| anyhow::anyhow!("Blueprint not found: {}", name))?;
+            let blueprint = &stored.blueprint;
+
+            println!("{}", format!("{} v{}", blueprint.name, stored.version).green().bold());
+            if let Some(description) = &blueprint.description {
+                println!("  {}", description);
+            }
+            println!("  Variables: {}", blueprint.variables()?.join(", "));
+            for (var, value) in &blueprint.defaults {
+                println!("    {} = {}", var, value);
+            }
+            println!("  Updated: {} by {}", stored.updated_at.format("%Y-%m-%d %H:%M:%S"), stored.updated_by);
             println!();
+            for (key, entry) in &blueprint.entries {
+                let kind = match (entry.secret, &entry.value_type) {
+                    (true, _) => " (secret)".to_string(),
+                    (false, Some(kind)) => format!(" ({})", kind),
+                    (false, None) => String::new(),
+                };
+                println!("  {}{} = {}", key, kind, entry.template);
+            }
+        }
+        BlueprintCommands::List => {
+            let stored = blueprints.list()?;
 
-            for version in history {
-                println!("  {} Version {}", "→".blue(), version.version.to_string().bold());
-                println!("    Value: {}", format_value(&version.value));
-                println!("    Created: {} by {}", version.created_at.format("%Y-%m-%d %H:%M:%S"), version.created_by);
-                if let Some(desc) = version.change_description {
-                    println!("    Note: {}", desc);
-                }
-                println!();
+            if stored.is_empty() {
+                println!("{}", "No blueprints found".yellow());
+                return Ok(());
+            }
+
+            println!("{}", "Blueprints".green().bold());
+            for stored in stored {
+                println!(
+                    "  {} {} v{} ({} entries){}",
+                    "•".blue(),
+                    stored.blueprint.name.bold(),
+                    stored.version,
+                    stored.blueprint.entries.len(),
+                    stored
+                        .blueprint
+                        .description
+                        .map(|description| format!(" - {}", description))
+                        .unwrap_or_default()
+                );
+            }
+        }
+        BlueprintCommands::Apply {
+            name,
+            namespace,
+            vars,
+            env,
+            user,
+            reviewed_by,
+            message,
+        } => {
+            let mut options = write_options(reviewed_by);
+            options.change_description = message;
+            let applied = blueprints.apply(&name, &namespace, env.into(), &vars.into_iter().collect(), &user, &options)?;
+
+            println!(
+                "{}",
+                format!(
+                    "Applied blueprint {} v{} to {} ({}): {} entries created",
+                    applied.blueprint,
+                    applied.version,
+                    applied.namespace,
+                    applied.environment,
+                    applied.created.len()
+                )
+                .green()
+                .bold()
+            );
+            for key in &applied.created {
+                let secret = if applied.secrets.contains(key) { " (secret)" } else { "" };
+                println!("  {} {}{}", "•".blue(), key, secret);
             }
         }
+    }
 
-        Commands::Rollback {
+    Ok(())
+}
+
+/// Parse a name=value pair
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected name=value, got {}", s))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn run_k8s_command(manager: &ConfigManager, command: K8sCommands) -> anyhow::Result<()> {
+    match command {
+        K8sCommands::Export {
             namespace,
-            key,
-            version,
             env,
+            name,
+            k8s_namespace,
+            sealed,
+            cert,
+            output,
+        } => {
+            let options = K8sExportOptions {
+                name,
+                k8s_namespace,
+            };
+            let manifests = manager.k8s_manifests(&namespace, env.into(), &options)?;
+
+            let mut documents = vec![manifests.config_map.to_yaml()];
+            if let Some(secret) = &manifests.secret {
+                documents.push(if sealed {
+                    kubeseal(&secret.to_yaml(), cert.as_deref())?
+                } else {
+                    secret.to_yaml()
+                });
+            }
+            let yaml = documents.join("---\n");
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, yaml)?;
+                    eprintln!("{}", format!("Wrote manifests to {}", path.display()).green().bold());
+                }
+                None => print!("{}", yaml),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_ns_command(manager: &ConfigManager, command: NsCommands) -> anyhow::Result<()> {
+    match command {
+        NsCommands::Clone {
+            src,
+            dst,
+            envs,
+            user,
+            reviewed_by,
         } => {
+            let envs: Vec<Environment> = envs.into_iter().map(Environment::from).collect();
+            let copies = manager.clone_namespace_with_options(&src, &dst, &envs, &user, &write_options(reviewed_by))?;
+            println!(
+                "{}",
+                format!("Cloned {} configs from {} to {}", copies.len(), src, dst).green().bold()
+            );
+        }
+        NsCommands::Rename {
+            src,
+            dst,
+            user,
+            reviewed_by,
+        } => {
+            let moved = manager.rename_namespace_with_options(&src, &dst, &user, &write_options(reviewed_by))?;
+            println!(
+                "{}",
+                format!("Renamed {} to {} ({} configs)", src, dst, moved.len()).green().bold()
+            );
+        }
+        NsCommands::Parents => {
+            let settings = manager.namespace_settings();
+            if settings.namespaces.is_empty() {
+                println!("{}", "No namespace has a parent".yellow());
+            } else {
+                println!("{}", "Namespace inheritance:".green().bold());
+            }
+            for namespace in settings.namespaces.keys() {
+                println!("  {} {}", "•".blue(), settings.lineage(namespace).join(" -> "));
+            }
+        }
+        NsCommands::SetParent { namespace, parent } => {
+            manager.set_namespace_parent(&namespace, Some(&parent))?;
+            let lineage = manager.namespace_settings().lineage(&namespace);
+            println!("{}", format!("{} now inherits from {}", namespace, parent).green().bold());
+            println!("  {} {}", "Lookup order:".bold(), lineage.join(" -> "));
+        }
+        NsCommands::ClearParent { namespace } => {
+            manager.set_namespace_parent(&namespace, None)?;
+            println!("{}", format!("{} no longer inherits from another namespace", namespace).green().bold());
+        }
+    }
+    Ok(())
+}
+
+fn run_drift_command(manager: &ConfigManager, output: Option<Output>, command: DriftCommands) -> anyhow::Result<()> {
+    match command {
+        DriftCommands::Check { file, env, format } => {
             let env: Environment = env.into();
+            let desired = DesiredState::from_yaml(&std::fs::read_to_string(&file)?)?;
+            let plan = manager.check_drift(&desired, env)?;
 
-            if let Some(entry) = manager.rollback(&namespace, &key, env, version)? {
-                println!("{}", "Rollback successful!".green().bold());
-                println!("  New version: {}", entry.version);
-                println!("  Value: {}", format_value(&entry.value));
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    if plan.is_empty() {
+                        println!("{}", format!("No drift from {} ({})", file.display(), env).green().bold());
+                    }
+                    for change in &plan.changes {
+                        let render = |value: &Option<serde_json::Value>| {
+                            value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+                        };
+                        let (marker, detail) = match change.action {
+                            PlanAction::Create => ("+".green(), format!("missing, want {}", render(&change.new_value))),
+                            PlanAction::Delete => ("-".red(), format!("not declared, is {}", render(&change.old_value))),
+                            PlanAction::Update => ("~".yellow(), format!(
+                                "is {}, want {}",
+                                render(&change.old_value),
+                                render(&change.new_value)
+                            )),
+                        };
+                        println!("  {} {}/{}: {}", marker, change.namespace, change.key.bold(), detail);
+                    }
+                }
+                OutputFormat::Json => print!("{}", plan.to_json()),
+                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&plan)?),
+            }
+
+            if !plan.is_empty() {
+                anyhow::bail!(
+                    "{} keys drifted ({} missing, {} not declared, {} differing)",
+                    plan.changes.len(),
+                    plan.summary.create,
+                    plan.summary.delete,
+                    plan.summary.update
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_sync_report(report: &SyncReport) {
+    println!(
+        "{} {}: {} applied, {} skipped",
+        "Synced".green().bold(),
+        &report.commit[..report.commit.len().min(12)],
+        report.applied.len(),
+        report.skipped.len()
+    );
+    for change in &report.applied {
+        println!("  {} {}/{} ({})", "✓".green(), change.namespace, change.key.bold(), change.environment);
+    }
+    for skipped in &report.skipped {
+        let change = &skipped.change;
+        println!(
+            "  {} {}/{} ({}): {}",
+            "✗".yellow(),
+            change.namespace,
+            change.key.bold(),
+            change.environment,
+            skipped.reason
+        );
+    }
+}
+
+/// Seal a Secret manifest with the kubeseal CLI
+fn kubeseal(secret_yaml: &str, cert: Option<&std::path::Path>) -> anyhow::Result<String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut command = Command::new("kubeseal");
+    command.args(["--format", "yaml"]);
+    if let Some(cert) = cert {
+        command.arg("--cert").arg(cert);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run kubeseal (is it installed?): {}", e))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(secret_yaml.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("kubeseal exited with {}", output.status);
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn run_environment_command(
+    manager: &ConfigManager,
+    command: EnvironmentCommands,
+) -> anyhow::Result<()> {
+    let graph = match command {
+        EnvironmentCommands::Show => manager.environment_graph(),
+        EnvironmentCommands::SetParent { env, parent } => {
+            let (env, parent): (Environment, Environment) = (env.into(), parent.into());
+            let graph = manager.environment_graph().with_parent(env, parent);
+            manager.set_environment_graph(graph.clone())?;
+            println!("{}", format!("{} now inherits from {}", env, parent).green().bold());
+            graph
+        }
+        EnvironmentCommands::Reset { legacy } => {
+            let graph = if legacy {
+                EnvironmentGraph::legacy()
+            } else {
+                EnvironmentGraph::new()
+            };
+            manager.set_environment_graph(graph.clone())?;
+            println!("{}", "Environment inheritance reset".green().bold());
+            graph
+        }
+    };
+
+    println!("{}", "Environment inheritance:".green().bold());
+    for env in ENVIRONMENTS {
+        let chain: Vec<String> = graph.chain(env).iter().map(ToString::to_string).collect();
+        println!("  {} {}: {}", "•".blue(), env, chain.join(" -> "));
+    }
+
+    Ok(())
+}
+
+/// Add or remove tags of a configuration
+fn run_tag_command(manager: &ConfigManager, command: TagCommands) -> anyhow::Result<()> {
+    let (namespace, key, tags, env, user, reviewed_by, add) = match command {
+        TagCommands::Add { namespace, key, tags, env, user, reviewed_by } => {
+            (namespace, key, tags, env, user, reviewed_by, true)
+        }
+        TagCommands::Remove { namespace, key, tags, env, user, reviewed_by } => {
+            (namespace, key, tags, env, user, reviewed_by, false)
+        }
+    };
+
+    let env: Environment = env.into();
+    let entry = manager
+        .get(&namespace, &key, env)?
+        .ok_or_else(|| anyhow::anyhow!("Configuration not found: {}:{}", namespace, key))?;
+
+    let mut current = entry.metadata.tags;
+    if add {
+        current.extend(tags);
+    } else {
+        current.retain(|tag| !tags.contains(tag));
+    }
+
+    let entry = manager.set_metadata_with_options(
+        &namespace,
+        &key,
+        env,
+        current,
+        entry.metadata.description,
+        &user,
+        &write_options(reviewed_by),
+    )?;
+
+    if entry.metadata.tags.is_empty() {
+        println!("{}", "No tags".yellow());
+    } else {
+        println!("{} {}", "Tags:".green().bold(), entry.metadata.tags.join(", "));
+    }
+    Ok(())
+}
+
+/// Write options carrying an optional reviewer
+fn write_options(reviewed_by: Option<String>) -> SetOptions {
+    match reviewed_by {
+        Some(reviewer) => SetOptions::default().with_reviewed_by(reviewer),
+        None => SetOptions::default(),
+    }
+}
+
+fn run_role_command(store: &RoleStore, output: Option<Output>, command: RoleCommands) -> anyhow::Result<()> {
+    match command {
+        RoleCommands::Define {
+            name,
+            permissions,
+            namespaces,
+            description,
+        } => {
+            let mut definition = RoleDefinition::new(&name);
+            definition.permissions = permissions.into_iter().collect();
+            definition.namespace_patterns = namespaces;
+            definition.description = description;
+
+            store.save(&definition)?;
+            println!("{}", format!("Role {} saved", name).green().bold());
+        }
+
+        RoleCommands::List { format } => {
+            let definitions = store.load_all()?;
+
+            if definitions.is_empty() && output.is_none() {
+                println!("{}", "No custom roles defined".yellow());
+                return Ok(());
+            }
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    println!("{}", "Custom roles".green().bold());
+                    println!();
+                    for definition in definitions {
+                        let mut permissions: Vec<String> =
+                            definition.permissions.iter().map(|p| p.to_string()).collect();
+                        permissions.sort();
+
+                        println!("  {} {}", "•".blue(), definition.name.bold());
+                        if let Some(description) = &definition.description {
+                            println!("    {}", description);
+                        }
+                        println!("    Permissions: {}", permissions.join(", "));
+                        if !definition.namespace_patterns.is_empty() {
+                            println!("    Namespaces: {}", definition.namespace_patterns.join(", "));
+                        }
+                        println!();
+                    }
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&definitions)?);
+                }
+                OutputFormat::Yaml => {
+                    println!("{}", serde_yaml::to_string(&definitions)?);
+                }
+            }
+        }
+
+        RoleCommands::Delete { name } => {
+            if store.delete(&name)? {
+                println!("{}", format!("Role {} deleted", name).green().bold());
             } else {
-                println!("{}", format!("Version {} not found", version).yellow());
+                println!("{}", format!("Role {} not found", name).yellow());
             }
         }
 
-        Commands::Export { path } => {
-            let count = manager.export_all(&path)?;
-            println!("{}", format!("Exported {} configurations to {}", count, path.display()).green().bold());
+        RoleCommands::Assign { user, role, scope } => {
+            if let Role::Custom(ref name) = role {
+                if store.get(name)?.is_none() {
+                    println!("{}", format!("Warning: custom role {} is not defined", name).yellow());
+                }
+            }
+
+            let assignment = RoleAssignment { user, role, scope };
+            store.assign(&assignment)?;
+            println!(
+                "{}",
+                format!(
+                    "Assigned {} to {} ({})",
+                    assignment.role,
+                    assignment.user,
+                    assignment.scope.as_deref().unwrap_or("all namespaces")
+                )
+                .green()
+                .bold()
+            );
         }
 
-        Commands::Keygen => {
-            let key = SecretKey::generate(Algorithm::Aes256Gcm)?;
-            println!("{}", "Generated encryption key:".green().bold());
-            println!();
-            println!("{}", key.to_base64());
-            println!();
-            println!("Set this key using:");
-            println!("  {} export LLM_CONFIG_KEY=\"{}\"", "•".blue(), key.to_base64());
-            println!("  {} llm-config --encryption-key <key> ...", "•".blue());
+        RoleCommands::Revoke { user, role, scope } => {
+            let assignment = RoleAssignment { user, role, scope };
+            if store.revoke(&assignment)? {
+                println!("{}", format!("Revoked {} from {}", assignment.role, assignment.user).green().bold());
+            } else {
+                println!("{}", "Role assignment not found".yellow());
+            }
         }
     }
 
     Ok(())
 }
 
-fn parse_value(s: &str) -> anyhow::Result<ConfigValue> {
-    // Try to parse as JSON first
-    if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(s) {
-        return Ok(match json_value {
-            serde_json::Value::String(s) => ConfigValue::String(s),
-            serde_json::Value::Number(n) => {
-                if let Some(i) = n.as_i64() {
-                    ConfigValue::Integer(i)
-                } else if let Some(f) = n.as_f64() {
-                    ConfigValue::Float(f)
-                } else {
-                    ConfigValue::String(s.to_string())
+fn run_group_command(store: &RoleStore, output: Option<Output>, command: GroupCommands) -> anyhow::Result<()> {
+    match command {
+        GroupCommands::Define {
+            name,
+            members,
+            external_groups,
+            description,
+        } => {
+            let mut group = store.get_group(&name)?.unwrap_or_else(|| Group::new(&name));
+            group.members = members.into_iter().collect();
+            group.external_groups = external_groups.into_iter().collect();
+            group.description = description;
+
+            store.save_group(&group)?;
+            println!("{}", format!("Group {} saved", name).green().bold());
+        }
+
+        GroupCommands::List { format } => {
+            let groups = store.load_groups()?;
+
+            if groups.is_empty() && output.is_none() {
+                println!("{}", "No groups defined".yellow());
+                return Ok(());
+            }
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    println!("{}", "Groups".green().bold());
+                    println!();
+                    for group in groups {
+                        println!("  {} {}", "•".blue(), group.name.bold());
+                        if let Some(description) = &group.description {
+                            println!("    {}", description);
+                        }
+                        if !group.members.is_empty() {
+                            let members: Vec<&str> = group.members.iter().map(String::as_str).collect();
+                            println!("    Members: {}", members.join(", "));
+                        }
+                        if !group.external_groups.is_empty() {
+                            let external: Vec<&str> =
+                                group.external_groups.iter().map(String::as_str).collect();
+                            println!("    External groups: {}", external.join(", "));
+                        }
+                        let roles: Vec<String> = group
+                            .roles
+                            .iter()
+                            .map(|role| match &role.scope {
+                                Some(scope) => format!("{} ({})", role.role, scope),
+                                None => role.role.to_string(),
+                            })
+                            .collect();
+                        println!(
+                            "    Roles: {}",
+                            if roles.is_empty() { "none".to_string() } else { roles.join(", ") }
+                        );
+                        println!();
+                    }
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&groups)?);
+                }
+                OutputFormat::Yaml => {
+                    println!("{}", serde_yaml::to_string(&groups)?);
                 }
             }
-            serde_json::Value::Bool(b) => ConfigValue::Boolean(b),
-            serde_json::Value::Array(arr) => {
-                let values: Result<Vec<_>, _> = arr
-                    .iter()
-                    .map(|v| parse_value(&v.to_string()))
-                    .collect();
-                ConfigValue::Array(values?)
+        }
+
+        GroupCommands::Delete { name } => {
+            if store.delete_group(&name)? {
+                println!("{}", format!("Group {} deleted", name).green().bold());
+            } else {
+                println!("{}", format!("Group {} not found", name).yellow());
             }
-            serde_json::Value::Object(_) => {
-                // For objects, store as JSON string
-                ConfigValue::String(s.to_string())
+        }
+
+        GroupCommands::Assign { group, role, scope } => {
+            let Some(mut definition) = store.get_group(&group)? else {
+                anyhow::bail!("Group {} not found", group);
+            };
+            if let Role::Custom(ref name) = role {
+                if store.get(name)?.is_none() {
+                    println!("{}", format!("Warning: custom role {} is not defined", name).yellow());
+                }
+            }
+
+            let role = GroupRole { role, scope };
+            definition.assign(role.clone());
+            store.save_group(&definition)?;
+            println!(
+                "{}",
+                format!(
+                    "Assigned {} to group {} ({})",
+                    role.role,
+                    group,
+                    role.scope.as_deref().unwrap_or("all namespaces")
+                )
+                .green()
+                .bold()
+            );
+        }
+
+        GroupCommands::Revoke { group, role, scope } => {
+            let Some(mut definition) = store.get_group(&group)? else {
+                anyhow::bail!("Group {} not found", group);
+            };
+
+            let role = GroupRole { role, scope };
+            if definition.revoke(&role) {
+                store.save_group(&definition)?;
+                println!("{}", format!("Revoked {} from group {}", role.role, group).green().bold());
+            } else {
+                println!("{}", "Group role assignment not found".yellow());
             }
-            serde_json::Value::Null => ConfigValue::String(String::new()),
-        });
+        }
     }
 
-    // Otherwise treat as string
-    Ok(ConfigValue::String(s.to_string()))
+    Ok(())
+}
+
+/// Parse a value given on the command line: JSON if it parses (arrays and
+/// objects recursively, like values set through the API), a string otherwise
+fn parse_value(s: &str) -> ConfigValue {
+    serde_json::from_str::<serde_json::Value>(s).map_or_else(|_| ConfigValue::String(s.to_string()), ConfigValue::from)
 }
 
 fn format_value(value: &ConfigValue) -> String {
@@ -423,7 +4257,10 @@ fn format_value(value: &ConfigValue) -> String {
         ConfigValue::Float(f) => f.to_string(),
         ConfigValue::Boolean(b) => b.to_string(),
         ConfigValue::Array(arr) => format!("[{}]", arr.iter().map(format_value).collect::<Vec<_>>().join(", ")),
-        ConfigValue::Object(_) => "<object>".to_string(),
+        ConfigValue::Object(_) => serde_json::to_string(&value.redacted()).unwrap_or_default(),
+        ConfigValue::Duration(_) | ConfigValue::Size(_) | ConfigValue::Url(_) => {
+            value.typed_text().unwrap_or_default()
+        }
         ConfigValue::Secret(_) => "<encrypted>".yellow().to_string(),
     }
 }
@@ -0,0 +1,171 @@
+//! Machine-readable output and exit codes for scripting
+//!
+//! `--output json|yaml|plain` replaces the colored human output of the main
+//! commands with a document on stdout (`plain`: just the value, version or
+//! key). Errors are reported on stderr in the same format, and the exit code
+//! tells the error class apart without parsing messages.
+//!
+//! `get --raw` and `history --raw` print nothing but the value, as is:
+//! revealed secrets are written byte for byte, with no trailing newline
+//! unless stdout is a terminal. Colors are only used on a terminal and never
+//! with `NO_COLOR` set.
+
+use crate::remote::RemoteError;
+use clap::ValueEnum;
+use llm_config_core::{ConfigError, ConfigValue, StorageError};
+use serde::Serialize;
+use std::ffi::OsStr;
+use std::io::{self, IsTerminal, Write};
+
+/// Any error not covered by a more specific code
+pub const EXIT_FAILURE: i32 = 1;
+/// The config, version or namespace does not exist
+pub const EXIT_NOT_FOUND: i32 = 2;
+/// Invalid arguments or values
+pub const EXIT_VALIDATION: i32 = 3;
+/// Missing or wrong key, or the environment's protection rejected the write
+pub const EXIT_AUTH: i32 = 4;
+/// The value was changed concurrently or already exists
+pub const EXIT_CONFLICT: i32 = 5;
+
+/// Format of `--output`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    Json,
+    Yaml,
+    Plain,
+}
+
+impl Output {
+    /// Print `value` as a document, or `plain` in plain mode
+    pub fn print<T: Serialize>(self, value: &T, plain: impl FnOnce() -> String) -> anyhow::Result<()> {
+        match self {
+            Output::Json => println!("{}", serde_json::to_string_pretty(value)?),
+            Output::Yaml => print!("{}", serde_yaml::to_string(value)?),
+            Output::Plain => println!("{}", plain()),
+        }
+        Ok(())
+    }
+
+    /// Print an error on stderr
+    pub fn print_error(self, err: &anyhow::Error) {
+        let error = ErrorOutput {
+            error: error_kind(err),
+            message: err.to_string(),
+            exit_code: exit_code(err),
+        };
+        match self {
+            Output::Json => eprintln!("{}", serde_json::to_string(&error).unwrap_or_default()),
+            Output::Yaml => eprint!("{}", serde_yaml::to_string(&error).unwrap_or_default()),
+            Output::Plain => eprintln!("Error: {}", error.message),
+        }
+    }
+}
+
+/// Whether to color the human output, given `NO_COLOR` and whether stdout is a terminal
+pub fn use_color(no_color: Option<&OsStr>, terminal: bool) -> bool {
+    terminal && no_color.map_or(true, OsStr::is_empty)
+}
+
+/// Text of `value` for `--raw`: strings as is, arrays and objects as JSON
+///
+/// Fails for secrets that were not revealed, rather than printing a placeholder.
+pub fn raw_text(value: &ConfigValue) -> anyhow::Result<String> {
+    Ok(match value {
+        ConfigValue::String(s) => s.clone(),
+        ConfigValue::Secret(_) => anyhow::bail!("The value is an encrypted secret; use --reveal to print it"),
+        ConfigValue::Array(_) | ConfigValue::Object(_) => serde_json::to_string(value)?,
+        ConfigValue::Integer(i) => i.to_string(),
+        ConfigValue::Float(f) => f.to_string(),
+        ConfigValue::Boolean(b) => b.to_string(),
+        ConfigValue::Duration(_) | ConfigValue::Size(_) | ConfigValue::Url(_) => {
+            value.typed_text().unwrap_or_default()
+        }
+    })
+}
+
+/// Write `text` to stdout as is, ending it with a newline only on a terminal
+pub fn print_raw(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(text.as_bytes())?;
+    if stdout.is_terminal() {
+        stdout.write_all(b"\n")?;
+    }
+    stdout.flush()
+}
+
+/// Error document of the structured formats
+#[derive(Serialize)]
+struct ErrorOutput {
+    error: &'static str,
+    message: String,
+    exit_code: i32,
+}
+
+/// Exit code for `err`, by the first config or API error in its chain
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    if let Some(err) = err.chain().find_map(|cause| cause.downcast_ref::<RemoteError>()) {
+        return err.exit_code();
+    }
+    let Some(err) = err.chain().find_map(|cause| cause.downcast_ref::<ConfigError>()) else {
+        return EXIT_FAILURE;
+    };
+    match err {
+        ConfigError::NotFound(_) | ConfigError::StorageError(StorageError::NotFound(_)) => EXIT_NOT_FOUND,
+        ConfigError::ValidationError(_)
+        | ConfigError::TypeChange(_)
+        | ConfigError::TemplateError(_)
+        | ConfigError::InvalidOperation(_)
+        | ConfigError::StorageError(StorageError::InvalidPath(_)) => EXIT_VALIDATION,
+        ConfigError::CryptoError(_) | ConfigError::Protected(_) | ConfigError::Signature(_) => EXIT_AUTH,
+        ConfigError::Conflict(_)
+        | ConfigError::AlreadyExists(_)
+        | ConfigError::StorageError(StorageError::AlreadyExists(_)) => EXIT_CONFLICT,
+        ConfigError::StorageError(_) => EXIT_FAILURE,
+    }
+}
+
+/// Error class reported in structured error output
+fn error_kind(err: &anyhow::Error) -> &'static str {
+    match exit_code(err) {
+        EXIT_NOT_FOUND => "not_found",
+        EXIT_VALIDATION => "validation",
+        EXIT_AUTH => "auth",
+        EXIT_CONFLICT => "conflict",
+        _ => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let code = |err: ConfigError| exit_code(&err.into());
+        assert_eq!(code(ConfigError::NotFound("app:model".into())), EXIT_NOT_FOUND);
+        assert_eq!(code(StorageError::NotFound("namespace app".into()).into()), EXIT_NOT_FOUND);
+        assert_eq!(code(ConfigError::TypeChange("app:model".into())), EXIT_VALIDATION);
+        assert_eq!(code(ConfigError::Protected("production".into())), EXIT_AUTH);
+        assert_eq!(code(ConfigError::AlreadyExists("app".into())), EXIT_CONFLICT);
+
+        // Context added on top keeps the code of the underlying error
+        let err = anyhow::Error::new(ConfigError::Conflict("at version 2".into())).context("retry");
+        assert_eq!(exit_code(&err), EXIT_CONFLICT);
+        assert_eq!(error_kind(&err), "conflict");
+        assert_eq!(exit_code(&anyhow::anyhow!("kubeseal exited with 1")), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn test_raw_text_and_color() {
+        assert_eq!(raw_text(&ConfigValue::String(" sk-123\n".into())).unwrap(), " sk-123\n");
+        assert_eq!(raw_text(&ConfigValue::Integer(42)).unwrap(), "42");
+        let array = ConfigValue::Array(vec![ConfigValue::String("a".into()), ConfigValue::Boolean(true)]);
+        assert_eq!(raw_text(&array).unwrap(), r#"["a",true]"#);
+
+        assert!(use_color(None, true));
+        assert!(use_color(Some(OsStr::new("")), true));
+        assert!(!use_color(Some(OsStr::new("1")), true));
+        assert!(!use_color(None, false));
+    }
+}
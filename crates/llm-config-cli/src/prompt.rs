@@ -0,0 +1,78 @@
+//! Hidden terminal prompts for passphrases and secret values
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal;
+use std::io::{IsTerminal, Write};
+use zeroize::Zeroize;
+
+/// Read a passphrase from the terminal without echoing it
+///
+/// A passphrase for a store that has none yet is asked for twice, so a typo
+/// does not become the store's key.
+pub fn passphrase(new: bool) -> anyhow::Result<String> {
+    let passphrase = read_hidden("Passphrase: ")?;
+    if passphrase.is_empty() {
+        anyhow::bail!("passphrase must not be empty");
+    }
+    if new && read_hidden("Repeat passphrase: ")? != passphrase {
+        anyhow::bail!("passphrases do not match");
+    }
+    Ok(passphrase)
+}
+
+/// Read the value of the secret `key` from the terminal without echoing it
+///
+/// The value is asked for twice, so a typo is not stored. Fails if stdin is
+/// not a terminal: scripts pass the value with `--from-file` or `--stdin`.
+pub fn secret(key: &str) -> anyhow::Result<String> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("no value given and stdin is not a terminal; use --from-file or --stdin");
+    }
+    let mut value = read_hidden(&format!("Value for {}: ", key))?;
+    if value.is_empty() {
+        anyhow::bail!("secret must not be empty");
+    }
+    let mut repeated = read_hidden("Repeat value: ")?;
+    let matches = repeated == value;
+    repeated.zeroize();
+    if !matches {
+        value.zeroize();
+        anyhow::bail!("values do not match");
+    }
+    Ok(value)
+}
+
+fn read_hidden(label: &str) -> anyhow::Result<String> {
+    eprint!("{}", label);
+    std::io::stderr().flush()?;
+
+    terminal::enable_raw_mode()?;
+    let input = read_line();
+    terminal::disable_raw_mode()?;
+    eprintln!();
+    input
+}
+
+fn read_line() -> anyhow::Result<String> {
+    let mut input = String::new();
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => return Ok(input),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                anyhow::bail!("interrupted");
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => anyhow::bail!("interrupted"),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,516 @@
+//! Remote mode: run commands against an API server instead of a local store
+//!
+//! With `--server`, the config commands (`get`, `set`, `list`, `resolve`,
+//! `explain`, `delete`, `history` and `rollback`) go through the REST API,
+//! authenticated with `--token` as a bearer token. Values are encrypted and
+//! checked by the server, so no encryption key is needed locally. Structured
+//! `--output` prints the API's responses. Commands that work on the storage directory
+//! itself (fsck, compact, sync, ...) are not available remotely.
+
+use crate::output::{print_raw, Output, EXIT_AUTH, EXIT_CONFLICT, EXIT_FAILURE, EXIT_NOT_FOUND, EXIT_VALIDATION};
+use crate::{confirm, document_output, output_format, read_set_value, set_value_text, show_dry_run, show_trace, Commands, OutputFormat};
+use colored::Colorize;
+use llm_config_core::{Environment, Plan};
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+use std::fmt;
+
+/// An error response of the API server
+#[derive(Debug)]
+pub struct RemoteError {
+    pub status: StatusCode,
+    /// Machine-readable error code, if the server sent one
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl RemoteError {
+    /// Exit code for the response status
+    pub fn exit_code(&self) -> i32 {
+        match self.status {
+            StatusCode::NOT_FOUND => EXIT_NOT_FOUND,
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => EXIT_VALIDATION,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => EXIT_AUTH,
+            StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED => EXIT_CONFLICT,
+            _ => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.code {
+            Some(code) => write!(f, "{} ({}, {})", self.message, self.status, code),
+            None => write!(f, "{} ({})", self.message, self.status),
+        }
+    }
+}
+
+impl std::error::Error for RemoteError {}
+
+/// Minimal blocking client of the REST API
+pub struct RemoteClient {
+    base_url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl RemoteClient {
+    pub fn new(server: &str, token: Option<String>) -> anyhow::Result<Self> {
+        Ok(Self {
+            base_url: format!("{}/api/v1", server.trim_end_matches('/')),
+            token,
+            http: reqwest::Client::builder().user_agent(concat!("llm-config/", env!("CARGO_PKG_VERSION"))).build()?,
+            runtime: tokio::runtime::Builder::new_current_thread().enable_all().build()?,
+        })
+    }
+
+    /// Send a request, returning the JSON body (`Null` when empty) and the response headers
+    fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+        user: Option<&str>,
+    ) -> anyhow::Result<(Value, reqwest::header::HeaderMap)> {
+        let mut request = self.http.request(method, format!("{}{}", self.base_url, path)).query(query);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(user) = user {
+            request = request.header("x-user-id", user);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        self.runtime.block_on(async {
+            let response = request.send().await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let bytes = response.bytes().await?;
+            let json: Value = if bytes.is_empty() {
+                Value::Null
+            } else {
+                serde_json::from_slice(&bytes).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+            };
+
+            if !status.is_success() {
+                let message = match json.get("message").and_then(Value::as_str) {
+                    Some(message) => message.to_string(),
+                    None => status.canonical_reason().unwrap_or("request failed").to_string(),
+                };
+                return Err(RemoteError {
+                    status,
+                    code: json.get("code").and_then(Value::as_str).map(str::to_string),
+                    message,
+                }
+                .into());
+            }
+            Ok((json, headers))
+        })
+    }
+}
+
+/// Path segment of a namespace or key (namespaces contain `/`)
+fn segment(part: &str) -> String {
+    part.replace('%', "%25").replace('/', "%2F").replace('?', "%3F").replace('#', "%23")
+}
+
+/// Text of a JSON value as the local commands print it
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Run a command against the server
+pub fn run(client: &RemoteClient, output: Option<Output>, command: Commands) -> anyhow::Result<()> {
+    match command {
+        Commands::Get {
+            namespace,
+            key,
+            env,
+            with_overrides,
+            reveal,
+            reason,
+            format,
+            raw,
+        } => {
+            let env: Environment = env.into();
+            let output = document_output(output, format);
+            let mut query = vec![("env", env.to_string()), ("reveal", reveal.to_string())];
+            query.extend(reason.map(|reason| ("reason", reason)));
+
+            if with_overrides {
+                let path = format!("/configs/{}/resolved", segment(&namespace));
+                let (resolved, _) = client.send(Method::GET, &path, &query, None, None)?;
+                let value = resolved["values"].get(&key).cloned().ok_or_else(|| RemoteError {
+                    status: StatusCode::NOT_FOUND,
+                    code: None,
+                    message: format!("Configuration not found: {}:{}", namespace, key),
+                })?;
+                if raw {
+                    print_raw(&value_text(&value))?;
+                    return Ok(());
+                }
+                match output {
+                    Some(output) => output.print(&value, || value_text(&value))?,
+                    None => println!("{}", value_text(&value)),
+                }
+                return Ok(());
+            }
+
+            let path = format!("/configs/{}/{}", segment(&namespace), segment(&key));
+            let (entry, _) = client.send(Method::GET, &path, &query, None, None)?;
+            if raw {
+                print_raw(&value_text(&entry["value"]))?;
+                return Ok(());
+            }
+            match output {
+                Some(output) => output.print(&entry, || value_text(&entry["value"]))?,
+                None => {
+                    println!("{}", "Configuration:".green().bold());
+                    println!("  Namespace: {}", value_text(&entry["namespace"]));
+                    println!("  Key: {}", value_text(&entry["key"]));
+                    println!("  Environment: {}", value_text(&entry["environment"]));
+                    println!("  Value: {}", value_text(&entry["value"]));
+                    println!("  Version: {}", entry["version"]);
+                    println!("  Updated: {}", value_text(&entry["metadata"]["updated_at"]));
+                    println!("  Updated by: {}", value_text(&entry["metadata"]["updated_by"]));
+                }
+            }
+        }
+
+        Commands::Set {
+            namespace,
+            key,
+            value,
+            from_file,
+            stdin,
+            env,
+            user,
+            secret,
+            value_type,
+            merge,
+            allow_type_change,
+            expected_version,
+            reviewed_by,
+            message,
+            dry_run,
+        } => {
+            let env: Environment = env.into();
+            let bytes = read_set_value(&key, value, from_file.as_deref(), stdin)?;
+            let value = if secret {
+                // The API takes secrets as JSON strings
+                String::from_utf8(bytes).map_err(|_| anyhow::anyhow!("Secrets set through a server must be valid UTF-8"))?
+            } else {
+                set_value_text(bytes, from_file.as_deref())?
+            };
+            // Same rules as locally: JSON if it parses, a string otherwise
+            let value = if secret || value_type.is_some() {
+                Value::String(value)
+            } else {
+                serde_json::from_str(&value).unwrap_or(Value::String(value))
+            };
+            let body = serde_json::json!({
+                "value": value,
+                "env": env,
+                "user": user,
+                "secret": secret,
+                "type": value_type,
+                "merge": merge.map(|merge| merge.to_string()),
+                "allow_type_change": allow_type_change,
+                "expected_version": expected_version,
+                "reviewed_by": reviewed_by,
+                "change_description": message,
+            });
+            let path = format!("/configs/{}/{}", segment(&namespace), segment(&key));
+            let query = [("dry_run", dry_run.to_string())];
+            let (entry, _) = client.send(Method::POST, &path, &query, Some(body), Some(&user))?;
+
+            if dry_run {
+                return print_remote_dry_run(output, &entry);
+            }
+            match output {
+                Some(output) => output.print(&entry, || entry["version"].to_string())?,
+                None => {
+                    println!("{}", "Configuration saved successfully!".green().bold());
+                    println!("  Version: {}", entry["version"]);
+                    println!("  ID: {}", value_text(&entry["id"]));
+                }
+            }
+        }
+
+        Commands::List {
+            namespace,
+            env,
+            format,
+            reveal,
+            limit,
+            offset,
+            page_token,
+            sort_by,
+            desc,
+            prefix,
+            tag,
+        } => {
+            let env: Environment = env.into();
+            let mut query = vec![
+                ("env", env.to_string()),
+                ("reveal", reveal.to_string()),
+                ("offset", offset.to_string()),
+                ("sort_by", value_text(&serde_json::to_value(sort_by)?)),
+                ("descending", desc.to_string()),
+            ];
+            query.extend(limit.map(|limit| ("limit", limit.to_string())));
+            query.extend(page_token.map(|token| ("page_token", token)));
+            query.extend(prefix.map(|prefix| ("key_prefix", prefix)));
+            query.extend(tag.map(|tag| ("tag", tag)));
+            let path = format!("/configs/{}", segment(&namespace));
+            let (entries, headers) = client.send(Method::GET, &path, &query, None, None)?;
+            let entries = entries.as_array().cloned().unwrap_or_default();
+
+            if entries.is_empty() && output.is_none() {
+                println!("{}", "No configurations found".yellow());
+                return Ok(());
+            }
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    println!("{}", format!("Configurations in {} ({})", namespace, env).green().bold());
+                    println!();
+                    for entry in &entries {
+                        println!(
+                            "  {} {} = {}",
+                            "•".blue(),
+                            value_text(&entry["key"]).bold(),
+                            value_text(&entry["value"])
+                        );
+                        println!(
+                            "    Version: {} | Updated: {} by {}",
+                            entry["version"],
+                            value_text(&entry["metadata"]["updated_at"]),
+                            value_text(&entry["metadata"]["updated_by"])
+                        );
+                        println!();
+                    }
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+                OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&entries)?),
+            }
+
+            // On stderr, so structured output stays parseable
+            let next = headers
+                .get(llm_config_api::NEXT_PAGE_TOKEN_HEADER)
+                .and_then(|token| token.to_str().ok());
+            if let Some(token) = next {
+                eprintln!("Next page: --page-token '{}'", token);
+            }
+        }
+
+        Commands::Resolve {
+            namespace,
+            env,
+            format,
+            reveal,
+        } => {
+            let env: Environment = env.into();
+            let query = [("env", env.to_string()), ("reveal", reveal.to_string())];
+            let path = format!("/configs/{}/resolved", segment(&namespace));
+            let (resolved, _) = client.send(Method::GET, &path, &query, None, None)?;
+            let values = resolved["values"].as_object().cloned().unwrap_or_default();
+
+            if values.is_empty() && output.is_none() {
+                println!("{}", "No configurations found".yellow());
+                return Ok(());
+            }
+
+            match output_format(output, format) {
+                OutputFormat::Table => {
+                    println!("{}", format!("Resolved configuration of {} ({})", namespace, env).green().bold());
+                    println!();
+                    for (key, value) in &values {
+                        println!("  {} {} = {}", "•".blue(), key.bold(), value_text(value));
+                    }
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&values)?),
+                OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&values)?),
+            }
+        }
+
+        Commands::Explain {
+            namespace,
+            key,
+            env,
+            format,
+            reveal,
+        } => {
+            let env: Environment = env.into();
+            let query = [("env", env.to_string()), ("reveal", reveal.to_string())];
+            let path = format!("/configs/{}/{}/explain", segment(&namespace), segment(&key));
+            let (response, _) = client.send(Method::GET, &path, &query, None, None)?;
+            show_trace(output_format(output, format), &serde_json::from_value(response)?)?;
+        }
+
+        Commands::Delete {
+            namespace,
+            key,
+            env,
+            user,
+            reviewed_by,
+            yes,
+            dry_run,
+        } => {
+            let env: Environment = env.into();
+            if !yes && !dry_run && output.is_some() {
+                return Err(llm_config_core::ConfigError::ValidationError(
+                    "--yes is required with --output".to_string(),
+                )
+                .into());
+            }
+            if !yes
+                && !dry_run
+                && !confirm(&format!("Delete configuration {}:{} in {} environment?", namespace, key, env))?
+            {
+                println!("Cancelled.");
+                return Ok(());
+            }
+
+            let mut query = vec![("env", env.to_string()), ("dry_run", dry_run.to_string())];
+            query.extend(reviewed_by.map(|reviewer| ("reviewed_by", reviewer)));
+            let path = format!("/configs/{}/{}", segment(&namespace), segment(&key));
+            let (response, _) = client.send(Method::DELETE, &path, &query, None, Some(&user))?;
+            if dry_run {
+                return print_remote_dry_run(output, &response);
+            }
+
+            match output {
+                Some(output) => {
+                    let deleted = serde_json::json!({
+                        "namespace": namespace,
+                        "key": key,
+                        "environment": env,
+                        "deleted": true,
+                    });
+                    output.print(&deleted, || "deleted".to_string())?;
+                }
+                None => println!("{}", "Configuration deleted successfully!".green().bold()),
+            }
+        }
+
+        Commands::History {
+            command: None,
+            namespace: Some(namespace),
+            key: Some(key),
+            env,
+            limit,
+            before,
+            format,
+            raw,
+        } => {
+            let env: Environment = env.into();
+            let mut query = vec![("env", env.to_string())];
+            query.extend(limit.map(|limit| ("limit", limit.to_string())));
+            query.extend(before.map(|before| ("before_version", before.to_string())));
+            let path = format!("/configs/{}/{}/history", segment(&namespace), segment(&key));
+            let (history, _) = client.send(Method::GET, &path, &query, None, None)?;
+            let versions = history.as_array().cloned().unwrap_or_default();
+
+            if raw {
+                for version in &versions {
+                    println!("{}", value_text(&version["value"]));
+                }
+                return Ok(());
+            }
+
+            if let Some(output) = document_output(output, format) {
+                let plain = versions
+                    .iter()
+                    .map(|version| {
+                        format!(
+                            "{}\t{}\t{}\t{}",
+                            version["version"],
+                            value_text(&version["created_at"]),
+                            value_text(&version["created_by"]),
+                            value_text(&version["value"])
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                output.print(&history, || plain)?;
+                return Ok(());
+            }
+
+            if versions.is_empty() {
+                println!("{}", "No version history found".yellow());
+                return Ok(());
+            }
+
+            println!("{}", format!("Version history for {}:{}", namespace, key).green().bold());
+            println!();
+            for version in &versions {
+                println!("  {} Version {}", "→".blue(), version["version"].to_string().bold());
+                println!("    Value: {}", value_text(&version["value"]));
+                println!(
+                    "    Created: {} by {}",
+                    value_text(&version["created_at"]),
+                    value_text(&version["created_by"])
+                );
+                if let Some(note) = version["change_description"].as_str() {
+                    println!("    Note: {}", note);
+                }
+                println!();
+            }
+        }
+
+        Commands::Rollback {
+            namespace,
+            key,
+            version,
+            env,
+            user,
+            reviewed_by,
+            reason,
+            dry_run,
+        } => {
+            let env: Environment = env.into();
+            let mut query = vec![("env", env.to_string()), ("dry_run", dry_run.to_string())];
+            query.extend(reviewed_by.map(|reviewer| ("reviewed_by", reviewer)));
+            let path = format!("/configs/{}/{}/rollback/{}", segment(&namespace), segment(&key), version);
+            let body = serde_json::json!({ "reason": reason });
+            let (response, _) = client.send(Method::POST, &path, &query, Some(body), Some(&user))?;
+            if dry_run {
+                return print_remote_dry_run(output, &response);
+            }
+
+            let entry = &response["config"];
+            match output {
+                Some(output) => output.print(&entry, || entry["version"].to_string())?,
+                None => {
+                    println!("{}", "Rollback successful!".green().bold());
+                    println!("  New version: {}", entry["version"]);
+                    println!("  Value: {}", value_text(&entry["value"]));
+                }
+            }
+        }
+
+        _ => {
+            return Err(llm_config_core::ConfigError::InvalidOperation(
+                "this command works on a local store and is not available with --server".to_string(),
+            )
+            .into())
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a `DryRunResponse` of the API
+fn print_remote_dry_run(output: Option<Output>, response: &Value) -> anyhow::Result<()> {
+    let plan: Plan = serde_json::from_value(response["plan"].clone())?;
+    show_dry_run(output, response["version"].as_u64(), &plan)
+}
@@ -0,0 +1,777 @@
+//! Async facade over [`ConfigManager`] for use inside a tokio runtime
+//!
+//! [`ConfigManager`] performs blocking file IO, which stalls a runtime worker
+//! thread when called from an async task. [`AsyncConfigManager`] runs every
+//! operation on tokio's blocking thread pool instead, so handlers can `.await`
+//! it without blocking other requests. The synchronous API stays available
+//! (e.g. for the CLI) through [`AsyncConfigManager::blocking`] and shares the
+//! same underlying manager.
+//!
+//! With [`AsyncConfigManager::with_metrics`], every operation is counted and
+//! timed in a [`MetricsRegistry`].
+
+use crate::{
+    AccessStats, AppliedBlueprint, Blueprint, ChangeEvent, Changelog, CheckedEntry, CompactionReport, ConfigEntry, ConfigError, ConfigManager, ConfigPage, ConfigValue, Environment,
+    EnvironmentGraph, FsckReport, HistoryOptions, ListOptions, Plan, PromoteKeys, ReadOnlyMode, RenderedTemplate, ResolutionTrace, ResolvedValue, Result, RetentionConfig, RetentionReport, SetOptions,
+    StoredBlueprint, StoredTemplate, UnusedConfig, ValueDiff, VersionEntry,
+};
+use chrono::{DateTime, Utc};
+use llm_config_sink::MetricsSink;
+use llm_config_templates::{ConfigSource, Template};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Async configuration manager
+#[derive(Clone)]
+pub struct AsyncConfigManager {
+    inner: Arc<ConfigManager>,
+    metrics: Option<Arc<dyn MetricsSink>>,
+}
+
+impl AsyncConfigManager {
+    /// Wrap an existing manager
+    pub fn new(manager: Arc<ConfigManager>) -> Self {
+        Self {
+            inner: manager,
+            metrics: None,
+        }
+    }
+
+    /// Record operation counts, durations and errors in the given sink
+    pub fn with_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Open a manager without blocking the runtime
+    pub async fn open(storage_path: impl AsRef<Path>) -> Result<Self> {
+        let storage_path: PathBuf = storage_path.as_ref().to_path_buf();
+        let manager = run_blocking(move || ConfigManager::new(storage_path)).await?;
+        Ok(Self::new(Arc::new(manager)))
+    }
+
+    /// The underlying synchronous manager
+    pub fn blocking(&self) -> &Arc<ConfigManager> {
+        &self.inner
+    }
+
+    /// Run `f` against the manager on the blocking thread pool
+    ///
+    /// `operation` and `env` label the recorded metrics; operations spanning
+    /// every environment are recorded as `all`.
+    async fn run<T, F>(&self, operation: &'static str, env: Option<Environment>, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&ConfigManager) -> Result<T> + Send + 'static,
+    {
+        let manager = Arc::clone(&self.inner);
+        let started = Instant::now();
+        let result = run_blocking(move || f(&manager)).await;
+
+        if let Some(metrics) = &self.metrics {
+            let env = env.map_or_else(|| "all".to_string(), |env| env.to_string());
+            metrics.record_counter(
+                "config_operations_total",
+                &[("operation", operation), ("environment", &env)],
+                1.0,
+            );
+            metrics.observe(
+                "config_operation_duration_seconds",
+                &[("operation", operation)],
+                started.elapsed().as_secs_f64(),
+            );
+            if let Err(e) = &result {
+                metrics.record_counter(
+                    "config_errors_total",
+                    &[("error_type", e.kind()), ("operation", operation)],
+                    1.0,
+                );
+            }
+        }
+
+        result
+    }
+
+    /// Get a single configuration value (secrets stay encrypted)
+    pub async fn get(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+    ) -> Result<Option<ConfigEntry>> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("get", Some(env), move |m| m.get(&namespace, &key, env)).await
+    }
+
+    /// Get several configuration values from one namespace (secrets stay encrypted)
+    pub async fn get_many(
+        &self,
+        namespace: &str,
+        keys: Vec<String>,
+        env: Environment,
+    ) -> Result<Vec<(String, Option<ConfigEntry>)>> {
+        let namespace = namespace.to_string();
+        self.run("get_many", Some(env), move |m| m.get_many(&namespace, &keys, env)).await
+    }
+
+    /// Get a single configuration value with secrets decrypted
+    pub async fn get_revealed(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+    ) -> Result<Option<ConfigEntry>> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("get_revealed", Some(env), move |m| m.get_revealed(&namespace, &key, env))
+            .await
+    }
+
+    /// Resolve every key in a namespace with environment overrides applied
+    pub async fn resolve_namespace(
+        &self,
+        namespace: &str,
+        env: Environment,
+    ) -> Result<BTreeMap<String, ConfigValue>> {
+        let namespace = namespace.to_string();
+        self.run("resolve", Some(env), move |m| m.resolve_namespace(&namespace, env)).await
+    }
+
+    /// Resolve every key in a namespace, with the entries each value came from
+    pub async fn resolve_namespace_with_sources(
+        &self,
+        namespace: &str,
+        env: Environment,
+    ) -> Result<BTreeMap<String, ResolvedValue>> {
+        let namespace = namespace.to_string();
+        self.run("resolve", Some(env), move |m| m.resolve_namespace_with_sources(&namespace, env))
+            .await
+    }
+
+    /// Resolve a key and record how its value was found
+    pub async fn explain(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        reveal: bool,
+    ) -> Result<ResolutionTrace> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("explain", Some(env), move |m| m.explain(&namespace, &key, env, reveal)).await
+    }
+
+    /// Wait up to `wait` for writes made through other replicas and apply them
+    ///
+    /// Not recorded in the operation metrics, as it mostly waits.
+    pub async fn watch(&self, wait: Duration) -> Result<Vec<ChangeEvent>> {
+        let manager = Arc::clone(&self.inner);
+        run_blocking(move || manager.watch(wait)).await
+    }
+
+    /// Set a configuration value
+    pub async fn set_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: ConfigValue,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<ConfigEntry> {
+        let (namespace, key, user, options) = (
+            namespace.to_string(),
+            key.to_string(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("set", Some(env), move |m| {
+            m.set_with_options(namespace, key, value, env, user, &options)
+        })
+        .await
+    }
+
+    /// Set a secret value (encrypted before it is stored)
+    pub async fn set_secret_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        plaintext: Vec<u8>,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<ConfigEntry> {
+        let (namespace, key, user, options) = (
+            namespace.to_string(),
+            key.to_string(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("set_secret", Some(env), move |m| {
+            m.set_secret_with_options(namespace, key, plaintext, env, user, &options)
+        })
+        .await
+    }
+
+    /// List all configurations in a namespace (secrets stay encrypted)
+    pub async fn list(&self, namespace: &str, env: Environment) -> Result<Vec<ConfigEntry>> {
+        let namespace = namespace.to_string();
+        self.run("list", Some(env), move |m| m.list(&namespace, env)).await
+    }
+
+    /// List a page of the configurations in a namespace (secrets stay encrypted)
+    pub async fn list_with_options(
+        &self,
+        namespace: &str,
+        env: Environment,
+        options: ListOptions,
+    ) -> Result<ConfigPage> {
+        let namespace = namespace.to_string();
+        self.run("list", Some(env), move |m| m.list_with_options(&namespace, env, &options))
+            .await
+    }
+
+    /// List all configurations in a namespace with secrets decrypted
+    pub async fn list_revealed(
+        &self,
+        namespace: &str,
+        env: Environment,
+    ) -> Result<Vec<ConfigEntry>> {
+        let namespace = namespace.to_string();
+        self.run("list_revealed", Some(env), move |m| m.list_revealed(&namespace, env)).await
+    }
+
+    /// Delete a configuration on behalf of a user
+    pub async fn delete_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<bool> {
+        let (namespace, key, user, options) = (
+            namespace.to_string(),
+            key.to_string(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("delete", Some(env), move |m| {
+            m.delete_with_options(&namespace, &key, env, &user, &options)
+        })
+        .await
+    }
+
+    /// Replace the tags and description of a configuration on behalf of a user
+    #[allow(clippy::too_many_arguments)]
+    pub async fn set_metadata_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        tags: Vec<String>,
+        description: Option<String>,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<ConfigEntry> {
+        let (namespace, key, user, options) = (
+            namespace.to_string(),
+            key.to_string(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("set_metadata", Some(env), move |m| {
+            m.set_metadata_with_options(&namespace, &key, env, tags, description, &user, &options)
+        })
+        .await
+    }
+
+    /// Get version history
+    pub async fn get_history(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+    ) -> Result<Vec<VersionEntry>> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("history", Some(env), move |m| m.get_history(&namespace, &key, env))
+            .await
+    }
+
+    /// Get a page of version history, newest first
+    pub async fn get_history_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        options: HistoryOptions,
+    ) -> Result<Vec<VersionEntry>> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("history", Some(env), move |m| {
+            m.get_history_with_options(&namespace, &key, env, &options)
+        })
+        .await
+    }
+
+    /// Versions of every config in a namespace, newest first
+    pub async fn namespace_history(
+        &self,
+        namespace: &str,
+        env: Option<Environment>,
+    ) -> Result<Vec<VersionEntry>> {
+        let namespace = namespace.to_string();
+        self.run("history", env, move |m| m.namespace_history(&namespace, env)).await
+    }
+
+    /// Differences between two versions of a config
+    pub async fn diff_versions(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        v1: u64,
+        v2: u64,
+    ) -> Result<ValueDiff> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("diff", Some(env), move |m| m.diff_versions(&namespace, &key, env, v1, v2))
+            .await
+    }
+
+    /// Rollback to a specific version on behalf of a user
+    pub async fn rollback_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        version: u64,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Option<ConfigEntry>> {
+        let (namespace, key, user, options) = (
+            namespace.to_string(),
+            key.to_string(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("rollback", Some(env), move |m| {
+            m.rollback_with_options(&namespace, &key, env, version, &user, &options)
+        })
+        .await
+    }
+
+    /// Changes promoting `keys` of `namespace` from `from` to `to` would make
+    pub async fn plan_promotion(
+        &self,
+        namespace: &str,
+        keys: &PromoteKeys,
+        from: Environment,
+        to: Environment,
+    ) -> Result<Plan> {
+        let (namespace, keys) = (namespace.to_string(), keys.clone());
+        self.run("plan_promotion", Some(to), move |m| {
+            m.plan_promotion(&namespace, &keys, from, to)
+        })
+        .await
+    }
+
+    /// Promote `keys` of `namespace` from `from` to `to` on behalf of a user
+    pub async fn promote_with_options(
+        &self,
+        namespace: &str,
+        keys: &PromoteKeys,
+        from: Environment,
+        to: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Plan> {
+        let (namespace, keys, user, options) = (
+            namespace.to_string(),
+            keys.clone(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("promote", Some(to), move |m| {
+            m.promote_with_options(&namespace, &keys, from, to, &user, &options)
+        })
+        .await
+    }
+
+    /// Changes restoring `namespace` in `env` to its state at `at` would make
+    pub async fn plan_namespace_restore(&self, namespace: &str, env: Environment, at: DateTime<Utc>) -> Result<Plan> {
+        let namespace = namespace.to_string();
+        self.run("plan_namespace_restore", Some(env), move |m| {
+            m.plan_namespace_restore(&namespace, env, at)
+        })
+        .await
+    }
+
+    /// Restore `namespace` in `env` to its state at `at` on behalf of a user
+    pub async fn restore_namespace_at_with_options(
+        &self,
+        namespace: &str,
+        env: Environment,
+        at: DateTime<Utc>,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Plan> {
+        let (namespace, user, options) = (namespace.to_string(), user.to_string(), options.clone());
+        self.run("restore_namespace_at", Some(env), move |m| {
+            m.restore_namespace_at_with_options(&namespace, env, at, &user, &options)
+        })
+        .await
+    }
+
+    /// Copy the configs of `src` in `envs` (every environment if empty) to `dst` on behalf of a user
+    pub async fn clone_namespace_with_options(
+        &self,
+        src: &str,
+        dst: &str,
+        envs: &[Environment],
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Vec<ConfigEntry>> {
+        let (src, dst, envs, user, options) = (
+            src.to_string(),
+            dst.to_string(),
+            envs.to_vec(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("clone_namespace", None, move |m| {
+            m.clone_namespace_with_options(&src, &dst, &envs, &user, &options)
+        })
+        .await
+    }
+
+    /// Move every config of `src` to `dst` on behalf of a user
+    pub async fn rename_namespace_with_options(
+        &self,
+        src: &str,
+        dst: &str,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Vec<ConfigEntry>> {
+        let (src, dst, user, options) = (
+            src.to_string(),
+            dst.to_string(),
+            user.to_string(),
+            options.clone(),
+        );
+        self.run("rename_namespace", None, move |m| {
+            m.rename_namespace_with_options(&src, &dst, &user, &options)
+        })
+        .await
+    }
+
+    /// Release notes for a namespace since `since`
+    pub async fn changelog(
+        &self,
+        namespace: &str,
+        env: Option<Environment>,
+        since: DateTime<Utc>,
+    ) -> Result<Changelog> {
+        let namespace = namespace.to_string();
+        self.run("changelog", env, move |m| m.changelog(&namespace, env, since)).await
+    }
+
+    /// Export all configurations
+    pub async fn export_all(&self, export_path: impl AsRef<Path>) -> Result<usize> {
+        let export_path = export_path.as_ref().to_path_buf();
+        self.run("export", None, move |m| m.export_all(export_path)).await
+    }
+
+    /// Validate, replace and persist the environment inheritance graph
+    pub async fn set_environment_graph(&self, graph: EnvironmentGraph) -> Result<()> {
+        self.run("set_environment_graph", None, move |m| m.set_environment_graph(graph))
+            .await
+    }
+
+    /// Save a new version of a prompt template on behalf of a user
+    pub async fn put_template(
+        &self,
+        template: Template,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<StoredTemplate> {
+        let (user, options) = (user.to_string(), options.clone());
+        self.run("put_template", Some(env), move |m| {
+            m.templates().put(&template, env, &user, &options)
+        })
+        .await
+    }
+
+    /// Get the prompt template that applies to an environment
+    pub async fn get_template(&self, name: &str, env: Environment) -> Result<Option<StoredTemplate>> {
+        let name = name.to_string();
+        self.run("get_template", Some(env), move |m| m.templates().get(&name, env)).await
+    }
+
+    /// All prompt templates that apply to an environment
+    pub async fn list_templates(&self, env: Environment) -> Result<Vec<StoredTemplate>> {
+        self.run("list_templates", Some(env), move |m| m.templates().list(env)).await
+    }
+
+    /// Render the prompt template that applies to an environment
+    pub async fn render_template(
+        &self,
+        name: &str,
+        env: Environment,
+        vars: HashMap<String, String>,
+    ) -> Result<RenderedTemplate> {
+        let name = name.to_string();
+        self.run("render_template", Some(env), move |m| m.templates().render(&name, env, &vars))
+            .await
+    }
+
+    /// Render the prompt template that applies to an environment, resolving
+    /// references to stored values through `source`
+    pub async fn render_template_with_source<S>(
+        &self,
+        name: &str,
+        env: Environment,
+        vars: HashMap<String, String>,
+        source: S,
+    ) -> Result<RenderedTemplate>
+    where
+        S: ConfigSource<Environment = Environment> + Send + 'static,
+    {
+        let name = name.to_string();
+        self.run("render_template", Some(env), move |m| {
+            m.templates().render_with_source(&name, env, &vars, &source)
+        })
+        .await
+    }
+
+    /// Save a new version of a blueprint
+    pub async fn put_blueprint(
+        &self,
+        blueprint: Blueprint,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<StoredBlueprint> {
+        let (user, options) = (user.to_string(), options.clone());
+        self.run("put_blueprint", None, move |m| m.blueprints().put(&blueprint, &user, &options))
+            .await
+    }
+
+    /// Get a blueprint
+    pub async fn get_blueprint(&self, name: &str) -> Result<Option<StoredBlueprint>> {
+        let name = name.to_string();
+        self.run("get_blueprint", None, move |m| m.blueprints().get(&name)).await
+    }
+
+    /// All blueprints
+    pub async fn list_blueprints(&self) -> Result<Vec<StoredBlueprint>> {
+        self.run("list_blueprints", None, move |m| m.blueprints().list()).await
+    }
+
+    /// Apply a blueprint to a namespace, resolving references to stored
+    /// values through `source`
+    #[allow(clippy::too_many_arguments)]
+    pub async fn apply_blueprint_with_source<S>(
+        &self,
+        name: &str,
+        namespace: &str,
+        env: Environment,
+        vars: HashMap<String, String>,
+        user: &str,
+        options: &SetOptions,
+        source: S,
+    ) -> Result<AppliedBlueprint>
+    where
+        S: ConfigSource<Environment = Environment> + Send + 'static,
+    {
+        let (name, namespace, user, options) =
+            (name.to_string(), namespace.to_string(), user.to_string(), options.clone());
+        self.run("apply_blueprint", Some(env), move |m| {
+            m.blueprints()
+                .apply_with_source(&name, &namespace, env, &vars, &user, &options, &source)
+        })
+        .await
+    }
+
+    /// Cross-check the index, config files and version history, optionally repairing
+    pub async fn fsck(&self, repair: bool) -> Result<FsckReport> {
+        self.run("fsck", None, move |m| m.fsck(repair)).await
+    }
+
+    /// Save a fresh index snapshot and tidy up storage files
+    pub async fn compact(&self) -> Result<CompactionReport> {
+        self.run("compact", None, |m| m.compact()).await
+    }
+
+    /// Current read-only mode (`None` while the store is writable)
+    pub async fn read_only_mode(&self) -> Result<Option<ReadOnlyMode>> {
+        self.run("read_only_mode", None, |m| m.read_only_mode()).await
+    }
+
+    /// Reject all writes until read-only mode is disabled
+    pub async fn enable_read_only(&self, user: &str, reason: Option<String>) -> Result<ReadOnlyMode> {
+        let user = user.to_string();
+        self.run("enable_read_only", None, move |m| m.enable_read_only(&user, reason)).await
+    }
+
+    /// Accept writes again; returns whether the store was read-only
+    pub async fn disable_read_only(&self) -> Result<bool> {
+        self.run("disable_read_only", None, |m| m.disable_read_only()).await
+    }
+
+    /// Retention settings of the store
+    pub async fn retention(&self) -> Result<RetentionConfig> {
+        self.run("retention", None, |m| m.retention()).await
+    }
+
+    /// Replace the retention settings of the store
+    pub async fn set_retention(&self, config: RetentionConfig) -> Result<()> {
+        self.run("set_retention", None, move |m| m.set_retention(&config)).await
+    }
+
+    /// Remove the version history the retention settings do not keep
+    pub async fn apply_retention(&self) -> Result<RetentionReport> {
+        self.run("apply_retention", None, |m| m.apply_retention()).await
+    }
+
+    /// Get a configuration value without counting the read
+    pub async fn get_untracked(&self, namespace: &str, key: &str, env: Environment) -> Result<Option<ConfigEntry>> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("get", Some(env), move |m| m.get_untracked(&namespace, &key, env)).await
+    }
+
+    /// Get a configuration value together with a warning if its type drifted
+    pub async fn get_checked(&self, namespace: &str, key: &str, env: Environment) -> Result<Option<CheckedEntry>> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("get", Some(env), move |m| m.get_checked(&namespace, &key, env)).await
+    }
+
+    /// Access statistics of a config (`None` if it does not exist)
+    pub async fn access_stats(&self, namespace: &str, key: &str, env: Environment) -> Result<Option<AccessStats>> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run("access_stats", Some(env), move |m| m.access_stats(&namespace, &key, env)).await
+    }
+
+    /// Configs not read since `cutoff`, least recently read first
+    pub async fn unused_configs(&self, cutoff: DateTime<Utc>) -> Result<Vec<UnusedConfig>> {
+        self.run("unused_configs", None, move |m| m.unused_configs(cutoff)).await
+    }
+}
+
+impl From<Arc<ConfigManager>> for AsyncConfigManager {
+    fn from(manager: Arc<ConfigManager>) -> Self {
+        Self::new(manager)
+    }
+}
+
+/// Run a blocking closure on the blocking thread pool, propagating panics
+///
+/// The closure runs inside the caller's tracing span, so spans it creates
+/// are nested under the request that triggered it.
+async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let span = tracing::Span::current();
+    match tokio::task::spawn_blocking(move || span.in_scope(f)).await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(ConfigError::InvalidOperation(format!(
+            "Blocking task failed: {}",
+            err
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_metrics::MetricsRegistry;
+    use tempfile::TempDir;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_async_manager() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = AsyncConfigManager::open(temp_dir.path()).await.unwrap();
+        let env = Environment::Development;
+        let options = SetOptions::default();
+
+        // Concurrent writes to different keys
+        let writes = (0..8).map(|i| {
+            let manager = manager.clone();
+            let options = options.clone();
+            tokio::spawn(async move {
+                manager
+                    .set_with_options(
+                        "app",
+                        &format!("key{}", i),
+                        ConfigValue::Integer(i),
+                        env,
+                        "alice",
+                        &options,
+                    )
+                    .await
+            })
+        });
+        for write in writes {
+            write.await.unwrap().unwrap();
+        }
+
+        assert_eq!(manager.list("app", env).await.unwrap().len(), 8);
+        let entry = manager.get("app", "key3", env).await.unwrap().unwrap();
+        assert_eq!(entry.value.as_i64(), Some(3));
+
+        // The sync API sees the same state
+        assert!(manager
+            .blocking()
+            .get("app", "key3", env)
+            .unwrap()
+            .is_some());
+
+        assert!(manager
+            .delete_with_options("app", "key3", env, "alice", &options)
+            .await
+            .unwrap());
+        assert_eq!(
+            manager.get_history("app", "key0", env).await.unwrap().len(),
+            1
+        );
+        assert!(manager.fsck(false).await.unwrap().is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_async_manager_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let metrics = Arc::new(MetricsRegistry::new().unwrap());
+        let manager = AsyncConfigManager::open(temp_dir.path())
+            .await
+            .unwrap()
+            .with_metrics(metrics.clone());
+        let env = Environment::Production;
+
+        manager
+            .set_with_options("app", "k", ConfigValue::Integer(1), env, "alice", &SetOptions::default())
+            .await
+            .unwrap();
+        manager.get("app", "k", env).await.unwrap();
+        assert!(manager
+            .set_with_options(
+                "app",
+                "k",
+                ConfigValue::Integer(2),
+                env,
+                "alice",
+                &SetOptions::default().with_expected_version(7),
+            )
+            .await
+            .is_err());
+
+        let text = metrics.encode_text().unwrap();
+        assert!(text.contains(
+            "config_operations_total{environment=\"production\",operation=\"set\"} 2"
+        ));
+        assert!(text.contains(
+            "config_operations_total{environment=\"production\",operation=\"get\"} 1"
+        ));
+        assert!(text.contains("config_errors_total{error_type=\"conflict\",operation=\"set\"} 1"));
+    }
+}
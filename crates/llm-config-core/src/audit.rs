@@ -0,0 +1,35 @@
+//! Audit hooks
+//!
+//! A [`ConfigManager`](crate::ConfigManager) given a recorder with
+//! [`with_audit_logger`](crate::ConfigManager::with_audit_logger) reports
+//! every write and secret read to it, with the user and request context of
+//! the operation, so callers cannot forget to audit them.
+//! `llm_config_audit::AuditLogger` implements [`AuditRecorder`].
+
+use crate::{ChangeEvent, Environment, OperationContext, TenantId};
+
+/// An operation reported to an [`AuditRecorder`]
+#[derive(Debug, Clone, Copy)]
+pub enum AuditedOperation<'a> {
+    /// A successful write, as published on the change bus
+    Change(&'a ChangeEvent),
+    /// A secret decrypted by [`ConfigManager::get_secret_as`](crate::ConfigManager::get_secret_as)
+    SecretRead {
+        namespace: &'a str,
+        key: &'a str,
+        environment: Environment,
+        user: &'a str,
+        context: Option<&'a OperationContext>,
+        tenant: Option<&'a TenantId>,
+        /// Why the user needed the secret, if given
+        reason: Option<&'a str>,
+    },
+}
+
+/// Receives the operations of a config manager for auditing
+///
+/// Called synchronously after the operation succeeds, so implementations
+/// should hand the operation off rather than block.
+pub trait AuditRecorder: Send + Sync {
+    fn record(&self, operation: AuditedOperation<'_>);
+}
@@ -0,0 +1,285 @@
+//! Binding secrets to the entry they are stored in
+//!
+//! [`ConfigManager`] encrypts secrets with the identity of their entry,
+//! `tenant/namespace/key/env` (see [`secret_aad`]), as additional
+//! authenticated data. The identity is recorded in the encrypted value, and
+//! every read checks that it names the entry the value was read from, so an
+//! encrypted value copied to another key, namespace, environment or tenant
+//! is rejected with [`ConfigError::CryptoError`].
+//!
+//! Secrets written before binding was introduced carry no identity. They are
+//! still read unless [`ConfigManager::with_required_secret_binding`] is set,
+//! and [`ConfigManager::bind_secrets_with_options`] re-encrypts them bound.
+//! Cloning or renaming a namespace re-encrypts its secrets for the new
+//! namespace; versions from before the move keep their old binding and
+//! cannot be rolled back to.
+
+use crate::{ConfigEntry, ConfigError, ConfigManager, ConfigValue, Environment, Result, SetOptions, TenantId};
+use llm_config_crypto::{decrypt, decrypt_with_aad, CryptoError, EncryptedData};
+
+/// Change description of versions written by [`ConfigManager::bind_secrets_with_options`]
+pub const BIND_SECRETS_DESCRIPTION: &str = "Bound secret to its entry";
+
+/// Additional authenticated data binding a secret to its entry
+///
+/// `tenant/namespace/key/env`, without the tenant for stores that do not
+/// belong to one. `%` and `/` in the namespace and key are percent-encoded,
+/// so distinct entries never share an identity.
+pub fn secret_aad(tenant: Option<&TenantId>, namespace: &str, key: &str, env: Environment) -> String {
+    let escape = |part: &str| part.replace('%', "%25").replace('/', "%2F");
+    let identity = format!("{}/{}/{}", escape(namespace), escape(key), env);
+    match tenant {
+        Some(tenant) => format!("{}/{}", tenant.as_str(), identity),
+        None => identity,
+    }
+}
+
+impl ConfigManager {
+    /// Reject secrets that are not bound to their entry on read
+    ///
+    /// Run [`bind_secrets_with_options`](Self::bind_secrets_with_options)
+    /// first to migrate secrets written by older versions.
+    pub fn with_required_secret_binding(mut self, required: bool) -> Self {
+        self.require_bound_secrets = required;
+        self
+    }
+
+    /// Identity a secret of this manager's store is encrypted with
+    pub fn secret_aad(&self, namespace: &str, key: &str, env: Environment) -> String {
+        secret_aad(self.tenant(), namespace, key, env)
+    }
+
+    /// Fail if a secret being read is bound to another entry, or unbound
+    /// when binding is required
+    pub(crate) fn check_binding(&self, entry: &ConfigEntry) -> Result<()> {
+        let ConfigValue::Secret(encrypted) = &entry.value else {
+            return Ok(());
+        };
+        let unbound = match &encrypted.aad_context {
+            Some(aad) if *aad == self.secret_aad(&entry.namespace, &entry.key, entry.environment) => return Ok(()),
+            Some(aad) => format!("is bound to {}", aad),
+            None if self.require_bound_secrets => "is not bound to its entry".to_string(),
+            None => return Ok(()),
+        };
+        Err(ConfigError::CryptoError(CryptoError::DecryptionFailed(format!(
+            "Secret {}:{} ({}) {}",
+            entry.namespace, entry.key, entry.environment, unbound
+        ))))
+    }
+
+    /// Decrypt a secret of `entry`, authenticating the entry's identity
+    ///
+    /// Unbound secrets are decrypted as they are.
+    pub(crate) fn open_secret(&self, entry: &ConfigEntry, encrypted: &EncryptedData) -> Result<Vec<u8>> {
+        let key = self.encryption_key.as_ref().ok_or_else(|| {
+            ConfigError::ValidationError("Encryption key not configured".to_string())
+        })?;
+        let plaintext = self.metered_crypto("decrypt", encrypted.algorithm, || match encrypted.aad_context {
+            Some(_) => {
+                let aad = self.secret_aad(&entry.namespace, &entry.key, entry.environment);
+                decrypt_with_aad(key, encrypted, Some(&aad))
+            }
+            None => decrypt(key, encrypted),
+        })?;
+        Ok(plaintext)
+    }
+
+    /// Secrets written before binding, which any entry would accept
+    pub fn unbound_secrets(&self, namespace: Option<&str>) -> Result<Vec<ConfigEntry>> {
+        let mut entries: Vec<ConfigEntry> = self
+            .list_all()?
+            .into_iter()
+            .filter(|entry| namespace.map_or(true, |namespace| entry.namespace == namespace))
+            .filter(|entry| matches!(&entry.value, ConfigValue::Secret(e) if e.aad_context.is_none()))
+            .collect();
+        entries.sort_by(|a, b| {
+            (&a.namespace, &a.key, a.environment).cmp(&(&b.namespace, &b.key, b.environment))
+        });
+        Ok(entries)
+    }
+
+    /// Re-encrypt unbound secrets (of one namespace, or all) bound to their entry
+    ///
+    /// Each secret gets a new version. Every affected environment is checked
+    /// against its protection level before the first write. With `dry_run`,
+    /// returns the secrets that would be bound.
+    pub fn bind_secrets_with_options(
+        &self,
+        namespace: Option<&str>,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Vec<ConfigEntry>> {
+        let key = self.encryption_key.as_ref().ok_or_else(|| {
+            ConfigError::ValidationError("Encryption key not configured".to_string())
+        })?;
+        let unbound = self.unbound_secrets(namespace)?;
+        for entry in &unbound {
+            self.check_write(entry.environment, user, options)?;
+        }
+        if options.dry_run {
+            return Ok(unbound);
+        }
+        let _writes = self.lock_writes()?;
+
+        let mut options = options.clone();
+        options
+            .change_description
+            .get_or_insert_with(|| BIND_SECRETS_DESCRIPTION.to_string());
+
+        // Decrypt everything before the first write
+        let mut writes = Vec::new();
+        for entry in unbound {
+            let ConfigValue::Secret(ref encrypted) = entry.value else {
+                unreachable!("only secrets are unbound");
+            };
+            let plaintext = self.metered_crypto("decrypt", encrypted.algorithm, || decrypt(key, encrypted))?;
+            let value = self.encrypt_secret(&entry.namespace, &entry.key, entry.environment, &plaintext)?;
+            writes.push((entry, value));
+        }
+
+        let mut bound = Vec::new();
+        for (entry, value) in writes {
+            let (namespace, key, env) = (entry.namespace.clone(), entry.key.clone(), entry.environment);
+            bound.push(self.store(namespace, key, Some(entry), value, env, user.to_string(), &options)?);
+        }
+        Ok(bound)
+    }
+
+    /// Fail before moving `src` if it holds bound secrets that cannot be
+    /// re-encrypted for their new namespace
+    pub(crate) fn check_rebind(&self, src: &str) -> Result<()> {
+        if self.encryption_key.is_some() {
+            return Ok(());
+        }
+        let bound = self.list_all()?.into_iter().any(|entry| {
+            entry.namespace == src
+                && matches!(&entry.value, ConfigValue::Secret(e) if e.aad_context.is_some())
+        });
+        if bound {
+            return Err(ConfigError::ValidationError(format!(
+                "Encryption key required to move the secrets of {}",
+                src
+            )));
+        }
+        Ok(())
+    }
+
+    /// Re-encrypt the bound secrets of entries copied from `src` for their
+    /// new namespace, and re-sign them
+    pub(crate) fn rebind_copies(&self, src: &str, copies: &mut [ConfigEntry]) -> Result<()> {
+        for copy in copies.iter_mut() {
+            let rebind = matches!(&copy.value, ConfigValue::Secret(e) if e.aad_context.is_some());
+            if !rebind && self.signer.is_none() {
+                continue;
+            }
+            if rebind {
+                let source = ConfigEntry {
+                    namespace: src.to_string(),
+                    ..copy.clone()
+                };
+                let ConfigValue::Secret(ref encrypted) = copy.value else {
+                    unreachable!("checked above");
+                };
+                let plaintext = self.open_secret(&source, encrypted)?;
+                copy.value = self.encrypt_secret(&copy.namespace, &copy.key, copy.environment, &plaintext)?;
+            }
+            self.sign_entry(copy)?;
+            self.storage().set(copy.clone())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{encrypt, Algorithm, SecretKey};
+    use tempfile::TempDir;
+
+    fn manager(dir: &TempDir, key: &SecretKey) -> ConfigManager {
+        ConfigManager::new(dir.path()).unwrap().with_encryption_key(key.clone())
+    }
+
+    #[test]
+    fn test_secret_aad() {
+        let env = Environment::Production;
+        assert_eq!(secret_aad(None, "app/llm", "api_key", env), "app%2Fllm/api_key/production");
+        let tenant = TenantId::new("acme").unwrap();
+        assert_eq!(secret_aad(Some(&tenant), "app", "a/b", env), "acme/app/a%2Fb/production");
+        assert_ne!(secret_aad(None, "a/b", "c", env), secret_aad(None, "a", "b/c", env));
+    }
+
+    #[test]
+    fn test_copied_secret_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = manager(&temp_dir, &key);
+        let env = Environment::Production;
+
+        let stored = manager.set_secret("app", "api_key", b"sk-live", env, "alice").unwrap();
+        manager.set_secret("app", "other_key", b"sk-other", env, "alice").unwrap();
+        assert_eq!(manager.get_secret("app", "api_key", env).unwrap().unwrap(), b"sk-live");
+
+        // Paste the encrypted value of api_key into other_key
+        let mut pasted = manager.storage().get("app", "other_key", env).unwrap().unwrap();
+        pasted.value = stored.value;
+        manager.storage().set(pasted).unwrap();
+
+        assert!(matches!(manager.get_secret("app", "other_key", env), Err(ConfigError::CryptoError(_))));
+        assert!(manager.get("app", "other_key", env).is_err());
+        assert!(manager.get_revealed("app", "other_key", env).is_err());
+    }
+
+    #[test]
+    fn test_bind_legacy_secrets() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = manager(&temp_dir, &key);
+        let env = Environment::Staging;
+
+        // A secret as older versions wrote it, without an identity
+        let legacy = ConfigValue::Secret(encrypt(&key, b"hunter2", None).unwrap());
+        manager.set("app", "password", legacy, env, "alice").unwrap();
+        assert_eq!(manager.get_secret("app", "password", env).unwrap().unwrap(), b"hunter2");
+
+        let strict = || {
+            ConfigManager::new(temp_dir.path())
+                .unwrap()
+                .with_encryption_key(key.clone())
+                .with_required_secret_binding(true)
+        };
+        assert!(strict().get_secret("app", "password", env).is_err());
+
+        let planned = manager
+            .bind_secrets_with_options(None, "admin", &SetOptions::default().with_dry_run(true))
+            .unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(manager.unbound_secrets(Some("app")).unwrap().len(), 1);
+
+        let bound = manager.bind_secrets_with_options(None, "admin", &SetOptions::default()).unwrap();
+        assert_eq!(bound[0].version, 2);
+        assert!(manager.unbound_secrets(None).unwrap().is_empty());
+        assert_eq!(strict().get_secret("app", "password", env).unwrap().unwrap(), b"hunter2");
+        let history = manager.get_history("app", "password", env).unwrap();
+        let binding = history.iter().find(|version| version.version == 2).unwrap();
+        assert_eq!(binding.change_description.as_deref(), Some(BIND_SECRETS_DESCRIPTION));
+    }
+
+    #[test]
+    fn test_renamed_secrets_are_rebound() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = manager(&temp_dir, &key);
+        let env = Environment::Development;
+        manager.set_secret("team-a", "token", b"t0k3n", env, "alice").unwrap();
+
+        manager.clone_namespace("team-a", "team-b", &[]).unwrap();
+        manager.rename_namespace("team-a", "team-c").unwrap();
+        assert_eq!(manager.get_secret("team-b", "token", env).unwrap().unwrap(), b"t0k3n");
+        assert_eq!(manager.get_secret("team-c", "token", env).unwrap().unwrap(), b"t0k3n");
+
+        // Without a key, bound secrets cannot follow their namespace
+        let keyless = ConfigManager::new(temp_dir.path()).unwrap();
+        assert!(keyless.rename_namespace("team-c", "team-d").is_err());
+    }
+}
@@ -0,0 +1,460 @@
+//! Namespace blueprints
+//!
+//! A blueprint is a parameterized set of entries, e.g. the standard config of
+//! an LLM application, that is applied to a new namespace with variables
+//! substituted. Each entry is a [`Template`] (`{{variable}}` placeholders and
+//! `{{config:...}}`/`{{secret:...}}` references to stored values) whose
+//! output is parsed as the entry's type. Blueprints are stored as config
+//! entries in the reserved [`BLUEPRINT_NAMESPACE`] (in `base`), so they share
+//! the version history and rollback of ordinary configs.
+//!
+//! Blueprint files are YAML, JSON or TOML:
+//!
+//! ```yaml
+//! name: llm-app
+//! description: Standard LLM application
+//! defaults:
+//!   model: gpt-4o-mini
+//! entries:
+//!   model:
+//!     template: "{{model}}"
+//!   max_tokens:
+//!     template: "{{max_tokens}}"
+//!     type: integer
+//!   api_key:
+//!     template: "{{api_key}}"
+//!     secret: true
+//! ```
+
+use crate::document::parse_typed;
+use crate::plan::{render_value, value_from_json};
+use crate::{
+    ConfigEntry, ConfigError, ConfigManager, ConfigValue, DocumentFormat, Environment, Result,
+    SetOptions,
+};
+use chrono::{DateTime, Utc};
+use llm_config_templates::{ConfigSource, Template, TemplateEngine};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Namespace blueprints are stored in
+pub const BLUEPRINT_NAMESPACE: &str = "_blueprints";
+
+/// Type names an entry can be parsed as
+const ENTRY_TYPES: [&str; 9] = [
+    "string", "integer", "float", "boolean", "duration", "size", "url", "array", "object",
+];
+
+/// A parameterized set of entries that can be applied to a namespace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Default values for variables
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+    /// Entries to create, by key
+    pub entries: BTreeMap<String, BlueprintEntry>,
+}
+
+/// An entry of a blueprint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlueprintEntry {
+    /// Value template with `{{variable}}` placeholders
+    pub template: String,
+    /// Type the rendered text is parsed as (e.g. `integer` or `duration`);
+    /// without one, JSON text is stored as such and anything else as a string
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<String>,
+    /// Store the rendered value as an encrypted secret
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+}
+
+impl Blueprint {
+    /// Parse a blueprint file
+    pub fn parse(content: &str, format: DocumentFormat) -> Result<Self> {
+        let parsed = match format {
+            DocumentFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            DocumentFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            DocumentFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            DocumentFormat::Csv => Err("blueprints are YAML, JSON or TOML".to_string()),
+        };
+        let blueprint: Self = parsed
+            .map_err(|e| ConfigError::ValidationError(format!("Invalid blueprint: {}", e)))?;
+        blueprint.validate()?;
+        Ok(blueprint)
+    }
+
+    /// Check that the name, templates and entry types are valid
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| {
+            ConfigError::ValidationError(format!("Invalid blueprint {}: {}", self.name, reason))
+        };
+        if self.name.is_empty() {
+            return Err(ConfigError::ValidationError("Blueprint name is empty".to_string()));
+        }
+        if self.entries.is_empty() {
+            return Err(invalid("no entries".to_string()));
+        }
+        for (key, entry) in &self.entries {
+            Template::new(key.clone(), entry.template.clone())
+                .map_err(|e| invalid(format!("{}: {}", key, e)))?;
+            if let Some(kind) = &entry.value_type {
+                if !ENTRY_TYPES.contains(&kind.as_str()) {
+                    return Err(invalid(format!(
+                        "{}: unknown type {} (expected one of {})",
+                        key,
+                        kind,
+                        ENTRY_TYPES.join(", ")
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Variables used by the entries, sorted
+    pub fn variables(&self) -> Result<Vec<String>> {
+        let engine = self.engine()?;
+        let mut variables = BTreeSet::new();
+        for key in self.entries.keys() {
+            if let Some(template) = engine.get(key) {
+                variables.extend(template.required_vars.iter().cloned());
+            }
+        }
+        Ok(variables.into_iter().collect())
+    }
+
+    /// Template engine with one template per entry, named by its key
+    pub fn engine(&self) -> Result<TemplateEngine> {
+        let mut engine = TemplateEngine::new();
+        for (key, entry) in &self.entries {
+            let mut template = Template::new(key.clone(), entry.template.clone())?;
+            for (var, value) in &self.defaults {
+                template = template.with_default(var.clone(), value.clone());
+            }
+            engine.register(template);
+        }
+        Ok(engine)
+    }
+}
+
+/// A blueprint together with the entry it is stored in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredBlueprint {
+    pub blueprint: Blueprint,
+    pub version: u64,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: String,
+}
+
+impl TryFrom<ConfigEntry> for StoredBlueprint {
+    type Error = ConfigError;
+
+    fn try_from(entry: ConfigEntry) -> Result<Self> {
+        let mut json = render_value(&entry.value);
+        if let Some(fields) = json.as_object_mut() {
+            fields.insert("name".to_string(), entry.key.clone().into());
+        }
+        let blueprint = serde_json::from_value(json).map_err(|e| {
+            ConfigError::ValidationError(format!("Invalid blueprint {}: {}", entry.key, e))
+        })?;
+
+        Ok(Self {
+            blueprint,
+            version: entry.version,
+            updated_at: entry.metadata.updated_at,
+            updated_by: entry.metadata.updated_by,
+        })
+    }
+}
+
+/// Outcome of applying a blueprint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedBlueprint {
+    pub blueprint: String,
+    /// Version of the blueprint that was applied
+    pub version: u64,
+    pub namespace: String,
+    pub environment: Environment,
+    /// Keys created, sorted
+    pub created: Vec<String>,
+    /// Keys stored as secrets (also listed as created)
+    pub secrets: Vec<String>,
+}
+
+/// Blueprint registry backed by a [`ConfigManager`]
+pub struct BlueprintRegistry<'a> {
+    manager: &'a ConfigManager,
+}
+
+impl<'a> BlueprintRegistry<'a> {
+    /// Create a registry over a manager's storage
+    pub fn new(manager: &'a ConfigManager) -> Self {
+        Self { manager }
+    }
+
+    /// Save a new version of a blueprint on behalf of a user
+    pub fn put(&self, blueprint: &Blueprint, user: &str, options: &SetOptions) -> Result<StoredBlueprint> {
+        blueprint.validate()?;
+        let mut json = serde_json::to_value(blueprint)
+            .map_err(|e| ConfigError::ValidationError(format!("Invalid blueprint: {}", e)))?;
+        if let Some(fields) = json.as_object_mut() {
+            fields.remove("name");
+        }
+
+        let entry = self.manager.set_with_options(
+            BLUEPRINT_NAMESPACE,
+            blueprint.name.clone(),
+            value_from_json(&json),
+            Environment::Base,
+            user,
+            options,
+        )?;
+        entry.try_into()
+    }
+
+    /// Get a blueprint
+    pub fn get(&self, name: &str) -> Result<Option<StoredBlueprint>> {
+        self.manager
+            .get(BLUEPRINT_NAMESPACE, name, Environment::Base)?
+            .map(StoredBlueprint::try_from)
+            .transpose()
+    }
+
+    /// All blueprints, sorted by name
+    pub fn list(&self) -> Result<Vec<StoredBlueprint>> {
+        self.manager
+            .list(BLUEPRINT_NAMESPACE, Environment::Base)?
+            .into_iter()
+            .map(StoredBlueprint::try_from)
+            .collect()
+    }
+
+    /// Apply a blueprint to a namespace on behalf of a user
+    ///
+    /// References to stored values are resolved through the manager without
+    /// access checks.
+    pub fn apply(
+        &self,
+        name: &str,
+        namespace: &str,
+        env: Environment,
+        vars: &HashMap<String, String>,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<AppliedBlueprint> {
+        self.apply_with_source(name, namespace, env, vars, user, options, self.manager)
+    }
+
+    /// Apply a blueprint to a namespace, resolving references to stored
+    /// values through `source`
+    ///
+    /// Fails without writing anything if a variable is unknown or missing,
+    /// an entry does not render as its type, or the namespace already has one
+    /// of the blueprint's keys in the environment.
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_with_source<S>(
+        &self,
+        name: &str,
+        namespace: &str,
+        env: Environment,
+        vars: &HashMap<String, String>,
+        user: &str,
+        options: &SetOptions,
+        source: &S,
+    ) -> Result<AppliedBlueprint>
+    where
+        S: ConfigSource<Environment = Environment>,
+    {
+        let stored = self
+            .get(name)?
+            .ok_or_else(|| ConfigError::NotFound(format!("Blueprint not found: {}", name)))?;
+        let blueprint = &stored.blueprint;
+
+        let variables = blueprint.variables()?;
+        if let Some(unknown) = vars.keys().find(|var| !variables.contains(var)) {
+            return Err(ConfigError::ValidationError(format!(
+                "Unknown variable {} for blueprint {} (expected {})",
+                unknown,
+                name,
+                variables.join(", ")
+            )));
+        }
+
+        let engine = blueprint.engine()?;
+        let mut values = Vec::new();
+        for (key, entry) in &blueprint.entries {
+            if self.manager.get(namespace, key, env)?.is_some() {
+                return Err(ConfigError::AlreadyExists(format!(
+                    "{}/{} already exists in {}",
+                    namespace, key, env
+                )));
+            }
+            let Some(template) = engine.get(key) else {
+                continue;
+            };
+            let text = template.render_with_source(vars, env, source)?;
+            let value = match (entry.secret, &entry.value_type) {
+                (true, _) => None,
+                (false, Some(kind)) => Some(parse_typed(kind, &text).ok_or_else(|| {
+                    ConfigError::ValidationError(format!("{} rendered as {:?}, not a valid {}", key, text, kind))
+                })?),
+                (false, None) => Some(
+                    serde_json::from_str(&text)
+                        .map(|json| value_from_json(&json))
+                        .unwrap_or(ConfigValue::String(text.clone())),
+                ),
+            };
+            values.push((key, text, value));
+        }
+
+        let mut applied = AppliedBlueprint {
+            blueprint: blueprint.name.clone(),
+            version: stored.version,
+            namespace: namespace.to_string(),
+            environment: env,
+            created: Vec::new(),
+            secrets: Vec::new(),
+        };
+        for (key, text, value) in values {
+            match value {
+                Some(value) => {
+                    self.manager.set_with_options(namespace, key, value, env, user, options)?;
+                }
+                None => {
+                    self.manager
+                        .set_secret_with_options(namespace, key, text.as_bytes(), env, user, options)?;
+                    applied.secrets.push(key.clone());
+                }
+            }
+            applied.created.push(key.clone());
+        }
+
+        Ok(applied)
+    }
+}
+
+impl ConfigManager {
+    /// Blueprints stored by this manager
+    pub fn blueprints(&self) -> BlueprintRegistry<'_> {
+        BlueprintRegistry::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use tempfile::TempDir;
+
+    const BLUEPRINT: &str = r#"
+name: llm-app
+description: Standard LLM application
+defaults:
+  model: gpt-4o-mini
+  max_tokens: "4096"
+entries:
+  model:
+    template: "{{model}}"
+  max_tokens:
+    template: "{{max_tokens}}"
+    type: integer
+  timeout:
+    template: "30s"
+    type: duration
+  endpoint:
+    template: "{{config:shared/llm.gateway}}/{{app}}"
+  api_key:
+    template: "{{api_key}}"
+    secret: true
+"#;
+
+    #[test]
+    fn test_apply_blueprint() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key);
+        let options = SetOptions::default();
+        let env = Environment::Staging;
+        manager
+            .set("shared/llm", "gateway", ConfigValue::String("https://gw.internal".into()), env, "alice")
+            .unwrap();
+
+        let blueprint = Blueprint::parse(BLUEPRINT, DocumentFormat::Yaml).unwrap();
+        assert_eq!(blueprint.variables().unwrap(), vec!["api_key", "app", "max_tokens", "model"]);
+        let stored = manager.blueprints().put(&blueprint, "alice", &options).unwrap();
+        assert_eq!(stored.version, 1);
+        assert_eq!(stored.blueprint, blueprint);
+        assert_eq!(manager.blueprints().list().unwrap().len(), 1);
+
+        let mut vars = HashMap::new();
+        vars.insert("app".to_string(), "support-bot".to_string());
+        vars.insert("max_tokens".to_string(), "8192".to_string());
+
+        // Missing variables fail before anything is written
+        let blueprints = manager.blueprints();
+        assert!(matches!(
+            blueprints.apply("llm-app", "apps/support-bot", env, &vars, "alice", &options),
+            Err(ConfigError::TemplateError(_))
+        ));
+        assert!(manager.list("apps/support-bot", env).unwrap().is_empty());
+
+        vars.insert("api_key".to_string(), "sk-123".to_string());
+        let applied = blueprints
+            .apply("llm-app", "apps/support-bot", env, &vars, "alice", &options)
+            .unwrap();
+        assert_eq!(applied.created, vec!["api_key", "endpoint", "max_tokens", "model", "timeout"]);
+        assert_eq!(applied.secrets, vec!["api_key"]);
+
+        let get = |key: &str| manager.get("apps/support-bot", key, env).unwrap().unwrap().value;
+        assert_eq!(get("model").as_str(), Some("gpt-4o-mini"));
+        assert_eq!(get("max_tokens").as_i64(), Some(8192));
+        assert_eq!(get("timeout").type_name(), "duration");
+        assert_eq!(get("endpoint").as_str(), Some("https://gw.internal/support-bot"));
+        assert!(get("api_key").is_secret());
+
+        // Existing keys are not overwritten
+        assert!(matches!(
+            blueprints.apply("llm-app", "apps/support-bot", env, &vars, "alice", &options),
+            Err(ConfigError::AlreadyExists(_))
+        ));
+
+        vars.insert("max_tokens".to_string(), "lots".to_string());
+        assert!(matches!(
+            blueprints.apply("llm-app", "apps/other", env, &vars, "alice", &options),
+            Err(ConfigError::ValidationError(_))
+        ));
+        vars.insert("modle".to_string(), "gpt-4".to_string());
+        assert!(matches!(
+            blueprints.apply("llm-app", "apps/other", env, &vars, "alice", &options),
+            Err(ConfigError::ValidationError(_))
+        ));
+        assert!(matches!(
+            blueprints.apply("missing", "apps/other", env, &vars, "alice", &options),
+            Err(ConfigError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_blueprint() {
+        let toml = r#"
+name = "minimal"
+
+[entries.model]
+template = "{{model}}"
+"#;
+        let blueprint = Blueprint::parse(toml, DocumentFormat::Toml).unwrap();
+        assert_eq!(blueprint.entries["model"].template, "{{model}}");
+
+        let unknown_type = r#"{"name": "x", "entries": {"n": {"template": "1", "type": "number"}}}"#;
+        assert!(Blueprint::parse(unknown_type, DocumentFormat::Json).is_err());
+        let unclosed = r#"{"name": "x", "entries": {"n": {"template": "{{n"}}}"#;
+        assert!(Blueprint::parse(unclosed, DocumentFormat::Json).is_err());
+        assert!(Blueprint::parse("name,template\n", DocumentFormat::Csv).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! Human-readable release notes from version history
+//!
+//! A [`Changelog`] collects every version recorded for a namespace in a time
+//! window, grouped by key and environment, with the author, the change
+//! description and a diff against the previous version. Secret values are
+//! masked as in [plans](crate::plan).
+
+use crate::plan::render_value;
+use crate::{ConfigError, Environment, Result, VersionEntry};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A single recorded change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangelogChange {
+    pub version: u64,
+    pub changed_at: DateTime<Utc>,
+    pub changed_by: String,
+    pub description: Option<String>,
+    /// Value before the change (`None` when the config was created)
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: serde_json::Value,
+}
+
+impl ChangelogChange {
+    /// Lines describing how the value changed
+    ///
+    /// Objects are compared field by field; other values as a whole.
+    pub fn diff(&self) -> Vec<String> {
+        let compact = |value: &serde_json::Value| value.to_string();
+
+        match (&self.old_value, &self.new_value) {
+            (None, new) => vec![format!("set to {}", compact(new))],
+            (Some(serde_json::Value::Object(old)), serde_json::Value::Object(new)) => {
+                let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+                fields
+                    .into_iter()
+                    .filter_map(|field| match (old.get(field), new.get(field)) {
+                        (None, Some(added)) => Some(format!("{}: added {}", field, compact(added))),
+                        (Some(removed), None) => {
+                            Some(format!("{}: removed {}", field, compact(removed)))
+                        }
+                        (Some(before), Some(after)) if before != after => Some(format!(
+                            "{}: {} -> {}",
+                            field,
+                            compact(before),
+                            compact(after)
+                        )),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            (Some(old), new) if old == new => vec!["value unchanged".to_string()],
+            (Some(old), new) => vec![format!("{} -> {}", compact(old), compact(new))],
+        }
+    }
+}
+
+/// Changes to one key in one environment, newest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangelogGroup {
+    pub key: String,
+    pub environment: Environment,
+    pub changes: Vec<ChangelogChange>,
+}
+
+/// Release notes for a namespace over a time window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Changelog {
+    pub namespace: String,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Groups sorted by key, then environment
+    pub groups: Vec<ChangelogGroup>,
+}
+
+impl Changelog {
+    /// Build a changelog from the version history of a namespace
+    ///
+    /// `versions` may contain entries outside the window; they are used as
+    /// the previous value of the first change in the window.
+    pub fn build(
+        namespace: impl Into<String>,
+        versions: Vec<VersionEntry>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Self {
+        let mut histories: BTreeMap<(String, Environment), Vec<VersionEntry>> = BTreeMap::new();
+        for version in versions {
+            histories
+                .entry((version.key.clone(), version.environment))
+                .or_default()
+                .push(version);
+        }
+
+        let groups = histories
+            .into_iter()
+            .filter_map(|((key, environment), mut history)| {
+                history.sort_by_key(|version| (version.version, version.created_at));
+
+                let mut changes: Vec<ChangelogChange> = history
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, version)| version.created_at >= since && version.created_at <= until)
+                    .map(|(i, version)| ChangelogChange {
+                        version: version.version,
+                        changed_at: version.created_at,
+                        changed_by: version.created_by.clone(),
+                        description: version.change_description.clone(),
+                        old_value: i.checked_sub(1).map(|prev| render_value(&history[prev].value)),
+                        new_value: render_value(&version.value),
+                    })
+                    .collect();
+                changes.reverse();
+
+                (!changes.is_empty()).then_some(ChangelogGroup {
+                    key,
+                    environment,
+                    changes,
+                })
+            })
+            .collect();
+
+        Self {
+            namespace: namespace.into(),
+            since,
+            until,
+            groups,
+        }
+    }
+
+    /// Check if nothing changed in the window
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Total number of changes
+    pub fn change_count(&self) -> usize {
+        self.groups.iter().map(|group| group.changes.len()).sum()
+    }
+
+    /// Everyone who made a change, sorted
+    pub fn authors(&self) -> Vec<String> {
+        let authors: BTreeSet<&String> = self
+            .groups
+            .iter()
+            .flat_map(|group| group.changes.iter().map(|change| &change.changed_by))
+            .collect();
+        authors.into_iter().cloned().collect()
+    }
+
+    /// Render the changelog as Markdown release notes
+    pub fn to_markdown(&self) -> String {
+        const TIME_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+
+        let mut out = format!("# Changelog: {}\n\n", self.namespace);
+        out.push_str(&format!(
+            "{} to {}\n\n",
+            self.since.format(TIME_FORMAT),
+            self.until.format(TIME_FORMAT)
+        ));
+
+        if self.is_empty() {
+            out.push_str("No changes.\n");
+            return out;
+        }
+
+        out.push_str(&format!(
+            "{} change(s) by {}\n",
+            self.change_count(),
+            self.authors().join(", ")
+        ));
+
+        for group in &self.groups {
+            out.push_str(&format!("\n## {} ({})\n\n", group.key, group.environment));
+            for change in &group.changes {
+                out.push_str(&format!(
+                    "- v{} by {} at {}",
+                    change.version,
+                    change.changed_by,
+                    change.changed_at.format(TIME_FORMAT)
+                ));
+                if let Some(description) = &change.description {
+                    out.push_str(&format!(": {}", description));
+                }
+                out.push('\n');
+                for line in change.diff() {
+                    out.push_str(&format!("  - `{}`\n", line));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Parse the start of a changelog window
+///
+/// Accepts a relative age (`30m`, `12h`, `7d`, `2w`) counted back from `now`,
+/// an RFC 3339 timestamp or a date (`2024-01-31`, midnight UTC).
+pub fn parse_since(since: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let since = since.trim();
+    let invalid = || ConfigError::ValidationError(format!("Invalid time: {}", since));
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(since) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?.and_utc());
+    }
+
+    let (split, _) = since.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = since.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+
+    Ok(now - age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigValue;
+    use llm_config_crypto::{encrypt, Algorithm, SecretKey};
+    use uuid::Uuid;
+
+    fn version(key: &str, version: u64, value: ConfigValue, days_ago: i64, by: &str) -> VersionEntry {
+        VersionEntry {
+            version,
+            config_id: Uuid::nil(),
+            namespace: "ml/serving".to_string(),
+            key: key.to_string(),
+            value,
+            environment: Environment::Production,
+            created_at: Utc::now() - Duration::days(days_ago),
+            created_by: by.to_string(),
+            change_description: Some(format!("v{}", version)),
+        }
+    }
+
+    #[test]
+    fn test_build_changelog() {
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let secret = encrypt(&key, b"sk-test", None).unwrap();
+        let versions = vec![
+            version("model", 1, ConfigValue::String("gpt-4".into()), 30, "alice"),
+            version("model", 2, ConfigValue::String("gpt-4o".into()), 2, "bob"),
+            version("model", 3, ConfigValue::String("gpt-4o-mini".into()), 1, "alice"),
+            version("api_key", 1, ConfigValue::Secret(secret), 1, "carol"),
+            version("old", 1, ConfigValue::Integer(1), 20, "alice"),
+        ];
+
+        let now = Utc::now();
+        let changelog = Changelog::build("ml/serving", versions, now - Duration::days(7), now);
+
+        assert_eq!(changelog.change_count(), 3);
+        assert_eq!(changelog.authors(), vec!["alice", "bob", "carol"]);
+        assert_eq!(changelog.groups[0].key, "api_key");
+        assert_eq!(changelog.groups[0].changes[0].new_value, serde_json::json!("<secret>"));
+
+        let model = &changelog.groups[1];
+        assert_eq!(model.changes.iter().map(|c| c.version).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(model.changes[1].diff(), vec!["\"gpt-4\" -> \"gpt-4o\""]);
+
+        let markdown = changelog.to_markdown();
+        assert!(markdown.contains("## model (production)"));
+        assert!(markdown.contains("- v2 by bob"));
+        assert!(!markdown.contains("## old"));
+    }
+
+    #[test]
+    fn test_object_diff() {
+        let mut old = BTreeMap::new();
+        old.insert("temperature".to_string(), ConfigValue::Float(0.7));
+        old.insert("top_p".to_string(), ConfigValue::Float(1.0));
+        let mut new = old.clone();
+        new.insert("temperature".to_string(), ConfigValue::Float(0.2));
+        new.remove("top_p");
+        new.insert("max_tokens".to_string(), ConfigValue::Integer(512));
+
+        let change = ChangelogChange {
+            version: 2,
+            changed_at: Utc::now(),
+            changed_by: "alice".to_string(),
+            description: None,
+            old_value: Some(render_value(&ConfigValue::Object(old.into_iter().collect()))),
+            new_value: render_value(&ConfigValue::Object(new.into_iter().collect())),
+        };
+
+        assert_eq!(
+            change.diff(),
+            vec!["max_tokens: added 512", "temperature: 0.7 -> 0.2", "top_p: removed 1.0"]
+        );
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = Utc::now();
+        assert_eq!(parse_since("7d", now).unwrap(), now - Duration::days(7));
+        assert_eq!(parse_since("12h", now).unwrap(), now - Duration::hours(12));
+        assert_eq!(
+            parse_since("2024-01-31", now).unwrap().to_rfc3339(),
+            "2024-01-31T00:00:00+00:00"
+        );
+        assert!(parse_since("2024-01-31T10:00:00Z", now).is_ok());
+        assert!(parse_since("7y", now).is_err());
+        assert!(parse_since("", now).is_err());
+    }
+}
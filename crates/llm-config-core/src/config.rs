@@ -1,6 +1,18 @@
 //! Core configuration types
 
-pub use llm_config_storage::{ConfigEntry, ConfigValue, Environment, ConfigMetadata};
+pub use llm_config_storage::{
+    ConfigEntry, ConfigValue, Environment, ConfigMetadata, HistoryOptions, OperationContext,
+    MergeStrategy, RetentionPolicy, ValueType,
+};
+pub use llm_config_storage::{
+    BlobStats, CompactionReport, FsckIssue, FsckIssueKind, FsckReport, StorageError, DEFAULT_DEDUP_MIN_SIZE,
+};
+pub use llm_config_storage::{
+    AuditRetention, NamespaceRetention, NamespaceRule, RetentionConfig, RetentionReport, RETENTION_FILE,
+};
+pub use llm_config_storage::{KvList, KvPair, KvStore, MemoryKv, DEFAULT_KV_PREFIX};
+#[cfg(feature = "consul")]
+pub use llm_config_storage::{ConsulConfig, ConsulKv};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
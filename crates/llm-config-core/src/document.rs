@@ -0,0 +1,468 @@
+//! Namespace document (TOML, YAML, JSON and CSV) import and export
+//!
+//! A document holds the keys of one namespace. In TOML, YAML and JSON every
+//! top-level key is a config key and nested tables become `Object` values.
+//! CSV documents have a `key,type,value` header and one row per key, with
+//! arrays and objects written as JSON. Durations, sizes and URLs are written
+//! as text and imported back as their type when the stored value (or the CSV
+//! type column) has it. Secrets are left out of exports unless revealed; on
+//! import, keys that look like credentials (see [`is_secret_key`]) or are
+//! already secrets are encrypted.
+
+use crate::dotenv::is_secret_key;
+use crate::plan::{render_value, value_from_json};
+use crate::{ConfigError, ConfigManager, ConfigValue, Environment, Result, SetOptions};
+use llm_config_storage::csv;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Header row of CSV documents
+const CSV_HEADER: [&str; 3] = ["key", "type", "value"];
+
+/// Format of a namespace document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    Toml,
+    Yaml,
+    Json,
+    Csv,
+}
+
+impl DocumentFormat {
+    /// Format of a file, by its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl std::str::FromStr for DocumentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "toml" => Ok(DocumentFormat::Toml),
+            "yaml" | "yml" => Ok(DocumentFormat::Yaml),
+            "json" => Ok(DocumentFormat::Json),
+            "csv" => Ok(DocumentFormat::Csv),
+            _ => Err(format!("Unknown document format: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for DocumentFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentFormat::Toml => write!(f, "toml"),
+            DocumentFormat::Yaml => write!(f, "yaml"),
+            DocumentFormat::Json => write!(f, "json"),
+            DocumentFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// Outcome of a document import
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentImport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    /// Keys whose stored value already matched
+    pub unchanged: Vec<String>,
+    /// Keys stored as secrets (also listed as created, updated or unchanged)
+    pub secrets: Vec<String>,
+}
+
+/// A key read from a document
+struct DocumentEntry {
+    key: String,
+    value: ConfigValue,
+    /// Marked as a secret by the document (CSV type `secret`)
+    secret: bool,
+}
+
+impl ConfigManager {
+    /// Render a namespace as a document, with overrides applied
+    ///
+    /// Secrets are left out unless `reveal_secrets` is set, in which case
+    /// they are decrypted.
+    pub fn export_document(
+        &self,
+        namespace: &str,
+        env: Environment,
+        format: DocumentFormat,
+        reveal_secrets: bool,
+    ) -> Result<String> {
+        let mut values = Vec::new();
+        for (key, value) in self.resolve_namespace(namespace, env)? {
+            let secret = value.is_secret();
+            if secret && !reveal_secrets {
+                continue;
+            }
+            values.push((key, self.reveal(value)?, secret));
+        }
+
+        if format == DocumentFormat::Csv {
+            let mut document = csv::render_row(CSV_HEADER);
+            for (key, value, secret) in &values {
+                let kind = if *secret { "secret" } else { value.type_name() };
+                document.push_str(&csv::render_row([key.as_str(), kind, &value_text(value)]));
+            }
+            return Ok(document);
+        }
+
+        let document: serde_json::Map<String, serde_json::Value> = values
+            .iter()
+            .map(|(key, value, _)| (key.clone(), render_value(value)))
+            .collect();
+        let rendered = match format {
+            DocumentFormat::Toml => toml::to_string(&document).map_err(|e| e.to_string()),
+            DocumentFormat::Yaml => serde_yaml::to_string(&document).map_err(|e| e.to_string()),
+            _ => serde_json::to_string_pretty(&document)
+                .map(|json| json + "\n")
+                .map_err(|e| e.to_string()),
+        };
+        rendered.map_err(|e| {
+            ConfigError::ValidationError(format!("Cannot render {} as {}: {}", namespace, format, e))
+        })
+    }
+
+    /// Import a document into a namespace on behalf of a user
+    ///
+    /// Keys not in the document are left alone. Keys matching
+    /// [`is_secret_key`], already stored as secrets or of CSV type `secret`
+    /// are encrypted. Values that match what is stored are left untouched.
+    pub fn import_document(
+        &self,
+        namespace: &str,
+        content: &str,
+        format: DocumentFormat,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<DocumentImport> {
+        let entries = parse_document(content, format)?;
+        let mut import = DocumentImport::default();
+
+        for DocumentEntry { key, value, secret } in entries {
+            let existing = self.storage().get(namespace, &key, env)?;
+            if let Some(entry) = &existing {
+                self.check_binding(entry)?;
+            }
+            let existing = existing.map(|entry| entry.value);
+            let secret =
+                secret || is_secret_key(&key) || existing.as_ref().is_some_and(ConfigValue::is_secret);
+
+            if secret {
+                let plaintext = value_text(&value);
+                let unchanged = match &existing {
+                    Some(current) if current.is_secret() => {
+                        self.reveal(current.clone())?.as_str() == Some(plaintext.as_str())
+                    }
+                    _ => false,
+                };
+                if !unchanged {
+                    self.set_secret_with_options(namespace, &key, plaintext.as_bytes(), env, user, options)?;
+                }
+                import.secrets.push(key.clone());
+                record(&mut import, key, existing.is_some(), unchanged);
+            } else {
+                let value = typed_value(existing.as_ref(), value);
+                let unchanged = existing
+                    .as_ref()
+                    .is_some_and(|current| render_value(current) == render_value(&value));
+                if !unchanged {
+                    self.set_with_options(namespace, &key, value, env, user, options)?;
+                }
+                record(&mut import, key, existing.is_some(), unchanged);
+            }
+        }
+
+        Ok(import)
+    }
+}
+
+/// File a key of an import under created, updated or unchanged
+fn record(import: &mut DocumentImport, key: String, existed: bool, unchanged: bool) {
+    match (existed, unchanged) {
+        (_, true) => import.unchanged.push(key),
+        (true, false) => import.updated.push(key),
+        (false, false) => import.created.push(key),
+    }
+}
+
+/// Parse a document into its keys
+fn parse_document(content: &str, format: DocumentFormat) -> Result<Vec<DocumentEntry>> {
+    let invalid = |reason: String| ConfigError::ValidationError(format!("Invalid {} document: {}", format, reason));
+
+    let document = match format {
+        DocumentFormat::Csv => return parse_csv(content),
+        DocumentFormat::Toml => toml::from_str::<toml::Table>(content)
+            .map(|table| toml_to_json(toml::Value::Table(table)))
+            .map_err(|e| invalid(e.to_string()))?,
+        DocumentFormat::Yaml => serde_yaml::from_str(content).map_err(|e| invalid(e.to_string()))?,
+        DocumentFormat::Json => serde_json::from_str(content).map_err(|e| invalid(e.to_string()))?,
+    };
+
+    let map = match document {
+        serde_json::Value::Object(map) => map,
+        // An empty YAML document
+        serde_json::Value::Null => serde_json::Map::new(),
+        _ => return Err(invalid("expected a mapping of keys to values".to_string())),
+    };
+    Ok(map
+        .into_iter()
+        .map(|(key, value)| DocumentEntry {
+            key,
+            value: value_from_json(&value),
+            secret: false,
+        })
+        .collect())
+}
+
+/// Parse the rows of a CSV document
+fn parse_csv(content: &str) -> Result<Vec<DocumentEntry>> {
+    let mut rows = csv::parse(content)?.into_iter();
+    if !rows.next().is_some_and(|header| header == CSV_HEADER) {
+        return Err(ConfigError::ValidationError(format!(
+            "Invalid csv document: expected the header {}",
+            CSV_HEADER.join(",")
+        )));
+    }
+
+    rows.enumerate()
+        .map(|(index, row)| {
+            let invalid = |reason: String| {
+                ConfigError::ValidationError(format!("Invalid csv document: row {}: {}", index + 2, reason))
+            };
+            let [key, kind, text]: [String; 3] = row
+                .try_into()
+                .map_err(|row: Vec<String>| invalid(format!("expected 3 fields, found {}", row.len())))?;
+            let value = parse_typed(&kind, &text)
+                .ok_or_else(|| invalid(format!("invalid {} value for {}", kind, key)))?;
+            Ok(DocumentEntry {
+                secret: kind == "secret",
+                key,
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Parse the text of a value of the given type (as named by [`ConfigValue::type_name`])
+pub(crate) fn parse_typed(kind: &str, text: &str) -> Option<ConfigValue> {
+    let string = || ConfigValue::String(text.to_string());
+    match kind {
+        "" | "string" | "secret" => Some(string()),
+        "integer" => text.parse().ok().map(ConfigValue::Integer),
+        "float" => text.parse().ok().map(ConfigValue::Float),
+        "boolean" => text.parse().ok().map(ConfigValue::Boolean),
+        "duration" => string().as_duration().map(ConfigValue::Duration),
+        "size" => string().as_bytes().map(ConfigValue::Size),
+        "url" => string().as_url().map(ConfigValue::Url),
+        "array" | "object" => serde_json::from_str(text)
+            .ok()
+            .map(|json| value_from_json(&json))
+            .filter(|value| value.type_name() == kind),
+        _ => None,
+    }
+}
+
+/// Value as the type of the stored value, if it converts (e.g. `"30s"` to a duration)
+fn typed_value(current: Option<&ConfigValue>, value: ConfigValue) -> ConfigValue {
+    let converted = match (current, &value) {
+        (Some(ConfigValue::Float(_)), ConfigValue::Integer(i)) => Some(ConfigValue::Float(*i as f64)),
+        (Some(current @ (ConfigValue::Duration(_) | ConfigValue::Size(_) | ConfigValue::Url(_))), _) => {
+            parse_typed(current.type_name(), &value_text(&value))
+        }
+        _ => None,
+    };
+    converted.unwrap_or(value)
+}
+
+/// Text of a value: strings as-is, typed values as parsed, other values as JSON
+fn value_text(value: &ConfigValue) -> String {
+    match value {
+        ConfigValue::String(s) => s.clone(),
+        other => other.typed_text().unwrap_or_else(|| render_value(other).to_string()),
+    }
+}
+
+/// Convert a TOML value to JSON (datetimes become strings)
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => serde_json::Value::String(s),
+        toml::Value::Integer(i) => serde_json::Value::from(i),
+        toml::Value::Float(f) => serde_json::Value::from(f),
+        toml::Value::Boolean(b) => serde_json::Value::Bool(b),
+        toml::Value::Datetime(datetime) => serde_json::Value::String(datetime.to_string()),
+        toml::Value::Array(items) => items.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => serde_json::Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn manager(temp_dir: &TempDir) -> ConfigManager {
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key)
+    }
+
+    #[test]
+    fn test_document_format() {
+        assert_eq!(DocumentFormat::from_path(Path::new("app.yml")), Some(DocumentFormat::Yaml));
+        assert_eq!(DocumentFormat::from_path(Path::new("app.TOML")), Some(DocumentFormat::Toml));
+        assert_eq!(DocumentFormat::from_path(Path::new("app.env")), None);
+        assert_eq!("csv".parse::<DocumentFormat>().unwrap().to_string(), "csv");
+    }
+
+    #[test]
+    fn test_toml_import() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        let env = Environment::Production;
+        let options = SetOptions::default();
+        manager
+            .set("app", "timeout", ConfigValue::Duration(Duration::from_secs(30)), env, "alice")
+            .unwrap();
+        manager
+            .set("app", "temperature", ConfigValue::Float(0.5), env, "alice")
+            .unwrap();
+
+        let content = r#"
+model = "gpt-4"
+timeout = "1m"
+temperature = 1
+api_key = "sk-123"
+"database.host" = "db.internal"
+
+[limits]
+max_tokens = 4096
+stop = ["\n\n", "END"]
+"#;
+        let import = manager
+            .import_document("app", content, DocumentFormat::Toml, env, "alice", &options)
+            .unwrap();
+        assert_eq!(import.created, vec!["api_key", "database.host", "limits", "model"]);
+        assert_eq!(import.updated, vec!["temperature", "timeout"]);
+        assert_eq!(import.secrets, vec!["api_key"]);
+
+        // Nested tables become objects; existing values keep their type
+        let limits = manager.get("app", "limits", env).unwrap().unwrap().value;
+        let ConfigValue::Object(limits) = limits else { panic!("expected an object") };
+        assert_eq!(limits["max_tokens"].as_i64(), Some(4096));
+        let timeout = manager.get("app", "timeout", env).unwrap().unwrap().value;
+        assert_eq!(timeout.as_duration(), Some(Duration::from_secs(60)));
+        let temperature = manager.get("app", "temperature", env).unwrap().unwrap().value;
+        assert_eq!(temperature.as_f64(), Some(1.0));
+        assert!(manager.is_secret("app", "api_key", env).unwrap());
+
+        // Secrets are left out unless revealed; the export imports back unchanged
+        let exported = manager
+            .export_document("app", env, DocumentFormat::Toml, false)
+            .unwrap();
+        assert!(exported.contains("\"database.host\" = \"db.internal\"\n"));
+        assert!(exported.contains("[limits]\n"));
+        assert!(!exported.contains("api_key"));
+        let revealed = manager
+            .export_document("app", env, DocumentFormat::Toml, true)
+            .unwrap();
+        assert!(revealed.contains("api_key = \"sk-123\"\n"));
+        let import = manager
+            .import_document("app", &revealed, DocumentFormat::Toml, env, "alice", &options)
+            .unwrap();
+        assert!(import.created.is_empty() && import.updated.is_empty());
+        assert_eq!(import.unchanged.len(), 6);
+
+        assert!(manager
+            .import_document("app", "model = ", DocumentFormat::Toml, env, "alice", &options)
+            .is_err());
+    }
+
+    #[test]
+    fn test_yaml_and_json_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        let env = Environment::Staging;
+        let options = SetOptions::default();
+
+        let content = "model: gpt-4\nretries: 3\nfeatures:\n  streaming: true\n  regions: [eu, us]\n";
+        let import = manager
+            .import_document("app", content, DocumentFormat::Yaml, env, "alice", &options)
+            .unwrap();
+        assert_eq!(import.created, vec!["features", "model", "retries"]);
+
+        for format in [DocumentFormat::Yaml, DocumentFormat::Json] {
+            let exported = manager.export_document("app", env, format, false).unwrap();
+            let import = manager
+                .import_document("app", &exported, format, env, "alice", &options)
+                .unwrap();
+            assert_eq!(import.unchanged.len(), 3, "{}", exported);
+        }
+
+        assert!(manager
+            .import_document("app", "[1, 2]", DocumentFormat::Json, env, "alice", &options)
+            .is_err());
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        let env = Environment::Development;
+        let options = SetOptions::default();
+
+        let mut labels = HashMap::new();
+        labels.insert("team".to_string(), ConfigValue::String("ml".into()));
+        manager
+            .set("app", "labels", ConfigValue::Object(labels), env, "alice")
+            .unwrap();
+        manager
+            .set("app", "prompt", ConfigValue::String("Say \"hi\",\nthen stop".into()), env, "alice")
+            .unwrap();
+        manager
+            .set("app", "cache", ConfigValue::Size(512 * 1024 * 1024), env, "alice")
+            .unwrap();
+        manager.set_secret("app", "webhook", b"https://hooks.example.com/x", env, "alice").unwrap();
+
+        let exported = manager
+            .export_document("app", env, DocumentFormat::Csv, true)
+            .unwrap();
+        assert!(exported.starts_with("key,type,value\ncache,size,512MiB\n"));
+        assert!(exported.contains("labels,object,\"{\"\"team\"\":\"\"ml\"\"}\"\n"));
+        assert!(exported.contains("webhook,secret,https://hooks.example.com/x\n"));
+
+        let other = TempDir::new().unwrap();
+        let copy = self::manager(&other);
+        let import = copy
+            .import_document("app", &exported, DocumentFormat::Csv, env, "alice", &options)
+            .unwrap();
+        assert_eq!(import.created.len(), 4);
+        assert_eq!(import.secrets, vec!["webhook"]);
+        assert_eq!(copy.get("app", "cache", env).unwrap().unwrap().value.type_name(), "size");
+        assert_eq!(
+            copy.get("app", "prompt", env).unwrap().unwrap().value.as_str(),
+            Some("Say \"hi\",\nthen stop")
+        );
+
+        let invalid = "key,type,value\nretries,integer,three\n";
+        assert!(copy
+            .import_document("app", invalid, DocumentFormat::Csv, env, "alice", &options)
+            .is_err());
+        assert!(copy
+            .import_document("app", "model,gpt-4\n", DocumentFormat::Csv, env, "alice", &options)
+            .is_err());
+    }
+}
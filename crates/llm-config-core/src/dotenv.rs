@@ -0,0 +1,314 @@
+//! dotenv (`.env`) import and export
+//!
+//! Each `KEY=VALUE` pair maps to a config key of the same name in one
+//! namespace. Keys that look like credentials (see [`SECRET_KEY_PATTERNS`])
+//! are stored as encrypted secrets. Values are imported as strings unless the
+//! existing value has another type the text parses as, so an exported file
+//! imports back without type changes.
+
+use crate::plan::render_value;
+use crate::{ConfigError, ConfigManager, ConfigValue, Environment, Result, SetOptions};
+use llm_config_storage::dotenv;
+use serde::{Deserialize, Serialize};
+
+/// Words that mark a key as a secret
+///
+/// Matched case-insensitively against whole `_`-separated words, so
+/// `GITHUB_TOKEN` is a secret but `MAX_TOKENS` is not.
+pub const SECRET_KEY_PATTERNS: &[&str] = &[
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "TOKEN",
+    "API_KEY",
+    "APIKEY",
+    "PRIVATE_KEY",
+    "ACCESS_KEY",
+    "CREDENTIAL",
+    "CREDENTIALS",
+];
+
+/// Whether a dotenv key should be stored as a secret
+pub fn is_secret_key(key: &str) -> bool {
+    let key = format!("_{}_", key.to_ascii_uppercase().replace(['.', '-'], "_"));
+    SECRET_KEY_PATTERNS
+        .iter()
+        .any(|pattern| key.contains(&format!("_{}_", pattern)))
+}
+
+/// Outcome of a dotenv import
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DotenvImport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    /// Keys whose stored value already matched
+    pub unchanged: Vec<String>,
+    /// Keys stored as secrets (also listed as created, updated or unchanged)
+    pub secrets: Vec<String>,
+}
+
+impl ConfigManager {
+    /// Render a namespace as dotenv lines, with overrides applied and secrets decrypted
+    ///
+    /// Strings are written as-is and other values as JSON.
+    pub fn export_dotenv(&self, namespace: &str, env: Environment) -> Result<String> {
+        let mut pairs = Vec::new();
+        for (key, value) in self.resolve_namespace(namespace, env)? {
+            if !dotenv::is_valid_key(&key) {
+                return Err(ConfigError::ValidationError(format!(
+                    "Key {} is not a valid dotenv name",
+                    key
+                )));
+            }
+            pairs.push((key, self.env_text(value)?));
+        }
+
+        Ok(dotenv::render(
+            pairs.iter().map(|(key, value)| (key.as_str(), value.as_str())),
+        ))
+    }
+
+    /// A namespace as environment variables, with overrides applied and secrets decrypted
+    ///
+    /// Variable names are the keys as [`env_var_name`] maps them; values are
+    /// rendered as in [`export_dotenv`](Self::export_dotenv). Fails if a key
+    /// has no valid name or two keys map to the same one.
+    pub fn env_vars(&self, namespace: &str, env: Environment) -> Result<Vec<(String, String)>> {
+        let mut vars: Vec<(String, String)> = Vec::new();
+        for (key, value) in self.resolve_namespace(namespace, env)? {
+            let name = env_var_name(&key).ok_or_else(|| {
+                ConfigError::ValidationError(format!("Key {} is not a valid environment variable name", key))
+            })?;
+            if vars.iter().any(|(existing, _)| *existing == name) {
+                return Err(ConfigError::ValidationError(format!(
+                    "Key {} maps to environment variable {} more than once",
+                    key, name
+                )));
+            }
+            vars.push((name, self.env_text(value)?));
+        }
+        Ok(vars)
+    }
+
+    /// Render a namespace as `export NAME='value'` lines for POSIX shells
+    pub fn export_shell(&self, namespace: &str, env: Environment) -> Result<String> {
+        Ok(self
+            .env_vars(namespace, env)?
+            .iter()
+            .map(|(name, value)| format!("export {}={}\n", name, shell_quote(value)))
+            .collect())
+    }
+
+    /// Import dotenv content into a namespace on behalf of a user
+    ///
+    /// Keys matching [`SECRET_KEY_PATTERNS`], or already stored as secrets,
+    /// are encrypted. Values that match what is stored are left untouched.
+    pub fn import_dotenv(
+        &self,
+        namespace: &str,
+        content: &str,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<DotenvImport> {
+        let pairs = dotenv::parse(content)?;
+        let mut import = DotenvImport::default();
+
+        for (key, raw) in pairs {
+            let existing = self.storage().get(namespace, &key, env)?;
+            if let Some(entry) = &existing {
+                self.check_binding(entry)?;
+            }
+            let existing = existing.map(|entry| entry.value);
+            let secret = is_secret_key(&key) || existing.as_ref().is_some_and(ConfigValue::is_secret);
+
+            let unchanged = match &existing {
+                Some(current) if secret && current.is_secret() => {
+                    self.reveal(current.clone())?.as_str() == Some(raw.as_str())
+                }
+                Some(current) if !secret => {
+                    render_value(current) == render_value(&typed_value(current, &raw))
+                }
+                _ => false,
+            };
+
+            if unchanged {
+                import.unchanged.push(key.clone());
+            } else {
+                if secret {
+                    self.set_secret_with_options(namespace, &key, raw.as_bytes(), env, user, options)?;
+                } else {
+                    let value = match &existing {
+                        Some(current) => typed_value(current, &raw),
+                        None => ConfigValue::String(raw),
+                    };
+                    self.set_with_options(namespace, &key, value, env, user, options)?;
+                }
+
+                match existing {
+                    Some(_) => import.updated.push(key.clone()),
+                    None => import.created.push(key.clone()),
+                }
+            }
+
+            if secret {
+                import.secrets.push(key);
+            }
+        }
+
+        Ok(import)
+    }
+}
+
+/// Environment variable name of a config key
+///
+/// Upper-cased, with `.`, `-` and `/` replaced by `_`; `None` if the result
+/// is not a valid POSIX name.
+pub fn env_var_name(key: &str) -> Option<String> {
+    let name = key.to_ascii_uppercase().replace(['.', '-', '/'], "_");
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(name)
+}
+
+/// Quote a value for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    let bare = |c: char| c.is_ascii_alphanumeric() || "-_./:@+,=".contains(c);
+    if !value.is_empty() && value.chars().all(bare) {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+impl ConfigManager {
+    /// Decrypt a value and render it as variable text: strings as-is, other values as JSON
+    fn env_text(&self, value: ConfigValue) -> Result<String> {
+        Ok(match self.reveal(value)? {
+            ConfigValue::String(s) => s,
+            other => other.typed_text().unwrap_or_else(|| render_value(&other).to_string()),
+        })
+    }
+}
+
+/// Parse dotenv text as the type of the current value, falling back to a string
+fn typed_value(current: &ConfigValue, raw: &str) -> ConfigValue {
+    if current.as_str().is_none() {
+        if let Ok(value) = serde_json::from_str::<ConfigValue>(raw) {
+            if !value.is_secret() && value.same_type_as(current) {
+                return value;
+            }
+        }
+    }
+    ConfigValue::String(raw.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_secret_key() {
+        assert!(is_secret_key("OPENAI_API_KEY"));
+        assert!(is_secret_key("db_password"));
+        assert!(is_secret_key("GITHUB_TOKEN"));
+        assert!(is_secret_key("app.secret.key"));
+        assert!(!is_secret_key("DB_HOST"));
+        assert!(!is_secret_key("MAX_TOKENS"));
+    }
+
+    #[test]
+    fn test_dotenv_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key);
+        let env = Environment::Production;
+        let options = SetOptions::default();
+
+        let mut labels = HashMap::new();
+        labels.insert("team".to_string(), ConfigValue::String("ml".into()));
+        manager
+            .set("app", "MAX_TOKENS", ConfigValue::Integer(4096), env, "alice")
+            .unwrap();
+        manager
+            .set("app", "LABELS", ConfigValue::Object(labels), env, "alice")
+            .unwrap();
+
+        let content = "MODEL=gpt-4\nMAX_TOKENS=8192\nOPENAI_API_KEY='sk-123 456'\n";
+        let import = manager.import_dotenv("app", content, env, "alice", &options).unwrap();
+        assert_eq!(import.created, vec!["MODEL", "OPENAI_API_KEY"]);
+        assert_eq!(import.updated, vec!["MAX_TOKENS"]);
+        assert_eq!(import.secrets, vec!["OPENAI_API_KEY"]);
+
+        // Existing values keep their type; credentials are encrypted
+        let max_tokens = manager.get("app", "MAX_TOKENS", env).unwrap().unwrap();
+        assert_eq!(max_tokens.value.as_i64(), Some(8192));
+        assert!(manager.is_secret("app", "OPENAI_API_KEY", env).unwrap());
+
+        let exported = manager.export_dotenv("app", env).unwrap();
+        assert!(exported.contains("OPENAI_API_KEY='sk-123 456'\n"));
+        assert!(exported.contains("LABELS='{\"team\":\"ml\"}'\n"));
+
+        // Importing the export back changes nothing
+        let import = manager.import_dotenv("app", &exported, env, "alice", &options).unwrap();
+        assert!(import.created.is_empty());
+        assert!(import.updated.is_empty());
+        assert_eq!(import.unchanged.len(), 4);
+        assert_eq!(manager.get_history("app", "MAX_TOKENS", env).unwrap().len(), 2);
+
+        assert!(manager
+            .import_dotenv("app", "not a pair", env, "alice", &options)
+            .is_err());
+    }
+
+    #[test]
+    fn test_env_vars() {
+        assert_eq!(env_var_name("openai.api-key").as_deref(), Some("OPENAI_API_KEY"));
+        assert_eq!(env_var_name("model").as_deref(), Some("MODEL"));
+        assert_eq!(env_var_name("1st"), None);
+        assert_eq!(env_var_name("rate limit"), None);
+
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key);
+        let env = Environment::Production;
+        manager
+            .set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice")
+            .unwrap();
+        manager
+            .set("app", "max-tokens", ConfigValue::Integer(4096), env, "alice")
+            .unwrap();
+        manager
+            .set_secret("app", "api.key", b"sk-it's", env, "alice")
+            .unwrap();
+
+        let vars = manager.env_vars("app", env).unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("API_KEY".to_string(), "sk-it's".to_string()),
+                ("MAX_TOKENS".to_string(), "4096".to_string()),
+                ("MODEL".to_string(), "gpt-4".to_string()),
+            ]
+        );
+        assert_eq!(
+            manager.export_shell("app", env).unwrap(),
+            "export API_KEY='sk-it'\\''s'\nexport MAX_TOKENS=4096\nexport MODEL=gpt-4\n"
+        );
+
+        manager
+            .set("app", "MODEL", ConfigValue::String("gpt-4o".into()), env, "alice")
+            .unwrap();
+        assert!(matches!(
+            manager.env_vars("app", env),
+            Err(ConfigError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,246 @@
+//! Drift detection against a desired-state document
+//!
+//! A [`DesiredState`] declares the exact contents of some namespaces, e.g.
+//! kept in Git:
+//!
+//! ```yaml
+//! namespaces:
+//!   app/llm:
+//!     values:
+//!       model: gpt-4
+//!       temperature: 0.7
+//!     secrets:
+//!       # hex SHA-256 of the plaintext (`printf %s "$KEY" | sha256sum`)
+//!       api_key: 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+//! ```
+//!
+//! [`ConfigManager::check_drift`] compares the stored values of the listed
+//! namespaces with it and returns the changes that would reconcile the store
+//! as a [`Plan`]: creates for missing keys, deletes for keys that are not
+//! declared, and updates for values that differ. Secrets are compared by hash
+//! and masked in the plan, so desired-state files never hold plaintext
+//! secrets.
+
+use crate::plan::{render_value, MASKED_SECRET};
+use crate::{
+    ConfigError, ConfigManager, ConfigValue, Environment, Plan, PlanAction, PlannedChange, Result,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Operation name of drift plans
+pub const DRIFT_OPERATION: &str = "drift";
+
+/// Desired contents of a set of namespaces
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DesiredState {
+    pub namespaces: BTreeMap<String, DesiredNamespace>,
+}
+
+/// Desired contents of one namespace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DesiredNamespace {
+    /// Plain values by key
+    #[serde(default)]
+    pub values: BTreeMap<String, serde_json::Value>,
+    /// Hex SHA-256 hashes of secret plaintexts by key (see [`secret_hash`])
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+}
+
+impl DesiredState {
+    /// Parse a desired-state document (YAML, or JSON)
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let state: DesiredState = serde_yaml::from_str(yaml)
+            .map_err(|e| ConfigError::ValidationError(format!("Invalid desired state: {}", e)))?;
+        state.validate()?;
+        Ok(state)
+    }
+
+    /// Check that no key is declared as both a value and a secret
+    pub fn validate(&self) -> Result<()> {
+        for (namespace, desired) in &self.namespaces {
+            if let Some(key) = desired.values.keys().find(|key| desired.secrets.contains_key(*key)) {
+                return Err(ConfigError::ValidationError(format!(
+                    "{}/{} is declared as both a value and a secret",
+                    namespace, key
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hash identifying a secret in a desired-state document: hex SHA-256 of the plaintext
+pub fn secret_hash(plaintext: &[u8]) -> String {
+    hex::encode(Sha256::digest(plaintext))
+}
+
+/// A value in the desired state
+enum Desired<'a> {
+    Value(&'a serde_json::Value),
+    Secret(&'a str),
+}
+
+impl Desired<'_> {
+    fn render(&self) -> serde_json::Value {
+        match self {
+            Desired::Value(value) => (*value).clone(),
+            Desired::Secret(_) => serde_json::Value::String(MASKED_SECRET.to_string()),
+        }
+    }
+}
+
+impl ConfigManager {
+    /// Compare the namespaces listed in `desired` with what is stored in `env`
+    ///
+    /// Only values stored in `env` itself are compared; values inherited from
+    /// other environments do not count. Comparing secrets requires the
+    /// encryption key.
+    pub fn check_drift(&self, desired: &DesiredState, env: Environment) -> Result<Plan> {
+        let mut plan = Plan::new(DRIFT_OPERATION);
+
+        for (namespace, declared) in &desired.namespaces {
+            let mut expected: BTreeMap<&str, Desired> = declared
+                .values
+                .iter()
+                .map(|(key, value)| (key.as_str(), Desired::Value(value)))
+                .collect();
+            expected.extend(
+                declared
+                    .secrets
+                    .iter()
+                    .map(|(key, hash)| (key.as_str(), Desired::Secret(hash))),
+            );
+
+            for entry in self.list(namespace, env)? {
+                let change = |action, new_value: Option<&Desired>| PlannedChange {
+                    namespace: namespace.clone(),
+                    key: entry.key.clone(),
+                    environment: env,
+                    action,
+                    old_value: Some(render_value(&entry.value)),
+                    new_value: new_value.map(Desired::render),
+                };
+
+                match expected.remove(entry.key.as_str()) {
+                    None => plan.push(change(PlanAction::Delete, None)),
+                    Some(desired) => {
+                        if !self.matches(&entry.value, &desired)? {
+                            plan.push(change(PlanAction::Update, Some(&desired)));
+                        }
+                    }
+                }
+            }
+
+            for (key, desired) in expected {
+                plan.push(PlannedChange {
+                    namespace: namespace.clone(),
+                    key: key.to_string(),
+                    environment: env,
+                    action: PlanAction::Create,
+                    old_value: None,
+                    new_value: Some(desired.render()),
+                });
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Whether a stored value is the desired one
+    fn matches(&self, stored: &ConfigValue, desired: &Desired) -> Result<bool> {
+        Ok(match (stored, desired) {
+            (ConfigValue::Secret(_), Desired::Secret(hash)) => {
+                let ConfigValue::String(plaintext) = self.reveal(stored.clone())? else {
+                    return Ok(false);
+                };
+                secret_hash(plaintext.as_bytes()).eq_ignore_ascii_case(hash)
+            }
+            (ConfigValue::Secret(_), Desired::Value(_)) | (_, Desired::Secret(_)) => false,
+            (value, Desired::Value(desired)) => render_value(value) == **desired,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_drift() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap().with_encryption_key(key);
+        let env = Environment::Production;
+
+        manager
+            .set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice")
+            .unwrap();
+        manager
+            .set("app", "temperature", ConfigValue::Float(0.9), env, "alice")
+            .unwrap();
+        manager.set("app", "legacy", ConfigValue::Boolean(true), env, "alice").unwrap();
+        manager.set_secret("app", "api_key", b"sk-1", env, "alice").unwrap();
+        manager.set_secret("app", "db_password", b"hunter2", env, "alice").unwrap();
+        // Namespaces that are not listed are ignored
+        manager.set("other", "model", ConfigValue::Integer(1), env, "alice").unwrap();
+
+        let desired = DesiredState::from_yaml(&format!(
+            r#"
+namespaces:
+  app:
+    values:
+      model: gpt-4
+      temperature: 0.7
+      max_tokens: 1024
+    secrets:
+      api_key: {}
+      db_password: {}
+"#,
+            secret_hash(b"sk-1").to_uppercase(),
+            secret_hash(b"new"),
+        ))
+        .unwrap();
+
+        let plan = manager.check_drift(&desired, env).unwrap();
+        let changes: Vec<_> = plan
+            .changes
+            .iter()
+            .map(|c| (c.key.as_str(), c.action))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("db_password", PlanAction::Update),
+                ("legacy", PlanAction::Delete),
+                ("max_tokens", PlanAction::Create),
+                ("temperature", PlanAction::Update),
+            ]
+        );
+        assert_eq!(plan.changes[0].new_value, Some(serde_json::json!(MASKED_SECRET)));
+        assert!(!plan.to_json().contains("hunter2"));
+
+        // In sync once the drift is reconciled
+        assert_eq!(
+            manager.check_drift(&desired, Environment::Staging).unwrap().summary.create,
+            5
+        );
+        let mut in_sync = desired.clone();
+        let app = in_sync.namespaces.get_mut("app").unwrap();
+        app.values.insert("temperature".into(), serde_json::json!(0.9));
+        app.values.insert("legacy".into(), serde_json::json!(true));
+        app.values.remove("max_tokens");
+        app.secrets.insert("db_password".into(), secret_hash(b"hunter2"));
+        assert!(manager.check_drift(&in_sync, env).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_value_and_secret_conflict() {
+        let yaml = "namespaces:\n  app:\n    values: {api_key: x}\n    secrets: {api_key: y}\n";
+        assert!(DesiredState::from_yaml(yaml).is_err());
+    }
+}
@@ -343,6 +343,7 @@ impl<E: std::error::Error + 'static> std::error::Error for CircuitBreakerError<E
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
     #[test]
     fn test_retry_policy_backoff() {
@@ -356,15 +357,17 @@ mod tests {
 
     #[tokio::test]
     async fn test_retry_success_after_failures() {
-        let mut attempts = 0;
+        let attempts = AtomicU32::new(0);
 
         let result = retry_with_backoff(
-            || async {
-                attempts += 1;
-                if attempts < 3 {
-                    Err("temporary error")
-                } else {
-                    Ok("success")
+            || {
+                let attempts = &attempts;
+                async move {
+                    if attempts.fetch_add(1, Ordering::SeqCst) + 1 < 3 {
+                        Err("temporary error")
+                    } else {
+                        Ok("success")
+                    }
                 }
             },
             RetryPolicy {
@@ -378,17 +381,20 @@ mod tests {
         .await;
 
         assert_eq!(result, Ok("success"));
-        assert_eq!(attempts, 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
 
     #[tokio::test]
     async fn test_retry_exhausted() {
-        let mut attempts = 0;
+        let attempts = AtomicU32::new(0);
 
         let result = retry_with_backoff(
-            || async {
-                attempts += 1;
-                Err::<(), _>("persistent error")
+            || {
+                let attempts = &attempts;
+                async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err::<(), _>("persistent error")
+                }
             },
             RetryPolicy {
                 max_attempts: 3,
@@ -401,7 +407,7 @@ mod tests {
         .await;
 
         assert!(result.is_err());
-        assert_eq!(attempts, 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
 
     #[tokio::test]
@@ -0,0 +1,149 @@
+//! Config change events
+//!
+//! [`ConfigManager`](crate::ConfigManager) publishes a [`ChangeEvent`] on its
+//! [`ChangeBus`] after every successful write. Managers that share a bus
+//! (see [`ConfigManager::with_change_bus`](crate::ConfigManager::with_change_bus))
+//! publish to the same subscribers, which lets caches stay consistent with
+//! writes made through any of them.
+
+use crate::{ConfigEntry, Environment, OperationContext, TenantId};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Default number of events buffered per subscriber
+pub const DEFAULT_BUS_CAPACITY: usize = 1024;
+
+/// Kind of write that produced a change event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Set,
+    Delete,
+    Rollback,
+    Metadata,
+}
+
+/// A change to a single configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub namespace: String,
+    pub key: String,
+    pub environment: Environment,
+    /// Version after the change (the deleted version for deletes)
+    pub version: u64,
+    pub kind: ChangeKind,
+    /// The stored entry after the change (`None` for deletes); secrets stay encrypted
+    pub entry: Option<ConfigEntry>,
+    /// User who made the change
+    #[serde(default)]
+    pub user: String,
+    /// Request context of the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<OperationContext>,
+    /// Tenant whose store changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<TenantId>,
+}
+
+impl ChangeEvent {
+    /// Event for a write that stored `entry`
+    ///
+    /// The user and context are taken from the entry's metadata.
+    pub fn stored(kind: ChangeKind, entry: &ConfigEntry) -> Self {
+        Self {
+            namespace: entry.namespace.clone(),
+            key: entry.key.clone(),
+            environment: entry.environment,
+            version: entry.version,
+            kind,
+            entry: Some(entry.clone()),
+            user: entry.metadata.updated_by.clone(),
+            context: entry.metadata.context.clone(),
+            tenant_id: None,
+        }
+    }
+
+    /// Event for the deletion of `entry`
+    ///
+    /// Set the deleting user and context with [`with_user`](Self::with_user)
+    /// and [`with_context`](Self::with_context); they default to the last writer's.
+    pub fn deleted(entry: &ConfigEntry) -> Self {
+        Self {
+            entry: None,
+            ..Self::stored(ChangeKind::Delete, entry)
+        }
+    }
+
+    /// Attribute the change to `user`
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = user.into();
+        self
+    }
+
+    /// Set the request context of the change
+    pub fn with_context(mut self, context: Option<OperationContext>) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+/// Broadcast channel for change events
+///
+/// Cloning a bus yields a handle to the same channel.
+#[derive(Debug, Clone)]
+pub struct ChangeBus {
+    sender: broadcast::Sender<ChangeEvent>,
+}
+
+impl ChangeBus {
+    /// Create a bus buffering up to `capacity` events per subscriber
+    ///
+    /// Subscribers that fall further behind miss events and see
+    /// [`broadcast::error::RecvError::Lagged`].
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish an event to all current subscribers
+    pub fn publish(&self, event: ChangeEvent) {
+        // Having no subscribers is not an error
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Default for ChangeBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigValue;
+
+    #[test]
+    fn test_publish_and_subscribe() {
+        let bus = ChangeBus::new(2);
+        bus.publish(ChangeEvent::stored(
+            ChangeKind::Set,
+            &ConfigEntry::new("ns", "dropped", ConfigValue::Integer(1), Environment::Development),
+        ));
+
+        let mut receiver = bus.clone().subscribe();
+        let entry = ConfigEntry::new("ns", "key", ConfigValue::Integer(1), Environment::Development);
+        bus.publish(ChangeEvent::deleted(&entry));
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.key, "key");
+        assert_eq!(event.kind, ChangeKind::Delete);
+        assert!(event.entry.is_none());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! Explaining where a resolved value comes from
+//!
+//! [`ConfigManager::explain`] resolves a key the same way as
+//! [`get_with_overrides`](ConfigManager::get_with_overrides) and records
+//! every step: each environment of each namespace that was consulted, whether
+//! it defined the key, and how its value was combined with the value resolved
+//! before it.
+
+use crate::{ConfigEntry, ConfigManager, ConfigValue, Environment, MergeStrategy, Result, ResolvedValue, ValueSource};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How a consulted entry was combined with the value resolved before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeDecision {
+    /// First value found in the namespace
+    Set,
+    /// Replaced the value of a more general environment
+    Replace,
+    /// Deep-merged into the value of a more general environment
+    DeepMerge,
+}
+
+impl MergeDecision {
+    /// Decision for applying `entry` on top of `resolved`, with `default` as
+    /// the namespace's merge strategy
+    pub(crate) fn of(resolved: Option<&ResolvedValue>, entry: &ConfigEntry, default: MergeStrategy) -> Self {
+        match (resolved, entry.metadata.merge.unwrap_or(default)) {
+            (None, _) => MergeDecision::Set,
+            (Some(_), MergeStrategy::Replace) => MergeDecision::Replace,
+            (Some(_), MergeStrategy::DeepMerge) => MergeDecision::DeepMerge,
+        }
+    }
+}
+
+impl fmt::Display for MergeDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MergeDecision::Set => "set",
+            MergeDecision::Replace => "replace",
+            MergeDecision::DeepMerge => "deep-merge",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One environment of one namespace consulted while resolving a key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub namespace: String,
+    pub environment: Environment,
+    /// Version of the entry found (`None` if the environment does not define the key)
+    pub version: Option<u64>,
+    /// How the entry was applied (`None` if nothing was found)
+    pub merge: Option<MergeDecision>,
+}
+
+/// Every step of resolving a key, and its result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolutionTrace {
+    pub namespace: String,
+    pub key: String,
+    pub environment: Environment,
+    /// Namespaces the key may be resolved in, from the requested one to its
+    /// most distant parent
+    pub namespaces: Vec<String>,
+    /// Environments consulted in each namespace, from most general to `environment`
+    pub environments: Vec<Environment>,
+    /// Consulted environments in order; namespaces after the one that
+    /// supplied the value are not consulted
+    pub steps: Vec<TraceStep>,
+    /// Entries the value was built from (see [`ResolvedValue::sources`])
+    pub sources: Vec<ValueSource>,
+    /// Most specific entry the value came from
+    pub source: Option<ValueSource>,
+    /// Resolved value (`None` if no namespace defines the key)
+    pub value: Option<ConfigValue>,
+    /// Whether the resolved value is a secret
+    pub secret: bool,
+    /// Whether the secret was decrypted into [`value`](Self::value)
+    pub decrypted: bool,
+}
+
+impl ConfigManager {
+    /// Resolve a key and record how its value was found
+    ///
+    /// Secrets stay encrypted unless `reveal` is set, which needs the
+    /// encryption key.
+    pub fn explain(&self, namespace: &str, key: &str, env: Environment, reveal: bool) -> Result<ResolutionTrace> {
+        let mut steps = Vec::new();
+        let resolved = self.resolve_key_traced(namespace, key, env, &mut steps)?;
+        let (value, sources) = match resolved {
+            Some(resolved) => (Some(resolved.value), resolved.sources),
+            None => (None, Vec::new()),
+        };
+
+        let secret = value.as_ref().is_some_and(ConfigValue::is_secret);
+        let decrypted = secret && reveal;
+        let value = match value {
+            Some(value) if decrypted => Some(self.reveal(value)?),
+            value => value,
+        };
+
+        Ok(ResolutionTrace {
+            namespace: namespace.to_string(),
+            key: key.to_string(),
+            environment: env,
+            namespaces: self.namespace_settings().lineage(namespace),
+            environments: self.environment_graph().chain(env),
+            steps,
+            source: sources.last().cloned(),
+            sources,
+            value,
+            secret,
+            decrypted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MergeSettings, SetOptions};
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_explain_inherited_value() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        manager
+            .set_merge_settings(MergeSettings::new().with_namespace("org/app", MergeStrategy::DeepMerge))
+            .unwrap();
+        let object = |key: &str| ConfigValue::Object(HashMap::from([(key.to_string(), ConfigValue::Integer(1))]));
+        manager.set("org/app", "llm", object("retries"), Environment::Base, "alice").unwrap();
+        manager.set("org/app", "llm", object("timeout"), Environment::Production, "alice").unwrap();
+        manager.set_namespace_parent("org/app/svc", Some("org/app")).unwrap();
+
+        let trace = manager.explain("org/app/svc", "llm", Environment::Production, false).unwrap();
+        assert_eq!(trace.namespaces, vec!["org/app/svc", "org/app"]);
+        assert_eq!(trace.environments, vec![Environment::Base, Environment::Production]);
+        let decisions: Vec<_> = trace
+            .steps
+            .iter()
+            .map(|step| (step.namespace.as_str(), step.environment, step.merge))
+            .collect();
+        assert_eq!(
+            decisions,
+            vec![
+                ("org/app/svc", Environment::Base, None),
+                ("org/app/svc", Environment::Production, None),
+                ("org/app", Environment::Base, Some(MergeDecision::Set)),
+                ("org/app", Environment::Production, Some(MergeDecision::DeepMerge)),
+            ]
+        );
+        assert_eq!(trace.sources.len(), 2);
+        assert_eq!(trace.source.unwrap().environment, Environment::Production);
+        assert!(!trace.secret);
+
+        // A replace-strategy override drops the inherited value
+        manager
+            .set_with_options(
+                "org/app",
+                "llm",
+                object("timeout"),
+                Environment::Production,
+                "alice",
+                &SetOptions::default().with_merge(MergeStrategy::Replace),
+            )
+            .unwrap();
+        let trace = manager.explain("org/app", "llm", Environment::Production, false).unwrap();
+        assert_eq!(trace.steps[1].merge, Some(MergeDecision::Replace));
+        assert_eq!(trace.sources.len(), 1);
+
+        let missing = manager.explain("org/app", "missing", Environment::Production, false).unwrap();
+        assert!(missing.value.is_none() && missing.source.is_none());
+        assert!(missing.steps.iter().all(|step| step.version.is_none()));
+    }
+
+    #[test]
+    fn test_explain_secret() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap().with_encryption_key(key);
+        let env = Environment::Staging;
+        manager.set_secret("app", "api_key", b"sk-test", env, "alice").unwrap();
+
+        let trace = manager.explain("app", "api_key", env, false).unwrap();
+        assert!(trace.secret && !trace.decrypted);
+        assert!(trace.value.unwrap().is_secret());
+
+        let trace = manager.explain("app", "api_key", env, true).unwrap();
+        assert!(trace.decrypted);
+        assert_eq!(trace.value.unwrap().as_str(), Some("sk-test"));
+    }
+}
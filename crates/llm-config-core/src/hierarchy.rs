@@ -0,0 +1,405 @@
+//! Namespace inheritance
+//!
+//! A namespace can declare a parent namespace it inherits defaults from, so
+//! `org/app/service` can fall back to `org/app` for keys it does not define.
+//! Parents are independent of the path structure of names: any namespace can
+//! be the parent of any other, as long as no namespace ends up inheriting
+//! from itself.
+//!
+//! A key is resolved in the namespace itself first, along the full
+//! [environment chain](crate::EnvironmentGraph::chain), and only if no
+//! environment of the namespace defines it in its parent, then the parent's
+//! parent, and so on. The first namespace that defines the key supplies the
+//! whole value: a child value replaces the parent's even when it is
+//! [deep-merged](crate::merge) across environments. [`ResolvedValue::sources`]
+//! records which namespace, environments and versions the value came from.
+
+use crate::explain::{MergeDecision, TraceStep};
+use crate::merge::apply_override;
+use crate::{ConfigEntry, ConfigError, ConfigManager, ConfigValue, Environment, MergeStrategy, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Storage record kind and id the namespace settings are persisted under
+pub(crate) const NAMESPACE_SETTINGS_RECORD: (&str, &str) = ("settings", "namespaces");
+
+/// Settings of a single namespace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceMetadata {
+    /// Namespace that keys missing from this one are resolved in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// Metadata of every namespace that has any
+///
+/// Namespaces without metadata have no parent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceSettings {
+    #[serde(default)]
+    pub namespaces: BTreeMap<String, NamespaceMetadata>,
+}
+
+impl NamespaceSettings {
+    /// Create settings where no namespace has a parent
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the parent of a namespace
+    pub fn with_parent(mut self, namespace: impl Into<String>, parent: impl Into<String>) -> Self {
+        self.set_parent(namespace, Some(parent.into()));
+        self
+    }
+
+    /// Set or clear the parent of a namespace
+    pub fn set_parent(&mut self, namespace: impl Into<String>, parent: Option<String>) {
+        let namespace = namespace.into();
+        match parent {
+            Some(parent) => {
+                self.namespaces.entry(namespace).or_default().parent = Some(parent);
+            }
+            None => {
+                if let Some(metadata) = self.namespaces.get_mut(&namespace) {
+                    metadata.parent = None;
+                    if *metadata == NamespaceMetadata::default() {
+                        self.namespaces.remove(&namespace);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parent of a namespace
+    pub fn parent(&self, namespace: &str) -> Option<&str> {
+        self.namespaces.get(namespace)?.parent.as_deref()
+    }
+
+    /// Namespaces a key of `namespace` is resolved in, from `namespace` itself
+    /// to its most distant ancestor
+    pub fn lineage(&self, namespace: &str) -> Vec<String> {
+        let mut lineage = vec![namespace.to_string()];
+        while let Some(parent) = self.parent(lineage.last().unwrap()) {
+            // Cycles are rejected by validate(); stop rather than loop forever
+            if lineage.iter().any(|ancestor| ancestor == parent) {
+                break;
+            }
+            lineage.push(parent.to_string());
+        }
+        lineage
+    }
+
+    /// Check that parents are named and no namespace inherits from itself
+    pub fn validate(&self) -> Result<()> {
+        for namespace in self.namespaces.keys() {
+            let mut seen = vec![namespace.as_str()];
+            let mut current = namespace.as_str();
+            while let Some(parent) = self.parent(current) {
+                if parent.is_empty() {
+                    return Err(ConfigError::ValidationError(format!(
+                        "Parent of namespace {} cannot be empty",
+                        current
+                    )));
+                }
+                if seen.contains(&parent) {
+                    return Err(ConfigError::ValidationError(format!(
+                        "Namespace {} is part of an inheritance cycle",
+                        namespace
+                    )));
+                }
+                seen.push(parent);
+                current = parent;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Environment version of a namespace a resolved value came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueSource {
+    pub namespace: String,
+    pub environment: Environment,
+    pub version: u64,
+}
+
+/// A value resolved through environment and namespace inheritance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedValue {
+    pub value: ConfigValue,
+    /// Entries the value was built from, in the order they were applied
+    ///
+    /// A single entry unless overrides were deep-merged; the last one is the
+    /// most specific.
+    pub sources: Vec<ValueSource>,
+}
+
+impl ConfigManager {
+    /// Current namespace metadata
+    pub fn namespace_settings(&self) -> NamespaceSettings {
+        self.namespaces.read().unwrap().clone()
+    }
+
+    /// Validate, replace and persist the namespace metadata
+    pub fn set_namespace_settings(&self, settings: NamespaceSettings) -> Result<()> {
+        settings.validate()?;
+        self.check_writable()?;
+        let (kind, id) = NAMESPACE_SETTINGS_RECORD;
+        self.storage().put_record(kind, id, &settings)?;
+        *self.namespaces.write().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Set or clear the parent namespace of `namespace`
+    ///
+    /// Fails if the namespace would end up inheriting from itself.
+    pub fn set_namespace_parent(&self, namespace: &str, parent: Option<&str>) -> Result<()> {
+        let mut settings = self.namespace_settings();
+        settings.set_parent(namespace, parent.map(str::to_string));
+        self.set_namespace_settings(settings)
+    }
+
+    /// Resolve a key through environment and namespace inheritance
+    ///
+    /// See the [module documentation](self) for the resolution order, and
+    /// [`explain`](Self::explain) for every step taken. Secrets stay encrypted.
+    pub fn resolve_key(&self, namespace: &str, key: &str, env: Environment) -> Result<Option<ResolvedValue>> {
+        self.resolve_key_traced(namespace, key, env, &mut Vec::new())
+    }
+
+    /// Resolve a key, recording each environment of each namespace consulted
+    pub(crate) fn resolve_key_traced(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        trace: &mut Vec<TraceStep>,
+    ) -> Result<Option<ResolvedValue>> {
+        let chain = self.environment_graph().chain(env);
+        for namespace in self.namespace_settings().lineage(namespace) {
+            let strategy = self.merge_settings().strategy(&namespace);
+            let mut resolved: Option<ResolvedValue> = None;
+            for override_env in &chain {
+                let mut step = TraceStep {
+                    namespace: namespace.clone(),
+                    environment: *override_env,
+                    version: None,
+                    merge: None,
+                };
+                if let Some(entry) = self.storage().get(&namespace, key, *override_env)? {
+                    self.check_signature(&entry)?;
+                    self.check_binding(&entry)?;
+                    self.record_access(&entry);
+                    step.version = Some(entry.version);
+                    step.merge = Some(MergeDecision::of(resolved.as_ref(), &entry, strategy));
+                    resolved = Some(apply_resolved(resolved, entry, strategy));
+                }
+                trace.push(step);
+            }
+            if resolved.is_some() {
+                return Ok(resolved);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolve every key of a namespace and its ancestors through environment
+    /// and namespace inheritance
+    ///
+    /// Keys defined closer to `namespace` win. Secrets stay encrypted.
+    pub fn resolve_namespace_with_sources(
+        &self,
+        namespace: &str,
+        env: Environment,
+    ) -> Result<BTreeMap<String, ResolvedValue>> {
+        let chain = self.environment_graph().chain(env);
+        let mut resolved = BTreeMap::new();
+        for namespace in self.namespace_settings().lineage(namespace) {
+            let strategy = self.merge_settings().strategy(&namespace);
+            let mut level: BTreeMap<String, ResolvedValue> = BTreeMap::new();
+            for override_env in &chain {
+                for entry in self.storage().list(&namespace, *override_env)? {
+                    self.check_signature(&entry)?;
+                    self.check_binding(&entry)?;
+                    self.record_access(&entry);
+                    let key = entry.key.clone();
+                    let value = apply_resolved(level.remove(&key), entry, strategy);
+                    level.insert(key, value);
+                }
+            }
+            for (key, value) in level {
+                resolved.entry(key).or_insert(value);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Give `dst` the parent of `src`; when moving, children of `src` follow
+    /// it to `dst`
+    pub(crate) fn copy_namespace_parent(&self, src: &str, dst: &str, moved: bool) -> Result<()> {
+        let mut settings = self.namespace_settings();
+        let parent = settings.parent(src).map(str::to_string);
+        let children: Vec<String> = settings
+            .namespaces
+            .iter()
+            .filter(|(_, metadata)| metadata.parent.as_deref() == Some(src))
+            .map(|(namespace, _)| namespace.clone())
+            .collect();
+        if parent.is_none() && (!moved || children.is_empty()) {
+            return Ok(());
+        }
+
+        settings.set_parent(dst, parent);
+        if moved {
+            settings.set_parent(src, None);
+            for child in children {
+                settings.set_parent(child, Some(dst.to_string()));
+            }
+        }
+        self.set_namespace_settings(settings)
+    }
+}
+
+/// Apply an override entry on top of the value resolved so far, tracking
+/// where it came from
+fn apply_resolved(
+    resolved: Option<ResolvedValue>,
+    entry: ConfigEntry,
+    default: MergeStrategy,
+) -> ResolvedValue {
+    let source = ValueSource {
+        namespace: entry.namespace.clone(),
+        environment: entry.environment,
+        version: entry.version,
+    };
+    let merge = MergeDecision::of(resolved.as_ref(), &entry, default);
+    let (inherited, mut sources) = match resolved {
+        Some(resolved) => (Some(resolved.value), resolved.sources),
+        None => (None, Vec::new()),
+    };
+    if merge != MergeDecision::DeepMerge {
+        sources.clear();
+    }
+    sources.push(source);
+    ResolvedValue {
+        value: apply_override(inherited, entry, default),
+        sources,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MergeSettings, SetOptions};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lineage_and_cycles() {
+        let settings = NamespaceSettings::new()
+            .with_parent("org/app/service", "org/app")
+            .with_parent("org/app", "org");
+        assert_eq!(settings.lineage("org/app/service"), vec!["org/app/service", "org/app", "org"]);
+        assert_eq!(settings.lineage("other"), vec!["other"]);
+        assert!(settings.validate().is_ok());
+
+        let cycle = settings.clone().with_parent("org", "org/app/service");
+        assert!(cycle.validate().is_err());
+        assert_eq!(cycle.lineage("org"), vec!["org", "org/app/service", "org/app"]);
+        assert!(NamespaceSettings::new().with_parent("a", "a").validate().is_err());
+
+        let mut cleared = settings;
+        cleared.set_parent("org/app", None);
+        assert_eq!(cleared.lineage("org/app/service"), vec!["org/app/service", "org/app"]);
+        assert!(!cleared.namespaces.contains_key("org/app"));
+    }
+
+    #[test]
+    fn test_parent_fallback() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let (base, prod) = (Environment::Base, Environment::Production);
+        manager.set("org/app", "model", ConfigValue::String("gpt-4".into()), base, "alice").unwrap();
+        manager.set("org/app", "timeout", ConfigValue::Integer(30), prod, "alice").unwrap();
+        manager.set("org/app/svc", "timeout", ConfigValue::Integer(5), base, "alice").unwrap();
+
+        assert!(manager.get_with_overrides("org/app/svc", "model", prod).unwrap().is_none());
+        manager.set_namespace_parent("org/app/svc", Some("org/app")).unwrap();
+        assert!(manager.set_namespace_parent("org/app", Some("org/app/svc")).is_err());
+
+        let model = manager.resolve_key("org/app/svc", "model", prod).unwrap().unwrap();
+        assert_eq!(model.value.as_str(), Some("gpt-4"));
+        assert_eq!(
+            model.sources,
+            vec![ValueSource { namespace: "org/app".into(), environment: base, version: 1 }]
+        );
+
+        // The child's base value wins over the parent's production value
+        let timeout = manager.get_with_overrides("org/app/svc", "timeout", prod).unwrap().unwrap();
+        assert_eq!(timeout.as_i64(), Some(5));
+
+        let resolved = manager.resolve_namespace("org/app/svc", prod).unwrap();
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(resolved["timeout"].as_i64(), Some(5));
+
+        // Parents are persisted
+        let reopened = ConfigManager::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.namespace_settings().parent("org/app/svc"), Some("org/app"));
+    }
+
+    #[test]
+    fn test_deep_merged_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        manager
+            .set_merge_settings(MergeSettings::new().with_namespace("app", MergeStrategy::DeepMerge))
+            .unwrap();
+        let object = |key: &str, value: i64| {
+            ConfigValue::Object(HashMap::from([(key.to_string(), ConfigValue::Integer(value))]))
+        };
+        manager.set("app", "llm", object("retries", 3), Environment::Base, "alice").unwrap();
+        manager.set("app", "llm", object("timeout", 10), Environment::Staging, "alice").unwrap();
+
+        let resolved = manager.resolve_key("app", "llm", Environment::Staging).unwrap().unwrap();
+        let environments: Vec<_> = resolved.sources.iter().map(|s| s.environment).collect();
+        assert_eq!(environments, vec![Environment::Base, Environment::Staging]);
+
+        manager
+            .set_with_options(
+                "app",
+                "llm",
+                object("timeout", 20),
+                Environment::Staging,
+                "alice",
+                &SetOptions::default().with_merge(MergeStrategy::Replace),
+            )
+            .unwrap();
+        let resolved = manager.resolve_key("app", "llm", Environment::Staging).unwrap().unwrap();
+        assert_eq!(resolved.sources.len(), 1);
+        let ConfigValue::Object(fields) = resolved.value else { panic!("expected an object") };
+        assert_eq!(fields.len(), 1);
+    }
+
+    #[test]
+    fn test_parents_follow_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let env = Environment::Development;
+        manager.set("team-a", "x", ConfigValue::Integer(1), env, "alice").unwrap();
+        manager
+            .set_namespace_settings(
+                NamespaceSettings::new()
+                    .with_parent("team-a", "org")
+                    .with_parent("team-a/svc", "team-a"),
+            )
+            .unwrap();
+
+        manager.clone_namespace("team-a", "team-b", &[]).unwrap();
+        manager.rename_namespace("team-a", "team-c").unwrap();
+        let settings = manager.namespace_settings();
+        assert_eq!(settings.parent("team-b"), Some("org"));
+        assert_eq!(settings.parent("team-c"), Some("org"));
+        assert_eq!(settings.parent("team-a"), None);
+        assert_eq!(settings.parent("team-a/svc"), Some("team-c"));
+    }
+}
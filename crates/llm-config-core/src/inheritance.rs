@@ -0,0 +1,155 @@
+//! Environment inheritance
+//!
+//! Every environment except `base` has a parent it inherits values from.
+//! Resolving a key in an environment walks from `base` down the chain of
+//! parents, with more specific environments overriding more general ones.
+//! By default every environment inherits directly from `base`, so resolving
+//! `production` never picks up `development` or `staging` values.
+//!
+//! Stores created before the graph existed resolved through the fixed chain
+//! `base -> development -> staging -> production`; [`EnvironmentGraph::legacy`]
+//! reproduces it.
+
+use crate::{ConfigError, Environment, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Storage record kind and id the environment graph is persisted under
+pub(crate) const ENVIRONMENT_GRAPH_RECORD: (&str, &str) = ("settings", "environments");
+
+/// All environments, from most general to most specific
+pub const ENVIRONMENTS: [Environment; 5] = [
+    Environment::Base,
+    Environment::Development,
+    Environment::Staging,
+    Environment::Production,
+    Environment::Edge,
+];
+
+/// Parent of each environment
+///
+/// Environments without an explicit parent inherit from `base`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentGraph {
+    #[serde(default)]
+    pub parents: BTreeMap<Environment, Environment>,
+}
+
+impl EnvironmentGraph {
+    /// Create a graph where every environment inherits from `base`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The fixed override chain used before environments had parents
+    pub fn legacy() -> Self {
+        Self::new()
+            .with_parent(Environment::Development, Environment::Base)
+            .with_parent(Environment::Staging, Environment::Development)
+            .with_parent(Environment::Production, Environment::Staging)
+            .with_parent(Environment::Edge, Environment::Base)
+    }
+
+    /// Set the parent of an environment
+    pub fn with_parent(mut self, env: Environment, parent: Environment) -> Self {
+        self.parents.insert(env, parent);
+        self
+    }
+
+    /// Parent of an environment (`None` for `base`)
+    pub fn parent(&self, env: Environment) -> Option<Environment> {
+        match env {
+            Environment::Base => None,
+            _ => Some(self.parents.get(&env).copied().unwrap_or(Environment::Base)),
+        }
+    }
+
+    /// Environments whose values apply to `env`, from `base` to `env`
+    pub fn chain(&self, env: Environment) -> Vec<Environment> {
+        let mut chain = vec![env];
+        let mut current = env;
+        while let Some(parent) = self.parent(current) {
+            // Cycles are rejected by validate(); stop rather than loop forever
+            if chain.contains(&parent) {
+                break;
+            }
+            chain.push(parent);
+            current = parent;
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Check that `base` has no parent and every chain ends at `base`
+    pub fn validate(&self) -> Result<()> {
+        if let Some(parent) = self.parents.get(&Environment::Base) {
+            return Err(ConfigError::ValidationError(format!(
+                "base cannot inherit from {}",
+                parent
+            )));
+        }
+
+        for env in ENVIRONMENTS {
+            if self.chain(env).first() != Some(&Environment::Base) {
+                return Err(ConfigError::ValidationError(format!(
+                    "{} is part of an inheritance cycle",
+                    env
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_chain() {
+        let graph = EnvironmentGraph::new();
+        assert_eq!(
+            graph.chain(Environment::Production),
+            vec![Environment::Base, Environment::Production]
+        );
+        assert_eq!(graph.chain(Environment::Base), vec![Environment::Base]);
+        assert!(graph.validate().is_ok());
+    }
+
+    #[test]
+    fn test_legacy_chain() {
+        let graph = EnvironmentGraph::legacy();
+        assert_eq!(
+            graph.chain(Environment::Production),
+            vec![
+                Environment::Base,
+                Environment::Development,
+                Environment::Staging,
+                Environment::Production
+            ]
+        );
+        assert_eq!(graph.chain(Environment::Edge), vec![Environment::Base, Environment::Edge]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let graph = EnvironmentGraph::new()
+            .with_parent(Environment::Staging, Environment::Production)
+            .with_parent(Environment::Production, Environment::Staging);
+        assert!(graph.validate().is_err());
+
+        let graph = EnvironmentGraph::new().with_parent(Environment::Base, Environment::Edge);
+        assert!(graph.validate().is_err());
+
+        let graph = EnvironmentGraph::new().with_parent(Environment::Edge, Environment::Production);
+        assert!(graph.validate().is_ok());
+        assert_eq!(
+            graph.chain(Environment::Edge),
+            vec![Environment::Base, Environment::Production, Environment::Edge]
+        );
+
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(json, r#"{"parents":{"edge":"production"}}"#);
+    }
+}
@@ -0,0 +1,277 @@
+//! Kubernetes manifest export
+//!
+//! Renders a namespace, with environment overrides applied, into a
+//! `ConfigMap` for plain values and a `Secret` for secrets. Every manifest is
+//! labelled with its source environment and annotated with the namespace and
+//! the environment and version each key was resolved from (prefixed with the
+//! namespace for keys inherited from a [parent](crate::hierarchy)), so a running
+//! workload can be traced back to the exact config versions it was built from.
+//! Sealing the `Secret` (e.g. with `kubeseal`) is left to the caller.
+
+use crate::plan::render_value;
+use crate::{ConfigError, ConfigManager, ConfigValue, Environment, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Label and annotation prefix
+pub const K8S_PREFIX: &str = "llm-config.io";
+
+/// Value of the `app.kubernetes.io/managed-by` label
+pub const K8S_MANAGER: &str = "llm-config-manager";
+
+/// Options for [`ConfigManager::k8s_manifests`]
+#[derive(Debug, Clone, Default)]
+pub struct K8sExportOptions {
+    /// Manifest name (derived from the namespace if unset)
+    pub name: Option<String>,
+    /// Kubernetes namespace the manifests are created in
+    pub k8s_namespace: Option<String>,
+}
+
+impl K8sExportOptions {
+    /// Set the manifest name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the Kubernetes namespace
+    pub fn with_k8s_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.k8s_namespace = Some(namespace.into());
+        self
+    }
+}
+
+/// Object metadata of a manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct K8sMetadata {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// A `ConfigMap` or `Secret` manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct K8sManifest {
+    pub api_version: String,
+    pub kind: String,
+    pub metadata: K8sMetadata,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub secret_type: Option<String>,
+    /// Values, base64-encoded for secrets
+    #[serde(default)]
+    pub data: BTreeMap<String, String>,
+}
+
+/// Manifests exported for a namespace and environment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct K8sManifests {
+    pub config_map: K8sManifest,
+    /// `None` when the namespace has no secrets
+    pub secret: Option<K8sManifest>,
+}
+
+impl K8sManifests {
+    /// All manifests as a multi-document YAML stream
+    pub fn to_yaml(&self) -> String {
+        let mut documents = vec![self.config_map.to_yaml()];
+        if let Some(secret) = &self.secret {
+            documents.push(secret.to_yaml());
+        }
+        documents.join("---\n")
+    }
+}
+
+impl K8sManifest {
+    /// The manifest as a YAML document
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(self).expect("manifests are always serializable")
+    }
+}
+
+/// Derive a valid manifest name (RFC 1123 subdomain) from a config namespace
+pub fn manifest_name(namespace: &str) -> String {
+    let name: String = namespace
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' })
+        .collect();
+    let name = name.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+    name.chars().take(253).collect()
+}
+
+/// Whether `key` may be used as a `ConfigMap`/`Secret` data key
+fn is_valid_data_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 253
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+impl ConfigManager {
+    /// Render a namespace into Kubernetes manifests, with overrides applied
+    ///
+    /// Plain values go into a `ConfigMap` (strings as-is, other values as
+    /// JSON) and secrets, decrypted, into an `Opaque` `Secret`. Requires the
+    /// encryption key when the namespace has secrets.
+    pub fn k8s_manifests(
+        &self,
+        namespace: &str,
+        env: Environment,
+        options: &K8sExportOptions,
+    ) -> Result<K8sManifests> {
+        let resolved = self.resolve_namespace_with_sources(namespace, env)?;
+
+        let mut config_data = BTreeMap::new();
+        let mut secret_data = BTreeMap::new();
+        let mut sources = serde_json::Map::new();
+        for (key, resolved) in resolved {
+            if !is_valid_data_key(&key) {
+                return Err(ConfigError::ValidationError(format!(
+                    "Key {} is not a valid Kubernetes data key",
+                    key
+                )));
+            }
+
+            // Most specific entry; values from parent namespaces name their namespace
+            if let Some(source) = resolved.sources.last() {
+                let source = if source.namespace == namespace {
+                    format!("{}@v{}", source.environment, source.version)
+                } else {
+                    format!("{}:{}@v{}", source.namespace, source.environment, source.version)
+                };
+                sources.insert(key.clone(), source.into());
+            }
+            if resolved.value.is_secret() {
+                let ConfigValue::String(plaintext) = self.reveal(resolved.value)? else {
+                    unreachable!("revealed secrets are strings");
+                };
+                secret_data.insert(key, base64::engine::general_purpose::STANDARD.encode(plaintext));
+            } else {
+                let value = match resolved.value {
+                    ConfigValue::String(s) => s,
+                    other => other.typed_text().unwrap_or_else(|| render_value(&other).to_string()),
+                };
+                config_data.insert(key, value);
+            }
+        }
+
+        let name = options
+            .name
+            .clone()
+            .unwrap_or_else(|| manifest_name(namespace));
+        if name.is_empty() {
+            return Err(ConfigError::ValidationError(format!(
+                "Cannot derive a manifest name from {}",
+                namespace
+            )));
+        }
+
+        let labels = BTreeMap::from([
+            ("app.kubernetes.io/managed-by".to_string(), K8S_MANAGER.to_string()),
+            (format!("{}/environment", K8S_PREFIX), env.to_string()),
+        ]);
+        let annotations = BTreeMap::from([
+            (format!("{}/namespace", K8S_PREFIX), namespace.to_string()),
+            (format!("{}/environment", K8S_PREFIX), env.to_string()),
+            (
+                format!("{}/source-versions", K8S_PREFIX),
+                serde_json::Value::Object(sources).to_string(),
+            ),
+        ]);
+        let metadata = K8sMetadata {
+            name,
+            namespace: options.k8s_namespace.clone(),
+            labels,
+            annotations,
+        };
+
+        let secret = (!secret_data.is_empty()).then(|| K8sManifest {
+            api_version: "v1".to_string(),
+            kind: "Secret".to_string(),
+            metadata: metadata.clone(),
+            secret_type: Some("Opaque".to_string()),
+            data: secret_data,
+        });
+
+        Ok(K8sManifests {
+            config_map: K8sManifest {
+                api_version: "v1".to_string(),
+                kind: "ConfigMap".to_string(),
+                metadata,
+                secret_type: None,
+                data: config_data,
+            },
+            secret,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_name() {
+        assert_eq!(manifest_name("org/App/llm_service"), "org-app-llm-service");
+        assert_eq!(manifest_name("/team/"), "team");
+    }
+
+    #[test]
+    fn test_k8s_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key);
+
+        manager
+            .set("org/llm", "model", ConfigValue::String("gpt-4".into()), Environment::Base, "alice")
+            .unwrap();
+        manager
+            .set("org/llm", "max_tokens", ConfigValue::Integer(1024), Environment::Base, "alice")
+            .unwrap();
+        manager
+            .set("org/llm", "max_tokens", ConfigValue::Integer(4096), Environment::Production, "alice")
+            .unwrap();
+        manager
+            .set_secret("org/llm", "api_key", b"sk-123", Environment::Base, "alice")
+            .unwrap();
+
+        let options = K8sExportOptions::default().with_k8s_namespace("llm");
+        let manifests = manager
+            .k8s_manifests("org/llm", Environment::Production, &options)
+            .unwrap();
+
+        let config_map = &manifests.config_map;
+        assert_eq!(config_map.metadata.name, "org-llm");
+        assert_eq!(config_map.data["model"], "gpt-4");
+        assert_eq!(config_map.data["max_tokens"], "4096");
+        assert!(!config_map.data.contains_key("api_key"));
+
+        let sources: serde_json::Value = serde_json::from_str(
+            &config_map.metadata.annotations["llm-config.io/source-versions"],
+        )
+        .unwrap();
+        assert_eq!(sources["max_tokens"], "production@v1");
+        assert_eq!(sources["api_key"], "base@v1");
+
+        let secret = manifests.secret.as_ref().unwrap();
+        assert_eq!(secret.data["api_key"], "c2stMTIz");
+
+        let yaml = manifests.to_yaml();
+        assert!(yaml.contains("kind: ConfigMap"));
+        assert!(yaml.contains("---\n"));
+        assert!(yaml.contains("type: Opaque"));
+        assert!(yaml.contains("namespace: llm"));
+    }
+}
@@ -1,13 +1,72 @@
 //! Core logic for LLM Config Manager
 
+pub mod async_manager;
+pub mod audit;
+pub mod binding;
+pub mod blueprints;
+pub mod changelog;
 pub mod config;
+pub mod document;
+pub mod dotenv;
+pub mod drift;
+pub mod events;
+pub mod explain;
+pub mod hierarchy;
+pub mod inheritance;
+pub mod k8s;
+pub mod listing;
+pub mod maintenance;
 pub mod manager;
+pub mod merge;
+pub mod migrate;
+pub mod namespaces;
+pub mod plan;
+pub mod promote;
+pub mod prompts;
+pub mod protection;
+pub mod restore;
+pub mod signing;
+pub mod tenant;
+pub mod typecheck;
+pub mod usage;
 pub mod version;
+pub mod watch;
 pub mod error_utils;
 
+pub use async_manager::AsyncConfigManager;
+pub use audit::{AuditRecorder, AuditedOperation};
+pub use binding::{secret_aad, BIND_SECRETS_DESCRIPTION};
+pub use blueprints::{
+    AppliedBlueprint, Blueprint, BlueprintEntry, BlueprintRegistry, StoredBlueprint, BLUEPRINT_NAMESPACE,
+};
+pub use changelog::{parse_since, Changelog, ChangelogChange, ChangelogGroup};
 pub use config::*;
+pub use document::{DocumentFormat, DocumentImport};
+pub use dotenv::{env_var_name, is_secret_key, DotenvImport};
+pub use drift::{secret_hash, DesiredNamespace, DesiredState};
+pub use events::{ChangeBus, ChangeEvent, ChangeKind};
+pub use explain::{MergeDecision, ResolutionTrace, TraceStep};
+pub use hierarchy::{NamespaceMetadata, NamespaceSettings, ResolvedValue, ValueSource};
+pub use inheritance::{EnvironmentGraph, ENVIRONMENTS};
+pub use k8s::{K8sExportOptions, K8sManifest, K8sManifests};
+pub use listing::{ConfigPage, ListOptions, ListSort};
+pub use maintenance::{ReadOnlyMode, READ_ONLY_MODE};
 pub use manager::*;
+pub use merge::{deep_merge, MergeSettings};
+pub use migrate::{MigrateOptions, MigrationReport, MigrationTarget, MIGRATED_DESCRIPTION};
+pub use plan::{Plan, PlanAction, PlanSummary, PlannedChange};
+pub use promote::{PromoteKeys, PROMOTE_OPERATION};
+pub use prompts::{RenderedTemplate, StoredTemplate, TemplateRegistry, TEMPLATE_NAMESPACE};
+pub use protection::{EnvironmentProtection, ProtectionLevel};
+pub use restore::RESTORE_OPERATION;
+pub use signing::{
+    canonical_entry_json, EntrySigner, NamespaceVerification, SignatureCheck, SignaturePolicy, SignatureStatus,
+};
+pub use tenant::{TenantId, TenantKeyStore, TenantRegistry};
+pub use typecheck::{CheckedEntry, ExpectedTypeSource, TypeSchema, TypeWarning};
+pub use usage::{AccessStats, UnusedConfig, DEFAULT_ACCESS_FLUSH_INTERVAL};
 pub use version::*;
+pub use watch::REPLICATED_DESCRIPTION;
 pub use error_utils::*;
 
 use thiserror::Error;
@@ -20,6 +79,9 @@ pub enum ConfigError {
     #[error("Crypto error: {0}")]
     CryptoError(#[from] llm_config_crypto::CryptoError),
 
+    #[error("Template error: {0}")]
+    TemplateError(#[from] llm_config_templates::TemplateError),
+
     #[error("Validation error: {0}")]
     ValidationError(String),
 
@@ -31,6 +93,37 @@ pub enum ConfigError {
 
     #[error("Invalid operation: {0}")]
     InvalidOperation(String),
+
+    #[error("Type change not allowed: {0}")]
+    TypeChange(String),
+
+    #[error("Environment protected: {0}")]
+    Protected(String),
+
+    #[error("Version conflict: {0}")]
+    Conflict(String),
+
+    #[error("Signature check failed: {0}")]
+    Signature(String),
+}
+
+impl ConfigError {
+    /// Short snake_case name of the error variant, used as a metrics label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::StorageError(_) => "storage",
+            Self::CryptoError(_) => "crypto",
+            Self::TemplateError(_) => "template",
+            Self::ValidationError(_) => "validation",
+            Self::NotFound(_) => "not_found",
+            Self::AlreadyExists(_) => "already_exists",
+            Self::InvalidOperation(_) => "invalid_operation",
+            Self::TypeChange(_) => "type_change",
+            Self::Protected(_) => "protected",
+            Self::Conflict(_) => "conflict",
+            Self::Signature(_) => "signature",
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ConfigError>;
@@ -0,0 +1,282 @@
+//! Paged, sorted and filtered namespace listings
+//!
+//! [`ConfigManager::list_with_options`] filters the entries of a namespace by
+//! key prefix and tag, orders them by [`ListSort`] (ties broken by key, so the
+//! order is stable) and returns one [`ConfigPage`] with the total number of
+//! matching entries.
+//!
+//! Pages are addressed by `offset`, or by the `next_page_token` of the
+//! previous page. Tokens point just past the last entry of a page, so unlike
+//! offsets they neither skip nor repeat entries when others are written
+//! between requests.
+
+use crate::{ConfigEntry, ConfigError, ConfigManager, Environment, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Order of listed entries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSort {
+    #[default]
+    Key,
+    UpdatedAt,
+    Version,
+}
+
+impl ListSort {
+    /// Position of an entry in this order, before the tie-breaking key
+    fn rank(self, entry: &ConfigEntry) -> i128 {
+        match self {
+            ListSort::Key => 0,
+            ListSort::UpdatedAt => i128::from(entry.metadata.updated_at.timestamp_micros()),
+            ListSort::Version => i128::from(entry.version),
+        }
+    }
+}
+
+impl FromStr for ListSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "key" => Ok(ListSort::Key),
+            "updated_at" | "updated" => Ok(ListSort::UpdatedAt),
+            "version" => Ok(ListSort::Version),
+            _ => Err(format!("Unknown sort order: {} (expected key, updated_at or version)", s)),
+        }
+    }
+}
+
+/// Page of a namespace listing to read
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListOptions {
+    /// Return at most this many entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Skip this many entries (after `page_token`)
+    #[serde(default)]
+    pub offset: usize,
+    /// Continue after the page this token was returned with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+    #[serde(default)]
+    pub sort_by: ListSort,
+    /// Reverse the order
+    #[serde(default)]
+    pub descending: bool,
+    /// Only list keys starting with this prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_prefix: Option<String>,
+    /// Only list entries with this tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Leave out secrets
+    #[serde(default)]
+    pub exclude_secrets: bool,
+}
+
+impl ListOptions {
+    /// Return at most `limit` entries
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` entries
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Continue after the page `token` was returned with
+    pub fn with_page_token(mut self, token: impl Into<String>) -> Self {
+        self.page_token = Some(token.into());
+        self
+    }
+
+    /// Order entries by `sort_by`, descending if `descending` is set
+    pub fn with_sort(mut self, sort_by: ListSort, descending: bool) -> Self {
+        self.sort_by = sort_by;
+        self.descending = descending;
+        self
+    }
+
+    /// Only list keys starting with `prefix`
+    pub fn with_key_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.key_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Only list entries tagged `tag`
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Leave out secrets
+    pub fn with_exclude_secrets(mut self, exclude: bool) -> Self {
+        self.exclude_secrets = exclude;
+        self
+    }
+
+    fn matches(&self, entry: &ConfigEntry) -> bool {
+        self.key_prefix
+            .as_deref()
+            .map_or(true, |prefix| entry.key.starts_with(prefix))
+            && self
+                .tag
+                .as_ref()
+                .map_or(true, |tag| entry.metadata.tags.contains(tag))
+            && !(self.exclude_secrets && entry.value.is_secret())
+    }
+
+    /// Order of two positions (rank and key) in the listing
+    fn compare(&self, a: (i128, &str), b: (i128, &str)) -> Ordering {
+        let ordering = a.cmp(&b);
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    /// Filter, sort and page entries
+    fn page(&self, mut entries: Vec<ConfigEntry>) -> Result<ConfigPage> {
+        entries.retain(|entry| self.matches(entry));
+        let sort = self.sort_by;
+        entries.sort_by(|a, b| self.compare((sort.rank(a), &a.key), (sort.rank(b), &b.key)));
+        let total = entries.len();
+
+        let start = match &self.page_token {
+            Some(token) => {
+                let (rank, key) = parse_page_token(token)?;
+                entries.partition_point(|entry| {
+                    self.compare((sort.rank(entry), &entry.key), (rank, key)) != Ordering::Greater
+                })
+            }
+            None => 0,
+        };
+        let start = start.saturating_add(self.offset).min(total);
+        let end = match self.limit {
+            Some(limit) => start.saturating_add(limit).min(total),
+            None => total,
+        };
+
+        let next_page_token = (end < total && end > start)
+            .then(|| page_token(sort.rank(&entries[end - 1]), &entries[end - 1].key));
+        entries.truncate(end);
+        entries.drain(..start);
+
+        Ok(ConfigPage {
+            entries,
+            total,
+            next_page_token,
+        })
+    }
+}
+
+/// One page of a namespace listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigPage {
+    pub entries: Vec<ConfigEntry>,
+    /// Number of entries matching the filters, across all pages
+    pub total: usize,
+    /// Token for the next page, if there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+}
+
+fn page_token(rank: i128, key: &str) -> String {
+    format!("{}:{}", rank, key)
+}
+
+fn parse_page_token(token: &str) -> Result<(i128, &str)> {
+    token
+        .split_once(':')
+        .and_then(|(rank, key)| Some((rank.parse().ok()?, key)))
+        .ok_or_else(|| ConfigError::ValidationError(format!("Invalid page token: {}", token)))
+}
+
+impl ConfigManager {
+    /// List a page of the configurations in a namespace (secrets stay encrypted)
+    pub fn list_with_options(
+        &self,
+        namespace: &str,
+        env: Environment,
+        options: &ListOptions,
+    ) -> Result<ConfigPage> {
+        let entries = self.storage().list(namespace, env)?;
+        for entry in &entries {
+            self.check_signature(entry)?;
+            self.check_binding(entry)?;
+        }
+        options.page(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigValue;
+    use tempfile::TempDir;
+
+    fn keys(page: &ConfigPage) -> Vec<&str> {
+        page.entries.iter().map(|entry| entry.key.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let env = Environment::Development;
+        for key in ["d", "b", "e", "a", "c"] {
+            manager.set("app", key, ConfigValue::Integer(1), env, "alice").unwrap();
+        }
+        manager.set("app", "c", ConfigValue::Integer(2), env, "alice").unwrap();
+        manager.set_metadata("app", "b", env, vec!["llm".into()], None).unwrap();
+
+        let list = |options: ListOptions| manager.list_with_options("app", env, &options).unwrap();
+
+        let page = list(ListOptions::default());
+        assert_eq!(keys(&page), vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(page.total, 5);
+        assert!(page.next_page_token.is_none());
+
+        // Offsets and tokens walk the same pages
+        let first = list(ListOptions::default().with_limit(2));
+        assert_eq!(keys(&first), vec!["a", "b"]);
+        let token = first.next_page_token.clone().unwrap();
+        let second = list(ListOptions::default().with_limit(2).with_page_token(&token));
+        assert_eq!(keys(&second), vec!["c", "d"]);
+        assert_eq!(keys(&list(ListOptions::default().with_limit(2).with_offset(2))), vec!["c", "d"]);
+        let last = list(ListOptions::default().with_limit(2).with_page_token(second.next_page_token.unwrap()));
+        assert_eq!(keys(&last), vec!["e"]);
+        assert!(last.next_page_token.is_none());
+
+        // Tokens don't skip entries when earlier ones are deleted
+        manager.delete("app", "a", env).unwrap();
+        let second = list(ListOptions::default().with_limit(2).with_page_token(&token));
+        assert_eq!(keys(&second), vec!["c", "d"]);
+        assert_eq!(second.total, 4);
+
+        // Sorting, ties broken by key
+        let page = list(ListOptions::default().with_sort(ListSort::Version, true));
+        assert_eq!(keys(&page), vec!["c", "e", "d", "b"]);
+        let page = list(ListOptions::default().with_sort(ListSort::UpdatedAt, true).with_limit(1));
+        assert_eq!(keys(&page), vec!["b"]);
+
+        // Filters apply before paging
+        let page = list(ListOptions::default().with_tag("llm"));
+        assert_eq!((keys(&page), page.total), (vec!["b"], 1));
+        manager.set("app", "db_url", ConfigValue::Integer(1), env, "alice").unwrap();
+        let page = list(ListOptions::default().with_key_prefix("d").with_limit(1));
+        assert_eq!((keys(&page), page.total), (vec!["d"], 2));
+
+        assert!(matches!(
+            manager.list_with_options("app", env, &ListOptions::default().with_page_token("x")),
+            Err(ConfigError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+//! Read-only mode for migrations and backups
+//!
+//! While a store is read-only, every write through [`ConfigManager`] (values,
+//! metadata, rollbacks, promotions, namespace moves and settings) fails with
+//! `ConfigError::InvalidOperation("read-only mode")`. The mode is persisted
+//! with the store and checked on every write, so switching it from the CLI
+//! also stops a running server from writing. Maintenance (`fsck`, `compact`)
+//! and reads are not affected.
+
+use crate::{ConfigError, ConfigManager, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::MutexGuard;
+
+/// Storage record kind and id the read-only mode is persisted under
+pub(crate) const READ_ONLY_RECORD: (&str, &str) = ("settings", "read_only");
+
+/// Message of the [`ConfigError::InvalidOperation`] returned for writes in read-only mode
+pub const READ_ONLY_MODE: &str = "read-only mode";
+
+/// Who made the store read-only, when and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadOnlyMode {
+    pub enabled_by: String,
+    pub enabled_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ConfigManager {
+    /// Current read-only mode (`None` while the store is writable)
+    pub fn read_only_mode(&self) -> Result<Option<ReadOnlyMode>> {
+        let (kind, id) = READ_ONLY_RECORD;
+        Ok(self.storage().get_record(kind, id)?)
+    }
+
+    /// Reject all writes until [`disable_read_only`](Self::disable_read_only)
+    ///
+    /// Returns once writes of this manager that are in progress have finished.
+    pub fn enable_read_only(&self, user: &str, reason: Option<String>) -> Result<ReadOnlyMode> {
+        let _writes = self.writes.lock().unwrap();
+        let mode = ReadOnlyMode {
+            enabled_by: user.to_string(),
+            enabled_at: Utc::now(),
+            reason: reason.filter(|r| !r.trim().is_empty()),
+        };
+        let (kind, id) = READ_ONLY_RECORD;
+        self.storage().put_record(kind, id, &mode)?;
+        Ok(mode)
+    }
+
+    /// Accept writes again; returns whether the store was read-only
+    pub fn disable_read_only(&self) -> Result<bool> {
+        let _writes = self.writes.lock().unwrap();
+        let (kind, id) = READ_ONLY_RECORD;
+        Ok(self.storage().delete_record(kind, id)?)
+    }
+
+    /// Fail if the store is read-only
+    pub(crate) fn check_writable(&self) -> Result<()> {
+        match self.read_only_mode()? {
+            Some(_) => Err(ConfigError::InvalidOperation(READ_ONLY_MODE.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the write lock of a read-modify-write cycle, failing if the store is read-only
+    pub(crate) fn lock_writes(&self) -> Result<MutexGuard<'_, ()>> {
+        let writes = self.writes.lock().unwrap();
+        self.check_writable()?;
+        Ok(writes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigValue, Environment, MergeSettings, PromoteKeys};
+    use tempfile::TempDir;
+
+    fn is_read_only<T>(result: Result<T>) -> bool {
+        matches!(result, Err(ConfigError::InvalidOperation(msg)) if msg == READ_ONLY_MODE)
+    }
+
+    #[test]
+    fn test_read_only_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let env = Environment::Staging;
+        manager.set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        assert!(manager.read_only_mode().unwrap().is_none());
+
+        let mode = manager.enable_read_only("ops", Some("backup".to_string())).unwrap();
+        assert_eq!(mode.reason.as_deref(), Some("backup"));
+        assert!(is_read_only(manager.set("app", "model", ConfigValue::Integer(1), env, "alice")));
+        assert!(is_read_only(manager.delete("app", "model", env)));
+        assert!(is_read_only(manager.rollback("app", "model", env, 1)));
+        assert!(is_read_only(manager.promote("app", &PromoteKeys::All, env, Environment::Production, "alice")));
+        assert!(is_read_only(manager.rename_namespace("app", "other")));
+        assert!(is_read_only(manager.set_merge_settings(MergeSettings::new())));
+        assert_eq!(manager.get("app", "model", env).unwrap().unwrap().version, 1);
+        manager.compact().unwrap();
+
+        // Other processes see the mode
+        let other = ConfigManager::new(temp_dir.path()).unwrap();
+        assert_eq!(other.read_only_mode().unwrap(), Some(mode));
+        assert!(other.disable_read_only().unwrap());
+        assert!(!other.disable_read_only().unwrap());
+        manager
+            .set("app", "temperature", ConfigValue::Float(0.2), env, "alice")
+            .unwrap();
+    }
+}
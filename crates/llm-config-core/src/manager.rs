@@ -1,16 +1,125 @@
 //! Configuration manager - core business logic
 
-use crate::{ConfigEntry, ConfigValue, Environment, Result, VersionControl};
-use llm_config_crypto::{decrypt, encrypt, SecretKey};
+use crate::hierarchy::{NamespaceSettings, NAMESPACE_SETTINGS_RECORD};
+use crate::inheritance::ENVIRONMENT_GRAPH_RECORD;
+use crate::merge::{MergeSettings, MERGE_SETTINGS_RECORD};
+use crate::protection::PROTECTION_RECORD;
+use crate::signing::{EntrySigner, SignaturePolicy};
+use crate::typecheck::TypeChecks;
+use crate::usage::AccessTracker;
+use crate::version::rollback_description;
+use crate::watch::KvReplica;
+use crate::{
+    AuditRecorder, AuditedOperation, BlobStats, ChangeBus, ChangeEvent, ChangeKind, Changelog, CompactionReport, ConfigEntry, ConfigValue, Environment, EnvironmentGraph,
+    EnvironmentProtection, FsckReport, HistoryOptions, ListOptions, MergeStrategy, OperationContext, Result, RetentionConfig, RetentionPolicy, RetentionReport, TenantId,
+    ValueDiff, VersionControl,
+};
+use llm_config_crypto::{decrypt, encrypt, Algorithm, CryptoError, SecretKey};
+use llm_config_sink::MetricsSink;
 use llm_config_storage::file::FileStorage;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+/// Options for writes ([`ConfigManager::set_with_options`] and friends)
+#[derive(Debug, Clone, Default)]
+pub struct SetOptions {
+    /// Allow replacing a value with one of a different type
+    pub allow_type_change: bool,
+    /// Reviewer of the change, for review-required environments
+    pub reviewed_by: Option<String>,
+    /// Description recorded in the version history
+    pub change_description: Option<String>,
+    /// Version the stored entry must be at (`0` if it must not exist yet)
+    pub expected_version: Option<u64>,
+    /// Request context recorded in the entry's metadata and change event
+    pub context: Option<OperationContext>,
+    /// Merge strategy recorded for the entry (kept unchanged when unset)
+    pub merge: Option<MergeStrategy>,
+    /// Run every check and return the result, but write nothing (set, delete and rollback)
+    pub dry_run: bool,
+}
+
+impl SetOptions {
+    /// Allow (or forbid) changing the type of an existing value
+    pub fn with_allow_type_change(mut self, allow: bool) -> Self {
+        self.allow_type_change = allow;
+        self
+    }
+
+    /// Record who reviewed the change
+    pub fn with_reviewed_by(mut self, reviewer: impl Into<String>) -> Self {
+        self.reviewed_by = Some(reviewer.into());
+        self
+    }
+
+    /// Describe the change in the version history
+    pub fn with_change_description(mut self, description: impl Into<String>) -> Self {
+        self.change_description = Some(description.into());
+        self
+    }
+
+    /// Fail with [`ConfigError::Conflict`](crate::ConfigError::Conflict) unless
+    /// the stored entry is at `version` (`0`: it must not exist yet)
+    pub fn with_expected_version(mut self, version: u64) -> Self {
+        self.expected_version = Some(version);
+        self
+    }
+
+    /// Record where the change came from
+    pub fn with_context(mut self, context: OperationContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Combine the value with inherited values using `strategy`; see
+    /// [`merge`](crate::merge)
+    pub fn with_merge(mut self, strategy: MergeStrategy) -> Self {
+        self.merge = Some(strategy);
+        self
+    }
+
+    /// Validate the write and compute its result without applying it
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
 
 /// Main configuration manager
 pub struct ConfigManager {
     storage: FileStorage,
-    version_control: VersionControl,
-    encryption_key: Option<SecretKey>,
+    pub(crate) version_control: VersionControl,
+    pub(crate) encryption_key: Option<SecretKey>,
+    /// Reject secrets not bound to their entry (see [`binding`](crate::binding))
+    pub(crate) require_bound_secrets: bool,
+    /// Signs written entries (see [`signing`](crate::signing))
+    pub(crate) signer: Option<EntrySigner>,
+    /// Signatures checked on read
+    pub(crate) signatures: SignaturePolicy,
+    protection: RwLock<EnvironmentProtection>,
+    /// Persisted environment graph (`None` until one is saved)
+    environments: RwLock<Option<EnvironmentGraph>>,
+    merge: RwLock<MergeSettings>,
+    /// Namespace parents (see [`hierarchy`](crate::hierarchy))
+    pub(crate) namespaces: RwLock<NamespaceSettings>,
+    legacy_overrides: bool,
+    changes: ChangeBus,
+    audit: Option<Arc<dyn AuditRecorder>>,
+    /// Refuse secret reads that do not give a reason
+    require_access_reason: bool,
+    tenant: Option<TenantId>,
+    pub(crate) access: AccessTracker,
+    pub(crate) type_checks: RwLock<TypeChecks>,
+    /// Sink of type mismatch and crypto metrics (storage has its own reference)
+    metrics: RwLock<Option<Arc<dyn MetricsSink>>>,
+    /// Serializes read-modify-write cycles so version checks and increments are atomic
+    pub(crate) writes: Mutex<()>,
+    /// Key-value store writes are shared through (see [`watch`](crate::watch))
+    pub(crate) kv: Option<KvReplica>,
 }
 
 impl ConfigManager {
@@ -18,83 +127,315 @@ impl ConfigManager {
     pub fn new(storage_path: impl AsRef<Path>) -> Result<Self> {
         let storage = FileStorage::new(storage_path)?;
         let version_control = VersionControl::new(storage.clone());
+        let (kind, id) = PROTECTION_RECORD;
+        let protection = storage.get_record(kind, id)?.unwrap_or_default();
+        let (kind, id) = ENVIRONMENT_GRAPH_RECORD;
+        let environments = storage.get_record(kind, id)?;
+        let (kind, id) = MERGE_SETTINGS_RECORD;
+        let merge = storage.get_record(kind, id)?.unwrap_or_default();
+        let (kind, id) = NAMESPACE_SETTINGS_RECORD;
+        let namespaces = storage.get_record(kind, id)?.unwrap_or_default();
 
         Ok(Self {
+            access: AccessTracker::new(storage.clone()),
             storage,
             version_control,
             encryption_key: None,
+            require_bound_secrets: false,
+            signer: None,
+            signatures: SignaturePolicy::default(),
+            protection: RwLock::new(protection),
+            environments: RwLock::new(environments),
+            merge: RwLock::new(merge),
+            namespaces: RwLock::new(namespaces),
+            legacy_overrides: false,
+            changes: ChangeBus::default(),
+            audit: None,
+            require_access_reason: false,
+            tenant: None,
+            type_checks: RwLock::default(),
+            metrics: RwLock::new(None),
+            writes: Mutex::new(()),
+            kv: None,
         })
     }
 
+    /// Resolve overrides through the legacy chain until an environment graph is saved
+    ///
+    /// Compatibility flag for stores that rely on `production` inheriting
+    /// `staging` and `development` values; see [`EnvironmentGraph::legacy`].
+    pub fn with_legacy_overrides(mut self, legacy: bool) -> Self {
+        self.legacy_overrides = legacy;
+        self
+    }
+
+    /// Publish change events on a shared bus instead of a private one
+    pub fn with_change_bus(mut self, bus: ChangeBus) -> Self {
+        self.changes = bus;
+        self
+    }
+
+    /// Audit every write and secret read with the given recorder
+    ///
+    /// Writes are recorded with the user and [`OperationContext`] they were
+    /// made with; see [`get_secret_as`](Self::get_secret_as) for reads.
+    pub fn with_audit_logger(mut self, logger: Arc<dyn AuditRecorder>) -> Self {
+        self.audit = Some(logger);
+        self
+    }
+
+    /// Refuse to decrypt secrets for reads that do not give a reason
+    ///
+    /// See [`get_secret_with_reason`](Self::get_secret_with_reason); the
+    /// reason is recorded in the audit event of the read.
+    pub fn with_required_access_reason(mut self, required: bool) -> Self {
+        self.require_access_reason = required;
+        self
+    }
+
+    /// Mark the manager as serving `tenant`'s store
+    ///
+    /// Change and audit events are tagged with the tenant. Use
+    /// [`TenantRegistry`](crate::TenantRegistry) to open tenant stores.
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Tenant whose store this manager serves
+    pub fn tenant(&self) -> Option<&TenantId> {
+        self.tenant.as_ref()
+    }
+
+    /// Prune each config's history with `policy` after every write to it
+    ///
+    /// See [`prune_history`](Self::prune_history) to apply a policy to all
+    /// configs at once.
+    pub fn with_history_retention(mut self, policy: RetentionPolicy) -> Self {
+        self.version_control = self.version_control.with_retention(policy);
+        self
+    }
+
+    /// Bus that change events are published on
+    pub fn change_bus(&self) -> &ChangeBus {
+        &self.changes
+    }
+
+    /// Receive an event for every successful write from now on
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.changes.subscribe()
+    }
+
     /// Set the encryption key for secrets
     pub fn with_encryption_key(mut self, key: SecretKey) -> Self {
         self.encryption_key = Some(key);
         self
     }
 
+    /// Get the underlying storage backend
+    pub fn storage(&self) -> &FileStorage {
+        &self.storage
+    }
+
+    /// Current write protection settings
+    pub fn protection(&self) -> EnvironmentProtection {
+        self.protection.read().unwrap().clone()
+    }
+
+    /// Replace and persist the write protection settings
+    pub fn set_protection(&self, protection: EnvironmentProtection) -> Result<()> {
+        self.check_writable()?;
+        let (kind, id) = PROTECTION_RECORD;
+        self.storage.put_record(kind, id, &protection)?;
+        *self.protection.write().unwrap() = protection;
+        Ok(())
+    }
+
+    /// Current environment inheritance graph
+    pub fn environment_graph(&self) -> EnvironmentGraph {
+        match &*self.environments.read().unwrap() {
+            Some(graph) => graph.clone(),
+            None if self.legacy_overrides => EnvironmentGraph::legacy(),
+            None => EnvironmentGraph::default(),
+        }
+    }
+
+    /// Validate, replace and persist the environment inheritance graph
+    pub fn set_environment_graph(&self, graph: EnvironmentGraph) -> Result<()> {
+        graph.validate()?;
+        self.check_writable()?;
+        let (kind, id) = ENVIRONMENT_GRAPH_RECORD;
+        self.storage.put_record(kind, id, &graph)?;
+        *self.environments.write().unwrap() = Some(graph);
+        Ok(())
+    }
+
+    /// Current default merge strategies of namespaces
+    pub fn merge_settings(&self) -> MergeSettings {
+        self.merge.read().unwrap().clone()
+    }
+
+    /// Replace and persist the default merge strategies of namespaces
+    pub fn set_merge_settings(&self, settings: MergeSettings) -> Result<()> {
+        self.check_writable()?;
+        let (kind, id) = MERGE_SETTINGS_RECORD;
+        self.storage.put_record(kind, id, &settings)?;
+        *self.merge.write().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Check the environment's protection level before a write
+    pub(crate) fn check_write(&self, env: Environment, user: &str, options: &SetOptions) -> Result<()> {
+        self.protection
+            .read()
+            .unwrap()
+            .check_write(env, user, options.reviewed_by.as_deref())
+    }
+
+    /// Check the stored version against [`SetOptions::expected_version`]
+    fn check_version(
+        namespace: &str,
+        key: &str,
+        existing: Option<&ConfigEntry>,
+        options: &SetOptions,
+    ) -> Result<()> {
+        let Some(expected) = options.expected_version else {
+            return Ok(());
+        };
+
+        let current = existing.map_or(0, |entry| entry.version);
+        if current != expected {
+            return Err(crate::ConfigError::Conflict(format!(
+                "{}:{} is at version {}, expected {}",
+                namespace, key, current, expected
+            )));
+        }
+        Ok(())
+    }
+
     /// Get a single configuration value
+    ///
+    /// Secrets are returned in their encrypted (masked) form; use
+    /// [`get_revealed`](Self::get_revealed) to decrypt them.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.get", skip(self)))]
     pub fn get(
         &self,
         namespace: &str,
         key: &str,
         env: Environment,
+    ) -> Result<Option<ConfigEntry>> {
+        let entry = self.storage.get(namespace, key, env)?;
+        if let Some(entry) = &entry {
+            self.check_signature(entry)?;
+            self.check_binding(entry)?;
+            self.record_access(entry);
+            self.check_read_type(entry);
+        }
+        Ok(entry)
+    }
+
+    /// Get several configuration values from one namespace
+    ///
+    /// Returns one result per requested key, in request order, with `None`
+    /// for keys that do not exist. Secrets stay encrypted.
+    pub fn get_many<K: AsRef<str>>(
+        &self,
+        namespace: &str,
+        keys: &[K],
+        env: Environment,
+    ) -> Result<Vec<(String, Option<ConfigEntry>)>> {
+        keys.iter()
+            .map(|key| {
+                let key = key.as_ref();
+                Ok((key.to_string(), self.get(namespace, key, env)?))
+            })
+            .collect()
+    }
+
+    /// Get a single configuration value with secrets decrypted
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.get_revealed", skip(self)))]
+    pub fn get_revealed(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
     ) -> Result<Option<ConfigEntry>> {
         let mut entry = self.storage.get(namespace, key, env)?;
 
-        // Decrypt secrets if encryption key is available
         if let Some(ref mut config) = entry {
-            if let Some(ref key) = self.encryption_key {
-                self.decrypt_entry(config, key)?;
-            }
+            self.check_signature(config)?;
+            self.check_binding(config)?;
+            self.record_access(config);
+            self.check_read_type(config);
+            config.value = self.reveal(config.value.clone())?;
         }
 
         Ok(entry)
     }
 
+    /// Decrypt a secret value (other values are returned unchanged)
+    ///
+    /// The value is decrypted with the identity it was encrypted for; reads
+    /// through this manager have already checked that it is its entry's own.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "crypto.decrypt", skip_all))]
+    pub fn reveal(&self, value: ConfigValue) -> Result<ConfigValue> {
+        let ConfigValue::Secret(ref encrypted) = value else {
+            return Ok(value);
+        };
+
+        let key = self.encryption_key.as_ref().ok_or_else(|| {
+            crate::ConfigError::ValidationError("Encryption key not configured".to_string())
+        })?;
+
+        let plaintext = self.metered_crypto("decrypt", encrypted.algorithm, || decrypt(key, encrypted))?;
+        // For now, assume secrets are UTF-8 strings
+        let plaintext_str = String::from_utf8(plaintext)
+            .map_err(|e| crate::ConfigError::ValidationError(e.to_string()))?;
+        Ok(ConfigValue::String(plaintext_str))
+    }
+
     /// Get a configuration with environment overrides applied
+    ///
+    /// The value comes from the most specific environment on the
+    /// [inheritance chain](EnvironmentGraph::chain) of `env` that defines it,
+    /// or is deep-merged from the chain per its [merge strategy](crate::merge).
+    /// Keys the namespace does not define fall back to its
+    /// [parent namespaces](crate::hierarchy); [`resolve_key`](Self::resolve_key)
+    /// also reports where the value came from.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.get_with_overrides", skip(self)))]
     pub fn get_with_overrides(
         &self,
         namespace: &str,
         key: &str,
         env: Environment,
     ) -> Result<Option<ConfigValue>> {
-        // Start with base configuration
-        let mut value = self.storage.get(namespace, key, Environment::Base)?
-            .map(|e| e.value);
-
-        // Apply environment-specific overrides in order
-        let envs = match env {
-            Environment::Base => vec![],
-            Environment::Development => vec![Environment::Development],
-            Environment::Staging => vec![Environment::Development, Environment::Staging],
-            Environment::Production => vec![
-                Environment::Development,
-                Environment::Staging,
-                Environment::Production,
-            ],
-            Environment::Edge => vec![Environment::Edge],
-        };
-
-        for override_env in envs {
-            if let Some(override_entry) = self.storage.get(namespace, key, override_env)? {
-                value = Some(override_entry.value);
-            }
-        }
-
-        // Decrypt if it's a secret
-        if let Some(ConfigValue::Secret(ref encrypted)) = value {
-            if let Some(ref key) = self.encryption_key {
-                let plaintext = decrypt(key, encrypted)?;
-                let plaintext_str = String::from_utf8(plaintext)
-                    .map_err(|e| crate::ConfigError::ValidationError(e.to_string()))?;
-                return Ok(Some(ConfigValue::String(plaintext_str)));
-            }
-        }
+        // Secrets stay encrypted; callers opt in to decryption via reveal()
+        Ok(self.resolve_key(namespace, key, env)?.map(|resolved| resolved.value))
+    }
 
-        Ok(value)
+    /// Resolve every key in a namespace with environment overrides applied
+    ///
+    /// Values are merged along the same chain as
+    /// [`get_with_overrides`](Self::get_with_overrides), so keys only defined
+    /// in a more general environment or a parent namespace are included.
+    /// Secrets stay encrypted.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.resolve", skip(self)))]
+    pub fn resolve_namespace(
+        &self,
+        namespace: &str,
+        env: Environment,
+    ) -> Result<BTreeMap<String, ConfigValue>> {
+        Ok(self
+            .resolve_namespace_with_sources(namespace, env)?
+            .into_iter()
+            .map(|(key, resolved)| (key, resolved.value))
+            .collect())
     }
 
     /// Set a configuration value
+    ///
+    /// Fails with [`ConfigError::TypeChange`](crate::ConfigError::TypeChange)
+    /// if the key already holds a value of a different type.
     pub fn set(
         &self,
         namespace: impl Into<String>,
@@ -102,41 +443,147 @@ impl ConfigManager {
         value: ConfigValue,
         env: Environment,
         user: impl Into<String>,
+    ) -> Result<ConfigEntry> {
+        self.set_with_options(namespace, key, value, env, user, &SetOptions::default())
+    }
+
+    /// Set a configuration value with explicit options
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.set", skip_all, fields(namespace, key, env = %env)))]
+    pub fn set_with_options(
+        &self,
+        namespace: impl Into<String>,
+        key: impl Into<String>,
+        value: ConfigValue,
+        env: Environment,
+        user: impl Into<String>,
+        options: &SetOptions,
     ) -> Result<ConfigEntry> {
         let namespace = namespace.into();
         let key_str = key.into();
         let user = user.into();
+        #[cfg(feature = "otel")]
+        tracing::Span::current()
+            .record("namespace", namespace.as_str())
+            .record("key", key_str.as_str());
+
+        self.check_write(env, &user, options)?;
+        let _writes = self.lock_writes()?;
 
         // Check if config exists to determine if this is an update
         let existing = self.storage.get(&namespace, &key_str, env)?;
+        Self::check_replace(&namespace, &key_str, existing.as_ref(), &value, options)?;
+        if options.dry_run {
+            return Ok(Self::next_entry(namespace, key_str, existing, value, env, user, options));
+        }
+        self.store(namespace, key_str, existing, value, env, user, options)
+    }
 
-        let mut entry = if let Some(mut existing_entry) = existing {
+    /// Check that `value` may replace `existing` (version and type checks)
+    pub(crate) fn check_replace(
+        namespace: &str,
+        key: &str,
+        existing: Option<&ConfigEntry>,
+        value: &ConfigValue,
+        options: &SetOptions,
+    ) -> Result<()> {
+        Self::check_version(namespace, key, existing, options)?;
+
+        if let Some(existing_entry) = existing {
+            if !options.allow_type_change && !existing_entry.value.same_type_as(value) {
+                return Err(crate::ConfigError::TypeChange(format!(
+                    "{}:{} is {}, refusing to store {}",
+                    namespace,
+                    key,
+                    existing_entry.value.type_name(),
+                    value.type_name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a checked value, versioning it and publishing the change
+    ///
+    /// The caller holds the write lock and has checked the write.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn store(
+        &self,
+        namespace: String,
+        key_str: String,
+        existing: Option<ConfigEntry>,
+        value: ConfigValue,
+        env: Environment,
+        user: String,
+        options: &SetOptions,
+    ) -> Result<ConfigEntry> {
+        let mut entry = Self::next_entry(namespace, key_str, existing, value, env, user, options);
+
+        // Encrypt if the value is marked as a secret and we have an encryption key
+        if let Some(ref key) = self.encryption_key {
+            self.encrypt_entry(&mut entry, key)?;
+        }
+        self.sign_entry(&mut entry)?;
+
+        // Save to storage together with a version snapshot
+        let description = options
+            .change_description
+            .clone()
+            .unwrap_or_else(|| "Configuration updated".to_string());
+        self.share(&entry.namespace, &entry.key, env, Some(&entry), Some(&description))?;
+        self.version_control.commit(&entry, Some(description))?;
+
+        self.publish(ChangeEvent::stored(ChangeKind::Set, &entry));
+        Ok(entry)
+    }
+
+    /// The entry storing `value` would produce, at the next version
+    fn next_entry(
+        namespace: String,
+        key_str: String,
+        existing: Option<ConfigEntry>,
+        value: ConfigValue,
+        env: Environment,
+        user: String,
+        options: &SetOptions,
+    ) -> ConfigEntry {
+        if let Some(mut existing_entry) = existing {
             // Update existing
             existing_entry.value = value;
             existing_entry.version += 1;
             existing_entry.metadata.updated_at = Utc::now();
             existing_entry.metadata.updated_by = user;
+            existing_entry.metadata.context = options.context.clone();
+            if options.merge.is_some() {
+                existing_entry.metadata.merge = options.merge;
+            }
             existing_entry
         } else {
             // Create new
             let mut entry = ConfigEntry::new(namespace.clone(), key_str.clone(), value, env);
             entry.metadata.created_by = user.clone();
             entry.metadata.updated_by = user;
+            entry.metadata.context = options.context.clone();
+            entry.metadata.merge = options.merge;
             entry
-        };
-
-        // Encrypt if the value is marked as a secret and we have an encryption key
-        if let Some(ref key) = self.encryption_key {
-            self.encrypt_entry(&mut entry, key)?;
         }
+    }
 
-        // Save to storage
-        self.storage.set(entry.clone())?;
-
-        // Create version snapshot
-        self.version_control.create_snapshot(&entry, Some("Configuration updated".to_string()))?;
-
-        Ok(entry)
+    /// Set a configuration value only if it is still at `expected_version`
+    ///
+    /// Use `0` to create a value that must not exist yet. Concurrent writers
+    /// that read the same version get [`ConfigError::Conflict`](crate::ConfigError::Conflict)
+    /// instead of silently overwriting each other.
+    pub fn set_with_expected_version(
+        &self,
+        namespace: impl Into<String>,
+        key: impl Into<String>,
+        value: ConfigValue,
+        env: Environment,
+        user: impl Into<String>,
+        expected_version: u64,
+    ) -> Result<ConfigEntry> {
+        let options = SetOptions::default().with_expected_version(expected_version);
+        self.set_with_options(namespace, key, value, env, user, &options)
     }
 
     /// Set a secret value (automatically encrypted)
@@ -148,37 +595,180 @@ impl ConfigManager {
         env: Environment,
         user: impl Into<String>,
     ) -> Result<ConfigEntry> {
+        self.set_secret_with_options(namespace, key, plaintext, env, user, &SetOptions::default())
+    }
+
+    /// Set a secret value with explicit options
+    pub fn set_secret_with_options(
+        &self,
+        namespace: impl Into<String>,
+        key: impl Into<String>,
+        plaintext: impl AsRef<[u8]>,
+        env: Environment,
+        user: impl Into<String>,
+        options: &SetOptions,
+    ) -> Result<ConfigEntry> {
+        let (namespace, key) = (namespace.into(), key.into());
+        let value = self.encrypt_secret(&namespace, &key, env, plaintext.as_ref())?;
+        self.set_with_options(namespace, key, value, env, user, options)
+    }
+
+    /// Check that the configured key encrypts and decrypts a probe value
+    pub fn probe_encryption_key(&self) -> Result<()> {
+        const PROBE: &[u8] = b"llm-config-key-probe";
+
+        let key = self.encryption_key.as_ref().ok_or_else(|| {
+            crate::ConfigError::ValidationError("Encryption key not configured".to_string())
+        })?;
+        if decrypt(key, &encrypt(key, PROBE, None)?)? != PROBE {
+            return Err(crate::ConfigError::ValidationError(
+                "Encryption key probe decrypted to a different value".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether an encryption key is configured
+    pub fn has_encryption_key(&self) -> bool {
+        self.encryption_key.is_some()
+    }
+
+    /// Encrypt a secret value with the configured key, bound to its entry
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "crypto.encrypt", skip_all))]
+    pub(crate) fn encrypt_secret(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        plaintext: &[u8],
+    ) -> Result<ConfigValue> {
         let encryption_key = self.encryption_key.as_ref()
             .ok_or_else(|| crate::ConfigError::ValidationError(
                 "Encryption key not configured".to_string()
             ))?;
 
-        let encrypted = encrypt(encryption_key, plaintext.as_ref(), None)?;
-        let value = ConfigValue::Secret(encrypted);
+        let aad = self.secret_aad(namespace, key, env);
+        let encrypted = self.metered_crypto("encrypt", encryption_key.algorithm(), || {
+            encrypt(encryption_key, plaintext, Some(&aad))
+        })?;
+        Ok(ConfigValue::Secret(encrypted))
+    }
+
+    /// Record config reads and writes of storage, secret encryption and
+    /// decryption, and type mismatches found on read in `metrics`
+    pub fn set_metrics(&self, metrics: Arc<dyn MetricsSink>) {
+        self.storage.set_metrics(Arc::clone(&metrics));
+        *self.metrics.write().unwrap() = Some(metrics);
+    }
 
-        self.set(namespace, key, value, env, user)
+    /// The metrics sink, if one is set
+    pub(crate) fn metrics(&self) -> Option<Arc<dyn MetricsSink>> {
+        self.metrics.read().unwrap().clone()
     }
 
-    /// Get and decrypt a secret value
+    /// Run a crypto operation, recording it in the metrics sink if one is set
+    pub(crate) fn metered_crypto<T>(
+        &self,
+        operation: &str,
+        algorithm: Algorithm,
+        f: impl FnOnce() -> std::result::Result<T, CryptoError>,
+    ) -> std::result::Result<T, CryptoError> {
+        let Some(metrics) = self.metrics() else {
+            return f();
+        };
+        let started = Instant::now();
+        let result = f();
+        metrics.record_counter(
+            "crypto_operations_total",
+            &[("operation", operation), ("algorithm", algorithm.as_str())],
+            1.0,
+        );
+        metrics.observe(
+            "crypto_operation_duration_seconds",
+            &[("operation", operation)],
+            started.elapsed().as_secs_f64(),
+        );
+        if result.is_err() && operation == "encrypt" {
+            metrics.record_counter("crypto_encryption_errors_total", &[], 1.0);
+        }
+        result
+    }
+
+    /// Get and decrypt a secret value on behalf of the system user
     pub fn get_secret(
         &self,
         namespace: &str,
         key: &str,
         env: Environment,
     ) -> Result<Option<Vec<u8>>> {
-        let encryption_key = self.encryption_key.as_ref()
-            .ok_or_else(|| crate::ConfigError::ValidationError(
+        self.get_secret_as(namespace, key, env, "system", None)
+    }
+
+    /// Get and decrypt a secret value on behalf of a user
+    ///
+    /// The secret must be bound to this entry (see [`binding`](crate::binding)).
+    /// The read is reported to the audit logger, if one is set. Fails when
+    /// [reasons are required](Self::with_required_access_reason).
+    pub fn get_secret_as(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        user: &str,
+        context: Option<OperationContext>,
+    ) -> Result<Option<Vec<u8>>> {
+        self.get_secret_with_reason(namespace, key, env, user, context, None)
+    }
+
+    /// Get and decrypt a secret value on behalf of a user, saying why
+    ///
+    /// The reason is recorded in the audit event of the read. A blank
+    /// reason counts as none.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.get_secret", skip(self, context)))]
+    pub fn get_secret_with_reason(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        user: &str,
+        context: Option<OperationContext>,
+        reason: Option<&str>,
+    ) -> Result<Option<Vec<u8>>> {
+        if self.encryption_key.is_none() {
+            return Err(crate::ConfigError::ValidationError(
                 "Encryption key not configured".to_string()
-            ))?;
+            ));
+        }
+        let reason = reason.map(str::trim).filter(|reason| !reason.is_empty());
+        if reason.is_none() && self.require_access_reason {
+            return Err(crate::ConfigError::ValidationError(format!(
+                "A reason is required to read secret {}:{}",
+                namespace, key
+            )));
+        }
 
         let entry = match self.storage.get(namespace, key, env)? {
             Some(e) => e,
             None => return Ok(None),
         };
+        self.check_signature(&entry)?;
+        self.check_binding(&entry)?;
+        self.record_access(&entry);
 
         match entry.value {
             ConfigValue::Secret(ref encrypted_data) => {
-                let plaintext = decrypt(encryption_key, encrypted_data)?;
+                let plaintext = self.open_secret(&entry, encrypted_data)?;
+                if let Some(audit) = &self.audit {
+                    audit.record(AuditedOperation::SecretRead {
+                        namespace,
+                        key,
+                        environment: env,
+                        user,
+                        context: context.as_ref(),
+                        tenant: self.tenant.as_ref(),
+                        reason,
+                    });
+                }
                 Ok(Some(plaintext))
             }
             _ => Err(crate::ConfigError::ValidationError(
@@ -187,26 +777,138 @@ impl ConfigManager {
         }
     }
 
-    /// List all configurations in a namespace
+    /// Check whether a stored configuration holds an encrypted secret
+    pub fn is_secret(&self, namespace: &str, key: &str, env: Environment) -> Result<bool> {
+        Ok(self
+            .storage
+            .get(namespace, key, env)?
+            .map(|entry| entry.value.is_secret())
+            .unwrap_or(false))
+    }
+
+    /// List all configurations in a namespace (secrets stay encrypted)
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.list", skip(self)))]
     pub fn list(&self, namespace: &str, env: Environment) -> Result<Vec<ConfigEntry>> {
-        let mut entries = self.storage.list(namespace, env)?;
+        Ok(self.list_with_options(namespace, env, &ListOptions::default())?.entries)
+    }
 
-        // Decrypt secrets if encryption key is available
-        if let Some(ref key) = self.encryption_key {
-            for entry in &mut entries {
-                self.decrypt_entry(entry, key)?;
-            }
-        }
+    /// List all configurations in a namespace, decrypting secrets
+    ///
+    /// Callers are responsible for checking that the requester may reveal secrets.
+    pub fn list_revealed(&self, namespace: &str, env: Environment) -> Result<Vec<ConfigEntry>> {
+        self.list(namespace, env)?
+            .into_iter()
+            .map(|mut entry| {
+                entry.value = self.reveal(entry.value)?;
+                Ok(entry)
+            })
+            .collect()
+    }
 
-        Ok(entries)
+    /// List all configurations across namespaces and environments (secrets stay encrypted)
+    pub fn list_all(&self) -> Result<Vec<ConfigEntry>> {
+        Ok(self.storage.list_all()?)
     }
 
-    /// Delete a configuration
+    /// Delete a configuration on behalf of the system user
     pub fn delete(&self, namespace: &str, key: &str, env: Environment) -> Result<bool> {
-        Ok(self.storage.delete(namespace, key, env)?)
+        self.delete_with_options(namespace, key, env, "system", &SetOptions::default())
+    }
+
+    /// Delete a configuration on behalf of a user
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.delete", skip(self, options)))]
+    pub fn delete_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<bool> {
+        self.check_write(env, user, options)?;
+        let _writes = self.lock_writes()?;
+
+        let existing = self.storage.get(namespace, key, env)?;
+        Self::check_version(namespace, key, existing.as_ref(), options)?;
+        if options.dry_run {
+            return Ok(existing.is_some());
+        }
+        if existing.is_some() {
+            self.share(namespace, key, env, None, None)?;
+        }
+        let deleted = self.storage.delete(namespace, key, env)?;
+        if let (true, Some(entry)) = (deleted, existing) {
+            self.forget_access(&entry);
+            self.publish(
+                ChangeEvent::deleted(&entry)
+                    .with_user(user)
+                    .with_context(options.context.clone()),
+            );
+        }
+        Ok(deleted)
+    }
+
+    /// Replace the tags and description of a configuration on behalf of the system user
+    pub fn set_metadata(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        tags: Vec<String>,
+        description: Option<String>,
+    ) -> Result<ConfigEntry> {
+        self.set_metadata_with_options(namespace, key, env, tags, description, "system", &SetOptions::default())
+    }
+
+    /// Replace the tags and description of a configuration on behalf of a user
+    ///
+    /// Tags are trimmed and deduplicated. The value and version are left
+    /// unchanged, so no version history entry is recorded.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.set_metadata", skip(self, tags, description, options)))]
+    pub fn set_metadata_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        tags: Vec<String>,
+        description: Option<String>,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<ConfigEntry> {
+        self.check_write(env, user, options)?;
+        let _writes = self.lock_writes()?;
+
+        let mut entry = self.storage.get(namespace, key, env)?.ok_or_else(|| {
+            crate::ConfigError::NotFound(format!("{}:{} ({})", namespace, key, env))
+        })?;
+
+        let mut normalized: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.trim();
+            if tag.is_empty() {
+                return Err(crate::ConfigError::ValidationError("Tags must not be empty".to_string()));
+            }
+            if !normalized.iter().any(|existing| existing == tag) {
+                normalized.push(tag.to_string());
+            }
+        }
+
+        entry.metadata.tags = normalized;
+        entry.metadata.description = description.filter(|d| !d.trim().is_empty());
+        entry.metadata.updated_at = Utc::now();
+        entry.metadata.updated_by = user.to_string();
+        entry.metadata.context = options.context.clone();
+        self.sign_entry(&mut entry)?;
+
+        self.share(namespace, key, env, Some(&entry), None)?;
+        self.storage.set(entry.clone())?;
+        self.publish(ChangeEvent::stored(ChangeKind::Metadata, &entry));
+        Ok(entry)
     }
 
     /// Get version history
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.history", skip(self)))]
     pub fn get_history(
         &self,
         namespace: &str,
@@ -216,7 +918,84 @@ impl ConfigManager {
         self.version_control.get_history(namespace, key, env)
     }
 
-    /// Rollback to a specific version
+    /// Get a page of version history, newest first
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.history", skip(self)))]
+    pub fn get_history_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        options: &HistoryOptions,
+    ) -> Result<Vec<llm_config_storage::VersionEntry>> {
+        self.version_control
+            .get_history_with_options(namespace, key, env, options)
+    }
+
+    /// Versions of every config in a namespace, newest first
+    ///
+    /// With `env` set, only versions in that environment are included.
+    pub fn namespace_history(
+        &self,
+        namespace: &str,
+        env: Option<Environment>,
+    ) -> Result<Vec<llm_config_storage::VersionEntry>> {
+        let mut versions = self.storage.get_namespace_versions(namespace)?;
+        if let Some(env) = env {
+            versions.retain(|version| version.environment == env);
+        }
+        versions.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.version.cmp(&a.version)));
+        Ok(versions)
+    }
+
+    /// Delete the history of every config that `policy` does not keep
+    ///
+    /// The latest version of each config is always kept. Returns the number
+    /// of deleted versions.
+    pub fn prune_history(&self, policy: &RetentionPolicy) -> Result<usize> {
+        self.check_writable()?;
+        Ok(self.storage.prune_all_versions(policy, Utc::now())?)
+    }
+
+    /// Retention settings of the store, from its `retention.toml`
+    ///
+    /// See [`llm_config_storage::retention`] for the format.
+    pub fn retention(&self) -> Result<RetentionConfig> {
+        Ok(self.storage.retention()?)
+    }
+
+    /// Replace the retention settings of the store
+    ///
+    /// They take effect with the next [`apply_retention`](Self::apply_retention).
+    pub fn set_retention(&self, config: &RetentionConfig) -> Result<()> {
+        let _writes = self.lock_writes()?;
+        Ok(self.storage.set_retention(config)?)
+    }
+
+    /// Remove the version history the retention settings do not keep:
+    /// old versions of live configs, and the history of configs deleted
+    /// longer ago than their namespace's `trash_days`
+    pub fn apply_retention(&self) -> Result<RetentionReport> {
+        let _writes = self.lock_writes()?;
+        Ok(self.storage.apply_retention(Utc::now())?)
+    }
+
+    /// Release notes for a namespace since `since`
+    ///
+    /// With `env` set, only changes in that environment are included.
+    pub fn changelog(
+        &self,
+        namespace: &str,
+        env: Option<Environment>,
+        since: DateTime<Utc>,
+    ) -> Result<Changelog> {
+        let mut versions = self.storage.get_namespace_versions(namespace)?;
+        if let Some(env) = env {
+            versions.retain(|version| version.environment == env);
+        }
+        Ok(Changelog::build(namespace, versions, since, Utc::now()))
+    }
+
+    /// Rollback to a specific version on behalf of the system user
     pub fn rollback(
         &self,
         namespace: &str,
@@ -224,19 +1003,66 @@ impl ConfigManager {
         env: Environment,
         version: u64,
     ) -> Result<Option<ConfigEntry>> {
-        self.version_control.rollback(namespace, key, env, version)
+        self.rollback_with_options(namespace, key, env, version, "system", &SetOptions::default())
     }
 
-    /// Decrypt secrets in a config entry
-    fn decrypt_entry(&self, entry: &mut ConfigEntry, key: &SecretKey) -> Result<()> {
-        if let ConfigValue::Secret(ref encrypted) = entry.value {
-            let plaintext = decrypt(key, encrypted)?;
-            // For now, assume secrets are UTF-8 strings
-            let plaintext_str = String::from_utf8(plaintext)
-                .map_err(|e| crate::ConfigError::ValidationError(e.to_string()))?;
-            entry.value = ConfigValue::String(plaintext_str);
+    /// Differences between two versions of a config
+    pub fn diff_versions(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        v1: u64,
+        v2: u64,
+    ) -> Result<ValueDiff> {
+        self.version_control.diff(namespace, key, env, v1, v2)
+    }
+
+    /// Rollback to a specific version on behalf of a user
+    ///
+    /// A `change_description` in the options is recorded as the reason for
+    /// the rollback.
+    #[cfg_attr(feature = "otel", tracing::instrument(name = "config.rollback", skip(self, options)))]
+    pub fn rollback_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        version: u64,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Option<ConfigEntry>> {
+        self.check_write(env, user, options)?;
+        let _writes = self.lock_writes()?;
+
+        if options.dry_run {
+            return self
+                .version_control
+                .rollback_entry(namespace, key, env, version, user, options.context.clone());
         }
-        Ok(())
+        let Some(mut entry) = self
+            .version_control
+            .rollback_entry(namespace, key, env, version, user, options.context.clone())?
+        else {
+            return Ok(None);
+        };
+        self.check_binding(&entry)?;
+        self.sign_entry(&mut entry)?;
+        let description = rollback_description(version, options.change_description.as_deref());
+        self.share(namespace, key, env, Some(&entry), Some(&description))?;
+        self.version_control.commit(&entry, Some(description))?;
+
+        self.publish(ChangeEvent::stored(ChangeKind::Rollback, &entry));
+        Ok(Some(entry))
+    }
+
+    /// Publish a change event and report it to the audit logger
+    pub(crate) fn publish(&self, mut event: ChangeEvent) {
+        event.tenant_id = self.tenant.clone();
+        if let Some(audit) = &self.audit {
+            audit.record(AuditedOperation::Change(&event));
+        }
+        self.changes.publish(event);
     }
 
     /// Encrypt secrets in a config entry
@@ -250,11 +1076,46 @@ impl ConfigManager {
     pub fn export_all(&self, export_path: impl AsRef<Path>) -> Result<usize> {
         Ok(self.storage.export_all(export_path)?)
     }
+
+    /// Cross-check the index, config files and version history, optionally repairing
+    pub fn fsck(&self, repair: bool) -> Result<FsckReport> {
+        Ok(self.storage.fsck(repair)?)
+    }
+
+    /// Save a fresh index snapshot and tidy up storage files
+    ///
+    /// Waits for writes in progress, whose temporary files would otherwise
+    /// be removed.
+    pub fn compact(&self) -> Result<CompactionReport> {
+        let _writes = self.writes.lock().unwrap();
+        Ok(self.storage.compact()?)
+    }
+
+    /// Size from which values are stored once per content (`None` if they are stored inline)
+    pub fn value_dedup(&self) -> Option<usize> {
+        self.storage.dedup()
+    }
+
+    /// Store values of at least `min_size` bytes of JSON once per content,
+    /// or inline again with `None`
+    ///
+    /// Applies to new writes; [`compact`](Self::compact) moves existing
+    /// values. See [`llm_config_storage::blobs`].
+    pub fn set_value_dedup(&self, min_size: Option<usize>) -> Result<()> {
+        let _writes = self.lock_writes()?;
+        Ok(self.storage.set_dedup(min_size)?)
+    }
+
+    /// Number and size of deduplicated values and the space they save
+    pub fn blob_stats(&self) -> Result<BlobStats> {
+        Ok(self.storage.blob_stats()?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::PlannedChange;
     use llm_config_crypto::Algorithm;
     use tempfile::TempDir;
 
@@ -335,6 +1196,57 @@ mod tests {
         assert_eq!(dev_value.as_str().unwrap(), "base");
     }
 
+    #[test]
+    fn test_resolve_namespace() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+
+        manager
+            .set("test/ns", "model", ConfigValue::String("gpt-4".to_string()), Environment::Base, "user")
+            .unwrap();
+        manager
+            .set("test/ns", "timeout", ConfigValue::Integer(30), Environment::Base, "user")
+            .unwrap();
+        manager
+            .set("test/ns", "timeout", ConfigValue::Integer(10), Environment::Development, "user")
+            .unwrap();
+        manager
+            .set("test/ns", "model", ConfigValue::String("gpt-4o".to_string()), Environment::Production, "user")
+            .unwrap();
+        manager
+            .set("test/ns", "debug", ConfigValue::Boolean(true), Environment::Staging, "user")
+            .unwrap();
+
+        // Production inherits from base only
+        let production = manager.resolve_namespace("test/ns", Environment::Production).unwrap();
+        assert_eq!(production.len(), 2);
+        assert_eq!(production["model"].as_str(), Some("gpt-4o"));
+        assert_eq!(production["timeout"].as_i64(), Some(30));
+
+        let development = manager.resolve_namespace("test/ns", Environment::Development).unwrap();
+        assert_eq!(development.len(), 2);
+        assert_eq!(development["model"].as_str(), Some("gpt-4"));
+        assert!(!development.contains_key("debug"));
+
+        // The legacy chain runs through development and staging
+        let legacy = ConfigManager::new(temp_dir.path()).unwrap().with_legacy_overrides(true);
+        let production = legacy.resolve_namespace("test/ns", Environment::Production).unwrap();
+        assert_eq!(production.len(), 3);
+        assert_eq!(production["timeout"].as_i64(), Some(10));
+
+        // A saved graph takes precedence over the compatibility flag
+        legacy
+            .set_environment_graph(EnvironmentGraph::new().with_parent(Environment::Production, Environment::Staging))
+            .unwrap();
+        let reopened = ConfigManager::new(temp_dir.path()).unwrap().with_legacy_overrides(true);
+        let production = reopened.resolve_namespace("test/ns", Environment::Production).unwrap();
+        assert_eq!(production.len(), 3);
+        assert_eq!(production["timeout"].as_i64(), Some(30));
+        assert!(reopened
+            .set_environment_graph(EnvironmentGraph::new().with_parent(Environment::Base, Environment::Edge))
+            .is_err());
+    }
+
     #[test]
     fn test_secret_encryption() {
         let temp_dir = TempDir::new().unwrap();
@@ -355,16 +1267,55 @@ mod tests {
             )
             .unwrap();
 
-        // Retrieve should decrypt automatically
-        let retrieved = manager
+        // Plain reads keep the secret encrypted
+        let masked = manager
             .get("test/ns", "db.password", Environment::Production)
             .unwrap()
             .unwrap();
+        assert!(masked.value.is_secret());
+
+        // Decryption is an explicit opt-in
+        let retrieved = manager
+            .get_revealed("test/ns", "db.password", Environment::Production)
+            .unwrap()
+            .unwrap();
 
         assert_eq!(
             retrieved.value.as_str().unwrap(),
             std::str::from_utf8(secret_value).unwrap()
         );
+
+        assert!(manager.is_secret("test/ns", "db.password", Environment::Production).unwrap());
+        assert!(!manager.is_secret("test/ns", "missing", Environment::Production).unwrap());
+
+        // Listing masks secrets unless revealed
+        let listed = manager.list("test/ns", Environment::Production).unwrap();
+        assert!(listed[0].value.is_secret());
+        assert_eq!(listed[0].value.redacted().as_str(), Some("<encrypted>"));
+        let revealed = manager.list_revealed("test/ns", Environment::Production).unwrap();
+        assert_eq!(revealed[0].value.as_str(), Some("my-secret-password"));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256GcmSiv).unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap().with_encryption_key(key);
+        let sink = Arc::new(llm_config_sink::MemorySink::default());
+        manager.set_metrics(sink.clone());
+        let env = Environment::Production;
+
+        manager.set_secret("app", "api_key", b"sk-123", env, "alice").unwrap();
+        manager.get_revealed("app", "api_key", env).unwrap().unwrap();
+
+        let encrypt = [("operation", "encrypt"), ("algorithm", "aes-256-gcm-siv")];
+        let decrypt = [("operation", "decrypt"), ("algorithm", "aes-256-gcm-siv")];
+        assert_eq!(sink.counter("crypto_operations_total", &encrypt), 1.0);
+        assert_eq!(sink.counter("crypto_operations_total", &decrypt), 1.0);
+        assert_eq!(sink.observations("crypto_operation_duration_seconds", &[("operation", "encrypt")]).len(), 1);
+        // Storage records through the same sink
+        assert!(sink.counter("storage_operations_total", &[("operation", "set")]) >= 1.0);
+        assert!(sink.counter("storage_operations_total", &[("operation", "get")]) >= 1.0);
     }
 
     #[test]
@@ -383,6 +1334,27 @@ mod tests {
         assert_eq!(list.len(), 2);
     }
 
+    #[test]
+    fn test_get_many() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+
+        manager
+            .set("test/ns", "key1", ConfigValue::Integer(1), Environment::Development, "user")
+            .unwrap();
+        manager
+            .set("test/ns", "key2", ConfigValue::Integer(2), Environment::Development, "user")
+            .unwrap();
+
+        let results = manager
+            .get_many("test/ns", &["key2", "missing", "key1"], Environment::Development)
+            .unwrap();
+        let keys: Vec<&str> = results.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["key2", "missing", "key1"]);
+        assert_eq!(results[0].1.as_ref().unwrap().value.as_i64(), Some(2));
+        assert!(results[1].1.is_none());
+    }
+
     #[test]
     fn test_delete() {
         let temp_dir = TempDir::new().unwrap();
@@ -426,4 +1398,363 @@ mod tests {
 
         assert_eq!(rolled_back.value.as_str().unwrap(), "v1");
     }
+
+    #[test]
+    fn test_type_change_protection() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+
+        manager
+            .set("test/ns", "timeout", ConfigValue::Integer(30), Environment::Development, "user")
+            .unwrap();
+
+        let result = manager.set(
+            "test/ns",
+            "timeout",
+            ConfigValue::String("30s".to_string()),
+            Environment::Development,
+            "user",
+        );
+        assert!(matches!(result, Err(crate::ConfigError::TypeChange(_))));
+
+        // Same type is fine
+        manager
+            .set("test/ns", "timeout", ConfigValue::Integer(60), Environment::Development, "user")
+            .unwrap();
+
+        // Explicit opt-in allows the change
+        let entry = manager
+            .set_with_options(
+                "test/ns",
+                "timeout",
+                ConfigValue::String("30s".to_string()),
+                Environment::Development,
+                "user",
+                &SetOptions::default().with_allow_type_change(true),
+            )
+            .unwrap();
+        assert_eq!(entry.value.as_str(), Some("30s"));
+        assert_eq!(entry.version, 3);
+    }
+
+    #[test]
+    fn test_environment_protection() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        manager
+            .set("app", "model", ConfigValue::String("gpt-4".to_string()), Environment::Production, "alice")
+            .unwrap();
+
+        manager
+            .set_protection(
+                EnvironmentProtection::new()
+                    .with_level(Environment::Production, crate::ProtectionLevel::AdminOnly)
+                    .with_admin("deploy-bot"),
+            )
+            .unwrap();
+
+        let value = ConfigValue::String("gpt-4o".to_string());
+        let result = manager.set("app", "model", value.clone(), Environment::Production, "alice");
+        assert!(matches!(result, Err(crate::ConfigError::Protected(_))));
+        assert!(manager.delete("app", "model", Environment::Production).is_err());
+        assert!(manager.rollback("app", "model", Environment::Production, 1).is_err());
+
+        manager
+            .set("app", "model", value.clone(), Environment::Production, "deploy-bot")
+            .unwrap();
+        manager
+            .set("app", "model", value, Environment::Development, "alice")
+            .unwrap();
+
+        // Settings persist across managers
+        let reopened = ConfigManager::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.protection(), manager.protection());
+        assert!(reopened
+            .delete_with_options("app", "model", Environment::Production, "alice", &SetOptions::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_change_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let bus = ChangeBus::default();
+        let first = ConfigManager::new(temp_dir.path()).unwrap().with_change_bus(bus.clone());
+        let second = ConfigManager::new(temp_dir.path()).unwrap().with_change_bus(bus);
+        let mut events = first.subscribe_changes();
+        let env = Environment::Development;
+
+        second.set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        second.set("app", "model", ConfigValue::String("gpt-4o".into()), env, "alice").unwrap();
+        second.set_metadata("app", "model", env, vec!["llm".into()], None).unwrap();
+        second.rollback("app", "model", env, 1).unwrap();
+        second.delete("app", "model", env).unwrap();
+        assert!(!second.delete("app", "model", env).unwrap());
+
+        let received: Vec<(ChangeKind, u64)> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| (event.kind, event.version))
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                (ChangeKind::Set, 1),
+                (ChangeKind::Set, 2),
+                (ChangeKind::Metadata, 2),
+                (ChangeKind::Rollback, 3),
+                (ChangeKind::Delete, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operation_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let mut events = manager.subscribe_changes();
+        let env = Environment::Production;
+
+        let context = OperationContext::default()
+            .with_ip_address("10.0.0.7")
+            .with_request_id("req-1");
+        let options = SetOptions::default().with_context(context.clone());
+        manager
+            .set_with_options("app", "model", ConfigValue::String("gpt-4".into()), env, "alice", &options)
+            .unwrap();
+        let stored = manager.get("app", "model", env).unwrap().unwrap();
+        assert_eq!(stored.metadata.context, Some(context.clone()));
+
+        // A later write without context does not keep the stale one
+        manager.set("app", "model", ConfigValue::String("gpt-4o".into()), env, "bob").unwrap();
+        assert!(manager.get("app", "model", env).unwrap().unwrap().metadata.context.is_none());
+
+        let rolled_back = manager
+            .rollback_with_options("app", "model", env, 1, "carol", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rolled_back.metadata.updated_by, "carol");
+        assert_eq!(rolled_back.metadata.context, Some(context.clone()));
+
+        manager.delete_with_options("app", "model", env, "dave", &options).unwrap();
+
+        let received: Vec<(String, Option<String>)> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| (event.user, event.context.and_then(|c| c.request_id)))
+            .collect();
+        let request = Some("req-1".to_string());
+        assert_eq!(
+            received,
+            vec![
+                ("alice".to_string(), request.clone()),
+                ("bob".to_string(), None),
+                ("carol".to_string(), request.clone()),
+                ("dave".to_string(), request),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let env = Environment::Development;
+
+        assert!(matches!(
+            manager.set_metadata("app", "model", env, vec![], None),
+            Err(crate::ConfigError::NotFound(_))
+        ));
+
+        manager.set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        let entry = manager
+            .set_metadata(
+                "app",
+                "model",
+                env,
+                vec!["llm".into(), " prod ".into(), "llm".into()],
+                Some("Default chat model".into()),
+            )
+            .unwrap();
+        assert_eq!(entry.metadata.tags, vec!["llm", "prod"]);
+        assert_eq!(entry.version, 1);
+
+        let stored = manager.get("app", "model", env).unwrap().unwrap();
+        assert_eq!(stored.metadata.description.as_deref(), Some("Default chat model"));
+        assert_eq!(stored.metadata.updated_by, "system");
+        assert_eq!(manager.get_history("app", "model", env).unwrap().len(), 1);
+
+        assert!(manager.set_metadata("app", "model", env, vec!["  ".into()], None).is_err());
+    }
+
+    #[test]
+    fn test_changelog() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+
+        let env = Environment::Production;
+        manager.set("ml/serving", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        manager
+            .set_with_options(
+                "ml/serving",
+                "model",
+                ConfigValue::String("gpt-4o".into()),
+                env,
+                "bob",
+                &SetOptions::default().with_change_description("Upgrade model"),
+            )
+            .unwrap();
+        manager.set("ml/serving", "model", ConfigValue::String("dev".into()), Environment::Development, "carol").unwrap();
+        manager.set("other", "model", ConfigValue::String("x".into()), env, "dave").unwrap();
+
+        let since = Utc::now() - chrono::Duration::days(7);
+        let changelog = manager.changelog("ml/serving", Some(env), since).unwrap();
+        assert_eq!(changelog.change_count(), 2);
+        assert_eq!(changelog.authors(), vec!["alice", "bob"]);
+        assert_eq!(changelog.groups[0].changes[0].description.as_deref(), Some("Upgrade model"));
+
+        assert_eq!(manager.changelog("ml/serving", None, since).unwrap().change_count(), 3);
+        assert!(manager.changelog("ml/serving", None, Utc::now() + chrono::Duration::hours(1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_set_with_expected_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let env = Environment::Production;
+
+        // 0: the key must not exist yet
+        manager
+            .set_with_expected_version("app", "timeout", ConfigValue::Integer(30), env, "alice", 0)
+            .unwrap();
+        assert!(matches!(
+            manager.set_with_expected_version("app", "timeout", ConfigValue::Integer(45), env, "bob", 0),
+            Err(crate::ConfigError::Conflict(_))
+        ));
+
+        let entry = manager
+            .set_with_expected_version("app", "timeout", ConfigValue::Integer(60), env, "alice", 1)
+            .unwrap();
+        assert_eq!(entry.version, 2);
+
+        // A writer that read version 1 cannot overwrite version 2
+        let stale = manager.set_with_expected_version("app", "timeout", ConfigValue::Integer(90), env, "bob", 1);
+        assert!(matches!(stale, Err(crate::ConfigError::Conflict(_))));
+        assert_eq!(manager.get("app", "timeout", env).unwrap().unwrap().value.as_i64(), Some(60));
+
+        let options = SetOptions::default().with_expected_version(1);
+        assert!(manager.delete_with_options("app", "timeout", env, "bob", &options).is_err());
+
+        // Concurrent writers that read the same version: exactly one wins
+        let manager = std::sync::Arc::new(manager);
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let manager = std::sync::Arc::clone(&manager);
+                std::thread::spawn(move || {
+                    manager
+                        .set_with_expected_version("app", "timeout", ConfigValue::Integer(i), env, "writer", 2)
+                        .is_ok()
+                })
+            })
+            .collect();
+        let successes = writers
+            .into_iter()
+            .map(|writer| writer.join().unwrap())
+            .filter(|&won| won)
+            .count();
+        assert_eq!(successes, 1);
+        assert_eq!(manager.get("app", "timeout", env).unwrap().unwrap().version, 3);
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let env = Environment::Production;
+        let mut events = manager.subscribe_changes();
+        let dry_run = SetOptions::default().with_dry_run(true);
+        let set = |value: ConfigValue, options: &SetOptions| {
+            manager.set_with_options("app", "timeout", value, env, "alice", options)
+        };
+
+        let planned = set(ConfigValue::Integer(30), &dry_run).unwrap();
+        assert_eq!(planned.version, 1);
+        assert!(manager.get("app", "timeout", env).unwrap().is_none());
+
+        set(ConfigValue::Integer(30), &SetOptions::default()).unwrap();
+        set(ConfigValue::Integer(60), &SetOptions::default()).unwrap();
+        let planned = set(ConfigValue::Integer(90), &dry_run).unwrap();
+        assert_eq!((planned.version, planned.value.as_i64()), (3, Some(90)));
+        let change = PlannedChange::between(manager.get("app", "timeout", env).unwrap().as_ref(), Some(&planned)).unwrap();
+        assert_eq!(change.old_value, Some(serde_json::json!(60)));
+
+        // Checks still apply
+        assert!(matches!(
+            set(ConfigValue::String("90s".into()), &dry_run),
+            Err(crate::ConfigError::TypeChange(_))
+        ));
+        assert!(matches!(
+            set(ConfigValue::Integer(90), &dry_run.clone().with_expected_version(1)),
+            Err(crate::ConfigError::Conflict(_))
+        ));
+
+        let rolled_back = manager
+            .rollback_with_options("app", "timeout", env, 1, "alice", &dry_run)
+            .unwrap()
+            .unwrap();
+        assert_eq!((rolled_back.version, rolled_back.value.as_i64()), (3, Some(30)));
+        assert!(manager.delete_with_options("app", "timeout", env, "alice", &dry_run).unwrap());
+        assert!(!manager.delete_with_options("app", "missing", env, "alice", &dry_run).unwrap());
+
+        let current = manager.get("app", "timeout", env).unwrap().unwrap();
+        assert_eq!((current.version, current.value.as_i64()), (2, Some(60)));
+        assert_eq!(manager.get_history("app", "timeout", env).unwrap().len(), 2);
+        let published = std::iter::from_fn(|| events.try_recv().ok()).count();
+        assert_eq!(published, 2);
+    }
+
+    #[test]
+    fn test_history_retention() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_history_retention(RetentionPolicy::keep_last(3));
+        let env = Environment::Production;
+
+        for i in 1..=5 {
+            manager.set("app", "timeout", ConfigValue::Integer(i), env, "alice").unwrap();
+        }
+        let versions: Vec<u64> = manager
+            .get_history("app", "timeout", env)
+            .unwrap()
+            .iter()
+            .map(|v| v.version)
+            .collect();
+        assert_eq!(versions, vec![5, 4, 3]);
+
+        let page = manager
+            .get_history_with_options(
+                "app",
+                "timeout",
+                env,
+                &HistoryOptions::default().with_limit(1).with_before_version(5),
+            )
+            .unwrap();
+        assert_eq!(page[0].version, 4);
+
+        // Pruned versions can no longer be rolled back to
+        assert!(manager.rollback("app", "timeout", env, 1).unwrap().is_none());
+        let rolled_back = manager.rollback("app", "timeout", env, 3).unwrap().unwrap();
+        assert_eq!(rolled_back.version, 6);
+        assert_eq!(rolled_back.value.as_i64(), Some(3));
+
+        assert_eq!(manager.prune_history(&RetentionPolicy::keep_last(1)).unwrap(), 2);
+        assert!(manager.fsck(false).unwrap().is_clean());
+
+        // Retention settings of the store, applied on demand
+        for i in 1..=3 {
+            manager.set("app", "retries", ConfigValue::Integer(i), env, "alice").unwrap();
+        }
+        let mut settings = RetentionConfig::default();
+        settings.default.history = Some(RetentionPolicy::keep_last(2));
+        manager.set_retention(&settings).unwrap();
+        assert_eq!(manager.retention().unwrap(), settings);
+        assert_eq!(manager.apply_retention().unwrap().pruned_versions, 1);
+        assert_eq!(manager.get_history("app", "retries", env).unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,205 @@
+//! Merging environment overrides with inherited values
+//!
+//! When a key is resolved along the [inheritance chain](crate::EnvironmentGraph::chain),
+//! each environment that defines it is applied on top of the value inherited
+//! so far. With [`MergeStrategy::Replace`] (the default) the override wins
+//! outright. With [`MergeStrategy::DeepMerge`], object overrides only change
+//! the fields they specify:
+//!
+//! - objects are merged key by key, recursively, so nested objects merge too
+//! - arrays are replaced as a whole, never concatenated
+//! - scalars, secrets and values of a different type replace the inherited value
+//! - an override cannot remove an inherited field; write it with `replace`
+//!   to drop fields
+//!
+//! The strategy of an override comes from its entry
+//! ([`ConfigMetadata::merge`](crate::ConfigMetadata::merge), set with
+//! [`SetOptions::with_merge`](crate::SetOptions::with_merge)), falling back to
+//! the namespace default in [`MergeSettings`].
+
+use crate::{ConfigEntry, ConfigValue, MergeStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Storage record kind and id the merge settings are persisted under
+pub(crate) const MERGE_SETTINGS_RECORD: (&str, &str) = ("settings", "merge");
+
+/// Default merge strategy of each namespace
+///
+/// Namespaces without an explicit strategy replace inherited values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeSettings {
+    #[serde(default)]
+    pub namespaces: BTreeMap<String, MergeStrategy>,
+}
+
+impl MergeSettings {
+    /// Create settings where every namespace replaces inherited values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the default strategy of a namespace
+    pub fn with_namespace(mut self, namespace: impl Into<String>, strategy: MergeStrategy) -> Self {
+        self.namespaces.insert(namespace.into(), strategy);
+        self
+    }
+
+    /// Default strategy of a namespace
+    pub fn strategy(&self, namespace: &str) -> MergeStrategy {
+        self.namespaces.get(namespace).copied().unwrap_or_default()
+    }
+}
+
+/// Apply an override entry on top of the value inherited so far
+pub(crate) fn apply_override(
+    inherited: Option<ConfigValue>,
+    entry: ConfigEntry,
+    default: MergeStrategy,
+) -> ConfigValue {
+    match (inherited, entry.metadata.merge.unwrap_or(default)) {
+        (Some(inherited), MergeStrategy::DeepMerge) => deep_merge(inherited, entry.value),
+        _ => entry.value,
+    }
+}
+
+/// Merge `overlay` into `base`: objects key by key, anything else replaced
+pub fn deep_merge(base: ConfigValue, overlay: ConfigValue) -> ConfigValue {
+    match (base, overlay) {
+        (ConfigValue::Object(mut base), ConfigValue::Object(overlay)) => {
+            for (key, value) in overlay {
+                let merged = match base.remove(&key) {
+                    Some(inherited) => deep_merge(inherited, value),
+                    None => value,
+                };
+                base.insert(key, merged);
+            }
+            ConfigValue::Object(base)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigManager, Environment, SetOptions};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn object(fields: &[(&str, ConfigValue)]) -> ConfigValue {
+        ConfigValue::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+
+    fn string(s: &str) -> ConfigValue {
+        ConfigValue::String(s.to_string())
+    }
+
+    fn model_settings() -> ConfigValue {
+        object(&[
+            ("model", string("gpt-4")),
+            ("stop", ConfigValue::Array(vec![string("\n"), string("###")])),
+            (
+                "retry",
+                object(&[("attempts", ConfigValue::Integer(3)), ("backoff", string("1s"))]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_deep_merge_conflict_rules() {
+        let overlay = object(&[
+            ("stop", ConfigValue::Array(vec![string("END")])),
+            ("retry", object(&[("attempts", ConfigValue::Integer(5))])),
+            ("model", ConfigValue::Integer(4)),
+        ]);
+
+        let ConfigValue::Object(merged) = deep_merge(model_settings(), overlay) else {
+            panic!("objects merge into an object");
+        };
+        // Arrays and type changes replace; nested objects merge
+        assert_eq!(merged["stop"].type_name(), "array");
+        let ConfigValue::Array(stop) = &merged["stop"] else { unreachable!() };
+        assert_eq!(stop.len(), 1);
+        assert_eq!(merged["model"].as_i64(), Some(4));
+        let ConfigValue::Object(retry) = &merged["retry"] else {
+            panic!("nested objects stay objects");
+        };
+        assert_eq!(retry["attempts"].as_i64(), Some(5));
+        assert_eq!(retry["backoff"].as_str(), Some("1s"));
+
+        // A non-object on either side replaces
+        assert_eq!(deep_merge(model_settings(), string("off")).as_str(), Some("off"));
+        assert_eq!(deep_merge(string("off"), model_settings()).type_name(), "object");
+    }
+
+    #[test]
+    fn test_overrides_merge_per_entry_and_per_namespace() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let prod_override = object(&[("retry", object(&[("attempts", ConfigValue::Integer(5))]))]);
+        for namespace in ["entry", "namespace", "replaced"] {
+            manager
+                .set(namespace, "llm", model_settings(), Environment::Base, "alice")
+                .unwrap();
+        }
+        manager
+            .set_with_options(
+                "entry",
+                "llm",
+                prod_override.clone(),
+                Environment::Production,
+                "alice",
+                &SetOptions::default().with_merge(MergeStrategy::DeepMerge),
+            )
+            .unwrap();
+        for namespace in ["namespace", "replaced"] {
+            manager
+                .set(namespace, "llm", prod_override.clone(), Environment::Production, "alice")
+                .unwrap();
+        }
+        manager
+            .set_merge_settings(MergeSettings::new().with_namespace("namespace", MergeStrategy::DeepMerge))
+            .unwrap();
+
+        for namespace in ["entry", "namespace"] {
+            let merged = manager
+                .get_with_overrides(namespace, "llm", Environment::Production)
+                .unwrap()
+                .unwrap();
+            let ConfigValue::Object(fields) = merged else { panic!("expected an object") };
+            assert_eq!(fields["model"].as_str(), Some("gpt-4"), "{}", namespace);
+            let ConfigValue::Object(retry) = &fields["retry"] else { unreachable!() };
+            assert_eq!(retry["attempts"].as_i64(), Some(5));
+            assert_eq!(retry["backoff"].as_str(), Some("1s"));
+
+            let resolved = manager.resolve_namespace(namespace, Environment::Production).unwrap();
+            let ConfigValue::Object(fields) = &resolved["llm"] else { unreachable!() };
+            assert_eq!(fields.len(), 3);
+        }
+
+        // Without a strategy the override replaces the base value
+        let replaced = manager
+            .get_with_overrides("replaced", "llm", Environment::Production)
+            .unwrap()
+            .unwrap();
+        let ConfigValue::Object(fields) = replaced else { panic!("expected an object") };
+        assert_eq!(fields.len(), 1);
+
+        // The per-entry strategy survives updates that do not set one
+        manager
+            .set("entry", "llm", prod_override, Environment::Production, "bob")
+            .unwrap();
+        let entry = manager.get("entry", "llm", Environment::Production).unwrap().unwrap();
+        assert_eq!(entry.metadata.merge, Some(MergeStrategy::DeepMerge));
+
+        // Settings persist
+        let reopened = ConfigManager::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.merge_settings().strategy("namespace"), MergeStrategy::DeepMerge);
+    }
+}
@@ -0,0 +1,432 @@
+//! Migrating a store to another backend
+//!
+//! [`ConfigManager::migrate_to`] copies every entry of the store to a
+//! [`MigrationTarget`]. Another file store receives the entries with their
+//! version history (deleted entries' too) and the settings records (roles,
+//! protection, environment graph, ...); a key-value store receives the
+//! entries in the format replicas share them in (see [`crate::watch`]),
+//! without history.
+//!
+//! Entries the target already holds unchanged are skipped, so an interrupted
+//! migration is resumed by running it again. An entry the target holds with
+//! a different value fails the migration, unless it may be overwritten. Once
+//! everything is copied, the target is verified against the source: both
+//! must hold the same number of entries, and a SHA-256 digest over the
+//! entries (and histories, for file stores) must match.
+//!
+//! The source is read-only while entries are copied (see
+//! [`crate::maintenance`]), so no write made during the migration is missed.
+
+use crate::maintenance::READ_ONLY_RECORD;
+use crate::watch::SharedEntry;
+use crate::{ConfigEntry, ConfigError, ConfigManager, Environment, Result, StorageError, VersionEntry};
+use llm_config_storage::file::FileStorage;
+use llm_config_storage::{entry_key, KvStore};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Change description replicas record for entries a migration added to a key-value store
+pub const MIGRATED_DESCRIPTION: &str = "Migrated from another store";
+
+/// Store a migration copies entries to
+pub enum MigrationTarget {
+    /// Another file store, which also receives version history and settings
+    File(FileStorage),
+    /// A key-value store, under keys starting with `prefix`
+    Kv { store: Arc<dyn KvStore>, prefix: String },
+}
+
+impl MigrationTarget {
+    /// The file store in `path`, created if missing
+    pub fn file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::File(FileStorage::new(path)?))
+    }
+
+    /// A key-value store, under keys starting with `prefix`
+    pub fn kv(store: Arc<dyn KvStore>, prefix: impl Into<String>) -> Self {
+        Self::Kv {
+            store,
+            prefix: prefix.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Digests of the entries (and histories) the target holds
+    fn digests(&self) -> Result<Digests> {
+        match self {
+            Self::File(storage) => Digests::of(storage, true),
+            Self::Kv { store, prefix } => {
+                let mut digests = Digests::default();
+                for pair in store.list(&format!("{}/", prefix), 0, Duration::ZERO)?.pairs {
+                    digests.add_entry(&SharedEntry::decode(&pair)?.entry)?;
+                }
+                Ok(digests)
+            }
+        }
+    }
+}
+
+impl fmt::Display for MigrationTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(_) => f.write_str("file store"),
+            Self::Kv { prefix, .. } => write!(f, "key-value store under {}/", prefix),
+        }
+    }
+}
+
+/// How to migrate a store
+#[derive(Debug, Clone, Default)]
+pub struct MigrateOptions {
+    /// Replace entries the target holds with a different value
+    pub overwrite: bool,
+    /// Leave the source writable while copying
+    pub keep_writable: bool,
+    /// Leave the source read-only after a verified migration, for switching
+    /// clients over to the target
+    pub keep_read_only: bool,
+}
+
+impl MigrateOptions {
+    /// Replace entries the target holds with a different value
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Leave the source writable while copying
+    pub fn with_keep_writable(mut self, keep_writable: bool) -> Self {
+        self.keep_writable = keep_writable;
+        self
+    }
+
+    /// Leave the source read-only after a verified migration
+    pub fn with_keep_read_only(mut self, keep_read_only: bool) -> Self {
+        self.keep_read_only = keep_read_only;
+        self
+    }
+}
+
+/// Outcome of a migration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationReport {
+    /// Entries in the source
+    pub entries: usize,
+    /// Entries written to the target
+    pub copied: usize,
+    /// Entries the target already held unchanged
+    pub skipped: usize,
+    /// Versions in the histories of the source (0 for key-value stores)
+    pub versions: usize,
+    /// Settings records copied (0 for key-value stores)
+    pub records: usize,
+    /// Entries in the target after copying
+    pub target_entries: usize,
+    /// Digest of the entries (and histories) of the source
+    pub source_digest: String,
+    /// Digest of the entries (and histories) of the target after copying
+    pub target_digest: String,
+}
+
+impl MigrationReport {
+    /// Whether the target holds exactly what the source holds
+    pub fn is_verified(&self) -> bool {
+        self.entries == self.target_entries && self.source_digest == self.target_digest
+    }
+}
+
+/// SHA-256 of every entry and history of a store, by their key
+#[derive(Default)]
+struct Digests {
+    entries: usize,
+    hashes: BTreeMap<String, String>,
+}
+
+impl Digests {
+    /// Digests of the entries of a file store, and its histories if `history` is set
+    fn of(storage: &FileStorage, history: bool) -> Result<Self> {
+        let mut digests = Self::default();
+        for entry in storage.list_all()? {
+            digests.add_entry(&entry)?;
+        }
+        if history {
+            for (namespace, key, env) in storage.list_histories()? {
+                let history = History::read(storage, &namespace, &key, env)?;
+                digests.hashes.insert(entry_key("history", &namespace, &key, env), history.hash()?);
+            }
+        }
+        Ok(digests)
+    }
+
+    fn add_entry(&mut self, entry: &ConfigEntry) -> Result<()> {
+        let key = entry_key("entry", &entry.namespace, &entry.key, entry.environment);
+        self.hashes.insert(key, hash(entry)?);
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Digest over all hashes
+    fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        for (key, hash) in &self.hashes {
+            hasher.update(format!("{} {}\n", key, hash));
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// The version history of one config
+#[derive(Serialize)]
+struct History {
+    versions: Vec<VersionEntry>,
+    pruned: u64,
+}
+
+impl History {
+    fn read(storage: &FileStorage, namespace: &str, key: &str, env: Environment) -> Result<Self> {
+        Ok(Self {
+            versions: storage.get_versions(namespace, key, env)?,
+            pruned: storage.pruned_version(namespace, key, env),
+        })
+    }
+
+    fn hash(&self) -> Result<String> {
+        hash(self)
+    }
+}
+
+/// SHA-256 of a value's JSON, with object keys sorted
+fn hash<T: Serialize>(value: &T) -> Result<String> {
+    // `serde_json::Value` sorts object keys, so equal maps hash equally
+    let json = serde_json::to_value(value)
+        .and_then(|value| serde_json::to_vec(&value))
+        .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+    Ok(hex::encode(Sha256::digest(json)))
+}
+
+impl ConfigManager {
+    /// Copy every entry of the store to `target` and verify the copy
+    ///
+    /// Unless `options` keeps it writable, the store is read-only while
+    /// copying (as `user`), and writable again afterwards. Fails with a
+    /// conflict if the target holds an entry with a different value and
+    /// `options` does not allow overwriting it. A migration that copied
+    /// everything returns its report even if verification failed; check
+    /// [`MigrationReport::is_verified`].
+    pub fn migrate_to(&self, target: &MigrationTarget, user: &str, options: &MigrateOptions) -> Result<MigrationReport> {
+        let made_read_only = !options.keep_writable && self.read_only_mode()?.is_none();
+        if made_read_only {
+            self.enable_read_only(user, Some(format!("Migrating to {}", target)))?;
+        }
+
+        let result = self.copy_to(target, options);
+        let verified = result.as_ref().is_ok_and(MigrationReport::is_verified);
+        if made_read_only && !(options.keep_read_only && verified) {
+            self.disable_read_only()?;
+        }
+        result
+    }
+
+    fn copy_to(&self, target: &MigrationTarget, options: &MigrateOptions) -> Result<MigrationReport> {
+        let storage = self.storage();
+        let mut entries = storage.list_all()?;
+        entries.sort_by(|a, b| (&a.namespace, &a.key, a.environment).cmp(&(&b.namespace, &b.key, b.environment)));
+        let conflict = |entry: &ConfigEntry| {
+            ConfigError::Conflict(format!(
+                "{}:{} ({}) already exists in the {} with a different value",
+                entry.namespace, entry.key, entry.environment, target
+            ))
+        };
+
+        let mut report = MigrationReport {
+            entries: entries.len(),
+            copied: 0,
+            skipped: 0,
+            versions: 0,
+            records: 0,
+            target_entries: 0,
+            source_digest: String::new(),
+            target_digest: String::new(),
+        };
+        match target {
+            MigrationTarget::File(copy) => {
+                // Histories are written before their entry, so an entry the
+                // target holds has its history
+                let mut histories = storage.list_histories()?;
+                histories.sort();
+                for (namespace, key, env) in histories {
+                    let history = History::read(storage, &namespace, &key, env)?;
+                    report.versions += history.versions.len();
+                    let source = storage.get(&namespace, &key, env)?;
+                    let existing = copy.get(&namespace, &key, env)?;
+                    if let (Some(source), Some(existing)) = (&source, &existing) {
+                        if hash(existing)? != hash(source)? && !options.overwrite {
+                            return Err(conflict(source));
+                        }
+                    }
+                    if History::read(copy, &namespace, &key, env)?.hash()? != history.hash()? {
+                        copy.import_history(&namespace, &key, env, &history.versions, history.pruned)?;
+                    }
+                }
+
+                for entry in &entries {
+                    let existing = copy.get(&entry.namespace, &entry.key, entry.environment)?;
+                    match existing {
+                        Some(existing) if hash(&existing)? == hash(entry)? => report.skipped += 1,
+                        Some(_) if !options.overwrite => return Err(conflict(entry)),
+                        _ => {
+                            copy.set(entry.clone())?;
+                            report.copied += 1;
+                        }
+                    }
+                }
+
+                for kind in storage.record_kinds()? {
+                    for id in storage.record_ids(&kind)? {
+                        if (kind.as_str(), id.as_str()) == READ_ONLY_RECORD {
+                            continue;
+                        }
+                        if let Some(record) = storage.get_record::<serde_json::Value>(&kind, &id)? {
+                            copy.put_record(&kind, &id, &record)?;
+                            report.records += 1;
+                        }
+                    }
+                }
+            }
+            MigrationTarget::Kv { store, prefix } => {
+                for entry in &entries {
+                    let key = entry_key(prefix, &entry.namespace, &entry.key, entry.environment);
+                    let existing = store.get(&key)?;
+                    if let Some(pair) = &existing {
+                        if hash(&SharedEntry::decode(pair)?.entry)? == hash(entry)? {
+                            report.skipped += 1;
+                            continue;
+                        }
+                        if !options.overwrite {
+                            return Err(conflict(entry));
+                        }
+                    }
+
+                    let shared = SharedEntry {
+                        entry: entry.clone(),
+                        description: Some(MIGRATED_DESCRIPTION.to_string()),
+                    };
+                    let index = existing.map_or(0, |pair| pair.modify_index);
+                    if !store.cas(&key, &shared.encode()?, index)? {
+                        return Err(ConfigError::Conflict(format!(
+                            "{}:{} ({}) was changed in the {} during the migration",
+                            entry.namespace, entry.key, entry.environment, target
+                        )));
+                    }
+                    report.copied += 1;
+                }
+            }
+        }
+
+        let source = Digests::of(storage, matches!(target, MigrationTarget::File(_)))?;
+        let copy = target.digests()?;
+        report.source_digest = source.digest();
+        report.target_digest = copy.digest();
+        report.target_entries = copy.entries;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigValue, MergeSettings, MergeStrategy, READ_ONLY_MODE};
+    use llm_config_storage::MemoryKv;
+    use tempfile::TempDir;
+
+    fn source(dir: &TempDir) -> ConfigManager {
+        let manager = ConfigManager::new(dir.path()).unwrap();
+        let env = Environment::Production;
+        manager.set("app/llm", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        manager.set("app/llm", "model", ConfigValue::String("gpt-4o".into()), env, "alice").unwrap();
+        manager.set("app/llm", "temperature", ConfigValue::Float(0.2), env, "alice").unwrap();
+        manager.set("app/llm", "legacy", ConfigValue::Boolean(true), env, "alice").unwrap();
+        manager.delete("app/llm", "legacy", env).unwrap();
+        manager
+            .set_merge_settings(MergeSettings::new().with_namespace("app", MergeStrategy::DeepMerge))
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_migrate_to_file_store() {
+        let (source_dir, target_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let manager = source(&source_dir);
+        let env = Environment::Production;
+
+        let report = manager
+            .migrate_to(&MigrationTarget::file(target_dir.path()).unwrap(), "ops", &MigrateOptions::default())
+            .unwrap();
+        assert!(report.is_verified());
+        assert_eq!((report.entries, report.copied, report.skipped), (2, 2, 0));
+        assert_eq!(report.versions, 4);
+        assert_eq!(report.records, 1);
+        assert!(manager.read_only_mode().unwrap().is_none());
+
+        let migrated = ConfigManager::new(target_dir.path()).unwrap();
+        assert_eq!(migrated.get("app/llm", "model", env).unwrap().unwrap().value.as_str(), Some("gpt-4o"));
+        assert_eq!(migrated.get_history("app/llm", "model", env).unwrap().len(), 2);
+        assert_eq!(migrated.get_history("app/llm", "legacy", env).unwrap().len(), 1);
+        assert_eq!(migrated.merge_settings(), manager.merge_settings());
+        assert!(migrated.fsck(false).unwrap().is_clean());
+
+        // Running it again copies nothing
+        let again = manager
+            .migrate_to(&MigrationTarget::file(target_dir.path()).unwrap(), "ops", &MigrateOptions::default())
+            .unwrap();
+        assert!(again.is_verified());
+        assert_eq!((again.copied, again.skipped), (0, 2));
+        assert_eq!(again.source_digest, report.source_digest);
+
+        // Entries changed in the target are only replaced with --overwrite
+        migrated.set("app/llm", "model", ConfigValue::String("claude".into()), env, "bob").unwrap();
+        drop(migrated);
+        let target = MigrationTarget::file(target_dir.path()).unwrap();
+        let conflict = manager.migrate_to(&target, "ops", &MigrateOptions::default());
+        assert!(matches!(conflict, Err(ConfigError::Conflict(_))));
+        assert!(manager.read_only_mode().unwrap().is_none());
+        let report = manager
+            .migrate_to(&target, "ops", &MigrateOptions::default().with_overwrite(true))
+            .unwrap();
+        assert!(report.is_verified());
+        assert_eq!(report.copied, 1);
+    }
+
+    #[test]
+    fn test_migrate_to_kv_store() {
+        let source_dir = TempDir::new().unwrap();
+        let manager = source(&source_dir);
+        let kv = Arc::new(MemoryKv::new());
+        let target = MigrationTarget::kv(Arc::clone(&kv) as Arc<dyn KvStore>, "llm-config");
+
+        let options = MigrateOptions::default().with_keep_read_only(true);
+        let report = manager.migrate_to(&target, "ops", &options).unwrap();
+        assert!(report.is_verified());
+        assert_eq!((report.copied, report.versions, report.records), (2, 0, 0));
+        let mode = manager.read_only_mode().unwrap().unwrap();
+        assert_eq!(mode.enabled_by, "ops");
+        let write = manager.set("app/llm", "model", ConfigValue::Integer(1), Environment::Production, "alice");
+        assert!(matches!(write, Err(ConfigError::InvalidOperation(msg)) if msg == READ_ONLY_MODE));
+
+        // A replica starting from the key-value store gets every entry
+        let replica_dir = TempDir::new().unwrap();
+        let replica = ConfigManager::new(replica_dir.path())
+            .unwrap()
+            .with_kv_store(kv as Arc<dyn KvStore>, "llm-config");
+        assert_eq!(replica.watch(Duration::ZERO).unwrap().len(), 2);
+        let history = replica.get_history("app/llm", "model", Environment::Production).unwrap();
+        assert_eq!(history[0].change_description.as_deref(), Some(MIGRATED_DESCRIPTION));
+
+        let again = manager.migrate_to(&target, "ops", &MigrateOptions::default()).unwrap();
+        assert_eq!((again.copied, again.skipped), (0, 2));
+    }
+}
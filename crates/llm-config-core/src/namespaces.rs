@@ -0,0 +1,202 @@
+//! Cloning and renaming namespaces
+//!
+//! [`ConfigManager::clone_namespace`] copies every config of a namespace,
+//! with its version history, to a new namespace; [`ConfigManager::rename_namespace`]
+//! moves it. Either the whole namespace is copied or nothing is: the target
+//! must be empty, and files written before a failure are removed again.
+//! Secrets are re-encrypted [bound](crate::binding) to the new namespace,
+//! which needs the encryption key. The namespace's default merge strategy
+//! and [parent](crate::hierarchy) follow it; renamed parents keep their
+//! children.
+
+use crate::{
+    ChangeEvent, ChangeKind, ConfigEntry, ConfigError, ConfigManager, Environment, Result,
+    SetOptions,
+};
+use llm_config_storage::StorageError;
+use std::collections::BTreeSet;
+
+impl ConfigManager {
+    /// Copy the configs of `src` in `envs` (every environment if empty) to `dst`
+    pub fn clone_namespace(&self, src: &str, dst: &str, envs: &[Environment]) -> Result<Vec<ConfigEntry>> {
+        self.clone_namespace_with_options(src, dst, envs, "system", &SetOptions::default())
+    }
+
+    /// Copy a namespace on behalf of a user
+    ///
+    /// Every target environment is checked against its protection level.
+    pub fn clone_namespace_with_options(
+        &self,
+        src: &str,
+        dst: &str,
+        envs: &[Environment],
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Vec<ConfigEntry>> {
+        for env in self.namespace_environments(src, envs)? {
+            self.check_write(env, user, options)?;
+        }
+        self.check_rebind(src)?;
+        let _writes = self.lock_writes()?;
+
+        let mut copies = self
+            .storage()
+            .clone_namespace(src, dst, envs)
+            .map_err(namespace_error)?;
+        self.rebind_copies(src, &mut copies)?;
+        if envs.is_empty() {
+            self.copy_merge_strategy(src, dst, false)?;
+            self.copy_namespace_parent(src, dst, false)?;
+        }
+        for copy in &copies {
+            self.publish(
+                ChangeEvent::stored(ChangeKind::Set, copy)
+                    .with_user(user)
+                    .with_context(options.context.clone()),
+            );
+        }
+        Ok(copies)
+    }
+
+    /// Move every config of `src` to `dst`
+    pub fn rename_namespace(&self, src: &str, dst: &str) -> Result<Vec<ConfigEntry>> {
+        self.rename_namespace_with_options(src, dst, "system", &SetOptions::default())
+    }
+
+    /// Move a namespace on behalf of a user
+    ///
+    /// Every environment the namespace has configs in is checked against its
+    /// protection level.
+    pub fn rename_namespace_with_options(
+        &self,
+        src: &str,
+        dst: &str,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Vec<ConfigEntry>> {
+        for env in self.namespace_environments(src, &[])? {
+            self.check_write(env, user, options)?;
+        }
+        self.check_rebind(src)?;
+        let _writes = self.lock_writes()?;
+
+        let mut moved = self.storage().rename_namespace(src, dst).map_err(namespace_error)?;
+        self.rebind_copies(src, &mut moved)?;
+        self.copy_merge_strategy(src, dst, true)?;
+        self.copy_namespace_parent(src, dst, true)?;
+        for entry in &moved {
+            let original = ConfigEntry {
+                namespace: src.to_string(),
+                ..entry.clone()
+            };
+            for event in [
+                ChangeEvent::deleted(&original),
+                ChangeEvent::stored(ChangeKind::Set, entry),
+            ] {
+                self.publish(event.with_user(user).with_context(options.context.clone()));
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Environments `namespace` has configs in, limited to `envs` unless empty
+    fn namespace_environments(&self, namespace: &str, envs: &[Environment]) -> Result<BTreeSet<Environment>> {
+        Ok(self
+            .list_all()?
+            .into_iter()
+            .filter(|entry| entry.namespace == namespace)
+            .map(|entry| entry.environment)
+            .filter(|env| envs.is_empty() || envs.contains(env))
+            .collect())
+    }
+
+    /// Give `dst` the default merge strategy of `src`
+    fn copy_merge_strategy(&self, src: &str, dst: &str, remove: bool) -> Result<()> {
+        let mut settings = self.merge_settings();
+        let strategy = if remove {
+            settings.namespaces.remove(src)
+        } else {
+            settings.namespaces.get(src).copied()
+        };
+        match strategy {
+            Some(strategy) => self.set_merge_settings(settings.with_namespace(dst, strategy)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Report a missing source or taken target namespace as such
+fn namespace_error(err: StorageError) -> ConfigError {
+    match err {
+        StorageError::NotFound(msg) => ConfigError::NotFound(msg),
+        StorageError::AlreadyExists(msg) => ConfigError::AlreadyExists(msg),
+        other => other.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigValue, EnvironmentProtection, MergeSettings, MergeStrategy, ProtectionLevel};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clone_and_rename_namespace() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        for env in [Environment::Staging, Environment::Production] {
+            manager
+                .set("team-a", "model", ConfigValue::String("gpt-4".into()), env, "alice")
+                .unwrap();
+        }
+        manager
+            .set("team-a", "model", ConfigValue::String("gpt-4o".into()), Environment::Staging, "alice")
+            .unwrap();
+        manager
+            .set_merge_settings(MergeSettings::new().with_namespace("team-a", MergeStrategy::DeepMerge))
+            .unwrap();
+
+        let copies = manager
+            .clone_namespace("team-a", "sandbox", &[Environment::Staging])
+            .unwrap();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(manager.get_history("sandbox", "model", Environment::Staging).unwrap().len(), 2);
+        assert!(manager.get("sandbox", "model", Environment::Production).unwrap().is_none());
+
+        let mut changes = manager.subscribe_changes();
+        let moved = manager.rename_namespace("team-a", "team-b").unwrap();
+        assert_eq!(moved.len(), 2);
+        assert!(manager.list("team-a", Environment::Staging).unwrap().is_empty());
+        let entry = manager.get("team-b", "model", Environment::Staging).unwrap().unwrap();
+        assert_eq!(entry.version, 2);
+        assert_eq!(manager.get_history("team-b", "model", Environment::Staging).unwrap().len(), 2);
+        assert_eq!(manager.merge_settings().strategy("team-b"), MergeStrategy::DeepMerge);
+        assert_eq!(manager.merge_settings().strategy("team-a"), MergeStrategy::Replace);
+        assert_eq!(changes.try_recv().unwrap().kind, ChangeKind::Delete);
+
+        assert!(matches!(
+            manager.rename_namespace("team-b", "sandbox"),
+            Err(ConfigError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            manager.rename_namespace("team-a", "team-c"),
+            Err(ConfigError::NotFound(_))
+        ));
+
+        // Protected environments need the usual review
+        manager
+            .set_protection(
+                EnvironmentProtection::new()
+                    .with_level(Environment::Production, ProtectionLevel::ReviewRequired),
+            )
+            .unwrap();
+        assert!(matches!(
+            manager.rename_namespace("team-b", "team-c"),
+            Err(ConfigError::Protected(_))
+        ));
+        let reviewed = SetOptions::default().with_reviewed_by("carol");
+        manager
+            .rename_namespace_with_options("team-b", "team-c", "bob", &reviewed)
+            .unwrap();
+    }
+}
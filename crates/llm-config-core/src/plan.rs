@@ -0,0 +1,403 @@
+//! Deterministic change plans for dry-run output
+//!
+//! A plan describes what an operation (diff, promote, import, ...) would
+//! change without applying it. Changes are kept sorted by namespace, key and
+//! environment, object values are rendered with sorted keys, and secrets are
+//! masked, so the JSON form of a plan is byte-for-byte stable and can be
+//! checked into golden files.
+//!
+//! JSON schema (`schema_version` 1):
+//!
+//! ```text
+//! {
+//!   "schema_version": 1,
+//!   "operation": string,
+//!   "summary": { "create": int, "update": int, "delete": int },
+//!   "changes": [
+//!     {
+//!       "namespace": string,
+//!       "key": string,
+//!       "environment": "base" | "development" | "staging" | "production" | "edge",
+//!       "action": "create" | "update" | "delete",
+//!       "old_value": any,   // omitted for "create"
+//!       "new_value": any    // omitted for "delete"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Values are plain JSON; secrets are rendered as the string `"<secret>"`.
+
+use crate::{ConfigEntry, ConfigError, ConfigValue, Environment, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Current version of the plan JSON schema
+pub const PLAN_SCHEMA_VERSION: u32 = 1;
+
+/// Placeholder used for secret values in plans
+pub const MASKED_SECRET: &str = "<secret>";
+
+/// Environment variable that makes [`Plan::assert_golden`] rewrite golden files
+pub const UPDATE_GOLDEN_ENV: &str = "LLM_CONFIG_UPDATE_GOLDEN";
+
+/// Kind of change a plan entry describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// A single planned change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub namespace: String,
+    pub key: String,
+    pub environment: Environment,
+    pub action: PlanAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_value: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_value: Option<serde_json::Value>,
+}
+
+impl PlannedChange {
+    /// A value that would be created
+    pub fn create(
+        namespace: impl Into<String>,
+        key: impl Into<String>,
+        environment: Environment,
+        value: &ConfigValue,
+    ) -> Self {
+        Self {
+            namespace: namespace.into(),
+            key: key.into(),
+            environment,
+            action: PlanAction::Create,
+            old_value: None,
+            new_value: Some(render_value(value)),
+        }
+    }
+
+    /// A value that would be replaced
+    pub fn update(
+        namespace: impl Into<String>,
+        key: impl Into<String>,
+        environment: Environment,
+        old_value: &ConfigValue,
+        new_value: &ConfigValue,
+    ) -> Self {
+        Self {
+            namespace: namespace.into(),
+            key: key.into(),
+            environment,
+            action: PlanAction::Update,
+            old_value: Some(render_value(old_value)),
+            new_value: Some(render_value(new_value)),
+        }
+    }
+
+    /// A value that would be deleted
+    pub fn delete(
+        namespace: impl Into<String>,
+        key: impl Into<String>,
+        environment: Environment,
+        old_value: &ConfigValue,
+    ) -> Self {
+        Self {
+            namespace: namespace.into(),
+            key: key.into(),
+            environment,
+            action: PlanAction::Delete,
+            old_value: Some(render_value(old_value)),
+            new_value: None,
+        }
+    }
+
+    /// The change replacing `before` with `after`, where a missing entry
+    /// means the config does not exist (`None` if neither does)
+    pub fn between(before: Option<&ConfigEntry>, after: Option<&ConfigEntry>) -> Option<Self> {
+        match (before, after) {
+            (None, Some(after)) => Some(Self::create(&after.namespace, &after.key, after.environment, &after.value)),
+            (Some(before), Some(after)) => Some(Self::update(
+                &after.namespace,
+                &after.key,
+                after.environment,
+                &before.value,
+                &after.value,
+            )),
+            (Some(before), None) => Some(Self::delete(&before.namespace, &before.key, before.environment, &before.value)),
+            (None, None) => None,
+        }
+    }
+
+    fn sort_key(&self) -> (&str, &str, Environment, PlanAction) {
+        (&self.namespace, &self.key, self.environment, self.action)
+    }
+}
+
+/// Number of changes per action
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanSummary {
+    pub create: usize,
+    pub update: usize,
+    pub delete: usize,
+}
+
+/// An ordered set of planned changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    pub schema_version: u32,
+    pub operation: String,
+    pub summary: PlanSummary,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl Plan {
+    /// Create an empty plan for an operation (e.g. "promote")
+    pub fn new(operation: impl Into<String>) -> Self {
+        Self {
+            schema_version: PLAN_SCHEMA_VERSION,
+            operation: operation.into(),
+            summary: PlanSummary::default(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Add a change, keeping changes in stable order
+    pub fn push(&mut self, change: PlannedChange) {
+        match change.action {
+            PlanAction::Create => self.summary.create += 1,
+            PlanAction::Update => self.summary.update += 1,
+            PlanAction::Delete => self.summary.delete += 1,
+        }
+
+        let idx = self
+            .changes
+            .partition_point(|c| c.sort_key() <= change.sort_key());
+        self.changes.insert(idx, change);
+    }
+
+    /// Add a change (builder style)
+    pub fn with_change(mut self, change: PlannedChange) -> Self {
+        self.push(change);
+        self
+    }
+
+    /// Check if the plan has no changes
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Render the plan as stable, pretty-printed JSON (with trailing newline)
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("plans are always serializable");
+        json.push('\n');
+        json
+    }
+
+    /// Parse a plan from its JSON form
+    pub fn from_json(json: &str) -> Result<Self> {
+        let plan: Plan = serde_json::from_str(json)
+            .map_err(|e| ConfigError::ValidationError(format!("Invalid plan: {}", e)))?;
+
+        if plan.schema_version != PLAN_SCHEMA_VERSION {
+            return Err(ConfigError::ValidationError(format!(
+                "Unsupported plan schema version {}",
+                plan.schema_version
+            )));
+        }
+
+        Ok(plan)
+    }
+
+    /// Assert that the plan matches a golden file, for CI snapshot tests
+    ///
+    /// Set `LLM_CONFIG_UPDATE_GOLDEN=1` to (re)write the golden file instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the golden file is missing or differs from the plan.
+    pub fn assert_golden(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual = self.to_json();
+
+        if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).expect("failed to create golden file directory");
+            }
+            std::fs::write(path, &actual).expect("failed to write golden file");
+            return;
+        }
+
+        let expected = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            panic!(
+                "failed to read golden file {} ({}); set {}=1 to create it",
+                path.display(),
+                e,
+                UPDATE_GOLDEN_ENV
+            )
+        });
+
+        assert!(
+            expected == actual,
+            "plan does not match golden file {}\n--- expected\n{}--- actual\n{}",
+            path.display(),
+            expected,
+            actual
+        );
+    }
+}
+
+/// Render a config value as plain, deterministic JSON
+pub fn render_value(value: &ConfigValue) -> serde_json::Value {
+    match value {
+        ConfigValue::String(s) => serde_json::Value::String(s.clone()),
+        ConfigValue::Integer(i) => serde_json::Value::from(*i),
+        ConfigValue::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        ConfigValue::Boolean(b) => serde_json::Value::Bool(*b),
+        ConfigValue::Array(items) => items.iter().map(render_value).collect(),
+        // serde_json::Map keeps its keys sorted
+        ConfigValue::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render_value(v)))
+                .collect(),
+        ),
+        ConfigValue::Duration(_) | ConfigValue::Size(_) | ConfigValue::Url(_) => {
+            serde_json::Value::String(value.typed_text().unwrap_or_default())
+        }
+        ConfigValue::Secret(_) => serde_json::Value::String(MASKED_SECRET.to_string()),
+    }
+}
+
+/// Convert plain JSON into a config value, the inverse of [`render_value`]
+///
+/// Whole numbers become integers; `null` becomes an empty string.
+pub fn value_from_json(value: &serde_json::Value) -> ConfigValue {
+    match value {
+        serde_json::Value::String(s) => ConfigValue::String(s.clone()),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => ConfigValue::Integer(i),
+            None => ConfigValue::Float(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::Bool(b) => ConfigValue::Boolean(*b),
+        serde_json::Value::Array(items) => {
+            ConfigValue::Array(items.iter().map(value_from_json).collect())
+        }
+        serde_json::Value::Object(map) => ConfigValue::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), value_from_json(v)))
+                .collect(),
+        ),
+        serde_json::Value::Null => ConfigValue::String(String::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn sample_plan(reverse: bool) -> Plan {
+        let mut changes = vec![
+            PlannedChange::create(
+                "app",
+                "model",
+                Environment::Production,
+                &ConfigValue::String("gpt-4".to_string()),
+            ),
+            PlannedChange::update(
+                "app",
+                "model",
+                Environment::Development,
+                &ConfigValue::String("gpt-3.5".to_string()),
+                &ConfigValue::String("gpt-4".to_string()),
+            ),
+            PlannedChange::delete("app", "legacy", Environment::Base, &ConfigValue::Boolean(true)),
+        ];
+        if reverse {
+            changes.reverse();
+        }
+
+        changes
+            .into_iter()
+            .fold(Plan::new("promote"), Plan::with_change)
+    }
+
+    #[test]
+    fn test_order_is_stable() {
+        let plan = sample_plan(false);
+        assert_eq!(plan, sample_plan(true));
+        assert_eq!(plan.to_json(), sample_plan(true).to_json());
+
+        let order: Vec<_> = plan
+            .changes
+            .iter()
+            .map(|c| (c.key.as_str(), c.environment))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("legacy", Environment::Base),
+                ("model", Environment::Development),
+                ("model", Environment::Production),
+            ]
+        );
+        assert_eq!(
+            plan.summary,
+            PlanSummary {
+                create: 1,
+                update: 1,
+                delete: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let plan = sample_plan(false);
+        let json = plan.to_json();
+        assert!(json.contains("\"schema_version\": 1"));
+        assert!(json.contains("\"action\": \"update\""));
+        assert_eq!(Plan::from_json(&json).unwrap(), plan);
+
+        let unsupported = json.replace("\"schema_version\": 1", "\"schema_version\": 99");
+        assert!(Plan::from_json(&unsupported).is_err());
+    }
+
+    #[test]
+    fn test_render_value_is_deterministic() {
+        let mut object = HashMap::new();
+        for key in ["zeta", "alpha", "mid"] {
+            object.insert(key.to_string(), ConfigValue::Integer(1));
+        }
+        let rendered = render_value(&ConfigValue::Object(object)).to_string();
+        assert_eq!(rendered, r#"{"alpha":1,"mid":1,"zeta":1}"#);
+
+        let json = serde_json::json!({ "tags": ["a", true], "max": 1024, "temp": 0.7 });
+        assert_eq!(render_value(&value_from_json(&json)), json);
+    }
+
+    #[test]
+    fn test_assert_golden() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("promote.json");
+        std::fs::write(&path, sample_plan(true).to_json()).unwrap();
+
+        sample_plan(false).assert_golden(&path);
+
+        let changed = sample_plan(false).with_change(PlannedChange::create(
+            "app",
+            "temperature",
+            Environment::Base,
+            &ConfigValue::Float(0.7),
+        ));
+        let result = std::panic::catch_unwind(|| changed.assert_golden(&path));
+        assert!(result.is_err());
+    }
+}
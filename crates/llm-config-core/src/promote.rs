@@ -0,0 +1,283 @@
+//! Promoting configurations from one environment to another
+//!
+//! [`ConfigManager::promote`] copies the values of a namespace stored in one
+//! environment (e.g. staging) into another (e.g. production). Only keys whose
+//! value differs are written; keys that exist only in the target are left
+//! alone. Secrets are decrypted and encrypted again, so the target gets its
+//! own ciphertext.
+//!
+//! A promotion is checked as a whole before anything is written: the target's
+//! protection level, type changes and secret decryption either pass for every
+//! key or nothing is stored. [`ConfigManager::plan_promotion`] returns the
+//! same changes as a [`Plan`] without applying them, for `--dry-run`.
+
+use crate::plan::render_value;
+use crate::{ConfigError, ConfigManager, ConfigValue, Environment, Plan, PlannedChange, Result, SetOptions};
+
+/// Operation name of promotion plans
+pub const PROMOTE_OPERATION: &str = "promote";
+
+/// Keys of a namespace to promote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromoteKeys {
+    /// Every key stored in the source environment
+    All,
+    /// Only these keys (each must exist in the source environment)
+    Only(Vec<String>),
+}
+
+/// A key to be written by a promotion
+struct Promotion {
+    key: String,
+    /// Source value with secrets decrypted
+    revealed: ConfigValue,
+    is_secret: bool,
+    change: PlannedChange,
+}
+
+impl ConfigManager {
+    /// Changes promoting `keys` of `namespace` from `from` to `to` would make
+    pub fn plan_promotion(
+        &self,
+        namespace: &str,
+        keys: &PromoteKeys,
+        from: Environment,
+        to: Environment,
+    ) -> Result<Plan> {
+        let promotions = self.promotions(namespace, keys, from, to)?;
+        Ok(promotions
+            .into_iter()
+            .fold(Plan::new(PROMOTE_OPERATION), |plan, promotion| {
+                plan.with_change(promotion.change)
+            }))
+    }
+
+    /// Copy `keys` of `namespace` from `from` to `to`, returning the applied changes
+    pub fn promote(
+        &self,
+        namespace: &str,
+        keys: &PromoteKeys,
+        from: Environment,
+        to: Environment,
+        user: &str,
+    ) -> Result<Plan> {
+        self.promote_with_options(namespace, keys, from, to, user, &SetOptions::default())
+    }
+
+    /// Promote with explicit options
+    ///
+    /// The options apply to every written key; versions are described as
+    /// "Promoted from `<from>`" unless a change description is given.
+    pub fn promote_with_options(
+        &self,
+        namespace: &str,
+        keys: &PromoteKeys,
+        from: Environment,
+        to: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Plan> {
+        self.check_write(to, user, options)?;
+        let _writes = self.lock_writes()?;
+
+        let mut options = options.clone();
+        options
+            .change_description
+            .get_or_insert_with(|| format!("Promoted from {}", from));
+
+        // Check and encrypt everything before the first write
+        let mut writes = Vec::new();
+        for promotion in self.promotions(namespace, keys, from, to)? {
+            let existing = self.storage().get(namespace, &promotion.key, to)?;
+            let value = if promotion.is_secret {
+                let ConfigValue::String(ref plaintext) = promotion.revealed else {
+                    unreachable!("revealed secrets are strings");
+                };
+                self.encrypt_secret(namespace, &promotion.key, to, plaintext.as_bytes())?
+            } else {
+                promotion.revealed
+            };
+            Self::check_replace(namespace, &promotion.key, existing.as_ref(), &value, &options)?;
+            writes.push((promotion.key, existing, value, promotion.change));
+        }
+
+        let mut plan = Plan::new(PROMOTE_OPERATION);
+        for (key, existing, value, change) in writes {
+            self.store(namespace.to_string(), key, existing, value, to, user.to_string(), &options)?;
+            plan.push(change);
+        }
+        Ok(plan)
+    }
+
+    /// Keys whose value in `to` differs from the one in `from`
+    fn promotions(
+        &self,
+        namespace: &str,
+        keys: &PromoteKeys,
+        from: Environment,
+        to: Environment,
+    ) -> Result<Vec<Promotion>> {
+        if from == to {
+            return Err(ConfigError::ValidationError(format!(
+                "Cannot promote {} to itself",
+                from
+            )));
+        }
+
+        let sources = match keys {
+            PromoteKeys::All => self.list(namespace, from)?,
+            PromoteKeys::Only(keys) => keys
+                .iter()
+                .map(|key| {
+                    self.storage().get(namespace, key, from)?.ok_or_else(|| {
+                        ConfigError::NotFound(format!("{}:{} in {}", namespace, key, from))
+                    })
+                })
+                .collect::<Result<_>>()?,
+        };
+
+        let mut promotions = Vec::new();
+        for source in sources {
+            self.check_binding(&source)?;
+            let is_secret = matches!(source.value, ConfigValue::Secret(_));
+            let revealed = self.reveal(source.value.clone())?;
+            let change = match self.storage().get(namespace, &source.key, to)? {
+                None => PlannedChange::create(namespace, &source.key, to, &source.value),
+                Some(target) => {
+                    self.check_binding(&target)?;
+                    let target_secret = matches!(target.value, ConfigValue::Secret(_));
+                    let target_revealed = self.reveal(target.value.clone())?;
+                    if target_secret == is_secret
+                        && render_value(&target_revealed) == render_value(&revealed)
+                    {
+                        continue;
+                    }
+                    PlannedChange::update(namespace, &source.key, to, &target.value, &source.value)
+                }
+            };
+            promotions.push(Promotion {
+                key: source.key,
+                revealed,
+                is_secret,
+                change,
+            });
+        }
+        Ok(promotions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::MASKED_SECRET;
+    use crate::{EnvironmentProtection, PlanAction, ProtectionLevel};
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use tempfile::TempDir;
+
+    fn manager(temp_dir: &TempDir) -> ConfigManager {
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap().with_encryption_key(key);
+        let staging = Environment::Staging;
+        manager
+            .set("app", "model", ConfigValue::String("gpt-4".into()), staging, "alice")
+            .unwrap();
+        manager
+            .set("app", "temperature", ConfigValue::Float(0.2), staging, "alice")
+            .unwrap();
+        manager.set_secret("app", "api_key", "sk-new", staging, "alice").unwrap();
+        manager
+            .set("app", "model", ConfigValue::String("gpt-4".into()), Environment::Production, "alice")
+            .unwrap();
+        manager
+            .set("app", "temperature", ConfigValue::Float(0.7), Environment::Production, "alice")
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_promote() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        let (staging, production) = (Environment::Staging, Environment::Production);
+
+        let plan = manager
+            .plan_promotion("app", &PromoteKeys::All, staging, production)
+            .unwrap();
+        let changes: Vec<_> = plan.changes.iter().map(|c| (c.key.as_str(), c.action)).collect();
+        assert_eq!(
+            changes,
+            vec![("api_key", PlanAction::Create), ("temperature", PlanAction::Update)]
+        );
+        assert_eq!(plan.changes[0].new_value, Some(serde_json::json!(MASKED_SECRET)));
+        // Planning writes nothing
+        assert!(manager.get("app", "api_key", production).unwrap().is_none());
+
+        let applied = manager
+            .promote("app", &PromoteKeys::All, staging, production, "bob")
+            .unwrap();
+        assert_eq!(applied, plan);
+        assert_eq!(
+            manager.get_secret("app", "api_key", production).unwrap(),
+            Some(b"sk-new".to_vec())
+        );
+        let temperature = manager.get("app", "temperature", production).unwrap().unwrap();
+        assert_eq!(temperature.value.as_f64(), Some(0.2));
+        assert_eq!(temperature.metadata.updated_by, "bob");
+        let history = manager.get_history("app", "temperature", production).unwrap();
+        let promoted = history.iter().find(|v| v.version == temperature.version).unwrap();
+        assert_eq!(promoted.change_description.as_deref(), Some("Promoted from staging"));
+
+        // Secrets get their own ciphertext
+        let (ConfigValue::Secret(source), ConfigValue::Secret(target)) = (
+            manager.get("app", "api_key", staging).unwrap().unwrap().value,
+            manager.get("app", "api_key", production).unwrap().unwrap().value,
+        ) else {
+            panic!("expected secrets");
+        };
+        assert_ne!(source.nonce, target.nonce);
+
+        // Nothing left to promote
+        assert!(manager
+            .plan_promotion("app", &PromoteKeys::All, staging, production)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_promote_is_all_or_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        let (staging, production) = (Environment::Staging, Environment::Production);
+
+        // A type change on one key stops the whole promotion
+        let retype = SetOptions::default().with_allow_type_change(true);
+        manager
+            .set_with_options("app", "model", ConfigValue::Integer(4), staging, "alice", &retype)
+            .unwrap();
+        let result = manager.promote("app", &PromoteKeys::All, staging, production, "bob");
+        assert!(matches!(result, Err(ConfigError::TypeChange(_))));
+        assert!(manager.get("app", "api_key", production).unwrap().is_none());
+
+        let only = PromoteKeys::Only(vec!["temperature".to_string()]);
+        manager
+            .set_protection(
+                EnvironmentProtection::new().with_level(production, ProtectionLevel::ReviewRequired),
+            )
+            .unwrap();
+        assert!(manager.promote("app", &only, staging, production, "bob").is_err());
+        let options = SetOptions::default().with_reviewed_by("carol");
+        let plan = manager
+            .promote_with_options("app", &only, staging, production, "bob", &options)
+            .unwrap();
+        assert_eq!(plan.summary.update, 1);
+
+        let missing = PromoteKeys::Only(vec!["missing".to_string()]);
+        assert!(matches!(
+            manager.plan_promotion("app", &missing, staging, production),
+            Err(ConfigError::NotFound(_))
+        ));
+        assert!(manager
+            .plan_promotion("app", &PromoteKeys::All, staging, staging)
+            .is_err());
+    }
+}
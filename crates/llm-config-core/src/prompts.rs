@@ -0,0 +1,327 @@
+//! Versioned prompt templates
+//!
+//! Prompt templates are stored as config entries in the reserved
+//! [`TEMPLATE_NAMESPACE`], one key per template, so they share the version
+//! history, rollback, environment protection and change events of ordinary
+//! configs. Lookups follow the [environment graph](crate::EnvironmentGraph):
+//! a template saved in `base` renders in every environment until a more
+//! specific environment overrides it.
+//!
+//! Templates may reference stored values, e.g. `{{config:org/app.timeout}}` or
+//! `{{secret:org/app.api_key}}`. The manager resolves them with environment
+//! overrides applied; callers that enforce access control pass their own
+//! [`ConfigSource`] to [`TemplateRegistry::render_with_source`].
+
+use crate::plan::render_value;
+use crate::{ConfigEntry, ConfigError, ConfigManager, ConfigValue, Environment, Result, SetOptions};
+use chrono::{DateTime, Utc};
+use llm_config_templates::{ConfigSource, Reference, ReferenceKind, Template, TemplateError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Namespace prompt templates are stored in
+pub const TEMPLATE_NAMESPACE: &str = "_templates";
+
+/// A template together with the entry it is stored in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredTemplate {
+    pub template: Template,
+    /// Environment the template was found in
+    pub environment: Environment,
+    pub version: u64,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: String,
+}
+
+impl TryFrom<ConfigEntry> for StoredTemplate {
+    type Error = ConfigError;
+
+    fn try_from(entry: ConfigEntry) -> Result<Self> {
+        let invalid = |reason: &str| {
+            ConfigError::ValidationError(format!("Invalid template {}: {}", entry.key, reason))
+        };
+
+        let ConfigValue::Object(fields) = &entry.value else {
+            return Err(invalid("not an object"));
+        };
+        let content = fields
+            .get("content")
+            .and_then(ConfigValue::as_str)
+            .ok_or_else(|| invalid("missing content"))?;
+
+        let mut template = Template::new(entry.key.clone(), content)?;
+        if let Some(description) = fields.get("description").and_then(ConfigValue::as_str) {
+            template = template.with_description(description);
+        }
+        if let Some(ConfigValue::Object(defaults)) = fields.get("defaults") {
+            for (var, value) in defaults {
+                let value = value.as_str().ok_or_else(|| invalid("defaults must be strings"))?;
+                template = template.with_default(var.clone(), value);
+            }
+        }
+
+        Ok(Self {
+            template,
+            environment: entry.environment,
+            version: entry.version,
+            updated_at: entry.metadata.updated_at,
+            updated_by: entry.metadata.updated_by,
+        })
+    }
+}
+
+/// Output of rendering a stored template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderedTemplate {
+    pub name: String,
+    /// Environment the template was found in
+    pub environment: Environment,
+    pub version: u64,
+    pub output: String,
+}
+
+/// Prompt template registry backed by a [`ConfigManager`]
+pub struct TemplateRegistry<'a> {
+    manager: &'a ConfigManager,
+}
+
+impl<'a> TemplateRegistry<'a> {
+    /// Create a registry over a manager's storage
+    pub fn new(manager: &'a ConfigManager) -> Self {
+        Self { manager }
+    }
+
+    /// Save a new version of a template on behalf of a user
+    pub fn put(
+        &self,
+        template: &Template,
+        env: Environment,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<StoredTemplate> {
+        let mut fields = HashMap::new();
+        fields.insert("content".to_string(), ConfigValue::String(template.content.clone()));
+        if let Some(description) = &template.description {
+            fields.insert("description".to_string(), ConfigValue::String(description.clone()));
+        }
+        let defaults = template
+            .defaults
+            .iter()
+            .map(|(var, value)| (var.clone(), ConfigValue::String(value.clone())))
+            .collect();
+        fields.insert("defaults".to_string(), ConfigValue::Object(defaults));
+
+        let entry = self.manager.set_with_options(
+            TEMPLATE_NAMESPACE,
+            template.name.clone(),
+            ConfigValue::Object(fields),
+            env,
+            user,
+            options,
+        )?;
+        entry.try_into()
+    }
+
+    /// Get the template that applies to an environment
+    pub fn get(&self, name: &str, env: Environment) -> Result<Option<StoredTemplate>> {
+        for env in self.manager.environment_graph().chain(env).into_iter().rev() {
+            if let Some(entry) = self.manager.get(TEMPLATE_NAMESPACE, name, env)? {
+                return entry.try_into().map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// All templates that apply to an environment, sorted by name
+    pub fn list(&self, env: Environment) -> Result<Vec<StoredTemplate>> {
+        self.manager
+            .resolve_namespace(TEMPLATE_NAMESPACE, env)?
+            .into_keys()
+            .filter_map(|name| self.get(&name, env).transpose())
+            .collect()
+    }
+
+    /// Render the template that applies to an environment
+    ///
+    /// References to stored values are resolved through the manager without
+    /// access checks.
+    pub fn render(
+        &self,
+        name: &str,
+        env: Environment,
+        vars: &HashMap<String, String>,
+    ) -> Result<RenderedTemplate> {
+        self.render_with_source(name, env, vars, self.manager)
+    }
+
+    /// Render the template that applies to an environment, resolving
+    /// references to stored values through `source`
+    pub fn render_with_source<S>(
+        &self,
+        name: &str,
+        env: Environment,
+        vars: &HashMap<String, String>,
+        source: &S,
+    ) -> Result<RenderedTemplate>
+    where
+        S: ConfigSource<Environment = Environment>,
+    {
+        let stored = self
+            .get(name, env)?
+            .ok_or_else(|| ConfigError::NotFound(format!("Template not found: {}", name)))?;
+
+        Ok(RenderedTemplate {
+            output: stored.template.render_with_source(vars, env, source)?,
+            name: stored.template.name,
+            environment: stored.environment,
+            version: stored.version,
+        })
+    }
+}
+
+impl ConfigSource for ConfigManager {
+    type Environment = Environment;
+
+    /// Resolve a reference with environment overrides applied
+    ///
+    /// Strings render as-is and other values as JSON with sorted object keys. Secrets must be
+    /// referenced as `secret:` and render as their plaintext.
+    fn resolve(
+        &self,
+        reference: &Reference,
+        env: Environment,
+    ) -> llm_config_templates::Result<Option<String>> {
+        let unresolved =
+            |e: ConfigError| TemplateError::UnresolvedReference(format!("{}: {}", reference, e));
+
+        let Some(value) = self
+            .get_with_overrides(&reference.namespace, &reference.key, env)
+            .map_err(unresolved)?
+        else {
+            return Ok(None);
+        };
+
+        if value.is_secret() && reference.kind == ReferenceKind::Config {
+            return Err(TemplateError::AccessDenied(format!(
+                "{} is a secret; reference it as secret:{}.{}",
+                reference, reference.namespace, reference.key
+            )));
+        }
+
+        match self.reveal(value).map_err(unresolved)? {
+            ConfigValue::String(s) => Ok(Some(s)),
+            other => Ok(Some(other.typed_text().unwrap_or_else(|| render_value(&other).to_string()))),
+        }
+    }
+}
+
+impl ConfigManager {
+    /// Prompt templates stored by this manager
+    pub fn templates(&self) -> TemplateRegistry<'_> {
+        TemplateRegistry::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_crypto::{Algorithm, SecretKey};
+    use llm_config_templates::TemplateEngine;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_put_render_and_rollback() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let templates = manager.templates();
+        let options = SetOptions::default();
+
+        let template = Template::new("summarize", "Summarize for {{audience}}: {{text}}")
+            .unwrap()
+            .with_description("Summary prompt")
+            .with_default("audience", "engineers");
+        templates.put(&template, Environment::Base, "alice", &options).unwrap();
+
+        let template = Template::new("summarize", "Summarize briefly for {{audience}}: {{text}}")
+            .unwrap()
+            .with_default("audience", "engineers");
+        let stored = templates.put(&template, Environment::Base, "bob", &options).unwrap();
+        assert_eq!(stored.version, 2);
+
+        // Base templates apply to every environment
+        let mut vars = HashMap::new();
+        vars.insert("text".to_string(), "release notes".to_string());
+        let rendered = templates.render("summarize", Environment::Production, &vars).unwrap();
+        assert_eq!(rendered.output, "Summarize briefly for engineers: release notes");
+        assert_eq!(rendered.environment, Environment::Base);
+        assert_eq!(rendered.version, 2);
+
+        // Templates follow the config rollback workflow
+        manager
+            .rollback_with_options(TEMPLATE_NAMESPACE, "summarize", Environment::Base, 1, "alice", &options)
+            .unwrap();
+        let stored = templates.get("summarize", Environment::Development).unwrap().unwrap();
+        assert_eq!(stored.template.description.as_deref(), Some("Summary prompt"));
+        assert_eq!(
+            templates.render("summarize", Environment::Base, &vars).unwrap().output,
+            "Summarize for engineers: release notes"
+        );
+
+        assert_eq!(templates.list(Environment::Edge).unwrap().len(), 1);
+        assert!(matches!(
+            templates.render("summarize", Environment::Base, &HashMap::new()),
+            Err(ConfigError::TemplateError(_))
+        ));
+        assert!(matches!(
+            templates.render("missing", Environment::Base, &vars),
+            Err(ConfigError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption_key(key);
+        let options = SetOptions::default();
+
+        manager
+            .set("org/db", "host", ConfigValue::String("localhost".into()), Environment::Base, "alice")
+            .unwrap();
+        manager
+            .set("org/db", "host", ConfigValue::String("db.prod".into()), Environment::Production, "alice")
+            .unwrap();
+        manager
+            .set("org/db", "port", ConfigValue::Integer(5432), Environment::Base, "alice")
+            .unwrap();
+        manager
+            .set_secret("org/db", "password", b"hunter2", Environment::Base, "alice")
+            .unwrap();
+
+        let template = Template::new(
+            "dsn",
+            "postgres://{{config:org/db.host}}:{{config:org/db.port}}?password={{secret:org/db.password}}",
+        )
+        .unwrap();
+        manager.templates().put(&template, Environment::Base, "alice", &options).unwrap();
+
+        let rendered = manager
+            .templates()
+            .render("dsn", Environment::Production, &HashMap::new())
+            .unwrap();
+        assert_eq!(rendered.output, "postgres://db.prod:5432?password=hunter2");
+
+        // Secrets cannot be read through a config reference
+        let engine = TemplateEngine::new();
+        assert!(matches!(
+            engine.render_with_manager("{{config:org/db.password}}", Environment::Base, &manager),
+            Err(TemplateError::AccessDenied(_))
+        ));
+        assert!(matches!(
+            engine.render_with_manager("{{config:org/db.user}}", Environment::Base, &manager),
+            Err(TemplateError::UnresolvedReference(_))
+        ));
+    }
+}
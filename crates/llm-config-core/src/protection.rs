@@ -0,0 +1,160 @@
+//! Per-environment write protection
+//!
+//! Each environment has a [`ProtectionLevel`] that [`ConfigManager`] checks
+//! before every write (set, delete, rollback), independently of RBAC:
+//!
+//! - `open`: anyone may write
+//! - `review-required`: writes must name a reviewer other than the author
+//! - `frozen`: nobody may write
+//! - `admin-only`: only the configured admins (e.g. a deploy bot) may write
+//!
+//! [`ConfigManager`]: crate::ConfigManager
+
+use crate::{ConfigError, Environment, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Storage record kind and id the protection settings are persisted under
+pub(crate) const PROTECTION_RECORD: (&str, &str) = ("settings", "protection");
+
+/// Write protection level of an environment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProtectionLevel {
+    /// Anyone may write
+    #[default]
+    Open,
+    /// Writes must be reviewed by someone other than the author
+    ReviewRequired,
+    /// Nobody may write
+    Frozen,
+    /// Only protection admins may write
+    AdminOnly,
+}
+
+impl std::str::FromStr for ProtectionLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "open" => Ok(Self::Open),
+            "review-required" | "review" => Ok(Self::ReviewRequired),
+            "frozen" => Ok(Self::Frozen),
+            "admin-only" | "admin" => Ok(Self::AdminOnly),
+            _ => Err(format!("Unknown protection level: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for ProtectionLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::ReviewRequired => write!(f, "review-required"),
+            Self::Frozen => write!(f, "frozen"),
+            Self::AdminOnly => write!(f, "admin-only"),
+        }
+    }
+}
+
+/// Protection levels of all environments
+///
+/// Environments without an explicit level are open.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentProtection {
+    #[serde(default)]
+    pub levels: BTreeMap<Environment, ProtectionLevel>,
+    /// Principals allowed to write to admin-only environments
+    #[serde(default)]
+    pub admins: BTreeSet<String>,
+}
+
+impl EnvironmentProtection {
+    /// Create settings with every environment open
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the protection level of an environment
+    pub fn with_level(mut self, env: Environment, level: ProtectionLevel) -> Self {
+        self.levels.insert(env, level);
+        self
+    }
+
+    /// Allow a principal to write to admin-only environments
+    pub fn with_admin(mut self, user: impl Into<String>) -> Self {
+        self.admins.insert(user.into());
+        self
+    }
+
+    /// Protection level of an environment
+    pub fn level(&self, env: Environment) -> ProtectionLevel {
+        self.levels.get(&env).copied().unwrap_or_default()
+    }
+
+    /// Check whether `user` may write to `env`
+    pub fn check_write(&self, env: Environment, user: &str, reviewed_by: Option<&str>) -> Result<()> {
+        let denied = |reason: &str| {
+            Err(ConfigError::Protected(format!(
+                "{} is {}: {}",
+                env,
+                self.level(env),
+                reason
+            )))
+        };
+
+        match self.level(env) {
+            ProtectionLevel::Open => Ok(()),
+            ProtectionLevel::ReviewRequired => match reviewed_by {
+                Some(reviewer) if !reviewer.is_empty() && reviewer != user => Ok(()),
+                Some(_) => denied("writes must be reviewed by someone other than the author"),
+                None => denied("writes must name a reviewer"),
+            },
+            ProtectionLevel::Frozen => denied("writes are not allowed"),
+            ProtectionLevel::AdminOnly if self.admins.contains(user) => Ok(()),
+            ProtectionLevel::AdminOnly => denied(&format!("{} is not a protection admin", user)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protection() -> EnvironmentProtection {
+        EnvironmentProtection::new()
+            .with_level(Environment::Staging, ProtectionLevel::ReviewRequired)
+            .with_level(Environment::Production, ProtectionLevel::AdminOnly)
+            .with_level(Environment::Edge, ProtectionLevel::Frozen)
+            .with_admin("deploy-bot")
+    }
+
+    #[test]
+    fn test_check_write() {
+        let protection = protection();
+
+        assert!(protection.check_write(Environment::Development, "alice", None).is_ok());
+
+        assert!(protection.check_write(Environment::Staging, "alice", None).is_err());
+        assert!(protection.check_write(Environment::Staging, "alice", Some("alice")).is_err());
+        assert!(protection.check_write(Environment::Staging, "alice", Some("bob")).is_ok());
+
+        assert!(protection.check_write(Environment::Production, "alice", Some("bob")).is_err());
+        assert!(protection.check_write(Environment::Production, "deploy-bot", None).is_ok());
+
+        let err = protection.check_write(Environment::Edge, "deploy-bot", None).unwrap_err();
+        assert!(matches!(err, ConfigError::Protected(_)));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let protection = protection();
+        let json = serde_json::to_string(&protection).unwrap();
+        assert!(json.contains("\"production\":\"admin-only\""));
+
+        let parsed: EnvironmentProtection = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, protection);
+        assert_eq!(parsed.level(Environment::Base), ProtectionLevel::Open);
+        assert_eq!("review_required".parse::<ProtectionLevel>().unwrap(), ProtectionLevel::ReviewRequired);
+    }
+}
@@ -0,0 +1,212 @@
+//! Point-in-time restore of a namespace
+//!
+//! [`ConfigManager::restore_namespace_at`] reconstructs the values a
+//! namespace held in one environment at a given time from the version
+//! history and writes them back as new versions, so the restore itself can
+//! be rolled back. Keys created after that time are deleted; their history
+//! is kept.
+//!
+//! Deletions are not recorded in the history, so a key deleted before the
+//! restore point comes back with its last value.
+
+use crate::{
+    ChangeEvent, ConfigEntry, ConfigManager, ConfigValue, Environment, Plan, PlannedChange, Result,
+    SetOptions, VersionEntry,
+};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// Operation name of restore plans
+pub const RESTORE_OPERATION: &str = "restore";
+
+/// A key whose current value differs from the one at the restore point
+struct Restoration {
+    key: String,
+    current: Option<ConfigEntry>,
+    /// Value at the restore point (`None` if the key did not exist yet)
+    restored: Option<ConfigValue>,
+    change: PlannedChange,
+}
+
+impl ConfigManager {
+    /// Changes restoring `namespace` in `env` to its state at `at` would make
+    pub fn plan_namespace_restore(&self, namespace: &str, env: Environment, at: DateTime<Utc>) -> Result<Plan> {
+        Ok(self
+            .restorations(namespace, env, at)?
+            .into_iter()
+            .fold(Plan::new(RESTORE_OPERATION), |plan, restoration| {
+                plan.with_change(restoration.change)
+            }))
+    }
+
+    /// Restore `namespace` in `env` to its state at `at`, returning the applied changes
+    pub fn restore_namespace_at(&self, namespace: &str, env: Environment, at: DateTime<Utc>) -> Result<Plan> {
+        self.restore_namespace_at_with_options(namespace, env, at, "system", &SetOptions::default())
+    }
+
+    /// Restore a namespace on behalf of a user
+    ///
+    /// Values may change type back to the one they had. Versions are
+    /// described as "Restored to state at `<at>`" unless a change description
+    /// is given.
+    pub fn restore_namespace_at_with_options(
+        &self,
+        namespace: &str,
+        env: Environment,
+        at: DateTime<Utc>,
+        user: &str,
+        options: &SetOptions,
+    ) -> Result<Plan> {
+        self.check_write(env, user, options)?;
+        let _writes = self.lock_writes()?;
+
+        let mut options = options.clone().with_allow_type_change(true);
+        options
+            .change_description
+            .get_or_insert_with(|| format!("Restored to state at {}", at.to_rfc3339()));
+
+        let mut plan = Plan::new(RESTORE_OPERATION);
+        for restoration in self.restorations(namespace, env, at)? {
+            match restoration.restored {
+                Some(value) => {
+                    Self::check_replace(namespace, &restoration.key, restoration.current.as_ref(), &value, &options)?;
+                    self.store(
+                        namespace.to_string(),
+                        restoration.key,
+                        restoration.current,
+                        value,
+                        env,
+                        user.to_string(),
+                        &options,
+                    )?;
+                }
+                None => {
+                    if let Some(current) = restoration.current {
+                        if self.storage().delete(namespace, &current.key, env)? {
+                            self.publish(
+                                ChangeEvent::deleted(&current)
+                                    .with_user(user)
+                                    .with_context(options.context.clone()),
+                            );
+                        }
+                    }
+                }
+            }
+            plan.push(restoration.change);
+        }
+        Ok(plan)
+    }
+
+    /// Keys whose current value differs from their value at `at`
+    fn restorations(&self, namespace: &str, env: Environment, at: DateTime<Utc>) -> Result<Vec<Restoration>> {
+        // Latest version of each key written up to `at`; keys whose history
+        // only starts later map to `None`
+        let mut at_time: BTreeMap<String, Option<VersionEntry>> = BTreeMap::new();
+        for version in self.storage().get_namespace_versions(namespace)? {
+            if version.environment != env {
+                continue;
+            }
+            let slot = at_time.entry(version.key.clone()).or_default();
+            let newer = slot.as_ref().map_or(true, |kept| version.created_at > kept.created_at);
+            if version.created_at <= at && newer {
+                *slot = Some(version);
+            }
+        }
+        for entry in self.storage().list(namespace, env)? {
+            at_time.entry(entry.key).or_default();
+        }
+
+        let mut restorations = Vec::new();
+        for (key, version) in at_time {
+            let current = self.storage().get(namespace, &key, env)?;
+            let restored = version.map(|version| version.value);
+            let change = match (&current, &restored) {
+                (None, None) => continue,
+                (Some(current), Some(restored)) if same_value(&current.value, restored) => continue,
+                (None, Some(restored)) => PlannedChange::create(namespace, &key, env, restored),
+                (Some(current), Some(restored)) => {
+                    PlannedChange::update(namespace, &key, env, &current.value, restored)
+                }
+                (Some(current), None) => PlannedChange::delete(namespace, &key, env, &current.value),
+            };
+            restorations.push(Restoration {
+                key,
+                current,
+                restored,
+                change,
+            });
+        }
+        Ok(restorations)
+    }
+}
+
+/// Compare two values by their serialized form (secrets by ciphertext)
+fn same_value(a: &ConfigValue, b: &ConfigValue) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigError, EnvironmentProtection, PlanAction, ProtectionLevel};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_restore_namespace_at() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        let env = Environment::Production;
+        let set = |key: &str, value: ConfigValue| {
+            manager
+                .set_with_options("app", key, value, env, "alice", &SetOptions::default().with_allow_type_change(true))
+                .unwrap();
+        };
+        set("model", ConfigValue::String("gpt-4".into()));
+        set("temperature", ConfigValue::Float(0.2));
+        set("retries", ConfigValue::Integer(3));
+        manager.delete("app", "retries", env).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let before_deploy = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        // The bad deploy
+        set("model", ConfigValue::Integer(4));
+        set("max_tokens", ConfigValue::Integer(100));
+        manager.set("other", "model", ConfigValue::Integer(1), env, "bob").unwrap();
+
+        let plan = manager.plan_namespace_restore("app", env, before_deploy).unwrap();
+        let changes: Vec<_> = plan.changes.iter().map(|c| (c.key.as_str(), c.action)).collect();
+        // Deletions are not in the history, so `retries` comes back
+        assert_eq!(
+            changes,
+            vec![
+                ("max_tokens", PlanAction::Delete),
+                ("model", PlanAction::Update),
+                ("retries", PlanAction::Create),
+            ]
+        );
+        assert!(manager.get("app", "max_tokens", env).unwrap().is_some());
+
+        let applied = manager.restore_namespace_at("app", env, before_deploy).unwrap();
+        assert_eq!(applied, plan);
+        let model = manager.get("app", "model", env).unwrap().unwrap();
+        assert_eq!(model.value.as_str(), Some("gpt-4"));
+        assert_eq!(model.version, 3);
+        let history = manager.get_history("app", "model", env).unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history[0].change_description.as_deref().unwrap().starts_with("Restored to state at"));
+        assert!(manager.get("app", "max_tokens", env).unwrap().is_none());
+        assert_eq!(manager.get_history("app", "max_tokens", env).unwrap().len(), 1);
+        assert!(manager.get("other", "model", env).unwrap().is_some());
+        assert!(manager.plan_namespace_restore("app", env, before_deploy).unwrap().is_empty());
+
+        // Protected environments need the usual review
+        manager
+            .set_protection(EnvironmentProtection::new().with_level(env, ProtectionLevel::ReviewRequired))
+            .unwrap();
+        assert!(matches!(
+            manager.restore_namespace_at("app", env, Utc::now()),
+            Err(ConfigError::Protected(_))
+        ));
+    }
+}
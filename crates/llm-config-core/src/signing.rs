@@ -0,0 +1,384 @@
+//! Config entry signing and provenance verification
+//!
+//! With an [`EntrySigner`], [`ConfigManager`] signs every entry it writes
+//! (set, rollback, metadata changes) with Ed25519 over the entry's canonical
+//! JSON: the entry without its signature, with object keys sorted. The
+//! signature, signer and key ID are kept in the entry's
+//! [`ConfigMetadata::signature`](crate::ConfigMetadata).
+//!
+//! Reads are checked against the manager's [`SignaturePolicy`]: entries signed
+//! with an unknown key or whose signature does not match fail with
+//! [`ConfigError::Signature`], as do unsigned entries if signatures are
+//! required. Use [`ConfigManager::verify_namespace`] to check a whole
+//! namespace without failing on the first bad entry.
+
+use crate::{ConfigEntry, ConfigError, ConfigManager, Environment, Result};
+use base64::Engine;
+use llm_config_crypto::{SigningKey, VerifyingKey};
+use llm_config_storage::EntrySignature;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Signing key of a writer together with the identity recorded in signatures
+#[derive(Debug, Clone)]
+pub struct EntrySigner {
+    signer: String,
+    key: SigningKey,
+    verifying_key: VerifyingKey,
+}
+
+impl EntrySigner {
+    /// Sign as `signer` (e.g. `ci-release`) with `key`
+    pub fn new(signer: impl Into<String>, key: SigningKey) -> Result<Self> {
+        let verifying_key = key.verifying_key()?;
+        Ok(Self {
+            signer: signer.into(),
+            key,
+            verifying_key,
+        })
+    }
+
+    /// Identity recorded in signatures
+    pub fn signer(&self) -> &str {
+        &self.signer
+    }
+
+    /// Public key that verifies the signatures
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    /// Sign an entry, replacing any previous signature
+    pub fn sign(&self, entry: &mut ConfigEntry) -> Result<()> {
+        entry.metadata.signature = None;
+        let signature = self.key.sign(&canonical_entry_json(entry)?)?;
+        entry.metadata.signature = Some(EntrySignature {
+            signer: self.signer.clone(),
+            key_id: self.verifying_key.key_id(),
+            signature: base64::engine::general_purpose::STANDARD.encode(signature),
+        });
+        Ok(())
+    }
+}
+
+/// Keys whose signatures are trusted, and whether entries must be signed
+#[derive(Debug, Clone, Default)]
+pub struct SignaturePolicy {
+    /// Trusted keys by key ID, with the identity they belong to
+    trusted: BTreeMap<String, (String, VerifyingKey)>,
+    require_signatures: bool,
+}
+
+impl SignaturePolicy {
+    /// Trust signatures made by `key` on behalf of `signer`
+    pub fn with_trusted_key(mut self, signer: impl Into<String>, key: VerifyingKey) -> Self {
+        self.trusted.insert(key.key_id(), (signer.into(), key));
+        self
+    }
+
+    /// Reject unsigned entries on read
+    pub fn with_required_signatures(mut self, required: bool) -> Self {
+        self.require_signatures = required;
+        self
+    }
+
+    /// Whether unsigned entries are rejected on read
+    pub fn requires_signatures(&self) -> bool {
+        self.require_signatures
+    }
+
+    /// Whether reads are checked at all
+    pub fn is_enabled(&self) -> bool {
+        self.require_signatures || !self.trusted.is_empty()
+    }
+
+    /// Check the signature of an entry
+    pub fn check(&self, entry: &ConfigEntry) -> SignatureStatus {
+        let Some(signature) = &entry.metadata.signature else {
+            return SignatureStatus::Unsigned;
+        };
+        let Some((signer, key)) = self.trusted.get(&signature.key_id) else {
+            return SignatureStatus::UnknownSigner {
+                signer: signature.signer.clone(),
+                key_id: signature.key_id.clone(),
+            };
+        };
+        let invalid = |reason: String| SignatureStatus::Invalid {
+            signer: signer.clone(),
+            reason,
+        };
+
+        let bytes = match base64::engine::general_purpose::STANDARD.decode(&signature.signature) {
+            Ok(bytes) => bytes,
+            Err(e) => return invalid(format!("malformed signature: {}", e)),
+        };
+        let mut unsigned = entry.clone();
+        unsigned.metadata.signature = None;
+        let verified = canonical_entry_json(&unsigned).and_then(|message| Ok(key.verify(&message, &bytes)?));
+        match verified {
+            Ok(()) => SignatureStatus::Valid { signer: signer.clone() },
+            Err(e) => invalid(e.to_string()),
+        }
+    }
+}
+
+/// Outcome of checking an entry's signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// Signed by a trusted key, and unchanged since
+    Valid { signer: String },
+    /// No signature
+    Unsigned,
+    /// Signed with a key that is not trusted
+    UnknownSigner { signer: String, key_id: String },
+    /// Signed with a trusted key, but the signature does not match the entry
+    Invalid { signer: String, reason: String },
+}
+
+impl SignatureStatus {
+    /// Whether the entry may be read under a policy that does (not) require signatures
+    pub fn is_acceptable(&self, require_signatures: bool) -> bool {
+        match self {
+            Self::Valid { .. } => true,
+            Self::Unsigned => !require_signatures,
+            Self::UnknownSigner { .. } | Self::Invalid { .. } => false,
+        }
+    }
+}
+
+impl std::fmt::Display for SignatureStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Valid { signer } => write!(f, "signed by {}", signer),
+            Self::Unsigned => write!(f, "unsigned"),
+            Self::UnknownSigner { signer, key_id } => {
+                write!(f, "signed by untrusted key {} ({})", key_id, signer)
+            }
+            Self::Invalid { signer, reason } => write!(f, "invalid signature of {}: {}", signer, reason),
+        }
+    }
+}
+
+/// Signature check of one entry of a namespace
+#[derive(Debug, Clone, Serialize)]
+pub struct SignatureCheck {
+    pub key: String,
+    pub environment: Environment,
+    pub version: u64,
+    #[serde(flatten)]
+    pub status: SignatureStatus,
+}
+
+/// Signature checks of every entry of a namespace
+#[derive(Debug, Clone, Serialize)]
+pub struct NamespaceVerification {
+    pub namespace: String,
+    /// Checks ordered by key and environment
+    pub entries: Vec<SignatureCheck>,
+}
+
+impl NamespaceVerification {
+    /// Entries without a signature
+    pub fn unsigned(&self) -> impl Iterator<Item = &SignatureCheck> {
+        self.entries
+            .iter()
+            .filter(|check| check.status == SignatureStatus::Unsigned)
+    }
+
+    /// Entries signed with an untrusted key or whose signature does not match
+    pub fn invalid(&self) -> impl Iterator<Item = &SignatureCheck> {
+        self.entries.iter().filter(|check| {
+            matches!(
+                check.status,
+                SignatureStatus::UnknownSigner { .. } | SignatureStatus::Invalid { .. }
+            )
+        })
+    }
+
+    /// Whether every entry carries a valid, trusted signature
+    pub fn is_verified(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|check| matches!(check.status, SignatureStatus::Valid { .. }))
+    }
+}
+
+/// Canonical JSON of an entry: compact, with object keys sorted
+pub fn canonical_entry_json(entry: &ConfigEntry) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(entry)
+        .map_err(|e| ConfigError::ValidationError(format!("Failed to serialize entry: {}", e)))?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    Ok(out.into_bytes())
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut fields: Vec<_> = map.iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (name, field)) in fields.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(name.clone()).to_string());
+                out.push(':');
+                write_canonical(field, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+impl ConfigManager {
+    /// Sign every written entry with `signer`, and trust its signatures on read
+    pub fn with_signer(mut self, signer: EntrySigner) -> Self {
+        self.signatures = std::mem::take(&mut self.signatures)
+            .with_trusted_key(signer.signer().to_string(), signer.verifying_key().clone());
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Check signatures on read with `policy`
+    ///
+    /// The key of a signer set with [`with_signer`](Self::with_signer) stays trusted.
+    pub fn with_signature_policy(mut self, policy: SignaturePolicy) -> Self {
+        self.signatures = match &self.signer {
+            Some(signer) => policy.with_trusted_key(signer.signer().to_string(), signer.verifying_key().clone()),
+            None => policy,
+        };
+        self
+    }
+
+    /// Policy that reads are checked with
+    pub fn signature_policy(&self) -> &SignaturePolicy {
+        &self.signatures
+    }
+
+    /// Sign an entry about to be stored, or drop its previous (now stale)
+    /// signature if no signer is set
+    pub(crate) fn sign_entry(&self, entry: &mut ConfigEntry) -> Result<()> {
+        match &self.signer {
+            Some(signer) => signer.sign(entry),
+            None => {
+                entry.metadata.signature = None;
+                Ok(())
+            }
+        }
+    }
+
+    /// Fail if an entry being read does not pass the signature policy
+    pub(crate) fn check_signature(&self, entry: &ConfigEntry) -> Result<()> {
+        if !self.signatures.is_enabled() {
+            return Ok(());
+        }
+        let status = self.signatures.check(entry);
+        if status.is_acceptable(self.signatures.requires_signatures()) {
+            return Ok(());
+        }
+        Err(ConfigError::Signature(format!(
+            "{}:{} ({}) is {}",
+            entry.namespace, entry.key, entry.environment, status
+        )))
+    }
+
+    /// Check the signature of every entry of a namespace, in all environments
+    pub fn verify_namespace(&self, namespace: &str) -> Result<NamespaceVerification> {
+        let mut entries: Vec<SignatureCheck> = self
+            .storage()
+            .list_all()?
+            .into_iter()
+            .filter(|entry| entry.namespace == namespace)
+            .map(|entry| SignatureCheck {
+                status: self.signatures.check(&entry),
+                key: entry.key,
+                environment: entry.environment,
+                version: entry.version,
+            })
+            .collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(a.environment.cmp(&b.environment)));
+
+        Ok(NamespaceVerification {
+            namespace: namespace.to_string(),
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigValue;
+    use tempfile::TempDir;
+
+    fn signer(name: &str) -> EntrySigner {
+        EntrySigner::new(name, SigningKey::generate().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_signed_writes_verify_on_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let env = Environment::Production;
+        let manager = ConfigManager::new(temp_dir.path()).unwrap().with_signer(signer("ci"));
+
+        let entry = manager.set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        let signature = entry.metadata.signature.clone().unwrap();
+        assert_eq!(signature.signer, "ci");
+        manager.set_metadata("app", "model", env, vec!["llm".into()], None).unwrap();
+        manager.set("app", "model", ConfigValue::String("gpt-5".into()), env, "alice").unwrap();
+        manager.rollback("app", "model", env, 1).unwrap();
+
+        assert_eq!(manager.get("app", "model", env).unwrap().unwrap().value.as_str(), Some("gpt-4"));
+        let verification = manager.verify_namespace("app").unwrap();
+        assert!(verification.is_verified());
+
+        // Tampering with the stored entry is detected
+        let mut tampered = manager.storage().get("app", "model", env).unwrap().unwrap();
+        tampered.value = ConfigValue::String("evil".into());
+        manager.storage().set(tampered).unwrap();
+        assert!(matches!(manager.get("app", "model", env), Err(ConfigError::Signature(_))));
+        let verification = manager.verify_namespace("app").unwrap();
+        assert_eq!(verification.invalid().count(), 1);
+    }
+
+    #[test]
+    fn test_signature_policy() {
+        let temp_dir = TempDir::new().unwrap();
+        let env = Environment::Development;
+        let unsigned = ConfigManager::new(temp_dir.path()).unwrap();
+        unsigned.set("app", "plain", ConfigValue::Integer(1), env, "alice").unwrap();
+        let other = ConfigManager::new(temp_dir.path()).unwrap().with_signer(signer("laptop"));
+        other.set("app", "foreign", ConfigValue::Integer(2), env, "bob").unwrap();
+
+        let ci = signer("ci");
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_signature_policy(SignaturePolicy::default().with_trusted_key("ci", ci.verifying_key().clone()));
+        assert!(manager.get("app", "plain", env).unwrap().is_some());
+        assert!(manager.get("app", "foreign", env).is_err());
+
+        let strict = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_signer(ci)
+            .with_signature_policy(SignaturePolicy::default().with_required_signatures(true));
+        assert!(strict.get("app", "plain", env).is_err());
+
+        let verification = strict.verify_namespace("app").unwrap();
+        assert_eq!(verification.unsigned().count(), 1);
+        assert_eq!(verification.invalid().count(), 1);
+        assert!(!verification.is_verified());
+    }
+}
@@ -0,0 +1,374 @@
+//! Multi-tenancy
+//!
+//! Each tenant's configs live in their own store under
+//! `<root>/tenants/<tenant-id>` and are served by their own
+//! [`ConfigManager`], with their own roles and encryption key. A manager only
+//! ever reads and writes its own directory, so one tenant's configs cannot be
+//! reached through another tenant's manager. [`TenantRegistry`] opens and
+//! caches the managers; [`TenantKeyStore`] keeps the per-tenant keys,
+//! wrapped with a master key.
+
+use crate::{AuditRecorder, ChangeBus, ConfigError, ConfigManager, Result};
+use llm_config_crypto::{decrypt, encrypt, EncryptedData, SecretKey};
+use llm_config_storage::StorageError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// Directory under the storage root holding one store per tenant
+pub const TENANTS_DIR: &str = "tenants";
+
+/// Directory under the storage root holding the wrapped tenant keys
+pub const TENANT_KEYS_DIR: &str = "tenant-keys";
+
+/// Identifier of a tenant
+///
+/// 1 to 63 lowercase letters, digits and `-`, neither starting nor ending
+/// with `-`, so it is safe to use as a directory name and a cache key prefix.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Validate a tenant ID
+    pub fn new(id: impl Into<String>) -> Result<Self> {
+        let id = id.into();
+        let valid = (1..=63).contains(&id.len())
+            && !id.starts_with('-')
+            && !id.ends_with('-')
+            && id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(ConfigError::ValidationError(format!("Invalid tenant ID: {:?}", id)));
+        }
+        Ok(Self(id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Storage directory of the tenant under `root`
+    pub fn storage_path(&self, root: impl AsRef<Path>) -> PathBuf {
+        root.as_ref().join(TENANTS_DIR).join(&self.0)
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for TenantId {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = ConfigError;
+
+    fn try_from(id: String) -> Result<Self> {
+        Self::new(id)
+    }
+}
+
+impl From<TenantId> for String {
+    fn from(id: TenantId) -> Self {
+        id.0
+    }
+}
+
+/// Per-tenant encryption keys, stored wrapped with a master key
+///
+/// A tenant's key is generated the first time it is requested. Each wrapped
+/// key is bound to its tenant ID, so a key file copied to another tenant's
+/// name is rejected instead of being used.
+pub struct TenantKeyStore {
+    dir: PathBuf,
+    master: SecretKey,
+    keys: RwLock<HashMap<TenantId, SecretKey>>,
+}
+
+impl TenantKeyStore {
+    /// Open (or create) a key store in `dir`
+    ///
+    /// Tenant keys encrypt with the [algorithm](SecretKey::algorithm) of `master`.
+    pub fn new(dir: impl AsRef<Path>, master: SecretKey) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(io_error)?;
+        Ok(Self {
+            dir,
+            master,
+            keys: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Key of `tenant`, generating and storing one if it has none yet
+    pub fn key(&self, tenant: &TenantId) -> Result<SecretKey> {
+        if let Some(key) = self.keys.read().unwrap().get(tenant) {
+            return Ok(key.clone());
+        }
+
+        let mut keys = self.keys.write().unwrap();
+        if let Some(key) = keys.get(tenant) {
+            return Ok(key.clone());
+        }
+        let key = match self.load(tenant)? {
+            Some(key) => key,
+            None => self.create(tenant)?,
+        };
+        keys.insert(tenant.clone(), key.clone());
+        Ok(key)
+    }
+
+    fn path(&self, tenant: &TenantId) -> PathBuf {
+        self.dir.join(format!("{}.json", tenant))
+    }
+
+    fn load(&self, tenant: &TenantId) -> Result<Option<SecretKey>> {
+        let content = match fs::read_to_string(self.path(tenant)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io_error(e)),
+        };
+
+        let wrapped: EncryptedData = serde_json::from_str(&content)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        if wrapped.aad_context.as_deref() != Some(tenant.as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "Key file of tenant {} belongs to another tenant",
+                tenant
+            )));
+        }
+        let bytes = decrypt(&self.master, &wrapped)?;
+        Ok(Some(SecretKey::from_bytes(self.master.algorithm(), bytes)?))
+    }
+
+    /// Generate and store a key, keeping the existing one if another process won the race
+    fn create(&self, tenant: &TenantId) -> Result<SecretKey> {
+        let key = SecretKey::generate(self.master.algorithm())?;
+        let wrapped = encrypt(&self.master, key.as_bytes(), Some(tenant.as_str()))?;
+        let content = serde_json::to_string_pretty(&wrapped)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+
+        let temp_path = self.dir.join(format!(".{}.{}.tmp", tenant, uuid::Uuid::new_v4()));
+        fs::write(&temp_path, content).map_err(io_error)?;
+        // Linking fails if the key already exists, unlike a rename
+        let linked = fs::hard_link(&temp_path, self.path(tenant));
+        let _ = fs::remove_file(&temp_path);
+        match linked {
+            Ok(()) => Ok(key),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => self
+                .load(tenant)?
+                .ok_or_else(|| ConfigError::NotFound(format!("Key of tenant {}", tenant))),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+}
+
+fn io_error(e: std::io::Error) -> ConfigError {
+    StorageError::IoError(e).into()
+}
+
+/// Opens and caches one [`ConfigManager`] per tenant
+pub struct TenantRegistry {
+    root: PathBuf,
+    keys: Option<TenantKeyStore>,
+    legacy_overrides: bool,
+    require_bound_secrets: bool,
+    audit: Option<Arc<dyn AuditRecorder>>,
+    changes: ChangeBus,
+    managers: RwLock<HashMap<TenantId, Arc<ConfigManager>>>,
+}
+
+impl TenantRegistry {
+    /// Serve tenants from stores under `root`
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            keys: None,
+            legacy_overrides: false,
+            require_bound_secrets: false,
+            audit: None,
+            changes: ChangeBus::default(),
+            managers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Give every tenant's manager its own key from `keys`
+    ///
+    /// Without a key store, tenants cannot store or read secrets.
+    pub fn with_key_store(mut self, keys: TenantKeyStore) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+
+    /// See [`ConfigManager::with_legacy_overrides`]
+    pub fn with_legacy_overrides(mut self, legacy: bool) -> Self {
+        self.legacy_overrides = legacy;
+        self
+    }
+
+    /// See [`ConfigManager::with_required_secret_binding`]
+    pub fn with_required_secret_binding(mut self, required: bool) -> Self {
+        self.require_bound_secrets = required;
+        self
+    }
+
+    /// Audit every tenant's operations with the given recorder
+    ///
+    /// Events carry the tenant they belong to.
+    pub fn with_audit_logger(mut self, logger: Arc<dyn AuditRecorder>) -> Self {
+        self.audit = Some(logger);
+        self
+    }
+
+    /// Bus shared by all tenants' managers
+    ///
+    /// Change events carry the tenant they belong to.
+    pub fn change_bus(&self) -> &ChangeBus {
+        &self.changes
+    }
+
+    /// Manager of `tenant`, opening its store on first use
+    pub fn manager(&self, tenant: &TenantId) -> Result<Arc<ConfigManager>> {
+        if let Some(manager) = self.managers.read().unwrap().get(tenant) {
+            return Ok(Arc::clone(manager));
+        }
+
+        let mut managers = self.managers.write().unwrap();
+        if let Some(manager) = managers.get(tenant) {
+            return Ok(Arc::clone(manager));
+        }
+
+        let mut manager = ConfigManager::new(tenant.storage_path(&self.root))?
+            .with_legacy_overrides(self.legacy_overrides)
+            .with_required_secret_binding(self.require_bound_secrets)
+            .with_tenant(tenant.clone())
+            .with_change_bus(self.changes.clone());
+        if let Some(keys) = &self.keys {
+            manager = manager.with_encryption_key(keys.key(tenant)?);
+        }
+        if let Some(audit) = &self.audit {
+            manager = manager.with_audit_logger(Arc::clone(audit));
+        }
+
+        let manager = Arc::new(manager);
+        managers.insert(tenant.clone(), Arc::clone(&manager));
+        Ok(manager)
+    }
+
+    /// Tenants that have a store, sorted by ID
+    pub fn tenants(&self) -> Result<Vec<TenantId>> {
+        let dir = self.root.join(TENANTS_DIR);
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error(e)),
+        };
+
+        let mut tenants = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(io_error)?;
+            if entry.file_type().map_err(io_error)?.is_dir() {
+                if let Some(Ok(tenant)) = entry.file_name().to_str().map(TenantId::new) {
+                    tenants.push(tenant);
+                }
+            }
+        }
+        tenants.sort();
+        Ok(tenants)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigValue, Environment};
+    use llm_config_crypto::Algorithm;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tenant_id() {
+        assert!(TenantId::new("acme").is_ok());
+        assert!(TenantId::new("acme-2").is_ok());
+        assert!(TenantId::new("").is_err());
+        assert!(TenantId::new("Acme").is_err());
+        assert!(TenantId::new("-acme").is_err());
+        assert!(TenantId::new("../acme").is_err());
+        assert!(TenantId::new("a".repeat(64)).is_err());
+
+        let tenant: TenantId = serde_json::from_str("\"acme\"").unwrap();
+        assert_eq!(tenant.as_str(), "acme");
+        assert!(serde_json::from_str::<TenantId>("\"a/b\"").is_err());
+    }
+
+    #[test]
+    fn test_key_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let master = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let acme = TenantId::new("acme").unwrap();
+        let globex = TenantId::new("globex").unwrap();
+
+        let store = TenantKeyStore::new(temp_dir.path(), master.clone()).unwrap();
+        let key = store.key(&acme).unwrap();
+        assert_eq!(store.key(&acme).unwrap().as_bytes(), key.as_bytes());
+        assert_ne!(store.key(&globex).unwrap().as_bytes(), key.as_bytes());
+
+        // Keys survive a reopen, and only open with the master key
+        let reopened = TenantKeyStore::new(temp_dir.path(), master).unwrap();
+        assert_eq!(reopened.key(&acme).unwrap().as_bytes(), key.as_bytes());
+        let other_master = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        assert!(TenantKeyStore::new(temp_dir.path(), other_master).unwrap().key(&acme).is_err());
+
+        // A key file swapped in from another tenant is rejected
+        let master = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let swapped = TempDir::new().unwrap();
+        let store = TenantKeyStore::new(swapped.path(), master.clone()).unwrap();
+        store.key(&acme).unwrap();
+        fs::copy(swapped.path().join("acme.json"), swapped.path().join("globex.json")).unwrap();
+        let reopened = TenantKeyStore::new(swapped.path(), master).unwrap();
+        assert!(reopened.key(&globex).is_err());
+    }
+
+    #[test]
+    fn test_tenant_isolation() {
+        let temp_dir = TempDir::new().unwrap();
+        let master = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let registry = TenantRegistry::new(temp_dir.path()).with_key_store(
+            TenantKeyStore::new(temp_dir.path().join(TENANT_KEYS_DIR), master).unwrap(),
+        );
+        let acme = TenantId::new("acme").unwrap();
+        let globex = TenantId::new("globex").unwrap();
+        let env = Environment::Production;
+        let mut changes = registry.change_bus().subscribe();
+
+        let acme_configs = registry.manager(&acme).unwrap();
+        assert!(Arc::ptr_eq(&acme_configs, &registry.manager(&acme).unwrap()));
+        assert_eq!(acme_configs.tenant(), Some(&acme));
+        acme_configs
+            .set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice")
+            .unwrap();
+        acme_configs.set_secret("app", "api_key", b"sk-acme", env, "alice").unwrap();
+        assert_eq!(changes.try_recv().unwrap().tenant_id.as_ref(), Some(&acme));
+
+        let globex_configs = registry.manager(&globex).unwrap();
+        assert!(globex_configs.get("app", "model", env).unwrap().is_none());
+        assert!(globex_configs.list("app", env).unwrap().is_empty());
+
+        // Each tenant's secrets are encrypted with its own key
+        let encrypted = acme_configs.get("app", "api_key", env).unwrap().unwrap();
+        assert!(globex_configs.reveal(encrypted.value).is_err());
+
+        assert_eq!(registry.tenants().unwrap(), vec![acme, globex]);
+    }
+}
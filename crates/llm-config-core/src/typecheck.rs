@@ -0,0 +1,296 @@
+//! Type checks on read
+//!
+//! Values can change type through writes that allow it (imports, restores,
+//! `--allow-type-change`) or edits of the store, and services then fail when
+//! they parse them. [`ConfigManager::get_checked`] compares a value with the
+//! type its key is declared as in a [`TypeSchema`], or else with the type of
+//! its previous version, and returns a [`TypeWarning`] with the coerced value
+//! if the text parses as the expected type.
+//!
+//! With [`ConfigManager::with_strict_types`], every `get` is checked as well:
+//! mismatches are logged and counted in `config_type_mismatches_total`.
+
+use crate::{ConfigEntry, ConfigManager, ConfigValue, Environment, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Declared value types of keys
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeSchema {
+    /// Type name (see `ConfigValue::type_name`) by namespace and key
+    types: HashMap<String, HashMap<String, String>>,
+}
+
+impl TypeSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare `namespace:key` as holding values of `type_name` (e.g. `"integer"`)
+    pub fn with_key_type(mut self, namespace: impl Into<String>, key: impl Into<String>, type_name: impl Into<String>) -> Self {
+        self.types
+            .entry(namespace.into())
+            .or_default()
+            .insert(key.into(), type_name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Declared type of a key
+    pub fn key_type(&self, namespace: &str, key: &str) -> Option<&str> {
+        self.types.get(namespace)?.get(key).map(String::as_str)
+    }
+}
+
+/// Where the expected type of a value comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "source")]
+pub enum ExpectedTypeSource {
+    /// The key's type in the [`TypeSchema`]
+    Schema,
+    /// The type of the value before the current version
+    PreviousVersion { version: u64 },
+}
+
+/// A value whose type differs from the expected one
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeWarning {
+    pub namespace: String,
+    pub key: String,
+    pub environment: Environment,
+    pub version: u64,
+    pub expected: String,
+    pub actual: String,
+    #[serde(flatten)]
+    pub source: ExpectedTypeSource,
+    /// The value converted to the expected type, if it converts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coerced: Option<ConfigValue>,
+}
+
+impl std::fmt::Display for TypeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{} in {} is {} but expected {}",
+            self.namespace, self.key, self.environment, self.actual, self.expected
+        )?;
+        match self.source {
+            ExpectedTypeSource::Schema => write!(f, " by the type schema")?,
+            ExpectedTypeSource::PreviousVersion { version } => write!(f, " as of version {}", version)?,
+        }
+        if self.coerced.is_some() {
+            write!(f, " (the value converts)")?;
+        }
+        Ok(())
+    }
+}
+
+/// A config entry with the outcome of its type check
+#[derive(Debug, Clone)]
+pub struct CheckedEntry {
+    pub entry: ConfigEntry,
+    pub warning: Option<TypeWarning>,
+}
+
+/// Type check settings of a manager
+#[derive(Default)]
+pub(crate) struct TypeChecks {
+    /// Check every `get`, not only `get_checked`
+    pub strict: bool,
+    pub schema: TypeSchema,
+}
+
+impl ConfigManager {
+    /// Check the type of every value read with `get`
+    ///
+    /// Mismatches are logged and counted (see
+    /// [`set_metrics`](Self::set_metrics)); the values are returned
+    /// unchanged.
+    pub fn with_strict_types(mut self, strict: bool) -> Self {
+        self.type_checks.get_mut().unwrap().strict = strict;
+        self
+    }
+
+    /// Expect the types declared in `schema` instead of the previous version's
+    pub fn with_type_schema(mut self, schema: TypeSchema) -> Self {
+        self.type_checks.get_mut().unwrap().schema = schema;
+        self
+    }
+
+    /// Get a configuration value together with a warning if its type drifted
+    ///
+    /// Warnings are counted but not logged; the caller reports them.
+    pub fn get_checked(&self, namespace: &str, key: &str, env: Environment) -> Result<Option<CheckedEntry>> {
+        let Some(entry) = self.storage().get(namespace, key, env)? else {
+            return Ok(None);
+        };
+        self.check_signature(&entry)?;
+        self.check_binding(&entry)?;
+        self.record_access(&entry);
+        let warning = self.type_warning(&entry)?;
+        if let Some(warning) = &warning {
+            self.count_type_warning(warning);
+        }
+        Ok(Some(CheckedEntry { entry, warning }))
+    }
+
+    /// Check an entry read with `get` if strict types are enabled
+    pub(crate) fn check_read_type(&self, entry: &ConfigEntry) {
+        if !self.type_checks.read().unwrap().strict {
+            return;
+        }
+        match self.type_warning(entry) {
+            Ok(Some(warning)) => {
+                tracing::warn!("Type drift: {}", warning);
+                self.count_type_warning(&warning);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to check the type of {}:{}: {}", entry.namespace, entry.key, e),
+        }
+    }
+
+    /// Compare an entry's type with the schema or its previous version
+    fn type_warning(&self, entry: &ConfigEntry) -> Result<Option<TypeWarning>> {
+        let actual = value_kind(&entry.value);
+        let declared = self
+            .type_checks
+            .read()
+            .unwrap()
+            .schema
+            .key_type(&entry.namespace, &entry.key)
+            .map(str::to_string);
+
+        let (expected, source) = match declared {
+            Some(expected) => (expected, ExpectedTypeSource::Schema),
+            None => {
+                let previous = match entry.version.checked_sub(1).filter(|v| *v > 0) {
+                    Some(version) => {
+                        self.version_control
+                            .get_version(&entry.namespace, &entry.key, entry.environment, version)?
+                    }
+                    None => None,
+                };
+                let Some(previous) = previous else {
+                    return Ok(None);
+                };
+                let version = previous.version;
+                (
+                    value_kind(&previous.value).to_string(),
+                    ExpectedTypeSource::PreviousVersion { version },
+                )
+            }
+        };
+
+        if expected == actual {
+            return Ok(None);
+        }
+        Ok(Some(TypeWarning {
+            namespace: entry.namespace.clone(),
+            key: entry.key.clone(),
+            environment: entry.environment,
+            version: entry.version,
+            coerced: coerce(&entry.value, &expected),
+            expected,
+            actual: actual.to_string(),
+            source,
+        }))
+    }
+
+    fn count_type_warning(&self, warning: &TypeWarning) {
+        if let Some(metrics) = self.metrics() {
+            metrics.record_counter(
+                "config_type_mismatches_total",
+                &[("expected", &warning.expected), ("actual", &warning.actual)],
+                1.0,
+            );
+        }
+    }
+}
+
+/// Type of a value for type checks (secrets decrypt to strings)
+fn value_kind(value: &ConfigValue) -> &'static str {
+    match value {
+        ConfigValue::Secret(_) => "string",
+        other => other.type_name(),
+    }
+}
+
+/// Convert a value to the type named `expected`, if its text parses as one
+fn coerce(value: &ConfigValue, expected: &str) -> Option<ConfigValue> {
+    let text = match value {
+        ConfigValue::String(s) => Some(s.trim()),
+        _ => None,
+    };
+    match expected {
+        "string" => match value {
+            ConfigValue::Integer(i) => Some(ConfigValue::String(i.to_string())),
+            ConfigValue::Float(f) => Some(ConfigValue::String(f.to_string())),
+            ConfigValue::Boolean(b) => Some(ConfigValue::String(b.to_string())),
+            other => other.typed_text().map(ConfigValue::String),
+        },
+        "integer" => text?.parse().ok().map(ConfigValue::Integer),
+        "float" => match value {
+            ConfigValue::Integer(i) => Some(ConfigValue::Float(*i as f64)),
+            _ => text?.parse().ok().map(ConfigValue::Float),
+        },
+        "boolean" => text?.parse().ok().map(ConfigValue::Boolean),
+        "duration" => value.as_duration().map(ConfigValue::Duration),
+        "size" => value.as_bytes().map(ConfigValue::Size),
+        "url" => value.as_url().map(ConfigValue::Url),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SetOptions;
+    use llm_config_metrics::MetricsRegistry;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_type_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = Arc::new(MetricsRegistry::new().unwrap());
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_strict_types(true)
+            .with_type_schema(TypeSchema::new().with_key_type("app", "temperature", "float"));
+        manager.set_metrics(registry.clone());
+        let env = Environment::Production;
+        let retype = SetOptions::default().with_allow_type_change(true);
+
+        manager.set("app", "max_tokens", ConfigValue::Integer(4096), env, "alice").unwrap();
+        assert!(manager.get_checked("app", "max_tokens", env).unwrap().unwrap().warning.is_none());
+        assert!(manager.get_checked("app", "missing", env).unwrap().is_none());
+
+        // A string where the previous version held an integer
+        manager
+            .set_with_options("app", "max_tokens", ConfigValue::String("8192".into()), env, "alice", &retype)
+            .unwrap();
+        let checked = manager.get_checked("app", "max_tokens", env).unwrap().unwrap();
+        let warning = checked.warning.unwrap();
+        assert_eq!((warning.expected.as_str(), warning.actual.as_str()), ("integer", "string"));
+        assert_eq!(warning.source, ExpectedTypeSource::PreviousVersion { version: 1 });
+        assert_eq!(warning.coerced.unwrap().as_i64(), Some(8192));
+        assert_eq!(checked.entry.value.as_str(), Some("8192"));
+
+        // The schema wins over the history
+        manager.set("app", "temperature", ConfigValue::String("warm".into()), env, "alice").unwrap();
+        let warning = manager.get_checked("app", "temperature", env).unwrap().unwrap().warning.unwrap();
+        assert_eq!(warning.source, ExpectedTypeSource::Schema);
+        assert!(warning.coerced.is_none());
+
+        // Strict mode checks plain reads too
+        manager.get("app", "max_tokens", env).unwrap();
+        assert_eq!(registry.config().type_mismatches("integer", "string"), 2.0);
+        assert_eq!(registry.config().type_mismatches("float", "string"), 1.0);
+
+        // Once the new type is kept, it is expected
+        manager
+            .set("app", "max_tokens", ConfigValue::String("1024".into()), env, "alice")
+            .unwrap();
+        assert!(manager.get_checked("app", "max_tokens", env).unwrap().unwrap().warning.is_none());
+    }
+}
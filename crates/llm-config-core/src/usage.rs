@@ -0,0 +1,301 @@
+//! Access statistics of config entries
+//!
+//! Reads through [`ConfigManager`] (`get`, `get_revealed`, secret reads and
+//! resolving with overrides) are counted per entry to find configs nobody
+//! uses. Counts are kept in memory and persisted at most once per flush
+//! interval and entry (and when the manager is dropped), so reads do not
+//! turn into a write each. Statistics of several processes sharing a store
+//! are added up; a crash loses at most one interval of counts.
+//!
+//! Nothing is persisted while the store is [read-only](crate::maintenance).
+
+use crate::maintenance::READ_ONLY_RECORD;
+use crate::{ConfigEntry, ConfigManager, Environment, ReadOnlyMode, Result};
+use chrono::{DateTime, Utc};
+use llm_config_storage::file::FileStorage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Storage record kind access statistics are persisted under (one record per entry id)
+pub(crate) const ACCESS_RECORD_KIND: &str = "access";
+
+/// How often the statistics of a frequently read entry are persisted by default
+pub const DEFAULT_ACCESS_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often and when a config entry was read
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessStats {
+    pub reads: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_accessed: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_accessed: Option<DateTime<Utc>>,
+}
+
+impl AccessStats {
+    /// Add the reads of `other`
+    fn merge(&mut self, other: &AccessStats) {
+        self.reads += other.reads;
+        self.first_accessed = match (self.first_accessed, other.first_accessed) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last_accessed = self.last_accessed.max(other.last_accessed);
+    }
+}
+
+/// A config that has not been read since the cutoff of [`ConfigManager::unused_configs`]
+#[derive(Debug, Clone, Serialize)]
+pub struct UnusedConfig {
+    pub entry: ConfigEntry,
+    pub stats: AccessStats,
+}
+
+/// Reads not yet persisted
+struct PendingAccess {
+    stats: AccessStats,
+    /// When the entry's statistics were last persisted by this process
+    flushed_at: Option<Instant>,
+}
+
+/// Counts reads in memory and persists them per entry
+pub(crate) struct AccessTracker {
+    storage: FileStorage,
+    flush_interval: Duration,
+    pending: Mutex<HashMap<Uuid, PendingAccess>>,
+}
+
+impl AccessTracker {
+    pub fn new(storage: FileStorage) -> Self {
+        Self {
+            storage,
+            flush_interval: DEFAULT_ACCESS_FLUSH_INTERVAL,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Count a read of `entry`, persisting the counts if the interval has passed
+    pub fn record(&self, entry: &ConfigEntry) {
+        let now = Utc::now();
+        let due = {
+            let mut pending = self.pending.lock().unwrap();
+            let access = pending.entry(entry.id).or_insert_with(|| PendingAccess {
+                stats: AccessStats::default(),
+                flushed_at: None,
+            });
+            access.stats.merge(&AccessStats {
+                reads: 1,
+                first_accessed: Some(now),
+                last_accessed: Some(now),
+            });
+            let due = access
+                .flushed_at
+                .map_or(true, |flushed_at| flushed_at.elapsed() >= self.flush_interval);
+            if due {
+                access.flushed_at = Some(Instant::now());
+                Some(std::mem::take(&mut access.stats))
+            } else {
+                None
+            }
+        };
+
+        if let Some(stats) = due {
+            if let Err(e) = self.persist(entry.id, &stats) {
+                tracing::warn!("Failed to save access statistics of {}: {}", entry.id, e);
+                self.restore(entry.id, &stats);
+            }
+        }
+    }
+
+    /// Persisted statistics of an entry together with the reads not yet persisted
+    pub fn stats(&self, id: Uuid) -> Result<AccessStats> {
+        let mut stats: AccessStats = self
+            .storage
+            .get_record(ACCESS_RECORD_KIND, &id.to_string())?
+            .unwrap_or_default();
+        if let Some(access) = self.pending.lock().unwrap().get(&id) {
+            stats.merge(&access.stats);
+        }
+        Ok(stats)
+    }
+
+    /// Forget the statistics of a deleted entry
+    pub fn remove(&self, id: Uuid) -> Result<()> {
+        self.pending.lock().unwrap().remove(&id);
+        self.storage.delete_record(ACCESS_RECORD_KIND, &id.to_string())?;
+        Ok(())
+    }
+
+    /// Persist all pending reads
+    pub fn flush(&self) -> Result<()> {
+        let pending: Vec<(Uuid, AccessStats)> = self
+            .pending
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter(|(_, access)| access.stats.reads > 0)
+            .map(|(id, access)| (*id, std::mem::take(&mut access.stats)))
+            .collect();
+
+        for (index, (id, stats)) in pending.iter().enumerate() {
+            if let Err(e) = self.persist(*id, stats) {
+                for (id, stats) in &pending[index..] {
+                    self.restore(*id, stats);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `stats` to the persisted statistics of an entry
+    fn persist(&self, id: Uuid, stats: &AccessStats) -> Result<()> {
+        let (kind, record_id) = READ_ONLY_RECORD;
+        if self.storage.get_record::<ReadOnlyMode>(kind, record_id)?.is_some() {
+            self.restore(id, stats);
+            return Ok(());
+        }
+
+        let record_id = id.to_string();
+        let mut persisted: AccessStats = self
+            .storage
+            .get_record(ACCESS_RECORD_KIND, &record_id)?
+            .unwrap_or_default();
+        persisted.merge(stats);
+        self.storage.put_record(ACCESS_RECORD_KIND, &record_id, &persisted)?;
+        Ok(())
+    }
+
+    /// Put reads that could not be persisted back into the pending counts
+    fn restore(&self, id: Uuid, stats: &AccessStats) {
+        if let Some(access) = self.pending.lock().unwrap().get_mut(&id) {
+            access.stats.merge(stats);
+        }
+    }
+}
+
+impl Drop for AccessTracker {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!("Failed to save access statistics: {}", e);
+        }
+    }
+}
+
+impl ConfigManager {
+    /// Persist access statistics of entries read at least this often
+    ///
+    /// Lower intervals make statistics survive crashes at the cost of more
+    /// writes; the default is [`DEFAULT_ACCESS_FLUSH_INTERVAL`].
+    pub fn with_access_flush_interval(mut self, interval: Duration) -> Self {
+        self.access = AccessTracker::new(self.storage().clone()).with_flush_interval(interval);
+        self
+    }
+
+    /// Get a configuration value without counting the read
+    ///
+    /// For lookups that are part of a write, such as existence checks.
+    pub fn get_untracked(&self, namespace: &str, key: &str, env: Environment) -> Result<Option<ConfigEntry>> {
+        Ok(self.storage().get(namespace, key, env)?)
+    }
+
+    /// Count a read of `entry` that was served from a cache instead of this manager
+    pub fn record_cached_read(&self, entry: &ConfigEntry) {
+        self.record_access(entry);
+    }
+
+    /// Access statistics of a config (`None` if it does not exist)
+    pub fn access_stats(&self, namespace: &str, key: &str, env: Environment) -> Result<Option<AccessStats>> {
+        match self.storage().get(namespace, key, env)? {
+            Some(entry) => Ok(Some(self.access.stats(entry.id)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Configs not read since `cutoff`, least recently read first
+    ///
+    /// Configs that were never read count from their creation, so configs
+    /// created after `cutoff` are not listed.
+    pub fn unused_configs(&self, cutoff: DateTime<Utc>) -> Result<Vec<UnusedConfig>> {
+        let mut unused = Vec::new();
+        for entry in self.storage().list_all()? {
+            let stats = self.access.stats(entry.id)?;
+            if stats.last_accessed.unwrap_or(entry.metadata.created_at) < cutoff {
+                unused.push(UnusedConfig { entry, stats });
+            }
+        }
+        unused.sort_by(|a, b| {
+            (a.stats.last_accessed, &a.entry.namespace, &a.entry.key, a.entry.environment.to_string())
+                .cmp(&(b.stats.last_accessed, &b.entry.namespace, &b.entry.key, b.entry.environment.to_string()))
+        });
+        Ok(unused)
+    }
+
+    /// Persist access statistics that have not been saved yet
+    pub fn flush_access_stats(&self) -> Result<()> {
+        self.access.flush()
+    }
+
+    /// Count a read of `entry`
+    pub(crate) fn record_access(&self, entry: &ConfigEntry) {
+        self.access.record(entry);
+    }
+
+    /// Forget the access statistics of a deleted entry
+    pub(crate) fn forget_access(&self, entry: &ConfigEntry) {
+        if let Err(e) = self.access.remove(entry.id) {
+            tracing::warn!("Failed to remove access statistics of {}: {}", entry.id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigValue;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_access_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path())
+            .unwrap()
+            .with_access_flush_interval(Duration::from_secs(3600));
+        let env = Environment::Production;
+        manager.set("app", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        manager.set("app", "legacy", ConfigValue::Integer(1), env, "alice").unwrap();
+        assert_eq!(manager.access_stats("app", "model", env).unwrap(), Some(AccessStats::default()));
+        assert!(manager.access_stats("app", "missing", env).unwrap().is_none());
+
+        for _ in 0..3 {
+            manager.get("app", "model", env).unwrap();
+        }
+        manager.get_with_overrides("app", "model", env).unwrap();
+        let stats = manager.access_stats("app", "model", env).unwrap().unwrap();
+        assert_eq!(stats.reads, 4);
+        assert!(stats.first_accessed <= stats.last_accessed);
+
+        // Only the first read was persisted; the rest is saved on drop
+        let other = ConfigManager::new(temp_dir.path()).unwrap();
+        assert_eq!(other.access_stats("app", "model", env).unwrap().unwrap().reads, 1);
+        drop(manager);
+        assert_eq!(other.access_stats("app", "model", env).unwrap().unwrap().reads, 4);
+
+        let unused = other.unused_configs(Utc::now()).unwrap();
+        let keys: Vec<&str> = unused.iter().map(|u| u.entry.key.as_str()).collect();
+        assert_eq!(keys, vec!["legacy", "model"]);
+        assert!(other.unused_configs(Utc::now() - chrono::Duration::days(1)).unwrap().is_empty());
+
+        other.delete("app", "model", env).unwrap();
+        other.set("app", "model", ConfigValue::Integer(2), env, "alice").unwrap();
+        assert_eq!(other.access_stats("app", "model", env).unwrap().unwrap().reads, 0);
+    }
+}
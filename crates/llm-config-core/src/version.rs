@@ -3,16 +3,113 @@
 pub use llm_config_storage::VersionEntry;
 
 use chrono::Utc;
-use crate::{ConfigEntry, Result};
+use crate::plan::render_value;
+use crate::{
+    ConfigEntry, ConfigError, ConfigValue, HistoryOptions, OperationContext, Result,
+    RetentionPolicy,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A single difference between two values
+///
+/// Paths are JSON pointers into the value (`""` for the value itself).
+/// Secrets are masked, and arrays are compared as a whole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ValueChange {
+    Added {
+        path: String,
+        value: serde_json::Value,
+    },
+    Removed {
+        path: String,
+        value: serde_json::Value,
+    },
+    Changed {
+        path: String,
+        from: serde_json::Value,
+        to: serde_json::Value,
+    },
+}
+
+/// Differences between two versions of a config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ValueDiff {
+    pub from_version: u64,
+    pub to_version: u64,
+    /// Changes sorted by path (empty when the values are equal)
+    pub changes: Vec<ValueChange>,
+}
+
+impl ValueDiff {
+    /// Compare two values, recursing into objects
+    pub fn between(from_version: u64, from: &ConfigValue, to_version: u64, to: &ConfigValue) -> Self {
+        let mut changes = Vec::new();
+        diff_values(String::new(), from, to, &mut changes);
+        Self {
+            from_version,
+            to_version,
+            changes,
+        }
+    }
+
+    /// Whether the two versions hold the same value
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+fn diff_values(path: String, from: &ConfigValue, to: &ConfigValue, changes: &mut Vec<ValueChange>) {
+    match (from, to) {
+        (ConfigValue::Object(from), ConfigValue::Object(to)) => {
+            let fields: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+            for field in fields {
+                let field_path = format!("{}/{}", path, field.replace('~', "~0").replace('/', "~1"));
+                match (from.get(field), to.get(field)) {
+                    (Some(from), Some(to)) => diff_values(field_path, from, to, changes),
+                    (None, Some(added)) => changes.push(ValueChange::Added {
+                        path: field_path,
+                        value: render_value(added),
+                    }),
+                    (Some(removed), None) => changes.push(ValueChange::Removed {
+                        path: field_path,
+                        value: render_value(removed),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        // Compare serialized forms, so re-encrypted secrets count as changed
+        _ if serde_json::to_value(from).ok() == serde_json::to_value(to).ok() => {}
+        _ => changes.push(ValueChange::Changed {
+            path,
+            from: render_value(from),
+            to: render_value(to),
+        }),
+    }
+}
 
 /// Version control manager
 pub struct VersionControl {
     storage: llm_config_storage::file::FileStorage,
+    retention: Option<RetentionPolicy>,
 }
 
 impl VersionControl {
     pub fn new(storage: llm_config_storage::file::FileStorage) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            retention: None,
+        }
+    }
+
+    /// Prune a config's history with `policy` whenever a snapshot of it is created
+    pub fn with_retention(mut self, policy: RetentionPolicy) -> Self {
+        self.retention = Some(policy);
+        self
     }
 
     /// Create a version snapshot of a configuration
@@ -21,23 +118,29 @@ impl VersionControl {
         config: &ConfigEntry,
         change_description: Option<String>,
     ) -> Result<VersionEntry> {
-        let version = VersionEntry {
-            version: config.version,
-            config_id: config.id,
-            namespace: config.namespace.clone(),
-            key: config.key.clone(),
-            value: config.value.clone(),
-            environment: config.environment,
-            created_at: Utc::now(),
-            created_by: config.metadata.updated_by.clone(),
-            change_description,
-        };
-
+        let version = snapshot_of(config, change_description);
         self.storage.store_version(version.clone())?;
+        self.apply_retention(config)?;
+        Ok(version)
+    }
 
+    /// Store a configuration and its version snapshot as one crash-safe write
+    pub fn commit(&self, config: &ConfigEntry, change_description: Option<String>) -> Result<VersionEntry> {
+        let version = snapshot_of(config, change_description);
+        self.storage.set_versioned(config.clone(), version.clone())?;
+        self.apply_retention(config)?;
         Ok(version)
     }
 
+    /// Prune a config's history with the retention policy, if there is one
+    fn apply_retention(&self, config: &ConfigEntry) -> Result<()> {
+        if let Some(policy) = &self.retention {
+            self.storage
+                .prune_versions(&config.namespace, &config.key, config.environment, policy, Utc::now())?;
+        }
+        Ok(())
+    }
+
     /// Get version history
     pub fn get_history(
         &self,
@@ -48,6 +151,56 @@ impl VersionControl {
         Ok(self.storage.get_versions(namespace, key, env)?)
     }
 
+    /// Get a page of version history, newest first
+    pub fn get_history_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: llm_config_storage::Environment,
+        options: &HistoryOptions,
+    ) -> Result<Vec<VersionEntry>> {
+        Ok(self
+            .storage
+            .get_versions_with_options(namespace, key, env, options)?)
+    }
+
+    /// Get a single version (the most recent one, if the number was reused)
+    pub fn get_version(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: llm_config_storage::Environment,
+        version: u64,
+    ) -> Result<Option<VersionEntry>> {
+        let page = self.get_history_with_options(
+            namespace,
+            key,
+            env,
+            &HistoryOptions::default()
+                .with_limit(1)
+                .with_before_version(version.saturating_add(1)),
+        )?;
+        Ok(page.into_iter().next().filter(|v| v.version == version))
+    }
+
+    /// Differences between two versions of a config
+    pub fn diff(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: llm_config_storage::Environment,
+        v1: u64,
+        v2: u64,
+    ) -> Result<ValueDiff> {
+        let load = |version| {
+            self.get_version(namespace, key, env, version)?.ok_or_else(|| {
+                ConfigError::NotFound(format!("{}:{} version {}", namespace, key, version))
+            })
+        };
+        let (from, to) = (load(v1)?, load(v2)?);
+        Ok(ValueDiff::between(v1, &from.value, v2, &to.value))
+    }
+
     /// Rollback to a specific version
     pub fn rollback(
         &self,
@@ -56,31 +209,90 @@ impl VersionControl {
         env: llm_config_storage::Environment,
         target_version: u64,
     ) -> Result<Option<ConfigEntry>> {
-        let versions = self.get_history(namespace, key, env)?;
+        self.rollback_as(namespace, key, env, target_version, "system", None, None)
+    }
 
-        let target = versions.iter().find(|v| v.version == target_version);
+    /// Rollback to a specific version, recording who made the change, from
+    /// where and why
+    ///
+    /// The reason is appended to the change description of the new version.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rollback_as(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: llm_config_storage::Environment,
+        target_version: u64,
+        user: &str,
+        context: Option<OperationContext>,
+        reason: Option<&str>,
+    ) -> Result<Option<ConfigEntry>> {
+        let Some(config) = self.rollback_entry(namespace, key, env, target_version, user, context)? else {
+            return Ok(None);
+        };
 
-        if let Some(version) = target {
-            let mut config = ConfigEntry::new(
-                version.namespace.clone(),
-                version.key.clone(),
-                version.value.clone(),
-                version.environment,
-            );
+        // Store the rollback with a snapshot of it
+        self.commit(&config, Some(rollback_description(target_version, reason)))?;
 
-            // Increment version for the rollback
-            config.version = versions.first().map(|v| v.version + 1).unwrap_or(1);
-            config.metadata.updated_at = Utc::now();
+        Ok(Some(config))
+    }
 
-            self.storage.set(config.clone())?;
+    /// The entry a rollback to `target_version` would store, without storing it
+    pub fn rollback_entry(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: llm_config_storage::Environment,
+        target_version: u64,
+        user: &str,
+        context: Option<OperationContext>,
+    ) -> Result<Option<ConfigEntry>> {
+        let latest = self.get_history_with_options(
+            namespace,
+            key,
+            env,
+            &HistoryOptions::default().with_limit(1),
+        )?;
 
-            // Create a snapshot of the rollback
-            self.create_snapshot(&config, Some(format!("Rollback to version {}", target_version)))?;
+        let Some(version) = self.get_version(namespace, key, env, target_version)? else {
+            return Ok(None);
+        };
+        let mut config = ConfigEntry::new(
+            version.namespace.clone(),
+            version.key.clone(),
+            version.value.clone(),
+            version.environment,
+        );
 
-            Ok(Some(config))
-        } else {
-            Ok(None)
-        }
+        // Increment version for the rollback
+        config.version = latest.first().map(|v| v.version + 1).unwrap_or(1);
+        config.metadata.updated_at = Utc::now();
+        config.metadata.updated_by = user.to_string();
+        config.metadata.context = context;
+        Ok(Some(config))
+    }
+}
+
+/// Change description of a rollback to `target_version`, with its reason
+pub(crate) fn rollback_description(target_version: u64, reason: Option<&str>) -> String {
+    match reason.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(reason) => format!("Rollback to version {}: {}", target_version, reason),
+        None => format!("Rollback to version {}", target_version),
+    }
+}
+
+/// Version entry recording the current value of a config
+fn snapshot_of(config: &ConfigEntry, change_description: Option<String>) -> VersionEntry {
+    VersionEntry {
+        version: config.version,
+        config_id: config.id,
+        namespace: config.namespace.clone(),
+        key: config.key.clone(),
+        value: config.value.clone(),
+        environment: config.environment,
+        created_at: Utc::now(),
+        created_by: config.metadata.updated_by.clone(),
+        change_description,
     }
 }
 
@@ -134,4 +346,55 @@ mod tests {
         assert_eq!(history[0].version, 2); // Most recent first
         assert_eq!(history[1].version, 1);
     }
+
+    #[test]
+    fn test_diff_and_annotated_rollback() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        let vc = VersionControl::new(storage.clone());
+        let env = Environment::Development;
+
+        let object = |model: &str, extra: Option<i64>| {
+            let mut fields = std::collections::HashMap::new();
+            fields.insert("model".to_string(), ConfigValue::String(model.to_string()));
+            if let Some(extra) = extra {
+                fields.insert("max_tokens".to_string(), ConfigValue::Integer(extra));
+            }
+            ConfigValue::Object(fields)
+        };
+        let mut config = ConfigEntry::new("llm", "settings", object("gpt-4", None), env);
+        vc.create_snapshot(&config, None).unwrap();
+        config.version = 2;
+        config.value = object("gpt-4o", Some(512));
+        vc.create_snapshot(&config, None).unwrap();
+
+        let diff = vc.diff("llm", "settings", env, 1, 2).unwrap();
+        assert_eq!(
+            diff.changes,
+            vec![
+                ValueChange::Added {
+                    path: "/max_tokens".to_string(),
+                    value: serde_json::json!(512),
+                },
+                ValueChange::Changed {
+                    path: "/model".to_string(),
+                    from: serde_json::json!("gpt-4"),
+                    to: serde_json::json!("gpt-4o"),
+                },
+            ]
+        );
+        assert!(vc.diff("llm", "settings", env, 1, 9).is_err());
+
+        let rolled_back = vc
+            .rollback_as("llm", "settings", env, 1, "alice", None, Some("latency regression"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(rolled_back.version, 3);
+        let latest = vc.get_version("llm", "settings", env, 3).unwrap().unwrap();
+        assert_eq!(
+            latest.change_description.as_deref(),
+            Some("Rollback to version 1: latency regression")
+        );
+        assert!(vc.diff("llm", "settings", env, 1, 3).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,284 @@
+//! Sharing a store between replicas through a key-value store
+//!
+//! With [`ConfigManager::with_kv_store`], sets, rollbacks, metadata changes
+//! and deletes are also written to a shared [`KvStore`] (see
+//! [`entry_key`] for the keys), as a compare-and-swap on the entry the write
+//! was based on: a write to an entry another replica changed since fails
+//! with [`ConfigError::Conflict`]. Entries the shared store does not hold
+//! yet are added by the first write to them. Namespace clones, renames and
+//! restores are not shared.
+//!
+//! [`ConfigManager::watch`] waits on the store for writes made through other
+//! replicas, applies them to the local store and publishes them on the
+//! change bus, so caches, webhooks and change subscribers see writes made
+//! through any replica. Reads are always served from the local store.
+
+use crate::{ChangeEvent, ChangeKind, ConfigEntry, ConfigError, ConfigManager, Environment, Result, StorageError};
+use llm_config_storage::{entry_key, KvPair, KvStore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Change description of versions applied from other replicas that have none
+pub const REPLICATED_DESCRIPTION: &str = "Replicated from another replica";
+
+/// A manager's connection to a shared key-value store
+pub(crate) struct KvReplica {
+    store: Arc<dyn KvStore>,
+    prefix: String,
+    watch: Mutex<WatchState>,
+}
+
+/// Where [`ConfigManager::watch`] left off
+#[derive(Default)]
+struct WatchState {
+    index: u64,
+    keys: HashMap<String, SeenKey>,
+}
+
+/// A key seen by the last watch
+struct SeenKey {
+    modify_index: u64,
+    namespace: String,
+    key: String,
+    env: Environment,
+}
+
+/// An entry as stored in the key-value store
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SharedEntry {
+    pub(crate) entry: ConfigEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+}
+
+impl SharedEntry {
+    pub(crate) fn encode(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| StorageError::SerializationError(e.to_string()).into())
+    }
+
+    pub(crate) fn decode(pair: &KvPair) -> Result<Self> {
+        serde_json::from_slice(&pair.value).map_err(|e| {
+            StorageError::SerializationError(format!("Shared entry {}: {}", pair.key, e)).into()
+        })
+    }
+}
+
+/// Whether two entries are the same write
+fn same_write(a: &ConfigEntry, b: &ConfigEntry) -> bool {
+    a.version == b.version && a.metadata.updated_at == b.metadata.updated_at
+}
+
+impl ConfigManager {
+    /// Share writes with other replicas through `store`, under keys
+    /// starting with `prefix`
+    ///
+    /// Call [`watch`](Self::watch) in a loop to receive their writes.
+    pub fn with_kv_store(mut self, store: Arc<dyn KvStore>, prefix: impl Into<String>) -> Self {
+        self.kv = Some(KvReplica {
+            store,
+            prefix: prefix.into().trim_end_matches('/').to_string(),
+            watch: Mutex::default(),
+        });
+        self
+    }
+
+    /// Whether writes are shared through a key-value store
+    pub fn has_kv_store(&self) -> bool {
+        self.kv.is_some()
+    }
+
+    /// Write `entry` (or a delete, for `None`) to the shared store before it
+    /// replaces the local entry
+    ///
+    /// The caller holds the write lock. Fails with a conflict if another
+    /// replica changed the entry since the local store last saw it.
+    pub(crate) fn share(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        entry: Option<&ConfigEntry>,
+        description: Option<&str>,
+    ) -> Result<()> {
+        let Some(replica) = &self.kv else {
+            return Ok(());
+        };
+        let conflict = || {
+            ConfigError::Conflict(format!(
+                "{}:{} ({}) was changed through another replica",
+                namespace, key, env
+            ))
+        };
+
+        let kv_key = entry_key(&replica.prefix, namespace, key, env);
+        let remote = replica.store.get(&kv_key)?;
+        let index = match &remote {
+            Some(pair) => {
+                let shared = SharedEntry::decode(pair)?;
+                let local = self.storage().get(namespace, key, env)?;
+                if !local.is_some_and(|local| same_write(&local, &shared.entry)) {
+                    return Err(conflict());
+                }
+                pair.modify_index
+            }
+            None => 0,
+        };
+
+        let stored = match entry {
+            Some(entry) => {
+                let shared = SharedEntry {
+                    entry: entry.clone(),
+                    description: description.map(str::to_string),
+                };
+                replica.store.cas(&kv_key, &shared.encode()?, index)?
+            }
+            None if remote.is_none() => true,
+            None => replica.store.delete_cas(&kv_key, index)?,
+        };
+        if !stored {
+            return Err(conflict());
+        }
+        Ok(())
+    }
+
+    /// Wait up to `wait` for writes made through other replicas and apply them
+    ///
+    /// Returns the changes applied to the local store, which are also
+    /// published on the change bus (but not audited again). The first call
+    /// returns at once, applying every shared entry that differs from the
+    /// local one. Fails if no key-value store is configured.
+    pub fn watch(&self, wait: Duration) -> Result<Vec<ChangeEvent>> {
+        let replica = self
+            .kv
+            .as_ref()
+            .ok_or_else(|| ConfigError::InvalidOperation("No shared key-value store configured".to_string()))?;
+        let mut state = replica.watch.lock().unwrap();
+        let list = replica.store.list(&format!("{}/", replica.prefix), state.index, wait)?;
+        if state.index != 0 && list.index == state.index {
+            return Ok(Vec::new());
+        }
+
+        let _writes = self.lock_writes()?;
+        let mut events = Vec::new();
+        let mut seen = HashMap::new();
+        for pair in list.pairs {
+            if let Some(known) = state.keys.remove(&pair.key).filter(|known| known.modify_index == pair.modify_index) {
+                seen.insert(pair.key, known);
+                continue;
+            }
+            let shared = match SharedEntry::decode(&pair) {
+                Ok(shared) => shared,
+                Err(e) => {
+                    tracing::warn!("Skipping shared entry: {}", e);
+                    continue;
+                }
+            };
+            let entry = shared.entry;
+            seen.insert(
+                pair.key,
+                SeenKey {
+                    modify_index: pair.modify_index,
+                    namespace: entry.namespace.clone(),
+                    key: entry.key.clone(),
+                    env: entry.environment,
+                },
+            );
+
+            let local = self.storage().get(&entry.namespace, &entry.key, entry.environment)?;
+            match local {
+                Some(local) if same_write(&local, &entry) => continue,
+                Some(local) if local.version == entry.version => {
+                    self.storage().set(entry.clone())?;
+                    events.push(ChangeEvent::stored(ChangeKind::Metadata, &entry));
+                }
+                _ => {
+                    let description = shared.description.unwrap_or_else(|| REPLICATED_DESCRIPTION.to_string());
+                    self.version_control.commit(&entry, Some(description))?;
+                    events.push(ChangeEvent::stored(ChangeKind::Set, &entry));
+                }
+            }
+        }
+
+        // Keys seen before and gone now were deleted
+        for (_, gone) in state.keys.drain() {
+            if let Some(local) = self.storage().get(&gone.namespace, &gone.key, gone.env)? {
+                self.storage().delete(&gone.namespace, &gone.key, gone.env)?;
+                self.forget_access(&local);
+                events.push(ChangeEvent::deleted(&local));
+            }
+        }
+        state.keys = seen;
+        state.index = list.index;
+
+        for event in &mut events {
+            event.tenant_id = self.tenant().cloned();
+            self.change_bus().publish(event.clone());
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigValue, SetOptions};
+    use llm_config_storage::MemoryKv;
+    use tempfile::TempDir;
+
+    fn replica(dir: &TempDir, kv: &Arc<MemoryKv>) -> ConfigManager {
+        let store: Arc<dyn KvStore> = Arc::clone(kv) as Arc<dyn KvStore>;
+        ConfigManager::new(dir.path()).unwrap().with_kv_store(store, "llm-config")
+    }
+
+    #[test]
+    fn test_replicas_share_writes() {
+        let kv = Arc::new(MemoryKv::new());
+        let (dir_a, dir_b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let (a, b) = (replica(&dir_a, &kv), replica(&dir_b, &kv));
+        let env = Environment::Production;
+        assert!(b.watch(Duration::ZERO).unwrap().is_empty());
+
+        a.set("app/llm", "model", ConfigValue::String("gpt-4".into()), env, "alice").unwrap();
+        let mut changes = b.subscribe_changes();
+        let events = b.watch(Duration::from_secs(5)).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, ChangeKind::Set);
+        assert_eq!(changes.try_recv().unwrap().key, "model");
+        let entry = b.get("app/llm", "model", env).unwrap().unwrap();
+        assert_eq!(entry.value.as_str(), Some("gpt-4"));
+        assert_eq!(b.get_history("app/llm", "model", env).unwrap().len(), 1);
+
+        // A's own writes are not applied again
+        assert!(a.watch(Duration::ZERO).unwrap().is_empty());
+
+        // B is up to date, so its write wins; A's write is based on a stale entry
+        b.set("app/llm", "model", ConfigValue::String("gpt-4o".into()), env, "bob").unwrap();
+        let stale = a.set("app/llm", "model", ConfigValue::String("claude".into()), env, "alice");
+        assert!(matches!(stale, Err(ConfigError::Conflict(_))));
+        assert_eq!(a.get("app/llm", "model", env).unwrap().unwrap().value.as_str(), Some("gpt-4"));
+
+        let events = a.watch(Duration::from_secs(5)).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(a.get("app/llm", "model", env).unwrap().unwrap().version, 2);
+        a.set_metadata("app/llm", "model", env, vec!["llm".into()], None).unwrap();
+
+        let events = b.watch(Duration::from_secs(5)).unwrap();
+        assert_eq!(events[0].kind, ChangeKind::Metadata);
+        assert_eq!(b.get("app/llm", "model", env).unwrap().unwrap().metadata.tags, vec!["llm"]);
+
+        b.delete_with_options("app/llm", "model", env, "bob", &SetOptions::default()).unwrap();
+        let events = a.watch(Duration::from_secs(5)).unwrap();
+        assert_eq!(events[0].kind, ChangeKind::Delete);
+        assert!(a.get("app/llm", "model", env).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_watch_without_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path()).unwrap();
+        assert!(!manager.has_kv_store());
+        assert!(manager.watch(Duration::ZERO).is_err());
+    }
+}
@@ -1,10 +1,37 @@
 //! Key derivation using Argon2id
+//!
+//! [`derive_key_from_passphrase`] derives the encryption key of a storage
+//! directory from an operator's passphrase. The salt, Argon2 parameters and a
+//! value encrypted with the derived key are kept in [`PASSPHRASE_FILE`], so the
+//! same passphrase always yields the same key and a wrong one is rejected
+//! before anything is decrypted with it.
 
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2, Params,
 };
-use crate::{CryptoError, Result, SecretKey, Algorithm, KEY_SIZE};
+use crate::{decrypt, encrypt, CryptoError, EncryptedData, Result, SecretKey, Algorithm, KEY_SIZE};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// File in a storage directory holding the parameters of its passphrase key
+pub const PASSPHRASE_FILE: &str = "passphrase.json";
+
+/// Argon2 memory cost in KiB (64 MB)
+const MEMORY_KIB: u32 = 65536;
+
+/// Argon2 iterations
+const ITERATIONS: u32 = 3;
+
+/// Argon2 parallelism
+const PARALLELISM: u32 = 4;
+
+/// Plaintext of the verifier, encrypted with the derived key
+const VERIFIER_PLAINTEXT: &[u8] = b"llm-config passphrase check";
+
+/// Associated data of the verifier
+const VERIFIER_CONTEXT: &str = "passphrase";
 
 /// Derive a key from a password using Argon2id
 pub fn derive_key_from_password(
@@ -18,16 +45,7 @@ pub fn derive_key_from_password(
     };
 
     // Argon2 parameters: memory=64MB, iterations=3, parallelism=4
-    let params = Params::new(65536, 3, 4, Some(KEY_SIZE))
-        .map_err(|e| CryptoError::KeyGenerationFailed(format!("Invalid Argon2 params: {}", e)))?;
-
-    let argon2 = Argon2::new(
-        argon2::Algorithm::Argon2id,
-        argon2::Version::V0x13,
-        params,
-    );
-
-    let password_hash = argon2
+    let password_hash = argon2(MEMORY_KIB, ITERATIONS, PARALLELISM)?
         .hash_password(password.as_bytes(), &salt_string)
         .map_err(|e| CryptoError::KeyGenerationFailed(format!("Hash failed: {}", e)))?;
 
@@ -41,6 +59,133 @@ pub fn derive_key_from_password(
     Ok((key, password_hash.to_string()))
 }
 
+/// Argon2id hasher producing keys with the given costs
+fn argon2(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Argon2<'static>> {
+    let params = Params::new(memory_kib, iterations, parallelism, Some(KEY_SIZE))
+        .map_err(|e| CryptoError::KeyGenerationFailed(format!("Invalid Argon2 params: {}", e)))?;
+
+    Ok(Argon2::new(
+        argon2::Algorithm::Argon2id,
+        argon2::Version::V0x13,
+        params,
+    ))
+}
+
+/// Salt, Argon2 costs and verification data of a passphrase-derived key
+///
+/// Contains nothing that reveals the key; the verifier only tells whether a
+/// derived key is the right one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassphraseParams {
+    /// Argon2 salt (unpadded base64)
+    pub salt: String,
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of iterations
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+    /// A known value encrypted with the derived key
+    pub verifier: EncryptedData,
+}
+
+impl PassphraseParams {
+    /// Derive a key from a new passphrase, with a fresh salt
+    pub fn create(passphrase: &str) -> Result<(SecretKey, Self)> {
+        let salt = SaltString::generate(&mut OsRng).as_str().to_string();
+        let key = derive(passphrase, &salt, MEMORY_KIB, ITERATIONS, PARALLELISM)?;
+        let verifier = encrypt(&key, VERIFIER_PLAINTEXT, Some(VERIFIER_CONTEXT))?;
+
+        let params = Self {
+            salt,
+            memory_kib: MEMORY_KIB,
+            iterations: ITERATIONS,
+            parallelism: PARALLELISM,
+            verifier,
+        };
+        Ok((key, params))
+    }
+
+    /// Derive the key of a passphrase
+    ///
+    /// Fails with [`CryptoError::WrongPassphrase`] unless it is the passphrase
+    /// these parameters were created with.
+    pub fn derive(&self, passphrase: &str) -> Result<SecretKey> {
+        let key = derive(
+            passphrase,
+            &self.salt,
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+        )?;
+        match decrypt(&key, &self.verifier) {
+            Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => Ok(key),
+            _ => Err(CryptoError::WrongPassphrase),
+        }
+    }
+}
+
+/// Raw Argon2id key of a passphrase
+fn derive(
+    passphrase: &str,
+    salt: &str,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> Result<SecretKey> {
+    let salt = SaltString::from_b64(salt)
+        .map_err(|e| CryptoError::KeyGenerationFailed(format!("Invalid salt: {}", e)))?;
+    let mut bytes = vec![0u8; KEY_SIZE];
+    argon2(memory_kib, iterations, parallelism)?
+        .hash_password_into(passphrase.as_bytes(), salt.as_str().as_bytes(), &mut bytes)
+        .map_err(|e| CryptoError::KeyGenerationFailed(format!("Hash failed: {}", e)))?;
+    SecretKey::from_bytes(Algorithm::Aes256Gcm, bytes)
+}
+
+/// Whether a storage directory already has a passphrase key
+pub fn has_passphrase(dir: impl AsRef<Path>) -> bool {
+    dir.as_ref().join(PASSPHRASE_FILE).exists()
+}
+
+/// Derive the key of a storage directory from a passphrase
+///
+/// The first call sets up the directory's [`PASSPHRASE_FILE`]; later calls
+/// fail with [`CryptoError::WrongPassphrase`] for any other passphrase.
+pub fn derive_key_from_passphrase(dir: impl AsRef<Path>, passphrase: &str) -> Result<SecretKey> {
+    let path = dir.as_ref().join(PASSPHRASE_FILE);
+    let storage_error = |e: std::io::Error| {
+        CryptoError::KeyStorage(format!("{}: {}", path.display(), e))
+    };
+
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            let params: PassphraseParams = serde_json::from_slice(&bytes)
+                .map_err(|e| CryptoError::KeyStorage(format!("{}: {}", path.display(), e)))?;
+            params.derive(passphrase)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let (key, params) = PassphraseParams::create(passphrase)?;
+            let json = serde_json::to_vec_pretty(&params)
+                .map_err(|e| CryptoError::KeyStorage(e.to_string()))?;
+
+            std::fs::create_dir_all(dir.as_ref()).map_err(storage_error)?;
+            // Never replace the parameters another process just created
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(&json).map_err(storage_error)?;
+                    file.sync_all().map_err(storage_error)?;
+                    Ok(key)
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    derive_key_from_passphrase(dir, passphrase)
+                }
+                Err(e) => Err(storage_error(e)),
+            }
+        }
+        Err(e) => Err(storage_error(e)),
+    }
+}
+
 /// Verify a password against a hash
 pub fn verify_password(password: &str, hash_str: &str) -> Result<bool> {
     let parsed_hash = PasswordHash::new(hash_str)
@@ -86,4 +231,25 @@ mod tests {
 
         assert_eq!(key1.as_bytes(), key2.as_bytes());
     }
+
+    #[test]
+    fn test_passphrase_key() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(!has_passphrase(temp_dir.path()));
+
+        let key = derive_key_from_passphrase(temp_dir.path(), "correct horse").unwrap();
+        assert!(has_passphrase(temp_dir.path()));
+        let again = derive_key_from_passphrase(temp_dir.path(), "correct horse").unwrap();
+        assert_eq!(key.as_bytes(), again.as_bytes());
+
+        assert!(matches!(
+            derive_key_from_passphrase(temp_dir.path(), "wrong horse"),
+            Err(CryptoError::WrongPassphrase)
+        ));
+
+        // The stored parameters do not contain the key
+        let params = std::fs::read_to_string(temp_dir.path().join(PASSPHRASE_FILE)).unwrap();
+        assert!(!params.contains(&key.to_base64()));
+        assert!(!params.contains(&key.to_hex()));
+    }
 }
@@ -1,7 +1,15 @@
 //! Cryptographic primitives for LLM Config Manager
 //!
 //! This module provides secure encryption and decryption for sensitive configuration values
-//! using AES-256-GCM (native) or ChaCha20-Poly1305 (WASM) with envelope encryption pattern.
+//! using AES-256-GCM (native) or ChaCha20-Poly1305 (WASM) with envelope encryption pattern,
+//! and Ed25519 signatures (native only, see [`signing`]).
+//!
+//! Keys may instead select AES-256-GCM-SIV ([RFC 8452]), which stays secure if
+//! a nonce is ever repeated: a repeat only reveals whether two plaintexts
+//! encrypted under the same nonce and AAD are equal. Data records the
+//! algorithm it was encrypted with, so both can be decrypted with the same key.
+//!
+//! [RFC 8452]: https://www.rfc-editor.org/rfc/rfc8452
 
 #[cfg(feature = "ring-crypto")]
 use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
@@ -9,10 +17,10 @@ use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
 use ring::rand::{SecureRandom, SystemRandom};
 
 #[cfg(not(feature = "ring-crypto"))]
-use chacha20poly1305::{
-    aead::{Aead, KeyInit},
-    ChaCha20Poly1305, Nonce as ChaCha20Nonce,
-};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce as ChaCha20Nonce};
+
+use aes_gcm_siv::aead::{Aead, KeyInit, Payload};
+use aes_gcm_siv::{Aes256GcmSiv, Nonce as SivNonce};
 
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
@@ -20,6 +28,11 @@ use thiserror::Error;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 pub mod key_derivation;
+#[cfg(feature = "ring-crypto")]
+pub mod signing;
+
+#[cfg(feature = "ring-crypto")]
+pub use signing::{SigningKey, VerifyingKey};
 
 #[derive(Error, Debug)]
 pub enum CryptoError {
@@ -38,6 +51,18 @@ pub enum CryptoError {
     #[error("Invalid nonce length: expected {expected}, got {actual}")]
     InvalidNonceLength { expected: usize, actual: usize },
 
+    #[error("Wrong passphrase")]
+    WrongPassphrase,
+
+    #[error("Key storage error: {0}")]
+    KeyStorage(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Unknown algorithm {0:?} (expected aes-256-gcm or aes-256-gcm-siv)")]
+    InvalidAlgorithm(String),
+
     #[cfg(feature = "ring-crypto")]
     #[error("Ring error: {0}")]
     RingError(String),
@@ -59,17 +84,50 @@ pub type Result<T> = std::result::Result<T, CryptoError>;
 /// Size of AES-256 keys in bytes
 pub const KEY_SIZE: usize = 32;
 
-/// Size of AES-GCM nonces in bytes (96 bits)
+/// Size of AES-GCM and AES-GCM-SIV nonces in bytes (96 bits)
 pub const NONCE_SIZE: usize = 12;
 
 /// Size of AES-GCM authentication tag in bytes (128 bits)
 pub const TAG_SIZE: usize = 16;
 
 /// Encryption algorithm identifier
+///
+/// Both algorithms use 256-bit AES keys.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Algorithm {
     #[serde(rename = "aes-256-gcm")]
     Aes256Gcm,
+    /// Nonce-misuse-resistant AES-256-GCM-SIV
+    #[serde(rename = "aes-256-gcm-siv")]
+    Aes256GcmSiv,
+}
+
+impl Algorithm {
+    /// Name of the algorithm, as serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Aes256Gcm => "aes-256-gcm",
+            Algorithm::Aes256GcmSiv => "aes-256-gcm-siv",
+        }
+    }
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "aes-256-gcm" => Ok(Algorithm::Aes256Gcm),
+            "aes-256-gcm-siv" => Ok(Algorithm::Aes256GcmSiv),
+            other => Err(CryptoError::InvalidAlgorithm(other.to_string())),
+        }
+    }
 }
 
 impl Default for Algorithm {
@@ -90,7 +148,7 @@ impl SecretKey {
     /// Create a new secret key from bytes
     pub fn from_bytes(algorithm: Algorithm, bytes: Vec<u8>) -> Result<Self> {
         let expected_len = match algorithm {
-            Algorithm::Aes256Gcm => KEY_SIZE,
+            Algorithm::Aes256Gcm | Algorithm::Aes256GcmSiv => KEY_SIZE,
         };
 
         if bytes.len() != expected_len {
@@ -124,10 +182,19 @@ impl SecretKey {
     }
 
     /// Get the algorithm used by this key
+    ///
+    /// New data is encrypted with it; existing data is decrypted with the
+    /// algorithm it records.
     pub fn algorithm(&self) -> Algorithm {
         self.algorithm
     }
 
+    /// Use the same key bytes with another algorithm
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
     /// Get the key bytes (careful with this!)
     pub fn as_bytes(&self) -> &[u8] {
         &self.bytes
@@ -212,162 +279,146 @@ mod hex_serde {
     }
 }
 
-/// Encrypt plaintext using AES-256-GCM (native) or ChaCha20-Poly1305 (WASM)
+/// Encrypt plaintext with the key's algorithm
+///
+/// AES-256-GCM is ChaCha20-Poly1305 in WASM builds. `aad_context` is
+/// authenticated along with the ciphertext and recorded in the result.
 pub fn encrypt(
     key: &SecretKey,
     plaintext: &[u8],
     aad_context: Option<&str>,
 ) -> Result<EncryptedData> {
-    if key.algorithm() != Algorithm::Aes256Gcm {
-        return Err(CryptoError::EncryptionFailed(
-            "Only AES-256-GCM is currently supported".to_string(),
-        ));
-    }
-
-    #[cfg(feature = "ring-crypto")]
-    {
-        // Generate random nonce
-        let rng = SystemRandom::new();
-        let mut nonce_bytes = [0u8; NONCE_SIZE];
-        rng.fill(&mut nonce_bytes)?;
-
-        // Create sealing key
-        let unbound_key = UnboundKey::new(&AES_256_GCM, key.as_bytes())?;
-        let less_safe_key = LessSafeKey::new(unbound_key);
-        let nonce = Nonce::try_assume_unique_for_key(&nonce_bytes)?;
-
-        // Prepare data to encrypt
-        let mut in_out = plaintext.to_vec();
-
-        // Prepare AAD
-        let aad = match aad_context {
-            Some(ctx) => Aad::from(ctx.as_bytes()),
-            None => Aad::from(&[] as &[u8]),
-        };
-
-        // Encrypt in place
-        less_safe_key
-            .seal_in_place_append_tag(nonce, aad, &mut in_out)
-            .map_err(|e| CryptoError::EncryptionFailed(format!("{:?}", e)))?;
-
-        Ok(EncryptedData {
-            algorithm: Algorithm::Aes256Gcm,
-            nonce: nonce_bytes.to_vec(),
-            ciphertext: in_out,
-            key_version: 1,
-            aad_context: aad_context.map(String::from),
-        })
-    }
-
-    #[cfg(not(feature = "ring-crypto"))]
-    {
-        use chacha20poly1305::aead::AeadCore;
-
-        // Generate random nonce
-        let mut rng = rand::thread_rng();
-        let nonce = ChaCha20Poly1305::generate_nonce(&mut rng);
-
-        // Create cipher
-        let cipher = ChaCha20Poly1305::new_from_slice(key.as_bytes())
-            .map_err(|e| CryptoError::EncryptionFailed(format!("Invalid key: {:?}", e)))?;
-
-        // Encrypt with optional AAD
-        let ciphertext = if let Some(_ctx) = aad_context {
-            // Note: ChaCha20Poly1305 AAD would require a different API
-            // For simplicity in WASM, we'll encrypt without AAD
+    let nonce_bytes = random_nonce()?;
+    let aad = aad_context.map(str::as_bytes).unwrap_or_default();
+
+    let ciphertext = match key.algorithm() {
+        Algorithm::Aes256Gcm => seal_aes_gcm(key, &nonce_bytes, aad, plaintext)?,
+        Algorithm::Aes256GcmSiv => {
+            let cipher = Aes256GcmSiv::new_from_slice(key.as_bytes())
+                .map_err(|e| CryptoError::EncryptionFailed(format!("Invalid key: {:?}", e)))?;
             cipher
-                .encrypt(&nonce, plaintext)
+                .encrypt(SivNonce::from_slice(&nonce_bytes), Payload { msg: plaintext, aad })
                 .map_err(|e| CryptoError::EncryptionFailed(format!("{:?}", e)))?
-        } else {
-            cipher
-                .encrypt(&nonce, plaintext)
-                .map_err(|e| CryptoError::EncryptionFailed(format!("{:?}", e)))?
-        };
-
-        Ok(EncryptedData {
-            algorithm: Algorithm::Aes256Gcm,
-            nonce: nonce.to_vec(),
-            ciphertext,
-            key_version: 1,
-            aad_context: aad_context.map(String::from),
-        })
-    }
+        }
+    };
+
+    Ok(EncryptedData {
+        algorithm: key.algorithm(),
+        nonce: nonce_bytes.to_vec(),
+        ciphertext,
+        key_version: 1,
+        aad_context: aad_context.map(String::from),
+    })
 }
 
-/// Decrypt ciphertext using AES-256-GCM (native) or ChaCha20-Poly1305 (WASM)
+/// Decrypt ciphertext with the algorithm it was encrypted with
 pub fn decrypt(
     key: &SecretKey,
     encrypted: &EncryptedData,
 ) -> Result<Vec<u8>> {
-    if encrypted.algorithm != Algorithm::Aes256Gcm {
-        return Err(CryptoError::DecryptionFailed(
-            "Only AES-256-GCM is currently supported".to_string(),
-        ));
-    }
+    decrypt_with_aad(key, encrypted, encrypted.aad_context.as_deref())
+}
 
-    if key.algorithm() != Algorithm::Aes256Gcm {
-        return Err(CryptoError::DecryptionFailed(
-            "Key algorithm mismatch".to_string(),
-        ));
+/// Decrypt ciphertext, authenticating `aad_context` instead of the recorded one
+///
+/// Fails unless the data was encrypted with exactly this context, so callers
+/// can check that a ciphertext belongs where it was found.
+pub fn decrypt_with_aad(
+    key: &SecretKey,
+    encrypted: &EncryptedData,
+    aad_context: Option<&str>,
+) -> Result<Vec<u8>> {
+    if encrypted.nonce.len() != NONCE_SIZE {
+        return Err(CryptoError::InvalidNonceLength {
+            expected: NONCE_SIZE,
+            actual: encrypted.nonce.len(),
+        });
     }
-
-    #[cfg(feature = "ring-crypto")]
-    {
-        if encrypted.nonce.len() != NONCE_SIZE {
-            return Err(CryptoError::InvalidNonceLength {
-                expected: NONCE_SIZE,
-                actual: encrypted.nonce.len(),
-            });
+    let mut nonce_bytes = [0u8; NONCE_SIZE];
+    nonce_bytes.copy_from_slice(&encrypted.nonce);
+    let aad = aad_context.map(str::as_bytes).unwrap_or_default();
+
+    match encrypted.algorithm {
+        Algorithm::Aes256Gcm => open_aes_gcm(key, &nonce_bytes, aad, &encrypted.ciphertext),
+        Algorithm::Aes256GcmSiv => {
+            let cipher = Aes256GcmSiv::new_from_slice(key.as_bytes())
+                .map_err(|e| CryptoError::DecryptionFailed(format!("Invalid key: {:?}", e)))?;
+            cipher
+                .decrypt(
+                    SivNonce::from_slice(&nonce_bytes),
+                    Payload {
+                        msg: &encrypted.ciphertext,
+                        aad,
+                    },
+                )
+                .map_err(|e| CryptoError::DecryptionFailed(format!("{:?}", e)))
         }
+    }
+}
 
-        // Create opening key
-        let unbound_key = UnboundKey::new(&AES_256_GCM, key.as_bytes())?;
-        let less_safe_key = LessSafeKey::new(unbound_key);
-        let mut nonce_bytes = [0u8; NONCE_SIZE];
-        nonce_bytes.copy_from_slice(&encrypted.nonce);
-        let nonce = Nonce::try_assume_unique_for_key(&nonce_bytes)?;
-
-        // Prepare data to decrypt
-        let mut in_out = encrypted.ciphertext.clone();
-
-        // Prepare AAD
-        let aad = match &encrypted.aad_context {
-            Some(ctx) => Aad::from(ctx.as_bytes()),
-            None => Aad::from(&[] as &[u8]),
-        };
-
-        // Decrypt in place
-        let plaintext = less_safe_key
-            .open_in_place(nonce, aad, &mut in_out)
-            .map_err(|e| CryptoError::DecryptionFailed(format!("{:?}", e)))?;
+/// Generate a random nonce
+fn random_nonce() -> Result<[u8; NONCE_SIZE]> {
+    let mut nonce_bytes = [0u8; NONCE_SIZE];
 
-        Ok(plaintext.to_vec())
-    }
+    #[cfg(feature = "ring-crypto")]
+    SystemRandom::new().fill(&mut nonce_bytes)?;
 
     #[cfg(not(feature = "ring-crypto"))]
-    {
-        // ChaCha20Poly1305 nonce is 12 bytes (96 bits)
-        if encrypted.nonce.len() != 12 {
-            return Err(CryptoError::InvalidNonceLength {
-                expected: 12,
-                actual: encrypted.nonce.len(),
-            });
-        }
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
 
-        // Create cipher
-        let cipher = ChaCha20Poly1305::new_from_slice(key.as_bytes())
-            .map_err(|e| CryptoError::DecryptionFailed(format!("Invalid key: {:?}", e)))?;
+    Ok(nonce_bytes)
+}
 
-        // Create nonce from bytes
-        let nonce = ChaCha20Nonce::from_slice(&encrypted.nonce);
+/// Encrypt with AES-256-GCM
+#[cfg(feature = "ring-crypto")]
+fn seal_aes_gcm(key: &SecretKey, nonce_bytes: &[u8; NONCE_SIZE], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    // Create sealing key
+    let unbound_key = UnboundKey::new(&AES_256_GCM, key.as_bytes())?;
+    let less_safe_key = LessSafeKey::new(unbound_key);
+    let nonce = Nonce::assume_unique_for_key(*nonce_bytes);
+
+    // Encrypt in place
+    let mut in_out = plaintext.to_vec();
+    less_safe_key
+        .seal_in_place_append_tag(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|e| CryptoError::EncryptionFailed(format!("{:?}", e)))?;
+    Ok(in_out)
+}
 
-        // Decrypt
-        let plaintext = cipher
-            .decrypt(nonce, encrypted.ciphertext.as_ref())
-            .map_err(|e| CryptoError::DecryptionFailed(format!("{:?}", e)))?;
+/// Decrypt with AES-256-GCM
+#[cfg(feature = "ring-crypto")]
+fn open_aes_gcm(key: &SecretKey, nonce_bytes: &[u8; NONCE_SIZE], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    // Create opening key
+    let unbound_key = UnboundKey::new(&AES_256_GCM, key.as_bytes())?;
+    let less_safe_key = LessSafeKey::new(unbound_key);
+    let nonce = Nonce::assume_unique_for_key(*nonce_bytes);
+
+    // Decrypt in place
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = less_safe_key
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|e| CryptoError::DecryptionFailed(format!("{:?}", e)))?;
+    Ok(plaintext.to_vec())
+}
 
-        Ok(plaintext)
-    }
+/// Encrypt with ChaCha20-Poly1305, standing in for AES-256-GCM in WASM builds
+#[cfg(not(feature = "ring-crypto"))]
+fn seal_aes_gcm(key: &SecretKey, nonce_bytes: &[u8; NONCE_SIZE], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new_from_slice(key.as_bytes())
+        .map_err(|e| CryptoError::EncryptionFailed(format!("Invalid key: {:?}", e)))?;
+    cipher
+        .encrypt(ChaCha20Nonce::from_slice(nonce_bytes), Payload { msg: plaintext, aad })
+        .map_err(|e| CryptoError::EncryptionFailed(format!("{:?}", e)))
+}
+
+/// Decrypt with ChaCha20-Poly1305, standing in for AES-256-GCM in WASM builds
+#[cfg(not(feature = "ring-crypto"))]
+fn open_aes_gcm(key: &SecretKey, nonce_bytes: &[u8; NONCE_SIZE], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new_from_slice(key.as_bytes())
+        .map_err(|e| CryptoError::DecryptionFailed(format!("Invalid key: {:?}", e)))?;
+    cipher
+        .decrypt(ChaCha20Nonce::from_slice(nonce_bytes), Payload { msg: ciphertext, aad })
+        .map_err(|e| CryptoError::DecryptionFailed(format!("{:?}", e)))
 }
 
 #[cfg(test)]
@@ -421,6 +472,40 @@ mod tests {
         assert_eq!(decrypted, plaintext);
     }
 
+    #[test]
+    fn test_decrypt_with_other_aad_fails() {
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let encrypted = encrypt(&key, b"Secret data", Some("app/api_key/production")).unwrap();
+
+        assert_eq!(decrypt_with_aad(&key, &encrypted, Some("app/api_key/production")).unwrap(), b"Secret data");
+        assert!(decrypt_with_aad(&key, &encrypted, Some("app/other_key/production")).is_err());
+        assert!(decrypt_with_aad(&key, &encrypted, None).is_err());
+    }
+
+    #[test]
+    fn test_aes_gcm_siv() {
+        let key = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
+        let siv_key = key.clone().with_algorithm(Algorithm::Aes256GcmSiv);
+
+        let encrypted = encrypt(&siv_key, b"Secret data", Some("context")).unwrap();
+        assert_eq!(encrypted.algorithm, Algorithm::Aes256GcmSiv);
+        assert_eq!(encrypted.nonce.len(), NONCE_SIZE);
+        assert_eq!(encrypted.ciphertext.len(), b"Secret data".len() + TAG_SIZE);
+
+        // Data records its algorithm, so either mode of the key decrypts it
+        assert_eq!(decrypt(&key, &encrypted).unwrap(), b"Secret data");
+        assert_eq!(decrypt(&siv_key, &encrypted).unwrap(), b"Secret data");
+        assert!(decrypt_with_aad(&key, &encrypted, Some("other")).is_err());
+
+        let json = serde_json::to_string(&encrypted).unwrap();
+        assert!(json.contains("\"aes-256-gcm-siv\""));
+        assert_eq!("aes-256-gcm-siv".parse::<Algorithm>().unwrap(), Algorithm::Aes256GcmSiv);
+        assert!(matches!(
+            "aes-128-cbc".parse::<Algorithm>(),
+            Err(CryptoError::InvalidAlgorithm(name)) if name == "aes-128-cbc"
+        ));
+    }
+
     #[test]
     fn test_wrong_key_fails() {
         let key1 = SecretKey::generate(Algorithm::Aes256Gcm).unwrap();
@@ -0,0 +1,177 @@
+//! Ed25519 signatures
+//!
+//! A [`SigningKey`] is kept as its 32-byte seed, encoded like a [`SecretKey`]
+//! (hex or base64). Its [`VerifyingKey`] is published to whoever verifies the
+//! signatures, and identified by a short [key ID](VerifyingKey::key_id).
+//!
+//! [`SecretKey`]: crate::SecretKey
+
+use crate::{CryptoError, Result};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Size of Ed25519 seeds and public keys in bytes
+pub const SIGNING_KEY_SIZE: usize = 32;
+
+/// Size of Ed25519 signatures in bytes
+pub const SIGNATURE_SIZE: usize = 64;
+
+/// Ed25519 private key, zeroed when dropped
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct SigningKey {
+    seed: Vec<u8>,
+}
+
+impl SigningKey {
+    /// Create a signing key from its 32-byte seed
+    pub fn from_bytes(seed: Vec<u8>) -> Result<Self> {
+        if seed.len() != SIGNING_KEY_SIZE {
+            return Err(CryptoError::InvalidKeyLength {
+                expected: SIGNING_KEY_SIZE,
+                actual: seed.len(),
+            });
+        }
+        Ok(Self { seed })
+    }
+
+    /// Generate a new random signing key
+    pub fn generate() -> Result<Self> {
+        let mut seed = vec![0u8; SIGNING_KEY_SIZE];
+        SystemRandom::new()
+            .fill(&mut seed)
+            .map_err(|e| CryptoError::KeyGenerationFailed(format!("{:?}", e)))?;
+        Ok(Self { seed })
+    }
+
+    /// Create from hex string
+    pub fn from_hex(hex_str: &str) -> Result<Self> {
+        let seed = hex::decode(hex_str)
+            .map_err(|e| CryptoError::KeyGenerationFailed(format!("Invalid hex: {}", e)))?;
+        Self::from_bytes(seed)
+    }
+
+    /// Create from base64 string
+    pub fn from_base64(b64_str: &str) -> Result<Self> {
+        use base64::Engine;
+        let seed = base64::engine::general_purpose::STANDARD
+            .decode(b64_str)
+            .map_err(|e| CryptoError::KeyGenerationFailed(format!("Invalid base64: {}", e)))?;
+        Self::from_bytes(seed)
+    }
+
+    /// Convert to base64 string
+    pub fn to_base64(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(&self.seed)
+    }
+
+    fn key_pair(&self) -> Result<Ed25519KeyPair> {
+        Ed25519KeyPair::from_seed_unchecked(&self.seed)
+            .map_err(|e| CryptoError::KeyGenerationFailed(format!("Invalid Ed25519 seed: {}", e)))
+    }
+
+    /// The public key that verifies this key's signatures
+    pub fn verifying_key(&self) -> Result<VerifyingKey> {
+        Ok(VerifyingKey {
+            bytes: self.key_pair()?.public_key().as_ref().to_vec(),
+        })
+    }
+
+    /// Sign a message
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.key_pair()?.sign(message).as_ref().to_vec())
+    }
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("seed", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Ed25519 public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyingKey {
+    bytes: Vec<u8>,
+}
+
+impl VerifyingKey {
+    /// Create a verifying key from its 32 bytes
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() != SIGNING_KEY_SIZE {
+            return Err(CryptoError::InvalidKeyLength {
+                expected: SIGNING_KEY_SIZE,
+                actual: bytes.len(),
+            });
+        }
+        Ok(Self { bytes })
+    }
+
+    /// Create from base64 string
+    pub fn from_base64(b64_str: &str) -> Result<Self> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(b64_str)
+            .map_err(|e| CryptoError::KeyStorage(format!("Invalid base64: {}", e)))?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Convert to base64 string
+    pub fn to_base64(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(&self.bytes)
+    }
+
+    /// Get the key bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Short identifier of the key: the first 8 bytes of its SHA-256, in hex
+    pub fn key_id(&self) -> String {
+        hex::encode(&digest(&SHA256, &self.bytes).as_ref()[..8])
+    }
+
+    /// Check a signature over a message
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        UnparsedPublicKey::new(&ED25519, &self.bytes)
+            .verify(message, signature)
+            .map_err(|_| CryptoError::InvalidSignature(format!("not signed by key {}", self.key_id())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = SigningKey::generate().unwrap();
+        let verifying_key = key.verifying_key().unwrap();
+
+        let signature = key.sign(b"payload").unwrap();
+        assert_eq!(signature.len(), SIGNATURE_SIZE);
+        assert!(verifying_key.verify(b"payload", &signature).is_ok());
+        assert!(verifying_key.verify(b"tampered", &signature).is_err());
+
+        let other = SigningKey::generate().unwrap().verifying_key().unwrap();
+        assert!(other.verify(b"payload", &signature).is_err());
+        assert_ne!(other.key_id(), verifying_key.key_id());
+    }
+
+    #[test]
+    fn test_key_encoding_round_trip() {
+        let key = SigningKey::generate().unwrap();
+        let restored = SigningKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(restored.verifying_key().unwrap(), key.verifying_key().unwrap());
+
+        let verifying_key = key.verifying_key().unwrap();
+        assert_eq!(VerifyingKey::from_base64(&verifying_key.to_base64()).unwrap(), verifying_key);
+        assert_eq!(verifying_key.key_id().len(), 16);
+        assert!(SigningKey::from_bytes(vec![0; 16]).is_err());
+    }
+}
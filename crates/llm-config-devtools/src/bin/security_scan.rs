@@ -2,11 +2,15 @@
 //!
 //! Runs comprehensive security scans on Rust projects.
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::Colorize;
+use llm_config_devtools::report::compare::{self, ReportComparison};
 use llm_config_devtools::report::{generate_report, write_report, OutputFormat};
+use llm_config_devtools::security::baseline::{Baseline, BASELINE_FILE};
+use llm_config_devtools::security::license::LicensePolicy;
+use llm_config_devtools::security::secret::SecretScanConfig;
 use llm_config_devtools::security::{ScanConfig, SecurityScanner};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use tracing_subscriber::EnvFilter;
 
@@ -14,7 +18,11 @@ use tracing_subscriber::EnvFilter;
 #[command(name = "llm-security-scan")]
 #[command(about = "Security scanner for LLM Config Manager", long_about = None)]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Project root directory to scan
     #[arg(short, long, default_value = ".")]
     project: PathBuf,
@@ -23,7 +31,7 @@ struct Cli {
     #[arg(short, long)]
     output: Option<PathBuf>,
 
-    /// Output format (json, yaml, markdown, sarif)
+    /// Output format (json, yaml, markdown, sarif, html)
     #[arg(short, long, default_value = "markdown")]
     format: String,
 
@@ -39,10 +47,50 @@ struct Cli {
     #[arg(long)]
     no_secrets: bool,
 
+    /// Exclude paths matching a gitignore-style glob from secret scanning; repeatable
+    #[arg(long = "exclude-secrets", value_name = "GLOB")]
+    exclude_secrets: Vec<String>,
+
+    /// Disable detection of high-entropy strings in secret scanning
+    #[arg(long)]
+    no_entropy: bool,
+
     /// Disable SQL injection scanning
     #[arg(long)]
     no_sql: bool,
 
+    /// Disable the dependency vulnerability audit
+    #[arg(long)]
+    no_dependencies: bool,
+
+    /// RustSec advisory database checkout (fetched into ~/.cargo/advisory-db if not given)
+    #[arg(long)]
+    advisory_db: Option<PathBuf>,
+
+    /// Disable license compliance scanning
+    #[arg(long)]
+    no_licenses: bool,
+
+    /// Allow a license (SPDX id) in addition to the default permissive ones; repeatable
+    #[arg(long = "allow-license", value_name = "SPDX_ID")]
+    allow_licenses: Vec<String>,
+
+    /// Report a license (SPDX id) as high severity; repeatable
+    #[arg(long = "deny-license", value_name = "SPDX_ID")]
+    deny_licenses: Vec<String>,
+
+    /// Baseline of accepted findings, relative to the project root
+    #[arg(long, default_value = BASELINE_FILE)]
+    baseline: PathBuf,
+
+    /// Report all findings, ignoring the baseline
+    #[arg(long, conflicts_with = "update_baseline")]
+    no_baseline: bool,
+
+    /// Accept all current findings by writing them to the baseline
+    #[arg(long)]
+    update_baseline: bool,
+
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
@@ -52,6 +100,30 @@ struct Cli {
     fail_on_high: bool,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Compare two JSON reports and show new, fixed and persisting findings
+    Compare {
+        /// Report of the earlier scan
+        old: PathBuf,
+
+        /// Report of the later scan
+        new: PathBuf,
+
+        /// Output file path (prints to stdout if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format (json, yaml, markdown, html)
+        #[arg(short, long, default_value = "markdown")]
+        format: String,
+
+        /// Fail with non-zero exit code if new high severity findings are found
+        #[arg(long)]
+        fail_on_new_high: bool,
+    },
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -67,14 +139,18 @@ fn main() {
         .with_target(false)
         .init();
 
-    // Parse output format
-    let format = match cli.format.parse::<OutputFormat>() {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            process::exit(1);
-        }
-    };
+    if let Some(Command::Compare {
+        old,
+        new,
+        output,
+        format,
+        fail_on_new_high,
+    }) = cli.command
+    {
+        run_compare(&old, &new, output.as_deref(), &format, fail_on_new_high);
+    }
+
+    let format = parse_format(&cli.format);
 
     // Create scan configuration
     let config = ScanConfig {
@@ -82,7 +158,20 @@ fn main() {
         scan_clippy: !cli.no_clippy,
         scan_unsafe: !cli.no_unsafe,
         scan_secrets: !cli.no_secrets,
+        secrets: SecretScanConfig {
+            exclude: SecretScanConfig::default().exclude.into_iter().chain(cli.exclude_secrets).collect(),
+            entropy: !cli.no_entropy,
+            ..SecretScanConfig::default()
+        },
         scan_sql: !cli.no_sql,
+        scan_dependencies: !cli.no_dependencies,
+        advisory_db: cli.advisory_db.clone(),
+        scan_licenses: !cli.no_licenses,
+        licenses: LicensePolicy {
+            allow: LicensePolicy::default().allow.into_iter().chain(cli.allow_licenses).collect(),
+            deny: cli.deny_licenses,
+        },
+        baseline: (!cli.no_baseline && !cli.update_baseline).then(|| cli.baseline.clone()),
         max_workers: None,
     };
 
@@ -97,7 +186,7 @@ fn main() {
     println!();
 
     let scanner = SecurityScanner::new(config);
-    let report = match scanner.scan() {
+    let mut report = match scanner.scan() {
         Ok(r) => r,
         Err(e) => {
             eprintln!("{} {}", "Error:".red().bold(), e);
@@ -105,6 +194,23 @@ fn main() {
         }
     };
 
+    if cli.update_baseline {
+        let path = cli.project.join(&cli.baseline);
+        let baseline = Baseline::from_findings(&report.findings);
+        if let Err(e) = baseline.save(&path) {
+            eprintln!("{} {}", "Error writing baseline:".red().bold(), e);
+            process::exit(1);
+        }
+        println!(
+            "{} {} ({} findings accepted)",
+            "Baseline written to:".green().bold(),
+            path.display(),
+            baseline.findings.len()
+        );
+        println!();
+        report.apply_baseline(&baseline);
+    }
+
     // Print summary
     print_summary(&report);
 
@@ -169,6 +275,63 @@ fn main() {
     process::exit(0);
 }
 
+fn parse_format(format: &str) -> OutputFormat {
+    match format.parse::<OutputFormat>() {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_compare(old: &Path, new: &Path, output: Option<&Path>, format: &str, fail_on_new_high: bool) -> ! {
+    let format = parse_format(format);
+    let (old_report, new_report) = match (compare::load_report(old), compare::load_report(new)) {
+        (Ok(old_report), Ok(new_report)) => (old_report, new_report),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            process::exit(1);
+        }
+    };
+
+    let comparison = ReportComparison::new(&old_report, &new_report);
+    println!("{}", "Comparison:".blue().bold());
+    println!("  {} {}", "New:".dimmed(), comparison.new_findings.len().to_string().red());
+    println!("  {} {}", "Fixed:".dimmed(), comparison.fixed_findings.len().to_string().green());
+    println!("  {} {}", "Persisting:".dimmed(), comparison.persisting_findings.len());
+    println!();
+
+    let content = match compare::generate(&comparison, format) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{} {}", "Error generating comparison:".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, content) {
+                eprintln!("{} {}", "Error writing comparison:".red().bold(), e);
+                process::exit(1);
+            }
+            println!("{} {}", "Comparison written to:".green().bold(), path.display());
+        }
+        None => println!("{}", content),
+    }
+
+    if fail_on_new_high && comparison.has_new_high_severity() {
+        eprintln!();
+        eprintln!(
+            "{} {}",
+            "✗".red().bold(),
+            "New high severity findings detected".red()
+        );
+        process::exit(1);
+    }
+    process::exit(0);
+}
+
 fn print_summary(report: &llm_config_devtools::security::SecurityReport) {
     println!("{}", "Summary:".blue().bold());
     println!(
@@ -184,6 +347,13 @@ fn print_summary(report: &llm_config_devtools::security::SecurityReport) {
     println!("  {} {}", "High:".dimmed(), report.summary.high.to_string().bright_red());
     println!("  {} {}", "Medium:".dimmed(), report.summary.medium.to_string().yellow());
     println!("  {} {}", "Low:".dimmed(), report.summary.low.to_string().blue());
+    if report.summary.suppressed > 0 {
+        println!(
+            "  {} {}",
+            "Suppressed:".dimmed(),
+            report.summary.suppressed.to_string().dimmed()
+        );
+    }
     println!(
         "  {} {:.2}s",
         "Duration:".dimmed(),
@@ -57,6 +57,10 @@ pub enum DevtoolsError {
     #[error("Report generation failed: {0}")]
     ReportGeneration(String),
 
+    /// Reading stored configuration failed.
+    #[error("Config manager error: {0}")]
+    ConfigManager(#[from] llm_config_core::ConfigError),
+
     /// Git repository error.
     #[error("Git error: {0}")]
     Git(#[from] git2::Error),
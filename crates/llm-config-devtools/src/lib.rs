@@ -10,12 +10,17 @@
 //!   - Unsafe code detection
 //!   - Secret scanning
 //!   - SQL injection vulnerability detection
+//!   - Dependency vulnerability audit (`RustSec` advisory database)
+//!   - Dependency license compliance (allow/deny lists)
 //!
 //! - **Report Generation**: Multiple output formats
 //!   - JSON (machine-readable)
 //!   - YAML (human-readable structured)
 //!   - Markdown (documentation)
 //!   - SARIF (GitHub Security integration)
+//!   - HTML (self-contained, with charts and filtering)
+//!
+//! - **Report Comparison**: New, fixed and persisting findings between two scans
 //!
 //! # Usage
 //!
@@ -32,7 +37,13 @@
 //!     scan_clippy: true,
 //!     scan_unsafe: true,
 //!     scan_secrets: true,
+//!     secrets: Default::default(),
 //!     scan_sql: true,
+//!     scan_dependencies: true,
+//!     advisory_db: None,
+//!     scan_licenses: true,
+//!     licenses: Default::default(),
+//!     baseline: None,
 //!     max_workers: None,
 //! };
 //!
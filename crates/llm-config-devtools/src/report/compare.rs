@@ -0,0 +1,210 @@
+//! Comparison of two scan reports.
+//!
+//! Findings are matched by their baseline fingerprint (file, category and
+//! snippet), so a finding that only moved to another line persists rather
+//! than being reported as fixed and new.
+
+use crate::error::{DevtoolsError, Result};
+use crate::report::{html, OutputFormat};
+use crate::security::baseline::fingerprint;
+use crate::security::{Finding, SecurityReport, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Differences between an earlier and a later scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportComparison {
+    /// The earlier report (without its findings).
+    pub old: SecurityReport,
+
+    /// The later report (without its findings).
+    pub new: SecurityReport,
+
+    /// Findings only in the later report.
+    pub new_findings: Vec<Finding>,
+
+    /// Findings only in the earlier report.
+    pub fixed_findings: Vec<Finding>,
+
+    /// Findings in both reports, as found by the later scan.
+    pub persisting_findings: Vec<Finding>,
+}
+
+impl ReportComparison {
+    /// Compare an earlier report with a later one.
+    #[must_use]
+    pub fn new(old: &SecurityReport, new: &SecurityReport) -> Self {
+        let mut remaining: HashMap<String, Vec<&Finding>> = HashMap::new();
+        for finding in old.findings.iter().rev() {
+            remaining.entry(fingerprint(finding)).or_default().push(finding);
+        }
+
+        let (mut new_findings, mut persisting_findings) = (Vec::new(), Vec::new());
+        for finding in &new.findings {
+            match remaining.get_mut(&fingerprint(finding)).and_then(Vec::pop) {
+                Some(_) => persisting_findings.push(finding.clone()),
+                None => new_findings.push(finding.clone()),
+            }
+        }
+
+        let fixed_findings = old
+            .findings
+            .iter()
+            .filter(|finding| {
+                remaining
+                    .get(&fingerprint(finding))
+                    .is_some_and(|left| left.iter().any(|f| std::ptr::eq(*f, *finding)))
+            })
+            .cloned()
+            .collect();
+
+        Self {
+            old: SecurityReport { findings: Vec::new(), ..old.clone() },
+            new: SecurityReport { findings: Vec::new(), ..new.clone() },
+            new_findings,
+            fixed_findings,
+            persisting_findings,
+        }
+    }
+
+    /// Check if the later scan found any new critical or high severity findings.
+    #[must_use]
+    pub fn has_new_high_severity(&self) -> bool {
+        self.new_findings.iter().any(|f| f.severity >= Severity::High)
+    }
+}
+
+/// Load a report written in the JSON format.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is not a JSON report.
+pub fn load_report(path: &Path) -> Result<SecurityReport> {
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map_err(|e| DevtoolsError::Deserialization(format!("{}: {}", path.display(), e)))
+}
+
+/// Generate a comparison in the specified format.
+///
+/// # Errors
+///
+/// Returns an error for the SARIF format, which has no notion of fixed
+/// findings, or if serialization fails.
+pub fn generate(comparison: &ReportComparison, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(comparison)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(comparison)?),
+        OutputFormat::Markdown => Ok(markdown(comparison)),
+        OutputFormat::Html => Ok(html::generate_comparison(comparison)),
+        OutputFormat::Sarif => Err(DevtoolsError::Config(
+            "SARIF output is not supported for comparisons".to_string(),
+        )),
+    }
+}
+
+fn markdown(comparison: &ReportComparison) -> String {
+    let mut md = String::from("# Security Scan Comparison\n\n");
+    for (label, report) in [("Before", &comparison.old), ("After", &comparison.new)] {
+        let _ = write!(
+            md,
+            "**{label}**: {}",
+            report.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        if let Some(commit) = &report.git_commit {
+            let _ = write!(md, " (`{commit}`)");
+        }
+        md.push_str("  \n");
+    }
+
+    let _ = write!(
+        md,
+        "\n## Summary\n\n- **New**: {}\n- **Fixed**: {}\n- **Persisting**: {}\n\n",
+        comparison.new_findings.len(),
+        comparison.fixed_findings.len(),
+        comparison.persisting_findings.len()
+    );
+
+    for (heading, findings) in [
+        ("New Findings", &comparison.new_findings),
+        ("Fixed Findings", &comparison.fixed_findings),
+        ("Persisting Findings", &comparison.persisting_findings),
+    ] {
+        if findings.is_empty() {
+            continue;
+        }
+        let _ = write!(md, "## {heading}\n\n| Severity | Category | Location | Finding |\n|---|---|---|---|\n");
+        for finding in findings {
+            let mut location = finding.file.display().to_string();
+            if let Some(line) = finding.line {
+                let _ = write!(location, ":{line}");
+            }
+            let _ = writeln!(
+                md,
+                "| {} | `{}` | `{location}` | {} |",
+                finding.severity,
+                finding.category,
+                finding.title.replace('|', "\\|")
+            );
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn finding(line: usize, snippet: &str, severity: Severity) -> Finding {
+        Finding {
+            severity,
+            category: "secret".to_string(),
+            title: "Potential hardcoded API Key".to_string(),
+            message: "Found a potential hardcoded secret".to_string(),
+            file: PathBuf::from("src/config.rs"),
+            line: Some(line),
+            column: None,
+            snippet: Some(snippet.to_string()),
+            recommendation: None,
+        }
+    }
+
+    #[test]
+    fn test_report_comparison() {
+        let old = SecurityReport::new(
+            PathBuf::from("."),
+            vec![
+                finding(3, "api_key = \"****\"", Severity::Critical),
+                finding(9, "token = \"****\"", Severity::High),
+            ],
+        );
+        let new = SecurityReport::new(
+            PathBuf::from("."),
+            vec![
+                finding(5, "api_key = \"****\"", Severity::Critical),
+                finding(12, "password = \"****\"", Severity::Low),
+            ],
+        );
+
+        let comparison = ReportComparison::new(&old, &new);
+        assert_eq!(comparison.persisting_findings.len(), 1);
+        assert_eq!(comparison.persisting_findings[0].line, Some(5));
+        assert_eq!(comparison.fixed_findings.len(), 1);
+        assert_eq!(comparison.fixed_findings[0].line, Some(9));
+        assert_eq!(comparison.new_findings.len(), 1);
+        assert!(!comparison.has_new_high_severity());
+
+        let md = generate(&comparison, OutputFormat::Markdown).unwrap();
+        assert!(md.contains("- **New**: 1\n- **Fixed**: 1\n- **Persisting**: 1"));
+        assert!(md.contains("`src/config.rs:12`"));
+        let html = generate(&comparison, OutputFormat::Html).unwrap();
+        assert!(html.contains("<td class=\"fixed\">fixed</td>"));
+        assert!(generate(&comparison, OutputFormat::Sarif).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! HTML report generator.
+//!
+//! Produces a single self-contained page (inline CSS, SVG and script, no
+//! external assets) with summary charts and a findings table that can be
+//! filtered by severity, category and text.
+
+use crate::error::Result;
+use crate::report::compare::ReportComparison;
+use crate::security::{Finding, SecurityReport, Severity};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { margin-bottom: 0.25rem; }
+.meta { color: #656d76; margin-bottom: 1.5rem; }
+.cards { display: flex; flex-wrap: wrap; gap: 1rem; margin-bottom: 1.5rem; }
+.card { border: 1px solid #d0d7de; border-radius: 6px; padding: 0.75rem 1.25rem; min-width: 7rem; }
+.card .value { font-size: 1.75rem; font-weight: 600; }
+.charts { display: flex; flex-wrap: wrap; gap: 2rem; margin-bottom: 1.5rem; }
+.filters { display: flex; flex-wrap: wrap; gap: 0.5rem; margin-bottom: 0.75rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border-bottom: 1px solid #d0d7de; padding: 0.4rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+code { font-size: 0.85em; white-space: pre-wrap; }
+.sev { font-weight: 600; border-radius: 4px; padding: 0.1rem 0.4rem; color: #fff; }
+.critical { background: #b91c1c; } .high { background: #ea580c; }
+.medium { background: #ca8a04; } .low { background: #16a34a; }
+.new { color: #b91c1c; font-weight: 600; } .fixed { color: #16a34a; font-weight: 600; }
+.persisting { color: #656d76; }
+";
+
+const SCRIPT: &str = "
+function applyFilters() {
+  const values = {};
+  document.querySelectorAll('[data-filter]').forEach(el => values[el.dataset.filter] = el.value.toLowerCase());
+  document.querySelectorAll('#findings tbody tr').forEach(row => {
+    const visible = Object.entries(values).every(([key, value]) =>
+      value === '' || (key === 'text'
+        ? row.textContent.toLowerCase().includes(value)
+        : row.dataset[key] === value));
+    row.style.display = visible ? '' : 'none';
+  });
+}
+document.querySelectorAll('[data-filter]').forEach(el => el.addEventListener('input', applyFilters));
+";
+
+/// Colors of the severity bars, lowest severity first.
+const SEVERITY_COLORS: [&str; 4] = ["#16a34a", "#ca8a04", "#ea580c", "#b91c1c"];
+
+/// Generate an HTML report.
+///
+/// # Errors
+///
+/// Never fails; returns a `Result` like the other generators.
+pub fn generate(report: &SecurityReport) -> Result<String> {
+    let mut body = String::new();
+    let _ = write!(body, "<h1>Security Scan Report</h1>\n<div class=\"meta\">{}</div>\n", meta(report));
+
+    body.push_str("<div class=\"cards\">\n");
+    card(&mut body, "Total", report.summary.total);
+    card(&mut body, "Critical", report.summary.critical);
+    card(&mut body, "High", report.summary.high);
+    card(&mut body, "Medium", report.summary.medium);
+    card(&mut body, "Low", report.summary.low);
+    if report.summary.suppressed > 0 {
+        card(&mut body, "Suppressed by baseline", report.summary.suppressed);
+    }
+    body.push_str("</div>\n");
+
+    let findings: Vec<_> = report.findings.iter().map(|f| (None, f)).collect();
+    charts(&mut body, &findings);
+    findings_table(&mut body, &findings);
+
+    Ok(page("Security Scan Report", &body))
+}
+
+/// Generate an HTML page comparing two reports.
+pub(crate) fn generate_comparison(comparison: &ReportComparison) -> String {
+    let mut body = String::new();
+    let _ = write!(
+        body,
+        "<h1>Security Scan Comparison</h1>\n<div class=\"meta\">{} &rarr; {}</div>\n",
+        meta(&comparison.old),
+        meta(&comparison.new)
+    );
+
+    body.push_str("<div class=\"cards\">\n");
+    card(&mut body, "New", comparison.new_findings.len());
+    card(&mut body, "Fixed", comparison.fixed_findings.len());
+    card(&mut body, "Persisting", comparison.persisting_findings.len());
+    body.push_str("</div>\n");
+
+    let findings: Vec<_> = comparison
+        .new_findings
+        .iter()
+        .map(|f| (Some("new"), f))
+        .chain(comparison.fixed_findings.iter().map(|f| (Some("fixed"), f)))
+        .chain(comparison.persisting_findings.iter().map(|f| (Some("persisting"), f)))
+        .collect();
+    charts(&mut body, &findings);
+    findings_table(&mut body, &findings);
+
+    page("Security Scan Comparison", &body)
+}
+
+/// Timestamp, project and git revision of a report.
+fn meta(report: &SecurityReport) -> String {
+    let mut meta = format!(
+        "{} &middot; {}",
+        report.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+        escape(&report.project_root.display().to_string())
+    );
+    if let Some(branch) = &report.git_branch {
+        let _ = write!(meta, " &middot; {}", escape(branch));
+    }
+    if let Some(commit) = &report.git_commit {
+        let _ = write!(meta, " <code>{}</code>", escape(commit.get(..12).unwrap_or(commit)));
+    }
+    meta
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n{body}\
+         <script>{SCRIPT}</script>\n</body>\n</html>\n"
+    )
+}
+
+fn card(html: &mut String, label: &str, value: usize) {
+    let _ = writeln!(
+        html,
+        "<div class=\"card\"><div>{label}</div><div class=\"value\">{value}</div></div>"
+    );
+}
+
+/// Bar charts of findings by severity and by category.
+fn charts(html: &mut String, findings: &[(Option<&str>, &Finding)]) {
+    let by_severity: Vec<_> = [Severity::Critical, Severity::High, Severity::Medium, Severity::Low]
+        .iter()
+        .map(|severity| {
+            let count = findings.iter().filter(|(_, f)| f.severity == *severity).count();
+            (severity.to_string(), count, SEVERITY_COLORS[*severity as usize])
+        })
+        .collect();
+
+    let mut categories: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, finding) in findings {
+        *categories.entry(finding.category.as_str()).or_default() += 1;
+    }
+    let by_category: Vec<_> = categories
+        .into_iter()
+        .map(|(category, count)| (category.to_string(), count, "#0969da"))
+        .collect();
+
+    html.push_str("<div class=\"charts\">\n");
+    bar_chart(html, "By severity", &by_severity);
+    if !by_category.is_empty() {
+        bar_chart(html, "By category", &by_category);
+    }
+    html.push_str("</div>\n");
+}
+
+/// Horizontal SVG bar chart of labelled counts.
+fn bar_chart(html: &mut String, title: &str, bars: &[(String, usize, &str)]) {
+    const LABEL_WIDTH: usize = 170;
+    const BAR_WIDTH: usize = 260;
+    const ROW_HEIGHT: usize = 24;
+
+    let max = bars.iter().map(|(_, count, _)| *count).max().unwrap_or(0).max(1);
+    let height = bars.len() * ROW_HEIGHT;
+    let _ = writeln!(
+        html,
+        "<div><h3>{title}</h3><svg width=\"{}\" height=\"{height}\" role=\"img\">",
+        LABEL_WIDTH + BAR_WIDTH + 50
+    );
+    for (i, (label, count, color)) in bars.iter().enumerate() {
+        let y = i * ROW_HEIGHT;
+        let width = count * BAR_WIDTH / max;
+        let _ = writeln!(
+            html,
+            "<text x=\"0\" y=\"{}\" font-size=\"13\">{}</text>\
+             <rect x=\"{LABEL_WIDTH}\" y=\"{}\" width=\"{width}\" height=\"16\" fill=\"{color}\"/>\
+             <text x=\"{}\" y=\"{}\" font-size=\"13\">{count}</text>",
+            y + 16,
+            escape(label),
+            y + 3,
+            LABEL_WIDTH + width + 6,
+            y + 16
+        );
+    }
+    html.push_str("</svg></div>\n");
+}
+
+/// Findings table with severity, category, change (if any) and text filters.
+fn findings_table(html: &mut String, findings: &[(Option<&str>, &Finding)]) {
+    let with_change = findings.iter().any(|(change, _)| change.is_some());
+    let categories: std::collections::BTreeSet<&str> =
+        findings.iter().map(|(_, f)| f.category.as_str()).collect();
+
+    html.push_str("<h2>Findings</h2>\n<div class=\"filters\">\n");
+    html.push_str("<select data-filter=\"severity\"><option value=\"\">All severities</option>");
+    for severity in ["critical", "high", "medium", "low"] {
+        let _ = write!(html, "<option value=\"{severity}\">{severity}</option>");
+    }
+    html.push_str("</select>\n<select data-filter=\"category\"><option value=\"\">All categories</option>");
+    for category in categories {
+        let category = escape(category);
+        let _ = write!(html, "<option value=\"{category}\">{category}</option>");
+    }
+    html.push_str("</select>\n");
+    if with_change {
+        html.push_str(
+            "<select data-filter=\"change\"><option value=\"\">All changes</option>\
+             <option value=\"new\">new</option><option value=\"fixed\">fixed</option>\
+             <option value=\"persisting\">persisting</option></select>\n",
+        );
+    }
+    html.push_str("<input data-filter=\"text\" type=\"search\" placeholder=\"Search\">\n</div>\n");
+
+    html.push_str("<table id=\"findings\">\n<thead><tr>");
+    if with_change {
+        html.push_str("<th>Change</th>");
+    }
+    html.push_str("<th>Severity</th><th>Category</th><th>Location</th><th>Finding</th></tr></thead>\n<tbody>\n");
+
+    for (change, finding) in findings {
+        let severity = finding.severity.to_string().to_lowercase();
+        let category = escape(&finding.category);
+        let _ = write!(
+            html,
+            "<tr data-severity=\"{severity}\" data-category=\"{category}\" data-change=\"{}\">",
+            change.unwrap_or_default()
+        );
+        if let Some(change) = change {
+            let _ = write!(html, "<td class=\"{change}\">{change}</td>");
+        }
+
+        let mut location = escape(&finding.file.display().to_string());
+        if let Some(line) = finding.line {
+            let _ = write!(location, ":{line}");
+        }
+        let _ = write!(
+            html,
+            "<td><span class=\"sev {severity}\">{}</span></td><td>{category}</td><td><code>{location}</code></td>\
+             <td><strong>{}</strong><br>{}",
+            finding.severity,
+            escape(&finding.title),
+            escape(&finding.message)
+        );
+        if let Some(snippet) = &finding.snippet {
+            let _ = write!(html, "<br><code>{}</code>", escape(snippet));
+        }
+        if let Some(recommendation) = &finding.recommendation {
+            let _ = write!(html, "<br><em>{}</em>", escape(recommendation));
+        }
+        html.push_str("</td></tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// Escape text for HTML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_html_generation() {
+        let findings = vec![Finding {
+            severity: Severity::High,
+            category: "sql_injection".to_string(),
+            title: "Potential SQL injection".to_string(),
+            message: "Query built with format!".to_string(),
+            file: PathBuf::from("src/db.rs"),
+            line: Some(7),
+            column: None,
+            snippet: Some("format!(\"SELECT * FROM t WHERE id = '{}'\", id) <script>".to_string()),
+            recommendation: None,
+        }];
+
+        let report = SecurityReport::new(PathBuf::from("."), findings);
+        let html = generate(&report).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("data-severity=\"high\" data-category=\"sql_injection\""));
+        assert!(html.contains("<code>src/db.rs:7</code>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<link") && !html.contains("src=\"http"));
+    }
+}
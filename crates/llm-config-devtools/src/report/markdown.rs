@@ -30,6 +30,9 @@ pub fn generate(report: &SecurityReport) -> Result<String> {
     md.push_str(&format!("- **High**: {} 🟠\n", report.summary.high));
     md.push_str(&format!("- **Medium**: {} 🟡\n", report.summary.medium));
     md.push_str(&format!("- **Low**: {} 🟢\n", report.summary.low));
+    if report.summary.suppressed > 0 {
+        md.push_str(&format!("- **Suppressed by Baseline**: {}\n", report.summary.suppressed));
+    }
     md.push_str(&format!("- **Scan Duration**: {:.2}s\n", report.summary.duration_seconds));
 
     md.push_str("\n");
@@ -5,12 +5,18 @@
 //! - YAML (human-readable structured)
 //! - Markdown (documentation)
 //! - SARIF (GitHub Security integration)
+//! - HTML (self-contained page with charts and filtering)
+//!
+//! The [`compare`] module diffs two reports into new, fixed and persisting
+//! findings.
 
 use crate::error::{DevtoolsError, Result};
 use crate::security::SecurityReport;
 use std::fs;
 use std::path::Path;
 
+pub mod compare;
+pub mod html;
 pub mod json;
 pub mod markdown;
 pub mod sarif;
@@ -27,6 +33,8 @@ pub enum OutputFormat {
     Markdown,
     /// SARIF format (for GitHub Security).
     Sarif,
+    /// Self-contained HTML page.
+    Html,
 }
 
 impl std::str::FromStr for OutputFormat {
@@ -38,6 +46,7 @@ impl std::str::FromStr for OutputFormat {
             "yaml" | "yml" => Ok(Self::Yaml),
             "markdown" | "md" => Ok(Self::Markdown),
             "sarif" => Ok(Self::Sarif),
+            "html" | "htm" => Ok(Self::Html),
             _ => Err(DevtoolsError::Config(format!("Unknown output format: {}", s))),
         }
     }
@@ -50,6 +59,7 @@ pub fn generate_report(report: &SecurityReport, format: OutputFormat) -> Result<
         OutputFormat::Yaml => yaml::generate(report),
         OutputFormat::Markdown => markdown::generate(report),
         OutputFormat::Sarif => sarif::generate(report),
+        OutputFormat::Html => html::generate(report),
     }
 }
 
@@ -75,6 +85,7 @@ mod tests {
         );
         assert_eq!("md".parse::<OutputFormat>().unwrap(), OutputFormat::Markdown);
         assert_eq!("sarif".parse::<OutputFormat>().unwrap(), OutputFormat::Sarif);
+        assert_eq!("html".parse::<OutputFormat>().unwrap(), OutputFormat::Html);
     }
 
     #[test]
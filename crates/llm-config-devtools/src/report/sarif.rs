@@ -60,41 +60,7 @@ pub fn generate(report: &SecurityReport) -> Result<String> {
                         "name": "llm-config-devtools",
                         "informationUri": "https://github.com/globalbusinessadvisors/llm-config-manager",
                         "version": env!("CARGO_PKG_VERSION"),
-                        "rules": [
-                            {
-                                "id": "unsafe_code",
-                                "name": "UnsafeCode",
-                                "shortDescription": {
-                                    "text": "Unsafe code block detected"
-                                },
-                                "fullDescription": {
-                                    "text": "Unsafe code requires careful review to ensure memory safety"
-                                },
-                                "helpUri": "https://doc.rust-lang.org/book/ch19-01-unsafe-rust.html"
-                            },
-                            {
-                                "id": "secret",
-                                "name": "HardcodedSecret",
-                                "shortDescription": {
-                                    "text": "Potential hardcoded secret"
-                                },
-                                "fullDescription": {
-                                    "text": "Hardcoded secrets should never be committed to version control"
-                                },
-                                "helpUri": "https://owasp.org/www-community/vulnerabilities/Use_of_hard-coded_password"
-                            },
-                            {
-                                "id": "sql_injection",
-                                "name": "SqlInjection",
-                                "shortDescription": {
-                                    "text": "Potential SQL injection vulnerability"
-                                },
-                                "fullDescription": {
-                                    "text": "SQL queries constructed with string concatenation may be vulnerable to injection attacks"
-                                },
-                                "helpUri": "https://owasp.org/www-community/attacks/SQL_Injection"
-                            }
-                        ]
+                        "rules": rules(),
                     }
                 },
                 "results": results,
@@ -110,6 +76,67 @@ pub fn generate(report: &SecurityReport) -> Result<String> {
     Ok(json)
 }
 
+/// Rules describing the finding categories.
+fn rules() -> serde_json::Value {
+    json!([
+        {
+            "id": "unsafe_code",
+            "name": "UnsafeCode",
+            "shortDescription": {
+                "text": "Unsafe code block detected"
+            },
+            "fullDescription": {
+                "text": "Unsafe code requires careful review to ensure memory safety"
+            },
+            "helpUri": "https://doc.rust-lang.org/book/ch19-01-unsafe-rust.html"
+        },
+        {
+            "id": "secret",
+            "name": "HardcodedSecret",
+            "shortDescription": {
+                "text": "Potential hardcoded secret"
+            },
+            "fullDescription": {
+                "text": "Hardcoded secrets should never be committed to version control"
+            },
+            "helpUri": "https://owasp.org/www-community/vulnerabilities/Use_of_hard-coded_password"
+        },
+        {
+            "id": "sql_injection",
+            "name": "SqlInjection",
+            "shortDescription": {
+                "text": "Potential SQL injection vulnerability"
+            },
+            "fullDescription": {
+                "text": "SQL queries constructed with string concatenation may be vulnerable to injection attacks"
+            },
+            "helpUri": "https://owasp.org/www-community/attacks/SQL_Injection"
+        },
+        {
+            "id": "dependency_vulnerability",
+            "name": "VulnerableDependency",
+            "shortDescription": {
+                "text": "Dependency with a known vulnerability"
+            },
+            "fullDescription": {
+                "text": "A locked dependency version is affected by a RustSec advisory"
+            },
+            "helpUri": "https://rustsec.org/advisories/"
+        },
+        {
+            "id": "license",
+            "name": "LicenseCompliance",
+            "shortDescription": {
+                "text": "Dependency license not allowed by the license policy"
+            },
+            "fullDescription": {
+                "text": "Copyleft, denied or unknown dependency licenses need review before a release"
+            },
+            "helpUri": "https://spdx.org/licenses/"
+        }
+    ])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,220 @@
+//! Baseline of accepted findings.
+//!
+//! A baseline file (`.security-baseline.json` in the project root by default)
+//! lists findings that were reviewed and accepted, so that only new findings
+//! show up in reports and fail CI. Findings are matched by fingerprint: a
+//! hash of their file, category and snippet, which stays the same when code
+//! above a finding moves it to another line. Each baseline entry suppresses
+//! one finding, so a second copy of an accepted snippet in the same file is
+//! still reported.
+
+use crate::error::{DevtoolsError, Result};
+use crate::security::Finding;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Default baseline file name, relative to the project root.
+pub const BASELINE_FILE: &str = ".security-baseline.json";
+
+/// Baseline file format version.
+const BASELINE_VERSION: u32 = 1;
+
+/// An accepted finding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// Fingerprint of the finding (see [`fingerprint`]).
+    pub fingerprint: String,
+
+    /// Finding category, for reviewing the file.
+    pub category: String,
+
+    /// File of the finding, for reviewing the file.
+    pub file: PathBuf,
+
+    /// Finding title, for reviewing the file.
+    pub title: String,
+}
+
+/// Accepted findings of a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// File format version.
+    pub version: u32,
+
+    /// When the baseline was written.
+    pub updated_at: DateTime<Utc>,
+
+    /// Accepted findings.
+    pub findings: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Create a baseline accepting the given findings.
+    #[must_use]
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        let mut entries: Vec<BaselineEntry> = findings
+            .iter()
+            .map(|finding| BaselineEntry {
+                fingerprint: fingerprint(finding),
+                category: finding.category.clone(),
+                file: finding.file.clone(),
+                title: finding.title.clone(),
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            (&a.file, &a.category, &a.fingerprint).cmp(&(&b.file, &b.category, &b.fingerprint))
+        });
+
+        Self {
+            version: BASELINE_VERSION,
+            updated_at: Utc::now(),
+            findings: entries,
+        }
+    }
+
+    /// Load a baseline file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a baseline.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let baseline: Self = serde_json::from_str(&contents).map_err(|e| {
+            DevtoolsError::Deserialization(format!("{}: {}", path.display(), e))
+        })?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(DevtoolsError::Config(format!(
+                "{}: unsupported baseline version {}",
+                path.display(),
+                baseline.version
+            )));
+        }
+        Ok(baseline)
+    }
+
+    /// Write the baseline file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Split findings into new findings and findings accepted by the baseline.
+    #[must_use]
+    pub fn partition(&self, findings: Vec<Finding>) -> (Vec<Finding>, Vec<Finding>) {
+        let mut remaining: HashMap<&str, usize> = HashMap::new();
+        for entry in &self.findings {
+            *remaining.entry(entry.fingerprint.as_str()).or_default() += 1;
+        }
+
+        findings.into_iter().partition(|finding| {
+            match remaining.get_mut(fingerprint(finding).as_str()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        })
+    }
+}
+
+/// Fingerprint of a finding: SHA-256 of its file, category and snippet hash.
+///
+/// Line and column are left out so that findings keep their fingerprint when
+/// code moves. Findings without a snippet use their title instead.
+#[must_use]
+pub fn fingerprint(finding: &Finding) -> String {
+    let text = finding.snippet.as_deref().unwrap_or(&finding.title);
+    let snippet_hash = hex::encode(Sha256::digest(text.trim().as_bytes()));
+
+    let mut hasher = Sha256::new();
+    hasher.update(normalize_path(&finding.file).as_bytes());
+    hasher.update([0]);
+    hasher.update(finding.category.as_bytes());
+    hasher.update([0]);
+    hasher.update(snippet_hash.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Path with `/` separators and without `.` components, so that baselines
+/// written on another platform or from `./` still match.
+fn normalize_path(path: &Path) -> String {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::Severity;
+    use tempfile::TempDir;
+
+    fn finding(file: &str, line: usize, snippet: &str) -> Finding {
+        Finding {
+            severity: Severity::High,
+            category: "unsafe_code".to_string(),
+            title: "Unsafe code block detected".to_string(),
+            message: "Found unsafe code block".to_string(),
+            file: PathBuf::from(file),
+            line: Some(line),
+            column: None,
+            snippet: Some(snippet.to_string()),
+            recommendation: None,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let original = finding("src/lib.rs", 10, "unsafe { ptr.read() }");
+        assert_eq!(
+            fingerprint(&original),
+            fingerprint(&finding("./src/lib.rs", 42, "  unsafe { ptr.read() }"))
+        );
+        assert_ne!(
+            fingerprint(&original),
+            fingerprint(&finding("src/main.rs", 10, "unsafe { ptr.read() }"))
+        );
+        assert_ne!(
+            fingerprint(&original),
+            fingerprint(&finding("src/lib.rs", 10, "unsafe { ptr.write(0) }"))
+        );
+    }
+
+    #[test]
+    fn test_baseline_partition() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(BASELINE_FILE);
+        Baseline::from_findings(&[
+            finding("src/lib.rs", 10, "unsafe { ptr.read() }"),
+            finding("src/main.rs", 3, "unsafe { libc::exit(0) }"),
+        ])
+        .save(&path)
+        .unwrap();
+
+        let baseline = Baseline::load(&path).unwrap();
+        let (new, accepted) = baseline.partition(vec![
+            finding("src/lib.rs", 12, "unsafe { ptr.read() }"),
+            finding("src/lib.rs", 20, "unsafe { ptr.read() }"),
+            finding("src/lib.rs", 30, "unsafe { ptr.write(0) }"),
+        ]);
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].line, Some(12));
+        assert_eq!(
+            new.iter().map(|f| f.line).collect::<Vec<_>>(),
+            vec![Some(20), Some(30)]
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Dependency vulnerability scanner.
+//!
+//! Checks the packages locked in `Cargo.lock` against a local copy of the
+//! `RustSec` advisory database with the [`rustsec`] crate, the library behind
+//! `cargo audit`. Advisories limited to other operating systems or
+//! architectures than the scanned target are skipped, and the affected
+//! functions of an advisory are listed in its finding.
+//!
+//! The severity of a finding follows the CVSS v3 severity of the advisory.
+//! Vulnerabilities without a score are reported as high, informational
+//! advisories (unmaintained or unsound crates) as low.
+
+use crate::error::{DevtoolsError, Result};
+use crate::security::{Finding, Severity};
+use rustsec::advisory::Severity as CvssSeverity;
+use rustsec::database::Query;
+use rustsec::platforms::target::{Arch, OS};
+use rustsec::package::Package;
+use rustsec::{Advisory, Collection, Database, Lockfile, SourceId, Vulnerability};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Git repository of the `RustSec` advisory database.
+pub const ADVISORY_DB_URL: &str = "https://github.com/RustSec/advisory-db";
+
+/// Default location of the advisory database (shared with `cargo audit`).
+#[must_use]
+pub fn default_advisory_db() -> PathBuf {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")))
+        .unwrap_or_else(|| PathBuf::from(".cargo"))
+        .join("advisory-db")
+}
+
+/// Scanner for dependencies with known vulnerabilities.
+pub struct DependencyScanner {
+    project_root: PathBuf,
+    advisory_db: PathBuf,
+    fetch: bool,
+    target_arch: Option<Arch>,
+    target_os: Option<OS>,
+}
+
+impl DependencyScanner {
+    /// Create a new dependency scanner using the default advisory database,
+    /// targeting the platform it runs on.
+    #[must_use]
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            project_root: project_root.to_path_buf(),
+            advisory_db: default_advisory_db(),
+            fetch: false,
+            target_arch: std::env::consts::ARCH.parse().ok(),
+            target_os: std::env::consts::OS.parse().ok(),
+        }
+    }
+
+    /// Use the advisory database checked out at `path`.
+    #[must_use]
+    pub fn with_advisory_db(mut self, path: impl Into<PathBuf>) -> Self {
+        self.advisory_db = path.into();
+        self
+    }
+
+    /// Clone the advisory database if it is not checked out yet.
+    #[must_use]
+    pub const fn with_fetch(mut self, fetch: bool) -> Self {
+        self.fetch = fetch;
+        self
+    }
+
+    /// Only report advisories that affect the given target platform.
+    #[must_use]
+    pub const fn with_target(mut self, arch: Arch, os: OS) -> Self {
+        self.target_arch = Some(arch);
+        self.target_os = Some(os);
+        self
+    }
+
+    /// Scan the locked dependencies for advisories.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the project has no readable `Cargo.lock` or the advisory
+    /// database is missing and cannot be fetched or loaded.
+    pub fn scan(&self) -> Result<Vec<Finding>> {
+        tracing::info!("Scanning dependencies for known vulnerabilities");
+
+        let lockfile = self.project_root.join("Cargo.lock");
+        let lock = fs::read_to_string(&lockfile).map_err(|e| {
+            DevtoolsError::Config(format!("Cannot read {}: {}", lockfile.display(), e))
+        })?;
+        let packages: Lockfile =
+            lock.parse().map_err(|e: rustsec::cargo_lock::Error| {
+                DevtoolsError::Deserialization(e.to_string())
+            })?;
+        self.ensure_advisory_db()?;
+        let database = Database::open(&self.advisory_db).map_err(|e| {
+            DevtoolsError::Config(format!(
+                "Cannot load advisory database {}: {}",
+                self.advisory_db.display(),
+                e
+            ))
+        })?;
+
+        let mut query = Query::new().collection(Collection::Crates).withdrawn(false);
+        if let Some(arch) = self.target_arch {
+            query = query.target_arch(arch);
+        }
+        if let Some(os) = self.target_os {
+            query = query.target_os(os);
+        }
+
+        let mut findings = Vec::new();
+        // Workspace and path crates have no advisories
+        for package in packages
+            .packages
+            .iter()
+            .filter(|package| package.source.as_ref().is_some_and(SourceId::is_registry))
+        {
+            let mut advisories = database.query(&query.clone().package(package));
+            advisories.sort_by(|a, b| a.id().as_str().cmp(b.id().as_str()));
+            for advisory in advisories {
+                findings.push(finding(advisory, package, &lock));
+            }
+        }
+
+        tracing::info!("Found {} vulnerable dependencies", findings.len());
+        Ok(findings)
+    }
+
+    /// Clone the advisory database if it is missing and fetching is enabled.
+    fn ensure_advisory_db(&self) -> Result<()> {
+        if self.advisory_db.join("crates").is_dir() {
+            return Ok(());
+        }
+        if !self.fetch {
+            return Err(DevtoolsError::Config(format!(
+                "No advisory database at {} (clone {})",
+                self.advisory_db.display(),
+                ADVISORY_DB_URL
+            )));
+        }
+
+        tracing::info!("Fetching advisory database into {}", self.advisory_db.display());
+        git2::Repository::clone(ADVISORY_DB_URL, &self.advisory_db)?;
+        Ok(())
+    }
+}
+
+fn severity(advisory: &Advisory) -> Severity {
+    if advisory.metadata.informational.is_some() {
+        return Severity::Low;
+    }
+    match advisory.severity() {
+        Some(CvssSeverity::Critical) => Severity::Critical,
+        Some(CvssSeverity::High) | None => Severity::High,
+        Some(CvssSeverity::Medium) => Severity::Medium,
+        Some(CvssSeverity::Low | CvssSeverity::None) => Severity::Low,
+    }
+}
+
+fn finding(advisory: &Advisory, package: &Package, lock: &str) -> Finding {
+    let vulnerability = Vulnerability::new(advisory, package);
+    let metadata = &advisory.metadata;
+
+    let mut message = format!(
+        "{} {} is affected by {}",
+        package.name, package.version, metadata.id
+    );
+    if !metadata.aliases.is_empty() {
+        let aliases: Vec<&str> = metadata.aliases.iter().map(|alias| alias.as_str()).collect();
+        let _ = write!(message, " ({})", aliases.join(", "));
+    }
+    if let Some(kind) = &metadata.informational {
+        let _ = write!(message, " [{kind}]");
+    }
+    let _ = write!(message, ": {}", metadata.title);
+    if let Some(functions) = vulnerability.affected_functions() {
+        let functions: Vec<String> = functions.iter().map(ToString::to_string).collect();
+        let _ = write!(message, "; affected functions: {}", functions.join(", "));
+    }
+    if let Some(url) = &metadata.url {
+        let _ = write!(message, " <{url}>");
+    }
+
+    let patched = vulnerability.versions.patched();
+    let recommendation = if patched.is_empty() {
+        format!(
+            "No patched version of {} is available; consider replacing it",
+            metadata.package
+        )
+    } else {
+        let patched: Vec<String> = patched.iter().map(ToString::to_string).collect();
+        format!(
+            "Upgrade {} to a patched version ({})",
+            metadata.package,
+            patched.join(" or ")
+        )
+    };
+
+    Finding {
+        severity: severity(advisory),
+        category: "dependency_vulnerability".to_string(),
+        title: format!("{}: {}", metadata.id, metadata.title),
+        message,
+        file: PathBuf::from("Cargo.lock"),
+        line: lock_line(lock, package.name.as_str(), &package.version.to_string()),
+        column: None,
+        snippet: Some(format!("{} {}", package.name, package.version)),
+        recommendation: Some(recommendation),
+    }
+}
+
+/// Line of a package's entry in `Cargo.lock`.
+pub(crate) fn lock_line(lock: &str, name: &str, version: &str) -> Option<usize> {
+    let name = format!("name = \"{name}\"");
+    let version = format!("version = \"{version}\"");
+    let lines: Vec<&str> = lock.lines().collect();
+    lines
+        .windows(2)
+        .position(|pair| pair[0] == name && pair[1] == version)
+        .map(|index| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const LOCK: &str = r#"version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+
+[[package]]
+name = "hyper"
+version = "0.14.9"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "time"
+version = "0.3.30"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    fn write_advisory(db: &Path, package: &str, id: &str, front_matter: &str) {
+        let dir = db.join("crates").join(package);
+        fs::create_dir_all(&dir).unwrap();
+        let contents = format!(
+            "```toml\n[advisory]\nid = \"{id}\"\npackage = \"{package}\"\ndate = \"2021-07-07\"\n{front_matter}\n```\n\n# Problem in {package}\n\nDetails.\n"
+        );
+        fs::write(dir.join(format!("{id}.md")), contents).unwrap();
+    }
+
+    #[test]
+    fn test_dependency_scanner() {
+        let project = TempDir::new().unwrap();
+        let db = TempDir::new().unwrap();
+        fs::write(project.path().join("Cargo.lock"), LOCK).unwrap();
+
+        write_advisory(
+            db.path(),
+            "hyper",
+            "RUSTSEC-2021-0078",
+            "aliases = [\"CVE-2021-32715\"]\ncvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:L/A:N\"\n\n[versions]\npatched = [\">= 0.14.10\"]",
+        );
+        write_advisory(
+            db.path(),
+            "hyper",
+            "RUSTSEC-2021-0079",
+            "cvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H\"\n\n[versions]\npatched = [\">= 0.14.10\"]\nunaffected = [\"< 0.14.0\"]\n\n[affected.functions]\n\"hyper::Client::get\" = [\"< 0.14.10\"]",
+        );
+        write_advisory(
+            db.path(),
+            "hyper",
+            "RUSTSEC-2020-0008",
+            "\n[versions]\npatched = [\">= 0.13.10, < 0.14.0\", \">= 0.14.3\"]",
+        );
+        write_advisory(
+            db.path(),
+            "time",
+            "RUSTSEC-2020-0071",
+            "withdrawn = \"2024-01-01\"\n\n[versions]\npatched = []",
+        );
+        write_advisory(
+            db.path(),
+            "time",
+            "RUSTSEC-2099-0001",
+            "informational = \"unsound\"\n\n[versions]\npatched = []",
+        );
+        // Advisories for other platforms do not apply
+        write_advisory(
+            db.path(),
+            "time",
+            "RUSTSEC-2099-0003",
+            "\n[affected]\nos = [\"windows\"]\n\n[versions]\npatched = []",
+        );
+        // Workspace crates are not checked
+        write_advisory(db.path(), "app", "RUSTSEC-2099-0002", "\n[versions]\npatched = []");
+
+        let findings = DependencyScanner::new(project.path())
+            .with_advisory_db(db.path())
+            .with_target(Arch::X86_64, OS::Linux)
+            .scan()
+            .unwrap();
+        let found: Vec<(&str, Severity)> = findings
+            .iter()
+            .map(|f| (f.title.split(':').next().unwrap(), f.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("RUSTSEC-2021-0078", Severity::Medium),
+                ("RUSTSEC-2021-0079", Severity::Critical),
+                ("RUSTSEC-2099-0001", Severity::Low),
+            ]
+        );
+
+        let finding = &findings[0];
+        assert_eq!(finding.category, "dependency_vulnerability");
+        assert_eq!(finding.file, PathBuf::from("Cargo.lock"));
+        assert_eq!(finding.line, Some(8));
+        assert!(finding.message.contains("hyper 0.14.9 is affected by RUSTSEC-2021-0078 (CVE-2021-32715)"));
+        assert!(finding.recommendation.as_ref().unwrap().contains(">=0.14.10"));
+        assert!(findings[1].message.contains("affected functions: hyper::Client::get"));
+        assert!(findings[2].message.contains("[unsound]"));
+        assert!(findings[2].recommendation.as_ref().unwrap().starts_with("No patched version"));
+    }
+
+    #[test]
+    fn test_missing_advisory_db() {
+        let project = TempDir::new().unwrap();
+        fs::write(project.path().join("Cargo.lock"), LOCK).unwrap();
+        let scanner = DependencyScanner::new(project.path()).with_advisory_db(project.path().join("missing"));
+        assert!(scanner.scan().is_err());
+    }
+}
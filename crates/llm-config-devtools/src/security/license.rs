@@ -0,0 +1,379 @@
+//! License compliance scanner.
+//!
+//! Reads the licenses of all dependencies from `cargo metadata` and checks
+//! them against the allow and deny lists of a [`LicensePolicy`]. SPDX
+//! expressions are evaluated as a whole: `MIT OR GPL-3.0` passes if `MIT` is
+//! allowed, `MIT AND GPL-3.0` needs both.
+//!
+//! Denied and strong copyleft licenses (GPL, AGPL) are reported as high
+//! severity, weak copyleft licenses (LGPL, MPL, EPL, ...) and licenses that
+//! are neither allowed nor known, including missing license information,
+//! as medium.
+
+use crate::error::Result;
+use crate::security::dependency::lock_line;
+use crate::security::{Finding, Severity};
+use cargo_metadata::MetadataCommand;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Licenses allowed by default: permissive licenses common in the Rust ecosystem.
+pub const DEFAULT_ALLOWED_LICENSES: &[&str] = &[
+    "MIT",
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "ISC",
+    "Zlib",
+    "0BSD",
+    "CC0-1.0",
+    "Unlicense",
+    "Unicode-3.0",
+    "Unicode-DFS-2016",
+    "BSL-1.0",
+];
+
+/// Strong copyleft license families (matched as prefixes of SPDX ids).
+const STRONG_COPYLEFT: &[&str] = &["GPL-", "AGPL-", "SSPL-", "OSL-"];
+
+/// Weak copyleft license families (matched as prefixes of SPDX ids).
+const WEAK_COPYLEFT: &[&str] = &["LGPL-", "MPL-", "EPL-", "CDDL-", "EUPL-", "CC-BY-SA-", "MS-RL"];
+
+/// Allowed and denied licenses (SPDX ids).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LicensePolicy {
+    /// Licenses that pass without a finding.
+    pub allow: Vec<String>,
+
+    /// Licenses that are never acceptable, even if they are copyleft-free.
+    pub deny: Vec<String>,
+}
+
+impl Default for LicensePolicy {
+    fn default() -> Self {
+        Self {
+            allow: DEFAULT_ALLOWED_LICENSES.iter().map(ToString::to_string).collect(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+/// How a license (expression) fares against a policy, from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Verdict {
+    Allowed,
+    Unknown,
+    WeakCopyleft,
+    StrongCopyleft,
+    Denied,
+}
+
+impl Verdict {
+    const fn severity(self) -> Severity {
+        match self {
+            Self::Allowed => Severity::Low,
+            Self::Unknown | Self::WeakCopyleft => Severity::Medium,
+            Self::StrongCopyleft | Self::Denied => Severity::High,
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Allowed => "Allowed",
+            Self::Unknown => "Unapproved",
+            Self::WeakCopyleft => "Weak copyleft",
+            Self::StrongCopyleft => "Copyleft",
+            Self::Denied => "Denied",
+        }
+    }
+}
+
+impl LicensePolicy {
+    /// Verdict on a single SPDX license id.
+    fn check_id(&self, id: &str) -> Verdict {
+        let id = id.trim_end_matches('+');
+        let listed = |list: &[String]| list.iter().any(|entry| entry.eq_ignore_ascii_case(id));
+        let family = |prefixes: &[&str]| prefixes.iter().any(|prefix| id.starts_with(prefix));
+
+        if listed(&self.deny) {
+            Verdict::Denied
+        } else if listed(&self.allow) {
+            Verdict::Allowed
+        } else if family(STRONG_COPYLEFT) {
+            Verdict::StrongCopyleft
+        } else if family(WEAK_COPYLEFT) {
+            Verdict::WeakCopyleft
+        } else {
+            Verdict::Unknown
+        }
+    }
+
+    /// Verdict on an SPDX expression (`Unknown` if it does not parse).
+    fn check(&self, expression: &str) -> Verdict {
+        parse_expression(expression).map_or(Verdict::Unknown, |expr| self.eval(&expr))
+    }
+
+    fn eval(&self, expr: &Expr) -> Verdict {
+        match expr {
+            Expr::License(id) => self.check_id(id),
+            Expr::Or(alternatives) => alternatives
+                .iter()
+                .map(|e| self.eval(e))
+                .min()
+                .unwrap_or(Verdict::Unknown),
+            Expr::And(terms) => terms
+                .iter()
+                .map(|e| self.eval(e))
+                .max()
+                .unwrap_or(Verdict::Unknown),
+        }
+    }
+}
+
+/// A parsed SPDX license expression.
+#[derive(Debug, PartialEq, Eq)]
+enum Expr {
+    License(String),
+    Or(Vec<Self>),
+    And(Vec<Self>),
+}
+
+/// Parse an SPDX expression; `/` is read as `OR` as in old crate manifests,
+/// and `WITH` exceptions are judged by their license.
+fn parse_expression(expression: &str) -> Option<Expr> {
+    let spaced = expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut position = 0;
+    let expr = parse_or(&tokens, &mut position)?;
+    (position == tokens.len()).then_some(expr)
+}
+
+fn parse_or(tokens: &[&str], position: &mut usize) -> Option<Expr> {
+    let mut alternatives = vec![parse_and(tokens, position)?];
+    while tokens.get(*position).is_some_and(|t| t.eq_ignore_ascii_case("OR")) {
+        *position += 1;
+        alternatives.push(parse_and(tokens, position)?);
+    }
+    Some(if alternatives.len() == 1 {
+        alternatives.remove(0)
+    } else {
+        Expr::Or(alternatives)
+    })
+}
+
+fn parse_and(tokens: &[&str], position: &mut usize) -> Option<Expr> {
+    let mut terms = vec![parse_atom(tokens, position)?];
+    while tokens.get(*position).is_some_and(|t| t.eq_ignore_ascii_case("AND")) {
+        *position += 1;
+        terms.push(parse_atom(tokens, position)?);
+    }
+    Some(if terms.len() == 1 {
+        terms.remove(0)
+    } else {
+        Expr::And(terms)
+    })
+}
+
+fn parse_atom(tokens: &[&str], position: &mut usize) -> Option<Expr> {
+    let token = *tokens.get(*position)?;
+    *position += 1;
+    if token == "(" {
+        let expr = parse_or(tokens, position)?;
+        (tokens.get(*position) == Some(&")")).then_some(())?;
+        *position += 1;
+        return Some(expr);
+    }
+    if token == ")" || ["AND", "OR", "WITH"].iter().any(|op| token.eq_ignore_ascii_case(op)) {
+        return None;
+    }
+    if tokens.get(*position).is_some_and(|t| t.eq_ignore_ascii_case("WITH")) {
+        tokens.get(*position + 1)?;
+        *position += 2;
+    }
+    Some(Expr::License(token.to_string()))
+}
+
+/// A dependency and its declared license.
+#[derive(Debug, Clone)]
+pub struct LicensedPackage {
+    /// Package name.
+    pub name: String,
+    /// Package version.
+    pub version: String,
+    /// SPDX license expression from the manifest.
+    pub license: Option<String>,
+    /// License file named in the manifest instead of an expression.
+    pub license_file: Option<String>,
+}
+
+/// Scanner for dependency licenses.
+pub struct LicenseScanner {
+    project_root: PathBuf,
+    policy: LicensePolicy,
+}
+
+impl LicenseScanner {
+    /// Create a new license scanner with the default policy.
+    #[must_use]
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            project_root: project_root.to_path_buf(),
+            policy: LicensePolicy::default(),
+        }
+    }
+
+    /// Check licenses against `policy`.
+    #[must_use]
+    pub fn with_policy(mut self, policy: LicensePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Scan the licenses of all dependencies.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `cargo metadata` cannot be run for the project.
+    pub fn scan(&self) -> Result<Vec<Finding>> {
+        tracing::info!("Checking dependency licenses");
+
+        let metadata = MetadataCommand::new()
+            .current_dir(&self.project_root)
+            .exec()?;
+        let packages: Vec<LicensedPackage> = metadata
+            .packages
+            .iter()
+            .filter(|package| !metadata.workspace_members.contains(&package.id))
+            .map(|package| LicensedPackage {
+                name: package.name.clone(),
+                version: package.version.to_string(),
+                license: package.license.clone(),
+                license_file: package.license_file.as_ref().map(ToString::to_string),
+            })
+            .collect();
+        let lock = fs::read_to_string(self.project_root.join("Cargo.lock")).unwrap_or_default();
+
+        let findings = self.check(&packages, &lock);
+        tracing::info!("Found {} license issues", findings.len());
+        Ok(findings)
+    }
+
+    /// Findings for packages whose license the policy does not allow.
+    #[must_use]
+    pub fn check(&self, packages: &[LicensedPackage], lock: &str) -> Vec<Finding> {
+        let mut seen = BTreeSet::new();
+        let mut findings = Vec::new();
+        for package in packages {
+            if !seen.insert((&package.name, &package.version)) {
+                continue;
+            }
+            let (verdict, license) = match (&package.license, &package.license_file) {
+                (Some(license), _) => (self.policy.check(license), license.clone()),
+                (None, Some(file)) => (Verdict::Unknown, format!("a custom license ({file})")),
+                (None, None) => (Verdict::Unknown, "no declared license".to_string()),
+            };
+            if verdict == Verdict::Allowed {
+                continue;
+            }
+
+            findings.push(Finding {
+                severity: verdict.severity(),
+                category: "license".to_string(),
+                title: format!("{} license: {} {}", verdict.label(), package.name, package.version),
+                message: format!(
+                    "{} {} is distributed under {}, which the license policy does not allow",
+                    package.name, package.version, license
+                ),
+                file: PathBuf::from("Cargo.lock"),
+                line: lock_line(lock, &package.name, &package.version),
+                column: None,
+                snippet: package.license.clone(),
+                recommendation: Some(match verdict {
+                    Verdict::StrongCopyleft | Verdict::Denied => format!(
+                        "Replace {} or obtain legal approval before releasing",
+                        package.name
+                    ),
+                    _ => format!(
+                        "Review the license of {} and add it to the allow list if it is acceptable",
+                        package.name
+                    ),
+                }),
+            });
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, license: Option<&str>) -> LicensedPackage {
+        LicensedPackage {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            license: license.map(ToString::to_string),
+            license_file: None,
+        }
+    }
+
+    #[test]
+    fn test_license_expressions() {
+        let policy = LicensePolicy::default();
+        assert_eq!(policy.check("MIT OR Apache-2.0"), Verdict::Allowed);
+        assert_eq!(policy.check("MIT/Apache-2.0"), Verdict::Allowed);
+        assert_eq!(policy.check("(MIT OR Apache-2.0) AND Unicode-DFS-2016"), Verdict::Allowed);
+        assert_eq!(policy.check("Apache-2.0 WITH LLVM-exception"), Verdict::Allowed);
+        assert_eq!(policy.check("GPL-3.0-only OR MIT"), Verdict::Allowed);
+        assert_eq!(policy.check("MIT AND GPL-3.0-or-later"), Verdict::StrongCopyleft);
+        assert_eq!(policy.check("MPL-2.0"), Verdict::WeakCopyleft);
+        assert_eq!(policy.check("LGPL-2.1+ OR GPL-2.0"), Verdict::WeakCopyleft);
+        assert_eq!(policy.check("Proprietary"), Verdict::Unknown);
+        assert_eq!(policy.check("MIT AND (Apache-2.0"), Verdict::Unknown);
+
+        let policy = LicensePolicy {
+            allow: vec!["MIT".to_string(), "MPL-2.0".to_string()],
+            deny: vec!["Apache-2.0".to_string()],
+        };
+        assert_eq!(policy.check("MPL-2.0"), Verdict::Allowed);
+        assert_eq!(policy.check("Apache-2.0 AND MIT"), Verdict::Denied);
+    }
+
+    #[test]
+    fn test_license_findings() {
+        let lock = "[[package]]\nname = \"gpl-crate\"\nversion = \"1.0.0\"\n";
+        let mut custom = package("custom", None);
+        custom.license_file = Some("LICENSE.txt".to_string());
+        let packages = vec![
+            package("serde", Some("MIT OR Apache-2.0")),
+            package("gpl-crate", Some("GPL-3.0")),
+            package("gpl-crate", Some("GPL-3.0")),
+            package("webpki-roots", Some("MPL-2.0")),
+            custom,
+            package("anonymous", None),
+        ];
+
+        let findings = LicenseScanner::new(Path::new(".")).check(&packages, lock);
+        let found: Vec<(&str, Severity)> = findings
+            .iter()
+            .map(|f| (f.title.as_str(), f.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Copyleft license: gpl-crate 1.0.0", Severity::High),
+                ("Weak copyleft license: webpki-roots 1.0.0", Severity::Medium),
+                ("Unapproved license: custom 1.0.0", Severity::Medium),
+                ("Unapproved license: anonymous 1.0.0", Severity::Medium),
+            ]
+        );
+        assert_eq!(findings[0].category, "license");
+        assert_eq!(findings[0].line, Some(2));
+        assert!(findings[2].message.contains("custom license (LICENSE.txt)"));
+    }
+}
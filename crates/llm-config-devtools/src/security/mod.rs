@@ -5,13 +5,28 @@
 //! - Unsafe code detection
 //! - Secret scanning
 //! - SQL injection vulnerability detection
-
-use crate::error::Result;
+//! - Dependency vulnerability audit against the `RustSec` advisory database
+//! - Dependency license compliance
+//! - Leaked credentials in stored configuration values (see [`config_values`])
+//!
+//! Findings accepted in a [`baseline::Baseline`] file are left out of the
+//! report and only counted in its summary. A scanner that fails is reported
+//! as a high severity `scanner_error` finding, so an incomplete scan does not
+//! pass as a clean one.
+
+use crate::error::{DevtoolsError, Result};
+use crate::security::baseline::{Baseline, BASELINE_FILE};
+use crate::security::license::LicensePolicy;
+use crate::security::secret::SecretScanConfig;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+pub mod baseline;
 pub mod clippy;
+pub mod config_values;
+pub mod dependency;
+pub mod license;
 pub mod secret;
 pub mod sql;
 pub mod unsafe_code;
@@ -31,13 +46,47 @@ pub struct ScanConfig {
     /// Whether to scan for secrets.
     pub scan_secrets: bool,
 
+    /// Excluded paths and entropy thresholds of the secret scan.
+    #[serde(default)]
+    pub secrets: SecretScanConfig,
+
     /// Whether to scan for SQL injection.
     pub scan_sql: bool,
 
+    /// Whether to check dependencies for known vulnerabilities.
+    #[serde(default = "default_true")]
+    pub scan_dependencies: bool,
+
+    /// Advisory database checkout (fetched into `~/.cargo/advisory-db` if not set).
+    #[serde(default)]
+    pub advisory_db: Option<PathBuf>,
+
+    /// Whether to check dependency licenses against `licenses`.
+    #[serde(default = "default_true")]
+    pub scan_licenses: bool,
+
+    /// Allowed and denied dependency licenses.
+    #[serde(default)]
+    pub licenses: LicensePolicy,
+
+    /// Baseline of accepted findings, relative to `project_root` unless
+    /// absolute (ignored if the file does not exist).
+    #[serde(default = "default_baseline")]
+    pub baseline: Option<PathBuf>,
+
     /// Maximum number of parallel workers.
     pub max_workers: Option<usize>,
 }
 
+const fn default_true() -> bool {
+    true
+}
+
+#[allow(clippy::unnecessary_wraps)] // serde needs the field's type
+fn default_baseline() -> Option<PathBuf> {
+    Some(PathBuf::from(BASELINE_FILE))
+}
+
 impl Default for ScanConfig {
     fn default() -> Self {
         Self {
@@ -45,7 +94,13 @@ impl Default for ScanConfig {
             scan_clippy: true,
             scan_unsafe: true,
             scan_secrets: true,
+            secrets: SecretScanConfig::default(),
             scan_sql: true,
+            scan_dependencies: true,
+            advisory_db: None,
+            scan_licenses: true,
+            licenses: LicensePolicy::default(),
+            baseline: Some(PathBuf::from(BASELINE_FILE)),
             max_workers: None,
         }
     }
@@ -147,6 +202,10 @@ pub struct ScanSummary {
     /// Number of low severity findings.
     pub low: usize,
 
+    /// Number of findings left out because the baseline accepts them.
+    #[serde(default)]
+    pub suppressed: usize,
+
     /// Number of files scanned.
     pub files_scanned: usize,
 
@@ -195,11 +254,27 @@ impl SecurityReport {
             high,
             medium,
             low,
+            suppressed: 0,
             files_scanned: 0,
             duration_seconds: 0.0,
         }
     }
 
+    /// Remove the findings accepted by a baseline, counting them as suppressed.
+    pub fn apply_baseline(&mut self, baseline: &Baseline) {
+        let findings = std::mem::take(&mut self.findings);
+        let (findings, accepted) = baseline.partition(findings);
+        let summary = Self::calculate_summary(&findings);
+
+        self.findings = findings;
+        self.summary = ScanSummary {
+            suppressed: self.summary.suppressed + accepted.len(),
+            files_scanned: self.summary.files_scanned,
+            duration_seconds: self.summary.duration_seconds,
+            ..summary
+        };
+    }
+
     /// Get current git commit hash.
     fn get_git_commit() -> Result<String> {
         let repo = git2::Repository::discover(".")?;
@@ -242,48 +317,73 @@ impl SecurityScanner {
 
         tracing::info!("Starting security scan at {:?}", self.config.project_root);
 
-        // Collect scanner functions to run
-        let mut scanners: Vec<Box<dyn Fn() -> Result<Vec<Finding>> + Send + Sync>> = Vec::new();
+        // Collect scanner functions to run, by name
+        type Scan = Box<dyn Fn() -> Result<Vec<Finding>> + Send + Sync>;
+        let mut scanners: Vec<(&str, Scan)> = Vec::new();
 
         if self.config.scan_clippy {
             let root = self.config.project_root.clone();
-            scanners.push(Box::new(move || {
+            scanners.push(("clippy", Box::new(move || {
                 clippy::ClippyScanner::new(&root).scan()
-            }));
+            })));
         }
 
         if self.config.scan_unsafe {
             let root = self.config.project_root.clone();
-            scanners.push(Box::new(move || {
+            scanners.push(("unsafe code", Box::new(move || {
                 unsafe_code::UnsafeCodeDetector::new(&root).scan()
-            }));
+            })));
         }
 
         if self.config.scan_secrets {
             let root = self.config.project_root.clone();
-            scanners.push(Box::new(move || {
-                secret::SecretScanner::new(&root).scan()
-            }));
+            let secrets = self.config.secrets.clone();
+            scanners.push(("secret", Box::new(move || {
+                secret::SecretScanner::new(&root).with_config(secrets.clone()).scan()
+            })));
         }
 
         if self.config.scan_sql {
             let root = self.config.project_root.clone();
-            scanners.push(Box::new(move || {
+            scanners.push(("SQL injection", Box::new(move || {
                 sql::SqlInjectionScanner::new(&root).scan()
-            }));
+            })));
+        }
+
+        if self.config.scan_dependencies {
+            let root = self.config.project_root.clone();
+            let advisory_db = self.config.advisory_db.clone();
+            scanners.push(("dependency", Box::new(move || {
+                let scanner = dependency::DependencyScanner::new(&root);
+                match &advisory_db {
+                    Some(path) => scanner.with_advisory_db(path).scan(),
+                    None => scanner.with_fetch(true).scan(),
+                }
+            })));
+        }
+
+        if self.config.scan_licenses {
+            let root = self.config.project_root.clone();
+            let policy = self.config.licenses.clone();
+            scanners.push(("license", Box::new(move || {
+                license::LicenseScanner::new(&root).with_policy(policy.clone()).scan()
+            })));
         }
 
         // Run scans in parallel using rayon
-        let scan_results: Vec<Result<Vec<Finding>>> = scanners
+        let scan_results: Vec<(&str, Result<Vec<Finding>>)> = scanners
             .par_iter()
-            .map(|scanner| scanner())
+            .map(|(name, scanner)| (*name, scanner()))
             .collect();
 
         // Collect all findings
-        for result in scan_results {
+        for (name, result) in scan_results {
             match result {
                 Ok(mut found) => findings.append(&mut found),
-                Err(e) => tracing::warn!("Scanner error: {}", e),
+                Err(e) => {
+                    tracing::error!("The {} scan failed: {}", name, e);
+                    findings.push(scanner_error(name, &e));
+                }
             }
         }
 
@@ -291,9 +391,17 @@ impl SecurityScanner {
         let mut report = SecurityReport::new(self.config.project_root.clone(), findings);
         report.summary.duration_seconds = duration.as_secs_f64();
 
+        if let Some(path) = &self.config.baseline {
+            let path = self.config.project_root.join(path);
+            if path.exists() {
+                report.apply_baseline(&Baseline::load(&path)?);
+            }
+        }
+
         tracing::info!(
-            "Security scan complete: {} findings in {:.2}s",
+            "Security scan complete: {} findings ({} suppressed by baseline) in {:.2}s",
             report.summary.total,
+            report.summary.suppressed,
             report.summary.duration_seconds
         );
 
@@ -301,6 +409,21 @@ impl SecurityScanner {
     }
 }
 
+/// Finding for a scanner that could not run.
+fn scanner_error(scanner: &str, error: &DevtoolsError) -> Finding {
+    Finding {
+        severity: Severity::High,
+        category: "scanner_error".to_string(),
+        title: format!("The {scanner} scan failed"),
+        message: format!("The {scanner} scan could not run, so its findings are missing: {error}"),
+        file: PathBuf::from("."),
+        line: None,
+        column: None,
+        snippet: None,
+        recommendation: Some(format!("Fix the cause and run the {scanner} scan again")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,6 +442,29 @@ mod tests {
         assert!(config.scan_unsafe);
         assert!(config.scan_secrets);
         assert!(config.scan_sql);
+        assert!(config.scan_dependencies);
+        assert!(config.scan_licenses);
+        assert!(config.licenses.allow.contains(&"MIT".to_string()));
+        assert_eq!(config.baseline, Some(PathBuf::from(".security-baseline.json")));
+    }
+
+    #[test]
+    fn test_config_defaults_when_omitted() {
+        let config: ScanConfig = serde_json::from_str(
+            r#"{
+                "project_root": ".",
+                "scan_clippy": false,
+                "scan_unsafe": false,
+                "scan_secrets": false,
+                "scan_sql": false,
+                "max_workers": null
+            }"#,
+        )
+        .unwrap();
+        assert!(config.scan_dependencies);
+        assert!(config.scan_licenses);
+        assert_eq!(config.baseline, Some(PathBuf::from(BASELINE_FILE)));
+        assert_eq!(config.advisory_db, None);
     }
 
     #[test]
@@ -353,5 +499,36 @@ mod tests {
         assert_eq!(report.summary.critical, 1);
         assert_eq!(report.summary.high, 1);
         assert!(report.has_high_severity());
+
+        let mut report = report;
+        report.apply_baseline(&Baseline::from_findings(&report.findings[..1]));
+        assert_eq!(report.summary.total, 1);
+        assert_eq!(report.summary.critical, 0);
+        assert_eq!(report.summary.suppressed, 1);
+        assert!(report.has_high_severity());
+    }
+
+    #[test]
+    fn test_failed_scanner_is_reported() {
+        // No Cargo.lock, so the dependency scan cannot run
+        let project = tempfile::tempdir().unwrap();
+        let config = ScanConfig {
+            project_root: project.path().to_path_buf(),
+            scan_clippy: false,
+            scan_unsafe: false,
+            scan_secrets: false,
+            scan_sql: false,
+            scan_dependencies: true,
+            advisory_db: Some(project.path().join("advisory-db")),
+            scan_licenses: false,
+            baseline: None,
+            ..ScanConfig::default()
+        };
+
+        let report = SecurityScanner::new(config).scan().unwrap();
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].category, "scanner_error");
+        assert_eq!(report.findings[0].title, "The dependency scan failed");
+        assert!(report.has_high_severity());
     }
 }
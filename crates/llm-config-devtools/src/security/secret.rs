@@ -1,15 +1,30 @@
 //! Secret scanning module.
 //!
 //! Scans source files for potential hardcoded secrets like passwords, API keys, and tokens.
+//!
+//! Besides keyword patterns, string literals containing long base64 or hex
+//! tokens are reported when their Shannon entropy is above a threshold, which
+//! catches keys assigned to names no pattern knows. Paths are excluded with
+//! gitignore-style globs from [`SecretScanConfig::exclude`], and a line is
+//! skipped when it or the line above contains a `devtools:allow-secret`
+//! comment.
 
-use crate::error::Result;
+use crate::error::{DevtoolsError, Result};
 use crate::security::{Finding, Severity};
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::WalkBuilder;
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Marker that suppresses secret findings on its line and the line below.
+pub const ALLOW_SECRET_MARKER: &str = "devtools:allow-secret";
+
+/// Paths excluded from secret scanning by default (gitignore-style globs).
+pub const DEFAULT_SECRET_EXCLUDES: &[&str] = &["target/", "node_modules/", ".git/", "package-lock.json"];
+
 lazy_static! {
     /// Regex patterns for detecting secrets.
     static ref SECRET_PATTERNS: Vec<SecretPattern> = vec![
@@ -68,12 +83,55 @@ lazy_static! {
             regex: Regex::new(r#"gh[pousr]_[A-Za-z0-9_]{36,}"#).unwrap(),
             severity: Severity::Critical,
         },
+        SecretPattern {
+            name: "URL Credentials",
+            regex: Regex::new(r#"(?i)\b[a-z][a-z0-9+.\-]*://([^/\s:@"'\[\]{}]+):([^/\s@"'\[\]{}]{3,})@"#).unwrap(),
+            severity: Severity::High,
+        },
         SecretPattern {
             name: "Generic Secret",
             regex: Regex::new(r#"(?i)(secret|token)\s*[:=]\s*["']([a-zA-Z0-9_\-\.]{20,})["']"#).unwrap(),
             severity: Severity::Medium,
         },
     ];
+
+    /// String literals (double or single quoted).
+    static ref STRING_LITERAL: Regex = Regex::new(r#""((?:[^"\\]|\\.)*)"|'((?:[^'\\]|\\.)*)'"#).unwrap();
+
+    /// Base64 (standard or URL-safe) and hex tokens within string literals.
+    static ref ENTROPY_TOKEN: Regex = Regex::new(r"[A-Za-z0-9+/_\-]+={0,2}").unwrap();
+}
+
+/// Secret scanning settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretScanConfig {
+    /// Paths not to scan, as gitignore-style globs relative to the project root.
+    pub exclude: Vec<String>,
+
+    /// Whether to report high-entropy string literals.
+    pub entropy: bool,
+
+    /// Shortest token checked for entropy.
+    pub entropy_min_length: usize,
+
+    /// Entropy (bits per character) above which a base64 token is reported.
+    pub base64_entropy_threshold: f64,
+
+    /// Entropy (bits per character) above which a hex token is reported.
+    pub hex_entropy_threshold: f64,
+}
+
+impl Default for SecretScanConfig {
+    fn default() -> Self {
+        Self {
+            exclude: DEFAULT_SECRET_EXCLUDES.iter().map(ToString::to_string).collect(),
+            entropy: true,
+            entropy_min_length: 20,
+            base64_entropy_threshold: 4.5,
+            hex_entropy_threshold: 3.0,
+        }
+    }
 }
 
 /// A pattern for detecting secrets.
@@ -83,9 +141,49 @@ struct SecretPattern {
     severity: Severity,
 }
 
+/// What a secret was recognized by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SecretKind {
+    /// A keyword pattern, by name (e.g. "API Key").
+    Pattern(&'static str),
+    /// A token whose Shannon entropy is above the threshold for its alphabet.
+    HighEntropy {
+        /// Length of the token.
+        length: usize,
+        /// Entropy in bits per character.
+        entropy: f64,
+    },
+}
+
+impl std::fmt::Display for SecretKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pattern(name) => write!(f, "{name}"),
+            Self::HighEntropy { .. } => write!(f, "high-entropy string"),
+        }
+    }
+}
+
+/// A potential secret on a line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretMatch {
+    /// What the secret was recognized by.
+    pub kind: SecretKind,
+
+    /// Severity of the match.
+    pub severity: Severity,
+
+    /// Column of the secret (1-based), if known.
+    pub column: Option<usize>,
+
+    /// The line with the secret masked.
+    pub redacted: String,
+}
+
 /// Scanner for detecting hardcoded secrets.
 pub struct SecretScanner {
     project_root: PathBuf,
+    config: SecretScanConfig,
 }
 
 impl SecretScanner {
@@ -93,9 +191,17 @@ impl SecretScanner {
     pub fn new(project_root: &Path) -> Self {
         Self {
             project_root: project_root.to_path_buf(),
+            config: SecretScanConfig::default(),
         }
     }
 
+    /// Use the given exclusions and entropy settings.
+    #[must_use]
+    pub fn with_config(mut self, config: SecretScanConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// Scan for hardcoded secrets.
     pub fn scan(&self) -> Result<Vec<Finding>> {
         tracing::info!("Scanning for hardcoded secrets");
@@ -104,7 +210,7 @@ impl SecretScanner {
 
         // Walk through relevant source files
         for entry in WalkBuilder::new(&self.project_root)
-            .filter_entry(|e| !Self::is_excluded(e.path()))
+            .overrides(self.excludes()?)
             .build()
         {
             let entry = entry?;
@@ -121,13 +227,17 @@ impl SecretScanner {
         Ok(findings)
     }
 
-    fn is_excluded(path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
-        path_str.contains("target/")
-            || path_str.contains("node_modules/")
-            || path_str.contains(".git/")
-            || path_str.contains("test")
-            || path_str.contains("example")
+    /// Excluded paths as ignore overrides.
+    fn excludes(&self) -> Result<Override> {
+        let mut builder = OverrideBuilder::new(&self.project_root);
+        for pattern in &self.config.exclude {
+            builder
+                .add(&format!("!{pattern}"))
+                .map_err(|e| DevtoolsError::Config(format!("invalid secret exclude '{pattern}': {e}")))?;
+        }
+        builder
+            .build()
+            .map_err(|e| DevtoolsError::Config(e.to_string()))
     }
 
     fn should_scan(path: &Path) -> bool {
@@ -143,8 +253,19 @@ impl SecretScanner {
 
     fn scan_file(&self, path: &Path, content: &str) -> Result<Vec<Finding>> {
         let mut findings = Vec::new();
+        let relative_path = path
+            .strip_prefix(&self.project_root)
+            .unwrap_or(path)
+            .to_path_buf();
+        let mut previous_line = "";
 
         for (line_num, line) in content.lines().enumerate() {
+            let allowed = line.contains(ALLOW_SECRET_MARKER) || previous_line.contains(ALLOW_SECRET_MARKER);
+            previous_line = line;
+            if allowed {
+                continue;
+            }
+
             // Skip comments (basic heuristic)
             let trimmed = line.trim();
             if trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with("/*") {
@@ -157,41 +278,109 @@ impl SecretScanner {
                 }
             }
 
-            for pattern in SECRET_PATTERNS.iter() {
-                if pattern.regex.is_match(line) {
-                    // Filter out obvious false positives
-                    if Self::is_false_positive(line) {
-                        continue;
-                    }
-
-                    let relative_path = path
-                        .strip_prefix(&self.project_root)
-                        .unwrap_or(path)
-                        .to_path_buf();
-
-                    findings.push(Finding {
-                        severity: pattern.severity,
-                        category: "secret".to_string(),
-                        title: format!("Potential hardcoded {}", pattern.name),
-                        message: format!(
-                            "Found potential hardcoded {} in source code. Never commit secrets to version control.",
-                            pattern.name
+            for found in self.detect(line) {
+                let (title, message) = match found.kind {
+                    SecretKind::Pattern(name) => (
+                        format!("Potential hardcoded {name}"),
+                        format!(
+                            "Found potential hardcoded {name} in source code. Never commit secrets to version control."
                         ),
-                        file: relative_path,
-                        line: Some(line_num + 1),
-                        column: None,
-                        snippet: Some(Self::redact_secret(line)),
-                        recommendation: Some(
-                            "Use environment variables or a secret management service (e.g., AWS Secrets Manager, HashiCorp Vault) instead of hardcoding secrets.".to_string(),
+                    ),
+                    SecretKind::HighEntropy { length, entropy } => (
+                        "Potential hardcoded high-entropy string".to_string(),
+                        format!(
+                            "Found a {length}-character string with {entropy:.2} bits of entropy per character, which looks like a key or token. Never commit secrets to version control."
                         ),
-                    });
-                }
+                    ),
+                };
+                let recommendation = match found.kind {
+                    SecretKind::Pattern(_) => "Use environment variables or a secret management service (e.g., AWS Secrets Manager, HashiCorp Vault) instead of hardcoding secrets.".to_string(),
+                    SecretKind::HighEntropy { .. } => format!(
+                        "Load the value from the environment or a secret manager. If it is not a secret, add a `// {ALLOW_SECRET_MARKER}` comment."
+                    ),
+                };
+
+                findings.push(Finding {
+                    severity: found.severity,
+                    category: "secret".to_string(),
+                    title,
+                    message,
+                    file: relative_path.clone(),
+                    line: Some(line_num + 1),
+                    column: found.column,
+                    snippet: Some(found.redacted),
+                    recommendation: Some(recommendation),
+                });
             }
         }
 
         Ok(findings)
     }
 
+    /// Potential secrets on a line: keyword pattern matches, or else the
+    /// first high-entropy token in a string literal (if enabled).
+    ///
+    /// Lines that look like placeholders or test data yield nothing.
+    #[must_use]
+    pub fn detect(&self, line: &str) -> Vec<SecretMatch> {
+        if Self::is_false_positive(line) {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<SecretMatch> = SECRET_PATTERNS
+            .iter()
+            .filter(|pattern| pattern.regex.is_match(line))
+            .map(|pattern| SecretMatch {
+                kind: SecretKind::Pattern(pattern.name),
+                severity: pattern.severity,
+                column: None,
+                redacted: Self::redact_secret(line),
+            })
+            .collect();
+
+        if matches.is_empty() && self.config.entropy {
+            if let Some((token, entropy)) = self.high_entropy_token(line) {
+                matches.push(SecretMatch {
+                    kind: SecretKind::HighEntropy {
+                        length: token.len(),
+                        entropy,
+                    },
+                    severity: Severity::Medium,
+                    column: line.find(token).map(|i| i + 1),
+                    redacted: Self::redact_token(line, token),
+                });
+            }
+        }
+        matches
+    }
+
+    /// First token in a string literal of the line whose entropy is above
+    /// the threshold for its alphabet, with its entropy.
+    fn high_entropy_token<'a>(&self, line: &'a str) -> Option<(&'a str, f64)> {
+        STRING_LITERAL
+            .captures_iter(line)
+            .filter_map(|literal| literal.get(1).or_else(|| literal.get(2)))
+            .flat_map(|literal| ENTROPY_TOKEN.find_iter(literal.as_str()))
+            .map(|token| token.as_str())
+            .filter(|token| token.len() >= self.config.entropy_min_length)
+            .find_map(|token| {
+                let threshold = if token.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    self.config.hex_entropy_threshold
+                } else {
+                    self.config.base64_entropy_threshold
+                };
+                let entropy = shannon_entropy(token);
+                (entropy > threshold).then_some((token, entropy))
+            })
+    }
+
+    /// Mask all but the first four characters of a secret in a line.
+    fn redact_token(line: &str, secret: &str) -> String {
+        let visible: String = secret.chars().take(4).collect();
+        let masked = "*".repeat(secret.chars().count().saturating_sub(4));
+        line.replace(secret, &format!("{visible}{masked}"))
+    }
+
     fn is_false_positive(line: &str) -> bool {
         let lower = line.to_lowercase();
         lower.contains("example")
@@ -223,6 +412,24 @@ impl SecretScanner {
     }
 }
 
+/// Shannon entropy of a string in bits per character.
+#[allow(clippy::cast_precision_loss)]
+fn shannon_entropy(text: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for byte in text.bytes() {
+        counts[usize::from(byte)] += 1;
+    }
+    let len = text.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,7 +438,6 @@ mod tests {
     #[test]
     fn test_secret_scanner_detects_password() {
         let dir = TempDir::new().unwrap();
-        // Create a subdirectory that won't be filtered out by "test" exclusion
         let src_dir = dir.path().join("src");
         fs::create_dir(&src_dir).unwrap();
         let file_path = src_dir.join("config.rs");
@@ -276,6 +482,59 @@ const API_KEY: &str = "example-api-key";
         assert_eq!(findings.len(), 0);
     }
 
+    #[test]
+    fn test_shannon_entropy() {
+        assert!(shannon_entropy("aaaaaaaa").abs() < f64::EPSILON);
+        assert!((shannon_entropy("abcdabcd") - 2.0).abs() < 1e-9);
+        assert!(shannon_entropy("wJalrXUtnFEMI/K7MDENG/bPxRfiCYzK8u3pQ9e") > 4.5);
+    }
+
+    #[test]
+    fn test_secret_scanner_entropy_and_suppression() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("tests/fixtures")).unwrap();
+        let content = r#"
+let upstream = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYzK8u3pQ9e";
+let digest = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+let name = "configuration_manager_service_name";
+let known = "Zm9vYmFyYmF6cXV4cXV1eGNvcmdlZ3JhdWx0Z2FycGx5"; // devtools:allow-secret
+// devtools:allow-secret
+let sample = "bG9yZW0gaXBzdW0gZG9sb3Igc2l0IGFtZXQgY29uc2Vj";
+"#;
+        fs::write(dir.path().join("src/client.rs"), content).unwrap();
+        fs::write(dir.path().join("tests/fixtures/keys.rs"), content).unwrap();
+
+        // Test directories are scanned unless excluded.
+        let findings = SecretScanner::new(dir.path()).scan().unwrap();
+        let mut lines: Vec<_> = findings
+            .iter()
+            .map(|f| (f.file.to_string_lossy().into_owned(), f.line))
+            .collect();
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec![
+                ("src/client.rs".to_string(), Some(2)),
+                ("src/client.rs".to_string(), Some(3)),
+                ("tests/fixtures/keys.rs".to_string(), Some(2)),
+                ("tests/fixtures/keys.rs".to_string(), Some(3)),
+            ]
+        );
+        assert!(findings[0].snippet.as_deref().unwrap().contains("****"));
+        assert!(!findings[0].snippet.as_deref().unwrap().contains("bPxRfiCYzK8u3pQ9e"));
+
+        let config = SecretScanConfig {
+            exclude: vec!["tests/".to_string()],
+            hex_entropy_threshold: 4.0,
+            ..SecretScanConfig::default()
+        };
+        let findings = SecretScanner::new(dir.path()).with_config(config).scan().unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].file, PathBuf::from("src/client.rs"));
+        assert_eq!(findings[0].line, Some(2));
+    }
+
     #[test]
     fn test_redact_secret() {
         let line = r#"password = "secretvalue123""#;
@@ -5,7 +5,7 @@
 
 use llm_config_audit::{AuditLogger, AuditEventType, FileAuditStorage};
 use llm_config_cache::CacheManager;
-use llm_config_core::{ConfigManager, ConfigValue, Environment};
+use llm_config_core::{ChangeBus, ConfigManager, ConfigValue, Environment};
 use llm_config_crypto::{Algorithm, SecretKey};
 use llm_config_rbac::{permissions::*, PolicyEnforcer, Role, RoleAssignment};
 use llm_config_templates::{Template, TemplateEngine};
@@ -123,13 +123,19 @@ fn test_secrets_encryption_integration() {
 
     assert_eq!(retrieved, secret_data);
 
-    // Verify that get() also decrypts secrets automatically
-    let entry2 = manager
+    // Verify that get() keeps secrets encrypted
+    let masked = manager
         .get("production/api", "api_key", Environment::Production)
         .unwrap()
         .unwrap();
+    assert!(matches!(masked.value, ConfigValue::Secret(_)));
+
+    // Decryption is an explicit opt-in via get_revealed()
+    let entry2 = manager
+        .get_revealed("production/api", "api_key", Environment::Production)
+        .unwrap()
+        .unwrap();
 
-    // When retrieved via get(), secrets are automatically decrypted to strings
     if let ConfigValue::String(decrypted) = entry2.value {
         assert_eq!(decrypted.as_bytes(), secret_data);
     } else {
@@ -281,6 +287,56 @@ async fn test_cache_integration() {
     assert_eq!(stats2.size, 1);
 }
 
+#[tokio::test]
+async fn test_cache_invalidation_across_managers() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage_dir = temp_dir.path().join("storage");
+
+    // Two managers (e.g. two API workers) sharing a change bus
+    let bus = ChangeBus::default();
+    let writer = ConfigManager::new(&storage_dir).unwrap().with_change_bus(bus.clone());
+    let reader = ConfigManager::new(&storage_dir).unwrap().with_change_bus(bus.clone());
+
+    let cache = Arc::new(CacheManager::new(100, temp_dir.path().join("cache")).unwrap());
+    let subscription = cache.subscribe_invalidations(bus.subscribe());
+
+    let env = Environment::Production;
+    let entry = reader
+        .set("app", "model", ConfigValue::String("gpt-4".to_string()), env, "user")
+        .unwrap();
+    cache.put(entry).unwrap();
+
+    writer
+        .set("app", "model", ConfigValue::String("gpt-4o".to_string()), env, "user")
+        .unwrap();
+    wait_until(|| cache.get("app", "model", "production").is_err()).await;
+
+    let refreshed = writer.get("app", "model", env).unwrap().unwrap();
+    assert_eq!(refreshed.value.as_str(), Some("gpt-4o"));
+    cache.put(refreshed).unwrap();
+
+    writer.delete("app", "model", env).unwrap();
+    wait_until(|| cache.get("app", "model", "production").is_err()).await;
+    assert_eq!(cache.l2_size(), 0);
+
+    // The subscription ends once every manager and bus handle is gone
+    drop((writer, reader, bus));
+    tokio::time::timeout(std::time::Duration::from_secs(5), subscription)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+async fn wait_until(condition: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if condition() {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("condition not met in time");
+}
+
 #[test]
 fn test_template_integration() {
     let mut engine = TemplateEngine::new();
@@ -142,7 +142,7 @@ pub trait HealthCheck: Send + Sync {
 
 /// Health checker that manages multiple health checks
 pub struct HealthChecker {
-    checks: Arc<RwLock<HashMap<String, Box<dyn HealthCheck>>>>,
+    checks: Arc<RwLock<HashMap<String, Arc<dyn HealthCheck>>>>,
     start_time: Instant,
 }
 
@@ -158,17 +158,24 @@ impl HealthChecker {
     /// Register a health check
     pub fn register(&self, check: Box<dyn HealthCheck>) {
         let name = check.name().to_string();
-        self.checks.write().unwrap().insert(name, check);
+        self.checks.write().unwrap().insert(name, Arc::from(check));
     }
 
     /// Perform all health checks
     pub async fn check_health(&self) -> HealthReport {
-        let checks_map = self.checks.read().unwrap();
+        // Clone the checks out so the lock is not held across awaits
+        let checks: Vec<_> = self
+            .checks
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, check)| (name.clone(), Arc::clone(check)))
+            .collect();
         let mut results = HashMap::new();
 
-        for (name, check) in checks_map.iter() {
+        for (name, check) in checks {
             let result = check.check().await;
-            results.insert(name.clone(), result);
+            results.insert(name, result);
         }
 
         HealthReport::new(results, self.start_time.elapsed())
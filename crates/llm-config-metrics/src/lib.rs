@@ -1,19 +1,32 @@
 //! Metrics and monitoring for LLM Config Manager
 //!
 //! This crate provides comprehensive metrics collection using Prometheus.
-//! It includes metrics for all major subsystems and health checks.
+//! It includes metrics for all major subsystems and health checks. Metrics
+//! are scraped in the Prometheus or OpenMetrics text format, or pushed to a
+//! Pushgateway where they cannot be scraped. [`MetricsRegistry`] implements
+//! [`MetricsSink`], through which the storage, config and cache crates
+//! record their metrics without depending on this crate.
 
 pub mod collectors;
 pub mod health;
+pub mod openmetrics;
+pub mod push;
+pub mod sink;
 
 use prometheus::{
-    Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramVec, Opts, Registry,
+    Counter, CounterVec, Encoder, Gauge, GaugeVec, Histogram, HistogramVec, Opts, Registry,
+    TextEncoder,
 };
+use openmetrics::Exemplars;
+use sink::SinkFamilies;
 use std::sync::Arc;
 use thiserror::Error;
 
 pub use collectors::*;
 pub use health::*;
+pub use openmetrics::{Exemplar, OPENMETRICS_CONTENT_TYPE};
+pub use push::{spawn_push, PushConfig, DEFAULT_PUSH_INTERVAL, PUSH_TIMEOUT};
+pub use llm_config_sink::MetricsSink;
 
 #[derive(Error, Debug)]
 pub enum MetricsError {
@@ -25,6 +38,12 @@ pub enum MetricsError {
 
     #[error("Health check failed: {0}")]
     HealthCheckFailed(String),
+
+    #[error("Push to gateway failed: {0}")]
+    PushFailed(String),
+
+    #[error("Invalid push label {0:?}: expected NAME=VALUE")]
+    InvalidPushLabel(String),
 }
 
 pub type Result<T> = std::result::Result<T, MetricsError>;
@@ -38,13 +57,19 @@ pub struct MetricsRegistry {
     audit_metrics: AuditMetrics,
     storage_metrics: StorageMetrics,
     crypto_metrics: CryptoMetrics,
+    security_metrics: SecurityMetrics,
+    notification_metrics: NotificationMetrics,
     system_metrics: SystemMetrics,
+    exemplars: Arc<Exemplars>,
+    /// Families recorded through [`MetricsSink`] the registry does not define
+    sink_families: SinkFamilies,
 }
 
 impl MetricsRegistry {
     /// Create a new metrics registry
     pub fn new() -> Result<Self> {
         let registry = Arc::new(Registry::new());
+        let exemplars = Arc::new(Exemplars::default());
 
         Ok(Self {
             config_metrics: ConfigMetrics::new(Arc::clone(&registry))?,
@@ -53,7 +78,11 @@ impl MetricsRegistry {
             audit_metrics: AuditMetrics::new(Arc::clone(&registry))?,
             storage_metrics: StorageMetrics::new(Arc::clone(&registry))?,
             crypto_metrics: CryptoMetrics::new(Arc::clone(&registry))?,
-            system_metrics: SystemMetrics::new(Arc::clone(&registry))?,
+            security_metrics: SecurityMetrics::new(Arc::clone(&registry))?,
+            notification_metrics: NotificationMetrics::new(Arc::clone(&registry))?,
+            system_metrics: SystemMetrics::new(Arc::clone(&registry), Arc::clone(&exemplars))?,
+            exemplars,
+            sink_families: SinkFamilies::new(Arc::clone(&registry)),
             registry,
         })
     }
@@ -93,6 +122,16 @@ impl MetricsRegistry {
         &self.crypto_metrics
     }
 
+    /// Get security middleware metrics
+    pub fn security(&self) -> &SecurityMetrics {
+        &self.security_metrics
+    }
+
+    /// Get webhook notification metrics
+    pub fn notifications(&self) -> &NotificationMetrics {
+        &self.notification_metrics
+    }
+
     /// Get system metrics
     pub fn system(&self) -> &SystemMetrics {
         &self.system_metrics
@@ -102,6 +141,13 @@ impl MetricsRegistry {
     pub fn gather(&self) -> Vec<prometheus::proto::MetricFamily> {
         self.registry.gather()
     }
+
+    /// Encode all metrics in the Prometheus text exposition format
+    pub fn encode_text(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
 }
 
 impl Default for MetricsRegistry {
@@ -116,6 +162,7 @@ pub struct ConfigMetrics {
     operation_duration: HistogramVec,
     active_configs: GaugeVec,
     errors_total: CounterVec,
+    type_mismatches_total: CounterVec,
 }
 
 impl ConfigMetrics {
@@ -153,16 +200,26 @@ impl ConfigMetrics {
             &["error_type", "operation"],
         )?;
 
+        let type_mismatches_total = CounterVec::new(
+            Opts::new(
+                "config_type_mismatches_total",
+                "Values read with a different type than expected",
+            ),
+            &["expected", "actual"],
+        )?;
+
         registry.register(Box::new(operations_total.clone()))?;
         registry.register(Box::new(operation_duration.clone()))?;
         registry.register(Box::new(active_configs.clone()))?;
         registry.register(Box::new(errors_total.clone()))?;
+        registry.register(Box::new(type_mismatches_total.clone()))?;
 
         Ok(Self {
             operations_total,
             operation_duration,
             active_configs,
             errors_total,
+            type_mismatches_total,
         })
     }
 
@@ -189,6 +246,19 @@ impl ConfigMetrics {
             .with_label_values(&[error_type, operation])
             .inc();
     }
+
+    /// Record a value read as `actual` where `expected` was declared or stored before
+    pub fn record_type_mismatch(&self, expected: &str, actual: &str) {
+        self.type_mismatches_total
+            .with_label_values(&[expected, actual])
+            .inc();
+    }
+
+    pub fn type_mismatches(&self, expected: &str, actual: &str) -> f64 {
+        self.type_mismatches_total
+            .with_label_values(&[expected, actual])
+            .get()
+    }
 }
 
 /// Cache metrics
@@ -197,7 +267,10 @@ pub struct CacheMetrics {
     misses_total: CounterVec,
     evictions_total: CounterVec,
     size: GaugeVec,
+    size_bytes: GaugeVec,
     operation_duration: HistogramVec,
+    warmup_duration: Histogram,
+    warmed_entries_total: Counter,
 }
 
 impl CacheMetrics {
@@ -214,7 +287,7 @@ impl CacheMetrics {
 
         let evictions_total = CounterVec::new(
             Opts::new("cache_evictions_total", "Total cache evictions"),
-            &["tier"],
+            &["tier", "cause"],
         )?;
 
         let size = GaugeVec::new(
@@ -222,6 +295,11 @@ impl CacheMetrics {
             &["tier"],
         )?;
 
+        let size_bytes = GaugeVec::new(
+            Opts::new("cache_size_bytes", "Approximate size of the cached entries in bytes"),
+            &["tier"],
+        )?;
+
         let operation_duration = HistogramVec::new(
             prometheus::HistogramOpts::new(
                 "cache_operation_duration_seconds",
@@ -231,18 +309,37 @@ impl CacheMetrics {
             &["operation", "tier"],
         )?;
 
+        let warmup_duration = Histogram::with_opts(
+            prometheus::HistogramOpts::new(
+                "cache_warmup_duration_seconds",
+                "Duration of loading namespaces into the cache",
+            )
+            .buckets(vec![0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0]),
+        )?;
+
+        let warmed_entries_total = Counter::new(
+            "cache_warmed_entries_total",
+            "Total entries loaded into the cache by warmups",
+        )?;
+
         registry.register(Box::new(hits_total.clone()))?;
         registry.register(Box::new(misses_total.clone()))?;
         registry.register(Box::new(evictions_total.clone()))?;
         registry.register(Box::new(size.clone()))?;
+        registry.register(Box::new(size_bytes.clone()))?;
         registry.register(Box::new(operation_duration.clone()))?;
+        registry.register(Box::new(warmup_duration.clone()))?;
+        registry.register(Box::new(warmed_entries_total.clone()))?;
 
         Ok(Self {
             hits_total,
             misses_total,
             evictions_total,
             size,
+            size_bytes,
             operation_duration,
+            warmup_duration,
+            warmed_entries_total,
         })
     }
 
@@ -254,20 +351,41 @@ impl CacheMetrics {
         self.misses_total.with_label_values(&[tier]).inc();
     }
 
-    pub fn record_eviction(&self, tier: &str) {
-        self.evictions_total.with_label_values(&[tier]).inc();
+    /// Record an eviction and the limit that caused it (e.g. `entry_limit`, `byte_limit`)
+    pub fn record_eviction(&self, tier: &str, cause: &str) {
+        self.evictions_total.with_label_values(&[tier, cause]).inc();
     }
 
     pub fn set_size(&self, tier: &str, size: usize) {
         self.size.with_label_values(&[tier]).set(size as f64);
     }
 
+    pub fn set_size_bytes(&self, tier: &str, bytes: usize) {
+        self.size_bytes.with_label_values(&[tier]).set(bytes as f64);
+    }
+
+    /// Last size in bytes reported for a tier
+    pub fn size_bytes(&self, tier: &str) -> u64 {
+        self.size_bytes.with_label_values(&[tier]).get() as u64
+    }
+
     pub fn observe_duration(&self, operation: &str, tier: &str, duration: f64) {
         self.operation_duration
             .with_label_values(&[operation, tier])
             .observe(duration);
     }
 
+    /// Record a warmup that loaded `entries` entries
+    pub fn record_warmup(&self, entries: usize, duration: f64) {
+        self.warmup_duration.observe(duration);
+        self.warmed_entries_total.inc_by(entries as f64);
+    }
+
+    /// Total entries loaded by warmups
+    pub fn warmed_entries(&self) -> u64 {
+        self.warmed_entries_total.get() as u64
+    }
+
     pub fn hit_rate(&self, tier: &str) -> f64 {
         let hits = self.hits_total.with_label_values(&[tier]).get();
         let misses = self.misses_total.with_label_values(&[tier]).get();
@@ -285,6 +403,8 @@ pub struct RbacMetrics {
     permission_denials_total: CounterVec,
     check_duration: HistogramVec,
     active_roles: GaugeVec,
+    decision_cache_hits_total: Counter,
+    decision_cache_misses_total: Counter,
 }
 
 impl RbacMetrics {
@@ -319,16 +439,30 @@ impl RbacMetrics {
             &["role"],
         )?;
 
+        let decision_cache_hits_total = Counter::new(
+            "rbac_decision_cache_hits_total",
+            "Permission checks answered from the decision cache",
+        )?;
+
+        let decision_cache_misses_total = Counter::new(
+            "rbac_decision_cache_misses_total",
+            "Permission checks computed from role assignments",
+        )?;
+
         registry.register(Box::new(permission_checks_total.clone()))?;
         registry.register(Box::new(permission_denials_total.clone()))?;
         registry.register(Box::new(check_duration.clone()))?;
         registry.register(Box::new(active_roles.clone()))?;
+        registry.register(Box::new(decision_cache_hits_total.clone()))?;
+        registry.register(Box::new(decision_cache_misses_total.clone()))?;
 
         Ok(Self {
             permission_checks_total,
             permission_denials_total,
             check_duration,
             active_roles,
+            decision_cache_hits_total,
+            decision_cache_misses_total,
         })
     }
 
@@ -356,6 +490,24 @@ impl RbacMetrics {
             .with_label_values(&[role])
             .set(count as f64);
     }
+
+    pub fn record_decision_cache_hit(&self) {
+        self.decision_cache_hits_total.inc();
+    }
+
+    pub fn record_decision_cache_miss(&self) {
+        self.decision_cache_misses_total.inc();
+    }
+
+    pub fn decision_cache_hit_rate(&self) -> f64 {
+        let hits = self.decision_cache_hits_total.get();
+        let misses = self.decision_cache_misses_total.get();
+        if hits + misses == 0.0 {
+            0.0
+        } else {
+            hits / (hits + misses)
+        }
+    }
 }
 
 /// Audit log metrics
@@ -364,6 +516,7 @@ pub struct AuditMetrics {
     events_by_user: CounterVec,
     event_processing_duration: Histogram,
     queue_size: Gauge,
+    overflow_total: CounterVec,
 }
 
 impl AuditMetrics {
@@ -394,13 +547,23 @@ impl AuditMetrics {
         registry.register(Box::new(events_total.clone()))?;
         registry.register(Box::new(events_by_user.clone()))?;
         registry.register(Box::new(event_processing_duration.clone()))?;
+        let overflow_total = CounterVec::new(
+            Opts::new(
+                "audit_queue_overflow_total",
+                "Audit events that did not fit the queue, by outcome",
+            ),
+            &["outcome"],
+        )?;
+
         registry.register(Box::new(queue_size.clone()))?;
+        registry.register(Box::new(overflow_total.clone()))?;
 
         Ok(Self {
             events_total,
             events_by_user,
             event_processing_duration,
             queue_size,
+            overflow_total,
         })
     }
 
@@ -416,6 +579,17 @@ impl AuditMetrics {
     pub fn set_queue_size(&self, size: usize) {
         self.queue_size.set(size as f64);
     }
+
+    /// Count events that did not fit the queue ("dropped" or "spilled")
+    pub fn record_overflow(&self, outcome: &str, count: usize) {
+        self.overflow_total
+            .with_label_values(&[outcome])
+            .inc_by(count as f64);
+    }
+
+    pub fn overflow(&self, outcome: &str) -> f64 {
+        self.overflow_total.with_label_values(&[outcome]).get()
+    }
 }
 
 /// Storage metrics
@@ -492,6 +666,7 @@ pub struct CryptoMetrics {
     operation_duration: HistogramVec,
     key_rotations_total: Counter,
     encryption_errors_total: Counter,
+    key_age_days: Gauge,
 }
 
 impl CryptoMetrics {
@@ -522,14 +697,21 @@ impl CryptoMetrics {
 
         registry.register(Box::new(operations_total.clone()))?;
         registry.register(Box::new(operation_duration.clone()))?;
+        let key_age_days = Gauge::new(
+            "crypto_key_age_days",
+            "Age of the encryption key in days",
+        )?;
+
         registry.register(Box::new(key_rotations_total.clone()))?;
         registry.register(Box::new(encryption_errors_total.clone()))?;
+        registry.register(Box::new(key_age_days.clone()))?;
 
         Ok(Self {
             operations_total,
             operation_duration,
             key_rotations_total,
             encryption_errors_total,
+            key_age_days,
         })
     }
 
@@ -552,8 +734,114 @@ impl CryptoMetrics {
     pub fn record_encryption_error(&self) {
         self.encryption_errors_total.inc();
     }
+
+    pub fn set_key_age_days(&self, days: i64) {
+        self.key_age_days.set(days as f64);
+    }
+
+    /// Age of the encryption key in days
+    pub fn key_age_days(&self) -> f64 {
+        self.key_age_days.get()
+    }
+}
+
+/// Security middleware metrics
+pub struct SecurityMetrics {
+    rejections_total: CounterVec,
 }
 
+impl SecurityMetrics {
+    fn new(registry: Arc<Registry>) -> Result<Self> {
+        let rejections_total = CounterVec::new(
+            Opts::new(
+                "security_rejections_total",
+                "Requests rejected by the security middleware",
+            ),
+            &["check"],
+        )?;
+
+        registry.register(Box::new(rejections_total.clone()))?;
+
+        Ok(Self { rejections_total })
+    }
+
+    /// Record a request rejected by a check (e.g. `rate_limit`, `input_validation`)
+    pub fn record_rejection(&self, check: &str) {
+        self.rejections_total.with_label_values(&[check]).inc();
+    }
+
+    pub fn rejections(&self, check: &str) -> f64 {
+        self.rejections_total.with_label_values(&[check]).get()
+    }
+}
+
+/// Webhook notification delivery metrics
+pub struct NotificationMetrics {
+    deliveries_total: CounterVec,
+    attempts_total: Counter,
+    delivery_duration: Histogram,
+}
+
+impl NotificationMetrics {
+    fn new(registry: Arc<Registry>) -> Result<Self> {
+        let deliveries_total = CounterVec::new(
+            Opts::new(
+                "webhook_deliveries_total",
+                "Webhook notifications by final delivery outcome",
+            ),
+            &["event", "outcome"],
+        )?;
+
+        let attempts_total = Counter::new(
+            "webhook_delivery_attempts_total",
+            "Webhook requests sent, including retries",
+        )?;
+
+        let delivery_duration = Histogram::with_opts(
+            prometheus::HistogramOpts::new(
+                "webhook_delivery_duration_seconds",
+                "Time to deliver a webhook notification, including retries",
+            )
+            .buckets(vec![0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0]),
+        )?;
+
+        registry.register(Box::new(deliveries_total.clone()))?;
+        registry.register(Box::new(attempts_total.clone()))?;
+        registry.register(Box::new(delivery_duration.clone()))?;
+
+        Ok(Self {
+            deliveries_total,
+            attempts_total,
+            delivery_duration,
+        })
+    }
+
+    /// Record the outcome (`delivered` or `failed`) of a notification
+    pub fn record_delivery(&self, event: &str, outcome: &str) {
+        self.deliveries_total.with_label_values(&[event, outcome]).inc();
+    }
+
+    /// Record a single webhook request
+    pub fn record_attempt(&self) {
+        self.attempts_total.inc();
+    }
+
+    pub fn observe_duration(&self, duration: f64) {
+        self.delivery_duration.observe(duration);
+    }
+
+    pub fn deliveries(&self, event: &str, outcome: &str) -> f64 {
+        self.deliveries_total.with_label_values(&[event, outcome]).get()
+    }
+
+    pub fn attempts(&self) -> f64 {
+        self.attempts_total.get()
+    }
+}
+
+/// Buckets of `http_request_duration_seconds`
+const HTTP_DURATION_BUCKETS: [f64; 8] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
 /// System-wide metrics
 pub struct SystemMetrics {
     uptime_seconds: Gauge,
@@ -561,10 +849,13 @@ pub struct SystemMetrics {
     goroutines: Gauge,
     http_requests_total: CounterVec,
     http_request_duration: HistogramVec,
+    http_requests_in_flight: Gauge,
+    http_requests_abandoned_total: Counter,
+    exemplars: Arc<Exemplars>,
 }
 
 impl SystemMetrics {
-    fn new(registry: Arc<Registry>) -> Result<Self> {
+    fn new(registry: Arc<Registry>, exemplars: Arc<Exemplars>) -> Result<Self> {
         let uptime_seconds = Gauge::new(
             "system_uptime_seconds",
             "System uptime in seconds",
@@ -590,15 +881,27 @@ impl SystemMetrics {
                 "http_request_duration_seconds",
                 "HTTP request duration",
             )
-            .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0]),
+            .buckets(HTTP_DURATION_BUCKETS.to_vec()),
             &["method", "path"],
         )?;
 
+        let http_requests_in_flight = Gauge::new(
+            "http_requests_in_flight",
+            "HTTP requests currently being served",
+        )?;
+
+        let http_requests_abandoned_total = Counter::new(
+            "http_requests_abandoned_total",
+            "HTTP requests still in flight when the shutdown deadline passed",
+        )?;
+
         registry.register(Box::new(uptime_seconds.clone()))?;
         registry.register(Box::new(memory_usage_bytes.clone()))?;
         registry.register(Box::new(goroutines.clone()))?;
         registry.register(Box::new(http_requests_total.clone()))?;
         registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(http_requests_in_flight.clone()))?;
+        registry.register(Box::new(http_requests_abandoned_total.clone()))?;
 
         Ok(Self {
             uptime_seconds,
@@ -606,6 +909,9 @@ impl SystemMetrics {
             goroutines,
             http_requests_total,
             http_request_duration,
+            http_requests_in_flight,
+            http_requests_abandoned_total,
+            exemplars,
         })
     }
 
@@ -632,6 +938,37 @@ impl SystemMetrics {
             .with_label_values(&[method, path])
             .observe(duration);
     }
+
+    /// Observe a request duration, keeping `exemplar` (e.g. its trace ID)
+    /// for the OpenMetrics exposition
+    pub fn observe_http_duration_with_exemplar(
+        &self,
+        method: &str,
+        path: &str,
+        duration: f64,
+        exemplar: &[(&str, &str)],
+    ) {
+        self.observe_http_duration(method, path, duration);
+        self.exemplars.record(
+            "http_request_duration_seconds",
+            &[("method", method), ("path", path)],
+            &HTTP_DURATION_BUCKETS,
+            duration,
+            exemplar,
+        );
+    }
+
+    pub fn set_http_requests_in_flight(&self, count: usize) {
+        self.http_requests_in_flight.set(count as f64);
+    }
+
+    pub fn record_abandoned_requests(&self, count: usize) {
+        self.http_requests_abandoned_total.inc_by(count as f64);
+    }
+
+    pub fn abandoned_requests(&self) -> f64 {
+        self.http_requests_abandoned_total.get()
+    }
 }
 
 #[cfg(test)]
@@ -644,6 +981,16 @@ mod tests {
         assert!(!registry.gather().is_empty());
     }
 
+    #[test]
+    fn test_encode_text() {
+        let registry = MetricsRegistry::new().unwrap();
+        registry.config().record_operation("get", "production");
+
+        let text = registry.encode_text().unwrap();
+        assert!(text.contains("# TYPE"));
+        assert!(text.contains("production"));
+    }
+
     #[test]
     fn test_config_metrics() {
         let registry = MetricsRegistry::new().unwrap();
@@ -664,6 +1011,11 @@ mod tests {
         registry.cache().record_hit("l1");
         registry.cache().record_miss("l1");
         registry.cache().set_size("l1", 100);
+        registry.cache().set_size_bytes("l2", 4096);
+        assert_eq!(registry.cache().size_bytes("l2"), 4096);
+        registry.cache().record_eviction("l1", "byte_limit");
+        registry.cache().record_warmup(12, 0.2);
+        assert_eq!(registry.cache().warmed_entries(), 12);
 
         let hit_rate = registry.cache().hit_rate("l1");
         assert!((hit_rate - 0.5).abs() < 0.01); // 1 hit, 1 miss = 50%
@@ -681,4 +1033,18 @@ mod tests {
         let metrics = registry.gather();
         assert!(!metrics.is_empty());
     }
+
+    #[test]
+    fn test_security_metrics() {
+        let registry = MetricsRegistry::new().unwrap();
+
+        registry.security().record_rejection("rate_limit");
+        registry.security().record_rejection("rate_limit");
+
+        assert_eq!(registry.security().rejections("rate_limit"), 2.0);
+        assert!(registry
+            .encode_text()
+            .unwrap()
+            .contains("security_rejections_total{check=\"rate_limit\"} 2"));
+    }
 }
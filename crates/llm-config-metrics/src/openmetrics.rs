@@ -0,0 +1,265 @@
+//! OpenMetrics text exposition with exemplars
+//!
+//! [`MetricsRegistry::encode_openmetrics`] encodes the gathered metrics in
+//! the OpenMetrics 1.0 text format (served for scrapers that ask for
+//! [`OPENMETRICS_CONTENT_TYPE`]). Histogram buckets carry the last exemplar
+//! recorded for them, e.g. the trace and request ID of a slow HTTP request,
+//! so a latency spike on a dashboard links to a trace.
+//!
+//! The Prometheus client does not keep exemplars, so they are kept here, one
+//! per bucket of each histogram series, and merged in when encoding.
+
+use crate::{MetricsRegistry, Result};
+use prometheus::proto::{LabelPair, Metric, MetricFamily, MetricType};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Content type of the OpenMetrics text format
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Longest label set of an exemplar, in characters (set by the format)
+pub const MAX_EXEMPLAR_LABELS_LENGTH: usize = 128;
+
+/// A sample observed by a histogram, with labels identifying where it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exemplar {
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+    /// Seconds since the Unix epoch
+    pub timestamp: f64,
+}
+
+/// Histogram series: metric name and label pairs sorted by name
+type SeriesKey = (String, Vec<(String, String)>);
+
+/// Last exemplar of each bucket of each histogram series
+#[derive(Debug, Default)]
+pub(crate) struct Exemplars {
+    series: Mutex<HashMap<SeriesKey, Vec<Option<Exemplar>>>>,
+}
+
+impl Exemplars {
+    /// Keep an exemplar for the bucket of `buckets` (upper bounds, without
+    /// `+Inf`) that `value` falls into
+    ///
+    /// Exemplars whose labels are longer than the format allows are dropped.
+    pub(crate) fn record(
+        &self,
+        name: &str,
+        labels: &[(&str, &str)],
+        buckets: &[f64],
+        value: f64,
+        exemplar: &[(&str, &str)],
+    ) {
+        let length: usize = exemplar
+            .iter()
+            .map(|(name, value)| name.chars().count() + value.chars().count())
+            .sum();
+        if exemplar.is_empty() || length > MAX_EXEMPLAR_LABELS_LENGTH {
+            return;
+        }
+
+        let bucket = buckets.iter().position(|&bound| value <= bound).unwrap_or(buckets.len());
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64());
+        let mut series = self.series.lock().unwrap();
+        let slots = series
+            .entry(series_key(name, labels.iter().map(|(name, value)| (*name, *value))))
+            .or_insert_with(|| vec![None; buckets.len() + 1]);
+        slots[bucket] = Some(Exemplar {
+            labels: exemplar
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            value,
+            timestamp,
+        });
+    }
+
+    /// Exemplars of a series by bucket index (the last one is `+Inf`)
+    fn get(&self, name: &str, labels: &[LabelPair]) -> Option<Vec<Option<Exemplar>>> {
+        let key = series_key(name, labels.iter().map(|pair| (pair.get_name(), pair.get_value())));
+        self.series.lock().unwrap().get(&key).cloned()
+    }
+}
+
+fn series_key<'a>(name: &str, labels: impl Iterator<Item = (&'a str, &'a str)>) -> SeriesKey {
+    let mut labels: Vec<_> = labels.map(|(name, value)| (name.to_string(), value.to_string())).collect();
+    labels.sort();
+    (name.to_string(), labels)
+}
+
+impl MetricsRegistry {
+    /// Encode all metrics in the OpenMetrics text format, with exemplars
+    pub fn encode_openmetrics(&self) -> Result<String> {
+        Ok(encode(&self.gather(), &self.exemplars))
+    }
+}
+
+fn encode(families: &[MetricFamily], exemplars: &Exemplars) -> String {
+    let mut out = String::new();
+    for family in families {
+        let name = family.get_name();
+        let (family_name, kind) = match family.get_field_type() {
+            MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+            MetricType::GAUGE => (name, "gauge"),
+            MetricType::HISTOGRAM => (name, "histogram"),
+            MetricType::SUMMARY => (name, "summary"),
+            MetricType::UNTYPED => (name, "unknown"),
+        };
+        let _ = writeln!(out, "# TYPE {} {}", family_name, kind);
+        if !family.get_help().is_empty() {
+            let _ = writeln!(out, "# HELP {} {}", family_name, escape(family.get_help()));
+        }
+
+        for metric in family.get_metric() {
+            let labels = metric.get_label();
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    sample(&mut out, &format!("{}_total", family_name), labels, None, &float(metric.get_counter().get_value()));
+                }
+                MetricType::GAUGE => sample(&mut out, name, labels, None, &float(metric.get_gauge().get_value())),
+                MetricType::UNTYPED => sample(&mut out, name, labels, None, &float(metric.get_untyped().get_value())),
+                MetricType::HISTOGRAM => histogram(&mut out, name, metric, exemplars.get(name, labels)),
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        let extra = ("quantile", float(quantile.get_quantile()));
+                        sample(&mut out, name, labels, Some(extra), &float(quantile.get_value()));
+                    }
+                    sample(&mut out, &format!("{}_sum", name), labels, None, &float(summary.get_sample_sum()));
+                    sample(&mut out, &format!("{}_count", name), labels, None, &summary.get_sample_count().to_string());
+                }
+            }
+        }
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+fn histogram(out: &mut String, name: &str, metric: &Metric, exemplars: Option<Vec<Option<Exemplar>>>) {
+    let labels = metric.get_label();
+    let histogram = metric.get_histogram();
+    let exemplar = |index: usize| exemplars.as_ref().and_then(|slots| slots.get(index).cloned().flatten());
+    let bucket_name = format!("{}_bucket", name);
+
+    let buckets = histogram.get_bucket();
+    for (index, bucket) in buckets.iter().enumerate() {
+        let le = ("le", float(bucket.get_upper_bound()));
+        bucket_sample(out, &bucket_name, labels, Some(le), &bucket.get_cumulative_count().to_string(), exemplar(index));
+    }
+    if buckets.last().map_or(true, |bucket| bucket.get_upper_bound() != f64::INFINITY) {
+        let le = ("le", "+Inf".to_string());
+        bucket_sample(out, &bucket_name, labels, Some(le), &histogram.get_sample_count().to_string(), exemplar(buckets.len()));
+    }
+    sample(out, &format!("{}_sum", name), labels, None, &float(histogram.get_sample_sum()));
+    sample(out, &format!("{}_count", name), labels, None, &histogram.get_sample_count().to_string());
+}
+
+/// Write a sample line, with one more label (`le`, `quantile`) if given
+fn sample(out: &mut String, name: &str, labels: &[LabelPair], extra: Option<(&str, String)>, value: &str) {
+    bucket_sample(out, name, labels, extra, value, None);
+}
+
+fn bucket_sample(
+    out: &mut String,
+    name: &str,
+    labels: &[LabelPair],
+    extra: Option<(&str, String)>,
+    value: &str,
+    exemplar: Option<Exemplar>,
+) {
+    out.push_str(name);
+    let mut pairs: Vec<(&str, &str)> = labels.iter().map(|pair| (pair.get_name(), pair.get_value())).collect();
+    if let Some((label, value)) = &extra {
+        pairs.push((label, value));
+    }
+    label_set(out, &pairs);
+    let _ = write!(out, " {}", value);
+    if let Some(exemplar) = exemplar {
+        out.push_str(" # ");
+        let pairs: Vec<(&str, &str)> = exemplar.labels.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+        label_set(out, &pairs);
+        let _ = write!(out, " {} {}", float(exemplar.value), exemplar.timestamp);
+    }
+    out.push('\n');
+}
+
+fn label_set(out: &mut String, pairs: &[(&str, &str)]) {
+    if pairs.is_empty() {
+        return;
+    }
+    out.push('{');
+    for (index, (name, value)) in pairs.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{}=\"{}\"", name, escape(value));
+    }
+    out.push('}');
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Floats as the format spells them (`1.0`, `+Inf`, `NaN`)
+fn float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_openmetrics() {
+        let registry = MetricsRegistry::new().unwrap();
+        registry.security().record_rejection("rate_limit");
+        registry.system().observe_http_duration("GET", "/health", 0.002);
+        registry
+            .system()
+            .observe_http_duration_with_exemplar("GET", "/health", 0.3, &[("trace_id", "4bf92f3577b34da6")]);
+
+        let text = registry.encode_openmetrics().unwrap();
+        assert!(text.ends_with("# EOF\n"));
+        assert!(text.contains("# TYPE security_rejections counter\n"));
+        assert!(text.contains("security_rejections_total{check=\"rate_limit\"} 1.0\n"));
+        assert!(text.contains("# TYPE http_request_duration_seconds histogram\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{method=\"GET\",path=\"/health\",le=\"0.005\"} 1\n"));
+        assert!(text.contains(
+            "http_request_duration_seconds_bucket{method=\"GET\",path=\"/health\",le=\"0.5\"} 2 # {trace_id=\"4bf92f3577b34da6\"} 0.3 "
+        ));
+        assert!(text.contains("http_request_duration_seconds_bucket{method=\"GET\",path=\"/health\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("http_request_duration_seconds_count{method=\"GET\",path=\"/health\"} 2\n"));
+    }
+
+    #[test]
+    fn test_exemplar_buckets() {
+        let exemplars = Exemplars::default();
+        let buckets = [0.1, 1.0];
+        exemplars.record("latency", &[("op", "get")], &buckets, 0.5, &[("trace_id", "a")]);
+        exemplars.record("latency", &[("op", "get")], &buckets, 0.7, &[("trace_id", "b")]);
+        exemplars.record("latency", &[("op", "get")], &buckets, 9.0, &[("trace_id", "c")]);
+        exemplars.record("latency", &[("op", "get")], &buckets, 0.01, &[("trace_id", &"x".repeat(200))]);
+
+        let mut pair = LabelPair::default();
+        pair.set_name("op".to_string());
+        pair.set_value("get".to_string());
+        let slots = exemplars.get("latency", &[pair]).unwrap();
+        assert!(slots[0].is_none());
+        assert_eq!(slots[1].as_ref().unwrap().labels, vec![("trace_id".to_string(), "b".to_string())]);
+        assert_eq!(slots[2].as_ref().unwrap().value, 9.0);
+    }
+}
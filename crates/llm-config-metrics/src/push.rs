@@ -0,0 +1,199 @@
+//! Pushing metrics to a Prometheus Pushgateway
+//!
+//! For deployments Prometheus cannot scrape (batch hosts, locked-down
+//! networks), [`spawn_push`] pushes the gathered metrics to a Pushgateway at
+//! a fixed interval. Each push replaces the metrics of the group named by the
+//! job and grouping labels of the [`PushConfig`], so the gateway always holds
+//! the latest values of every instance that pushes with its own labels.
+
+use crate::{MetricsError, MetricsRegistry, Result};
+use base64::engine::general_purpose::URL_SAFE;
+use base64::Engine;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default interval between pushes
+pub const DEFAULT_PUSH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Time a single push may take before it is abandoned
+pub const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where and how often metrics are pushed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushConfig {
+    /// Base URL of the Pushgateway, e.g. `http://pushgateway:9091`
+    pub url: String,
+    pub job: String,
+    /// Grouping labels besides `job`, e.g. `instance`
+    pub labels: BTreeMap<String, String>,
+    pub interval: Duration,
+}
+
+impl PushConfig {
+    /// Push to the gateway at `url` as `job` every [`DEFAULT_PUSH_INTERVAL`]
+    pub fn new(url: impl Into<String>, job: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            job: job.into(),
+            labels: BTreeMap::new(),
+            interval: DEFAULT_PUSH_INTERVAL,
+        }
+    }
+
+    /// Add a grouping label
+    pub fn with_label(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(name.into(), value.into());
+        self
+    }
+
+    /// Add a grouping label given as `NAME=VALUE`, e.g. from the command line
+    pub fn with_label_spec(self, spec: &str) -> Result<Self> {
+        let (name, value) = spec
+            .split_once('=')
+            .ok_or_else(|| MetricsError::InvalidPushLabel(spec.to_string()))?;
+        Ok(self.with_label(name.trim(), value.trim()))
+    }
+
+    /// Push this often
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "push interval must be non-zero");
+        self.interval = interval;
+        self
+    }
+
+    /// URL of the group, `<url>/metrics/job/<job>[/<label>/<value>...]`
+    ///
+    /// Values that are not plain URL path characters are base64url-encoded,
+    /// as the gateway expects (`<label>@base64/<encoded>`).
+    pub fn group_url(&self) -> String {
+        let mut url = format!("{}/metrics", self.url.trim_end_matches('/'));
+        for (name, value) in std::iter::once(("job", self.job.as_str()))
+            .chain(self.labels.iter().map(|(name, value)| (name.as_str(), value.as_str())))
+        {
+            url.push_str(&path_segment(name, value));
+        }
+        url
+    }
+}
+
+fn path_segment(name: &str, value: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-._~".contains(c);
+    if !value.is_empty() && value.chars().all(plain) {
+        format!("/{}/{}", name, value)
+    } else if value.is_empty() {
+        format!("/{}@base64/=", name)
+    } else {
+        format!("/{}@base64/{}", name, URL_SAFE.encode(value))
+    }
+}
+
+impl MetricsRegistry {
+    /// Replace the metrics of the group of `config` on its Pushgateway with
+    /// the gathered metrics
+    pub async fn push(&self, client: &reqwest::Client, config: &PushConfig) -> Result<()> {
+        let response = client
+            .put(config.group_url())
+            .header(reqwest::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(self.encode_text()?)
+            .send()
+            .await
+            .map_err(|e| MetricsError::PushFailed(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(MetricsError::PushFailed(format!("{}: {}", status, body.trim())));
+        }
+        Ok(())
+    }
+}
+
+/// Push the metrics of `registry` every `config.interval`, starting now
+///
+/// Failed pushes, including those taking longer than [`PUSH_TIMEOUT`], are
+/// logged and retried at the next interval.
+pub fn spawn_push(registry: Arc<MetricsRegistry>, config: PushConfig) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder().timeout(PUSH_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to create the metrics push client: {}", e);
+                return;
+            }
+        };
+        let mut interval = tokio::time::interval(config.interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = registry.push(&client, &config).await {
+                tracing::warn!("Failed to push metrics to {}: {}", config.url, e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_group_url() {
+        let config = PushConfig::new("http://gateway:9091/", "llm-config")
+            .with_label("instance", "api-1")
+            .with_label("path", "/var/lib")
+            .with_label("zone", "");
+        assert_eq!(
+            config.group_url(),
+            "http://gateway:9091/metrics/job/llm-config/instance/api-1/path@base64/L3Zhci9saWI=/zone@base64/="
+        );
+    }
+
+    #[test]
+    fn test_with_label_spec() {
+        let config = PushConfig::new("http://gateway:9091", "llm-config")
+            .with_label_spec("instance = api-1")
+            .unwrap()
+            .with_label_spec("url=http://a?b=c")
+            .unwrap();
+        assert_eq!(config.labels["instance"], "api-1");
+        assert_eq!(config.labels["url"], "http://a?b=c");
+        assert!(matches!(
+            config.with_label_spec("instance"),
+            Err(MetricsError::InvalidPushLabel(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_push() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let gateway = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            while !String::from_utf8_lossy(&request).contains("security_rejections_total") {
+                let read = socket.read(&mut buffer).await.unwrap();
+                assert!(read > 0);
+                request.extend_from_slice(&buffer[..read]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let registry = MetricsRegistry::new().unwrap();
+        registry.security().record_rejection("rate_limit");
+        let config = PushConfig::new(url, "llm-config").with_label("instance", "api-1");
+        registry.push(&reqwest::Client::new(), &config).await.unwrap();
+
+        let request = gateway.await.unwrap();
+        assert!(request.starts_with("PUT /metrics/job/llm-config/instance/api-1 HTTP/1.1"));
+        assert!(request.contains("security_rejections_total{check=\"rate_limit\"} 1"));
+    }
+}
@@ -0,0 +1,220 @@
+//! [`MetricsSink`] implementation of the registry
+//!
+//! Samples named after a family the registry defines for configs, the cache,
+//! storage or crypto (`cache_hits_total`, `storage_operations_total`, ...)
+//! are recorded to it, with labels matched by name. Other names get a family
+//! of their own on first use, labelled with the label names of that sample.
+//! Samples that do not fit their family (a counter recorded as a gauge,
+//! other label names) are dropped and logged at debug level.
+
+use crate::MetricsRegistry;
+use llm_config_sink::MetricsSink;
+use prometheus::{Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, Opts, Registry};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// What a sample was recorded as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Counter,
+    Histogram,
+    Gauge,
+}
+
+/// A family samples are recorded to
+#[derive(Clone)]
+enum Family {
+    Counter(Counter),
+    CounterVec(CounterVec),
+    Histogram(Histogram),
+    HistogramVec(HistogramVec),
+    Gauge(Gauge),
+    GaugeVec(GaugeVec),
+}
+
+impl Family {
+    fn record(&self, kind: Kind, labels: &[(&str, &str)], value: f64) -> Result<(), String> {
+        let labels: HashMap<&str, &str> = labels.iter().copied().collect();
+        let unlabelled = || {
+            labels
+                .is_empty()
+                .then_some(())
+                .ok_or_else(|| "family has no labels".to_string())
+        };
+        match (self, kind) {
+            (Family::Counter(_) | Family::CounterVec(_), Kind::Counter) if value < 0.0 => {
+                return Err(format!("negative counter increment {}", value));
+            }
+            (Family::Counter(counter), Kind::Counter) => {
+                unlabelled()?;
+                counter.inc_by(value);
+            }
+            (Family::CounterVec(counters), Kind::Counter) => {
+                counters.get_metric_with(&labels).map_err(|e| e.to_string())?.inc_by(value);
+            }
+            (Family::Histogram(histogram), Kind::Histogram) => {
+                unlabelled()?;
+                histogram.observe(value);
+            }
+            (Family::HistogramVec(histograms), Kind::Histogram) => {
+                histograms.get_metric_with(&labels).map_err(|e| e.to_string())?.observe(value);
+            }
+            (Family::Gauge(gauge), Kind::Gauge) => {
+                unlabelled()?;
+                gauge.set(value);
+            }
+            (Family::GaugeVec(gauges), Kind::Gauge) => {
+                gauges.get_metric_with(&labels).map_err(|e| e.to_string())?.set(value);
+            }
+            (_, kind) => return Err(format!("family is not a {:?}", kind)),
+        }
+        Ok(())
+    }
+}
+
+/// Families registered for names the registry does not define
+pub(crate) struct SinkFamilies {
+    registry: Arc<Registry>,
+    families: Mutex<HashMap<String, Family>>,
+}
+
+impl SinkFamilies {
+    pub(crate) fn new(registry: Arc<Registry>) -> Self {
+        Self {
+            registry,
+            families: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The family of `name`, registered as `kind` with the label names of
+    /// `labels` if it does not exist yet
+    fn get_or_register(&self, name: &str, kind: Kind, labels: &[(&str, &str)]) -> prometheus::Result<Family> {
+        let mut families = self.families.lock().unwrap();
+        if let Some(family) = families.get(name) {
+            return Ok(family.clone());
+        }
+
+        let label_names: Vec<&str> = labels.iter().map(|(name, _)| *name).collect();
+        let (family, collector): (Family, Box<dyn prometheus::core::Collector>) = match kind {
+            Kind::Counter => {
+                let counters = CounterVec::new(Opts::new(name, name), &label_names)?;
+                (Family::CounterVec(counters.clone()), Box::new(counters))
+            }
+            Kind::Histogram => {
+                let histograms = HistogramVec::new(HistogramOpts::new(name, name), &label_names)?;
+                (Family::HistogramVec(histograms.clone()), Box::new(histograms))
+            }
+            Kind::Gauge => {
+                let gauges = GaugeVec::new(Opts::new(name, name), &label_names)?;
+                (Family::GaugeVec(gauges.clone()), Box::new(gauges))
+            }
+        };
+        self.registry.register(collector)?;
+        families.insert(name.to_string(), family.clone());
+        Ok(family)
+    }
+}
+
+impl MetricsRegistry {
+    /// The family the registry defines under `name`
+    fn defined_family(&self, name: &str) -> Option<Family> {
+        let config = &self.config_metrics;
+        let cache = &self.cache_metrics;
+        let storage = &self.storage_metrics;
+        let crypto = &self.crypto_metrics;
+        Some(match name {
+            "config_operations_total" => Family::CounterVec(config.operations_total.clone()),
+            "config_operation_duration_seconds" => Family::HistogramVec(config.operation_duration.clone()),
+            "config_active_total" => Family::GaugeVec(config.active_configs.clone()),
+            "config_errors_total" => Family::CounterVec(config.errors_total.clone()),
+            "config_type_mismatches_total" => Family::CounterVec(config.type_mismatches_total.clone()),
+            "cache_hits_total" => Family::CounterVec(cache.hits_total.clone()),
+            "cache_misses_total" => Family::CounterVec(cache.misses_total.clone()),
+            "cache_evictions_total" => Family::CounterVec(cache.evictions_total.clone()),
+            "cache_size_entries" => Family::GaugeVec(cache.size.clone()),
+            "cache_size_bytes" => Family::GaugeVec(cache.size_bytes.clone()),
+            "cache_operation_duration_seconds" => Family::HistogramVec(cache.operation_duration.clone()),
+            "cache_warmup_duration_seconds" => Family::Histogram(cache.warmup_duration.clone()),
+            "cache_warmed_entries_total" => Family::Counter(cache.warmed_entries_total.clone()),
+            "storage_operations_total" => Family::CounterVec(storage.operations_total.clone()),
+            "storage_operation_duration_seconds" => Family::HistogramVec(storage.operation_duration.clone()),
+            "storage_size_bytes" => Family::Gauge(storage.storage_size_bytes.clone()),
+            "storage_errors_total" => Family::CounterVec(storage.errors_total.clone()),
+            "crypto_operations_total" => Family::CounterVec(crypto.operations_total.clone()),
+            "crypto_operation_duration_seconds" => Family::HistogramVec(crypto.operation_duration.clone()),
+            "crypto_key_rotations_total" => Family::Counter(crypto.key_rotations_total.clone()),
+            "crypto_encryption_errors_total" => Family::Counter(crypto.encryption_errors_total.clone()),
+            "crypto_key_age_days" => Family::Gauge(crypto.key_age_days.clone()),
+            _ => return None,
+        })
+    }
+
+    fn record_sample(&self, name: &str, kind: Kind, labels: &[(&str, &str)], value: f64) {
+        let family = match self.defined_family(name) {
+            Some(family) => Ok(family),
+            None => self.sink_families.get_or_register(name, kind, labels).map_err(|e| e.to_string()),
+        };
+        if let Err(e) = family.and_then(|family| family.record(kind, labels, value)) {
+            tracing::debug!("Dropped {:?} sample of {} {:?}: {}", kind, name, labels, e);
+        }
+    }
+}
+
+impl MetricsSink for MetricsRegistry {
+    fn record_counter(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.record_sample(name, Kind::Counter, labels, value);
+    }
+
+    fn observe(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.record_sample(name, Kind::Histogram, labels, value);
+    }
+
+    fn gauge(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.record_sample(name, Kind::Gauge, labels, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defined_families() {
+        let registry = MetricsRegistry::new().unwrap();
+        let sink: &dyn MetricsSink = &registry;
+        sink.record_counter("cache_hits_total", &[("tier", "l1")], 3.0);
+        sink.record_counter("cache_misses_total", &[("tier", "l1")], 1.0);
+        sink.record_counter("cache_warmed_entries_total", &[], 4.0);
+        sink.record_counter("crypto_operations_total", &[("algorithm", "aes-256-gcm"), ("operation", "encrypt")], 1.0);
+        sink.gauge("crypto_key_age_days", &[], 12.0);
+
+        assert!((registry.cache().hit_rate("l1") - 0.75).abs() < 0.01);
+        assert_eq!(registry.cache().warmed_entries(), 4);
+        assert_eq!(registry.crypto().key_age_days(), 12.0);
+        let text = registry.encode_text().unwrap();
+        assert!(text.contains("crypto_operations_total{algorithm=\"aes-256-gcm\",operation=\"encrypt\"} 1"));
+    }
+
+    #[test]
+    fn test_other_families() {
+        let registry = MetricsRegistry::new().unwrap();
+        let sink: &dyn MetricsSink = &registry;
+        sink.record_counter("plugin_calls_total", &[("plugin", "vault")], 2.0);
+        sink.record_counter("plugin_calls_total", &[("plugin", "vault")], 1.0);
+        sink.observe("plugin_call_duration_seconds", &[], 0.2);
+
+        // Samples that do not fit their family are dropped
+        sink.gauge("plugin_calls_total", &[("plugin", "vault")], 9.0);
+        sink.record_counter("plugin_calls_total", &[("other", "label")], 1.0);
+        sink.record_counter("cache_hits_total", &[], 1.0);
+        sink.record_counter("cache_warmed_entries_total", &[], -1.0);
+        sink.observe("cache_warmed_entries_total", &[], 1.0);
+
+        let text = registry.encode_text().unwrap();
+        assert!(text.contains("plugin_calls_total{plugin=\"vault\"} 3\n"));
+        assert!(text.contains("plugin_call_duration_seconds_count 1\n"));
+        assert!(!text.contains("other=\"label\""));
+        assert_eq!(registry.cache().warmed_entries(), 0);
+        assert_eq!(registry.cache().hit_rate("l1"), 0.0);
+    }
+}
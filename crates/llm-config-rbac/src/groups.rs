@@ -0,0 +1,131 @@
+//! Groups of users that share role assignments
+//!
+//! Roles assigned to a group apply to its members. Members are listed
+//! explicitly or come from an identity provider: a group lists the external
+//! groups (e.g. values of an OIDC `groups` claim) whose members belong to it,
+//! so SSO-provisioned users get their roles without a manual assignment.
+
+use crate::roles::{Role, RoleAssignment};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Role assigned to a group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupRole {
+    pub role: Role,
+    /// Optional namespace scope; a glob pattern such as `team-a/*`
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+/// Group of users with shared role assignments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    pub name: String,
+    /// Human-readable description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Users that belong to the group
+    #[serde(default)]
+    pub members: BTreeSet<String>,
+    /// External groups whose members belong to the group
+    #[serde(default)]
+    pub external_groups: BTreeSet<String>,
+    /// Roles the members get
+    #[serde(default)]
+    pub roles: Vec<GroupRole>,
+}
+
+impl Group {
+    /// Create an empty group
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            members: BTreeSet::new(),
+            external_groups: BTreeSet::new(),
+            roles: Vec::new(),
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a member
+    pub fn with_member(mut self, user: impl Into<String>) -> Self {
+        self.members.insert(user.into());
+        self
+    }
+
+    /// Map an external group (e.g. `platform-eng` from an OIDC `groups` claim) to the group
+    pub fn with_external_group(mut self, external_group: impl Into<String>) -> Self {
+        self.external_groups.insert(external_group.into());
+        self
+    }
+
+    /// Assign a role to the group, optionally limited to a namespace pattern
+    pub fn with_role(mut self, role: Role, scope: Option<String>) -> Self {
+        self.assign(GroupRole { role, scope });
+        self
+    }
+
+    /// Assign a role to the group (no-op if it already has it)
+    pub fn assign(&mut self, role: GroupRole) {
+        if !self.roles.contains(&role) {
+            self.roles.push(role);
+        }
+    }
+
+    /// Remove a role from the group, returning whether it had it
+    pub fn revoke(&mut self, role: &GroupRole) -> bool {
+        let before = self.roles.len();
+        self.roles.retain(|r| r != role);
+        self.roles.len() != before
+    }
+
+    /// Check if a user with the given external groups belongs to the group
+    pub fn includes(&self, user: &str, external_groups: &[String]) -> bool {
+        self.members.contains(user)
+            || external_groups
+                .iter()
+                .any(|external_group| self.external_groups.contains(external_group))
+    }
+
+    /// The group's roles as assignments to a user
+    pub fn assignments_for(&self, user: &str) -> impl Iterator<Item = RoleAssignment> + '_ {
+        let user = user.to_string();
+        self.roles.iter().map(move |role| RoleAssignment {
+            user: user.clone(),
+            role: role.role.clone(),
+            scope: role.scope.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_membership() {
+        let group = Group::new("ml-platform")
+            .with_member("alice")
+            .with_external_group("sso-ml-eng")
+            .with_role(Role::Editor, Some("ml/*".to_string()))
+            .with_role(Role::Editor, Some("ml/*".to_string()));
+
+        assert_eq!(group.roles.len(), 1);
+        assert!(group.includes("alice", &[]));
+        assert!(group.includes("bob", &["sso-ml-eng".to_string()]));
+        assert!(!group.includes("bob", &["sso-finance".to_string()]));
+
+        let assignments: Vec<_> = group.assignments_for("bob").collect();
+        assert_eq!(
+            assignments,
+            vec![RoleAssignment::with_scope("bob", Role::Editor, "ml/*")]
+        );
+    }
+}
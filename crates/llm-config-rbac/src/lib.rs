@@ -2,17 +2,28 @@
 //!
 //! This module provides comprehensive RBAC capabilities including:
 //! - Predefined roles (Admin, Editor, Viewer, Auditor)
+//! - Custom roles persisted to storage
 //! - Fine-grained permissions
 //! - Policy enforcement
 //! - Role hierarchies
+//! - Groups, with members mapped from external identity groups
+//! - Temporary (break-glass) role elevation
+//! - Access reviews
 
+pub mod groups;
 pub mod permissions;
 pub mod policy;
+pub mod review;
 pub mod roles;
+pub mod store;
 
+pub use groups::{Group, GroupRole};
+pub use llm_config_storage::namespace_matches;
 pub use permissions::{Action, Permission, Resource};
 pub use policy::PolicyEnforcer;
-pub use roles::{Role, RoleAssignment};
+pub use review::{AccessReview, AccessUse, PermissionUsage, UserAccess};
+pub use roles::{Elevation, Role, RoleAssignment, RoleDefinition};
+pub use store::RoleStore;
 
 use thiserror::Error;
 
@@ -27,8 +38,14 @@ pub enum RbacError {
     #[error("Invalid permission: {0}")]
     InvalidPermission(String),
 
+    #[error("Invalid elevation: {0}")]
+    InvalidElevation(String),
+
     #[error("User not found: {0}")]
     UserNotFound(String),
+
+    #[error("Storage error: {0}")]
+    StorageError(#[from] llm_config_storage::StorageError),
 }
 
 pub type Result<T> = std::result::Result<T, RbacError>;
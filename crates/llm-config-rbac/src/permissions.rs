@@ -1,7 +1,9 @@
 //! Permission definitions
 
+use crate::RbacError;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
 /// Resource types in the system
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -37,6 +39,23 @@ impl fmt::Display for Resource {
     }
 }
 
+impl FromStr for Resource {
+    type Err = RbacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "config" => Ok(Resource::Config),
+            "secret" => Ok(Resource::Secret),
+            "history" => Ok(Resource::History),
+            "audit_log" | "auditlog" => Ok(Resource::AuditLog),
+            "system" => Ok(Resource::System),
+            "users" => Ok(Resource::Users),
+            "roles" => Ok(Resource::Roles),
+            _ => Err(RbacError::InvalidPermission(format!("Unknown resource: {}", s))),
+        }
+    }
+}
+
 /// Actions that can be performed on resources
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -57,6 +76,8 @@ pub enum Action {
     Export,
     /// Import data
     Import,
+    /// Decrypt and view secret plaintext
+    Reveal,
 }
 
 impl fmt::Display for Action {
@@ -70,6 +91,26 @@ impl fmt::Display for Action {
             Action::Rollback => write!(f, "rollback"),
             Action::Export => write!(f, "export"),
             Action::Import => write!(f, "import"),
+            Action::Reveal => write!(f, "reveal"),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = RbacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "read" => Ok(Action::Read),
+            "create" => Ok(Action::Create),
+            "update" => Ok(Action::Update),
+            "delete" => Ok(Action::Delete),
+            "list" => Ok(Action::List),
+            "rollback" => Ok(Action::Rollback),
+            "export" => Ok(Action::Export),
+            "import" => Ok(Action::Import),
+            "reveal" => Ok(Action::Reveal),
+            _ => Err(RbacError::InvalidPermission(format!("Unknown action: {}", s))),
         }
     }
 }
@@ -99,6 +140,18 @@ impl fmt::Display for Permission {
     }
 }
 
+impl FromStr for Permission {
+    type Err = RbacError;
+
+    /// Parse a permission in `resource:action` form (e.g. `config:update`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (resource, action) = s.split_once(':').ok_or_else(|| {
+            RbacError::InvalidPermission(format!("Expected resource:action, got {}", s))
+        })?;
+        Ok(Self::new(resource.parse()?, action.parse()?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,6 +177,16 @@ mod tests {
         assert_eq!(perm.to_string(), "config:update");
     }
 
+    #[test]
+    fn test_permission_from_str() {
+        let perm: Permission = "audit_log:export".parse().unwrap();
+        assert_eq!(perm, Permission::new(Resource::AuditLog, Action::Export));
+        assert_eq!(perm.to_string().parse::<Permission>().unwrap(), perm);
+
+        assert!("config".parse::<Permission>().is_err());
+        assert!("config:fly".parse::<Permission>().is_err());
+    }
+
     #[test]
     fn test_permission_serialization() {
         let perm = Permission::new(Resource::Secret, Action::Create);
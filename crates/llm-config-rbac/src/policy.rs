@@ -1,15 +1,48 @@
 //! Policy enforcement engine
 
 use crate::{
+    groups::Group,
     permissions::{Action, Resource},
-    roles::RoleAssignment,
+    roles::{Elevation, Role, RoleAssignment, RoleDefinition},
+    store::{validate_group_name, validate_role_name, RoleStore},
     RbacError, Result,
 };
+use chrono::Utc;
+use llm_config_metrics::MetricsRegistry;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Cached decisions kept before the decision cache starts over
+const DECISION_CACHE_CAPACITY: usize = 10_000;
+
+/// Decision cache key: user, external groups (sorted), resource, action and scope
+type DecisionKey = (String, Vec<String>, Resource, Action, Option<String>);
+
+/// Outcome of a permission check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decision {
+    Allowed,
+    Denied,
+    NoAssignments,
+}
 
 /// Policy enforcer that checks permissions based on role assignments
+///
+/// Users get the roles assigned to them directly and those of the groups
+/// they belong to, either as listed members or through one of the external
+/// groups their identity provider reports.
+///
+/// Decisions are cached per user, external groups, resource, action and
+/// scope until the roles, assignments or groups change. Checks of users with
+/// an active elevation bypass the cache, as elevations expire on their own.
 pub struct PolicyEnforcer {
     assignments: HashMap<String, Vec<RoleAssignment>>,
+    role_definitions: HashMap<String, RoleDefinition>,
+    groups: HashMap<String, Group>,
+    elevations: Vec<Elevation>,
+    decisions: RwLock<HashMap<DecisionKey, Decision>>,
+    metrics: Option<Arc<MetricsRegistry>>,
 }
 
 impl PolicyEnforcer {
@@ -17,17 +50,248 @@ impl PolicyEnforcer {
     pub fn new() -> Self {
         Self {
             assignments: HashMap::new(),
+            role_definitions: HashMap::new(),
+            groups: HashMap::new(),
+            elevations: Vec::new(),
+            decisions: RwLock::new(HashMap::new()),
+            metrics: None,
+        }
+    }
+
+    /// Count decision cache hits and misses in the given registry
+    pub fn set_metrics(&mut self, metrics: Arc<MetricsRegistry>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Forget all cached decisions
+    fn invalidate_decisions(&mut self) {
+        self.decisions.get_mut().unwrap().clear();
+    }
+
+    /// Create a policy enforcer with the custom roles, assignments and groups from a role store
+    pub fn from_store(store: &RoleStore) -> Result<Self> {
+        let mut enforcer = Self::new();
+        enforcer.load_role_definitions(store)?;
+        enforcer.load_assignments(store)?;
+        enforcer.load_groups(store)?;
+        Ok(enforcer)
+    }
+
+    /// Replace all role assignments with those persisted in a role store
+    pub fn load_assignments(&mut self, store: &RoleStore) -> Result<usize> {
+        self.invalidate_decisions();
+        self.assignments.clear();
+        let assignments = store.load_all_assignments()?;
+        let count = assignments.len();
+        for assignment in assignments {
+            self.assign_role(assignment);
+        }
+        Ok(count)
+    }
+
+    /// Load (or reload) all custom role definitions from a role store
+    pub fn load_role_definitions(&mut self, store: &RoleStore) -> Result<usize> {
+        self.invalidate_decisions();
+        self.role_definitions = store
+            .load_all()?
+            .into_iter()
+            .map(|definition| (definition.name.clone(), definition))
+            .collect();
+        Ok(self.role_definitions.len())
+    }
+
+    /// Define (or redefine) a custom role
+    pub fn define_role(&mut self, definition: RoleDefinition) -> Result<()> {
+        validate_role_name(&definition.name)?;
+        self.invalidate_decisions();
+        self.role_definitions
+            .insert(definition.name.clone(), definition);
+        Ok(())
+    }
+
+    /// Remove a custom role definition
+    ///
+    /// Assignments of the removed role remain but no longer grant anything.
+    pub fn remove_role_definition(&mut self, name: &str) -> Option<RoleDefinition> {
+        self.invalidate_decisions();
+        self.role_definitions.remove(name)
+    }
+
+    /// Get a custom role definition
+    pub fn get_role_definition(&self, name: &str) -> Option<&RoleDefinition> {
+        self.role_definitions.get(name)
+    }
+
+    /// List all custom role definitions, ordered by name
+    pub fn role_definitions(&self) -> Vec<&RoleDefinition> {
+        let mut definitions: Vec<_> = self.role_definitions.values().collect();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
+    /// Load (or reload) all groups from a role store
+    pub fn load_groups(&mut self, store: &RoleStore) -> Result<usize> {
+        self.invalidate_decisions();
+        self.groups = store
+            .load_groups()?
+            .into_iter()
+            .map(|group| (group.name.clone(), group))
+            .collect();
+        Ok(self.groups.len())
+    }
+
+    /// Define (or redefine) a group
+    pub fn define_group(&mut self, group: Group) -> Result<()> {
+        validate_group_name(&group.name)?;
+        self.invalidate_decisions();
+        self.groups.insert(group.name.clone(), group);
+        Ok(())
+    }
+
+    /// Remove a group; its members lose the roles they had through it
+    pub fn remove_group(&mut self, name: &str) -> Option<Group> {
+        self.invalidate_decisions();
+        self.groups.remove(name)
+    }
+
+    /// Get a group
+    pub fn get_group(&self, name: &str) -> Option<&Group> {
+        self.groups.get(name)
+    }
+
+    /// List all groups, ordered by name
+    pub fn groups(&self) -> Vec<&Group> {
+        let mut groups: Vec<_> = self.groups.values().collect();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+
+    /// Role assignments of a user with the given external groups: the direct
+    /// ones followed by those inherited from the groups the user belongs to
+    pub fn assignments_of(&self, user: &str, external_groups: &[String]) -> Vec<RoleAssignment> {
+        let mut assignments: Vec<RoleAssignment> =
+            self.get_user_roles(user).into_iter().cloned().collect();
+        for group in self.groups() {
+            if group.includes(user, external_groups) {
+                assignments.extend(group.assignments_for(user));
+            }
+        }
+        assignments
+    }
+
+    /// Check whether an assignment grants the action in the given scope
+    fn assignment_allows(
+        &self,
+        assignment: &RoleAssignment,
+        resource: &Resource,
+        action: &Action,
+        scope: Option<&str>,
+    ) -> bool {
+        if !assignment.applies_to_scope(scope) {
+            return false;
+        }
+
+        match &assignment.role {
+            Role::Custom(name) => self
+                .role_definitions
+                .get(name)
+                .is_some_and(|definition| {
+                    definition.applies_to_namespace(scope) && definition.can(resource, action)
+                }),
+            role => role.can(resource, action),
         }
     }
 
     /// Assign a role to a user
     pub fn assign_role(&mut self, assignment: RoleAssignment) {
+        self.invalidate_decisions();
         self.assignments
             .entry(assignment.user.clone())
             .or_insert_with(Vec::new)
             .push(assignment);
     }
 
+    /// Grant a user a role for a limited time (break-glass access)
+    ///
+    /// The elevation applies to all namespaces and stops granting anything
+    /// once `ttl` has passed. Elevations are kept in memory only.
+    pub fn assign_role_temporary(
+        &mut self,
+        user: impl Into<String>,
+        role: Role,
+        ttl: Duration,
+        justification: impl Into<String>,
+    ) -> Result<Elevation> {
+        let justification = justification.into();
+        if justification.trim().is_empty() {
+            return Err(RbacError::InvalidElevation(
+                "A justification is required".to_string(),
+            ));
+        }
+        let ttl = chrono::Duration::from_std(ttl)
+            .ok()
+            .filter(|ttl| *ttl > chrono::Duration::zero())
+            .ok_or_else(|| RbacError::InvalidElevation(format!("Invalid TTL: {:?}", ttl)))?;
+
+        self.remove_expired_elevations();
+        self.invalidate_decisions();
+        let granted_at = Utc::now();
+        let elevation = Elevation {
+            assignment: RoleAssignment::new(user, role),
+            justification,
+            granted_at,
+            expires_at: granted_at + ttl,
+        };
+        self.elevations.push(elevation.clone());
+        Ok(elevation)
+    }
+
+    /// Elevations that have not expired, ordered by expiry
+    pub fn active_elevations(&self) -> Vec<&Elevation> {
+        let now = Utc::now();
+        let mut elevations: Vec<_> = self
+            .elevations
+            .iter()
+            .filter(|elevation| elevation.is_active(now))
+            .collect();
+        elevations.sort_by_key(|elevation| elevation.expires_at);
+        elevations
+    }
+
+    /// Drop expired elevations, returning how many were removed
+    pub fn remove_expired_elevations(&mut self) -> usize {
+        let now = Utc::now();
+        let before = self.elevations.len();
+        self.elevations.retain(|elevation| elevation.is_active(now));
+        before - self.elevations.len()
+    }
+
+    /// The active elevation an action is allowed through, if the user's
+    /// regular role assignments do not allow it
+    pub fn granting_elevation(
+        &self,
+        user: &str,
+        external_groups: &[String],
+        resource: &Resource,
+        action: &Action,
+        scope: Option<&str>,
+    ) -> Option<&Elevation> {
+        if self
+            .assignments_of(user, external_groups)
+            .iter()
+            .any(|assignment| self.assignment_allows(assignment, resource, action, scope))
+        {
+            return None;
+        }
+
+        let now = Utc::now();
+        self.elevations.iter().find(|elevation| {
+            elevation.assignment.user == user
+                && elevation.is_active(now)
+                && self.assignment_allows(&elevation.assignment, resource, action, scope)
+        })
+    }
+
     /// Remove a role from a user
     pub fn revoke_role(&mut self, user: &str, role_index: usize) -> Result<()> {
         let assignments = self
@@ -43,6 +307,7 @@ impl PolicyEnforcer {
         }
 
         assignments.remove(role_index);
+        self.decisions.get_mut().unwrap().clear();
 
         // Remove user entry if no more assignments
         if assignments.is_empty() {
@@ -52,6 +317,24 @@ impl PolicyEnforcer {
         Ok(())
     }
 
+    /// Remove a specific role assignment, returning whether it existed
+    pub fn revoke_assignment(&mut self, assignment: &RoleAssignment) -> bool {
+        let Some(assignments) = self.assignments.get_mut(&assignment.user) else {
+            return false;
+        };
+
+        let before = assignments.len();
+        assignments.retain(|a| a != assignment);
+        let removed = assignments.len() != before;
+        self.decisions.get_mut().unwrap().clear();
+
+        if assignments.is_empty() {
+            self.assignments.remove(&assignment.user);
+        }
+
+        removed
+    }
+
     /// Get all role assignments for a user
     pub fn get_user_roles(&self, user: &str) -> Vec<&RoleAssignment> {
         self.assignments
@@ -68,26 +351,116 @@ impl PolicyEnforcer {
         action: &Action,
         scope: Option<&str>,
     ) -> Result<()> {
-        let assignments = self.get_user_roles(user);
+        self.check_permission_as(user, &[], resource, action, scope)
+    }
 
-        if assignments.is_empty() {
-            return Err(RbacError::AccessDenied(format!(
+    /// Check if a user that belongs to the given external groups (e.g. from
+    /// an OIDC `groups` claim) can perform an action on a resource
+    pub fn check_permission_as(
+        &self,
+        user: &str,
+        external_groups: &[String],
+        resource: &Resource,
+        action: &Action,
+        scope: Option<&str>,
+    ) -> Result<()> {
+        let now = Utc::now();
+        let elevated = self
+            .elevations
+            .iter()
+            .any(|elevation| elevation.assignment.user == user && elevation.is_active(now));
+
+        let decision = if elevated {
+            self.decide(user, external_groups, resource, action, scope, true)
+        } else {
+            self.cached_decision(user, external_groups, resource, action, scope)
+        };
+
+        match decision {
+            Decision::Allowed => Ok(()),
+            Decision::NoAssignments => Err(RbacError::AccessDenied(format!(
                 "User {} has no role assignments",
                 user
-            )));
+            ))),
+            Decision::Denied => Err(RbacError::AccessDenied(format!(
+                "User {} is not authorized to {} on {}",
+                user, action, resource
+            ))),
         }
+    }
 
-        // Check if any of the user's roles allows the action
-        for assignment in assignments {
-            if assignment.applies_to_scope(scope) && assignment.role.can(resource, action) {
-                return Ok(());
+    /// Decide a permission check from the cache, deciding and caching it on a miss
+    fn cached_decision(
+        &self,
+        user: &str,
+        external_groups: &[String],
+        resource: &Resource,
+        action: &Action,
+        scope: Option<&str>,
+    ) -> Decision {
+        let mut groups = external_groups.to_vec();
+        groups.sort();
+        groups.dedup();
+        let key = (
+            user.to_string(),
+            groups,
+            resource.clone(),
+            action.clone(),
+            scope.map(str::to_string),
+        );
+        let cached = self.decisions.read().unwrap().get(&key).copied();
+        if let Some(metrics) = &self.metrics {
+            if cached.is_some() {
+                metrics.rbac().record_decision_cache_hit();
+            } else {
+                metrics.rbac().record_decision_cache_miss();
             }
         }
+        if let Some(decision) = cached {
+            return decision;
+        }
+
+        let decision = self.decide(user, external_groups, resource, action, scope, false);
+        let mut decisions = self.decisions.write().unwrap();
+        if decisions.len() >= DECISION_CACHE_CAPACITY {
+            decisions.clear();
+        }
+        decisions.insert(key, decision);
+        decision
+    }
+
+    /// Decide a permission check from the user's assignments, groups (and elevations)
+    fn decide(
+        &self,
+        user: &str,
+        external_groups: &[String],
+        resource: &Resource,
+        action: &Action,
+        scope: Option<&str>,
+        elevated: bool,
+    ) -> Decision {
+        let assignments = self.assignments_of(user, external_groups);
+        if assignments.is_empty() && !elevated {
+            return Decision::NoAssignments;
+        }
+
+        // Check if any of the user's roles allows the action
+        if assignments
+            .iter()
+            .any(|assignment| self.assignment_allows(assignment, resource, action, scope))
+        {
+            return Decision::Allowed;
+        }
 
-        Err(RbacError::AccessDenied(format!(
-            "User {} is not authorized to {} on {}",
-            user, action, resource
-        )))
+        if elevated
+            && self
+                .granting_elevation(user, external_groups, resource, action, scope)
+                .is_some()
+        {
+            return Decision::Allowed;
+        }
+
+        Decision::Denied
     }
 
     /// Check if a user has permission (returns bool instead of Result)
@@ -122,7 +495,7 @@ impl Default for PolicyEnforcer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::roles::Role;
+    use tempfile::TempDir;
 
     #[test]
     fn test_enforcer_creation() {
@@ -272,4 +645,234 @@ mod tests {
             .check_permission("unknown", &Resource::Config, &Action::Read, None)
             .is_err());
     }
+
+    #[test]
+    fn test_custom_role_definition() {
+        let mut enforcer = PolicyEnforcer::new();
+        enforcer
+            .define_role(
+                RoleDefinition::new("prompt-engineer")
+                    .with_permission(Resource::Config, Action::Read)
+                    .with_permission(Resource::Config, Action::Update)
+                    .with_namespace_pattern("*/prompts/*"),
+            )
+            .unwrap();
+        enforcer.assign_role(RoleAssignment::new(
+            "carol",
+            Role::Custom("prompt-engineer".to_string()),
+        ));
+
+        assert!(enforcer.has_permission(
+            "carol",
+            &Resource::Config,
+            &Action::Update,
+            Some("team-a/prompts/summarize")
+        ));
+        assert!(!enforcer.has_permission(
+            "carol",
+            &Resource::Config,
+            &Action::Update,
+            Some("team-a/models/gpt")
+        ));
+        assert!(!enforcer.has_permission(
+            "carol",
+            &Resource::Secret,
+            &Action::Read,
+            Some("team-a/prompts/summarize")
+        ));
+
+        // Undefined custom roles grant nothing
+        enforcer.remove_role_definition("prompt-engineer");
+        assert!(!enforcer.has_permission(
+            "carol",
+            &Resource::Config,
+            &Action::Read,
+            Some("team-a/prompts/summarize")
+        ));
+    }
+
+    #[test]
+    fn test_define_builtin_role_rejected() {
+        let mut enforcer = PolicyEnforcer::new();
+        assert!(enforcer.define_role(RoleDefinition::new("editor")).is_err());
+    }
+
+    #[test]
+    fn test_load_role_definitions_from_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+        store
+            .save(&RoleDefinition::new("release-manager").with_permission(Resource::Config, Action::Rollback))
+            .unwrap();
+
+        let mut enforcer = PolicyEnforcer::from_store(&store).unwrap();
+        assert_eq!(enforcer.role_definitions().len(), 1);
+
+        enforcer.assign_role(RoleAssignment::new(
+            "dave",
+            Role::Custom("release-manager".to_string()),
+        ));
+        assert!(enforcer.has_permission("dave", &Resource::Config, &Action::Rollback, None));
+        assert!(!enforcer.has_permission("dave", &Resource::Config, &Action::Delete, None));
+    }
+
+    #[test]
+    fn test_glob_scoped_permissions() {
+        let mut enforcer = PolicyEnforcer::new();
+        enforcer.assign_role(RoleAssignment::with_scope("bob", Role::Editor, "team-a/*"));
+
+        assert!(enforcer.has_permission(
+            "bob",
+            &Resource::Config,
+            &Action::Update,
+            Some("team-a/prompts")
+        ));
+        assert!(!enforcer.has_permission(
+            "bob",
+            &Resource::Config,
+            &Action::Update,
+            Some("team-b/prompts")
+        ));
+        assert!(!enforcer.has_permission("bob", &Resource::Config, &Action::Update, None));
+
+        assert!(enforcer.revoke_assignment(&RoleAssignment::with_scope(
+            "bob",
+            Role::Editor,
+            "team-a/*"
+        )));
+        assert_eq!(enforcer.assignment_count(), 0);
+    }
+
+    #[test]
+    fn test_temporary_role_assignment() {
+        let mut enforcer = PolicyEnforcer::new();
+        enforcer.assign_role(RoleAssignment::new("alice", Role::Viewer));
+
+        assert!(enforcer
+            .assign_role_temporary("alice", Role::Admin, Duration::from_secs(3600), " ")
+            .is_err());
+        assert!(enforcer
+            .assign_role_temporary("alice", Role::Admin, Duration::ZERO, "INC-42")
+            .is_err());
+
+        let elevation = enforcer
+            .assign_role_temporary("alice", Role::Admin, Duration::from_secs(3600), "INC-42")
+            .unwrap();
+        assert_eq!(elevation.justification, "INC-42");
+        assert_eq!(enforcer.active_elevations(), vec![&elevation]);
+
+        // Reads are granted by the regular assignment, secrets only by the elevation
+        assert!(enforcer
+            .granting_elevation("alice", &[], &Resource::Config, &Action::Read, Some("app"))
+            .is_none());
+        assert!(enforcer.has_permission("alice", &Resource::Secret, &Action::Reveal, Some("app")));
+        assert_eq!(
+            enforcer.granting_elevation("alice", &[], &Resource::Secret, &Action::Reveal, Some("app")),
+            Some(&elevation)
+        );
+
+        // Users without regular assignments can be elevated too
+        enforcer
+            .assign_role_temporary("bob", Role::Editor, Duration::from_secs(60), "INC-43")
+            .unwrap();
+        assert!(enforcer.has_permission("bob", &Resource::Config, &Action::Update, None));
+
+        // Expired elevations no longer grant anything
+        for elevation in &mut enforcer.elevations {
+            elevation.expires_at = Utc::now() - chrono::Duration::seconds(1);
+        }
+        assert!(!enforcer.has_permission("alice", &Resource::Secret, &Action::Reveal, Some("app")));
+        assert!(!enforcer.has_permission("bob", &Resource::Config, &Action::Update, None));
+        assert!(enforcer.active_elevations().is_empty());
+        assert_eq!(enforcer.remove_expired_elevations(), 2);
+    }
+
+    #[test]
+    fn test_decision_cache() {
+        let metrics = Arc::new(MetricsRegistry::new().unwrap());
+        let mut enforcer = PolicyEnforcer::new();
+        enforcer.set_metrics(Arc::clone(&metrics));
+        enforcer.assign_role(RoleAssignment::new("alice", Role::Viewer));
+
+        assert!(!enforcer.has_permission("alice", &Resource::Config, &Action::Update, Some("app")));
+        assert!(!enforcer.has_permission("alice", &Resource::Config, &Action::Update, Some("app")));
+        assert!((metrics.rbac().decision_cache_hit_rate() - 0.5).abs() < 0.01);
+
+        // Assignment changes invalidate cached decisions
+        enforcer.assign_role(RoleAssignment::new("alice", Role::Editor));
+        assert!(enforcer.has_permission("alice", &Resource::Config, &Action::Update, Some("app")));
+        assert!(enforcer.revoke_assignment(&RoleAssignment::new("alice", Role::Editor)));
+        assert!(!enforcer.has_permission("alice", &Resource::Config, &Action::Update, Some("app")));
+
+        // So do role definition changes
+        enforcer.assign_role(RoleAssignment::new(
+            "bob",
+            Role::Custom("release-manager".to_string()),
+        ));
+        assert!(!enforcer.has_permission("bob", &Resource::Config, &Action::Rollback, None));
+        enforcer
+            .define_role(
+                RoleDefinition::new("release-manager")
+                    .with_permission(Resource::Config, Action::Rollback),
+            )
+            .unwrap();
+        assert!(enforcer.has_permission("bob", &Resource::Config, &Action::Rollback, None));
+
+        let text = metrics.encode_text().unwrap();
+        assert!(text.contains("rbac_decision_cache_hits_total 1"));
+        assert!(text.contains("rbac_decision_cache_misses_total 5"));
+    }
+
+    #[test]
+    fn test_group_roles() {
+        let mut enforcer = PolicyEnforcer::new();
+        enforcer
+            .define_group(
+                Group::new("ml-platform")
+                    .with_member("alice")
+                    .with_external_group("sso-ml-eng")
+                    .with_role(Role::Editor, Some("ml/*".to_string())),
+            )
+            .unwrap();
+        let sso = vec!["sso-ml-eng".to_string()];
+
+        // Listed members and users of a mapped external group inherit the roles
+        assert!(enforcer.has_permission("alice", &Resource::Config, &Action::Update, Some("ml/models")));
+        assert!(!enforcer.has_permission("alice", &Resource::Config, &Action::Update, Some("web")));
+        assert!(!enforcer.has_permission("bob", &Resource::Config, &Action::Update, Some("ml/models")));
+        assert!(enforcer
+            .check_permission_as("bob", &sso, &Resource::Config, &Action::Update, Some("ml/models"))
+            .is_ok());
+        assert_eq!(
+            enforcer.assignments_of("bob", &sso),
+            vec![RoleAssignment::with_scope("bob", Role::Editor, "ml/*")]
+        );
+
+        // Direct assignments still apply alongside group roles
+        enforcer.assign_role(RoleAssignment::new("bob", Role::Auditor));
+        assert_eq!(enforcer.assignments_of("bob", &sso).len(), 2);
+
+        enforcer.remove_group("ml-platform");
+        assert!(enforcer
+            .check_permission_as("bob", &sso, &Resource::Config, &Action::Update, Some("ml/models"))
+            .is_err());
+        assert!(enforcer.define_group(Group::new("ml platform")).is_err());
+    }
+
+    #[test]
+    fn test_load_assignments_from_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+        store
+            .assign(&RoleAssignment::with_scope("bob", Role::Editor, "team-a/*"))
+            .unwrap();
+
+        let enforcer = PolicyEnforcer::from_store(&store).unwrap();
+        assert!(enforcer.has_permission(
+            "bob",
+            &Resource::Config,
+            &Action::Create,
+            Some("team-a/models")
+        ));
+    }
 }
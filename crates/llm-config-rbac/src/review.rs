@@ -0,0 +1,399 @@
+//! Access review reports
+//!
+//! Combines role assignments with the permissions users actually used during
+//! a period (taken from the audit log) to show who has access to what, and
+//! what they did with it. Granted permissions nobody used are candidates for
+//! removal; uses that no assignment grants show access that bypassed RBAC,
+//! e.g. while it was not enforced.
+
+use crate::{
+    permissions::Permission,
+    policy::PolicyEnforcer,
+    roles::{Role, RoleAssignment},
+};
+use chrono::{DateTime, Utc};
+use llm_config_storage::csv::render_row;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+
+/// CSV column names, in order
+const CSV_HEADER: [&str; 8] = [
+    "user",
+    "roles",
+    "permission",
+    "granted",
+    "uses",
+    "ungranted_uses",
+    "last_used",
+    "namespaces",
+];
+
+/// One use of a permission by a user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessUse {
+    pub user: String,
+    pub permission: Permission,
+    /// Namespace the permission was used in
+    pub namespace: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// How a user used one permission during the period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PermissionUsage {
+    pub permission: Permission,
+    /// Number of uses
+    pub uses: usize,
+    /// Uses none of the user's assignments grants in their namespace
+    pub ungranted_uses: usize,
+    pub last_used: DateTime<Utc>,
+    /// Namespaces the permission was used in
+    pub namespaces: BTreeSet<String>,
+}
+
+/// Access of one user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserAccess {
+    pub user: String,
+    pub assignments: Vec<RoleAssignment>,
+    /// Permissions granted by the assignments, in at least one namespace
+    pub granted: Vec<Permission>,
+    /// Permissions used during the period
+    pub used: Vec<PermissionUsage>,
+}
+
+impl UserAccess {
+    /// Granted permissions that were not used during the period
+    pub fn unused(&self) -> Vec<&Permission> {
+        self.granted
+            .iter()
+            .filter(|permission| !self.used.iter().any(|usage| &usage.permission == *permission))
+            .collect()
+    }
+
+    /// Uses of permissions the user's assignments do not grant
+    pub fn ungranted(&self) -> Vec<&PermissionUsage> {
+        self.used
+            .iter()
+            .filter(|usage| usage.ungranted_uses > 0)
+            .collect()
+    }
+
+    /// Whether the user has role assignments but used none of them
+    pub fn is_inactive(&self) -> bool {
+        !self.assignments.is_empty() && self.used.is_empty()
+    }
+
+    /// The user's roles, e.g. `editor (team-a/*), viewer`
+    fn roles(&self) -> String {
+        self.assignments
+            .iter()
+            .map(|assignment| match &assignment.scope {
+                Some(scope) => format!("{} ({})", assignment.role, scope),
+                None => assignment.role.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Who has access to what over a period, and what they used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccessReview {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Users with role assignments or uses, ordered by name
+    pub users: Vec<UserAccess>,
+}
+
+impl AccessReview {
+    /// Review the assignments of an enforcer against the uses within `start..=end`
+    pub fn new(
+        enforcer: &PolicyEnforcer,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        uses: impl IntoIterator<Item = AccessUse>,
+    ) -> Self {
+        let mut used: BTreeMap<String, BTreeMap<String, PermissionUsage>> = BTreeMap::new();
+        for access in uses {
+            if access.at < start || access.at > end {
+                continue;
+            }
+            let granted = enforcer.has_permission(
+                &access.user,
+                &access.permission.resource,
+                &access.permission.action,
+                access.namespace.as_deref(),
+            );
+            let usage = used
+                .entry(access.user)
+                .or_default()
+                .entry(access.permission.to_string())
+                .or_insert_with(|| PermissionUsage {
+                    permission: access.permission,
+                    uses: 0,
+                    ungranted_uses: 0,
+                    last_used: access.at,
+                    namespaces: BTreeSet::new(),
+                });
+            usage.uses += 1;
+            if !granted {
+                usage.ungranted_uses += 1;
+            }
+            usage.last_used = usage.last_used.max(access.at);
+            usage.namespaces.extend(access.namespace);
+        }
+
+        let mut users: BTreeSet<String> = used.keys().cloned().collect();
+        users.extend(enforcer.list_users().into_iter().map(str::to_string));
+        users.extend(
+            enforcer
+                .groups()
+                .into_iter()
+                .flat_map(|group| group.members.iter().cloned()),
+        );
+
+        let users = users
+            .into_iter()
+            .map(|user| {
+                let assignments = enforcer.assignments_of(&user, &[]);
+                let mut granted: Vec<Permission> = assignments
+                    .iter()
+                    .flat_map(|assignment| granted_permissions(enforcer, &assignment.role))
+                    .collect::<HashSet<_>>()
+                    .into_iter()
+                    .collect();
+                granted.sort_by_key(ToString::to_string);
+
+                UserAccess {
+                    assignments,
+                    granted,
+                    used: used.remove(&user).unwrap_or_default().into_values().collect(),
+                    user,
+                }
+            })
+            .collect();
+
+        Self { start, end, users }
+    }
+
+    /// Render the review as a Markdown report
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Access review\n");
+        let _ = writeln!(
+            out,
+            "Period: {} to {}\n",
+            self.start.to_rfc3339(),
+            self.end.to_rfc3339()
+        );
+
+        out.push_str("| User | Roles | Granted | Used | Unused | Not granted |\n");
+        out.push_str("|---|---|---|---|---|---|\n");
+        for user in &self.users {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} |",
+                user.user,
+                if user.assignments.is_empty() { "none".to_string() } else { user.roles() },
+                user.granted.len(),
+                user.used.len(),
+                user.unused().len(),
+                user.ungranted().len()
+            );
+        }
+
+        for user in &self.users {
+            let _ = writeln!(out, "\n## {}\n", user.user);
+            if user.assignments.is_empty() {
+                out.push_str("No role assignments.\n");
+            } else {
+                let _ = writeln!(out, "Roles: {}", user.roles());
+            }
+            if user.is_inactive() {
+                out.push_str("\nNo activity during the period.\n");
+            }
+
+            if !user.used.is_empty() {
+                out.push_str("\n| Permission | Uses | Last used | Namespaces |\n");
+                out.push_str("|---|---|---|---|\n");
+                for usage in &user.used {
+                    let namespaces: Vec<&str> = usage.namespaces.iter().map(String::as_str).collect();
+                    let _ = writeln!(
+                        out,
+                        "| `{}` | {} | {} | {} |",
+                        usage.permission,
+                        usage.uses,
+                        usage.last_used.to_rfc3339(),
+                        namespaces.join(", ")
+                    );
+                }
+            }
+
+            let unused = user.unused();
+            if !unused.is_empty() && !user.is_inactive() {
+                let unused: Vec<String> = unused.iter().map(|p| format!("`{}`", p)).collect();
+                let _ = writeln!(out, "\nUnused: {}", unused.join(", "));
+            }
+
+            let ungranted = user.ungranted();
+            if !ungranted.is_empty() {
+                let ungranted: Vec<String> = ungranted
+                    .iter()
+                    .map(|usage| format!("`{}` ({} uses)", usage.permission, usage.ungranted_uses))
+                    .collect();
+                let _ = writeln!(out, "\nNot granted: {}", ungranted.join(", "));
+            }
+        }
+
+        out
+    }
+
+    /// Render the review as CSV, one row per user and permission
+    ///
+    /// Users without any granted or used permission get a single row with
+    /// an empty permission.
+    pub fn to_csv(&self) -> String {
+        let mut out = render_row(CSV_HEADER);
+        for user in &self.users {
+            let roles = user.roles();
+            let mut permissions: BTreeMap<String, (bool, Option<&PermissionUsage>)> =
+                BTreeMap::new();
+            for permission in &user.granted {
+                permissions.insert(permission.to_string(), (true, None));
+            }
+            for usage in &user.used {
+                permissions
+                    .entry(usage.permission.to_string())
+                    .or_insert((false, None))
+                    .1 = Some(usage);
+            }
+
+            if permissions.is_empty() {
+                out.push_str(&render_row([
+                    user.user.as_str(),
+                    &roles,
+                    "",
+                    "no",
+                    "0",
+                    "0",
+                    "",
+                    "",
+                ]));
+            }
+            for (permission, (granted, usage)) in permissions {
+                let namespaces = usage
+                    .map(|usage| usage.namespaces.iter().map(String::as_str).collect::<Vec<_>>().join(";"))
+                    .unwrap_or_default();
+                out.push_str(&render_row([
+                    user.user.as_str(),
+                    &roles,
+                    &permission,
+                    if granted { "yes" } else { "no" },
+                    &usage.map_or(0, |usage| usage.uses).to_string(),
+                    &usage.map_or(0, |usage| usage.ungranted_uses).to_string(),
+                    &usage.map(|usage| usage.last_used.to_rfc3339()).unwrap_or_default(),
+                    &namespaces,
+                ]));
+            }
+        }
+        out
+    }
+}
+
+/// Permissions a role grants, resolving custom roles through their definition
+fn granted_permissions(enforcer: &PolicyEnforcer, role: &Role) -> HashSet<Permission> {
+    match role {
+        Role::Custom(name) => enforcer
+            .get_role_definition(name)
+            .map(|definition| definition.permissions.clone())
+            .unwrap_or_default(),
+        role => role.permissions(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::{Action, Resource};
+    use crate::roles::RoleDefinition;
+    use chrono::Duration;
+
+    fn access(user: &str, permission: &str, namespace: &str, at: DateTime<Utc>) -> AccessUse {
+        AccessUse {
+            user: user.to_string(),
+            permission: permission.parse().unwrap(),
+            namespace: Some(namespace.to_string()),
+            at,
+        }
+    }
+
+    #[test]
+    fn test_access_review() {
+        let end = Utc::now();
+        let start = end - Duration::days(90);
+
+        let mut enforcer = PolicyEnforcer::new();
+        enforcer
+            .define_role(
+                RoleDefinition::new("prompt-engineer")
+                    .with_permission(Resource::Config, Action::Read)
+                    .with_permission(Resource::Config, Action::Update),
+            )
+            .unwrap();
+        enforcer.assign_role(RoleAssignment::with_scope(
+            "alice",
+            Role::Custom("prompt-engineer".to_string()),
+            "team-a/*",
+        ));
+        enforcer.assign_role(RoleAssignment::new("bob", Role::Viewer));
+
+        let uses = vec![
+            access("alice", "config:read", "team-a/prompts", end - Duration::days(3)),
+            access("alice", "config:read", "team-a/models", end - Duration::days(1)),
+            access("alice", "config:update", "team-b/prompts", end - Duration::days(2)),
+            // Outside the period
+            access("bob", "config:read", "team-a/prompts", start - Duration::days(1)),
+            access("carol", "secret:reveal", "payments", end - Duration::hours(1)),
+        ];
+        let review = AccessReview::new(&enforcer, start, end, uses);
+
+        let users: Vec<&str> = review.users.iter().map(|user| user.user.as_str()).collect();
+        assert_eq!(users, vec!["alice", "bob", "carol"]);
+
+        let alice = &review.users[0];
+        assert_eq!(alice.used.len(), 2);
+        assert_eq!(alice.used[0].uses, 2);
+        assert_eq!(alice.used[0].last_used, end - Duration::days(1));
+        assert_eq!(alice.used[0].namespaces.len(), 2);
+        assert!(alice.unused().is_empty());
+        // Updated outside of her scope
+        assert_eq!(alice.ungranted().len(), 1);
+        assert_eq!(alice.ungranted()[0].permission.to_string(), "config:update");
+
+        let bob = &review.users[1];
+        assert!(bob.is_inactive());
+        assert_eq!(bob.unused().len(), bob.granted.len());
+
+        let carol = &review.users[2];
+        assert!(carol.assignments.is_empty() && carol.granted.is_empty());
+        assert_eq!(carol.ungranted().len(), 1);
+
+        let markdown = review.to_markdown();
+        assert!(markdown.contains("| alice | prompt-engineer (team-a/*) | 2 | 2 | 0 | 1 |"));
+        assert!(markdown.contains("No activity during the period."));
+        assert!(markdown.contains("Not granted: `secret:reveal` (1 uses)"));
+
+        let csv = review.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("user,roles,permission,granted,uses,ungranted_uses,last_used,namespaces")
+        );
+        assert!(csv.contains("\nalice,prompt-engineer (team-a/*),config:read,yes,2,0,"));
+        assert!(csv.contains("team-a/models;team-a/prompts\n"));
+        assert!(csv.contains("\nbob,viewer,history:list,yes,0,0,,\n"));
+        assert!(csv.contains("\ncarol,,secret:reveal,no,1,1,"));
+    }
+}
@@ -1,8 +1,12 @@
 //! Role definitions and assignments
 
 use crate::permissions::{Action, Permission, Resource};
+use chrono::{DateTime, Utc};
+use llm_config_storage::namespace_matches;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
 
 /// Predefined roles in the system
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -49,6 +53,8 @@ impl Role {
                         perms.insert(Permission::new(resource.clone(), action));
                     }
                 }
+                // Only admins may decrypt secrets by default
+                perms.insert(Permission::new(Resource::Secret, Action::Reveal));
                 perms
             }
             Role::Editor => {
@@ -97,7 +103,7 @@ impl Role {
                 perms
             }
             Role::Custom(_) => {
-                // Custom roles need to be configured separately
+                // Custom roles are resolved through their RoleDefinition
                 HashSet::new()
             }
         }
@@ -115,12 +121,40 @@ impl Role {
     }
 }
 
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::Editor => write!(f, "editor"),
+            Role::Viewer => write!(f, "viewer"),
+            Role::Auditor => write!(f, "auditor"),
+            Role::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = std::convert::Infallible;
+
+    /// Parse a predefined role name; any other name refers to a custom role
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "admin" => Role::Admin,
+            "editor" => Role::Editor,
+            "viewer" => Role::Viewer,
+            "auditor" => Role::Auditor,
+            _ => Role::Custom(s.to_string()),
+        })
+    }
+}
+
 /// Assignment of a role to a user
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RoleAssignment {
     pub user: String,
     pub role: Role,
-    /// Optional scope restriction (e.g., specific namespace)
+    /// Optional namespace scope; a glob pattern such as `team-a/*`
+    /// (see [`namespace_matches`])
     pub scope: Option<String>,
 }
 
@@ -146,13 +180,103 @@ impl RoleAssignment {
     /// Check if this assignment applies to the given scope
     pub fn applies_to_scope(&self, scope: Option<&str>) -> bool {
         match (&self.scope, scope) {
-            (None, _) => true,              // Global scope applies to everything
-            (Some(s1), Some(s2)) => s1 == s2, // Exact scope match
-            (Some(_), None) => false,       // Scoped assignment doesn't apply globally
+            (None, _) => true, // Global scope applies to everything
+            (Some(pattern), Some(namespace)) => namespace_matches(pattern, namespace),
+            (Some(_), None) => false, // Scoped assignment doesn't apply globally
         }
     }
 }
 
+/// Temporary (break-glass) role assignment that expires on its own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Elevation {
+    #[serde(flatten)]
+    pub assignment: RoleAssignment,
+    /// Why the role was needed (e.g. an incident reference)
+    pub justification: String,
+    pub granted_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Elevation {
+    /// Check if the elevation has not expired yet
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        now < self.expires_at
+    }
+}
+
+/// Definition of a custom role with an explicit permission set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleDefinition {
+    /// Role name, referenced by `Role::Custom(name)`
+    pub name: String,
+    /// Human-readable description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Permissions granted by the role
+    pub permissions: HashSet<Permission>,
+    /// Namespace patterns the role applies to (empty means all namespaces)
+    #[serde(default)]
+    pub namespace_patterns: Vec<String>,
+}
+
+impl RoleDefinition {
+    /// Create an empty role definition
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            permissions: HashSet::new(),
+            namespace_patterns: Vec::new(),
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Grant a permission
+    pub fn with_permission(mut self, resource: Resource, action: Action) -> Self {
+        self.permissions.insert(Permission::new(resource, action));
+        self
+    }
+
+    /// Restrict the role to namespaces matching a glob pattern (e.g. `*/prompts/*`)
+    pub fn with_namespace_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.namespace_patterns.push(pattern.into());
+        self
+    }
+
+    /// The role this definition describes
+    pub fn role(&self) -> Role {
+        Role::Custom(self.name.clone())
+    }
+
+    /// Check if the role can perform the action on the resource
+    pub fn can(&self, resource: &Resource, action: &Action) -> bool {
+        self.permissions
+            .iter()
+            .any(|permission| permission.allows(resource, action))
+    }
+
+    /// Check if the role applies to the given namespace
+    ///
+    /// Roles restricted to namespace patterns never apply globally.
+    pub fn applies_to_namespace(&self, namespace: Option<&str>) -> bool {
+        if self.namespace_patterns.is_empty() {
+            return true;
+        }
+
+        namespace.is_some_and(|ns| {
+            self.namespace_patterns
+                .iter()
+                .any(|pattern| namespace_matches(pattern, ns))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,6 +288,7 @@ mod tests {
         assert!(role.can(&Resource::Secret, &Action::Create));
         assert!(role.can(&Resource::System, &Action::Update));
         assert!(role.can(&Resource::Users, &Action::Delete));
+        assert!(role.can(&Resource::Secret, &Action::Reveal));
     }
 
     #[test]
@@ -174,6 +299,9 @@ mod tests {
         assert!(role.can(&Resource::Secret, &Action::Create));
         assert!(role.can(&Resource::AuditLog, &Action::Read));
 
+        // Decrypting secrets requires the dedicated reveal permission
+        assert!(!role.can(&Resource::Secret, &Action::Reveal));
+
         // Cannot modify system settings
         assert!(!role.can(&Resource::System, &Action::Update));
         assert!(!role.can(&Resource::Users, &Action::Create));
@@ -222,6 +350,26 @@ mod tests {
         assert!(!assignment.applies_to_scope(None));
     }
 
+    #[test]
+    fn test_glob_scoped_assignment() {
+        let assignment = RoleAssignment::with_scope("bob", Role::Editor, "team-a/*");
+        assert!(assignment.applies_to_scope(Some("team-a/prompts")));
+        assert!(assignment.applies_to_scope(Some("team-a/prompts/summarize")));
+        assert!(!assignment.applies_to_scope(Some("team-a")));
+        assert!(!assignment.applies_to_scope(Some("team-b/prompts")));
+        assert!(!assignment.applies_to_scope(None));
+    }
+
+    #[test]
+    fn test_role_from_str() {
+        assert_eq!("Editor".parse::<Role>().unwrap(), Role::Editor);
+        assert_eq!(
+            "prompt-engineer".parse::<Role>().unwrap(),
+            Role::Custom("prompt-engineer".to_string())
+        );
+        assert_eq!(Role::Custom("x".to_string()).to_string(), "x");
+    }
+
     #[test]
     fn test_global_assignment() {
         let assignment = RoleAssignment::new("admin", Role::Admin);
@@ -229,4 +377,32 @@ mod tests {
         assert!(assignment.applies_to_scope(Some("namespace2")));
         assert!(assignment.applies_to_scope(None));
     }
+
+    #[test]
+    fn test_role_definition() {
+        let definition = RoleDefinition::new("prompt-engineer")
+            .with_permission(Resource::Config, Action::Read)
+            .with_permission(Resource::Config, Action::Update)
+            .with_namespace_pattern("*/prompts/*");
+
+        assert_eq!(definition.role(), Role::Custom("prompt-engineer".to_string()));
+        assert!(definition.can(&Resource::Config, &Action::Update));
+        assert!(!definition.can(&Resource::Secret, &Action::Read));
+
+        assert!(definition.applies_to_namespace(Some("team-a/prompts/summarize")));
+        assert!(!definition.applies_to_namespace(Some("team-a/models/gpt")));
+        assert!(!definition.applies_to_namespace(None));
+    }
+
+    #[test]
+    fn test_role_definition_serialization() {
+        let definition = RoleDefinition::new("release-manager")
+            .with_description("Can roll back configs")
+            .with_permission(Resource::Config, Action::Rollback);
+
+        let json = serde_json::to_string(&definition).unwrap();
+        let deserialized: RoleDefinition = serde_json::from_str(&json).unwrap();
+        assert_eq!(definition, deserialized);
+        assert!(deserialized.applies_to_namespace(None));
+    }
 }
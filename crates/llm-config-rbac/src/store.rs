@@ -0,0 +1,246 @@
+//! Persistent storage for custom role definitions, role assignments and groups
+
+use crate::{
+    groups::Group,
+    roles::{RoleAssignment, RoleDefinition},
+    RbacError, Result,
+};
+use llm_config_storage::file::FileStorage;
+use std::path::Path;
+
+/// Record kind under which role definitions are stored
+const ROLE_RECORD_KIND: &str = "roles";
+
+/// Record kind under which role assignments are stored (one record per user)
+const ASSIGNMENT_RECORD_KIND: &str = "assignments";
+
+/// Record kind under which groups are stored
+const GROUP_RECORD_KIND: &str = "groups";
+
+/// Names reserved for the predefined roles
+const BUILTIN_ROLE_NAMES: [&str; 4] = ["admin", "editor", "viewer", "auditor"];
+
+/// Store for custom role definitions and assignments backed by file storage
+#[derive(Clone)]
+pub struct RoleStore {
+    storage: FileStorage,
+}
+
+impl RoleStore {
+    /// Create a role store on top of an existing storage backend
+    pub fn new(storage: FileStorage) -> Self {
+        Self { storage }
+    }
+
+    /// Open a role store in the given storage directory
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(FileStorage::new(path)?))
+    }
+
+    /// Save (create or replace) a role definition
+    pub fn save(&self, definition: &RoleDefinition) -> Result<()> {
+        validate_role_name(&definition.name)?;
+        self.storage
+            .put_record(ROLE_RECORD_KIND, &definition.name, definition)?;
+        Ok(())
+    }
+
+    /// Get a role definition by name
+    pub fn get(&self, name: &str) -> Result<Option<RoleDefinition>> {
+        validate_role_name(name)?;
+        Ok(self.storage.get_record(ROLE_RECORD_KIND, name)?)
+    }
+
+    /// Load all role definitions
+    pub fn load_all(&self) -> Result<Vec<RoleDefinition>> {
+        Ok(self.storage.list_records(ROLE_RECORD_KIND)?)
+    }
+
+    /// Delete a role definition
+    pub fn delete(&self, name: &str) -> Result<bool> {
+        validate_role_name(name)?;
+        Ok(self.storage.delete_record(ROLE_RECORD_KIND, name)?)
+    }
+
+    /// Get all role assignments of a user
+    pub fn get_assignments(&self, user: &str) -> Result<Vec<RoleAssignment>> {
+        Ok(self
+            .storage
+            .get_record(ASSIGNMENT_RECORD_KIND, user)?
+            .unwrap_or_default())
+    }
+
+    /// Load the role assignments of all users
+    pub fn load_all_assignments(&self) -> Result<Vec<RoleAssignment>> {
+        let assignments: Vec<Vec<RoleAssignment>> =
+            self.storage.list_records(ASSIGNMENT_RECORD_KIND)?;
+        Ok(assignments.into_iter().flatten().collect())
+    }
+
+    /// Persist a role assignment (no-op if the user already has it)
+    pub fn assign(&self, assignment: &RoleAssignment) -> Result<()> {
+        let mut assignments = self.get_assignments(&assignment.user)?;
+        if !assignments.contains(assignment) {
+            assignments.push(assignment.clone());
+            self.storage
+                .put_record(ASSIGNMENT_RECORD_KIND, &assignment.user, &assignments)?;
+        }
+        Ok(())
+    }
+
+    /// Remove a persisted role assignment
+    pub fn revoke(&self, assignment: &RoleAssignment) -> Result<bool> {
+        let mut assignments = self.get_assignments(&assignment.user)?;
+        let before = assignments.len();
+        assignments.retain(|a| a != assignment);
+
+        if assignments.len() == before {
+            return Ok(false);
+        }
+
+        if assignments.is_empty() {
+            self.storage
+                .delete_record(ASSIGNMENT_RECORD_KIND, &assignment.user)?;
+        } else {
+            self.storage
+                .put_record(ASSIGNMENT_RECORD_KIND, &assignment.user, &assignments)?;
+        }
+        Ok(true)
+    }
+
+    /// Save (create or replace) a group
+    pub fn save_group(&self, group: &Group) -> Result<()> {
+        validate_group_name(&group.name)?;
+        self.storage.put_record(GROUP_RECORD_KIND, &group.name, group)?;
+        Ok(())
+    }
+
+    /// Get a group by name
+    pub fn get_group(&self, name: &str) -> Result<Option<Group>> {
+        validate_group_name(name)?;
+        Ok(self.storage.get_record(GROUP_RECORD_KIND, name)?)
+    }
+
+    /// Load all groups
+    pub fn load_groups(&self) -> Result<Vec<Group>> {
+        Ok(self.storage.list_records(GROUP_RECORD_KIND)?)
+    }
+
+    /// Delete a group
+    pub fn delete_group(&self, name: &str) -> Result<bool> {
+        validate_group_name(name)?;
+        Ok(self.storage.delete_record(GROUP_RECORD_KIND, name)?)
+    }
+}
+
+/// Whether a name is usable as a role or group name (and record key)
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Ensure a group name is usable
+pub(crate) fn validate_group_name(name: &str) -> Result<()> {
+    if !is_valid_name(name) {
+        return Err(RbacError::InvalidRole(format!(
+            "Invalid group name: {:?}",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Ensure a custom role name is usable and does not shadow a predefined role
+pub(crate) fn validate_role_name(name: &str) -> Result<()> {
+    if !is_valid_name(name) {
+        return Err(RbacError::InvalidRole(format!(
+            "Invalid role name: {:?}",
+            name
+        )));
+    }
+
+    if BUILTIN_ROLE_NAMES.contains(&name.to_ascii_lowercase().as_str()) {
+        return Err(RbacError::InvalidRole(format!(
+            "{} is a predefined role",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::{Action, Resource};
+    use crate::roles::Role;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+
+        let definition = RoleDefinition::new("prompt-engineer")
+            .with_permission(Resource::Config, Action::Update)
+            .with_namespace_pattern("*/prompts/*");
+        store.save(&definition).unwrap();
+
+        // Reopen to verify persistence
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.get("prompt-engineer").unwrap(), Some(definition));
+        assert_eq!(store.load_all().unwrap().len(), 1);
+
+        assert!(store.delete("prompt-engineer").unwrap());
+        assert!(store.load_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_role_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+
+        assert!(store.save(&RoleDefinition::new("Admin")).is_err());
+        assert!(store.save(&RoleDefinition::new("../etc")).is_err());
+        assert!(store.save(&RoleDefinition::new("")).is_err());
+    }
+
+    #[test]
+    fn test_assignments() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+
+        let scoped = RoleAssignment::with_scope("bob", Role::Editor, "team-a/*");
+        store.assign(&scoped).unwrap();
+        store.assign(&scoped).unwrap();
+        store.assign(&RoleAssignment::new("alice", Role::Viewer)).unwrap();
+
+        assert_eq!(store.get_assignments("bob").unwrap(), vec![scoped.clone()]);
+        assert_eq!(store.load_all_assignments().unwrap().len(), 2);
+
+        assert!(store.revoke(&scoped).unwrap());
+        assert!(!store.revoke(&scoped).unwrap());
+        assert!(store.get_assignments("bob").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_groups() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+
+        let group = Group::new("ml-platform")
+            .with_external_group("sso-ml-eng")
+            .with_role(Role::Editor, Some("ml/*".to_string()));
+        store.save_group(&group).unwrap();
+        assert!(store.save_group(&Group::new("../etc")).is_err());
+
+        let store = RoleStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.get_group("ml-platform").unwrap(), Some(group));
+        assert_eq!(store.load_groups().unwrap().len(), 1);
+
+        assert!(store.delete_group("ml-platform").unwrap());
+        assert!(store.load_groups().unwrap().is_empty());
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::errors::{SecurityError, SecurityResult};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Audit configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +15,20 @@ pub struct AuditConfig {
     pub expected_event_rate: Option<f64>,
     /// Maximum gap between events (seconds)
     pub max_event_gap_seconds: Option<u64>,
+    /// How event sequences are verified
+    #[serde(default)]
+    pub sequence_mode: SequenceMode,
+}
+
+/// Sequence verification mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceMode {
+    /// Chronological order, gap, and sequence number heuristics
+    #[default]
+    Heuristic,
+    /// Verify the hash chain linking each event to its predecessor
+    HashChain,
 }
 
 impl Default for AuditConfig {
@@ -23,6 +38,7 @@ impl Default for AuditConfig {
             enable_completeness_check: true,
             expected_event_rate: None,
             max_event_gap_seconds: Some(300), // 5 minutes
+            sequence_mode: SequenceMode::Heuristic,
         }
     }
 }
@@ -129,6 +145,10 @@ impl AuditValidator {
             return Ok(());
         }
 
+        if self.config.sequence_mode == SequenceMode::HashChain {
+            return self.validate_hash_chain(events);
+        }
+
         // Check chronological order
         for i in 1..events.len() {
             if let (Some(prev), Some(curr)) = (events[i - 1].timestamp, events[i].timestamp) {
@@ -171,6 +191,34 @@ impl AuditValidator {
         Ok(())
     }
 
+    /// Validate the hash chain of a sequence, reporting the first broken link
+    pub fn validate_hash_chain(&self, events: &[AuditEvent]) -> SecurityResult<()> {
+        for (i, event) in events.iter().enumerate() {
+            let Some(hash) = &event.hash else {
+                return Err(SecurityError::AuditError(format!(
+                    "Broken hash chain at event {}: event is not hashed",
+                    i
+                )));
+            };
+
+            if i > 0 && event.prev_hash != events[i - 1].hash {
+                return Err(SecurityError::AuditError(format!(
+                    "Broken hash chain at event {}: previous hash mismatch",
+                    i
+                )));
+            }
+
+            if event.compute_hash() != *hash {
+                return Err(SecurityError::AuditError(format!(
+                    "Broken hash chain at event {}: content does not match hash",
+                    i
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Calculate audit statistics
     pub fn calculate_stats(&self, events: &[AuditEvent]) -> AuditStats {
         if events.is_empty() {
@@ -226,6 +274,35 @@ pub struct AuditEvent {
     pub result: String,
     pub severity: EventSeverity,
     pub metadata: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    pub prev_hash: Option<String>,
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+impl AuditEvent {
+    /// Compute the SHA-256 hash of the event (excluding `hash`), hex encoded
+    pub fn compute_hash(&self) -> String {
+        let mut unhashed = self.clone();
+        unhashed.hash = None;
+
+        // serde_json::Value objects keep their keys sorted
+        let canonical = serde_json::to_value(&unhashed)
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+
+        Sha256::digest(canonical.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Link the event to its predecessor and seal it with its own hash
+    pub fn seal(mut self, prev_hash: Option<String>) -> Self {
+        self.prev_hash = prev_hash;
+        self.hash = Some(self.compute_hash());
+        self
+    }
 }
 
 /// Event severity
@@ -264,6 +341,8 @@ mod tests {
             result: "success".to_string(),
             severity: EventSeverity::Medium,
             metadata: HashMap::new(),
+            prev_hash: None,
+            hash: None,
         }
     }
 
@@ -376,4 +455,37 @@ mod tests {
 
         assert!(validator.validate_sequence(&events).is_err());
     }
+
+    #[test]
+    fn test_hash_chain_mode() {
+        let validator = AuditValidator::new(AuditConfig {
+            sequence_mode: SequenceMode::HashChain,
+            ..Default::default()
+        });
+
+        // Gaps are fine as long as the chain is intact
+        let base = chrono::Utc::now();
+        let mut events: Vec<AuditEvent> = vec![];
+        for i in 0..5 {
+            let mut event = create_test_event("user123", "read");
+            event.timestamp = Some(base + chrono::Duration::hours(i));
+            let prev_hash = events.last().and_then(|e| e.hash.clone());
+            events.push(event.seal(prev_hash));
+        }
+        assert!(validator.validate_sequence(&events).is_ok());
+
+        let mut tampered = events.clone();
+        tampered[2].user_id = "attacker".to_string();
+        let err = validator.validate_sequence(&tampered).unwrap_err();
+        assert!(err.to_string().contains("event 2"));
+
+        let mut removed = events.clone();
+        removed.remove(1);
+        let err = validator.validate_sequence(&removed).unwrap_err();
+        assert!(err.to_string().contains("event 1"));
+
+        // Unhashed events fail in hash chain mode
+        let unhashed = vec![create_test_event("user123", "read")];
+        assert!(validator.validate_sequence(&unhashed).is_err());
+    }
 }
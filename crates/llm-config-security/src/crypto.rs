@@ -9,7 +9,7 @@ use zeroize::Zeroizing;
 const MIN_KEY_SIZE: usize = 32; // 256 bits
 
 /// Maximum key age in days
-const MAX_KEY_AGE_DAYS: i64 = 90;
+pub const MAX_KEY_AGE_DAYS: i64 = 90;
 
 /// Crypto validator
 pub struct CryptoValidator {
@@ -223,6 +223,11 @@ impl KeyValidator {
         Self::new(MAX_KEY_AGE_DAYS)
     }
 
+    /// Age after which keys should be rotated
+    pub fn max_age_days(&self) -> i64 {
+        self.max_age_days
+    }
+
     /// Check if a key should be rotated based on age
     pub fn should_rotate(
         &self,
@@ -0,0 +1,346 @@
+//! Workload identity (SPIFFE) support
+//!
+//! Service-to-service callers authenticate with X.509 SVIDs over mTLS. The
+//! TLS-terminating proxy forwards the verified SPIFFE ID, which is mapped here
+//! to an RBAC principal, the namespaces that workload may access and, in
+//! multi-tenant deployments, the tenant it belongs to.
+
+use crate::errors::{SecurityError, SecurityResult};
+use llm_config_storage::namespace_matches;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// URI scheme used by SPIFFE IDs
+const SPIFFE_SCHEME: &str = "spiffe://";
+
+/// A parsed SPIFFE ID (`spiffe://<trust-domain>/<path>`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpiffeId {
+    trust_domain: String,
+    path: String,
+}
+
+impl SpiffeId {
+    /// Parse a SPIFFE ID from its URI form
+    pub fn parse(uri: &str) -> SecurityResult<Self> {
+        let rest = uri.strip_prefix(SPIFFE_SCHEME).ok_or_else(|| {
+            SecurityError::AuthenticationError(format!("Not a SPIFFE ID: {}", uri))
+        })?;
+
+        let (trust_domain, path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, ""),
+        };
+
+        if trust_domain.is_empty()
+            || !trust_domain
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-._".contains(c))
+        {
+            return Err(SecurityError::AuthenticationError(format!(
+                "Invalid SPIFFE trust domain: {}",
+                trust_domain
+            )));
+        }
+
+        if path.contains("//")
+            || path.ends_with('/')
+            || path.split('/').any(|segment| segment == "." || segment == "..")
+            || path.contains(['?', '#'])
+        {
+            return Err(SecurityError::AuthenticationError(format!(
+                "Invalid SPIFFE ID path: {}",
+                path
+            )));
+        }
+
+        Ok(Self {
+            trust_domain: trust_domain.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    /// Get the trust domain
+    pub fn trust_domain(&self) -> &str {
+        &self.trust_domain
+    }
+
+    /// Get the workload path (including the leading `/`)
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Extract the SPIFFE ID from an `X-Forwarded-Client-Cert` header
+    ///
+    /// The header is set by the mTLS-terminating proxy (e.g. Envoy) and may
+    /// contain several comma-separated elements; the first element carrying a
+    /// `URI=spiffe://...` field (the direct client) wins.
+    pub fn from_xfcc(header: &str) -> Option<Self> {
+        header.split(',').find_map(|element| {
+            element.split(';').find_map(|field| {
+                let (name, value) = field.split_once('=')?;
+                if name.trim().eq_ignore_ascii_case("uri") {
+                    Self::parse(value.trim().trim_matches('"')).ok()
+                } else {
+                    None
+                }
+            })
+        })
+    }
+}
+
+impl fmt::Display for SpiffeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", SPIFFE_SCHEME, self.trust_domain, self.path)
+    }
+}
+
+impl FromStr for SpiffeId {
+    type Err = SecurityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Mapping from a SPIFFE ID (or ID prefix) to an RBAC principal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadMapping {
+    /// SPIFFE ID to match; a trailing `*` matches any ID with that prefix
+    pub spiffe_id: String,
+    /// RBAC principal the workload acts as
+    pub principal: String,
+    /// Namespace patterns the workload may access (empty means all)
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// Tenant the workload belongs to (unset: it may act for any tenant)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl WorkloadMapping {
+    /// Create a new mapping
+    pub fn new(spiffe_id: impl Into<String>, principal: impl Into<String>) -> Self {
+        Self {
+            spiffe_id: spiffe_id.into(),
+            principal: principal.into(),
+            namespaces: Vec::new(),
+            tenant: None,
+        }
+    }
+
+    /// Restrict the mapping to a namespace pattern (see [`namespace_matches`])
+    pub fn with_namespace(mut self, pattern: impl Into<String>) -> Self {
+        self.namespaces.push(pattern.into());
+        self
+    }
+
+    /// Restrict the mapping to a tenant
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    fn matches(&self, id: &SpiffeId) -> bool {
+        let id = id.to_string();
+        match self.spiffe_id.strip_suffix('*') {
+            Some(prefix) => id.starts_with(prefix),
+            None => id == self.spiffe_id,
+        }
+    }
+}
+
+/// A workload authenticated by its SPIFFE ID and resolved to a principal
+#[derive(Debug, Clone)]
+pub struct WorkloadIdentity {
+    /// Verified SPIFFE ID of the caller
+    pub spiffe_id: SpiffeId,
+    /// RBAC principal the workload acts as
+    pub principal: String,
+    /// Namespace patterns the workload may access (empty means all)
+    pub namespaces: Vec<String>,
+    /// Tenant the workload belongs to
+    pub tenant: Option<String>,
+}
+
+impl WorkloadIdentity {
+    /// Check whether the workload may access the given namespace
+    pub fn allows_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.is_empty()
+            || self
+                .namespaces
+                .iter()
+                .any(|pattern| namespace_matches(pattern, namespace))
+    }
+
+    /// Fail with an authorization error if the namespace is out of scope
+    pub fn check_namespace(&self, namespace: &str) -> SecurityResult<()> {
+        if self.allows_namespace(namespace) {
+            Ok(())
+        } else {
+            Err(SecurityError::AuthorizationError(format!(
+                "Workload {} is not authorized for namespace {}",
+                self.spiffe_id, namespace
+            )))
+        }
+    }
+}
+
+/// Resolves SPIFFE IDs from a single trust domain to RBAC principals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadIdentityMapper {
+    /// Trust domain whose SVIDs are accepted
+    pub trust_domain: String,
+    /// Mappings evaluated in order; the first match wins
+    #[serde(default)]
+    pub mappings: Vec<WorkloadMapping>,
+}
+
+impl WorkloadIdentityMapper {
+    /// Create a mapper for the given trust domain
+    pub fn new(trust_domain: impl Into<String>) -> Self {
+        Self {
+            trust_domain: trust_domain.into(),
+            mappings: Vec::new(),
+        }
+    }
+
+    /// Add a mapping
+    pub fn with_mapping(mut self, mapping: WorkloadMapping) -> Self {
+        self.mappings.push(mapping);
+        self
+    }
+
+    /// Load a mapper from its JSON representation
+    pub fn from_json(json: &str) -> SecurityResult<Self> {
+        serde_json::from_str(json).map_err(|e| {
+            SecurityError::ConfigError(format!("Invalid workload identity mappings: {}", e))
+        })
+    }
+
+    /// Resolve a SPIFFE ID to a workload identity
+    pub fn resolve(&self, id: &SpiffeId) -> SecurityResult<WorkloadIdentity> {
+        if id.trust_domain() != self.trust_domain {
+            return Err(SecurityError::AuthenticationError(format!(
+                "Untrusted SPIFFE trust domain: {}",
+                id.trust_domain()
+            )));
+        }
+
+        let mapping = self
+            .mappings
+            .iter()
+            .find(|m| m.matches(id))
+            .ok_or_else(|| {
+                SecurityError::AuthenticationError(format!("Unmapped workload identity: {}", id))
+            })?;
+
+        Ok(WorkloadIdentity {
+            spiffe_id: id.clone(),
+            principal: mapping.principal.clone(),
+            namespaces: mapping.namespaces.clone(),
+            tenant: mapping.tenant.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_mapper() -> WorkloadIdentityMapper {
+        WorkloadIdentityMapper::new("prod.example.org")
+            .with_mapping(
+                WorkloadMapping::new("spiffe://prod.example.org/ns/ml/sa/serving", "ml-serving")
+                    .with_namespace("ml/**"),
+            )
+            .with_mapping(WorkloadMapping::new(
+                "spiffe://prod.example.org/ns/platform/*",
+                "platform",
+            ))
+    }
+
+    #[test]
+    fn test_parse_spiffe_id() {
+        let id = SpiffeId::parse("spiffe://prod.example.org/ns/ml/sa/serving").unwrap();
+        assert_eq!(id.trust_domain(), "prod.example.org");
+        assert_eq!(id.path(), "/ns/ml/sa/serving");
+        assert_eq!(id.to_string(), "spiffe://prod.example.org/ns/ml/sa/serving");
+
+        assert!(SpiffeId::parse("https://prod.example.org/x").is_err());
+        assert!(SpiffeId::parse("spiffe:///path").is_err());
+        assert!(SpiffeId::parse("spiffe://Prod.example.org/x").is_err());
+        assert!(SpiffeId::parse("spiffe://prod.example.org/a/../b").is_err());
+        assert!(SpiffeId::parse("spiffe://prod.example.org/a/").is_err());
+    }
+
+    #[test]
+    fn test_from_xfcc() {
+        let header = "By=spiffe://prod.example.org/ns/config/sa/api;\
+                      Hash=abc123;URI=spiffe://prod.example.org/ns/ml/sa/serving";
+        let id = SpiffeId::from_xfcc(header).unwrap();
+        assert_eq!(id.path(), "/ns/ml/sa/serving");
+
+        assert!(SpiffeId::from_xfcc("Hash=abc123;Subject=\"CN=foo\"").is_none());
+    }
+
+    #[test]
+    fn test_resolve_exact_and_prefix() {
+        let mapper = test_mapper();
+
+        let serving = mapper
+            .resolve(&SpiffeId::parse("spiffe://prod.example.org/ns/ml/sa/serving").unwrap())
+            .unwrap();
+        assert_eq!(serving.principal, "ml-serving");
+
+        let platform = mapper
+            .resolve(&SpiffeId::parse("spiffe://prod.example.org/ns/platform/sa/deployer").unwrap())
+            .unwrap();
+        assert_eq!(platform.principal, "platform");
+        assert!(platform.allows_namespace("anything/at/all"));
+    }
+
+    #[test]
+    fn test_resolve_rejects_unknown() {
+        let mapper = test_mapper();
+
+        assert!(mapper
+            .resolve(&SpiffeId::parse("spiffe://other.example.org/ns/ml/sa/serving").unwrap())
+            .is_err());
+        assert!(mapper
+            .resolve(&SpiffeId::parse("spiffe://prod.example.org/ns/ml/sa/batch").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_namespace_scope() {
+        let identity = test_mapper()
+            .resolve(&SpiffeId::parse("spiffe://prod.example.org/ns/ml/sa/serving").unwrap())
+            .unwrap();
+
+        assert!(identity.check_namespace("ml").is_ok());
+        assert!(identity.check_namespace("ml/serving").is_ok());
+        assert!(identity.check_namespace("mlops").is_err());
+        assert!(identity.check_namespace("billing/api").is_err());
+    }
+
+    #[test]
+    fn test_mapper_from_json() {
+        let mapper = WorkloadIdentityMapper::from_json(
+            r#"{
+                "trust_domain": "prod.example.org",
+                "mappings": [
+                    {"spiffe_id": "spiffe://prod.example.org/ns/ml/*", "principal": "ml", "namespaces": ["ml/*"]},
+                    {"spiffe_id": "spiffe://prod.example.org/tenant/acme/*", "principal": "acme-app", "tenant": "acme"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(mapper.mappings.len(), 2);
+        assert_eq!(mapper.mappings[0].namespaces, vec!["ml/*".to_string()]);
+        assert!(mapper.mappings[0].tenant.is_none());
+        assert_eq!(mapper.mappings[1].tenant.as_deref(), Some("acme"));
+    }
+}
@@ -19,6 +19,9 @@ static COMMAND_INJECTION_PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
 /// LDAP injection patterns
 static LDAP_INJECTION_PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
 
+/// Injection patterns for fields of JSON payloads
+static FIELD_INJECTION_PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+
 /// Initialize security patterns
 fn init_patterns() {
     SQL_INJECTION_PATTERNS.get_or_init(|| {
@@ -71,6 +74,20 @@ fn init_patterns() {
             Regex::new(r"\x00").unwrap(),
         ]
     });
+
+    // Config values routinely contain quotes, `;`, `&` and `@`, so fields are
+    // only checked for unambiguous SQL keywords and shell command substitution
+    FIELD_INJECTION_PATTERNS.get_or_init(|| {
+        vec![
+            Regex::new(r"(?i)(\bunion\b.*\bselect\b)").unwrap(),
+            Regex::new(r"(?i)(\bdrop\b\s+\btable\b)").unwrap(),
+            Regex::new(r"(?i)\bexec(\s|\+)+(s|x)p\w+").unwrap(),
+            Regex::new(r"'\s*;\s*--").unwrap(),
+            Regex::new(r"\$\(.*\)").unwrap(),
+            Regex::new(r"`.*`").unwrap(),
+            Regex::new(r"\x00").unwrap(),
+        ]
+    });
 }
 
 /// Configuration for input sanitization
@@ -303,6 +320,43 @@ impl InputValidator {
         serde_json::from_str(json)
             .map_err(|e| SecurityError::ValidationError(format!("Invalid JSON: {}", e)))
     }
+
+    /// Validate a single field (string value or object key) of a JSON payload
+    ///
+    /// Unlike [`validate`](Self::validate), punctuation common in config values
+    /// is allowed; only XSS, path traversal and unambiguous injection patterns
+    /// are rejected.
+    pub fn validate_json_field(&self, field: &str) -> SecurityResult<()> {
+        if self.detect_xss(field) {
+            return Err(SecurityError::XssAttempt);
+        }
+
+        if self.detect_path_traversal(field) {
+            return Err(SecurityError::PathTraversalAttempt);
+        }
+
+        let patterns = FIELD_INJECTION_PATTERNS.get().unwrap();
+        match patterns.iter().position(|pattern| pattern.is_match(field)) {
+            Some(0..=3) => Err(SecurityError::SqlInjectionAttempt),
+            Some(_) => Err(SecurityError::CommandInjectionAttempt),
+            None => Ok(()),
+        }
+    }
+
+    /// Validate every string value and object key of a parsed JSON payload
+    pub fn validate_json_fields(&self, value: &serde_json::Value) -> SecurityResult<()> {
+        match value {
+            serde_json::Value::String(s) => self.validate_json_field(s),
+            serde_json::Value::Array(items) => items
+                .iter()
+                .try_for_each(|item| self.validate_json_fields(item)),
+            serde_json::Value::Object(map) => map.iter().try_for_each(|(key, item)| {
+                self.validate_json_field(key)?;
+                self.validate_json_fields(item)
+            }),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// HTML escape special characters
@@ -435,6 +489,41 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    fn test_json_field_validation() {
+        let validator = InputValidator::default();
+
+        let value = serde_json::json!({
+            "dsn": "postgres://app:p@ss;word@db/app?sslmode=require&x='y'",
+            "tags": ["a", "b"],
+            "limit": 10,
+        });
+        assert!(validator.validate_json_fields(&value).is_ok());
+
+        // Escaped markup is only caught once the payload is parsed
+        let value: serde_json::Value =
+            serde_json::from_str(r#"{"value": "\u003cscript\u003ealert(1)\u003c/script\u003e"}"#)
+                .unwrap();
+        assert!(validator
+            .validate_json_fields(&value)
+            .is_err_and(|e| matches!(e, SecurityError::XssAttempt)));
+
+        let value = serde_json::json!({"value": ["ok", "x' UNION SELECT password FROM users"]});
+        assert!(validator
+            .validate_json_fields(&value)
+            .is_err_and(|e| matches!(e, SecurityError::SqlInjectionAttempt)));
+
+        let value = serde_json::json!({"$(reboot)": true});
+        assert!(validator
+            .validate_json_fields(&value)
+            .is_err_and(|e| matches!(e, SecurityError::CommandInjectionAttempt)));
+
+        let value = serde_json::json!({"path": "../../etc/passwd"});
+        assert!(validator
+            .validate_json_fields(&value)
+            .is_err_and(|e| matches!(e, SecurityError::PathTraversalAttempt)));
+    }
+
     #[test]
     fn test_sanitization() {
         let validator = InputValidator::default();
@@ -10,6 +10,8 @@
 //! - Security policy enforcement
 //! - Audit logging validation
 //! - Attack prevention (SQLi, XSS, CSRF, etc.)
+//! - Workload identity (SPIFFE) mapping
+//! - Client IP resolution behind trusted proxies
 
 pub mod errors;
 pub mod input;
@@ -18,14 +20,23 @@ pub mod crypto;
 pub mod policy;
 pub mod audit;
 pub mod validation;
+pub mod identity;
+pub mod proxy;
 
 pub use errors::{SecurityError, SecurityResult};
 pub use input::{InputValidator, SanitizationConfig};
-pub use rate_limit::{RateLimiter, RateLimitConfig};
-pub use crypto::{CryptoValidator, KeyValidator};
+pub use rate_limit::{
+    PrincipalQuota, PrincipalQuotas, RateLimitConfig, RateLimitStats, RateLimitStore,
+    RateLimiter, SecretAccessLimit, SecretAccessLimiter, SharedBan,
+};
+#[cfg(feature = "redis")]
+pub use rate_limit::redis::{RedisRateLimitConfig, RedisRateLimitStore};
+pub use crypto::{CryptoValidator, KeyValidator, MAX_KEY_AGE_DAYS};
 pub use policy::{SecurityPolicy, PolicyEnforcer};
-pub use audit::{AuditValidator, AuditConfig};
+pub use audit::{AuditValidator, AuditConfig, SequenceMode};
 pub use validation::{Validator, ValidationRule};
+pub use identity::{SpiffeId, WorkloadIdentity, WorkloadIdentityMapper, WorkloadMapping};
+pub use proxy::TrustedProxies;
 
 /// Security context for operations
 #[derive(Debug, Clone)]
@@ -1,9 +1,11 @@
 //! Security policy enforcement
 
 use crate::errors::{SecurityError, SecurityResult};
+use crate::proxy::TrustedProxies;
 use crate::SecurityContext;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::net::IpAddr;
 
 /// Security policy configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +34,10 @@ pub struct SecurityPolicy {
     pub enable_audit: bool,
     /// Data classification levels
     pub data_classifications: Vec<DataClassification>,
+    /// Reverse proxies (addresses or CIDR ranges) trusted to report the
+    /// client IP in `Forwarded` / `X-Forwarded-For`
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
 }
 
 impl Default for SecurityPolicy {
@@ -54,6 +60,7 @@ impl Default for SecurityPolicy {
                 DataClassification::Confidential,
                 DataClassification::Secret,
             ],
+            trusted_proxies: vec![],
         }
     }
 }
@@ -71,15 +78,21 @@ pub enum DataClassification {
 pub struct PolicyEnforcer {
     policy: SecurityPolicy,
     blocked_ips: HashSet<String>,
+    trusted_proxies: TrustedProxies,
 }
 
 impl PolicyEnforcer {
     /// Create a new policy enforcer
+    ///
+    /// No proxy is trusted if a trusted proxy entry is invalid; validate them
+    /// with [`TrustedProxies::parse`] to reject them instead.
     pub fn new(policy: SecurityPolicy) -> Self {
         let blocked_ips = policy.blocked_ips.iter().cloned().collect();
+        let trusted_proxies = trusted_proxies(&policy);
         Self {
             policy,
             blocked_ips,
+            trusted_proxies,
         }
     }
 
@@ -115,6 +128,22 @@ impl PolicyEnforcer {
         Ok(())
     }
 
+    /// The client IP of a request from `peer`, taken from the forwarding
+    /// headers when `peer` is a trusted proxy
+    pub fn client_ip(
+        &self,
+        peer: IpAddr,
+        forwarded: Option<&str>,
+        x_forwarded_for: Option<&str>,
+    ) -> IpAddr {
+        self.trusted_proxies.client_ip(peer, forwarded, x_forwarded_for)
+    }
+
+    /// Whether `peer` is a trusted proxy, whose forwarding headers are honored
+    pub fn is_trusted_proxy(&self, peer: IpAddr) -> bool {
+        self.trusted_proxies.is_trusted(peer)
+    }
+
     /// Check if TLS is required
     pub fn check_tls(&self, is_tls: bool, version: &str) -> SecurityResult<()> {
         if self.policy.require_tls && !is_tls {
@@ -310,10 +339,19 @@ impl PolicyEnforcer {
     /// Update the policy
     pub fn update_policy(&mut self, policy: SecurityPolicy) {
         self.blocked_ips = policy.blocked_ips.iter().cloned().collect();
+        self.trusted_proxies = trusted_proxies(&policy);
         self.policy = policy;
     }
 }
 
+/// The trusted proxies of a policy, none if any entry is invalid
+fn trusted_proxies(policy: &SecurityPolicy) -> TrustedProxies {
+    TrustedProxies::parse(&policy.trusted_proxies).unwrap_or_else(|e| {
+        tracing::warn!("Not trusting any proxy: {}", e);
+        TrustedProxies::default()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -462,6 +500,28 @@ mod tests {
         assert!(enforcer.check_request(&context).is_ok());
     }
 
+    #[test]
+    fn test_client_ip_from_trusted_proxy() {
+        let policy = SecurityPolicy {
+            trusted_proxies: vec!["10.0.0.0/8".to_string()],
+            ..Default::default()
+        };
+
+        let enforcer = PolicyEnforcer::new(policy);
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let client: IpAddr = "198.51.100.7".parse().unwrap();
+
+        assert_eq!(enforcer.client_ip(proxy, None, Some("198.51.100.7")), client);
+        assert_eq!(enforcer.client_ip(client, None, Some("10.9.9.9")), client);
+
+        let mut enforcer = enforcer;
+        enforcer.update_policy(SecurityPolicy {
+            trusted_proxies: vec!["10.0.0.0/8".to_string(), "garbage".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(enforcer.client_ip(proxy, None, Some("198.51.100.7")), proxy);
+    }
+
     #[test]
     fn test_pattern_matching() {
         let enforcer = PolicyEnforcer::default();
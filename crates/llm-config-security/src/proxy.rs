@@ -0,0 +1,194 @@
+//! Client IP resolution behind trusted reverse proxies
+
+use crate::errors::{SecurityError, SecurityResult};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+
+/// Reverse proxies whose forwarding headers are trusted
+///
+/// Entries are single addresses (`10.0.0.7`) or CIDR ranges (`10.0.0.0/8`).
+/// The client IP is only taken from `Forwarded` or `X-Forwarded-For` when
+/// the peer is one of them; otherwise any client could spoof its address.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    ranges: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    /// Parse the trusted proxy addresses and ranges
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> SecurityResult<Self> {
+        let ranges = entries
+            .iter()
+            .map(|entry| parse_range(entry.as_ref()))
+            .collect::<SecurityResult<_>>()?;
+        Ok(Self { ranges })
+    }
+
+    /// Whether no proxy is trusted
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Whether `ip` is a trusted proxy
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        self.ranges.iter().any(|range| range.contains(&ip))
+    }
+
+    /// The client IP of a request received from `peer`
+    ///
+    /// Forwarded addresses are walked from the nearest hop outwards, skipping
+    /// trusted proxies; the first untrusted address is the client. The
+    /// standard `Forwarded` header takes precedence over `X-Forwarded-For`.
+    /// Malformed or obfuscated entries end the walk at the last address that
+    /// could be resolved.
+    pub fn client_ip(
+        &self,
+        peer: IpAddr,
+        forwarded: Option<&str>,
+        x_forwarded_for: Option<&str>,
+    ) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let hops: Vec<&str> = match (forwarded, x_forwarded_for) {
+            (Some(header), _) => header.split(',').map(forwarded_for).collect(),
+            (None, Some(header)) => header.split(',').collect(),
+            (None, None) => return peer,
+        };
+
+        let mut client = peer;
+        for hop in hops.iter().rev() {
+            match parse_hop(hop) {
+                Some(ip) => {
+                    client = ip;
+                    if !self.is_trusted(ip) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        client
+    }
+}
+
+/// Parse an address or CIDR range
+fn parse_range(entry: &str) -> SecurityResult<IpNet> {
+    let entry = entry.trim();
+    entry
+        .parse::<IpNet>()
+        .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| {
+            SecurityError::ValidationError(format!("Invalid trusted proxy address: {}", entry))
+        })
+}
+
+/// The `for=` parameter of one `Forwarded` element (empty if it has none)
+fn forwarded_for(element: &str) -> &str {
+    element
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("for"))
+        .map(|(_, value)| value)
+        .unwrap_or("")
+}
+
+/// Parse one hop: an IP with an optional port, possibly quoted and bracketed
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim().trim_matches('"');
+    if let Ok(ip) = hop.parse::<IpAddr>() {
+        return Some(canonical(ip));
+    }
+    if let Ok(addr) = hop.parse::<SocketAddr>() {
+        return Some(canonical(addr.ip()));
+    }
+    // Bracketed IPv6 without a port
+    hop.strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .map(canonical)
+}
+
+/// IPv4-mapped IPv6 addresses as plain IPv4
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let proxies = TrustedProxies::parse(&["10.0.0.0/8", "192.168.1.5", "fd00::/8"]).unwrap();
+
+        assert!(proxies.is_trusted(ip("10.1.2.3")));
+        assert!(proxies.is_trusted(ip("192.168.1.5")));
+        assert!(proxies.is_trusted(ip("::ffff:10.0.0.1")));
+        assert!(proxies.is_trusted(ip("fd12::1")));
+        assert!(!proxies.is_trusted(ip("192.168.1.6")));
+
+        assert!(TrustedProxies::parse(&["not-an-ip"]).is_err());
+        assert!(TrustedProxies::parse(&["10.0.0.0/33"]).is_err());
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_headers() {
+        let proxies = TrustedProxies::parse(&["10.0.0.0/8"]).unwrap();
+
+        let client = proxies.client_ip(ip("203.0.113.9"), None, Some("198.51.100.1"));
+        assert_eq!(client, ip("203.0.113.9"));
+
+        let client = TrustedProxies::default().client_ip(ip("10.0.0.1"), None, Some("198.51.100.1"));
+        assert_eq!(client, ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_x_forwarded_for_skips_trusted_hops() {
+        let proxies = TrustedProxies::parse(&["10.0.0.0/8"]).unwrap();
+
+        // Spoofed first entry, real client, then an inner proxy
+        let client = proxies.client_ip(
+            ip("10.0.0.1"),
+            None,
+            Some("1.2.3.4, 198.51.100.7, 10.0.0.2"),
+        );
+        assert_eq!(client, ip("198.51.100.7"));
+
+        // Only trusted hops: the outermost one is the client
+        let client = proxies.client_ip(ip("10.0.0.1"), None, Some("10.0.0.3"));
+        assert_eq!(client, ip("10.0.0.3"));
+
+        // Garbage stops the walk
+        let client = proxies.client_ip(ip("10.0.0.1"), None, Some("bogus, 10.0.0.2"));
+        assert_eq!(client, ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let proxies = TrustedProxies::parse(&["10.0.0.0/8"]).unwrap();
+
+        let client = proxies.client_ip(
+            ip("10.0.0.1"),
+            Some(r#"for=192.0.2.60;proto=https, For="[2001:db8::1]:4711";by=10.0.0.1"#),
+            Some("198.51.100.1"),
+        );
+        assert_eq!(client, ip("2001:db8::1"));
+
+        let client = proxies.client_ip(ip("10.0.0.1"), Some("for=192.0.2.60:8080"), None);
+        assert_eq!(client, ip("192.0.2.60"));
+
+        // Obfuscated identifiers cannot be resolved
+        let client = proxies.client_ip(ip("10.0.0.1"), Some("for=_hidden"), None);
+        assert_eq!(client, ip("10.0.0.1"));
+    }
+}
@@ -1,4 +1,11 @@
 //! Rate limiting and throttling
+//!
+//! State is kept in memory by default. With a [`RateLimitStore`] (such as
+//! `redis::RedisRateLimitStore` with the `redis` feature), buckets, violations
+//! and bans are shared between API instances and survive restarts.
+
+#[cfg(feature = "redis")]
+pub mod redis;
 
 use crate::errors::{SecurityError, SecurityResult};
 use governor::{
@@ -6,10 +13,12 @@ use governor::{
     state::{InMemoryState, NotKeyed},
     Quota, RateLimiter as GovernorRateLimiter,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::IpAddr;
 use std::num::NonZeroU32;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 
 /// Rate limit configuration
@@ -42,6 +51,110 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// Request quota of a single principal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrincipalQuota {
+    /// Sustained requests per second
+    pub requests_per_second: u32,
+    /// Requests allowed in a burst
+    pub burst: u32,
+}
+
+impl PrincipalQuota {
+    /// The governor quota, with zero values raised to one
+    fn quota(&self) -> Quota {
+        Quota::per_second(NonZeroU32::new(self.requests_per_second).unwrap_or(NonZeroU32::MIN))
+            .allow_burst(NonZeroU32::new(self.burst).unwrap_or(NonZeroU32::MIN))
+    }
+}
+
+/// Independent quotas for authenticated principals
+///
+/// Each principal (the common name of a client certificate or a workload
+/// principal) gets its own bucket, so one misbehaving caller is throttled
+/// before it drains the shared authenticated bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrincipalQuotas {
+    /// Quota of principals without a principal or role quota (principals
+    /// are not limited individually when unset)
+    #[serde(default)]
+    pub default: Option<PrincipalQuota>,
+    /// Quotas by RBAC role; the most generous quota of a principal's roles applies
+    #[serde(default)]
+    pub roles: HashMap<String, PrincipalQuota>,
+    /// Quotas by principal, taking precedence over role quotas
+    #[serde(default)]
+    pub principals: HashMap<String, PrincipalQuota>,
+}
+
+impl PrincipalQuotas {
+    /// Whether no principal is limited
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.roles.is_empty() && self.principals.is_empty()
+    }
+
+    /// The quota of a principal with the given roles
+    pub fn quota_for(&self, principal: &str, roles: &[String]) -> Option<PrincipalQuota> {
+        if let Some(quota) = self.principals.get(principal) {
+            return Some(*quota);
+        }
+        roles
+            .iter()
+            .filter_map(|role| self.roles.get(role))
+            .max_by_key(|quota| (quota.requests_per_second, quota.burst))
+            .copied()
+            .or(self.default)
+    }
+}
+
+/// A ban as kept in a [`RateLimitStore`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedBan {
+    /// When the IP was banned
+    pub banned_at: chrono::DateTime<chrono::Utc>,
+    /// Reason for the ban
+    pub reason: String,
+    /// Number of violations that led to the ban
+    pub violations: usize,
+}
+
+impl From<&SharedBan> for BanInfo {
+    fn from(ban: &SharedBan) -> Self {
+        let age = (chrono::Utc::now() - ban.banned_at).to_std().unwrap_or_default();
+        let now = std::time::Instant::now();
+        Self {
+            banned_at: now.checked_sub(age).unwrap_or(now),
+            reason: ban.reason.clone(),
+            violations: ban.violations,
+        }
+    }
+}
+
+/// Rate limiter state shared between API instances
+///
+/// Buckets are named `global:authenticated`, `global:anonymous`, `ip:<ip>` and
+/// `principal:<principal>`. Stores expire bans and buckets themselves.
+pub trait RateLimitStore: Send + Sync + std::fmt::Debug {
+    /// Count a request against `bucket`, which allows `burst` requests and
+    /// refills at `requests_per_second`; returns whether it is allowed
+    fn acquire(&self, bucket: &str, requests_per_second: u32, burst: u32) -> SecurityResult<bool>;
+
+    /// Count a violation by `ip`, returning its violations within `window`
+    fn record_violation(&self, ip: IpAddr, window: Duration) -> SecurityResult<usize>;
+
+    /// Ban `ip` for `duration`
+    fn ban(&self, ip: IpAddr, ban: &SharedBan, duration: Duration) -> SecurityResult<()>;
+
+    /// Lift the ban of `ip`
+    fn unban(&self, ip: IpAddr) -> SecurityResult<()>;
+
+    /// Whether `ip` is currently banned
+    fn is_banned(&self, ip: IpAddr) -> SecurityResult<bool>;
+
+    /// All current bans
+    fn bans(&self) -> SecurityResult<Vec<(IpAddr, SharedBan)>>;
+}
+
 /// Rate limiter for API endpoints
 pub struct RateLimiter {
     config: RateLimitConfig,
@@ -49,6 +162,20 @@ pub struct RateLimiter {
     unauthenticated_limiter: Arc<GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock>>,
     per_ip_limiters: Arc<RwLock<HashMap<IpAddr, IpLimiter>>>,
     banned_ips: Arc<RwLock<HashMap<IpAddr, BanInfo>>>,
+    principal_quotas: PrincipalQuotas,
+    per_principal_limiters: Arc<RwLock<HashMap<String, PrincipalLimiter>>>,
+    store: Option<Arc<dyn RateLimitStore>>,
+    /// Violations counted by this instance while using `store`
+    shared_violations: AtomicUsize,
+    shared_principal_violations: AtomicUsize,
+}
+
+#[derive(Debug, Clone)]
+struct PrincipalLimiter {
+    limiter: Arc<GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock>>,
+    quota: PrincipalQuota,
+    violations: usize,
+    last_seen: std::time::Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -92,7 +219,85 @@ impl RateLimiter {
             )),
             per_ip_limiters: Arc::new(RwLock::new(HashMap::new())),
             banned_ips: Arc::new(RwLock::new(HashMap::new())),
+            principal_quotas: PrincipalQuotas::default(),
+            per_principal_limiters: Arc::new(RwLock::new(HashMap::new())),
+            store: None,
+            shared_violations: AtomicUsize::new(0),
+            shared_principal_violations: AtomicUsize::new(0),
+        }
+    }
+
+    /// Keep buckets, violations and bans in a shared store instead of memory
+    ///
+    /// Requests are allowed while the store cannot be reached, so an outage
+    /// of the store does not take the API down with it.
+    pub fn with_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Get the shared store, if any
+    pub fn store(&self) -> Option<&Arc<dyn RateLimitStore>> {
+        self.store.as_ref()
+    }
+
+    /// Limit authenticated principals individually with the given quotas
+    pub fn with_principal_quotas(mut self, quotas: PrincipalQuotas) -> Self {
+        self.principal_quotas = quotas;
+        self
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Get the per-principal quotas
+    pub fn principal_quotas(&self) -> &PrincipalQuotas {
+        &self.principal_quotas
+    }
+
+    /// Check a request of an authenticated principal against its own quota
+    ///
+    /// Principals without a quota are not limited here. Check this before
+    /// [`check_request`](Self::check_request) so throttled principals do not
+    /// consume the shared buckets.
+    pub fn check_principal(&self, principal: &str, roles: &[String]) -> SecurityResult<()> {
+        let Some(quota) = self.principal_quotas.quota_for(principal, roles) else {
+            return Ok(());
+        };
+
+        if let Some(store) = &self.store {
+            let bucket = format!("principal:{}", principal);
+            if !acquire_shared(store.as_ref(), &bucket, quota.requests_per_second, quota.burst) {
+                self.shared_principal_violations.fetch_add(1, Ordering::Relaxed);
+                return Err(SecurityError::RateLimitExceeded(format!(
+                    "Too many requests from principal {}. Please try again later",
+                    principal
+                )));
+            }
+            return Ok(());
         }
+
+        let mut limiters = self.per_principal_limiters.write().unwrap();
+        let limiter = limiters
+            .entry(principal.to_string())
+            .or_insert_with(|| PrincipalLimiter::new(quota));
+        // Role changes take effect with a fresh bucket
+        if limiter.quota != quota {
+            *limiter = PrincipalLimiter::new(quota);
+        }
+        limiter.last_seen = std::time::Instant::now();
+
+        if limiter.limiter.check().is_err() {
+            limiter.violations += 1;
+            return Err(SecurityError::RateLimitExceeded(format!(
+                "Too many requests from principal {}. Please try again later",
+                principal
+            )));
+        }
+
+        Ok(())
     }
 
     /// Check if a request is allowed
@@ -108,6 +313,10 @@ impl RateLimiter {
             ));
         }
 
+        if let Some(store) = &self.store {
+            return self.check_request_shared(store.as_ref(), ip, authenticated);
+        }
+
         // Check global rate limit
         let limiter = if authenticated {
             &self.authenticated_limiter
@@ -157,8 +366,71 @@ impl RateLimiter {
         Ok(())
     }
 
+    /// Shared-store counterpart of the bucket checks of [`check_request`](Self::check_request),
+    /// with the same quotas
+    fn check_request_shared(
+        &self,
+        store: &dyn RateLimitStore,
+        ip: IpAddr,
+        authenticated: bool,
+    ) -> SecurityResult<()> {
+        let config = &self.config;
+        let (global, global_rps, global_burst, ip_rps) = if authenticated {
+            (
+                "global:authenticated",
+                config.authenticated_rps,
+                config.burst_size,
+                config.authenticated_rps / 10,
+            )
+        } else {
+            (
+                "global:anonymous",
+                config.unauthenticated_rps,
+                config.burst_size / 5,
+                config.unauthenticated_rps,
+            )
+        };
+
+        if !acquire_shared(store, global, global_rps, global_burst) {
+            self.record_shared_violation(store, ip, "Global rate limit exceeded");
+            return Err(SecurityError::RateLimitExceeded(
+                "Too many requests. Please try again later".to_string(),
+            ));
+        }
+
+        let bucket = format!("ip:{}", ip);
+        if !acquire_shared(store, &bucket, if ip_rps == 0 { 10 } else { ip_rps }, 10) {
+            self.record_shared_violation(store, ip, "Per-IP rate limit exceeded");
+            return Err(SecurityError::RateLimitExceeded(format!(
+                "Too many requests from IP {}. Please try again later",
+                ip
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Record a violation in the shared store, banning the IP at the threshold
+    fn record_shared_violation(&self, store: &dyn RateLimitStore, ip: IpAddr, reason: &str) {
+        self.shared_violations.fetch_add(1, Ordering::Relaxed);
+        match store.record_violation(ip, Duration::from_secs(self.config.window_seconds)) {
+            Ok(violations) if violations >= self.config.ban_threshold => {
+                self.ban_ip(ip, reason.to_string(), violations);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(ip = %ip, error = %e, "Failed to record rate limit violation"),
+        }
+    }
+
     /// Check if an IP is banned
     fn is_banned(&self, ip: IpAddr) -> bool {
+        if let Some(store) = &self.store {
+            return store.is_banned(ip).unwrap_or_else(|e| {
+                tracing::warn!(ip = %ip, error = %e, "Failed to check IP ban");
+                false
+            });
+        }
+
         let banned = self.banned_ips.read().unwrap();
         if let Some(ban_info) = banned.get(&ip) {
             let elapsed = ban_info.banned_at.elapsed();
@@ -189,6 +461,24 @@ impl RateLimiter {
 
     /// Ban an IP address
     fn ban_ip(&self, ip: IpAddr, reason: String, violations: usize) {
+        if let Some(store) = &self.store {
+            let ban = SharedBan {
+                banned_at: chrono::Utc::now(),
+                reason,
+                violations,
+            };
+            let duration = Duration::from_secs(self.config.ban_duration_seconds);
+            match store.ban(ip, &ban, duration) {
+                Ok(()) => tracing::warn!(
+                    ip = %ip,
+                    violations = violations,
+                    "IP address banned due to rate limit violations"
+                ),
+                Err(e) => tracing::error!(ip = %ip, error = %e, "Failed to store IP ban"),
+            }
+            return;
+        }
+
         let mut banned = self.banned_ips.write().unwrap();
         banned.insert(
             ip,
@@ -213,6 +503,14 @@ impl RateLimiter {
 
     /// Unban an IP
     pub fn unban(&self, ip: IpAddr) {
+        if let Some(store) = &self.store {
+            match store.unban(ip) {
+                Ok(()) => tracing::info!(ip = %ip, "IP address unbanned"),
+                Err(e) => tracing::error!(ip = %ip, error = %e, "Failed to remove IP ban"),
+            }
+            return;
+        }
+
         let mut banned = self.banned_ips.write().unwrap();
         if banned.remove(&ip).is_some() {
             tracing::info!(ip = %ip, "IP address unbanned");
@@ -221,6 +519,16 @@ impl RateLimiter {
 
     /// Get banned IPs
     pub fn get_banned_ips(&self) -> Vec<(IpAddr, BanInfo)> {
+        if let Some(store) = &self.store {
+            return match store.bans() {
+                Ok(bans) => bans.iter().map(|(ip, ban)| (*ip, ban.into())).collect(),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to list IP bans");
+                    Vec::new()
+                }
+            };
+        }
+
         let banned = self.banned_ips.read().unwrap();
         banned
             .iter()
@@ -240,27 +548,187 @@ impl RateLimiter {
         limiters.retain(|_, ip_limiter| {
             ip_limiter.last_violation.elapsed() < Duration::from_secs(3600)
         });
+
+        let mut limiters = self.per_principal_limiters.write().unwrap();
+        limiters.retain(|_, limiter| limiter.last_seen.elapsed() < Duration::from_secs(3600));
     }
 
     /// Get current statistics
     pub fn get_stats(&self) -> RateLimitStats {
-        let banned = self.banned_ips.read().unwrap();
+        let banned_ips = match &self.store {
+            Some(_) => self.get_banned_ips().len(),
+            None => self.banned_ips.read().unwrap().len(),
+        };
         let limiters = self.per_ip_limiters.read().unwrap();
+        let principals = self.per_principal_limiters.read().unwrap();
 
         RateLimitStats {
             active_limiters: limiters.len(),
-            banned_ips: banned.len(),
-            total_violations: limiters.values().map(|l| l.violations).sum(),
+            banned_ips,
+            total_violations: limiters.values().map(|l| l.violations).sum::<usize>()
+                + self.shared_violations.load(Ordering::Relaxed),
+            active_principal_limiters: principals.len(),
+            principal_violations: principals.values().map(|l| l.violations).sum::<usize>()
+                + self.shared_principal_violations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Count a request in a shared bucket, allowing it if the store fails
+fn acquire_shared(
+    store: &dyn RateLimitStore,
+    bucket: &str,
+    requests_per_second: u32,
+    burst: u32,
+) -> bool {
+    store
+        .acquire(bucket, requests_per_second.max(1), burst.max(1))
+        .unwrap_or_else(|e| {
+            tracing::warn!(bucket, error = %e, "Rate limit store unavailable, allowing request");
+            true
+        })
+}
+
+impl PrincipalLimiter {
+    fn new(quota: PrincipalQuota) -> Self {
+        Self {
+            limiter: Arc::new(GovernorRateLimiter::direct(quota.quota())),
+            quota,
+            violations: 0,
+            last_seen: std::time::Instant::now(),
+        }
+    }
+}
+
+/// How many secrets a single caller may reveal
+///
+/// Reads are counted per caller across all namespaces, so a caller dumping
+/// secrets in bulk runs out of reads after `burst` and is then slowed to
+/// `reads_per_minute`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretAccessLimit {
+    /// Sustained secret reads per minute
+    pub reads_per_minute: u32,
+    /// Secret reads allowed in a burst
+    pub burst: u32,
+}
+
+impl SecretAccessLimit {
+    /// The governor quota, with zero values raised to one
+    fn quota(&self) -> Quota {
+        Quota::per_minute(NonZeroU32::new(self.reads_per_minute).unwrap_or(NonZeroU32::MIN))
+            .allow_burst(NonZeroU32::new(self.burst).unwrap_or(NonZeroU32::MIN))
+    }
+
+    /// Time for an exhausted burst to refill completely, at least a
+    /// millisecond
+    fn refill_time(&self) -> Duration {
+        let millis = 60_000 * u64::from(self.burst.max(1)) / u64::from(self.reads_per_minute.max(1));
+        Duration::from_millis(millis.max(1))
+    }
+}
+
+/// Per-caller limiter of secret reads
+///
+/// Kept in memory; each API instance counts the reads it serves. Callers
+/// are named by whoever checks them; the API server uses verified
+/// principals and client IP addresses, never claimed user IDs. Callers idle
+/// for longer than the limit takes to refill are forgotten, see
+/// [`cleanup`](Self::cleanup).
+#[derive(Debug)]
+pub struct SecretAccessLimiter {
+    limit: SecretAccessLimit,
+    callers: RwLock<HashMap<String, SecretReads>>,
+    last_cleanup: Mutex<std::time::Instant>,
+}
+
+#[derive(Debug)]
+struct SecretReads {
+    limiter: GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock>,
+    denied: usize,
+    last_seen: std::time::Instant,
+}
+
+impl SecretAccessLimiter {
+    /// Create a limiter allowing every caller `limit`
+    pub fn new(limit: SecretAccessLimit) -> Self {
+        Self {
+            limit,
+            callers: RwLock::new(HashMap::new()),
+            last_cleanup: Mutex::new(std::time::Instant::now()),
+        }
+    }
+
+    /// The limit of each caller
+    pub fn limit(&self) -> SecretAccessLimit {
+        self.limit
+    }
+
+    /// Count a secret read by `caller`
+    ///
+    /// Fails with [`SecurityError::RateLimitExceeded`] once the caller is
+    /// over the limit; denied reads are counted, see [`denied`](Self::denied).
+    pub fn check(&self, caller: &str) -> SecurityResult<()> {
+        self.cleanup_if_due();
+
+        let mut callers = self.callers.write().unwrap();
+        let reads = callers.entry(caller.to_string()).or_insert_with(|| SecretReads {
+            limiter: GovernorRateLimiter::direct(self.limit.quota()),
+            denied: 0,
+            last_seen: std::time::Instant::now(),
+        });
+        reads.last_seen = std::time::Instant::now();
+        if reads.limiter.check().is_err() {
+            reads.denied += 1;
+            return Err(SecurityError::RateLimitExceeded(format!(
+                "Too many secret reads by {}. Please try again later",
+                caller
+            )));
+        }
+        Ok(())
+    }
+
+    /// Secret reads denied to `caller` since it was last active
+    pub fn denied(&self, caller: &str) -> usize {
+        self.callers.read().unwrap().get(caller).map_or(0, |reads| reads.denied)
+    }
+
+    /// Callers currently tracked
+    pub fn tracked_callers(&self) -> usize {
+        self.callers.read().unwrap().len()
+    }
+
+    /// Forget callers whose reads have refilled since they were last seen
+    ///
+    /// A forgotten caller starts over with a full burst, as it would have
+    /// had anyway. Also run by [`check`](Self::check) once per refill time.
+    pub fn cleanup(&self) {
+        let idle = self.limit.refill_time();
+        self.callers
+            .write()
+            .unwrap()
+            .retain(|_, reads| reads.last_seen.elapsed() < idle);
+        *self.last_cleanup.lock().unwrap() = std::time::Instant::now();
+    }
+
+    fn cleanup_if_due(&self) {
+        let due = self.last_cleanup.lock().unwrap().elapsed() >= self.limit.refill_time();
+        if due {
+            self.cleanup();
         }
     }
 }
 
 /// Rate limit statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RateLimitStats {
     pub active_limiters: usize,
     pub banned_ips: usize,
     pub total_violations: usize,
+    /// Principals with their own bucket
+    pub active_principal_limiters: usize,
+    /// Requests rejected by principal quotas
+    pub principal_violations: usize,
 }
 
 #[cfg(test)]
@@ -373,6 +841,133 @@ mod tests {
         assert_eq!(stats.active_limiters, 1);
     }
 
+    fn quota(requests_per_second: u32, burst: u32) -> PrincipalQuota {
+        PrincipalQuota {
+            requests_per_second,
+            burst,
+        }
+    }
+
+    #[test]
+    fn test_principal_quota_resolution() {
+        let quotas = PrincipalQuotas {
+            default: Some(quota(5, 5)),
+            roles: HashMap::from([
+                ("viewer".to_string(), quota(10, 10)),
+                ("admin".to_string(), quota(50, 100)),
+            ]),
+            principals: HashMap::from([("batch-job".to_string(), quota(1, 1))]),
+        };
+
+        assert_eq!(quotas.quota_for("alice", &[]), Some(quota(5, 5)));
+        assert_eq!(
+            quotas.quota_for("alice", &["viewer".to_string(), "admin".to_string()]),
+            Some(quota(50, 100))
+        );
+        assert_eq!(
+            quotas.quota_for("batch-job", &["admin".to_string()]),
+            Some(quota(1, 1))
+        );
+        assert_eq!(PrincipalQuotas::default().quota_for("alice", &[]), None);
+    }
+
+    #[test]
+    fn test_per_principal_limiting() {
+        let quotas = PrincipalQuotas {
+            principals: HashMap::from([("noisy".to_string(), quota(1, 3))]),
+            ..Default::default()
+        };
+        let limiter = RateLimiter::new(RateLimitConfig::default()).with_principal_quotas(quotas);
+
+        for _ in 0..3 {
+            assert!(limiter.check_principal("noisy", &[]).is_ok());
+        }
+        assert!(limiter.check_principal("noisy", &[]).is_err());
+
+        // Other principals and the shared buckets are unaffected
+        assert!(limiter.check_principal("quiet", &[]).is_ok());
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        assert!(limiter.check_request(ip, true).is_ok());
+
+        let stats = limiter.get_stats();
+        assert_eq!(stats.active_principal_limiters, 1);
+        assert_eq!(stats.principal_violations, 1);
+    }
+
+    /// Store shared by "replicas" in the tests, counting without refills
+    #[derive(Debug, Default)]
+    struct MemoryStore {
+        buckets: RwLock<HashMap<String, u32>>,
+        violations: RwLock<HashMap<IpAddr, usize>>,
+        bans: RwLock<HashMap<IpAddr, SharedBan>>,
+    }
+
+    impl RateLimitStore for MemoryStore {
+        fn acquire(&self, bucket: &str, _rps: u32, burst: u32) -> SecurityResult<bool> {
+            let mut buckets = self.buckets.write().unwrap();
+            let count = buckets.entry(bucket.to_string()).or_insert(0);
+            *count += 1;
+            Ok(*count <= burst)
+        }
+
+        fn record_violation(&self, ip: IpAddr, _window: Duration) -> SecurityResult<usize> {
+            let mut violations = self.violations.write().unwrap();
+            let count = violations.entry(ip).or_insert(0);
+            *count += 1;
+            Ok(*count)
+        }
+
+        fn ban(&self, ip: IpAddr, ban: &SharedBan, _duration: Duration) -> SecurityResult<()> {
+            self.bans.write().unwrap().insert(ip, ban.clone());
+            Ok(())
+        }
+
+        fn unban(&self, ip: IpAddr) -> SecurityResult<()> {
+            self.bans.write().unwrap().remove(&ip);
+            Ok(())
+        }
+
+        fn is_banned(&self, ip: IpAddr) -> SecurityResult<bool> {
+            Ok(self.bans.read().unwrap().contains_key(&ip))
+        }
+
+        fn bans(&self) -> SecurityResult<Vec<(IpAddr, SharedBan)>> {
+            Ok(self.bans.read().unwrap().iter().map(|(ip, ban)| (*ip, ban.clone())).collect())
+        }
+    }
+
+    #[test]
+    fn test_shared_store() {
+        let store: Arc<MemoryStore> = Arc::default();
+        let config = RateLimitConfig {
+            ban_threshold: 2,
+            ..Default::default()
+        };
+        let replica_a = RateLimiter::new(config.clone()).with_store(store.clone());
+        let replica_b = RateLimiter::new(config).with_store(store.clone());
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        // The per-IP bucket (burst 10) is shared between replicas
+        for _ in 0..5 {
+            assert!(replica_a.check_request(ip, false).is_ok());
+            assert!(replica_b.check_request(ip, false).is_ok());
+        }
+        assert!(replica_a.check_request(ip, false).is_err());
+        assert!(replica_b.check_request(ip, false).is_err());
+
+        // ...and so are the violations and the resulting ban
+        assert!(replica_a.is_banned(ip));
+        assert_eq!(replica_b.get_banned_ips().len(), 1);
+        assert_eq!(replica_b.get_stats().banned_ips, 1);
+        assert_eq!(replica_a.get_stats().total_violations, 1);
+
+        // A "restarted" replica sees the ban until it is lifted
+        let restarted = RateLimiter::new(RateLimitConfig::default()).with_store(store.clone());
+        assert!(restarted.check_request(ip, false).is_err());
+        restarted.unban(ip);
+        assert!(!replica_a.is_banned(ip));
+    }
+
     #[test]
     fn test_cleanup() {
         let limiter = RateLimiter::new(RateLimitConfig::default());
@@ -385,4 +980,70 @@ mod tests {
         // Should still have the limiter as it was just used
         assert_eq!(limiter.get_stats().active_limiters, 1);
     }
+
+    #[test]
+    fn test_secret_access_limiter() {
+        let limiter = SecretAccessLimiter::new(SecretAccessLimit {
+            reads_per_minute: 1,
+            burst: 3,
+        });
+
+        for _ in 0..3 {
+            assert!(limiter.check("alice").is_ok());
+        }
+        assert!(matches!(limiter.check("alice"), Err(SecurityError::RateLimitExceeded(_))));
+        assert!(limiter.check("alice").is_err());
+        assert_eq!(limiter.denied("alice"), 2);
+
+        // Callers are limited independently
+        assert!(limiter.check("bob").is_ok());
+        assert_eq!(limiter.denied("bob"), 0);
+    }
+
+    #[test]
+    fn test_secret_access_limiter_cleanup() {
+        // Refills in one second
+        let limiter = SecretAccessLimiter::new(SecretAccessLimit {
+            reads_per_minute: 60,
+            burst: 1,
+        });
+        assert!(limiter.check("alice").is_ok());
+        assert!(limiter.check("alice").is_err());
+
+        // Active callers are kept
+        limiter.cleanup();
+        assert_eq!(limiter.tracked_callers(), 1);
+        assert_eq!(limiter.denied("alice"), 1);
+
+        thread::sleep(Duration::from_millis(1100));
+        assert!(limiter.check("bob").is_ok());
+        assert_eq!(limiter.tracked_callers(), 1);
+        assert_eq!(limiter.denied("alice"), 0);
+        assert!(limiter.check("alice").is_ok());
+    }
+
+    #[test]
+    fn test_secret_access_limiter_high_rate() {
+        // Refills in half a second, faster than a whole second
+        let limit = SecretAccessLimit {
+            reads_per_minute: 600,
+            burst: 5,
+        };
+        assert_eq!(limit.refill_time(), Duration::from_millis(500));
+        assert_eq!(
+            SecretAccessLimit {
+                reads_per_minute: u32::MAX,
+                burst: 1,
+            }
+            .refill_time(),
+            Duration::from_millis(1)
+        );
+
+        let limiter = SecretAccessLimiter::new(limit);
+        for _ in 0..5 {
+            assert!(limiter.check("alice").is_ok());
+        }
+        assert!(limiter.check("alice").is_err());
+        assert_eq!(limiter.tracked_callers(), 1);
+    }
 }
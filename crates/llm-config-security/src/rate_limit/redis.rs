@@ -0,0 +1,538 @@
+//! Redis-backed rate limiter state shared between API instances
+//!
+//! Buckets are fixed windows of `burst / requests_per_second` seconds that
+//! each allow `burst` requests, counted with `INCR` so replicas never race.
+//! Violations are counters expiring after the violation window and bans are
+//! JSON values expiring with the ban. The client speaks RESP over a single
+//! blocking TCP connection, reconnecting once when a command fails with an IO
+//! error.
+
+use super::{RateLimitStore, SharedBan};
+use crate::errors::{SecurityError, SecurityResult};
+use llm_config_storage::patterns::escape_glob;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Default prefix of every key written by the store
+pub const DEFAULT_KEY_PREFIX: &str = "llm-config:ratelimit:";
+
+/// Number of keys requested per `SCAN` round trip
+const SCAN_COUNT: &str = "500";
+
+/// Redis connection and key settings
+#[derive(Debug, Clone)]
+pub struct RedisRateLimitConfig {
+    /// `redis://[[user]:password@]host[:port][/db]`
+    pub url: String,
+    pub key_prefix: String,
+    /// Connect, read and write timeout
+    pub timeout: Duration,
+}
+
+impl RedisRateLimitConfig {
+    /// Create a configuration for a Redis URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            key_prefix: DEFAULT_KEY_PREFIX.to_string(),
+            timeout: Duration::from_millis(500),
+        }
+    }
+
+    /// Namespace keys, e.g. per deployment
+    pub fn with_key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Set the connect, read and write timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Parsed connection URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RedisUrl {
+    host: String,
+    port: u16,
+    username: Option<String>,
+    password: Option<String>,
+    db: u32,
+}
+
+impl RedisUrl {
+    fn parse(url: &str) -> SecurityResult<Self> {
+        let invalid = |reason: &str| {
+            SecurityError::ConfigError(format!("Invalid Redis URL {}: {}", url, reason))
+        };
+
+        let rest = url
+            .strip_prefix("redis://")
+            .ok_or_else(|| invalid("expected redis:// scheme"))?;
+        let (authority, db) = match rest.split_once('/') {
+            Some((authority, "")) => (authority, 0),
+            Some((authority, db)) => (authority, db.parse().map_err(|_| invalid("bad database"))?),
+            None => (rest, 0),
+        };
+        let (credentials, address) = match authority.rsplit_once('@') {
+            Some((credentials, address)) => (Some(credentials), address),
+            None => (None, authority),
+        };
+        let (username, password) = match credentials.map(|c| c.split_once(':')) {
+            Some(Some((user, password))) => {
+                ((!user.is_empty()).then(|| user.to_string()), Some(password.to_string()))
+            }
+            Some(None) => (None, credentials.map(str::to_string)),
+            None => (None, None),
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid("bad port"))?),
+            None => (address, 6379),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            username,
+            password,
+            db,
+        })
+    }
+}
+
+/// A RESP reply
+#[derive(Debug, Clone, PartialEq)]
+enum Reply {
+    Nil,
+    Status(String),
+    Integer(i64),
+    Bulk(Vec<u8>),
+    Array(Vec<Reply>),
+}
+
+/// Failure of a command: IO errors are retried on a new connection
+#[derive(Debug)]
+enum CommandError {
+    Io(std::io::Error),
+    Redis(String),
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<CommandError> for SecurityError {
+    fn from(e: CommandError) -> Self {
+        match e {
+            CommandError::Io(e) => SecurityError::General(format!("Redis IO error: {}", e)),
+            CommandError::Redis(e) => SecurityError::General(format!("Redis error: {}", e)),
+        }
+    }
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(url: &RedisUrl, timeout: Duration) -> Result<Self, CommandError> {
+        let address = (url.host.as_str(), url.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| CommandError::Redis(format!("Cannot resolve {}", url.host)))?;
+        let stream = TcpStream::connect_timeout(&address, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let mut connection = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+
+        match (&url.username, &url.password) {
+            (Some(user), Some(password)) => {
+                connection.command(&[b"AUTH", user.as_bytes(), password.as_bytes()])?;
+            }
+            (None, Some(password)) => {
+                connection.command(&[b"AUTH", password.as_bytes()])?;
+            }
+            _ => {}
+        }
+        if url.db != 0 {
+            connection.command(&[b"SELECT", url.db.to_string().as_bytes()])?;
+        }
+
+        Ok(connection)
+    }
+
+    fn command(&mut self, args: &[&[u8]]) -> Result<Reply, CommandError> {
+        let mut request = format!("*{}\r\n", args.len()).into_bytes();
+        for arg in args {
+            request.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+            request.extend_from_slice(arg);
+            request.extend_from_slice(b"\r\n");
+        }
+        self.writer.write_all(&request)?;
+
+        read_reply(&mut self.reader)
+    }
+}
+
+fn read_reply(reader: &mut impl BufRead) -> Result<Reply, CommandError> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "Connection closed by Redis",
+        )
+        .into());
+    }
+    let line = line.trim_end_matches("\r\n");
+    let protocol_error = || CommandError::Redis(format!("Unexpected reply: {}", line));
+    let (kind, body) = line.split_at(line.len().min(1));
+
+    match kind {
+        "+" => Ok(Reply::Status(body.to_string())),
+        "-" => Err(CommandError::Redis(body.to_string())),
+        ":" => body.parse().map(Reply::Integer).map_err(|_| protocol_error()),
+        "$" => {
+            let len: i64 = body.parse().map_err(|_| protocol_error())?;
+            if len < 0 {
+                return Ok(Reply::Nil);
+            }
+            let mut data = vec![0; len as usize + 2];
+            reader.read_exact(&mut data)?;
+            data.truncate(len as usize);
+            Ok(Reply::Bulk(data))
+        }
+        "*" => {
+            let len: i64 = body.parse().map_err(|_| protocol_error())?;
+            if len < 0 {
+                return Ok(Reply::Nil);
+            }
+            (0..len)
+                .map(|_| read_reply(reader))
+                .collect::<Result<_, _>>()
+                .map(Reply::Array)
+        }
+        _ => Err(protocol_error()),
+    }
+}
+
+/// Rate limiter state stored in Redis
+pub struct RedisRateLimitStore {
+    config: RedisRateLimitConfig,
+    url: RedisUrl,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl std::fmt::Debug for RedisRateLimitStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisRateLimitStore")
+            .field("host", &self.url.host)
+            .field("port", &self.url.port)
+            .field("key_prefix", &self.config.key_prefix)
+            .finish()
+    }
+}
+
+impl RedisRateLimitStore {
+    /// Connect to Redis
+    pub fn new(config: RedisRateLimitConfig) -> SecurityResult<Self> {
+        let url = RedisUrl::parse(&config.url)?;
+        let connection = Connection::open(&url, config.timeout)?;
+
+        Ok(Self {
+            config,
+            url,
+            connection: Mutex::new(Some(connection)),
+        })
+    }
+
+    fn redis_key(&self, kind: &str, name: &str) -> String {
+        format!("{}{}:{}", self.config.key_prefix, kind, name)
+    }
+
+    /// Run a command, reconnecting once if the connection was lost
+    fn command(&self, args: &[&[u8]]) -> SecurityResult<Reply> {
+        let mut connection = self.connection.lock().unwrap();
+
+        if let Some(conn) = connection.as_mut() {
+            match conn.command(args) {
+                Err(CommandError::Io(_)) => {}
+                result => return result.map_err(Into::into),
+            }
+        }
+
+        *connection = None;
+        let conn = connection.insert(Connection::open(&self.url, self.config.timeout)?);
+        conn.command(args).map_err(Into::into)
+    }
+
+    /// Increment a counter, starting its expiry with the first increment
+    fn increment(&self, key: &str, expiry: Duration) -> SecurityResult<i64> {
+        let count = match self.command(&[b"INCR", key.as_bytes()])? {
+            Reply::Integer(count) => count,
+            _ => return Err(SecurityError::General("Unexpected INCR reply".to_string())),
+        };
+        if count == 1 {
+            let millis = expiry.as_millis().max(1).to_string();
+            self.command(&[b"PEXPIRE", key.as_bytes(), millis.as_bytes()])?;
+        }
+        Ok(count)
+    }
+
+    /// All keys starting with `prefix`
+    fn scan_keys(&self, prefix: &str) -> SecurityResult<Vec<Vec<u8>>> {
+        let pattern = format!("{}*", escape_glob(prefix));
+        let mut cursor = b"0".to_vec();
+        let mut keys = Vec::new();
+        let unexpected = || SecurityError::General("Unexpected SCAN reply".to_string());
+
+        loop {
+            let reply = self.command(&[
+                b"SCAN",
+                &cursor,
+                b"MATCH",
+                pattern.as_bytes(),
+                b"COUNT",
+                SCAN_COUNT.as_bytes(),
+            ])?;
+            let Reply::Array(mut parts) = reply else {
+                return Err(unexpected());
+            };
+            if parts.len() != 2 {
+                return Err(unexpected());
+            }
+            if let Reply::Array(batch) = parts.pop().unwrap() {
+                keys.extend(batch.into_iter().filter_map(|key| match key {
+                    Reply::Bulk(key) => Some(key),
+                    _ => None,
+                }));
+            }
+            cursor = match parts.pop().unwrap() {
+                Reply::Bulk(next) => next,
+                _ => return Err(unexpected()),
+            };
+            if cursor == b"0" {
+                return Ok(keys);
+            }
+        }
+    }
+}
+
+impl RateLimitStore for RedisRateLimitStore {
+    fn acquire(&self, bucket: &str, requests_per_second: u32, burst: u32) -> SecurityResult<bool> {
+        let rps = u64::from(requests_per_second.max(1));
+        let window_ms = (u64::from(burst.max(1)) * 1000).div_ceil(rps).max(1);
+        let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+
+        let key = self.redis_key("bucket", &format!("{}:{}", bucket, now_ms / window_ms));
+        let count = self.increment(&key, Duration::from_millis(window_ms * 2))?;
+        Ok(count <= i64::from(burst))
+    }
+
+    fn record_violation(&self, ip: IpAddr, window: Duration) -> SecurityResult<usize> {
+        let key = self.redis_key("violations", &ip.to_string());
+        Ok(self.increment(&key, window)?.max(0) as usize)
+    }
+
+    fn ban(&self, ip: IpAddr, ban: &SharedBan, duration: Duration) -> SecurityResult<()> {
+        let data = serde_json::to_vec(ban).map_err(|e| SecurityError::General(e.to_string()))?;
+        let key = self.redis_key("ban", &ip.to_string());
+        let millis = duration.as_millis().max(1).to_string();
+        self.command(&[b"SET", key.as_bytes(), &data, b"PX", millis.as_bytes()])?;
+        Ok(())
+    }
+
+    fn unban(&self, ip: IpAddr) -> SecurityResult<()> {
+        let key = self.redis_key("ban", &ip.to_string());
+        self.command(&[b"DEL", key.as_bytes()])?;
+        Ok(())
+    }
+
+    fn is_banned(&self, ip: IpAddr) -> SecurityResult<bool> {
+        let key = self.redis_key("ban", &ip.to_string());
+        Ok(matches!(self.command(&[b"EXISTS", key.as_bytes()])?, Reply::Integer(n) if n > 0))
+    }
+
+    fn bans(&self) -> SecurityResult<Vec<(IpAddr, SharedBan)>> {
+        let prefix = self.redis_key("ban", "");
+        let mut bans = Vec::new();
+
+        for key in self.scan_keys(&prefix)? {
+            let ip = String::from_utf8_lossy(&key[prefix.len()..]).parse::<IpAddr>();
+            // Bans may expire between SCAN and GET
+            if let (Ok(ip), Reply::Bulk(data)) = (ip, self.command(&[b"GET", &key])?) {
+                if let Ok(ban) = serde_json::from_slice(&data) {
+                    bans.push((ip, ban));
+                }
+            }
+        }
+        Ok(bans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limit::{RateLimitConfig, RateLimiter};
+    use std::collections::BTreeMap;
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::sync::Arc;
+
+    /// Minimal in-memory Redis speaking the commands the store uses
+    fn fake_redis() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<u8>>>> = Arc::default();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let data = Arc::clone(&data);
+                std::thread::spawn(move || serve(stream, data));
+            }
+        });
+
+        format!("redis://:secret@{}/1", address)
+    }
+
+    fn serve(stream: TcpStream, data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<u8>>>>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+
+        while let Ok(Reply::Array(args)) = read_reply(&mut reader) {
+            let args: Vec<Vec<u8>> = args
+                .into_iter()
+                .map(|arg| match arg {
+                    Reply::Bulk(arg) => arg,
+                    other => panic!("unexpected argument {:?}", other),
+                })
+                .collect();
+            let mut data = data.lock().unwrap();
+
+            let bulk = |value: &[u8]| {
+                let mut out = format!("${}\r\n", value.len()).into_bytes();
+                out.extend_from_slice(value);
+                out.extend_from_slice(b"\r\n");
+                out
+            };
+            let response = match args[0].as_slice() {
+                b"AUTH" if args[1] == b"secret" => b"+OK\r\n".to_vec(),
+                b"AUTH" => b"-WRONGPASS invalid password\r\n".to_vec(),
+                b"SELECT" | b"PEXPIRE" => b"+OK\r\n".to_vec(),
+                b"INCR" => {
+                    let value = data.entry(args[1].clone()).or_insert_with(|| b"0".to_vec());
+                    let count: i64 = String::from_utf8_lossy(value).parse::<i64>().unwrap() + 1;
+                    *value = count.to_string().into_bytes();
+                    format!(":{}\r\n", count).into_bytes()
+                }
+                b"GET" => match data.get(&args[1]) {
+                    Some(value) => bulk(value),
+                    None => b"$-1\r\n".to_vec(),
+                },
+                b"SET" => {
+                    assert_eq!(args[3], b"PX");
+                    data.insert(args[1].clone(), args[2].clone());
+                    b"+OK\r\n".to_vec()
+                }
+                b"EXISTS" => format!(":{}\r\n", data.contains_key(&args[1]) as i64).into_bytes(),
+                b"DEL" => {
+                    let removed = args[1..].iter().filter(|key| data.remove(*key).is_some()).count();
+                    format!(":{}\r\n", removed).into_bytes()
+                }
+                b"SCAN" => {
+                    let prefix = args[3].strip_suffix(b"*").unwrap();
+                    let keys: Vec<&Vec<u8>> =
+                        data.keys().filter(|key| key.starts_with(prefix)).collect();
+                    let mut out = format!("*2\r\n$1\r\n0\r\n*{}\r\n", keys.len()).into_bytes();
+                    for key in keys {
+                        out.extend(bulk(key));
+                    }
+                    out
+                }
+                other => format!("-ERR unknown command {}\r\n", String::from_utf8_lossy(other))
+                    .into_bytes(),
+            };
+            if writer.write_all(&response).is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            RedisUrl::parse("redis://user:pw@limits.internal:6380/3").unwrap(),
+            RedisUrl {
+                host: "limits.internal".to_string(),
+                port: 6380,
+                username: Some("user".to_string()),
+                password: Some("pw".to_string()),
+                db: 3,
+            }
+        );
+        assert!(RedisUrl::parse("http://localhost").is_err());
+    }
+
+    #[test]
+    fn test_redis_store() {
+        let url = fake_redis();
+        let store = RedisRateLimitStore::new(RedisRateLimitConfig::new(&url)).unwrap();
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        assert!(store.acquire("ip:10.0.0.1", 1, 2).unwrap());
+        assert!(store.acquire("ip:10.0.0.1", 1, 2).unwrap());
+        assert!(!store.acquire("ip:10.0.0.1", 1, 2).unwrap() || {
+            // The window rolled over between requests
+            store.acquire("ip:10.0.0.1", 1, 2).unwrap() && !store.acquire("ip:10.0.0.1", 1, 2).unwrap()
+        });
+
+        assert_eq!(store.record_violation(ip, Duration::from_secs(60)).unwrap(), 1);
+        assert_eq!(store.record_violation(ip, Duration::from_secs(60)).unwrap(), 2);
+
+        let ban = SharedBan {
+            banned_at: chrono::Utc::now(),
+            reason: "test".to_string(),
+            violations: 2,
+        };
+        store.ban(ip, &ban, Duration::from_secs(60)).unwrap();
+        assert!(store.is_banned(ip).unwrap());
+        assert_eq!(store.bans().unwrap(), vec![(ip, ban)]);
+
+        store.unban(ip).unwrap();
+        assert!(!store.is_banned(ip).unwrap());
+
+        assert!(RedisRateLimitStore::new(RedisRateLimitConfig::new(url.replace("secret", "wrong")))
+            .is_err());
+    }
+
+    #[test]
+    fn test_bans_survive_restart() {
+        let url = fake_redis();
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let limiter = |url: &str| {
+            let store = RedisRateLimitStore::new(RedisRateLimitConfig::new(url)).unwrap();
+            RateLimiter::new(RateLimitConfig::default()).with_store(Arc::new(store))
+        };
+
+        limiter(&url).ban(ip, "manual".to_string());
+
+        let restarted = limiter(&url);
+        assert!(restarted.check_request(ip, false).is_err());
+        let banned = restarted.get_banned_ips();
+        assert_eq!(banned.len(), 1);
+        assert_eq!(banned[0].1.reason, "manual");
+    }
+}
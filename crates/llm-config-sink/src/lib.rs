@@ -0,0 +1,95 @@
+//! Metrics facade for LLM Config Manager
+//!
+//! Crates that record metrics (storage, the config manager, the cache) do so
+//! through [`MetricsSink`], so they do not depend on the Prometheus-based
+//! `llm-config-metrics` crate, whose `MetricsRegistry` implements it. They
+//! hold an optional `Arc<dyn MetricsSink>` and record nothing without one.
+//!
+//! Metrics are named like Prometheus metrics (`storage_operations_total`)
+//! and labelled with name-value pairs. Recording never fails: a sink drops
+//! samples it cannot record.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Receiver of counter increments, histogram observations and gauge values
+pub trait MetricsSink: Send + Sync {
+    /// Add `value` (not negative) to the counter `name` of the series with `labels`
+    fn record_counter(&self, name: &str, labels: &[(&str, &str)], value: f64);
+
+    /// Observe `value` with the histogram `name`
+    fn observe(&self, name: &str, labels: &[(&str, &str)], value: f64);
+
+    /// Set the gauge `name` to `value`
+    fn gauge(&self, name: &str, labels: &[(&str, &str)], value: f64);
+}
+
+/// Metric name and label pairs sorted by name
+type Series = (String, Vec<(String, String)>);
+
+/// Sink keeping everything recorded in memory, for tests
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    counters: Mutex<BTreeMap<Series, f64>>,
+    observations: Mutex<BTreeMap<Series, Vec<f64>>>,
+    gauges: Mutex<BTreeMap<Series, f64>>,
+}
+
+impl MemorySink {
+    /// Total recorded for a counter series (0 if nothing was)
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> f64 {
+        self.counters.lock().unwrap().get(&series(name, labels)).copied().unwrap_or(0.0)
+    }
+
+    /// Values observed for a histogram series, oldest first
+    pub fn observations(&self, name: &str, labels: &[(&str, &str)]) -> Vec<f64> {
+        self.observations.lock().unwrap().get(&series(name, labels)).cloned().unwrap_or_default()
+    }
+
+    /// Last value set for a gauge series
+    pub fn gauge_value(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        self.gauges.lock().unwrap().get(&series(name, labels)).copied()
+    }
+}
+
+impl MetricsSink for MemorySink {
+    fn record_counter(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        *self.counters.lock().unwrap().entry(series(name, labels)).or_default() += value;
+    }
+
+    fn observe(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.observations.lock().unwrap().entry(series(name, labels)).or_default().push(value);
+    }
+
+    fn gauge(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.gauges.lock().unwrap().insert(series(name, labels), value);
+    }
+}
+
+fn series(name: &str, labels: &[(&str, &str)]) -> Series {
+    let mut labels: Vec<_> = labels.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+    labels.sort();
+    (name.to_string(), labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_sink() {
+        let sink = MemorySink::default();
+        sink.record_counter("ops_total", &[("operation", "set"), ("tier", "l1")], 1.0);
+        sink.record_counter("ops_total", &[("tier", "l1"), ("operation", "set")], 2.0);
+        sink.observe("duration_seconds", &[], 0.5);
+        sink.observe("duration_seconds", &[], 0.25);
+        sink.gauge("size", &[("tier", "l1")], 3.0);
+        sink.gauge("size", &[("tier", "l1")], 2.0);
+
+        assert_eq!(sink.counter("ops_total", &[("operation", "set"), ("tier", "l1")]), 3.0);
+        assert_eq!(sink.counter("ops_total", &[("operation", "get"), ("tier", "l1")]), 0.0);
+        assert_eq!(sink.observations("duration_seconds", &[]), vec![0.5, 0.25]);
+        assert_eq!(sink.gauge_value("size", &[("tier", "l1")]), Some(2.0));
+        assert_eq!(sink.gauge_value("size", &[("tier", "l2")]), None);
+    }
+}
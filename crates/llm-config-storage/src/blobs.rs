@@ -0,0 +1,294 @@
+//! Content-addressed storage of large values
+//!
+//! With deduplication enabled (see [`FileStorage::set_dedup`]), values whose
+//! JSON is at least the configured size are written once to
+//! `blobs/<xx>/<sha256>.json`, and config and version files refer to them by
+//! hash (`value_blob` in place of `value`). Identical values in several
+//! environments, their version history and rollbacks to an earlier value
+//! share one blob, which is not written again.
+//!
+//! Every blob has a reference count next to it (`<sha256>.refs`). Counts are
+//! raised before a file referring to the blob is written and lowered after
+//! such a file is replaced or removed, and a blob is deleted when its count
+//! drops to zero. An interrupted write can therefore only leave a count too
+//! high; [`FileStorage::compact`] recounts the references of every file and
+//! removes blobs nobody refers to.
+//!
+//! Files are always read with their blobs resolved, so turning deduplication
+//! off only affects new writes.
+//!
+//! [`FileStorage::set_dedup`]: crate::file::FileStorage::set_dedup
+//! [`FileStorage::compact`]: crate::file::FileStorage::compact
+
+use crate::{Result, StorageError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Directory of the blob store, relative to the storage directory
+pub(crate) const BLOB_DIR: &str = "blobs";
+
+/// Storage record kind and id the deduplication settings are persisted under
+pub(crate) const DEDUP_RECORD: (&str, &str) = ("settings", "dedup");
+
+/// Smallest value moved into a blob by default (in bytes of JSON)
+pub const DEFAULT_DEDUP_MIN_SIZE: usize = 1024;
+
+/// Field of config and version files holding the hash of their value's blob
+const BLOB_FIELD: &str = "value_blob";
+
+/// Persisted deduplication settings of a store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DedupSettings {
+    /// Values whose JSON is at least this many bytes are stored as blobs
+    pub min_size: usize,
+}
+
+/// Usage of the blob store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobStats {
+    pub blobs: usize,
+    /// Config and version files referring to a blob
+    pub references: usize,
+    /// Size of all blobs
+    pub bytes: u64,
+    /// Bytes the references would take up if every file held its value
+    pub bytes_saved: u64,
+}
+
+/// Blob files, reference counts and the size threshold shared by clones of a storage
+#[derive(Clone)]
+pub(crate) struct BlobStore {
+    dir: PathBuf,
+    min_size: Arc<RwLock<Option<usize>>>,
+    /// Serializes reference count updates of this process
+    counts: Arc<Mutex<()>>,
+}
+
+impl BlobStore {
+    pub fn new(base_path: &Path) -> Self {
+        Self {
+            dir: base_path.join(BLOB_DIR),
+            min_size: Arc::new(RwLock::new(None)),
+            counts: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Size threshold for new blobs (`None` while deduplication is off)
+    pub fn min_size(&self) -> Option<usize> {
+        *self.min_size.read().unwrap()
+    }
+
+    pub fn set_min_size(&self, min_size: Option<usize>) {
+        *self.min_size.write().unwrap() = min_size;
+    }
+
+    /// Serialize a config or version entry for its file, moving its value
+    /// into a blob if it is large enough
+    pub fn serialize<T: Serialize>(&self, record: &T) -> Result<String> {
+        let mut json = serde_json::to_value(record).map_err(serialization_error)?;
+        if let Some(min_size) = self.min_size() {
+            self.externalize(&mut json, min_size)?;
+        }
+        serde_json::to_string_pretty(&json).map_err(serialization_error)
+    }
+
+    /// Move the value of a serialized entry into a blob if it is at least
+    /// `min_size` bytes; returns whether it was moved
+    pub fn externalize(&self, json: &mut Value, min_size: usize) -> Result<bool> {
+        let Some(fields) = json.as_object_mut() else {
+            return Ok(false);
+        };
+        let Some(value) = fields.get("value") else {
+            return Ok(false);
+        };
+        let text = serde_json::to_string(value).map_err(serialization_error)?;
+        if text.len() < min_size {
+            return Ok(false);
+        }
+
+        let hash = self.put(&text)?;
+        fields.remove("value");
+        fields.insert(BLOB_FIELD.to_string(), Value::String(hash));
+        Ok(true)
+    }
+
+    /// Parse a config or version file, reading its value from its blob
+    pub fn parse<T: DeserializeOwned>(&self, contents: &str) -> Result<T> {
+        let mut json: Value = serde_json::from_str(contents).map_err(serialization_error)?;
+        if let Some(hash) = blob_ref(&json) {
+            let value = self.get(&hash)?;
+            if let Some(fields) = json.as_object_mut() {
+                fields.remove(BLOB_FIELD);
+                fields.insert("value".to_string(), value);
+            }
+        }
+        serde_json::from_value(json).map_err(serialization_error)
+    }
+
+    /// Remove a config or version file and drop its blob reference
+    pub fn remove_file(&self, path: &Path) -> Result<()> {
+        let hash = file_ref(path);
+        fs::remove_file(path)?;
+        if let Some(hash) = hash {
+            self.release(&hash)?;
+        }
+        Ok(())
+    }
+
+    /// Drop a reference to a blob, deleting the blob with its last reference
+    pub fn release(&self, hash: &str) -> Result<()> {
+        let _counts = self.counts.lock().unwrap();
+        let (blob, refs) = self.paths(hash)?;
+        match read_count(&refs).saturating_sub(1) {
+            0 => {
+                for path in [&blob, &refs] {
+                    if path.exists() {
+                        fs::remove_file(path)?;
+                    }
+                }
+            }
+            count => write_atomically(&refs, count.to_string().as_bytes())?,
+        }
+        Ok(())
+    }
+
+    /// Set every blob's count to `references` (hash -> files referring to
+    /// it) and delete blobs without references
+    ///
+    /// Returns the number of deleted blobs and of corrected counts.
+    pub fn collect(&self, references: &HashMap<String, usize>) -> Result<(usize, usize)> {
+        let _counts = self.counts.lock().unwrap();
+        let (mut removed, mut repaired) = (0, 0);
+        for (hash, blob, refs) in self.blobs()? {
+            match references.get(&hash).copied().unwrap_or(0) {
+                0 => {
+                    fs::remove_file(&blob)?;
+                    if refs.exists() {
+                        fs::remove_file(&refs)?;
+                    }
+                    removed += 1;
+                }
+                count if count != read_count(&refs) => {
+                    write_atomically(&refs, count.to_string().as_bytes())?;
+                    repaired += 1;
+                }
+                _ => {}
+            }
+        }
+        Ok((removed, repaired))
+    }
+
+    /// Usage of the blob store by their reference counts
+    pub fn stats(&self) -> Result<BlobStats> {
+        let mut stats = BlobStats::default();
+        for (_, blob, refs) in self.blobs()? {
+            let size = fs::metadata(&blob)?.len();
+            let count = read_count(&refs);
+            stats.blobs += 1;
+            stats.references += count;
+            stats.bytes += size;
+            stats.bytes_saved += size * count.saturating_sub(1) as u64;
+        }
+        Ok(stats)
+    }
+
+    /// Write a blob unless it exists and count a reference to it
+    fn put(&self, text: &str) -> Result<String> {
+        let hash = hex::encode(Sha256::digest(text.as_bytes()));
+        let _counts = self.counts.lock().unwrap();
+        let (blob, refs) = self.paths(&hash)?;
+        if !blob.exists() {
+            if let Some(dir) = blob.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            write_atomically(&blob, text.as_bytes())?;
+        }
+        write_atomically(&refs, (read_count(&refs) + 1).to_string().as_bytes())?;
+        Ok(hash)
+    }
+
+    /// Read the value of a blob
+    fn get(&self, hash: &str) -> Result<Value> {
+        let (blob, _) = self.paths(hash)?;
+        let contents = fs::read_to_string(&blob)
+            .map_err(|e| StorageError::NotFound(format!("blob {}: {}", hash, e)))?;
+        serde_json::from_str(&contents).map_err(serialization_error)
+    }
+
+    /// Blob file and reference count file of a hash
+    fn paths(&self, hash: &str) -> Result<(PathBuf, PathBuf)> {
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(StorageError::InvalidPath(format!("blob {}", hash)));
+        }
+        let dir = self.dir.join(&hash[..2]);
+        Ok((dir.join(format!("{}.json", hash)), dir.join(format!("{}.refs", hash))))
+    }
+
+    /// Hash, blob file and reference count file of every blob
+    fn blobs(&self) -> Result<Vec<(String, PathBuf, PathBuf)>> {
+        let mut blobs = Vec::new();
+        if !self.dir.exists() {
+            return Ok(blobs);
+        }
+        for shard in fs::read_dir(&self.dir)? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&shard)? {
+                let path = entry?.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                if let Some(hash) = path.file_stem().and_then(|s| s.to_str()) {
+                    let hash = hash.to_string();
+                    let refs = path.with_extension("refs");
+                    blobs.push((hash, path, refs));
+                }
+            }
+        }
+        blobs.sort();
+        Ok(blobs)
+    }
+}
+
+/// Hash of the blob a serialized entry refers to
+pub(crate) fn blob_ref(json: &Value) -> Option<String> {
+    json.get(BLOB_FIELD)?.as_str().map(str::to_string)
+}
+
+/// Hash of the blob a config or version file refers to, without reading the blob
+pub(crate) fn file_ref(path: &Path) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    blob_ref(&serde_json::from_str(&contents).ok()?)
+}
+
+/// Reference count in a count file (0 if it is missing)
+fn read_count(path: &Path) -> usize {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Write a file through a temporary file and a rename
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let temp_path = path.with_extension("tmp");
+    {
+        let mut temp_file = File::create(&temp_path)?;
+        temp_file.write_all(contents)?;
+        temp_file.sync_all()?;
+    }
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+fn serialization_error(e: serde_json::Error) -> StorageError {
+    StorageError::SerializationError(e.to_string())
+}
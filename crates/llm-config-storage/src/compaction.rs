@@ -0,0 +1,31 @@
+//! Compaction report for the file storage backend
+//!
+//! See [`FileStorage::compact`](crate::file::FileStorage::compact).
+
+use serde::{Deserialize, Serialize};
+
+/// Result of compacting a store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionReport {
+    /// Configs in the saved index snapshot
+    pub configs: usize,
+    /// Journal entries folded into the snapshot
+    pub journal_entries: usize,
+    /// Leftover temporary files of interrupted writes that were removed
+    pub temp_files_removed: usize,
+    /// History directories without versions that were removed
+    pub empty_dirs_removed: usize,
+    /// Version files rewritten under their canonical name
+    pub version_files_rewritten: usize,
+    /// Version files that could not be read and were left in place
+    pub unreadable_version_files: Vec<String>,
+    /// Config and version files whose value was moved into a blob
+    #[serde(default)]
+    pub values_moved_to_blobs: usize,
+    /// Blobs without references that were deleted
+    #[serde(default)]
+    pub blobs_removed: usize,
+    /// Blob reference counts that were corrected
+    #[serde(default)]
+    pub blob_counts_repaired: usize,
+}
@@ -0,0 +1,136 @@
+//! CSV file format
+//!
+//! Comma-separated rows as in RFC 4180: fields containing a comma, quote or
+//! line break are double-quoted, with quotes doubled. Both `\n` and `\r\n`
+//! line endings are accepted; [`render_row`] quotes fields so that [`parse`]
+//! returns them unchanged.
+
+use crate::{Result, StorageError};
+
+/// Parse CSV content into rows of fields, skipping blank lines
+pub fn parse(content: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                let start = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => {
+                            return Err(StorageError::SerializationError(format!(
+                                "line {}: unterminated \"",
+                                start
+                            )))
+                        }
+                    }
+                }
+                if !matches!(chars.peek(), None | Some(',' | '\r' | '\n')) {
+                    return Err(StorageError::SerializationError(format!(
+                        "line {}: unexpected characters after closing quote",
+                        line
+                    )));
+                }
+                // Keep quoted empty fields apart from a missing one
+                row.push(std::mem::take(&mut field));
+                if chars.peek() == Some(&',') {
+                    chars.next();
+                    if matches!(chars.peek(), None | Some('\r' | '\n')) {
+                        row.push(String::new());
+                    }
+                }
+            }
+            ',' => {
+                row.push(std::mem::take(&mut field));
+                if matches!(chars.peek(), None | Some('\r' | '\n')) {
+                    row.push(String::new());
+                }
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                end_row(&mut rows, &mut row, &mut field);
+                line += 1;
+            }
+            other => field.push(other),
+        }
+    }
+    end_row(&mut rows, &mut row, &mut field);
+
+    Ok(rows)
+}
+
+/// Finish the current row, unless it is blank
+fn end_row(rows: &mut Vec<Vec<String>>, row: &mut Vec<String>, field: &mut String) {
+    if !field.is_empty() {
+        row.push(std::mem::take(field));
+    }
+    if !row.is_empty() {
+        rows.push(std::mem::take(row));
+    }
+}
+
+/// Render one row as a CSV line
+pub fn render_row<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+    let mut line = fields.into_iter().map(quote).collect::<Vec<_>>().join(",");
+    line.push('\n');
+    line
+}
+
+/// Quote a field only if needed
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let content = "key,type,value\r\nmodel,string,gpt-4\n\n\"a,b\",string,\"say \"\"hi\"\"\nbye\"\nempty,string,\n";
+        let rows = parse(content).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["key", "type", "value"],
+                vec!["model", "string", "gpt-4"],
+                vec!["a,b", "string", "say \"hi\"\nbye"],
+                vec!["empty", "string", ""],
+            ]
+        );
+
+        assert!(parse("a,\"unterminated").is_err());
+        assert!(parse("\"quoted\"x,y").is_err());
+    }
+
+    #[test]
+    fn test_render_roundtrip() {
+        let rows = vec![
+            vec!["plain", "two words"],
+            vec!["comma,separated", "\"quoted\""],
+            vec!["multi\r\nline", ""],
+        ];
+        let rendered: String = rows.iter().map(|row| render_row(row.iter().copied())).collect();
+        assert!(rendered.starts_with("plain,two words\n"));
+        assert_eq!(parse(&rendered).unwrap(), rows);
+    }
+}
@@ -0,0 +1,176 @@
+//! dotenv (`.env`) file format
+//!
+//! Flat `KEY=VALUE` lines. Blank lines, `#` comments and an optional
+//! `export ` prefix are ignored. Values may be bare (a trailing ` # comment`
+//! is stripped), single-quoted (literal) or double-quoted (with `\n`, `\r`,
+//! `\t`, `\"`, `\\` and `\$` escapes). [`render`] quotes values so that
+//! [`parse`] returns them unchanged.
+
+use crate::{Result, StorageError};
+
+/// Whether `key` is a valid dotenv variable name
+///
+/// Letters, digits, `_` and `.`, not starting with a digit.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.starts_with(|c: char| c.is_ascii_digit())
+        && !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Parse dotenv content into `(key, value)` pairs, in file order
+pub fn parse(content: &str) -> Result<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let invalid = |reason: &str| {
+            StorageError::SerializationError(format!("line {}: {}", index + 1, reason))
+        };
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid("expected KEY=VALUE"))?;
+        let key = key.trim();
+        if !is_valid_key(key) {
+            return Err(invalid(&format!("invalid key {:?}", key)));
+        }
+
+        let value = value.trim_start();
+        let (value, rest) = match value.chars().next() {
+            Some('"') => parse_double_quoted(&value[1..]).ok_or_else(|| invalid("unterminated \""))?,
+            Some('\'') => {
+                let end = value[1..].find('\'').ok_or_else(|| invalid("unterminated '"))?;
+                (value[1..=end].to_string(), &value[end + 2..])
+            }
+            _ => {
+                let end = value.find(" #").unwrap_or(value.len());
+                (value[..end].trim_end().to_string(), "")
+            }
+        };
+
+        let rest = rest.trim_start();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return Err(invalid("unexpected characters after closing quote"));
+        }
+
+        pairs.push((key.to_string(), value));
+    }
+
+    Ok(pairs)
+}
+
+/// Parse the rest of a double-quoted value, returning it and what follows the closing quote
+fn parse_double_quoted(s: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &s[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                other => value.push(other),
+            },
+            other => value.push(other),
+        }
+    }
+
+    None
+}
+
+/// Render `(key, value)` pairs as dotenv lines
+pub fn render<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    pairs
+        .into_iter()
+        .map(|(key, value)| format!("{}={}\n", key, quote(value)))
+        .collect()
+}
+
+/// Quote a value only as much as needed
+fn quote(value: &str) -> String {
+    let bare = |c: char| c.is_ascii_alphanumeric() || "-_./:@+,=".contains(c);
+    if value.chars().all(bare) {
+        return value.to_string();
+    }
+    if !value.contains(['\'', '\n', '\r']) {
+        return format!("'{}'", value);
+    }
+
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            other => quoted.push(other),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+# Database
+export DB_HOST=localhost
+DB_PORT = 5432 # inline comment
+GREETING="Hello\n\"world\""
+LITERAL='no $expansion # here'
+EMPTY=
+"#;
+        let pairs = parse(content).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("DB_HOST".to_string(), "localhost".to_string()),
+                ("DB_PORT".to_string(), "5432".to_string()),
+                ("GREETING".to_string(), "Hello\n\"world\"".to_string()),
+                ("LITERAL".to_string(), "no $expansion # here".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+
+        assert!(parse("NO_EQUALS").is_err());
+        assert!(parse("1BAD=x").is_err());
+        assert!(parse("OPEN=\"unterminated").is_err());
+        assert!(parse("TRAILING='x' y").is_err());
+    }
+
+    #[test]
+    fn test_render_roundtrip() {
+        let values = [
+            ("PLAIN", "gpt-4"),
+            ("SPACES", "two words # not a comment"),
+            ("QUOTES", "it's \"quoted\""),
+            ("MULTILINE", "line one\nline two\ttab $HOME \\"),
+            ("JSON", r#"{"a":[1,2]}"#),
+            ("EMPTY", ""),
+        ];
+
+        let rendered = render(values);
+        assert!(rendered.starts_with("PLAIN=gpt-4\n"));
+
+        let parsed = parse(&rendered).unwrap();
+        let expected: Vec<_> = values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(parsed, expected);
+    }
+}
@@ -1,19 +1,50 @@
 //! File-based storage backend with atomic operations
-
-use crate::{ConfigEntry, Environment, Result, StorageError, VersionEntry};
+//!
+//! Files are sharded by a hash of their namespace so no directory grows with
+//! the size of the store: configs live in `configs/<shard>/`, and version
+//! history in one directory per config under `versions/<shard>/<namespace>/`,
+//! with file names ordered by version and creation time, so a page of
+//! history is read without touching the history of other configs. Stores
+//! written with the earlier flat layouts are migrated when opened.
+//!
+//! The in-memory index is persisted as a snapshot plus a journal of changed
+//! config files (see [`crate::index`]), so opening a store only reads the
+//! config files changed since the snapshot. Writes of a config together
+//! with its version go through a write-ahead journal (see [`crate::wal`]).
+//! Large values can be stored once per content (see [`crate::blobs`]).
+
+use crate::blobs::{self, BlobStats, BlobStore, DedupSettings, BLOB_DIR, DEDUP_RECORD};
+use crate::compaction::CompactionReport;
+use crate::fsck::{FsckIssueKind, FsckReport};
+use crate::index::{IndexSnapshot, Journal, INDEX_SNAPSHOT_FILE};
+use crate::retention::{RetentionConfig, RetentionReport};
+use crate::wal::{WalRecord, WAL_DIR};
+use crate::{
+    ConfigEntry, Environment, HistoryOptions, Result, RetentionPolicy, StorageError, VersionEntry,
+};
+use chrono::{DateTime, Utc};
+use llm_config_sink::MetricsSink;
+use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use uuid::Uuid;
 
+/// File in a config's history directory recording the newest pruned version
+const PRUNED_FILE: &str = "pruned";
+
 /// File-based storage backend
 #[derive(Clone)]
 pub struct FileStorage {
     base_path: PathBuf,
     /// In-memory index for fast lookups
     index: Arc<RwLock<HashMap<String, ConfigEntry>>>,
+    blobs: BlobStore,
+    /// Sink of operation metrics, shared by clones
+    metrics: Arc<RwLock<Option<Arc<dyn MetricsSink>>>>,
 }
 
 impl FileStorage {
@@ -25,37 +56,128 @@ impl FileStorage {
         fs::create_dir_all(&base_path)?;
         fs::create_dir_all(base_path.join("configs"))?;
         fs::create_dir_all(base_path.join("versions"))?;
+        fs::create_dir_all(base_path.join(WAL_DIR))?;
 
         let storage = Self {
+            blobs: BlobStore::new(&base_path),
             base_path,
             index: Arc::new(RwLock::new(HashMap::new())),
+            metrics: Arc::new(RwLock::new(None)),
         };
+        let (kind, id) = DEDUP_RECORD;
+        if let Some(settings) = storage.get_record::<DedupSettings>(kind, id)? {
+            storage.blobs.set_min_size(Some(settings.min_size));
+        }
 
         // Load existing configs into index
-        storage.rebuild_index()?;
+        storage.migrate_layout()?;
+        storage.migrate_versions()?;
+        storage.recover_writes()?;
+        storage.load_index()?;
 
         Ok(storage)
     }
 
+    /// Load the index from its snapshot and journal, or rebuild it from disk
+    ///
+    /// A new snapshot is saved whenever the journal had changes to replay.
+    fn load_index(&self) -> Result<()> {
+        let journal = Journal::read(&self.base_path)?;
+        let mut up_to_date = false;
+        let mut loaded = false;
+
+        if let Some(snapshot) = IndexSnapshot::read(&self.base_path) {
+            let offset = if snapshot.journal_id == journal.id && snapshot.journal_offset <= journal.len {
+                snapshot.journal_offset
+            } else {
+                0
+            };
+            let mut index = self.index.write().unwrap();
+            *index = snapshot
+                .entries
+                .into_iter()
+                .map(|config| (self.make_key(&config.namespace, &config.key, config.environment), config))
+                .collect();
+            let changed = journal.files_since(offset);
+            self.replay(&mut index, &changed);
+
+            // Files created or removed without a journal entry
+            loaded = self.config_file_count()? == index.len();
+            up_to_date = loaded && changed.is_empty() && offset == journal.len;
+        }
+
+        if !loaded {
+            self.rebuild_index()?;
+        }
+        if !up_to_date {
+            self.save_index(&self.index.read().unwrap(), &journal)?;
+        }
+        Ok(())
+    }
+
+    /// Re-read the config files named in the journal
+    fn replay(&self, index: &mut HashMap<String, ConfigEntry>, files: &[&str]) {
+        if files.is_empty() {
+            return;
+        }
+
+        let mut by_file: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, config) in index.iter() {
+            by_file
+                .entry(config_file_name(&config.namespace, &config.key, config.environment))
+                .or_default()
+                .push(key.clone());
+        }
+
+        for file in files {
+            for key in by_file.remove(*file).unwrap_or_default() {
+                index.remove(&key);
+            }
+            let path = self.base_path.join("configs").join(file);
+            if let Ok(config) = self.load_config_from_file(&path) {
+                let key = self.make_key(&config.namespace, &config.key, config.environment);
+                index.insert(key, config);
+            }
+        }
+    }
+
+    /// Number of config files on disk
+    fn config_file_count(&self) -> Result<usize> {
+        Ok(self.config_files()?.len())
+    }
+
+    /// Paths of the config files in every shard
+    fn config_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for shard in subdirs(&self.base_path.join("configs"))? {
+            for entry in fs::read_dir(&shard)? {
+                let path = entry?.path();
+                if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                    files.push(path);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Save the index as a snapshot covering `journal`
+    fn save_index(&self, index: &HashMap<String, ConfigEntry>, journal: &Journal) -> Result<()> {
+        let mut entries: Vec<ConfigEntry> = index.values().cloned().collect();
+        entries.sort_by_key(subject);
+        IndexSnapshot::new(journal, entries).write(&self.base_path)
+    }
+
     /// Rebuild the index from disk
     fn rebuild_index(&self) -> Result<()> {
-        let configs_dir = self.base_path.join("configs");
-        if !configs_dir.exists() {
-            return Ok(());
-        }
+        let files = self.config_files()?;
 
         let mut index = self.index.write().unwrap();
         index.clear();
 
-        for entry in fs::read_dir(&configs_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Ok(config) = self.load_config_from_file(&path) {
-                    let key = self.make_key(&config.namespace, &config.key, config.environment);
-                    index.insert(key, config);
-                }
+        for path in files {
+            if let Ok(config) = self.load_config_from_file(&path) {
+                let key = self.make_key(&config.namespace, &config.key, config.environment);
+                index.insert(key, config);
             }
         }
 
@@ -69,17 +191,158 @@ impl FileStorage {
 
     /// Get the file path for a config entry
     fn config_file_path(&self, namespace: &str, key: &str, env: Environment) -> PathBuf {
-        let safe_namespace = namespace.replace('/', "_");
-        let safe_key = key.replace('/', "_");
-        let filename = format!("{}_{}_{}.json", safe_namespace, safe_key, env);
-        self.base_path.join("configs").join(filename)
+        self.base_path
+            .join("configs")
+            .join(config_file_name(namespace, key, env))
+    }
+
+    /// Record in the journal that a config file is about to change
+    fn journal(&self, namespace: &str, key: &str, env: Environment) -> Result<()> {
+        Journal::append(&self.base_path, &config_file_name(namespace, key, env))
     }
 
-    /// Get the file path for a version entry
-    fn version_file_path(&self, version_id: Uuid) -> PathBuf {
+    /// Get the directory holding the history directories of a namespace
+    fn namespace_version_dir(&self, namespace: &str) -> PathBuf {
         self.base_path
             .join("versions")
-            .join(format!("{}.json", version_id))
+            .join(shard(namespace))
+            .join(escape(namespace))
+    }
+
+    /// Get the history directory of a config
+    fn version_dir(&self, namespace: &str, key: &str, env: Environment) -> PathBuf {
+        self.namespace_version_dir(namespace)
+            .join(format!("{}.{}", escape(key), env))
+    }
+
+    /// Get the file path for a version entry, named so that file names sort
+    /// by version and then creation time
+    fn version_file_path(&self, version: &VersionEntry) -> PathBuf {
+        self.named_version_file_path(version, Uuid::new_v4())
+    }
+
+    /// Get the file path for a version entry, ending in the given id
+    fn named_version_file_path(&self, version: &VersionEntry, id: Uuid) -> PathBuf {
+        let created = version.created_at.timestamp_micros().max(0);
+        self.version_dir(&version.namespace, &version.key, version.environment)
+            .join(format!("{:020}-{:020}-{}.json", version.version, created, id))
+    }
+
+    /// Complete versioned writes interrupted by a crash
+    ///
+    /// A config file is only rewritten if it does not hold a newer version
+    /// yet, so replaying never undoes later writes.
+    fn recover_writes(&self) -> Result<()> {
+        for record in WalRecord::pending(&self.base_path)? {
+            tracing::warn!(
+                "Completing interrupted write of {} version {}",
+                subject(&record.config),
+                record.config.version
+            );
+            let path = self.config_file_path(
+                &record.config.namespace,
+                &record.config.key,
+                record.config.environment,
+            );
+            let current = self.load_config_from_file(&path).ok();
+            if current.map_or(true, |current| current.version < record.config.version) {
+                self.write_config_atomically(&record.config)?;
+            }
+            self.write_named_version(&record)?;
+            record.remove(&self.base_path)?;
+        }
+        Ok(())
+    }
+
+    /// Write the version file of a journaled write, unless it exists
+    fn write_named_version(&self, record: &WalRecord) -> Result<()> {
+        let path = self.named_version_file_path(&record.version, record.id);
+        if path.exists() {
+            return Ok(());
+        }
+        self.write_version_file(&path, &record.version)
+    }
+
+    /// Move config files and history directories of the unsharded layout
+    /// into shard directories
+    ///
+    /// The index snapshot refers to config files by name, so it is dropped
+    /// (and the index rebuilt) when config files were moved.
+    fn migrate_layout(&self) -> Result<()> {
+        let mut moved = false;
+        for entry in fs::read_dir(self.base_path.join("configs"))? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let config = match self.load_config_from_file(&path) {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::warn!("Not migrating unreadable config file {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let target = self.config_file_path(&config.namespace, &config.key, config.environment);
+            if let Some(dir) = target.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::rename(&path, &target)?;
+            moved = true;
+        }
+        if moved {
+            let snapshot = self.base_path.join(INDEX_SNAPSHOT_FILE);
+            if snapshot.exists() {
+                fs::remove_file(snapshot)?;
+            }
+        }
+
+        // Unsharded history directories are named `<namespace>.<key>.<env>`;
+        // shard directory names have no dot
+        for dir in subdirs(&self.base_path.join("versions"))? {
+            let Some(name) = dir.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some((namespace, rest)) = name.split_once('.') else {
+                continue;
+            };
+            let Some(namespace) = unescape(namespace) else {
+                continue;
+            };
+            let target = self.namespace_version_dir(&namespace).join(rest);
+            if target.exists() {
+                for entry in fs::read_dir(&dir)? {
+                    let path = entry?.path();
+                    if let Some(file_name) = path.file_name() {
+                        fs::rename(&path, target.join(file_name))?;
+                    }
+                }
+                fs::remove_dir(&dir)?;
+            } else {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&dir, &target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move version files of the flat layout into per-config directories
+    fn migrate_versions(&self) -> Result<()> {
+        for entry in fs::read_dir(self.base_path.join("versions"))? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok(version) = serde_json::from_str::<VersionEntry>(&contents) {
+                self.store_version(version)?;
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
     }
 
     /// Load a config from a file
@@ -88,43 +351,68 @@ impl FileStorage {
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
 
-        serde_json::from_str(&contents)
-            .map_err(|e| StorageError::SerializationError(e.to_string()))
+        self.blobs.parse(&contents)
     }
 
     /// Atomically write a config to a file
     fn write_config_atomically(&self, config: &ConfigEntry) -> Result<()> {
         let path = self.config_file_path(&config.namespace, &config.key, config.environment);
+        self.journal(&config.namespace, &config.key, config.environment)?;
 
         // Serialize to JSON
-        let json = serde_json::to_string_pretty(config)
-            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        let json = self.blobs.serialize(config)?;
 
-        // Write to temporary file first
-        let temp_path = path.with_extension("tmp");
-        {
-            let mut temp_file = File::create(&temp_path)?;
-            temp_file.write_all(json.as_bytes())?;
-            temp_file.sync_all()?; // Ensure data is written to disk
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
         }
 
-        // Atomic rename
-        fs::rename(&temp_path, &path)?;
+        // Write to temporary file first, then rename atomically
+        let replaced = blobs::file_ref(&path);
+        blobs::write_atomically(&path, json.as_bytes())?;
+        if let Some(hash) = replaced {
+            self.blobs.release(&hash)?;
+        }
 
         Ok(())
     }
 
+    /// Record the count, duration and errors of config reads and writes in
+    /// `metrics` (`storage_operations_total` and friends)
+    ///
+    /// Clones of this storage record to the same sink.
+    pub fn set_metrics(&self, metrics: Arc<dyn MetricsSink>) {
+        *self.metrics.write().unwrap() = Some(metrics);
+    }
+
+    /// Run `operation`, recording it in the metrics sink if one is set
+    fn metered<T>(&self, operation: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let Some(metrics) = self.metrics.read().unwrap().clone() else {
+            return f();
+        };
+        let started = Instant::now();
+        let result = f();
+        let labels = [("operation", operation)];
+        metrics.record_counter("storage_operations_total", &labels, 1.0);
+        metrics.observe("storage_operation_duration_seconds", &labels, started.elapsed().as_secs_f64());
+        if let Err(e) = &result {
+            metrics.record_counter("storage_errors_total", &[("error_type", e.kind())], 1.0);
+        }
+        result
+    }
+
     /// Store a configuration
     pub fn set(&self, config: ConfigEntry) -> Result<()> {
-        // Write to disk atomically
-        self.write_config_atomically(&config)?;
+        self.metered("set", || {
+            // Write to disk atomically
+            self.write_config_atomically(&config)?;
 
-        // Update index
-        let key = self.make_key(&config.namespace, &config.key, config.environment);
-        let mut index = self.index.write().unwrap();
-        index.insert(key, config);
+            // Update index
+            let key = self.make_key(&config.namespace, &config.key, config.environment);
+            let mut index = self.index.write().unwrap();
+            index.insert(key, config);
 
-        Ok(())
+            Ok(())
+        })
     }
 
     /// Get a configuration
@@ -134,53 +422,130 @@ impl FileStorage {
         key: &str,
         env: Environment,
     ) -> Result<Option<ConfigEntry>> {
-        let storage_key = self.make_key(namespace, key, env);
-        let index = self.index.read().unwrap();
+        self.metered("get", || {
+            let storage_key = self.make_key(namespace, key, env);
+            let index = self.index.read().unwrap();
 
-        Ok(index.get(&storage_key).cloned())
+            Ok(index.get(&storage_key).cloned())
+        })
     }
 
     /// List all configurations in a namespace
     pub fn list(&self, namespace: &str, env: Environment) -> Result<Vec<ConfigEntry>> {
-        let index = self.index.read().unwrap();
-        let prefix = format!("{}::", namespace);
-        let suffix = format!("::{}", env);
-
-        let configs: Vec<ConfigEntry> = index
-            .iter()
-            .filter(|(k, _)| k.starts_with(&prefix) && k.ends_with(&suffix))
-            .map(|(_, v)| v.clone())
-            .collect();
+        self.metered("list", || {
+            let index = self.index.read().unwrap();
+            let prefix = format!("{}::", namespace);
+            let suffix = format!("::{}", env);
+
+            let configs: Vec<ConfigEntry> = index
+                .iter()
+                .filter(|(k, _)| k.starts_with(&prefix) && k.ends_with(&suffix))
+                .map(|(_, v)| v.clone())
+                .collect();
+
+            Ok(configs)
+        })
+    }
 
-        Ok(configs)
+    /// List all configurations across namespaces and environments
+    pub fn list_all(&self) -> Result<Vec<ConfigEntry>> {
+        let index = self.index.read().unwrap();
+        Ok(index.values().cloned().collect())
     }
 
     /// Delete a configuration
     pub fn delete(&self, namespace: &str, key: &str, env: Environment) -> Result<bool> {
-        let storage_key = self.make_key(namespace, key, env);
-
-        // Remove from index
-        let mut index = self.index.write().unwrap();
-        let removed = index.remove(&storage_key).is_some();
-
-        if removed {
-            // Delete file
-            let path = self.config_file_path(namespace, key, env);
-            if path.exists() {
-                fs::remove_file(path)?;
+        self.metered("delete", || {
+            let storage_key = self.make_key(namespace, key, env);
+
+            // Remove from index
+            let mut index = self.index.write().unwrap();
+            let removed = index.remove(&storage_key).is_some();
+
+            if removed {
+                // Delete file
+                let path = self.config_file_path(namespace, key, env);
+                if path.exists() {
+                    self.journal(namespace, key, env)?;
+                    self.blobs.remove_file(&path)?;
+                }
             }
-        }
 
-        Ok(removed)
+            Ok(removed)
+        })
+    }
+
+    /// Store a configuration together with its version entry
+    ///
+    /// The write is journaled first, so if the process dies before both files
+    /// are written, it is completed the next time the store is opened.
+    pub fn set_versioned(&self, config: ConfigEntry, version: VersionEntry) -> Result<()> {
+        self.metered("set", || {
+            let record = WalRecord::new(config, version);
+            record.write(&self.base_path)?;
+
+            self.write_config_atomically(&record.config)?;
+            let key = self.make_key(&record.config.namespace, &record.config.key, record.config.environment);
+            self.index.write().unwrap().insert(key, record.config.clone());
+            self.write_named_version(&record)?;
+
+            record.remove(&self.base_path)
+        })
     }
 
     /// Store a version entry
     pub fn store_version(&self, version: VersionEntry) -> Result<()> {
-        let version_id = Uuid::new_v4();
-        let path = self.version_file_path(version_id);
+        let path = self.version_file_path(&version);
+        self.write_version_file(&path, &version)
+    }
 
-        let json = serde_json::to_string_pretty(&version)
-            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+    /// Replace the version history of a config
+    ///
+    /// Used to copy histories between stores; `pruned` is the newest version
+    /// pruned from the history (0 if none was). Importing a history again
+    /// replaces it, so an interrupted copy can simply be repeated.
+    pub fn import_history(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        versions: &[VersionEntry],
+        pruned: u64,
+    ) -> Result<()> {
+        let dir = self.version_dir(namespace, key, env);
+        if dir.exists() {
+            self.remove_version_dir(&dir)?;
+        }
+        for version in versions {
+            self.store_version(version.clone())?;
+        }
+        if pruned > 0 {
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join(PRUNED_FILE), pruned.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Namespace, key and environment of every config with version history,
+    /// including deleted ones
+    pub fn list_histories(&self) -> Result<Vec<(String, String, Environment)>> {
+        let mut histories = Vec::new();
+        for dir in self.version_dirs()? {
+            // Every version of a directory names the same config
+            if let Some(version) = version_files(&dir)?.iter().find_map(|file| self.read_version(&file.path)) {
+                histories.push((version.namespace, version.key, version.environment));
+            }
+        }
+        Ok(histories)
+    }
+
+    /// Write a version entry to a file
+    fn write_version_file(&self, path: &Path, version: &VersionEntry) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let json = self.blobs.serialize(version)?;
 
         let mut file = File::create(path)?;
         file.write_all(json.as_bytes())?;
@@ -189,45 +554,291 @@ impl FileStorage {
         Ok(())
     }
 
-    /// Get version history for a config
+    /// Get version history for a config, newest first
     pub fn get_versions(
         &self,
         namespace: &str,
         key: &str,
         env: Environment,
     ) -> Result<Vec<VersionEntry>> {
-        let versions_dir = self.base_path.join("versions");
-        if !versions_dir.exists() {
-            return Ok(Vec::new());
+        self.get_versions_with_options(namespace, key, env, &HistoryOptions::default())
+    }
+
+    /// Get a page of version history for a config, newest first
+    ///
+    /// Only the files of the returned versions are read.
+    pub fn get_versions_with_options(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        options: &HistoryOptions,
+    ) -> Result<Vec<VersionEntry>> {
+        let mut files = version_files(&self.version_dir(namespace, key, env))?;
+        if let Some(before) = options.before_version {
+            files.retain(|file| file.version < before);
         }
+        if let Some(limit) = options.limit {
+            files.truncate(limit);
+        }
+
+        Ok(files.iter().filter_map(|file| self.read_version(&file.path)).collect())
+    }
 
+    /// Get the version history of every config in a namespace, in no particular order
+    pub fn get_namespace_versions(&self, namespace: &str) -> Result<Vec<VersionEntry>> {
         let mut versions = Vec::new();
+        for dir in subdirs(&self.namespace_version_dir(namespace))? {
+            versions.extend(
+                version_files(&dir)?
+                    .iter()
+                    .filter_map(|file| self.read_version(&file.path)),
+            );
+        }
+        Ok(versions)
+    }
+
+    /// Delete the versions of a config that a retention policy does not keep
+    ///
+    /// Returns the number of deleted versions.
+    pub fn prune_versions(
+        &self,
+        namespace: &str,
+        key: &str,
+        env: Environment,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        prune_dir(&self.blobs, &self.version_dir(namespace, key, env), policy, now)
+    }
 
-        for entry in fs::read_dir(&versions_dir)? {
-            let entry = entry?;
-            let path = entry.path();
+    /// Delete the versions of every config that a retention policy does not keep
+    ///
+    /// Returns the number of deleted versions.
+    pub fn prune_all_versions(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<usize> {
+        let mut pruned = 0;
+        for dir in self.version_dirs()? {
+            pruned += prune_dir(&self.blobs, &dir, policy, now)?;
+        }
+        Ok(pruned)
+    }
 
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Ok(mut file) = File::open(&path) {
-                    let mut contents = String::new();
-                    if file.read_to_string(&mut contents).is_ok() {
-                        if let Ok(version) = serde_json::from_str::<VersionEntry>(&contents) {
-                            if version.namespace == namespace
-                                && version.key == key
-                                && version.environment == env
-                            {
-                                versions.push(version);
-                            }
-                        }
+    /// Retention settings of the store (see [`crate::retention`])
+    ///
+    /// Read from `retention.toml` on every call, so changes made by other
+    /// processes apply at once.
+    pub fn retention(&self) -> Result<RetentionConfig> {
+        RetentionConfig::load(&self.base_path)
+    }
+
+    /// Replace the retention settings of the store
+    pub fn set_retention(&self, config: &RetentionConfig) -> Result<()> {
+        config.save(&self.base_path)
+    }
+
+    /// Remove the history the retention settings do not keep
+    ///
+    /// Live configs are pruned with the `history` policy of their namespace.
+    /// The history of a deleted config is removed entirely once its newest
+    /// version is older than the namespace's `trash_days`.
+    pub fn apply_retention(&self, now: DateTime<Utc>) -> Result<RetentionReport> {
+        let settings = self.retention()?;
+        let mut report = RetentionReport::default();
+        for dir in self.version_dirs()? {
+            let files = version_files(&dir)?;
+            let Some(version) = files.iter().find_map(|file| self.read_version(&file.path)) else {
+                continue;
+            };
+            let retention = settings.for_namespace(&version.namespace);
+            let key = self.make_key(&version.namespace, &version.key, version.environment);
+            if self.index.read().unwrap().contains_key(&key) {
+                if let Some(policy) = &retention.history {
+                    report.pruned_versions += prune_dir(&self.blobs, &dir, policy, now)?;
+                }
+            } else if let Some(days) = retention.trash_days {
+                // Files are newest first
+                if now - files[0].created_at >= chrono::Duration::days(days.into()) {
+                    for file in &files {
+                        self.blobs.remove_file(&file.path)?;
                     }
+                    fs::remove_dir_all(&dir)?;
+                    report.purged_histories += 1;
                 }
             }
         }
+        Ok(report)
+    }
 
-        // Sort by version number descending
-        versions.sort_by(|a, b| b.version.cmp(&a.version));
+    /// Newest version of a config removed by pruning (0 if none was)
+    pub fn pruned_version(&self, namespace: &str, key: &str, env: Environment) -> u64 {
+        pruned_version(&self.version_dir(namespace, key, env))
+    }
 
-        Ok(versions)
+    /// History directories of all configs
+    fn version_dirs(&self) -> Result<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        for shard in subdirs(&self.base_path.join("versions"))? {
+            for namespace in subdirs(&shard)? {
+                dirs.extend(subdirs(&namespace)?);
+            }
+        }
+        Ok(dirs)
+    }
+
+    /// Copy the configs of namespace `src` in `envs` (every environment if
+    /// empty), with their version history, to namespace `dst`
+    ///
+    /// `dst` must not have configs or history in those environments yet.
+    /// Copies get new ids. If a file cannot be written, the files written so
+    /// far are removed and the index is left unchanged. Returns the copies.
+    pub fn clone_namespace(
+        &self,
+        src: &str,
+        dst: &str,
+        envs: &[Environment],
+    ) -> Result<Vec<ConfigEntry>> {
+        let mut index = self.index.write().unwrap();
+        let (copies, _) = self.copy_namespace(&index, src, dst, envs, false)?;
+        for copy in &copies {
+            index.insert(self.make_key(dst, &copy.key, copy.environment), copy.clone());
+        }
+        Ok(copies)
+    }
+
+    /// Move every config of namespace `src`, with its version history, to
+    /// namespace `dst`
+    ///
+    /// `dst` must not have configs or history yet. Configs keep their ids.
+    /// The new files are written before the old ones are removed, so a
+    /// failure leaves `src` intact. Returns the moved configs.
+    pub fn rename_namespace(&self, src: &str, dst: &str) -> Result<Vec<ConfigEntry>> {
+        let mut index = self.index.write().unwrap();
+        let (moved, version_dirs) = self.copy_namespace(&index, src, dst, &[], true)?;
+        for entry in &moved {
+            index.remove(&self.make_key(src, &entry.key, entry.environment));
+            index.insert(self.make_key(dst, &entry.key, entry.environment), entry.clone());
+        }
+
+        for entry in &moved {
+            let path = self.config_file_path(src, &entry.key, entry.environment);
+            if path.exists() {
+                self.journal(src, &entry.key, entry.environment)?;
+                self.blobs.remove_file(&path)?;
+            }
+        }
+        for dir in version_dirs {
+            self.remove_version_dir(&dir)?;
+        }
+        let namespace_dir = self.namespace_version_dir(src);
+        if namespace_dir.exists() {
+            fs::remove_dir(namespace_dir)?;
+        }
+        Ok(moved)
+    }
+
+    /// Write copies of a namespace's configs and history under `dst`
+    ///
+    /// Returns the copies and the history directories they were copied from.
+    fn copy_namespace(
+        &self,
+        index: &HashMap<String, ConfigEntry>,
+        src: &str,
+        dst: &str,
+        envs: &[Environment],
+        keep_ids: bool,
+    ) -> Result<(Vec<ConfigEntry>, Vec<PathBuf>)> {
+        if src == dst {
+            return Err(StorageError::AlreadyExists(format!("namespace {}", dst)));
+        }
+        let in_scope = |env: Environment| envs.is_empty() || envs.contains(&env);
+        let namespace_dirs = |namespace: &str| -> Result<Vec<(PathBuf, String)>> {
+            let mut dirs = Vec::new();
+            for dir in subdirs(&self.namespace_version_dir(namespace))? {
+                let Some(name) = dir.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                let env = name.rsplit('.').next().and_then(|env| env.parse().ok());
+                if env.is_some_and(in_scope) {
+                    let name = name.to_string();
+                    dirs.push((dir, name));
+                }
+            }
+            Ok(dirs)
+        };
+
+        let taken = index
+            .values()
+            .any(|entry| entry.namespace == dst && in_scope(entry.environment));
+        if taken || !namespace_dirs(dst)?.is_empty() {
+            return Err(StorageError::AlreadyExists(format!("namespace {}", dst)));
+        }
+
+        let sources: Vec<&ConfigEntry> = index
+            .values()
+            .filter(|entry| entry.namespace == src && in_scope(entry.environment))
+            .collect();
+        let source_dirs = namespace_dirs(src)?;
+        if sources.is_empty() && source_dirs.is_empty() {
+            return Err(StorageError::NotFound(format!("namespace {}", src)));
+        }
+
+        // Old id -> id of the copy, shared by a config and its history
+        let mut ids: HashMap<Uuid, Uuid> = HashMap::new();
+        let mut new_id = |id: Uuid| *ids.entry(id).or_insert_with(|| if keep_ids { id } else { Uuid::new_v4() });
+
+        let copies: Vec<ConfigEntry> = sources
+            .into_iter()
+            .map(|entry| ConfigEntry {
+                id: new_id(entry.id),
+                namespace: dst.to_string(),
+                ..entry.clone()
+            })
+            .collect();
+
+        let mut written: Vec<PathBuf> = Vec::new();
+        let result = (|| -> Result<()> {
+            for copy in &copies {
+                written.push(self.config_file_path(dst, &copy.key, copy.environment));
+                self.write_config_atomically(copy)?;
+            }
+            for (dir, rest) in &source_dirs {
+                let target = self.namespace_version_dir(dst).join(rest);
+                written.push(target.clone());
+                fs::create_dir_all(&target)?;
+                for file in version_files(dir)? {
+                    let Some(mut version) = self.read_version(&file.path) else {
+                        continue;
+                    };
+                    version.namespace = dst.to_string();
+                    version.config_id = new_id(version.config_id);
+                    let json = self.blobs.serialize(&version)?;
+                    let name = file.path.file_name().expect("version files have names");
+                    let mut out = File::create(target.join(name))?;
+                    out.write_all(json.as_bytes())?;
+                    out.sync_all()?;
+                }
+                let pruned = dir.join(PRUNED_FILE);
+                if pruned.exists() {
+                    fs::copy(&pruned, target.join(PRUNED_FILE))?;
+                }
+            }
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            for path in written {
+                let _ = if path.is_dir() {
+                    self.remove_version_dir(&path)
+                } else if path.exists() {
+                    self.blobs.remove_file(&path)
+                } else {
+                    Ok(())
+                };
+            }
+            return Err(e);
+        }
+
+        Ok((copies, source_dirs.into_iter().map(|(dir, _)| dir).collect()))
     }
 
     /// Export all configurations to a directory
@@ -257,6 +868,588 @@ impl FileStorage {
 
         Ok(count)
     }
+
+    /// Get the file path for an auxiliary record (`records/<kind>/<id>.json`)
+    fn record_file_path(&self, kind: &str, id: &str) -> Result<PathBuf> {
+        for part in [kind, id] {
+            if part.is_empty()
+                || part == "."
+                || part == ".."
+                || part.contains(['/', '\\', '\0'])
+            {
+                return Err(StorageError::InvalidPath(format!("{}/{}", kind, id)));
+            }
+        }
+
+        Ok(self
+            .base_path
+            .join("records")
+            .join(kind)
+            .join(format!("{}.json", id)))
+    }
+
+    /// Store an auxiliary record (role definitions, policies, ...) of the given kind
+    pub fn put_record<T: Serialize>(&self, kind: &str, id: &str, record: &T) -> Result<()> {
+        let path = self.record_file_path(kind, id)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(record)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+
+        // Write to temporary file first, then rename atomically
+        let temp_path = path.with_extension("tmp");
+        {
+            let mut temp_file = File::create(&temp_path)?;
+            temp_file.write_all(json.as_bytes())?;
+            temp_file.sync_all()?;
+        }
+        fs::rename(&temp_path, &path)?;
+
+        Ok(())
+    }
+
+    /// Get an auxiliary record
+    pub fn get_record<T: DeserializeOwned>(&self, kind: &str, id: &str) -> Result<Option<T>> {
+        let path = self.record_file_path(kind, id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))
+    }
+
+    /// List all auxiliary records of the given kind, ordered by id
+    pub fn list_records<T: DeserializeOwned>(&self, kind: &str) -> Result<Vec<T>> {
+        // Validate the kind with a placeholder id
+        let dir = self.record_file_path(kind, "_")?;
+        let dir = dir.parent().expect("record path has a parent");
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+
+        paths
+            .into_iter()
+            .map(|path| {
+                let contents = fs::read_to_string(path)?;
+                serde_json::from_str(&contents)
+                    .map_err(|e| StorageError::SerializationError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Kinds of auxiliary records in the store, ordered by name
+    pub fn record_kinds(&self) -> Result<Vec<String>> {
+        let mut kinds: Vec<String> = subdirs(&self.base_path.join("records"))?
+            .iter()
+            .filter_map(|dir| dir.file_name().and_then(|s| s.to_str()).map(str::to_string))
+            .collect();
+        kinds.sort();
+        Ok(kinds)
+    }
+
+    /// Ids of the auxiliary records of the given kind, ordered
+    pub fn record_ids(&self, kind: &str) -> Result<Vec<String>> {
+        let dir = self.record_file_path(kind, "_")?;
+        let dir = dir.parent().expect("record path has a parent");
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut ids: Vec<String> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+            .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Delete an auxiliary record
+    pub fn delete_record(&self, kind: &str, id: &str) -> Result<bool> {
+        let path = self.record_file_path(kind, id)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        fs::remove_file(path)?;
+        Ok(true)
+    }
+
+    /// Cross-check the index, config files and version history
+    ///
+    /// With `repair`, the index is reloaded from readable config files,
+    /// missing files are rewritten from the index, and a history entry is
+    /// recorded for current values whose latest version is missing or
+    /// disagrees. Unreadable files and version gaps are only reported.
+    pub fn fsck(&self, repair: bool) -> Result<FsckReport> {
+        let mut report = FsckReport::default();
+
+        // Config files on disk
+        let mut on_disk = HashMap::new();
+        for path in self.config_files()? {
+            match self.load_config_from_file(&path) {
+                Ok(config) => {
+                    let key = self.make_key(&config.namespace, &config.key, config.environment);
+                    on_disk.insert(key, config);
+                }
+                Err(e) => report.push(
+                    FsckIssueKind::UnreadableFile,
+                    path.display().to_string(),
+                    e.to_string(),
+                    false,
+                ),
+            }
+        }
+
+        // Index against files
+        let mut index = self.index.write().unwrap();
+        for (key, config) in index.iter() {
+            if !on_disk.contains_key(key) {
+                if repair {
+                    self.write_config_atomically(config)?;
+                }
+                report.push(
+                    FsckIssueKind::MissingFile,
+                    subject(config),
+                    "indexed config has no file",
+                    repair,
+                );
+            }
+        }
+        for (key, config) in &on_disk {
+            match index.get(key) {
+                None => report.push(
+                    FsckIssueKind::UnindexedFile,
+                    subject(config),
+                    "config file is not indexed",
+                    repair,
+                ),
+                Some(indexed) if !same_json(indexed, config) => report.push(
+                    FsckIssueKind::IndexMismatch,
+                    subject(config),
+                    format!(
+                        "index holds version {}, file holds version {}",
+                        indexed.version, config.version
+                    ),
+                    repair,
+                ),
+                Some(_) => {}
+            }
+        }
+        if repair {
+            for (key, config) in &on_disk {
+                index.insert(key.clone(), config.clone());
+            }
+        }
+        let mut configs: Vec<ConfigEntry> = index.values().cloned().collect();
+        drop(index);
+        report.configs_checked = configs.len();
+
+        // Version history against current values
+        let mut history: HashMap<String, Vec<VersionEntry>> = HashMap::new();
+        for version in self.load_all_versions()? {
+            report.versions_checked += 1;
+            let key = self.make_key(&version.namespace, &version.key, version.environment);
+            history.entry(key).or_default().push(version);
+        }
+
+        configs.sort_by_key(subject);
+        for config in configs {
+            let key = self.make_key(&config.namespace, &config.key, config.environment);
+            let versions = history.get(&key).map(Vec::as_slice).unwrap_or_default();
+
+            // Versions removed by a retention policy are not gaps
+            let oldest = self.pruned_version(&config.namespace, &config.key, config.environment) + 1;
+            let missing: Vec<String> = (oldest..config.version)
+                .filter(|n| !versions.iter().any(|v| v.version == *n))
+                .map(|n| n.to_string())
+                .collect();
+            if !missing.is_empty() {
+                report.push(
+                    FsckIssueKind::VersionGap,
+                    subject(&config),
+                    format!("missing versions {}", missing.join(", ")),
+                    false,
+                );
+            }
+
+            // Entries of deleted and re-created configs share version numbers,
+            // so the most recent one is authoritative
+            let latest = versions
+                .iter()
+                .filter(|v| v.version == config.version)
+                .max_by_key(|v| v.created_at);
+            let (kind, detail) = match latest {
+                None => (
+                    FsckIssueKind::MissingLatestVersion,
+                    format!("no history entry for version {}", config.version),
+                ),
+                Some(latest) if !same_json(&latest.value, &config.value) => (
+                    FsckIssueKind::LatestVersionMismatch,
+                    format!("history entry for version {} holds a different value", config.version),
+                ),
+                Some(_) => continue,
+            };
+
+            if repair {
+                self.store_version(VersionEntry {
+                    version: config.version,
+                    config_id: config.id,
+                    namespace: config.namespace.clone(),
+                    key: config.key.clone(),
+                    value: config.value.clone(),
+                    environment: config.environment,
+                    created_at: Utc::now(),
+                    created_by: config.metadata.updated_by.clone(),
+                    change_description: Some("Recorded by consistency check".to_string()),
+                })?;
+            }
+            report.push(kind, subject(&config), detail, repair);
+        }
+
+        if repair {
+            self.save_index(&self.index.read().unwrap(), &Journal::read(&self.base_path)?)?;
+        }
+
+        report
+            .issues
+            .sort_by_key(|issue| (issue.subject.clone(), issue.kind));
+        Ok(report)
+    }
+
+    /// Fold the journal into a new index snapshot and tidy up the store
+    ///
+    /// Config files named in the journal are read again, leftover temporary
+    /// files of interrupted writes and history directories without versions
+    /// are removed, and version files not named by version and creation time
+    /// (which history reads skip) are rewritten under such a name. With
+    /// deduplication enabled, large inline values are moved into blobs;
+    /// blob reference counts are recounted and unreferenced blobs deleted.
+    /// Other processes should not write to the store while it is compacted.
+    pub fn compact(&self) -> Result<CompactionReport> {
+        let mut report = CompactionReport::default();
+        let mut index = self.index.write().unwrap();
+
+        let journal = Journal::read(&self.base_path)?;
+        let changed = journal.files_since(0);
+        report.journal_entries = changed.len();
+        self.replay(&mut index, &changed);
+
+        let mut temp_dirs = vec![
+            self.base_path.clone(),
+            self.base_path.join("configs"),
+            self.base_path.join(WAL_DIR),
+        ];
+        temp_dirs.extend(subdirs(&self.base_path.join("configs"))?);
+        temp_dirs.extend(subdirs(&self.base_path.join("records"))?);
+        temp_dirs.extend(subdirs(&self.base_path.join(BLOB_DIR))?);
+        for dir in temp_dirs {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("tmp") {
+                    fs::remove_file(&path)?;
+                    report.temp_files_removed += 1;
+                }
+            }
+        }
+
+        for dir in self.version_dirs()? {
+            let canonical: Vec<PathBuf> = version_files(&dir)?.into_iter().map(|file| file.path).collect();
+            let mut empty = true;
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                empty = false;
+                if canonical.contains(&path) || path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                match self.read_version(&path) {
+                    Some(version) => {
+                        let target = self.version_file_path(&version);
+                        if let Some(parent) = target.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        fs::rename(&path, target)?;
+                        report.version_files_rewritten += 1;
+                    }
+                    None => report.unreadable_version_files.push(path.display().to_string()),
+                }
+            }
+            if empty {
+                fs::remove_dir(&dir)?;
+                report.empty_dirs_removed += 1;
+            }
+        }
+        for shard in subdirs(&self.base_path.join("versions"))? {
+            for dir in subdirs(&shard)? {
+                if fs::read_dir(&dir)?.next().is_none() {
+                    fs::remove_dir(&dir)?;
+                    report.empty_dirs_removed += 1;
+                }
+            }
+        }
+
+        self.collect_blobs(&mut report)?;
+
+        report.configs = index.len();
+        self.save_index(&index, &Journal::rotate(&self.base_path)?)?;
+        report.unreadable_version_files.sort();
+        Ok(report)
+    }
+
+    /// Deduplication threshold of new writes (`None` while values are stored inline)
+    pub fn dedup(&self) -> Option<usize> {
+        self.blobs.min_size()
+    }
+
+    /// Store values of at least `min_size` bytes of JSON once per content, or
+    /// inline again with `None`
+    ///
+    /// The setting is persisted with the store and applies to new writes;
+    /// [`compact`](Self::compact) moves existing values into blobs. Other
+    /// processes pick it up when they open the store.
+    pub fn set_dedup(&self, min_size: Option<usize>) -> Result<()> {
+        let (kind, id) = DEDUP_RECORD;
+        match min_size {
+            Some(min_size) => self.put_record(kind, id, &DedupSettings { min_size })?,
+            None => {
+                self.delete_record(kind, id)?;
+            }
+        }
+        self.blobs.set_min_size(min_size);
+        Ok(())
+    }
+
+    /// Number and size of stored blobs and the space they save
+    pub fn blob_stats(&self) -> Result<BlobStats> {
+        self.blobs.stats()
+    }
+
+    /// Read a version file, skipping unreadable ones
+    fn read_version(&self, path: &Path) -> Option<VersionEntry> {
+        let contents = fs::read_to_string(path).ok()?;
+        self.blobs.parse(&contents).ok()
+    }
+
+    /// Remove a history directory, dropping the blob references of its versions
+    fn remove_version_dir(&self, dir: &Path) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                self.blobs.remove_file(&path)?;
+            }
+        }
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    /// Move inline values of config and version files into blobs and
+    /// recount blob references, deleting blobs nobody refers to
+    fn collect_blobs(&self, report: &mut CompactionReport) -> Result<()> {
+        let mut files = self.config_files()?;
+        for dir in self.version_dirs()? {
+            files.extend(version_files(&dir)?.into_iter().map(|file| file.path));
+        }
+
+        let mut references: HashMap<String, usize> = HashMap::new();
+        for path in files {
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(mut json) = serde_json::from_str::<serde_json::Value>(&contents) else {
+                continue;
+            };
+            if let Some(min_size) = self.blobs.min_size() {
+                if self.blobs.externalize(&mut json, min_size)? {
+                    let contents = serde_json::to_string_pretty(&json)
+                        .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+                    blobs::write_atomically(&path, contents.as_bytes())?;
+                    report.values_moved_to_blobs += 1;
+                }
+            }
+            if let Some(hash) = blobs::blob_ref(&json) {
+                *references.entry(hash).or_default() += 1;
+            }
+        }
+
+        let (removed, repaired) = self.blobs.collect(&references)?;
+        report.blobs_removed = removed;
+        report.blob_counts_repaired = repaired;
+        Ok(())
+    }
+
+    /// Load every readable version entry
+    fn load_all_versions(&self) -> Result<Vec<VersionEntry>> {
+        let mut versions = Vec::new();
+        for dir in self.version_dirs()? {
+            versions.extend(
+                version_files(&dir)?
+                    .iter()
+                    .filter_map(|file| self.read_version(&file.path)),
+            );
+        }
+        Ok(versions)
+    }
+}
+
+/// A version file, described by its name
+struct VersionFile {
+    path: PathBuf,
+    version: u64,
+    created_at: DateTime<Utc>,
+}
+
+/// Version files in a history directory, newest first
+fn version_files(dir: &Path) -> Result<Vec<VersionFile>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let mut parts = name.splitn(3, '-');
+        let version = parts.next().and_then(|v| v.parse().ok());
+        let created = parts
+            .next()
+            .and_then(|c| c.parse().ok())
+            .and_then(DateTime::from_timestamp_micros);
+        if let (Some(version), Some(created_at)) = (version, created) {
+            files.push(VersionFile {
+                path,
+                version,
+                created_at,
+            });
+        }
+    }
+
+    files.sort_by(|a, b| b.path.cmp(&a.path));
+    Ok(files)
+}
+
+/// Delete the versions in a history directory that a policy does not keep
+fn prune_dir(blobs: &BlobStore, dir: &Path, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<usize> {
+    let mut newest_pruned = pruned_version(dir);
+    let mut pruned = 0;
+    for (position, file) in version_files(dir)?.iter().enumerate() {
+        if !policy.retains(position, file.created_at, now) {
+            blobs.remove_file(&file.path)?;
+            newest_pruned = newest_pruned.max(file.version);
+            pruned += 1;
+        }
+    }
+
+    if pruned > 0 {
+        fs::write(dir.join(PRUNED_FILE), newest_pruned.to_string())?;
+    }
+    Ok(pruned)
+}
+
+/// Newest version pruned from a history directory (0 if none was)
+fn pruned_version(dir: &Path) -> u64 {
+    fs::read_to_string(dir.join(PRUNED_FILE))
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Escape a name for use in a directory name
+///
+/// ASCII letters, digits and `-` are kept, every other byte becomes `_xx`,
+/// so escaped names never contain `.` and never collide.
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("_{:02x}", byte));
+        }
+    }
+    escaped
+}
+
+/// Reverse [`escape`]
+fn unescape(escaped: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut rest = escaped.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'_' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Shard directory of a namespace: the low byte of its FNV-1a hash in hex
+///
+/// Spelled out rather than taken from `std`, whose hasher may change between
+/// releases and would strand existing files.
+fn shard(namespace: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in namespace.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:02x}", hash & 0xff)
+}
+
+/// Subdirectories of a directory (none if it does not exist)
+fn subdirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    Ok(dirs)
+}
+
+/// Path of the file of a config relative to `configs/`
+fn config_file_name(namespace: &str, key: &str, env: Environment) -> String {
+    format!(
+        "{}/{}_{}_{}.json",
+        shard(namespace),
+        namespace.replace('/', "_"),
+        key.replace('/', "_"),
+        env
+    )
+}
+
+/// Human-readable name of a config (`namespace:key@env`)
+fn subject(config: &ConfigEntry) -> String {
+    format!("{}:{}@{}", config.namespace, config.key, config.environment)
+}
+
+/// Compare two values by their serialized form
+fn same_json<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
 }
 
 #[cfg(test)]
@@ -297,6 +1490,33 @@ mod tests {
         assert_eq!(retrieved.key, entry.key);
     }
 
+    #[test]
+    fn test_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        let sink = Arc::new(llm_config_sink::MemorySink::default());
+        // Clones record to the same sink
+        storage.clone().set_metrics(sink.clone());
+
+        let entry = ConfigEntry::new("app", "model", ConfigValue::String("gpt-4".to_string()), Environment::Development);
+        storage.set(entry).unwrap();
+        storage.get("app", "model", Environment::Development).unwrap();
+        storage.get("app", "other", Environment::Development).unwrap();
+        storage.delete("app", "model", Environment::Development).unwrap();
+
+        assert_eq!(sink.counter("storage_operations_total", &[("operation", "set")]), 1.0);
+        assert_eq!(sink.counter("storage_operations_total", &[("operation", "get")]), 2.0);
+        assert_eq!(sink.counter("storage_operations_total", &[("operation", "delete")]), 1.0);
+        assert_eq!(sink.observations("storage_operation_duration_seconds", &[("operation", "get")]).len(), 2);
+
+        // Configs cannot be written where their directory should be
+        fs::remove_dir_all(temp_dir.path().join("configs")).unwrap();
+        fs::write(temp_dir.path().join("configs"), "").unwrap();
+        let entry = ConfigEntry::new("app", "model", ConfigValue::Integer(1), Environment::Development);
+        assert!(storage.set(entry).is_err());
+        assert_eq!(sink.counter("storage_errors_total", &[("error_type", "io")]), 1.0);
+    }
+
     #[test]
     fn test_list_configs() {
         let temp_dir = TempDir::new().unwrap();
@@ -320,6 +1540,15 @@ mod tests {
 
         let configs = storage.list("test/ns", Environment::Development).unwrap();
         assert_eq!(configs.len(), 2);
+
+        let other = ConfigEntry::new(
+            "other/ns",
+            "key1",
+            ConfigValue::String("val3".to_string()),
+            Environment::Production,
+        );
+        storage.set(other).unwrap();
+        assert_eq!(storage.list_all().unwrap().len(), 3);
     }
 
     #[test]
@@ -376,4 +1605,522 @@ mod tests {
             assert_eq!(retrieved.key, entry.key);
         }
     }
+
+    #[test]
+    fn test_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+
+        storage.put_record("roles", "b", &vec!["x".to_string()]).unwrap();
+        storage.put_record("roles", "a", &vec!["y".to_string()]).unwrap();
+
+        let record: Option<Vec<String>> = storage.get_record("roles", "a").unwrap();
+        assert_eq!(record, Some(vec!["y".to_string()]));
+
+        let all: Vec<Vec<String>> = storage.list_records("roles").unwrap();
+        assert_eq!(all, vec![vec!["y".to_string()], vec!["x".to_string()]]);
+        storage.put_record("settings", "merge", &1).unwrap();
+        assert_eq!(storage.record_kinds().unwrap(), vec!["roles", "settings"]);
+        assert_eq!(storage.record_ids("roles").unwrap(), vec!["a", "b"]);
+
+        assert!(storage.delete_record("roles", "a").unwrap());
+        assert!(!storage.delete_record("roles", "a").unwrap());
+        assert!(storage.get_record::<Vec<String>>("roles", "a").unwrap().is_none());
+        assert!(storage.list_records::<Vec<String>>("missing").unwrap().is_empty());
+
+        assert!(storage.put_record("roles", "../escape", &1).is_err());
+    }
+
+    fn snapshot(config: &ConfigEntry) -> VersionEntry {
+        VersionEntry {
+            version: config.version,
+            config_id: config.id,
+            namespace: config.namespace.clone(),
+            key: config.key.clone(),
+            value: config.value.clone(),
+            environment: config.environment,
+            created_at: chrono::Utc::now(),
+            created_by: "test".to_string(),
+            change_description: None,
+        }
+    }
+
+    fn kinds(report: &FsckReport) -> Vec<FsckIssueKind> {
+        report.issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn test_fsck_clean() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+
+        let entry = ConfigEntry::new("ns", "key", ConfigValue::Integer(1), Environment::Base);
+        storage.set(entry.clone()).unwrap();
+        storage.store_version(snapshot(&entry)).unwrap();
+
+        let report = storage.fsck(false).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.configs_checked, 1);
+        assert_eq!(report.versions_checked, 1);
+    }
+
+    #[test]
+    fn test_fsck_repairs_files_and_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+
+        // Current value without a history entry
+        let entry = ConfigEntry::new("ns", "a", ConfigValue::Integer(1), Environment::Base);
+        storage.set(entry).unwrap();
+
+        // Indexed config whose file disappeared
+        let mut entry = ConfigEntry::new("ns", "b", ConfigValue::Integer(1), Environment::Base);
+        storage.set(entry.clone()).unwrap();
+        storage.store_version(snapshot(&entry)).unwrap();
+        fs::remove_file(storage.config_file_path("ns", "b", Environment::Base)).unwrap();
+
+        // Version 3 with versions 1 and 2 missing from history
+        entry.key = "c".to_string();
+        entry.version = 3;
+        storage.set(entry.clone()).unwrap();
+        storage.store_version(snapshot(&entry)).unwrap();
+
+        let report = storage.fsck(false).unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![
+                FsckIssueKind::MissingLatestVersion,
+                FsckIssueKind::MissingFile,
+                FsckIssueKind::VersionGap,
+            ]
+        );
+        assert_eq!(report.issues[2].detail, "missing versions 1, 2");
+        assert_eq!(report.unrepaired(), 3);
+
+        let report = storage.fsck(true).unwrap();
+        assert_eq!(report.unrepaired(), 1);
+        assert!(storage.config_file_path("ns", "b", Environment::Base).exists());
+
+        let report = storage.fsck(false).unwrap();
+        assert_eq!(kinds(&report), vec![FsckIssueKind::VersionGap]);
+    }
+
+    #[test]
+    fn test_version_history_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+
+        let mut entry = ConfigEntry::new("org/app", "key", ConfigValue::Integer(0), Environment::Base);
+        for version in 1..=5 {
+            entry.version = version;
+            storage.store_version(snapshot(&entry)).unwrap();
+        }
+        // Same key in a namespace that would collide if `/` became `_`
+        let other = ConfigEntry::new("org_app", "key", ConfigValue::Integer(0), Environment::Base);
+        storage.store_version(snapshot(&other)).unwrap();
+
+        let versions = |options: HistoryOptions| -> Vec<u64> {
+            storage
+                .get_versions_with_options("org/app", "key", Environment::Base, &options)
+                .unwrap()
+                .iter()
+                .map(|v| v.version)
+                .collect()
+        };
+        assert_eq!(versions(HistoryOptions::default()), vec![5, 4, 3, 2, 1]);
+        assert_eq!(versions(HistoryOptions::default().with_limit(2)), vec![5, 4]);
+        assert_eq!(
+            versions(HistoryOptions::default().with_limit(2).with_before_version(4)),
+            vec![3, 2]
+        );
+        assert_eq!(storage.get_namespace_versions("org/app").unwrap().len(), 5);
+
+        // Pruned versions are not reported as gaps
+        entry.version = 5;
+        storage.set(entry).unwrap();
+        let pruned = storage
+            .prune_all_versions(&RetentionPolicy::keep_last(2), Utc::now())
+            .unwrap();
+        assert_eq!(pruned, 3);
+        assert_eq!(versions(HistoryOptions::default()), vec![5, 4]);
+        assert!(storage.fsck(false).unwrap().is_clean());
+
+        // Imported histories keep their pruned versions and can be imported again
+        let copy_dir = TempDir::new().unwrap();
+        let copy = FileStorage::new(copy_dir.path()).unwrap();
+        let history = storage.get_versions("org/app", "key", Environment::Base).unwrap();
+        let pruned = storage.pruned_version("org/app", "key", Environment::Base);
+        assert_eq!(pruned, 3);
+        for _ in 0..2 {
+            copy.import_history("org/app", "key", Environment::Base, &history, pruned).unwrap();
+        }
+        copy.set(storage.get("org/app", "key", Environment::Base).unwrap().unwrap()).unwrap();
+        assert_eq!(copy.get_versions("org/app", "key", Environment::Base).unwrap().len(), 2);
+        assert!(copy.fsck(false).unwrap().is_clean());
+        let mut histories = storage.list_histories().unwrap();
+        histories.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(histories[0], ("org/app".to_string(), "key".to_string(), Environment::Base));
+        assert_eq!(histories.len(), 2);
+    }
+
+    #[test]
+    fn test_apply_retention() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        let store = |namespace: &str, key: &str, versions: u64, live: bool| {
+            let mut entry = ConfigEntry::new(namespace, key, ConfigValue::Integer(0), Environment::Base);
+            for version in 1..=versions {
+                entry.version = version;
+                storage.store_version(snapshot(&entry)).unwrap();
+            }
+            if live {
+                storage.set(entry).unwrap();
+            }
+        };
+        store("app/llm", "model", 4, true);
+        store("app/llm", "deleted", 2, false);
+        store("billing", "plan", 3, true);
+
+        // Without retention.toml nothing is pruned
+        let now = Utc::now();
+        assert_eq!(storage.apply_retention(now).unwrap(), RetentionReport::default());
+
+        let settings = RetentionConfig::parse(
+            "[[namespace]]\npattern = \"app/*\"\nhistory = { keep_last = 2 }\ntrash_days = 1",
+        )
+        .unwrap();
+        storage.set_retention(&settings).unwrap();
+        assert_eq!(storage.retention().unwrap(), settings);
+        let report = storage.apply_retention(now).unwrap();
+        assert_eq!((report.pruned_versions, report.purged_histories), (2, 0));
+        assert_eq!(storage.get_versions("app/llm", "deleted", Environment::Base).unwrap().len(), 2);
+
+        let report = storage.apply_retention(now + chrono::Duration::days(2)).unwrap();
+        assert_eq!(report.purged_histories, 1);
+        assert!(storage.get_versions("app/llm", "deleted", Environment::Base).unwrap().is_empty());
+        assert_eq!(storage.get_versions("app/llm", "model", Environment::Base).unwrap().len(), 2);
+        assert_eq!(storage.get_versions("billing", "plan", Environment::Base).unwrap().len(), 3);
+        assert!(storage.fsck(false).unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_flat_version_files_migrated() {
+        let temp_dir = TempDir::new().unwrap();
+        let entry = ConfigEntry::new("ns", "key", ConfigValue::Integer(1), Environment::Base);
+        fs::create_dir_all(temp_dir.path().join("versions")).unwrap();
+        fs::write(
+            temp_dir.path().join("versions").join(format!("{}.json", Uuid::new_v4())),
+            serde_json::to_string(&snapshot(&entry)).unwrap(),
+        )
+        .unwrap();
+
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(storage.get_versions("ns", "key", Environment::Base).unwrap().len(), 1);
+        assert_eq!(fs::read_dir(temp_dir.path().join("versions")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_unsharded_layout_migrated() {
+        let temp_dir = TempDir::new().unwrap();
+        let entry = ConfigEntry::new("team/llm", "key", ConfigValue::Integer(1), Environment::Base);
+        let version = snapshot(&entry);
+        let configs_dir = temp_dir.path().join("configs");
+        let history_dir = temp_dir.path().join("versions").join("team_2fllm.key.base");
+        fs::create_dir_all(&configs_dir).unwrap();
+        fs::create_dir_all(&history_dir).unwrap();
+        fs::write(
+            configs_dir.join("team_llm_key_base.json"),
+            serde_json::to_string(&entry).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            history_dir.join(format!("{:020}-{:020}-{}.json", 1, version.created_at.timestamp_micros(), Uuid::new_v4())),
+            serde_json::to_string(&version).unwrap(),
+        )
+        .unwrap();
+
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        assert!(storage.get("team/llm", "key", Environment::Base).unwrap().is_some());
+        assert_eq!(storage.get_versions("team/llm", "key", Environment::Base).unwrap().len(), 1);
+        assert_eq!(storage.get_namespace_versions("team/llm").unwrap().len(), 1);
+        assert!(storage.config_file_path("team/llm", "key", Environment::Base).exists());
+        assert!(!configs_dir.join("team_llm_key_base.json").exists());
+        assert!(!history_dir.exists());
+        assert!(storage.fsck(false).unwrap().is_clean());
+
+        // Namespaces are spread over shard directories
+        for i in 0..64 {
+            let namespace = format!("ns-{}", i);
+            storage
+                .set(ConfigEntry::new(&namespace, "key", ConfigValue::Integer(1), Environment::Base))
+                .unwrap();
+        }
+        let shards = fs::read_dir(&configs_dir).unwrap().count();
+        assert!(shards > 16, "{} shards", shards);
+        assert_eq!(FileStorage::new(temp_dir.path()).unwrap().list_all().unwrap().len(), 65);
+    }
+
+    #[test]
+    fn test_interrupted_writes_recovered() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        let mut entry = ConfigEntry::new("ns", "key", ConfigValue::Integer(1), Environment::Base);
+        storage.set_versioned(entry.clone(), snapshot(&entry)).unwrap();
+        assert_eq!(fs::read_dir(temp_dir.path().join(WAL_DIR)).unwrap().count(), 0);
+
+        // Crash after journaling and writing the config, before the version
+        entry.value = ConfigValue::Integer(2);
+        entry.version = 2;
+        WalRecord::new(entry.clone(), snapshot(&entry)).write(temp_dir.path()).unwrap();
+        storage.write_config_atomically(&entry).unwrap();
+
+        // Crash right after journaling a new config
+        let other = ConfigEntry::new("ns", "other", ConfigValue::Integer(1), Environment::Base);
+        WalRecord::new(other.clone(), snapshot(&other)).write(temp_dir.path()).unwrap();
+
+        // Crash while journaling: rolled back
+        let torn = ConfigEntry::new("ns", "torn", ConfigValue::Integer(1), Environment::Base);
+        fs::write(temp_dir.path().join(WAL_DIR).join("torn.tmp"), "{").unwrap();
+        fs::write(
+            temp_dir.path().join(WAL_DIR).join(format!("{}.json", Uuid::new_v4())),
+            &serde_json::to_string(&WalRecord::new(torn.clone(), snapshot(&torn))).unwrap()[..40],
+        )
+        .unwrap();
+        drop(storage);
+
+        let reopened = FileStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(fs::read_dir(temp_dir.path().join(WAL_DIR)).unwrap().count(), 0);
+        let versions = reopened.get_versions("ns", "key", Environment::Base).unwrap();
+        assert_eq!(versions.iter().map(|v| v.version).collect::<Vec<_>>(), vec![2, 1]);
+        assert!(reopened.get("ns", "other", Environment::Base).unwrap().is_some());
+        assert_eq!(reopened.get_versions("ns", "other", Environment::Base).unwrap().len(), 1);
+        assert!(reopened.get("ns", "torn", Environment::Base).unwrap().is_none());
+        assert!(reopened.fsck(false).unwrap().is_clean());
+
+        // Replaying a record never rolls a config back
+        let stale = WalRecord::new(
+            ConfigEntry { version: 1, ..entry.clone() },
+            snapshot(&ConfigEntry { version: 1, ..entry }),
+        );
+        stale.write(temp_dir.path()).unwrap();
+        drop(reopened);
+        let reopened = FileStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.get("ns", "key", Environment::Base).unwrap().unwrap().version, 2);
+    }
+
+    #[test]
+    fn test_fsck_reloads_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+
+        let mut entry = ConfigEntry::new("ns", "key", ConfigValue::Integer(1), Environment::Base);
+        storage.set(entry.clone()).unwrap();
+        storage.store_version(snapshot(&entry)).unwrap();
+
+        // Another process rewrites the file behind the index
+        entry.value = ConfigValue::Integer(2);
+        entry.version = 2;
+        storage.store_version(snapshot(&entry)).unwrap();
+        storage.write_config_atomically(&entry).unwrap();
+
+        let report = storage.fsck(false).unwrap();
+        assert_eq!(kinds(&report), vec![FsckIssueKind::IndexMismatch]);
+
+        storage.fsck(true).unwrap();
+        let current = storage.get("ns", "key", Environment::Base).unwrap().unwrap();
+        assert_eq!(current.version, 2);
+        assert!(storage.fsck(false).unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_clone_and_rename_namespace() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        for env in [Environment::Staging, Environment::Production] {
+            let entry = ConfigEntry::new("team-a/llm", "model", ConfigValue::Integer(1), env);
+            storage.set(entry.clone()).unwrap();
+            storage.store_version(snapshot(&entry)).unwrap();
+        }
+
+        let copies = storage
+            .clone_namespace("team-a/llm", "team-b/llm", &[Environment::Staging])
+            .unwrap();
+        assert_eq!(copies.len(), 1);
+        let original = storage.get("team-a/llm", "model", Environment::Staging).unwrap().unwrap();
+        assert_ne!(copies[0].id, original.id);
+        let history = storage.get_versions("team-b/llm", "model", Environment::Staging).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].config_id, copies[0].id);
+        assert!(storage.get("team-b/llm", "model", Environment::Production).unwrap().is_none());
+
+        // Destinations must be empty
+        assert!(matches!(
+            storage.clone_namespace("team-a/llm", "team-b/llm", &[]),
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        let moved = storage.rename_namespace("team-a/llm", "platform/llm").unwrap();
+        assert_eq!(moved.len(), 2);
+        assert!(storage.list("team-a/llm", Environment::Staging).unwrap().is_empty());
+        assert!(storage.get_namespace_versions("team-a/llm").unwrap().is_empty());
+        let renamed = storage.get("platform/llm", "model", Environment::Staging).unwrap().unwrap();
+        assert_eq!(renamed.id, original.id);
+        assert_eq!(storage.get_namespace_versions("platform/llm").unwrap().len(), 2);
+
+        // The move survives a restart and leaves a consistent store
+        let reopened = FileStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.list_all().unwrap().len(), 3);
+        assert!(reopened.fsck(false).unwrap().is_clean());
+        assert!(matches!(
+            reopened.rename_namespace("team-a/llm", "other"),
+            Err(StorageError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_index_snapshot_replays_journal() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        for key in ["a", "b"] {
+            storage
+                .set(ConfigEntry::new("ns", key, ConfigValue::Integer(1), Environment::Base))
+                .unwrap();
+        }
+        drop(storage);
+        assert_eq!(FileStorage::new(temp_dir.path()).unwrap().list_all().unwrap().len(), 2);
+        assert!(temp_dir.path().join(crate::index::INDEX_SNAPSHOT_FILE).exists());
+
+        // Only files named in the journal are read again
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        let mut unjournaled = storage.get("ns", "a", Environment::Base).unwrap().unwrap();
+        unjournaled.value = ConfigValue::Integer(2);
+        fs::write(
+            storage.config_file_path("ns", "a", Environment::Base),
+            serde_json::to_string(&unjournaled).unwrap(),
+        )
+        .unwrap();
+        let mut journaled = storage.get("ns", "b", Environment::Base).unwrap().unwrap();
+        journaled.value = ConfigValue::Integer(2);
+        storage.set(journaled).unwrap();
+
+        let reopened = FileStorage::new(temp_dir.path()).unwrap();
+        let get = |key| reopened.get("ns", key, Environment::Base).unwrap().unwrap().value.as_i64();
+        assert_eq!(get("a"), Some(1));
+        assert_eq!(get("b"), Some(2));
+        reopened.fsck(true).unwrap();
+        assert_eq!(get("a"), Some(2));
+
+        // A file count that disagrees with the index forces a rebuild
+        fs::remove_file(reopened.config_file_path("ns", "a", Environment::Base)).unwrap();
+        let rebuilt = FileStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(rebuilt.list_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_compact() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        let entry = ConfigEntry::new("ns", "key", ConfigValue::Integer(1), Environment::Base);
+        storage.set(entry.clone()).unwrap();
+        storage.store_version(snapshot(&entry)).unwrap();
+        storage.delete("ns", "key", Environment::Base).unwrap();
+        storage
+            .set(ConfigEntry::new("ns", "other", ConfigValue::Integer(1), Environment::Base))
+            .unwrap();
+
+        // Leftovers of interrupted writes and fragmented history
+        fs::write(
+            storage.config_file_path("ns", "other", Environment::Base).with_extension("tmp"),
+            "{",
+        )
+        .unwrap();
+        let dir = storage.version_dir("ns", "key", Environment::Base);
+        let file = version_files(&dir).unwrap().remove(0).path;
+        fs::rename(&file, dir.join("fragment.json")).unwrap();
+        fs::write(dir.join("broken.json"), "{").unwrap();
+        fs::create_dir(storage.version_dir("ns", "gone", Environment::Base)).unwrap();
+        assert!(storage.get_versions("ns", "key", Environment::Base).unwrap().is_empty());
+
+        let report = storage.compact().unwrap();
+        assert_eq!(report.configs, 1);
+        assert_eq!(report.journal_entries, 2);
+        assert_eq!(report.temp_files_removed, 1);
+        assert_eq!(report.empty_dirs_removed, 1);
+        assert_eq!(report.version_files_rewritten, 1);
+        assert_eq!(report.unreadable_version_files.len(), 1);
+        assert_eq!(storage.get_versions("ns", "key", Environment::Base).unwrap().len(), 1);
+
+        // The journal starts over and the snapshot covers the store
+        assert!(Journal::read(temp_dir.path()).unwrap().files_since(0).is_empty());
+        let reopened = FileStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.list_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path()).unwrap();
+        let prompt = ConfigValue::String("You are a helpful assistant. ".repeat(100));
+        let blob_files = || fs::read_dir(temp_dir.path().join(BLOB_DIR)).map_or(0, |dir| dir.count());
+
+        // Values written before dedup was enabled stay inline until compacted
+        let inline = ConfigEntry::new("app", "prompt", prompt.clone(), Environment::Development);
+        storage.set_versioned(inline.clone(), snapshot(&inline)).unwrap();
+        storage.set_dedup(Some(1024)).unwrap();
+        assert_eq!(blob_files(), 0);
+
+        // One blob for the same value in two environments and their history
+        for env in [Environment::Staging, Environment::Production] {
+            let entry = ConfigEntry::new("app", "prompt", prompt.clone(), env);
+            storage.set_versioned(entry.clone(), snapshot(&entry)).unwrap();
+        }
+        storage
+            .set(ConfigEntry::new("app", "small", ConfigValue::Integer(1), Environment::Production))
+            .unwrap();
+        let stats = storage.blob_stats().unwrap();
+        assert_eq!((stats.blobs, stats.references), (1, 4));
+        assert_eq!(stats.bytes_saved, stats.bytes * 3);
+
+        // Files refer to the blob and read back in full, also after reopening
+        let path = storage.config_file_path("app", "prompt", Environment::Production);
+        assert!(fs::read_to_string(&path).unwrap().contains("value_blob"));
+        let reopened = FileStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(reopened.dedup(), Some(1024));
+        for env in [Environment::Staging, Environment::Production] {
+            let entry = reopened.get("app", "prompt", env).unwrap().unwrap();
+            assert_eq!(entry.value.as_str(), prompt.as_str());
+            let versions = reopened.get_versions("app", "prompt", env).unwrap();
+            assert_eq!(versions[0].value.as_str(), prompt.as_str());
+        }
+
+        // Replacing the last reference deletes the blob
+        let other = ConfigEntry::new("app", "other", ConfigValue::String("x".repeat(2000)), Environment::Base);
+        storage.set(other.clone()).unwrap();
+        assert_eq!(storage.blob_stats().unwrap().blobs, 2);
+        storage.set(ConfigEntry { value: ConfigValue::Integer(2), ..other }).unwrap();
+        assert_eq!(storage.blob_stats().unwrap().blobs, 1);
+        storage.delete("app", "prompt", Environment::Production).unwrap();
+        assert_eq!(storage.blob_stats().unwrap().references, 3);
+
+        // Compaction moves inline values into blobs, fixes counts and removes orphans
+        let orphan = temp_dir.path().join(BLOB_DIR).join("00").join(format!("{}.json", "0".repeat(64)));
+        fs::create_dir_all(orphan.parent().unwrap()).unwrap();
+        fs::write(&orphan, "1").unwrap();
+        let refs = subdirs(&temp_dir.path().join(BLOB_DIR))
+            .unwrap()
+            .into_iter()
+            .flat_map(|shard| fs::read_dir(shard).unwrap().map(|entry| entry.unwrap().path()))
+            .find(|path| path.extension().and_then(|s| s.to_str()) == Some("refs"))
+            .unwrap();
+        fs::write(&refs, "7").unwrap();
+        let report = storage.compact().unwrap();
+        assert_eq!(report.values_moved_to_blobs, 2);
+        assert_eq!((report.blobs_removed, report.blob_counts_repaired), (1, 1));
+        assert!(!orphan.exists());
+        assert_eq!(storage.blob_stats().unwrap().references, 5);
+        assert_eq!(storage.compact().unwrap().blob_counts_repaired, 0);
+
+        storage.set_dedup(None).unwrap();
+        assert_eq!(FileStorage::new(temp_dir.path()).unwrap().dedup(), None);
+    }
 }
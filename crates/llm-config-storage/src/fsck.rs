@@ -0,0 +1,80 @@
+//! Consistency report for the file storage backend
+//!
+//! See [`FileStorage::fsck`](crate::file::FileStorage::fsck).
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of inconsistency found by a consistency check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsckIssueKind {
+    /// A config file could not be read or parsed
+    UnreadableFile,
+    /// An indexed config has no file on disk
+    MissingFile,
+    /// A config file on disk is not in the index
+    UnindexedFile,
+    /// The index and the config file disagree
+    IndexMismatch,
+    /// Version numbers are missing from the history
+    VersionGap,
+    /// The current version has no history entry
+    MissingLatestVersion,
+    /// The history entry of the current version holds a different value
+    LatestVersionMismatch,
+}
+
+impl FsckIssueKind {
+    /// Whether `fsck` can repair this kind of issue
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Self::UnreadableFile | Self::VersionGap)
+    }
+}
+
+/// A single inconsistency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsckIssue {
+    pub kind: FsckIssueKind,
+    /// Affected config (`namespace:key@env`) or file
+    pub subject: String,
+    pub detail: String,
+    /// Whether the issue was repaired
+    pub repaired: bool,
+}
+
+/// Result of a consistency check
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsckReport {
+    /// Number of configs checked
+    pub configs_checked: usize,
+    /// Number of version history entries checked
+    pub versions_checked: usize,
+    pub issues: Vec<FsckIssue>,
+}
+
+impl FsckReport {
+    /// Whether no inconsistencies were found
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of issues that are still present
+    pub fn unrepaired(&self) -> usize {
+        self.issues.iter().filter(|issue| !issue.repaired).count()
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        kind: FsckIssueKind,
+        subject: impl Into<String>,
+        detail: impl Into<String>,
+        repaired: bool,
+    ) {
+        self.issues.push(FsckIssue {
+            kind,
+            subject: subject.into(),
+            detail: detail.into(),
+            repaired,
+        });
+    }
+}
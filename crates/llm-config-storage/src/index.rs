@@ -0,0 +1,156 @@
+//! Persistent index of the file storage backend
+//!
+//! Loading every config file at startup is slow for large stores, so the
+//! index is saved to a single snapshot file. Every write of a config file is
+//! first recorded in a dirty journal (one file name per line, synced before
+//! the config file is written), and at startup only the files named in the
+//! journal since the snapshot are read again. The snapshot records the id of
+//! the journal and how much of it is covered; when they do not match, or the
+//! number of config files disagrees with the index, the index is rebuilt
+//! from the config files as before.
+//!
+//! Journal entries are idempotent (replaying one re-reads the file's current
+//! state), so a crash between writing a config file and saving a snapshot
+//! only costs a re-read at the next start.
+
+use crate::{ConfigEntry, Result, StorageError};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Snapshot of the index
+pub const INDEX_SNAPSHOT_FILE: &str = "index.snapshot.json";
+
+/// Journal of config files changed since the snapshot
+pub const INDEX_JOURNAL_FILE: &str = "index.journal";
+
+/// Current snapshot format
+const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Prefix of the journal's first line, which holds its id
+const JOURNAL_HEADER: &str = "journal ";
+
+/// Saved index
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IndexSnapshot {
+    pub format: u32,
+    /// Id of the journal this snapshot was taken against
+    pub journal_id: String,
+    /// Bytes of that journal already reflected in `entries`
+    pub journal_offset: u64,
+    pub entries: Vec<ConfigEntry>,
+}
+
+impl IndexSnapshot {
+    pub fn new(journal: &Journal, entries: Vec<ConfigEntry>) -> Self {
+        Self {
+            format: SNAPSHOT_FORMAT,
+            journal_id: journal.id.clone(),
+            journal_offset: journal.len,
+            entries,
+        }
+    }
+
+    /// Read the snapshot in `dir`, ignoring missing, unreadable and outdated ones
+    pub fn read(dir: &Path) -> Option<Self> {
+        let contents = fs::read(dir.join(INDEX_SNAPSHOT_FILE)).ok()?;
+        match serde_json::from_slice::<IndexSnapshot>(&contents) {
+            Ok(snapshot) if snapshot.format == SNAPSHOT_FORMAT => Some(snapshot),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable index snapshot: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Atomically replace the snapshot in `dir`
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        let json =
+            serde_json::to_vec(self).map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        let path = dir.join(INDEX_SNAPSHOT_FILE);
+        let temp_path = path.with_extension("tmp");
+        {
+            let mut temp_file = File::create(&temp_path)?;
+            temp_file.write_all(&json)?;
+            temp_file.sync_all()?;
+        }
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+}
+
+/// Contents of the dirty journal
+pub(crate) struct Journal {
+    pub id: String,
+    /// Length in bytes
+    pub len: u64,
+    contents: String,
+}
+
+impl Journal {
+    /// Read the journal in `dir`, starting a new one if there is none
+    pub fn read(dir: &Path) -> Result<Self> {
+        let path = dir.join(INDEX_JOURNAL_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::rotate(dir),
+            Err(e) => return Err(e.into()),
+        };
+        // A journal recreated by an append after it was removed has no id,
+        // so it never matches a snapshot and is replayed in full
+        let id = contents
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix(JOURNAL_HEADER))
+            .unwrap_or_default()
+            .to_string();
+        Ok(Self {
+            id,
+            len: contents.len() as u64,
+            contents,
+        })
+    }
+
+    /// Replace the journal in `dir` with an empty one with a new id
+    pub fn rotate(dir: &Path) -> Result<Self> {
+        let contents = format!("{}{}\n", JOURNAL_HEADER, Uuid::new_v4());
+        let path = dir.join(INDEX_JOURNAL_FILE);
+        let temp_path = path.with_extension("tmp");
+        {
+            let mut temp_file = File::create(&temp_path)?;
+            temp_file.write_all(contents.as_bytes())?;
+            temp_file.sync_all()?;
+        }
+        fs::rename(&temp_path, &path)?;
+        Self::read(dir)
+    }
+
+    /// Config file names recorded after `offset` bytes, without duplicates
+    pub fn files_since(&self, offset: u64) -> Vec<&str> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX);
+        let Some(tail) = self.contents.get(start..) else {
+            return Vec::new();
+        };
+        let mut files: Vec<&str> = tail
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with(JOURNAL_HEADER))
+            .collect();
+        files.sort_unstable();
+        files.dedup();
+        files
+    }
+
+    /// Durably record that a config file is about to change
+    pub fn append(dir: &Path, file_name: &str) -> Result<()> {
+        let mut journal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(INDEX_JOURNAL_FILE))?;
+        writeln!(journal, "{}", file_name)?;
+        journal.sync_data()?;
+        Ok(())
+    }
+}
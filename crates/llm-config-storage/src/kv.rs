@@ -0,0 +1,201 @@
+//! Key-value stores shared between replicas
+//!
+//! A [`KvStore`] holds one key per config entry (see [`entry_key`]). Every
+//! key has a modify index taken from an index that grows with each change to
+//! the store; writes are compare-and-swap on it, and [`KvStore::list`] blocks
+//! until the store changes past a given index, so replicas learn about each
+//! other's writes as they happen instead of polling.
+//!
+//! [`MemoryKv`] keeps the keys in memory, for tests and replicas in one
+//! process. `ConsulKv` (feature `consul`) uses Consul's KV API.
+
+#[cfg(feature = "consul")]
+pub mod consul;
+
+use crate::{Environment, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default prefix of the keys of a store
+pub const DEFAULT_KV_PREFIX: &str = "llm-config";
+
+/// A key, its value and the index of its last change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KvPair {
+    pub key: String,
+    pub value: Vec<u8>,
+    pub modify_index: u64,
+}
+
+/// The pairs under a prefix as of an index of the store
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KvList {
+    /// Index to pass to the next [`KvStore::list`] to wait for changes after this one
+    pub index: u64,
+    pub pairs: Vec<KvPair>,
+}
+
+/// Key-value store with compare-and-swap writes and blocking reads
+pub trait KvStore: Send + Sync + fmt::Debug {
+    /// The pair stored under `key`
+    fn get(&self, key: &str) -> Result<Option<KvPair>>;
+
+    /// The pairs under `prefix`, once the store has changed after `index`
+    ///
+    /// Returns at once when `index` is 0 or the store already changed;
+    /// otherwise waits up to `wait` and returns the unchanged pairs.
+    fn list(&self, prefix: &str, index: u64, wait: Duration) -> Result<KvList>;
+
+    /// Store `value` under `key` if its modify index is still `index` (0 if
+    /// the key must not exist yet); returns whether it was stored
+    fn cas(&self, key: &str, value: &[u8], index: u64) -> Result<bool>;
+
+    /// Delete `key` if its modify index is still `index`; returns whether it was deleted
+    fn delete_cas(&self, key: &str, index: u64) -> Result<bool>;
+}
+
+/// Key of a config entry, `prefix/namespace/env/key`
+///
+/// `%` and `/` in the namespace and key are percent-encoded, so distinct
+/// entries never share a key and a namespace's keys share its prefix.
+pub fn entry_key(prefix: &str, namespace: &str, key: &str, env: Environment) -> String {
+    let escape = |part: &str| part.replace('%', "%25").replace('/', "%2F");
+    format!("{}/{}/{}/{}", prefix.trim_end_matches('/'), escape(namespace), env, escape(key))
+}
+
+/// In-memory [`KvStore`]
+#[derive(Debug, Default)]
+pub struct MemoryKv {
+    state: Mutex<MemoryState>,
+    changed: Condvar,
+}
+
+#[derive(Debug)]
+struct MemoryState {
+    index: u64,
+    pairs: BTreeMap<String, (Vec<u8>, u64)>,
+}
+
+impl Default for MemoryState {
+    fn default() -> Self {
+        // Index 0 asks for the pairs without waiting
+        Self {
+            index: 1,
+            pairs: BTreeMap::new(),
+        }
+    }
+}
+
+impl MemoryKv {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn pair(key: &str, (value, modify_index): &(Vec<u8>, u64)) -> KvPair {
+        KvPair {
+            key: key.to_string(),
+            value: value.clone(),
+            modify_index: *modify_index,
+        }
+    }
+}
+
+impl KvStore for MemoryKv {
+    fn get(&self, key: &str) -> Result<Option<KvPair>> {
+        let state = self.state.lock().unwrap();
+        Ok(state.pairs.get(key).map(|pair| Self::pair(key, pair)))
+    }
+
+    fn list(&self, prefix: &str, index: u64, wait: Duration) -> Result<KvList> {
+        let deadline = Instant::now() + wait;
+        let mut state = self.state.lock().unwrap();
+        while index != 0 && state.index <= index {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self.changed.wait_timeout(state, deadline - now).unwrap().0;
+        }
+
+        let pairs = state
+            .pairs
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, pair)| Self::pair(key, pair))
+            .collect();
+        Ok(KvList {
+            index: state.index,
+            pairs,
+        })
+    }
+
+    fn cas(&self, key: &str, value: &[u8], index: u64) -> Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        if state.pairs.get(key).map_or(0, |(_, modify_index)| *modify_index) != index {
+            return Ok(false);
+        }
+        state.index += 1;
+        let modify_index = state.index;
+        state.pairs.insert(key.to_string(), (value.to_vec(), modify_index));
+        self.changed.notify_all();
+        Ok(true)
+    }
+
+    fn delete_cas(&self, key: &str, index: u64) -> Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        match state.pairs.get(key) {
+            Some((_, modify_index)) if *modify_index == index => {}
+            _ => return Ok(false),
+        }
+        state.pairs.remove(key);
+        state.index += 1;
+        self.changed.notify_all();
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_entry_key() {
+        let env = Environment::Production;
+        assert_eq!(entry_key("llm-config/", "app/llm", "api_key", env), "llm-config/app%2Fllm/production/api_key");
+        assert_ne!(entry_key("p", "a/b", "c", env), entry_key("p", "a", "b/c", env));
+    }
+
+    #[test]
+    fn test_memory_kv_cas_and_watch() {
+        let kv = Arc::new(MemoryKv::new());
+        assert!(kv.cas("p/a", b"1", 0).unwrap());
+        assert!(!kv.cas("p/a", b"2", 0).unwrap());
+        let pair = kv.get("p/a").unwrap().unwrap();
+        assert!(kv.cas("p/a", b"2", pair.modify_index).unwrap());
+        assert!(!kv.delete_cas("p/a", pair.modify_index).unwrap());
+        kv.cas("q/b", b"3", 0).unwrap();
+
+        let list = kv.list("p/", 0, Duration::ZERO).unwrap();
+        assert_eq!(list.pairs.len(), 1);
+        assert_eq!(list.pairs[0].value, b"2");
+
+        // Nothing changed: the list returns after the wait
+        let unchanged = kv.list("p/", list.index, Duration::from_millis(10)).unwrap();
+        assert_eq!(unchanged.index, list.index);
+
+        let writer = Arc::clone(&kv);
+        let modify_index = list.pairs[0].modify_index;
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            writer.delete_cas("p/a", modify_index).unwrap()
+        });
+        let changed = kv.list("p/", list.index, Duration::from_secs(10)).unwrap();
+        assert!(handle.join().unwrap());
+        assert!(changed.index > list.index);
+        assert!(changed.pairs.is_empty());
+    }
+}
@@ -0,0 +1,387 @@
+//! Consul KV store
+//!
+//! Speaks Consul's HTTP KV API (`/v1/kv`) over plain TCP, one HTTP/1.0
+//! request per connection. Writes use Consul's `?cas=` check-and-set, and
+//! [`KvStore::list`] is a blocking query on the prefix, which Consul answers
+//! as soon as a key under it changes.
+
+use super::{KvList, KvPair, KvStore};
+use crate::{Result, StorageError};
+use base64::Engine;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use url::Url;
+
+/// Header carrying the index of a blocking query's result
+const INDEX_HEADER: &str = "x-consul-index";
+
+/// Longest wait Consul accepts for a blocking query
+const MAX_WAIT: Duration = Duration::from_secs(600);
+
+/// Consul agent address and credentials
+#[derive(Debug, Clone)]
+pub struct ConsulConfig {
+    /// `http://host[:port]` of a Consul agent
+    pub url: String,
+    /// ACL token (`X-Consul-Token`)
+    pub token: Option<String>,
+    /// Datacenter to use instead of the agent's
+    pub datacenter: Option<String>,
+    /// Connect, read and write timeout, on top of the wait of blocking queries
+    pub timeout: Duration,
+}
+
+impl ConsulConfig {
+    /// Create a configuration for an agent URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            token: None,
+            datacenter: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Authenticate with an ACL token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Use the KV store of another datacenter
+    pub fn with_datacenter(mut self, datacenter: impl Into<String>) -> Self {
+        self.datacenter = Some(datacenter.into());
+        self
+    }
+
+    /// Set the connect, read and write timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// [`KvStore`] backed by Consul
+#[derive(Debug, Clone)]
+pub struct ConsulKv {
+    config: ConsulConfig,
+    host: String,
+    port: u16,
+}
+
+/// A key as Consul returns it
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ConsulPair {
+    key: String,
+    /// Base64-encoded value (`null` for empty values)
+    value: Option<String>,
+    modify_index: u64,
+}
+
+/// Status, `X-Consul-Index` and body of a response
+struct Response {
+    status: u16,
+    index: Option<u64>,
+    body: Vec<u8>,
+}
+
+impl ConsulKv {
+    /// Create a store talking to the agent in `config`
+    pub fn new(config: ConsulConfig) -> Result<Self> {
+        let invalid = |reason: &str| StorageError::InvalidPath(format!("Invalid Consul URL {}: {}", config.url, reason));
+        let url = Url::parse(&config.url).map_err(|e| invalid(&e.to_string()))?;
+        if url.scheme() != "http" {
+            return Err(invalid("expected http:// scheme"));
+        }
+        let host = url.host_str().ok_or_else(|| invalid("missing host"))?.to_string();
+        let port = url.port().unwrap_or(8500);
+        Ok(Self { config, host, port })
+    }
+
+    /// Send a request for `key` and read the whole response
+    fn request(&self, method: &str, key: &str, query: &[(&str, String)], body: &[u8], wait: Duration) -> Result<Response> {
+        let mut path = format!("/v1/kv/{}", encode(key));
+        let datacenter = self.config.datacenter.iter().map(|dc| ("dc", dc.clone()));
+        for (i, (name, value)) in query.iter().cloned().chain(datacenter).enumerate() {
+            path.push(if i == 0 { '?' } else { '&' });
+            path.push_str(&format!("{}={}", name, encode(&value)));
+        }
+
+        let mut request = format!("{} {} HTTP/1.0\r\nHost: {}:{}\r\n", method, path, self.host, self.port);
+        if let Some(token) = &self.config.token {
+            request.push_str(&format!("X-Consul-Token: {}\r\n", token));
+        }
+        request.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| StorageError::BackendError(format!("Cannot resolve {}", self.host)))?;
+        let mut stream = TcpStream::connect_timeout(&address, self.config.timeout)?;
+        // Consul adds up to wait / 16 of jitter to blocking queries
+        stream.set_read_timeout(Some(self.config.timeout + wait + wait / 16))?;
+        stream.set_write_timeout(Some(self.config.timeout))?;
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        parse_response(&response)
+    }
+
+    /// Fail on statuses other than 200 (and 404, which callers handle)
+    fn check(method: &str, key: &str, response: &Response) -> Result<()> {
+        match response.status {
+            200 | 404 => Ok(()),
+            status => Err(StorageError::BackendError(format!(
+                "Consul {} {} failed with {}: {}",
+                method,
+                key,
+                status,
+                String::from_utf8_lossy(&response.body).trim()
+            ))),
+        }
+    }
+
+    /// Pairs of a `GET` response
+    fn pairs(response: &Response) -> Result<Vec<KvPair>> {
+        if response.status == 404 {
+            return Ok(Vec::new());
+        }
+        let pairs: Vec<ConsulPair> =
+            serde_json::from_slice(&response.body).map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        pairs
+            .into_iter()
+            .map(|pair| {
+                let value = match pair.value {
+                    Some(value) => base64::engine::general_purpose::STANDARD
+                        .decode(value)
+                        .map_err(|e| StorageError::SerializationError(e.to_string()))?,
+                    None => Vec::new(),
+                };
+                Ok(KvPair {
+                    key: pair.key,
+                    value,
+                    modify_index: pair.modify_index,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether a check-and-set request succeeded (Consul answers `true` or `false`)
+    fn succeeded(method: &str, key: &str, response: &Response) -> Result<bool> {
+        Self::check(method, key, response)?;
+        Ok(response.status == 200 && String::from_utf8_lossy(&response.body).trim() == "true")
+    }
+}
+
+impl KvStore for ConsulKv {
+    fn get(&self, key: &str) -> Result<Option<KvPair>> {
+        let response = self.request("GET", key, &[], &[], Duration::ZERO)?;
+        Self::check("GET", key, &response)?;
+        Ok(Self::pairs(&response)?.into_iter().find(|pair| pair.key == key))
+    }
+
+    fn list(&self, prefix: &str, index: u64, wait: Duration) -> Result<KvList> {
+        let wait = if index == 0 { Duration::ZERO } else { wait.min(MAX_WAIT) };
+        let mut query = vec![("recurse", "true".to_string())];
+        if index != 0 {
+            query.push(("index", index.to_string()));
+            query.push(("wait", format!("{}ms", wait.as_millis())));
+        }
+        let response = self.request("GET", prefix, &query, &[], wait)?;
+        Self::check("GET", prefix, &response)?;
+        Ok(KvList {
+            // Consul indexes only grow; a reset (e.g. a restored snapshot) restarts the watch
+            index: response.index.filter(|&new| new >= index).unwrap_or(0),
+            pairs: Self::pairs(&response)?,
+        })
+    }
+
+    fn cas(&self, key: &str, value: &[u8], index: u64) -> Result<bool> {
+        let response = self.request("PUT", key, &[("cas", index.to_string())], value, Duration::ZERO)?;
+        Self::succeeded("PUT", key, &response)
+    }
+
+    fn delete_cas(&self, key: &str, index: u64) -> Result<bool> {
+        let response = self.request("DELETE", key, &[("cas", index.to_string())], &[], Duration::ZERO)?;
+        Self::succeeded("DELETE", key, &response)
+    }
+}
+
+/// Percent-encode a key or query value, keeping `/` between path segments
+fn encode(part: &str) -> String {
+    let mut encoded = String::new();
+    for byte in part.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(byte as char),
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Parse an HTTP/1.0 response read to the end of the connection
+fn parse_response(response: &[u8]) -> Result<Response> {
+    let malformed = || StorageError::BackendError("Malformed response from Consul".to_string());
+    let split = response.windows(4).position(|w| w == b"\r\n\r\n").ok_or_else(malformed)?;
+    let head = std::str::from_utf8(&response[..split]).map_err(|_| malformed())?;
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(malformed)?;
+    let index = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(INDEX_HEADER))
+        .and_then(|(_, value)| value.trim().parse().ok());
+    Ok(Response {
+        status,
+        index,
+        body: response[split + 4..].to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::MemoryKv;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    /// Start a Consul agent stand-in serving `/v1/kv` from a [`MemoryKv`]
+    fn fake_consul() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let kv = Arc::new(MemoryKv::new());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let kv = Arc::clone(&kv);
+                std::thread::spawn(move || serve(stream.unwrap(), &kv));
+            }
+        });
+        url
+    }
+
+    fn serve(stream: TcpStream, kv: &MemoryKv) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let mut parts = line.split_whitespace();
+        let (method, target) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+
+        let mut length = 0;
+        let mut token = None;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':').unwrap();
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => length = value.trim().parse().unwrap(),
+                "x-consul-token" => token = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+        let key = decode(path.strip_prefix("/v1/kv/").unwrap());
+        let query: HashMap<&str, String> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(name, value)| (name, decode(value)))
+            .collect();
+        let index = |name: &str| query.get(name).map_or(0, |value| value.parse().unwrap());
+
+        let (status, consul_index, body) = if token.as_deref() != Some("secret") {
+            (403, 0, b"Permission denied".to_vec())
+        } else {
+            match method.as_str() {
+                "GET" => {
+                    let wait = Duration::from_millis(
+                        query.get("wait").map_or(0, |wait| wait.trim_end_matches("ms").parse().unwrap()),
+                    );
+                    let list = kv.list(&key, index("index"), wait).unwrap();
+                    let pairs: Vec<_> = list
+                        .pairs
+                        .iter()
+                        .filter(|pair| query.contains_key("recurse") || pair.key == key)
+                        .map(|pair| {
+                            serde_json::json!({
+                                "Key": pair.key,
+                                "Value": base64::engine::general_purpose::STANDARD.encode(&pair.value),
+                                "ModifyIndex": pair.modify_index,
+                            })
+                        })
+                        .collect();
+                    match pairs.is_empty() {
+                        true => (404, list.index, Vec::new()),
+                        false => (200, list.index, serde_json::to_vec(&pairs).unwrap()),
+                    }
+                }
+                "PUT" => (200, 0, kv.cas(&key, &body, index("cas")).unwrap().to_string().into_bytes()),
+                "DELETE" => (200, 0, kv.delete_cas(&key, index("cas")).unwrap().to_string().into_bytes()),
+                _ => (405, 0, Vec::new()),
+            }
+        };
+
+        let mut writer = stream;
+        let head = format!("HTTP/1.0 {} OK\r\nX-Consul-Index: {}\r\n\r\n", status, consul_index);
+        writer.write_all(head.as_bytes()).unwrap();
+        writer.write_all(&body).unwrap();
+    }
+
+    fn decode(part: &str) -> String {
+        let bytes = part.as_bytes();
+        let mut decoded = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' {
+                decoded.push(u8::from_str_radix(&part[i + 1..i + 3], 16).unwrap());
+                i += 3;
+            } else {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(decoded).unwrap()
+    }
+
+    #[test]
+    fn test_consul_kv() {
+        let url = fake_consul();
+        let kv = ConsulKv::new(ConsulConfig::new(&url).with_token("secret")).unwrap();
+        let key = "llm-config/app%2Fllm/production/api key";
+
+        assert!(kv.get(key).unwrap().is_none());
+        assert!(kv.cas(key, b"{\"v\":1}", 0).unwrap());
+        assert!(!kv.cas(key, b"{\"v\":2}", 0).unwrap());
+        let pair = kv.get(key).unwrap().unwrap();
+        assert_eq!(pair.key, key);
+        assert_eq!(pair.value, b"{\"v\":1}");
+
+        let list = kv.list("llm-config/", 0, Duration::ZERO).unwrap();
+        assert_eq!(list.pairs, vec![pair.clone()]);
+        let unchanged = kv.list("llm-config/", list.index, Duration::from_millis(10)).unwrap();
+        assert_eq!(unchanged.index, list.index);
+
+        assert!(kv.delete_cas(key, pair.modify_index).unwrap());
+        let changed = kv.list("llm-config/", list.index, Duration::from_secs(10)).unwrap();
+        assert!(changed.index > list.index && changed.pairs.is_empty());
+
+        let denied = ConsulKv::new(ConsulConfig::new(&url).with_token("wrong")).unwrap();
+        assert!(matches!(denied.get(key), Err(StorageError::BackendError(_))));
+        assert!(ConsulKv::new(ConsulConfig::new("https://consul.internal")).is_err());
+    }
+}
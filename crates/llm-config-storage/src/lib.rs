@@ -1,9 +1,31 @@
 //! Storage backend for LLM Config Manager
 
+pub mod blobs;
+pub mod compaction;
+pub mod csv;
+pub mod dotenv;
 pub mod file;
+pub mod fsck;
+pub mod index;
+pub mod kv;
 pub mod models;
+pub mod patterns;
+pub mod retention;
+pub mod types;
+pub mod wal;
 
+pub use blobs::{BlobStats, DedupSettings, DEFAULT_DEDUP_MIN_SIZE};
+pub use compaction::CompactionReport;
+pub use fsck::{FsckIssue, FsckIssueKind, FsckReport};
+pub use kv::{entry_key, KvList, KvPair, KvStore, MemoryKv, DEFAULT_KV_PREFIX};
+#[cfg(feature = "consul")]
+pub use kv::consul::{ConsulConfig, ConsulKv};
 pub use models::*;
+pub use patterns::namespace_matches;
+pub use retention::{
+    AuditRetention, NamespaceRetention, NamespaceRule, RetentionConfig, RetentionReport, RETENTION_FILE,
+};
+pub use types::ValueType;
 
 use thiserror::Error;
 
@@ -24,8 +46,26 @@ pub enum StorageError {
     #[error("Invalid path: {0}")]
     InvalidPath(String),
 
+    #[error("Backend error: {0}")]
+    BackendError(String),
+
     #[error("Crypto error: {0}")]
     CryptoError(#[from] llm_config_crypto::CryptoError),
 }
 
+impl StorageError {
+    /// Short snake_case name of the error variant, used as a metrics label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "io",
+            Self::SerializationError(_) => "serialization",
+            Self::NotFound(_) => "not_found",
+            Self::AlreadyExists(_) => "already_exists",
+            Self::InvalidPath(_) => "invalid_path",
+            Self::BackendError(_) => "backend",
+            Self::CryptoError(_) => "crypto",
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, StorageError>;
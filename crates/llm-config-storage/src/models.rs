@@ -1,13 +1,18 @@
 //! Core storage models
 
+use crate::types::{format_duration, format_size, parse_duration, parse_size, parse_url};
 use chrono::{DateTime, Utc};
 use llm_config_crypto::EncryptedData;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+use url::Url;
 use uuid::Uuid;
 
 /// Environment type for configuration
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Environments are ordered from most general (`Base`) to most specific.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Environment {
     Base,
@@ -44,7 +49,14 @@ impl std::fmt::Display for Environment {
     }
 }
 
-/// Configuration value that can be a simple type or a secret
+/// Placeholder shown in place of secret values
+pub const REDACTED_SECRET: &str = "<encrypted>";
+
+/// Configuration value that can be a simple type, a typed value or a secret
+///
+/// Untagged variants are tried in order, so typed values and `Secret` come
+/// before `Object`: otherwise they would load back as plain objects. See
+/// [`crate::types`] for how durations, sizes and URLs are parsed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ConfigValue {
@@ -53,8 +65,12 @@ pub enum ConfigValue {
     Float(f64),
     Boolean(bool),
     Array(Vec<ConfigValue>),
-    Object(HashMap<String, ConfigValue>),
+    Duration(#[serde(with = "crate::types::duration_repr")] Duration),
+    /// Size in bytes
+    Size(#[serde(with = "crate::types::size_repr")] u64),
+    Url(#[serde(with = "crate::types::url_repr")] Url),
     Secret(EncryptedData),
+    Object(HashMap<String, ConfigValue>),
 }
 
 impl ConfigValue {
@@ -63,6 +79,47 @@ impl ConfigValue {
         matches!(self, ConfigValue::Secret(_))
     }
 
+    /// Copy of the value with secrets replaced by [`REDACTED_SECRET`]
+    pub fn redacted(&self) -> ConfigValue {
+        match self {
+            ConfigValue::Secret(_) => ConfigValue::String(REDACTED_SECRET.to_string()),
+            ConfigValue::Array(items) => {
+                ConfigValue::Array(items.iter().map(ConfigValue::redacted).collect())
+            }
+            ConfigValue::Object(map) => ConfigValue::Object(
+                map.iter().map(|(k, v)| (k.clone(), v.redacted())).collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Name of the value's type
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ConfigValue::String(_) => "string",
+            ConfigValue::Integer(_) => "integer",
+            ConfigValue::Float(_) => "float",
+            ConfigValue::Boolean(_) => "boolean",
+            ConfigValue::Array(_) => "array",
+            ConfigValue::Object(_) => "object",
+            ConfigValue::Duration(_) => "duration",
+            ConfigValue::Size(_) => "size",
+            ConfigValue::Url(_) => "url",
+            ConfigValue::Secret(_) => "secret",
+        }
+    }
+
+    /// Check whether replacing this value with `other` keeps its type
+    ///
+    /// Secrets decrypt to strings, so strings and secrets are interchangeable.
+    pub fn same_type_as(&self, other: &ConfigValue) -> bool {
+        let kind = |value: &ConfigValue| match value {
+            ConfigValue::Secret(_) => "string",
+            other => other.type_name(),
+        };
+        kind(self) == kind(other)
+    }
+
     /// Get as string if possible
     pub fn as_str(&self) -> Option<&str> {
         match self {
@@ -94,6 +151,66 @@ impl ConfigValue {
             _ => None,
         }
     }
+
+    /// Get as duration if possible, parsing strings such as `"30s"`
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            ConfigValue::Duration(d) => Some(*d),
+            ConfigValue::String(s) => parse_duration(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get as a size in bytes if possible, parsing strings such as `"512MiB"`
+    pub fn as_bytes(&self) -> Option<u64> {
+        match self {
+            ConfigValue::Size(bytes) => Some(*bytes),
+            ConfigValue::Integer(i) => u64::try_from(*i).ok(),
+            ConfigValue::String(s) => parse_size(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get as URL if possible, parsing strings
+    pub fn as_url(&self) -> Option<Url> {
+        match self {
+            ConfigValue::Url(url) => Some(url.clone()),
+            ConfigValue::String(s) => parse_url(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Text of a typed value as it was parsed (e.g. `"1h30m"`, `"512MiB"`)
+    pub fn typed_text(&self) -> Option<String> {
+        match self {
+            ConfigValue::Duration(d) => Some(format_duration(*d)),
+            ConfigValue::Size(bytes) => Some(format_size(*bytes)),
+            ConfigValue::Url(url) => Some(url.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// JSON values map onto their natural types, arrays and objects recursively;
+/// `null` becomes an empty string
+impl From<serde_json::Value> for ConfigValue {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(s) => ConfigValue::String(s),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => ConfigValue::Integer(i),
+                None => ConfigValue::Float(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::Bool(b) => ConfigValue::Boolean(b),
+            serde_json::Value::Array(items) => {
+                ConfigValue::Array(items.into_iter().map(ConfigValue::from).collect())
+            }
+            serde_json::Value::Object(map) => ConfigValue::Object(
+                map.into_iter().map(|(k, v)| (k, ConfigValue::from(v))).collect(),
+            ),
+            serde_json::Value::Null => ConfigValue::String(String::new()),
+        }
+    }
 }
 
 /// Configuration metadata
@@ -107,6 +224,59 @@ pub struct ConfigMetadata {
     pub tags: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Where the last write came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<OperationContext>,
+    /// How the value combines with the value inherited from a more general
+    /// environment (the namespace default when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge: Option<MergeStrategy>,
+    /// Signature over the entry by the writer's signing key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<EntrySignature>,
+}
+
+/// Ed25519 signature over the canonical JSON of an entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntrySignature {
+    /// Identity of the signer
+    pub signer: String,
+    /// ID of the key that made the signature
+    pub key_id: String,
+    /// Signature, base64 encoded
+    pub signature: String,
+}
+
+/// How an environment override combines with the inherited value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// The override replaces the inherited value
+    #[default]
+    Replace,
+    /// Objects are merged key by key, recursively; anything else is replaced
+    DeepMerge,
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "replace" => Ok(MergeStrategy::Replace),
+            "deep-merge" | "merge" => Ok(MergeStrategy::DeepMerge),
+            _ => Err(format!("Unknown merge strategy: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeStrategy::Replace => write!(f, "replace"),
+            MergeStrategy::DeepMerge => write!(f, "deep-merge"),
+        }
+    }
 }
 
 impl Default for ConfigMetadata {
@@ -119,10 +289,64 @@ impl Default for ConfigMetadata {
             updated_by: "system".to_string(),
             tags: Vec::new(),
             description: None,
+            context: None,
+            merge: None,
+            signature: None,
         }
     }
 }
 
+/// Request context of a write, recorded alongside the user who made it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationContext {
+    /// Client IP address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Request ID for correlation with API and audit logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// SPIFFE ID of the calling workload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spiffe_id: Option<String>,
+    /// Git commit a GitOps sync applied the value from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+}
+
+impl OperationContext {
+    /// Set the client IP address
+    pub fn with_ip_address(mut self, ip: impl Into<String>) -> Self {
+        self.ip_address = Some(ip.into());
+        self
+    }
+
+    /// Set the session ID
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Set the request ID
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Set the calling workload's SPIFFE ID
+    pub fn with_spiffe_id(mut self, spiffe_id: impl Into<String>) -> Self {
+        self.spiffe_id = Some(spiffe_id.into());
+        self
+    }
+
+    /// Set the Git commit the change was synced from
+    pub fn with_git_commit(mut self, commit: impl Into<String>) -> Self {
+        self.git_commit = Some(commit.into());
+        self
+    }
+}
+
 /// A configuration entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigEntry {
@@ -173,6 +397,91 @@ pub struct VersionEntry {
     pub change_description: Option<String>,
 }
 
+/// Page of a config's version history to read
+///
+/// Versions are returned newest first; pass the last version of a page as
+/// `before_version` to read the next one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryOptions {
+    /// Return at most this many versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Only return versions older than this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before_version: Option<u64>,
+}
+
+impl HistoryOptions {
+    /// Return at most `limit` versions
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only return versions older than `version`
+    pub fn with_before_version(mut self, version: u64) -> Self {
+        self.before_version = Some(version);
+        self
+    }
+}
+
+/// How much version history to keep per config
+///
+/// A version is kept while it is among the last `keep_last` versions or
+/// younger than `keep_days` days. The latest version of a config is always
+/// kept, and a policy with neither limit keeps everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Keep this many most recent versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+    /// Keep versions created within this many days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_days: Option<u32>,
+}
+
+impl RetentionPolicy {
+    /// Keep the last `count` versions of every config
+    pub fn keep_last(count: usize) -> Self {
+        Self {
+            keep_last: Some(count),
+            keep_days: None,
+        }
+    }
+
+    /// Keep the versions of the last `days` days
+    pub fn keep_days(days: u32) -> Self {
+        Self {
+            keep_last: None,
+            keep_days: Some(days),
+        }
+    }
+
+    /// Also keep the last `count` versions
+    pub fn with_keep_last(mut self, count: usize) -> Self {
+        self.keep_last = Some(count);
+        self
+    }
+
+    /// Also keep the versions of the last `days` days
+    pub fn with_keep_days(mut self, days: u32) -> Self {
+        self.keep_days = Some(days);
+        self
+    }
+
+    /// Whether the version at `position` (0 = latest) created at `created_at` is kept
+    pub fn retains(&self, position: usize, created_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        if position == 0 || (self.keep_last.is_none() && self.keep_days.is_none()) {
+            return true;
+        }
+        let recent = self.keep_last.is_some_and(|count| position < count);
+        let young = self
+            .keep_days
+            .is_some_and(|days| created_at > now - chrono::Duration::days(days.into()));
+        recent || young
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,6 +503,45 @@ mod tests {
 
         let bool_val = ConfigValue::Boolean(true);
         assert_eq!(bool_val.as_bool(), Some(true));
+
+        assert_eq!(int_val.redacted().as_i64(), Some(42));
+
+        assert_eq!(int_val.type_name(), "integer");
+        assert!(int_val.same_type_as(&ConfigValue::Integer(7)));
+        assert!(!int_val.same_type_as(&string_val));
+        assert!(!int_val.same_type_as(&ConfigValue::Float(42.0)));
+    }
+
+    #[test]
+    fn test_secret_roundtrip() {
+        let key = llm_config_crypto::SecretKey::generate(llm_config_crypto::Algorithm::Aes256Gcm)
+            .unwrap();
+        let secret = ConfigValue::Secret(llm_config_crypto::encrypt(&key, b"sk-123", None).unwrap());
+
+        let json = serde_json::to_string(&secret).unwrap();
+        let loaded: ConfigValue = serde_json::from_str(&json).unwrap();
+        assert!(loaded.is_secret());
+
+        let object: ConfigValue = serde_json::from_str(r#"{"model": "gpt-4"}"#).unwrap();
+        assert_eq!(object.type_name(), "object");
+    }
+
+    #[test]
+    fn test_retention_policy() {
+        let now = Utc::now();
+        let old = now - chrono::Duration::days(30);
+
+        assert!(RetentionPolicy::default().retains(100, old, now));
+        assert!(RetentionPolicy::keep_last(0).retains(0, old, now));
+
+        let policy = RetentionPolicy::keep_last(2);
+        assert!(policy.retains(1, old, now));
+        assert!(!policy.retains(2, now, now));
+
+        let policy = RetentionPolicy::keep_days(7).with_keep_last(2);
+        assert!(policy.retains(5, now, now));
+        assert!(policy.retains(1, old, now));
+        assert!(!policy.retains(2, old, now));
     }
 
     #[test]
@@ -0,0 +1,104 @@
+//! Namespace pattern matching
+//!
+//! The one dialect of namespace globs, shared by role scopes, workload
+//! identity mappings and retention rules, and the escaping of literal text
+//! for Redis `SCAN MATCH` globs. Namespace patterns are matched segment by
+//! segment (segments are separated by `/`):
+//! - `*` within a segment matches any characters in that segment
+//! - `*` as the final segment matches one or more trailing segments, so
+//!   `team-a/*` covers everything below `team-a`
+//! - `**` as a whole segment matches zero or more segments
+
+/// Check whether a namespace matches a glob pattern
+pub fn namespace_matches(pattern: &str, namespace: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let namespace: Vec<&str> = namespace.split('/').collect();
+    match_segments(&pattern, &namespace)
+}
+
+/// Escape glob metacharacters so a Redis `SCAN MATCH` pattern matches
+/// `literal` as is (e.g. a key prefix)
+pub fn escape_glob(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn match_segments(pattern: &[&str], namespace: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => namespace.is_empty(),
+        Some((&"*", [])) => !namespace.is_empty(),
+        Some((&"**", rest)) => (0..=namespace.len()).any(|i| match_segments(rest, &namespace[i..])),
+        Some((segment_pattern, rest)) => match namespace.split_first() {
+            Some((segment, namespace_rest)) => {
+                segment_matches(segment_pattern, segment) && match_segments(rest, namespace_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn segment_matches(pattern: &str, segment: &str) -> bool {
+    if !pattern.contains('*') {
+        return pattern == segment;
+    }
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, rest) = parts.split_first().expect("split yields at least one part");
+    let (last, middle) = rest.split_last().expect("pattern contains '*'");
+
+    let Some(mut remaining) = segment.strip_prefix(first) else {
+        return false;
+    };
+
+    for part in middle {
+        match remaining.find(part) {
+            Some(idx) => remaining = &remaining[idx + part.len()..],
+            None => return false,
+        }
+    }
+
+    remaining.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_match() {
+        assert!(namespace_matches("team-a/prompts", "team-a/prompts"));
+        assert!(!namespace_matches("team-a/prompts", "team-a/models"));
+        assert!(!namespace_matches("team-a", "team-a/prompts"));
+    }
+
+    #[test]
+    fn test_single_segment_wildcard() {
+        assert!(namespace_matches("*/prompts/*", "team-a/prompts/summarize"));
+        assert!(namespace_matches("*/prompts/*", "team-a/prompts/summarize/v2"));
+        assert!(!namespace_matches("*/prompts/*", "team-a/prompts"));
+        assert!(!namespace_matches("*/prompts/*", "org/team-a/prompts/summarize"));
+        assert!(!namespace_matches("*/prompts/*", "team-a/models/summarize"));
+        assert!(namespace_matches("team-*", "team-a"));
+        assert!(!namespace_matches("team-*", "ops"));
+    }
+
+    #[test]
+    fn test_multi_segment_wildcard() {
+        assert!(namespace_matches("team-a/**", "team-a"));
+        assert!(namespace_matches("team-a/**", "team-a/prompts/summarize"));
+        assert!(namespace_matches("**/prompts/**", "org/team-a/prompts/v2"));
+        assert!(!namespace_matches("team-a/**", "team-b/prompts"));
+    }
+
+    #[test]
+    fn test_escape_glob() {
+        assert_eq!(escape_glob("llm-config:"), "llm-config:");
+        assert_eq!(escape_glob("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\");
+    }
+}
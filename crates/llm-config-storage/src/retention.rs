@@ -0,0 +1,215 @@
+//! Retention settings of a store: `retention.toml`
+//!
+//! The file in the storage directory says how long data is kept, per
+//! namespace pattern:
+//!
+//! ```toml
+//! # Namespaces no rule matches
+//! [default]
+//! history = { keep_last = 50 }
+//! trash_days = 90
+//!
+//! # The most specific matching pattern wins; settings it leaves out come
+//! # from [default]
+//! [[namespace]]
+//! pattern = "app/llm/*"
+//! history = { keep_last = 10, keep_days = 30 }
+//! trash_days = 7
+//!
+//! [audit]
+//! keep_days = 365
+//! ```
+//!
+//! `history` is the [`RetentionPolicy`] of the version history of live
+//! configs. `trash_days` is how long the history of a deleted config is kept
+//! after its last version, so it can still be restored. Patterns are
+//! namespace globs as in role scopes (see [`namespace_matches`]): `app/*`
+//! covers everything below `app`, `app/**` also `app` itself, and `*` every
+//! namespace. The audit log is hash chained, so it is only pruned from its
+//! start, for the whole store (`[audit] keep_days`).
+//!
+//! Nothing is pruned while the file does not exist; see
+//! [`FileStorage::apply_retention`](crate::file::FileStorage::apply_retention).
+
+use crate::patterns::namespace_matches;
+use crate::{Result, RetentionPolicy, StorageError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// File name of the retention settings in the storage directory
+pub const RETENTION_FILE: &str = "retention.toml";
+
+/// Retention settings of a store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Retention of namespaces no rule matches
+    #[serde(default)]
+    pub default: NamespaceRetention,
+    /// Per-namespace rules
+    #[serde(default, rename = "namespace", skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<NamespaceRule>,
+    #[serde(default)]
+    pub audit: AuditRetention,
+}
+
+/// How long the history of a namespace's configs is kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceRetention {
+    /// Version history of live configs (kept in full when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<RetentionPolicy>,
+    /// Days the history of deleted configs is kept (kept forever when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash_days: Option<u32>,
+}
+
+/// Retention of the namespaces matching a pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceRule {
+    pub pattern: String,
+    #[serde(flatten)]
+    pub retention: NamespaceRetention,
+}
+
+/// How long audit events are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRetention {
+    /// Days audit events are kept (kept forever when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_days: Option<u32>,
+}
+
+/// What [`FileStorage::apply_retention`](crate::file::FileStorage::apply_retention) removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// Versions of live configs removed by their `history` policy
+    pub pruned_versions: usize,
+    /// Histories of deleted configs removed after `trash_days`
+    pub purged_histories: usize,
+}
+
+impl RetentionConfig {
+    /// Read the settings of the store in `dir` (the defaults if it has none)
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(RETENTION_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::parse(&fs::read_to_string(&path)?)
+    }
+
+    /// Parse the contents of a retention file
+    pub fn parse(toml: &str) -> Result<Self> {
+        let config: Self = toml::from_str(toml)
+            .map_err(|e| StorageError::SerializationError(format!("{}: {}", RETENTION_FILE, e)))?;
+        if let Some(rule) = config.namespaces.iter().find(|rule| rule.pattern.trim().is_empty()) {
+            return Err(StorageError::SerializationError(format!(
+                "{}: empty namespace pattern ({:?})",
+                RETENTION_FILE, rule
+            )));
+        }
+        Ok(config)
+    }
+
+    /// Write the settings to the store in `dir`, replacing the file atomically
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let toml = toml::to_string(self).map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        let temp_path = dir.join(format!("{}.tmp", RETENTION_FILE));
+        fs::write(&temp_path, toml)?;
+        fs::rename(&temp_path, dir.join(RETENTION_FILE))?;
+        Ok(())
+    }
+
+    /// Effective retention of `namespace`
+    ///
+    /// The most specific matching rule wins (the later one among equally
+    /// specific rules); what it leaves unset comes from the default.
+    pub fn for_namespace(&self, namespace: &str) -> NamespaceRetention {
+        let rule = self
+            .namespaces
+            .iter()
+            .filter(|rule| namespace_matches(&rule.pattern, namespace))
+            .max_by_key(|rule| specificity(&rule.pattern));
+        match rule {
+            Some(rule) => NamespaceRetention {
+                history: rule.retention.history.or(self.default.history),
+                trash_days: rule.retention.trash_days.or(self.default.trash_days),
+            },
+            None => self.default,
+        }
+    }
+}
+
+/// Exact patterns before globs, then patterns with more literal characters
+fn specificity(pattern: &str) -> (bool, usize) {
+    (!pattern.contains('*'), pattern.chars().filter(|c| *c != '*').count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const EXAMPLE: &str = r#"
+        [default]
+        history = { keep_last = 50 }
+        trash_days = 90
+
+        [[namespace]]
+        pattern = "app/*"
+        history = { keep_days = 30 }
+
+        [[namespace]]
+        pattern = "app/llm"
+        trash_days = 7
+
+        [audit]
+        keep_days = 365
+    "#;
+
+    #[test]
+    fn test_effective_retention() {
+        let config = RetentionConfig::parse(EXAMPLE).unwrap();
+        assert_eq!(config.audit.keep_days, Some(365));
+
+        let other = config.for_namespace("billing");
+        assert_eq!(other, config.default);
+
+        let app = config.for_namespace("app/search");
+        assert_eq!(app.history, Some(RetentionPolicy::keep_days(30)));
+        assert_eq!(app.trash_days, Some(90));
+        assert_eq!(config.for_namespace("app/search/v2"), app);
+        assert_eq!(config.for_namespace("app"), config.default);
+        assert_eq!(config.for_namespace("application"), config.default);
+
+        // The exact pattern beats app/* and inherits the rest from the default
+        let llm = config.for_namespace("app/llm");
+        assert_eq!(llm.history, Some(RetentionPolicy::keep_last(50)));
+        assert_eq!(llm.trash_days, Some(7));
+
+        // Globs are matched like role scopes
+        let config = RetentionConfig::parse(
+            "[[namespace]]\npattern = \"app/**\"\ntrash_days = 1\n\
+             [[namespace]]\npattern = \"*/llm\"\ntrash_days = 2",
+        )
+        .unwrap();
+        assert_eq!(config.for_namespace("app").trash_days, Some(1));
+        assert_eq!(config.for_namespace("app/llm").trash_days, Some(2));
+        assert_eq!(config.for_namespace("ml/llm").trash_days, Some(2));
+        assert_eq!(config.for_namespace("ml/llm/v2").trash_days, None);
+
+        assert!(RetentionConfig::parse("[[namespace]]\npattern = \"\"").is_err());
+        assert!(RetentionConfig::parse("[default]\ntrash_days = \"soon\"").is_err());
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(RetentionConfig::load(temp_dir.path()).unwrap(), RetentionConfig::default());
+
+        let config = RetentionConfig::parse(EXAMPLE).unwrap();
+        config.save(temp_dir.path()).unwrap();
+        assert_eq!(RetentionConfig::load(temp_dir.path()).unwrap(), config);
+    }
+}
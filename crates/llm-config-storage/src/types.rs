@@ -0,0 +1,362 @@
+//! Typed config values: durations, byte sizes and URLs
+//!
+//! Typed values are validated when they are parsed, so a stored
+//! [`ConfigValue::Duration`](crate::ConfigValue::Duration) is always a valid
+//! duration. They are written as single-key objects (`{"$duration": "5m"}`,
+//! `{"$size": "512MiB"}`, `{"$url": "https://..."}`), which is how untagged
+//! deserialization tells them apart from strings and plain objects.
+//!
+//! [`ValueType`] also forces the plain types (`string`, `int`, `float`,
+//! `bool` and `json`) where guessing from the text would pick the wrong one,
+//! such as a version number `"1.10"` that would otherwise become a float.
+
+use crate::ConfigValue;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+
+/// Type a string is parsed as when setting a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    /// A duration such as `30s`, `5m` or `1h30m`
+    Duration,
+    /// A byte size such as `512MiB`, `10GB` or `4096`
+    Size,
+    /// An absolute URL
+    Url,
+    /// The text as is, even if it looks like a number or JSON
+    String,
+    /// A 64-bit signed integer
+    Int,
+    /// A finite floating point number
+    Float,
+    /// `true` or `false`
+    Bool,
+    /// Any JSON document; objects and arrays are converted recursively
+    Json,
+}
+
+impl ValueType {
+    /// Parse `s` as a value of this type
+    pub fn parse(self, s: &str) -> Result<ConfigValue, String> {
+        match self {
+            ValueType::Duration => parse_duration(s).map(ConfigValue::Duration),
+            ValueType::Size => parse_size(s).map(ConfigValue::Size),
+            ValueType::Url => parse_url(s).map(ConfigValue::Url),
+            ValueType::String => Ok(ConfigValue::String(s.to_string())),
+            ValueType::Int => s
+                .trim()
+                .parse()
+                .map(ConfigValue::Integer)
+                .map_err(|_| format!("Invalid integer: '{}'", s)),
+            ValueType::Float => s
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(ConfigValue::Float)
+                .ok_or_else(|| format!("Invalid float: '{}'", s)),
+            ValueType::Bool => s
+                .trim()
+                .parse()
+                .map(ConfigValue::Boolean)
+                .map_err(|_| format!("Invalid bool: '{}' (expected true or false)", s)),
+            ValueType::Json => serde_json::from_str::<serde_json::Value>(s)
+                .map(ConfigValue::from)
+                .map_err(|e| format!("Invalid JSON: {}", e)),
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueType::Duration => "duration",
+            ValueType::Size => "size",
+            ValueType::Url => "url",
+            ValueType::String => "string",
+            ValueType::Int => "int",
+            ValueType::Float => "float",
+            ValueType::Bool => "bool",
+            ValueType::Json => "json",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for ValueType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "duration" => Ok(ValueType::Duration),
+            "size" => Ok(ValueType::Size),
+            "url" => Ok(ValueType::Url),
+            "string" => Ok(ValueType::String),
+            "int" | "integer" => Ok(ValueType::Int),
+            "float" => Ok(ValueType::Float),
+            "bool" | "boolean" => Ok(ValueType::Bool),
+            "json" => Ok(ValueType::Json),
+            _ => Err(format!(
+                "Unknown value type: {} (expected duration, size, url, string, int, float, bool or json)",
+                s
+            )),
+        }
+    }
+}
+
+const DURATION_UNITS: [(&str, u64); 5] = [
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("m", 60_000),
+    ("s", 1_000),
+    ("ms", 1),
+];
+
+/// Parse a duration made of `<number><unit>` parts, e.g. `250ms`, `30s` or `1h30m`
+///
+/// Units are `ms`, `s`, `m`, `h` and `d`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration: '{}' (expected e.g. 30s, 5m or 1h30m)", s);
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut millis: u64 = 0;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        if digits == 0 {
+            return Err(invalid());
+        }
+        let number: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let factor = DURATION_UNITS
+            .iter()
+            .find(|(unit, _)| *unit == &rest[..unit_len])
+            .map(|(_, factor)| *factor)
+            .ok_or_else(invalid)?;
+        millis = number
+            .checked_mul(factor)
+            .and_then(|part| millis.checked_add(part))
+            .ok_or_else(invalid)?;
+        rest = &rest[unit_len..];
+    }
+    Ok(Duration::from_millis(millis))
+}
+
+/// Format a duration the way [`parse_duration`] reads it, e.g. `1h30m`
+pub fn format_duration(duration: Duration) -> String {
+    let mut millis = duration.as_millis() as u64;
+    if millis == 0 {
+        return "0s".to_string();
+    }
+    let mut formatted = String::new();
+    for (unit, factor) in DURATION_UNITS {
+        if millis >= factor {
+            formatted.push_str(&format!("{}{}", millis / factor, unit));
+            millis %= factor;
+        }
+    }
+    formatted
+}
+
+const SIZE_UNITS: [(&str, u64); 9] = [
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("KB", 1_000),
+    ("B", 1),
+];
+
+/// Parse a byte size, e.g. `4096`, `10GB` (powers of 1000) or `512MiB`
+/// (powers of 1024)
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid size: '{}' (expected e.g. 4096, 10GB or 512MiB)", s);
+    let trimmed = s.trim();
+    let digits = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    if digits == 0 {
+        return Err(invalid());
+    }
+    let number: u64 = trimmed[..digits].parse().map_err(|_| invalid())?;
+    let unit = trimmed[digits..].trim();
+    if unit.is_empty() {
+        return Ok(number);
+    }
+    let factor = SIZE_UNITS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+        .map(|(_, factor)| *factor)
+        .ok_or_else(invalid)?;
+    number.checked_mul(factor).ok_or_else(invalid)
+}
+
+/// Format a byte size with the unit giving the smallest whole number, e.g.
+/// `512MiB` or `10GB`
+pub fn format_size(bytes: u64) -> String {
+    SIZE_UNITS
+        .iter()
+        .filter(|(_, factor)| bytes != 0 && bytes % factor == 0)
+        .min_by_key(|(_, factor)| bytes / factor)
+        .map(|(unit, factor)| format!("{}{}", bytes / factor, unit))
+        .unwrap_or_else(|| "0B".to_string())
+}
+
+/// Parse an absolute URL
+pub fn parse_url(s: &str) -> Result<Url, String> {
+    Url::parse(s.trim()).map_err(|e| format!("Invalid URL: '{}' ({})", s, e))
+}
+
+/// Serde representation of [`ConfigValue::Duration`](crate::ConfigValue::Duration)
+pub(crate) mod duration_repr {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Repr {
+        #[serde(rename = "$duration")]
+        duration: String,
+    }
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        Repr { duration: format_duration(*duration) }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        parse_duration(&Repr::deserialize(deserializer)?.duration).map_err(D::Error::custom)
+    }
+}
+
+/// Serde representation of [`ConfigValue::Size`](crate::ConfigValue::Size)
+pub(crate) mod size_repr {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Repr {
+        #[serde(rename = "$size")]
+        size: String,
+    }
+
+    pub fn serialize<S: Serializer>(bytes: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        Repr { size: format_size(*bytes) }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        parse_size(&Repr::deserialize(deserializer)?.size).map_err(D::Error::custom)
+    }
+}
+
+/// Serde representation of [`ConfigValue::Url`](crate::ConfigValue::Url)
+pub(crate) mod url_repr {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Repr {
+        #[serde(rename = "$url")]
+        url: String,
+    }
+
+    pub fn serialize<S: Serializer>(url: &Url, serializer: S) -> Result<S::Ok, S::Error> {
+        Repr { url: url.to_string() }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Url, D::Error> {
+        parse_url(&Repr::deserialize(deserializer)?.url).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_round_trip() {
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1s500ms");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+
+        for invalid in ["", "30", "5x", "m5", "1.5h"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_size_round_trip() {
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert_eq!(parse_size("512MiB").unwrap(), 512 << 20);
+        assert_eq!(parse_size("10 gb").unwrap(), 10_000_000_000);
+        assert_eq!(format_size(512 << 20), "512MiB");
+        assert_eq!(format_size(10_000_000_000), "10GB");
+        assert_eq!(format_size(1001), "1001B");
+        assert_eq!(format_size(0), "0B");
+
+        for invalid in ["", "MiB", "12 parsecs", "-1"] {
+            assert!(parse_size(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_typed_values_serialize_tagged() {
+        let values = [
+            ValueType::Duration.parse("5m").unwrap(),
+            ValueType::Size.parse("1GiB").unwrap(),
+            ValueType::Url.parse("https://api.openai.com/v1").unwrap(),
+        ];
+        for value in values {
+            let json = serde_json::to_string(&value).unwrap();
+            let parsed: ConfigValue = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.type_name(), value.type_name(), "{}", json);
+        }
+        assert_eq!(
+            serde_json::to_string(&ValueType::Duration.parse("5m").unwrap()).unwrap(),
+            r#"{"$duration":"5m"}"#
+        );
+
+        // Plain strings and objects are not mistaken for typed values
+        let parsed: ConfigValue = serde_json::from_str(r#""5m""#).unwrap();
+        assert_eq!(parsed.type_name(), "string");
+        let parsed: ConfigValue = serde_json::from_str(r#"{"$duration":"soon"}"#).unwrap();
+        assert_eq!(parsed.type_name(), "object");
+        assert!(ValueType::Url.parse("not a url").is_err());
+    }
+
+    #[test]
+    fn test_plain_types() {
+        let parse = |value_type: &str, s: &str| value_type.parse::<ValueType>().unwrap().parse(s);
+        assert_eq!(parse("string", "1.10").unwrap().as_str(), Some("1.10"));
+        assert_eq!(parse("int", " 42 ").unwrap().as_i64(), Some(42));
+        assert_eq!(parse("float", "0.5").unwrap().as_f64(), Some(0.5));
+        assert_eq!(parse("boolean", "true").unwrap().as_bool(), Some(true));
+        for (value_type, invalid) in [("int", "4.2"), ("float", "NaN"), ("bool", "yes"), ("json", "{")] {
+            assert!(parse(value_type, invalid).is_err(), "{} {}", value_type, invalid);
+        }
+
+        let ConfigValue::Object(object) = parse("json", r#"{"retry": {"attempts": 5}, "models": ["a"]}"#).unwrap()
+        else {
+            panic!("expected an object");
+        };
+        let ConfigValue::Object(retry) = &object["retry"] else {
+            panic!("expected a nested object");
+        };
+        assert_eq!(retry["attempts"].as_i64(), Some(5));
+        assert_eq!(object["models"].type_name(), "array");
+    }
+}
@@ -0,0 +1,86 @@
+//! Write-ahead journal of versioned writes
+//!
+//! Storing a value writes two files: the config and its version snapshot. To
+//! keep them consistent across crashes, the intent (config and version) is
+//! first made durable as a record in `wal/`, then both files are written, and
+//! the record is removed. Records left behind by a crash are replayed when
+//! the store is opened; the version file is named after the record, so
+//! replaying is idempotent. Records that were never completely written
+//! (temporary or unreadable files) are rolled back by discarding them, since
+//! nothing else was written for them yet.
+
+use crate::{ConfigEntry, Result, StorageError, VersionEntry};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Directory of pending records
+pub const WAL_DIR: &str = "wal";
+
+/// Intent to store a config together with its version snapshot
+#[derive(Serialize, Deserialize)]
+pub(crate) struct WalRecord {
+    pub id: Uuid,
+    pub config: ConfigEntry,
+    pub version: VersionEntry,
+}
+
+impl WalRecord {
+    pub fn new(config: ConfigEntry, version: VersionEntry) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            config,
+            version,
+        }
+    }
+
+    fn path(dir: &Path, id: Uuid) -> PathBuf {
+        dir.join(WAL_DIR).join(format!("{}.json", id))
+    }
+
+    /// Durably record the intent in `dir`
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        let json =
+            serde_json::to_vec(self).map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        let path = Self::path(dir, self.id);
+        let temp_path = path.with_extension("tmp");
+        {
+            let mut temp_file = File::create(&temp_path)?;
+            temp_file.write_all(&json)?;
+            temp_file.sync_all()?;
+        }
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    /// Mark the operation as complete
+    pub fn remove(&self, dir: &Path) -> Result<()> {
+        fs::remove_file(Self::path(dir, self.id))?;
+        Ok(())
+    }
+
+    /// Complete records left in `dir`, discarding incomplete ones
+    pub fn pending(dir: &Path) -> Result<Vec<Self>> {
+        let mut records = Vec::new();
+        for entry in fs::read_dir(dir.join(WAL_DIR))? {
+            let path = entry?.path();
+            let record = match path.extension().and_then(|s| s.to_str()) {
+                Some("json") => fs::read(&path)
+                    .ok()
+                    .and_then(|contents| serde_json::from_slice::<WalRecord>(&contents).ok()),
+                _ => None,
+            };
+            match record {
+                Some(record) => records.push(record),
+                None => {
+                    tracing::warn!("Rolling back incomplete write {}", path.display());
+                    fs::remove_file(&path)?;
+                }
+            }
+        }
+        records.sort_by_key(|record| record.version.created_at);
+        Ok(records)
+    }
+}
@@ -0,0 +1,102 @@
+//! Git checkout of the manifest repository, using the `git` command
+
+use crate::{Result, SyncError};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Branch synced unless configured otherwise
+pub const DEFAULT_BRANCH: &str = "main";
+
+/// Commit trailer naming the reviewer who approved a change
+pub const REVIEWED_BY_TRAILER: &str = "Reviewed-by";
+
+/// A remote repository and the local checkout it is pulled into
+#[derive(Debug, Clone)]
+pub struct GitRepository {
+    url: String,
+    branch: String,
+    checkout: PathBuf,
+}
+
+/// The commit a checkout is at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncedCommit {
+    pub hash: String,
+    /// Reviewer from the commit's `Reviewed-by` trailer
+    pub reviewed_by: Option<String>,
+}
+
+impl GitRepository {
+    /// Sync `url` into the directory `checkout`
+    pub fn new(url: impl Into<String>, checkout: impl Into<PathBuf>) -> Self {
+        Self {
+            url: url.into(),
+            branch: DEFAULT_BRANCH.to_string(),
+            checkout: checkout.into(),
+        }
+    }
+
+    /// Sync a branch other than `main`
+    pub fn with_branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = branch.into();
+        self
+    }
+
+    /// Directory of the local checkout
+    pub fn checkout(&self) -> &Path {
+        &self.checkout
+    }
+
+    /// Clone the repository, or reset an existing checkout to the branch head
+    ///
+    /// Local changes in the checkout are discarded.
+    pub fn pull(&self) -> Result<SyncedCommit> {
+        if self.checkout.join(".git").exists() {
+            self.git(&["fetch", "--depth", "1", "origin", &self.branch])?;
+            self.git(&["reset", "--hard", "FETCH_HEAD"])?;
+            self.git(&["clean", "-fd"])?;
+        } else {
+            if let Some(parent) = self.checkout.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            run(Command::new("git")
+                .args(["clone", "--quiet", "--depth", "1", "--single-branch", "--branch"])
+                .arg(&self.branch)
+                .arg(&self.url)
+                .arg(&self.checkout))?;
+        }
+        self.head()
+    }
+
+    /// The commit the checkout is at
+    pub fn head(&self) -> Result<SyncedCommit> {
+        let hash = self.git(&["rev-parse", "HEAD"])?;
+        let trailer = format!(
+            "--format=%(trailers:key={},valueonly,separator=%x2C )",
+            REVIEWED_BY_TRAILER
+        );
+        let reviewed_by = self.git(&["log", "-1", &trailer])?;
+
+        Ok(SyncedCommit {
+            hash,
+            reviewed_by: (!reviewed_by.is_empty()).then_some(reviewed_by),
+        })
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String> {
+        run(Command::new("git").arg("-C").arg(&self.checkout).args(args))
+    }
+}
+
+/// Run a git command, returning its trimmed output
+fn run(command: &mut Command) -> Result<String> {
+    let output = command
+        .output()
+        .map_err(|e| SyncError::Git(format!("failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(SyncError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,49 @@
+//! GitOps sync for LLM Config Manager
+//!
+//! Reconciles storage from a Git repository of YAML manifests. Each manifest
+//! declares the desired contents of some namespaces in one environment (see
+//! [`SyncManifest`]); a [`Syncer`] pulls the repository, compares every
+//! manifest with the store and applies the difference. Applied values record
+//! the commit they came from in their metadata, and writes go through the
+//! config manager, so environment protection and audit logging apply as for
+//! any other write.
+//!
+//! ## Example
+//! ```no_run
+//! use llm_config_core::ConfigManager;
+//! use llm_config_sync::{GitRepository, Syncer};
+//! use std::sync::Arc;
+//!
+//! let manager = Arc::new(ConfigManager::new(".llm-config").unwrap());
+//! let repository = GitRepository::new("https://git.example.com/configs.git", ".llm-config/sync");
+//! let report = Syncer::new(manager, repository).sync().unwrap();
+//! println!("applied {} changes from {}", report.applied.len(), report.commit);
+//! ```
+
+pub mod git;
+pub mod manifest;
+pub mod syncer;
+
+pub use git::{GitRepository, SyncedCommit};
+pub use manifest::{load_manifests, SyncManifest};
+pub use syncer::{SkippedChange, SyncReport, Syncer, SYNC_USER};
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("Git error: {0}")]
+    Git(String),
+
+    #[error("Invalid manifest {path}: {message}")]
+    Manifest { path: PathBuf, message: String },
+
+    #[error("Config error: {0}")]
+    Config(#[from] llm_config_core::ConfigError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, SyncError>;
@@ -0,0 +1,139 @@
+//! Config manifests
+//!
+//! A manifest is a [`DesiredState`] document with the environment it
+//! applies to:
+//!
+//! ```yaml
+//! environment: production
+//! namespaces:
+//!   app/llm:
+//!     values:
+//!       model: gpt-4o
+//!     secrets:
+//!       api_key: 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+//! ```
+//!
+//! Secrets are declared by hash only (see [`llm_config_core::secret_hash`]),
+//! so plaintext never has to be committed; a sync reports secrets that differ
+//! instead of writing them.
+
+use crate::{Result, SyncError};
+use llm_config_core::{DesiredState, Environment};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Desired contents of namespaces in one environment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncManifest {
+    pub environment: Environment,
+    #[serde(flatten)]
+    pub state: DesiredState,
+}
+
+impl SyncManifest {
+    /// Parse a manifest
+    pub fn from_yaml(yaml: &str) -> std::result::Result<Self, String> {
+        let manifest: SyncManifest = serde_yaml::from_str(yaml).map_err(|e| e.to_string())?;
+        manifest.state.validate().map_err(|e| e.to_string())?;
+        Ok(manifest)
+    }
+}
+
+/// Load every `*.yaml` and `*.yml` manifest below `dir`, sorted by path
+///
+/// Hidden files and directories (such as `.git`) are skipped. Each namespace
+/// and environment may be declared by one manifest only.
+pub fn load_manifests(dir: &Path) -> Result<Vec<(PathBuf, SyncManifest)>> {
+    let mut paths = Vec::new();
+    collect(dir, &mut paths)?;
+    paths.sort();
+
+    let mut declared: BTreeMap<(Environment, String), PathBuf> = BTreeMap::new();
+    let mut manifests = Vec::new();
+    for path in paths {
+        let invalid = |message: String| SyncError::Manifest {
+            path: path.clone(),
+            message,
+        };
+        let manifest = SyncManifest::from_yaml(&std::fs::read_to_string(&path)?).map_err(&invalid)?;
+
+        for namespace in manifest.state.namespaces.keys() {
+            let key = (manifest.environment, namespace.clone());
+            if let Some(other) = declared.insert(key, path.clone()) {
+                return Err(invalid(format!(
+                    "{} ({}) is already declared in {}",
+                    namespace,
+                    manifest.environment,
+                    other.display()
+                )));
+            }
+        }
+        manifests.push((path, manifest));
+    }
+    Ok(manifests)
+}
+
+fn collect(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect(&path, paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("prod")).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(
+            dir.join("prod/llm.yaml"),
+            "environment: production\nnamespaces:\n  app/llm:\n    values: {model: gpt-4o}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join(".git/config.yaml"), "not a manifest").unwrap();
+        std::fs::write(dir.join("README.md"), "# configs").unwrap();
+
+        let manifests = load_manifests(dir).unwrap();
+        assert_eq!(manifests.len(), 1);
+        let (path, manifest) = &manifests[0];
+        assert!(path.ends_with("prod/llm.yaml"));
+        assert_eq!(manifest.environment, Environment::Production);
+        assert_eq!(
+            manifest.state.namespaces["app/llm"].values["model"],
+            serde_json::json!("gpt-4o")
+        );
+
+        // A namespace may only be declared once per environment
+        std::fs::write(
+            dir.join("dup.yml"),
+            "environment: production\nnamespaces:\n  app/llm:\n    values: {}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_manifests(dir),
+            Err(SyncError::Manifest { .. })
+        ));
+    }
+}
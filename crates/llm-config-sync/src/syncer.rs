@@ -0,0 +1,286 @@
+//! Reconciling storage from manifests
+
+use crate::git::{GitRepository, SyncedCommit};
+use crate::manifest::{load_manifests, SyncManifest};
+use crate::Result;
+use llm_config_core::plan::value_from_json;
+use llm_config_core::{ConfigManager, OperationContext, PlanAction, PlannedChange, SetOptions};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// User that synced changes are attributed to unless configured otherwise
+pub const SYNC_USER: &str = "gitops-sync";
+
+/// A change a sync did not apply
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedChange {
+    #[serde(flatten)]
+    pub change: PlannedChange,
+    pub reason: String,
+}
+
+/// Outcome of a sync
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncReport {
+    /// Commit the manifests were read from
+    pub commit: String,
+    pub applied: Vec<PlannedChange>,
+    pub skipped: Vec<SkippedChange>,
+}
+
+impl SyncReport {
+    /// Check whether the store now matches the manifests
+    pub fn is_in_sync(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// Applies the manifests of a repository to a config manager
+pub struct Syncer {
+    manager: Arc<ConfigManager>,
+    repository: GitRepository,
+    path: Option<PathBuf>,
+    user: String,
+    prune: bool,
+}
+
+impl Syncer {
+    /// Sync `manager` from the manifests in `repository`
+    pub fn new(manager: Arc<ConfigManager>, repository: GitRepository) -> Self {
+        Self {
+            manager,
+            repository,
+            path: None,
+            user: SYNC_USER.to_string(),
+            prune: false,
+        }
+    }
+
+    /// Only read manifests below this directory of the repository
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Attribute synced changes to `user`
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = user.into();
+        self
+    }
+
+    /// Delete keys of declared namespaces that no manifest lists
+    pub fn with_prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Pull the repository and apply its manifests
+    pub fn sync(&self) -> Result<SyncReport> {
+        let commit = self.repository.pull()?;
+        let dir = match &self.path {
+            Some(path) => self.repository.checkout().join(path),
+            None => self.repository.checkout().to_path_buf(),
+        };
+        let manifests = load_manifests(&dir)?;
+        self.apply(&manifests, &commit)
+    }
+
+    /// Sync every `interval`, passing each outcome to `on_sync`
+    pub fn run(&self, interval: Duration, mut on_sync: impl FnMut(Result<SyncReport>)) -> ! {
+        loop {
+            on_sync(self.sync());
+            std::thread::sleep(interval);
+        }
+    }
+
+    /// Apply manifests read at `commit`
+    ///
+    /// Writes to review-required environments are approved by the commit's
+    /// `Reviewed-by` trailer; without one they are skipped, like writes the
+    /// environment's protection rejects for any other reason. Secrets are
+    /// never written, so differing secrets are skipped too.
+    pub fn apply(
+        &self,
+        manifests: &[(PathBuf, SyncManifest)],
+        commit: &SyncedCommit,
+    ) -> Result<SyncReport> {
+        let mut options = SetOptions::default()
+            .with_allow_type_change(true)
+            .with_change_description(format!("Synced from commit {}", short(&commit.hash)))
+            .with_context(OperationContext::default().with_git_commit(&commit.hash));
+        options.reviewed_by = commit.reviewed_by.clone();
+
+        let mut report = SyncReport {
+            commit: commit.hash.clone(),
+            applied: Vec::new(),
+            skipped: Vec::new(),
+        };
+
+        for (_, manifest) in manifests {
+            let plan = self
+                .manager
+                .check_drift(&manifest.state, manifest.environment)?;
+
+            for change in plan.changes {
+                match self.apply_change(manifest, &change, &options) {
+                    Ok(()) => report.applied.push(change),
+                    Err(reason) => {
+                        tracing::warn!(
+                            "Not syncing {}/{} ({}): {}",
+                            change.namespace,
+                            change.key,
+                            change.environment,
+                            reason
+                        );
+                        report.skipped.push(SkippedChange { change, reason });
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn apply_change(
+        &self,
+        manifest: &SyncManifest,
+        change: &PlannedChange,
+        options: &SetOptions,
+    ) -> std::result::Result<(), String> {
+        let declared = &manifest.state.namespaces[&change.namespace];
+        let env = change.environment;
+
+        let result = match change.action {
+            PlanAction::Delete if !self.prune => return Err("pruning is disabled".to_string()),
+            PlanAction::Delete => self
+                .manager
+                .delete_with_options(&change.namespace, &change.key, env, &self.user, options)
+                .map(|_| ()),
+            PlanAction::Create | PlanAction::Update => {
+                let Some(value) = declared.values.get(&change.key) else {
+                    return Err("secrets are not synced; set them with set-secret".to_string());
+                };
+                self.manager
+                    .set_with_options(
+                        &change.namespace,
+                        &change.key,
+                        value_from_json(value),
+                        env,
+                        &self.user,
+                        options,
+                    )
+                    .map(|_| ())
+            }
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/// Abbreviated commit hash
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_config_audit::{AuditStorage, BlockingAuditRecorder, FileAuditStorage};
+    use llm_config_core::{ConfigValue, Environment, EnvironmentProtection, ProtectionLevel};
+    use std::path::Path;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=Dev", "-c", "user.email=dev@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn commit(repo: &Path, manifest: &str, message: &str) {
+        std::fs::write(repo.join("llm.yaml"), manifest).unwrap();
+        git(repo, &["add", "-A"]);
+        git(repo, &["commit", "-q", "-m", message]);
+    }
+
+    #[test]
+    fn test_sync_from_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let origin = temp_dir.path().join("origin");
+        std::fs::create_dir_all(&origin).unwrap();
+        git(&origin, &["init", "-q", "-b", "main"]);
+        commit(
+            &origin,
+            "environment: production\nnamespaces:\n  app/llm:\n    values:\n      model: gpt-4o\n      max_tokens: 1024\n",
+            "Configure llm",
+        );
+
+        let audit = Arc::new(FileAuditStorage::new(temp_dir.path().join("audit")).unwrap());
+        let manager = ConfigManager::new(temp_dir.path().join("store"))
+            .unwrap()
+            .with_audit_logger(Arc::new(BlockingAuditRecorder::new(audit.clone())));
+        let env = Environment::Production;
+        manager
+            .set("app/llm", "legacy", ConfigValue::Boolean(true), env, "alice")
+            .unwrap();
+        let manager = Arc::new(manager);
+
+        let repository = GitRepository::new(
+            origin.to_string_lossy(),
+            temp_dir.path().join("checkout"),
+        );
+        let syncer = Syncer::new(Arc::clone(&manager), repository.clone());
+
+        let report = syncer.sync().unwrap();
+        assert_eq!(report.applied.len(), 2);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].change.key, "legacy");
+
+        let entry = manager.get("app/llm", "model", env).unwrap().unwrap();
+        assert_eq!(entry.value.as_str(), Some("gpt-4o"));
+        let context = entry.metadata.context.unwrap();
+        assert_eq!(context.git_commit.as_deref(), Some(report.commit.as_str()));
+
+        let events = audit.read_all().unwrap();
+        let synced: Vec<_> = events.iter().filter(|e| e.user == SYNC_USER).collect();
+        assert_eq!(synced.len(), 2);
+        assert_eq!(synced[0].metadata["git_commit"], report.commit);
+
+        // Review-required environments need a Reviewed-by trailer
+        manager
+            .set_protection(
+                EnvironmentProtection::default().with_level(env, ProtectionLevel::ReviewRequired),
+            )
+            .unwrap();
+        commit(
+            &origin,
+            "environment: production\nnamespaces:\n  app/llm:\n    values:\n      model: gpt-4.1\n      max_tokens: 1024\n",
+            "Upgrade model",
+        );
+        let syncer = syncer.with_prune(true);
+        let report = syncer.sync().unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+
+        commit(
+            &origin,
+            "environment: production\nnamespaces:\n  app/llm:\n    values:\n      model: gpt-4.1\n      max_tokens: 2048\n",
+            "Raise token limit\n\nReviewed-by: bob",
+        );
+        let report = syncer.sync().unwrap();
+        assert!(report.is_in_sync());
+        assert_eq!(report.applied.len(), 3);
+        assert!(manager.get("app/llm", "legacy", env).unwrap().is_none());
+        assert_eq!(
+            repository.head().unwrap().reviewed_by.as_deref(),
+            Some("bob")
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Template engine for managing and rendering templates
 
-use crate::{parser, Result, Template, TemplateError};
+use crate::template::{render_tokens, resolve_reference};
+use crate::{parser, ConfigSource, Result, Template, TemplateError};
 use std::collections::HashMap;
 
 /// Template engine for rendering templates
@@ -47,22 +48,33 @@ impl TemplateEngine {
 
     /// Render a template string directly (without registration)
     pub fn render(&self, template_str: &str, vars: &HashMap<String, String>) -> Result<String> {
-        let tokens = parser::parse(template_str)?;
-        let mut result = String::new();
-
-        for token in tokens {
-            match token {
-                parser::Token::Literal(text) => result.push_str(&text),
-                parser::Token::Variable(var_name) => {
-                    let value = vars
-                        .get(&var_name)
-                        .ok_or_else(|| TemplateError::VariableNotFound(var_name))?;
-                    result.push_str(value);
-                }
-            }
-        }
-
-        Ok(result)
+        render_tokens(
+            parser::parse(template_str)?,
+            |name| vars.get(name).cloned(),
+            |reference| {
+                Err(TemplateError::UnresolvedReference(format!(
+                    "{} (no config source)",
+                    reference
+                )))
+            },
+        )
+    }
+
+    /// Render a template string whose placeholders all reference stored values
+    ///
+    /// E.g. `postgres://{{config:org/db.user}}:{{secret:org/db.password}}@{{config:org/db.host}}`,
+    /// resolved in `env` through `manager`.
+    pub fn render_with_manager<S: ConfigSource>(
+        &self,
+        template_str: &str,
+        env: S::Environment,
+        manager: &S,
+    ) -> Result<String> {
+        render_tokens(
+            parser::parse(template_str)?,
+            |_| None,
+            |reference| resolve_reference(manager, reference, env),
+        )
     }
 
     /// List all registered templates
@@ -20,9 +20,11 @@
 
 pub mod engine;
 pub mod parser;
+pub mod source;
 pub mod template;
 
 pub use engine::TemplateEngine;
+pub use source::{ConfigSource, Reference, ReferenceKind};
 pub use template::Template;
 
 use thiserror::Error;
@@ -40,6 +42,12 @@ pub enum TemplateError {
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Unresolved reference: {0}")]
+    UnresolvedReference(String),
+
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
 }
 
 pub type Result<T> = std::result::Result<T, TemplateError>;
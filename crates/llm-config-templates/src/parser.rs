@@ -1,6 +1,6 @@
 //! Template parsing functionality
 
-use crate::{Result, TemplateError};
+use crate::{Reference, ReferenceKind, Result, TemplateError};
 
 /// Token in a parsed template
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,6 +9,8 @@ pub enum Token {
     Literal(String),
     /// Variable placeholder
     Variable(String),
+    /// Reference to a stored value (`{{config:namespace.key}}` or `{{secret:namespace.key}}`)
+    Reference(Reference),
 }
 
 /// Parse a template string into tokens
@@ -37,7 +39,10 @@ pub fn parse(template: &str) -> Result<Vec<Token>> {
                     chars.next(); // Consume second '}'
                     found_close = true;
                     break;
-                } else if ch.is_whitespace() || ch.is_alphanumeric() || ch == '_' || ch == '-' {
+                } else if ch.is_whitespace()
+                    || ch.is_alphanumeric()
+                    || matches!(ch, '_' | '-' | ':' | '/' | '.')
+                {
                     var_name.push(ch);
                 } else {
                     return Err(TemplateError::ParseError(format!(
@@ -60,7 +65,7 @@ pub fn parse(template: &str) -> Result<Vec<Token>> {
                 ));
             }
 
-            tokens.push(Token::Variable(var_name));
+            tokens.push(parse_placeholder(var_name)?);
         } else {
             // Regular character
             current_literal.push(ch);
@@ -75,6 +80,45 @@ pub fn parse(template: &str) -> Result<Vec<Token>> {
     Ok(tokens)
 }
 
+/// Classify a placeholder as a variable or a reference
+fn parse_placeholder(name: String) -> Result<Token> {
+    let Some((kind, target)) = name.split_once(':') else {
+        if let Some(ch) = name.chars().find(|ch| matches!(ch, '/' | '.')) {
+            return Err(TemplateError::ParseError(format!(
+                "Invalid character in variable name: {}",
+                ch
+            )));
+        }
+        return Ok(Token::Variable(name));
+    };
+
+    let kind = match kind.trim() {
+        "config" => ReferenceKind::Config,
+        "secret" => ReferenceKind::Secret,
+        other => {
+            return Err(TemplateError::ParseError(format!(
+                "Unknown reference kind: {}",
+                other
+            )))
+        }
+    };
+
+    // The key follows the last dot; namespaces may contain slashes
+    let (namespace, key) = target
+        .trim()
+        .rsplit_once('.')
+        .filter(|(namespace, key)| !namespace.is_empty() && !key.is_empty())
+        .ok_or_else(|| {
+            TemplateError::ParseError(format!("Expected {}:namespace.key, got {}", kind, name))
+        })?;
+
+    Ok(Token::Reference(Reference {
+        kind,
+        namespace: namespace.to_string(),
+        key: key.to_string(),
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,6 +187,31 @@ mod tests {
         assert_eq!(tokens, vec![Token::Variable("name".to_string())]);
     }
 
+    #[test]
+    fn test_parse_references() {
+        let tokens = parse("{{config:org/app/service.timeout}} {{ secret:org/app.api_key }}").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Reference(Reference {
+                    kind: ReferenceKind::Config,
+                    namespace: "org/app/service".to_string(),
+                    key: "timeout".to_string(),
+                }),
+                Token::Literal(" ".to_string()),
+                Token::Reference(Reference {
+                    kind: ReferenceKind::Secret,
+                    namespace: "org/app".to_string(),
+                    key: "api_key".to_string(),
+                }),
+            ]
+        );
+
+        assert!(parse("{{env:HOME}}").is_err());
+        assert!(parse("{{config:timeout}}").is_err());
+        assert!(parse("{{org/app.timeout}}").is_err());
+    }
+
     #[test]
     fn test_parse_unclosed_variable() {
         let result = parse("{{name");
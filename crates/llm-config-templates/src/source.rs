@@ -0,0 +1,56 @@
+//! References from templates to stored configuration
+//!
+//! Besides plain variables, templates may reference stored values:
+//! `{{config:org/app/service.timeout}}` or `{{secret:org/app.api_key}}`. The
+//! key follows the last dot. References are resolved through a
+//! [`ConfigSource`] when rendering with
+//! [`TemplateEngine::render_with_manager`](crate::TemplateEngine::render_with_manager)
+//! or [`Template::render_with_source`](crate::Template::render_with_source).
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// Kind of stored value a template references
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    /// A plain configuration value
+    Config,
+    /// A secret, rendered as its decrypted plaintext
+    Secret,
+}
+
+impl std::fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Config => write!(f, "config"),
+            Self::Secret => write!(f, "secret"),
+        }
+    }
+}
+
+/// Reference to a stored value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    pub namespace: String,
+    pub key: String,
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}.{}", self.kind, self.namespace, self.key)
+    }
+}
+
+/// Source of stored values for template references
+pub trait ConfigSource {
+    /// Environment values are resolved in
+    type Environment: Copy;
+
+    /// Resolve a reference to its rendered form (`None` if it is not set)
+    ///
+    /// Implementations reject references the caller may not read with
+    /// [`TemplateError::AccessDenied`](crate::TemplateError::AccessDenied).
+    fn resolve(&self, reference: &Reference, env: Self::Environment) -> Result<Option<String>>;
+}
@@ -1,6 +1,7 @@
 //! Template structure and management
 
-use crate::{parser, Result, TemplateError};
+use crate::parser::Token;
+use crate::{parser, ConfigSource, Reference, Result, TemplateError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -65,31 +66,50 @@ impl Template {
     }
 
     /// Render the template with the given variables
+    ///
+    /// Fails on references to stored values; use
+    /// [`render_with_source`](Self::render_with_source) to resolve them.
     pub fn render(&self, vars: &HashMap<String, String>) -> Result<String> {
-        let tokens = parser::parse(&self.content)?;
-        let mut result = String::new();
+        render_tokens(
+            parser::parse(&self.content)?,
+            |name| self.variable(vars, name),
+            |reference| {
+                Err(TemplateError::UnresolvedReference(format!(
+                    "{} (no config source)",
+                    reference
+                )))
+            },
+        )
+    }
 
-        for token in tokens {
-            match token {
-                parser::Token::Literal(text) => result.push_str(&text),
-                parser::Token::Variable(var_name) => {
-                    // Check user-provided vars first
-                    if let Some(value) = vars.get(&var_name) {
-                        result.push_str(value);
-                    }
-                    // Then check defaults
-                    else if let Some(value) = self.defaults.get(&var_name) {
-                        result.push_str(value);
-                    }
-                    // Variable not found
-                    else {
-                        return Err(TemplateError::VariableNotFound(var_name));
-                    }
-                }
-            }
-        }
+    /// Render the template, resolving references to stored values through `source`
+    pub fn render_with_source<S: ConfigSource>(
+        &self,
+        vars: &HashMap<String, String>,
+        env: S::Environment,
+        source: &S,
+    ) -> Result<String> {
+        render_tokens(
+            parser::parse(&self.content)?,
+            |name| self.variable(vars, name),
+            |reference| resolve_reference(source, reference, env),
+        )
+    }
 
-        Ok(result)
+    /// References to stored values, in order of appearance
+    pub fn references(&self) -> Result<Vec<Reference>> {
+        Ok(parser::parse(&self.content)?
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Reference(reference) => Some(reference),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Value of a variable: user-provided first, then the default
+    fn variable(&self, vars: &HashMap<String, String>, name: &str) -> Option<String> {
+        vars.get(name).or_else(|| self.defaults.get(name)).cloned()
     }
 
     /// Check if all required variables are provided
@@ -103,6 +123,39 @@ impl Template {
     }
 }
 
+/// Render parsed tokens, looking up variables and resolving references
+pub(crate) fn render_tokens(
+    tokens: Vec<Token>,
+    variable: impl Fn(&str) -> Option<String>,
+    mut reference: impl FnMut(&Reference) -> Result<String>,
+) -> Result<String> {
+    let mut result = String::new();
+
+    for token in tokens {
+        match token {
+            Token::Literal(text) => result.push_str(&text),
+            Token::Variable(name) => {
+                let value = variable(&name).ok_or(TemplateError::VariableNotFound(name))?;
+                result.push_str(&value);
+            }
+            Token::Reference(target) => result.push_str(&reference(&target)?),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Resolve a reference, failing if the value is not set
+pub(crate) fn resolve_reference<S: ConfigSource>(
+    source: &S,
+    reference: &Reference,
+    env: S::Environment,
+) -> Result<String> {
+    source
+        .resolve(reference, env)?
+        .ok_or_else(|| TemplateError::UnresolvedReference(reference.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,6 +235,45 @@ mod tests {
         assert!(template.validate_vars(&partial_vars).is_err());
     }
 
+    struct MapSource(HashMap<String, String>);
+
+    impl ConfigSource for MapSource {
+        type Environment = ();
+
+        fn resolve(&self, reference: &Reference, _env: ()) -> Result<Option<String>> {
+            Ok(self.0.get(&reference.to_string()).cloned())
+        }
+    }
+
+    #[test]
+    fn test_template_references() {
+        let template = Template::new(
+            "dsn",
+            "postgres://{{user}}:{{secret:org/db.password}}@{{config:org/db.host}}",
+        )
+        .unwrap();
+        assert_eq!(template.required_vars, vec!["user"]);
+        assert_eq!(template.references().unwrap().len(), 2);
+
+        let mut values = HashMap::new();
+        values.insert("secret:org/db.password".to_string(), "hunter2".to_string());
+        values.insert("config:org/db.host".to_string(), "db:5432".to_string());
+        let mut vars = HashMap::new();
+        vars.insert("user".to_string(), "app".to_string());
+
+        let rendered = template.render_with_source(&vars, (), &MapSource(values)).unwrap();
+        assert_eq!(rendered, "postgres://app:hunter2@db:5432");
+
+        assert!(matches!(
+            template.render(&vars),
+            Err(TemplateError::UnresolvedReference(_))
+        ));
+        assert!(matches!(
+            template.render_with_source(&vars, (), &MapSource(HashMap::new())),
+            Err(TemplateError::UnresolvedReference(_))
+        ));
+    }
+
     #[test]
     fn test_template_serialization() {
         let template = Template::new("test", "Hello, {{name}}!")